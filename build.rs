@@ -1,7 +1,19 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Domain-only builds (e.g. for wasm32) need neither the messages nor protoc
+    if std::env::var_os("CARGO_FEATURE_PROTO").is_none() {
+        return Ok(());
+    }
+    let native = ["CARGO_FEATURE_SERVER", "CARGO_FEATURE_CLIENT"]
+        .iter()
+        .any(|feature| std::env::var_os(feature).is_some());
+
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
+        // `connect` needs tonic's native transport; other clients bring their own
+        .build_transport(native)
+        // A full result dwarfs a slice; box it so every chunk isn't result-sized
+        .boxed(".lp_solver.SolutionChunk.chunk.summary")
         .compile_protos(&["proto/lp_solver.proto"], &["proto"])?;
     Ok(())
 }
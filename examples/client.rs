@@ -112,6 +112,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             coefficients: vec![2.0, 3.0],
             bound: 100.0,
             name: "Labor hours limit".to_string(),
+            variable_names: vec![],
         },
         // Storage constraint: x1 + x2 <= 40
         Constraint {
@@ -119,6 +120,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             coefficients: vec![1.0, 1.0],
             bound: 40.0,
             name: "Storage capacity".to_string(),
+            variable_names: vec![],
         },
     ];
 
@@ -136,9 +138,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             verbose: false,
             mip_options: None,
             presolve: 0,
+            ..Default::default()
         }),
         problem_name: "Factory Production Planning".to_string(),
         description: "Maximize profit from producing chairs and tables".to_string(),
+        tags: Default::default(),
     };
 
     // Solve the problem
@@ -163,6 +167,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("  Variables:   {}", stats.num_variables);
                 println!("  Constraints: {}", stats.num_constraints);
                 println!("  Solve Time:  {:.2} ms", stats.solve_time_ms);
+                if let Some(usage) = stats.resources {
+                    println!("  CPU Time:    {:.2} ms", usage.cpu_time_ms);
+                    println!("  Peak RSS:    {} KiB", usage.peak_rss_bytes / 1024);
+                }
             }
         }
         Ok(SolutionStatus::Infeasible) => {
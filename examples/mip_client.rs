@@ -130,6 +130,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         coefficients: weights,
         bound: 15.0,
         name: "Weight capacity".to_string(),
+        variable_names: vec![],
     }];
 
     // Use selected solver
@@ -142,6 +143,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         verbose: false,
         mip_options: None,
         presolve: 0, // Auto
+        ..Default::default()
     };
 
     // Build the problem
@@ -152,6 +154,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         solver_config: Some(solver_config),
         problem_name: "Knapsack Problem".to_string(),
         description: "0/1 Knapsack with 5 items and 15 kg capacity".to_string(),
+        tags: Default::default(),
     };
 
     // Solve
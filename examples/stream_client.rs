@@ -9,9 +9,13 @@
 //
 // Benefits of streaming:
 // - Handles very large problems (exceeding message size limits)
+// - Batches many variables or constraints into one message
+// - Sends constraints as sparse rows, listing only their non-zero terms
 // - Reduces client memory usage by sending incrementally
 // - Enables dynamic problem generation
 
+#![allow(clippy::needless_range_loop)]
+
 use futures::stream;
 use std::io::{self, Write};
 use tonic::Request;
@@ -23,8 +27,9 @@ pub mod lp_solver {
 use lp_solver::{
     constraint::ConstraintType, linear_programming_solver_client::LinearProgrammingSolverClient,
     mip_options::MipEmphasis, objective_function::OptimizationType, problem_chunk,
-    solver_config::SolverBackend, variable::VariableType, Constraint, Empty, MipOptions,
-    ObjectiveFunction, ProblemChunk, ProblemMetadata, SolutionStatus, SolverConfig, Variable,
+    solver_config::SolverBackend, variable::VariableType, ConstraintBatch, Empty, MipOptions,
+    ObjectiveFunction, ProblemChunk, ProblemMetadata, SolutionStatus, SolverConfig,
+    SparseConstraint, Variable, VariableBatch,
 };
 
 const NUM_WAREHOUSES: usize = 10;
@@ -227,6 +232,7 @@ fn create_chunks(
         chunk: Some(problem_chunk::Chunk::Metadata(ProblemMetadata {
             problem_name: "Facility Location".to_string(),
             description: format!("{} wh, {} cust", NUM_WAREHOUSES, NUM_CUSTOMERS),
+            tags: Default::default(),
         })),
     });
 
@@ -245,37 +251,42 @@ fn create_chunks(
                 max_solutions: 0,
                 emphasis: MipEmphasis::Balanced as i32,
                 branching: 0,
+                ..Default::default()
             }),
             presolve: 0,
+            ..Default::default()
         })),
     });
 
-    // Variables: binary for warehouses
+    // Variables: binary for warehouses, one batch for all of them
+    let mut variables = Vec::new();
     for i in 0..NUM_WAREHOUSES {
-        chunks.push(ProblemChunk {
-            chunk: Some(problem_chunk::Chunk::Variable(Variable {
-                r#type: VariableType::Binary as i32,
-                lower_bound: 0.0,
-                upper_bound: Some(1.0),
-                name: format!("y{}", i),
-            })),
+        variables.push(Variable {
+            r#type: VariableType::Binary as i32,
+            lower_bound: 0.0,
+            upper_bound: Some(1.0),
+            name: format!("y{}", i),
         });
     }
 
     // Variables: continuous for flows
     for i in 0..NUM_WAREHOUSES {
         for j in 0..NUM_CUSTOMERS {
-            chunks.push(ProblemChunk {
-                chunk: Some(problem_chunk::Chunk::Variable(Variable {
-                    r#type: VariableType::Continuous as i32,
-                    lower_bound: 0.0,
-                    upper_bound: None,
-                    name: format!("x{}_{}", i, j),
-                })),
+            variables.push(Variable {
+                r#type: VariableType::Continuous as i32,
+                lower_bound: 0.0,
+                upper_bound: None,
+                name: format!("x{}_{}", i, j),
             });
         }
     }
 
+    chunks.push(ProblemChunk {
+        chunk: Some(problem_chunk::Chunk::VariableBatch(VariableBatch {
+            variables,
+        })),
+    });
+
     // Objective
     let mut coeffs = Vec::new();
     let mut names = Vec::new();
@@ -301,55 +312,51 @@ fn create_chunks(
     });
 
     // Demand constraints: sum_i(x_ij) >= demand_j
+    // Sparse rows carry only the 10 flows into each customer, not all 310 coefficients
+    let mut sparse_constraints = Vec::new();
     for j in 0..NUM_CUSTOMERS {
-        let mut c = vec![0.0; NUM_WAREHOUSES]; // Binary vars: no contribution
-
-        // Flow vars: x_ij for all i, j
-        for _i in 0..NUM_WAREHOUSES {
-            for jj in 0..NUM_CUSTOMERS {
-                if jj == j {
-                    c.push(1.0); // This customer's incoming flow
-                } else {
-                    c.push(0.0); // Other customers
-                }
-            }
-        }
-
-        chunks.push(ProblemChunk {
-            chunk: Some(problem_chunk::Chunk::Constraint(Constraint {
-                r#type: ConstraintType::GreaterThanOrEqual as i32,
-                coefficients: c,
-                bound: dem[j],
-                name: format!("dem{}", j),
-            })),
+        let indices = (0..NUM_WAREHOUSES)
+            .map(|i| (NUM_WAREHOUSES + i * NUM_CUSTOMERS + j) as u32)
+            .collect();
+
+        sparse_constraints.push(SparseConstraint {
+            r#type: ConstraintType::GreaterThanOrEqual as i32,
+            indices,
+            values: vec![1.0; NUM_WAREHOUSES],
+            bound: dem[j],
+            name: format!("dem{}", j),
+            ..Default::default()
         });
     }
 
     // Capacity constraints: sum_j(x_ij) - cap_i * y_i <= 0
+    // Terms may also be given by variable name instead of index
     for i in 0..NUM_WAREHOUSES {
-        let mut c = vec![0.0; NUM_WAREHOUSES];
-        c[i] = -wh[i].1; // -capacity * y_i
-
-        // Flow vars: x_ij for all warehouses and customers
-        for wi in 0..NUM_WAREHOUSES {
-            for _j in 0..NUM_CUSTOMERS {
-                if wi == i {
-                    c.push(1.0); // This warehouse's outflow
-                } else {
-                    c.push(0.0); // Other warehouses
-                }
-            }
+        let mut variable_names = vec![format!("y{}", i)];
+        let mut values = vec![-wh[i].1]; // -capacity * y_i
+
+        // Flow vars: this warehouse's outflow
+        for j in 0..NUM_CUSTOMERS {
+            variable_names.push(format!("x{}_{}", i, j));
+            values.push(1.0);
         }
 
-        chunks.push(ProblemChunk {
-            chunk: Some(problem_chunk::Chunk::Constraint(Constraint {
-                r#type: ConstraintType::LessThanOrEqual as i32,
-                coefficients: c,
-                bound: 0.0,
-                name: format!("cap{}", i),
-            })),
+        sparse_constraints.push(SparseConstraint {
+            r#type: ConstraintType::LessThanOrEqual as i32,
+            variable_names,
+            values,
+            bound: 0.0,
+            name: format!("cap{}", i),
+            ..Default::default()
         });
     }
 
+    chunks.push(ProblemChunk {
+        chunk: Some(problem_chunk::Chunk::ConstraintBatch(ConstraintBatch {
+            constraints: vec![],
+            sparse_constraints,
+        })),
+    });
+
     chunks
 }
@@ -0,0 +1,54 @@
+// Example client using the typed `letsopt::client` wrapper
+//
+// Solves the same production planning problem as examples/client.rs, but writes
+// it with the `letsopt::Model` DSL and lets the wrapper handle the protobuf
+// conversion:
+//   Maximize: 30*chairs + 50*tables
+//   Subject to:
+//     2*chairs + 3*tables <= 100  (labor hours)
+//     chairs + tables <= 40       (storage)
+
+use letsopt::{LetsOptClient, Model};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = LetsOptClient::connect("http://127.0.0.1:50051").await?;
+
+    let mut model = Model::new("Factory Production Planning");
+    let chairs = model.continuous("chairs");
+    let tables = model.continuous("tables");
+    model.add(
+        (2 * chairs + 3 * tables)
+            .leq(100)
+            .with_name("Labor hours limit"),
+    );
+    model.add((chairs + tables).leq(40).with_name("Storage capacity"));
+    model.maximize(30 * chairs + 50 * tables);
+    let problem = model.build();
+
+    for warning in client.validate(&problem).await? {
+        println!("Warning: {}", warning);
+    }
+
+    let solution = client.solve(&problem).await?;
+    println!("Status: {}", solution.status);
+    if let Some(profit) = solution.optimal_value {
+        println!(
+            "Chairs:  {:.2} units",
+            chairs.value(&solution).unwrap_or(0.0)
+        );
+        println!(
+            "Tables:  {:.2} units",
+            tables.value(&solution).unwrap_or(0.0)
+        );
+        println!("Maximum Profit: ${:.2}", profit);
+    }
+    if let Some(solver) = &solution.statistics.solved_by {
+        println!(
+            "Solved by {} in {:.2} ms",
+            solver, solution.statistics.solve_time_ms
+        );
+    }
+
+    Ok(())
+}
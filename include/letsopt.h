@@ -0,0 +1,97 @@
+/* LetsOpt C API: the solver layer embedded in-process, without the gRPC server
+ *
+ * Build the shared library with
+ *     cargo rustc --release --lib --features ffi --crate-type cdylib
+ * and link against target/release/libletsopt.so (.dylib, .dll).
+ *
+ * Pointer rules:
+ * - Strings passed in are NUL-terminated UTF-8. Where a name may be NULL it is
+ *   treated as empty.
+ * - Strings returned as `char *` belong to the caller; free them with
+ *   letsopt_string_free. Strings and arrays returned as `const` pointers belong
+ *   to the object they came from and live as long as it does.
+ * - A model or solution may be used from any thread, but not from two at once.
+ * - Functions that fail return NULL or -1; letsopt_last_error() then says why,
+ *   until the next call on the same thread.
+ */
+#ifndef LETSOPT_H
+#define LETSOPT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Variable types */
+#define LETSOPT_CONTINUOUS 0
+#define LETSOPT_INTEGER 1
+#define LETSOPT_BINARY 2
+
+/* Constraint senses */
+#define LETSOPT_LESS_EQUAL 0
+#define LETSOPT_EQUAL 1
+#define LETSOPT_GREATER_EQUAL 2
+
+/* Solution statuses */
+#define LETSOPT_OPTIMAL 0
+#define LETSOPT_FEASIBLE 1
+#define LETSOPT_INFEASIBLE 2
+#define LETSOPT_UNBOUNDED 3
+#define LETSOPT_TIME_LIMIT 4
+#define LETSOPT_ITERATION_LIMIT 5
+#define LETSOPT_NODE_LIMIT 6
+#define LETSOPT_ERROR 7
+#define LETSOPT_INTERRUPTED 8
+#define LETSOPT_MEMORY_LIMIT 9
+
+typedef struct LetsOptModel LetsOptModel;
+typedef struct LetsOptSolution LetsOptSolution;
+
+/* Solve a problem in the JSON problem-file form; returns the solution as JSON */
+char *letsopt_solve(const char *problem_json);
+void letsopt_string_free(char *string);
+const char *letsopt_last_error(void);
+const char *letsopt_version(void);
+
+/* Build a problem column by column. Indices count from 0 in creation order. */
+LetsOptModel *letsopt_model_new(const char *name);
+void letsopt_model_free(LetsOptModel *model);
+/* Returns the column index; pass INFINITY as `upper` for no upper bound */
+int64_t letsopt_model_add_variable(LetsOptModel *model, const char *name, int variable_type,
+                                   double lower, double upper);
+/* sum(coefficients[k] * x[columns[k]]) <sense> rhs; returns the row index */
+int64_t letsopt_model_add_constraint(LetsOptModel *model, const char *name, size_t count,
+                                     const size_t *columns, const double *coefficients,
+                                     int sense, double rhs);
+int letsopt_model_set_objective(LetsOptModel *model, int maximize, size_t count,
+                                const size_t *columns, const double *coefficients);
+/* 0 or less removes the limit */
+int letsopt_model_set_time_limit(LetsOptModel *model, double seconds);
+/* 0 or less uses the solver's default */
+int letsopt_model_set_gap_tolerance(LetsOptModel *model, double gap);
+/* Same, in objective units rather than relative to the objective */
+int letsopt_model_set_absolute_gap(LetsOptModel *model, double gap);
+/* Built-in solver key ("highs", "cbc"); NULL lets the library pick */
+int letsopt_model_set_solver(LetsOptModel *model, const char *name);
+/* NULL if the model can't be solved; infeasible models still get a solution */
+LetsOptSolution *letsopt_model_solve(const LetsOptModel *model);
+
+void letsopt_solution_free(LetsOptSolution *solution);
+int letsopt_solution_status(const LetsOptSolution *solution);
+/* NAN when no solution was found */
+double letsopt_solution_objective(const LetsOptSolution *solution);
+size_t letsopt_solution_num_values(const LetsOptSolution *solution);
+const double *letsopt_solution_values(const LetsOptSolution *solution);
+/* One dual per row when the solver reports them, as it does for LPs */
+size_t letsopt_solution_num_duals(const LetsOptSolution *solution);
+const double *letsopt_solution_duals(const LetsOptSolution *solution);
+const char *letsopt_solution_message(const LetsOptSolution *solution);
+char *letsopt_solution_json(const LetsOptSolution *solution);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LETSOPT_H */
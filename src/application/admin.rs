@@ -0,0 +1,80 @@
+// Admin: Guard the RPCs that operate the server rather than solve on it
+// Callers present the admin token the server was started with; a server without
+// one keeps these RPCs closed
+
+use tonic::metadata::MetadataMap;
+use tonic::Status;
+use tracing::warn;
+
+use super::workers::constant_time_eq;
+
+/// Metadata header admin RPCs expect the admin token in
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// Secret admin callers have to present
+#[derive(Debug, Clone, Default)]
+pub struct AdminToken {
+    token: Option<String>,
+}
+
+impl AdminToken {
+    /// Accept callers presenting `token`; an empty token accepts nobody
+    pub fn new(token: impl Into<String>) -> Self {
+        let token = token.into();
+        Self {
+            token: (!token.is_empty()).then_some(token),
+        }
+    }
+
+    /// Check the admin token in an admin RPC's metadata
+    ///
+    /// Callers without the token get PERMISSION_DENIED, as does everyone when no
+    /// token is configured.
+    pub fn authorize(&self, metadata: &MetadataMap, method: &str) -> Result<(), Box<Status>> {
+        let Some(token) = &self.token else {
+            warn!(method, "Admin call refused: no admin token is configured");
+            return Err(Box::new(Status::permission_denied(
+                "Admin calls are disabled on this server",
+            )));
+        };
+        let presented = metadata
+            .get(ADMIN_TOKEN_HEADER)
+            .map(|value| value.as_bytes())
+            .unwrap_or_default();
+        if constant_time_eq(presented, token.as_bytes()) {
+            Ok(())
+        } else {
+            warn!(method, "Admin call without a valid admin token refused");
+            Err(Box::new(Status::permission_denied(
+                "Missing or invalid admin token",
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(token: &str) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        metadata.insert(ADMIN_TOKEN_HEADER, token.parse().unwrap());
+        metadata
+    }
+
+    #[test]
+    fn only_the_configured_token_is_accepted() {
+        let admin = AdminToken::new("secret");
+        assert!(admin.authorize(&metadata("secret"), "Test").is_ok());
+        assert!(admin.authorize(&metadata("secreT"), "Test").is_err());
+        assert!(admin.authorize(&MetadataMap::new(), "Test").is_err());
+    }
+
+    #[test]
+    fn without_a_token_every_call_is_refused() {
+        for admin in [AdminToken::default(), AdminToken::new("")] {
+            let status = admin.authorize(&metadata(""), "Test").unwrap_err();
+            assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Audit log: Durable record of who solved what
+// Each finished solve produces one record; sinks decide where it is stored
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tonic::Request;
+
+use super::quotas::API_KEY_HEADER;
+use super::request_id::RequestId;
+use super::routing::SOLVER_HINT_HEADER;
+use crate::domain::models::OptimizationProblem;
+
+/// Metadata key identifying the calling client (service name, user, tenant...)
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Who sent a request
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub request_id: RequestId,
+    /// Client-supplied identity from `x-client-id`
+    pub client_id: Option<String>,
+    pub remote_addr: Option<SocketAddr>,
+    /// Routing hint from `x-solver-hint`
+    pub solver_hint: Option<String>,
+    /// Quota key from `x-api-key`; never written to the audit log
+    pub api_key: Option<String>,
+}
+
+impl Caller {
+    pub fn from_request<T>(request: &Request<T>) -> Self {
+        let header = |key: &str| {
+            request
+                .metadata()
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        Self {
+            request_id: RequestId::from_metadata(request.metadata()),
+            client_id: header(CLIENT_ID_HEADER),
+            remote_addr: request.remote_addr(),
+            solver_hint: header(SOLVER_HINT_HEADER),
+            api_key: header(API_KEY_HEADER),
+        }
+    }
+}
+
+/// One audited solve
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch when the solve was received
+    pub timestamp_ms: u64,
+    pub request_id: String,
+    pub client_id: Option<String>,
+    pub client_addr: Option<String>,
+    pub problem_name: String,
+    pub num_variables: usize,
+    pub num_constraints: usize,
+    pub num_integer_vars: usize,
+    pub num_nonzeros: usize,
+    pub backend: String,
+    /// Solution status, or "REJECTED"/"FAILED" when no solution was produced
+    pub status: String,
+    pub objective: Option<f64>,
+    pub duration_ms: f64,
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl AuditRecord {
+    /// Record for `problem` sent by `caller`; outcome fields start empty
+    pub fn new(caller: &Caller, problem: &OptimizationProblem) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            timestamp_ms,
+            request_id: caller.request_id.to_string(),
+            client_id: caller.client_id.clone(),
+            client_addr: caller.remote_addr.map(|addr| addr.to_string()),
+            problem_name: problem.name.clone(),
+            num_variables: problem.num_variables(),
+            num_constraints: problem.constraints.len(),
+            num_integer_vars: problem.num_integer_variables(),
+            num_nonzeros: problem.num_nonzeros(),
+            backend: problem.solver_config.backend.to_string(),
+            status: String::new(),
+            objective: None,
+            duration_ms: 0.0,
+            error: None,
+            tags: problem.tags.clone(),
+        }
+    }
+}
+
+/// Destination for audit records
+///
+/// Implement this to store records somewhere other than a local file, such as a
+/// database table.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> std::io::Result<()>;
+}
+
+/// Append-only JSON Lines file, one record per line
+pub struct JsonlAuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl JsonlAuditLog {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AuditSink for JsonlAuditLog {
+    fn record(&self, record: &AuditRecord) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        // One write per record keeps lines whole even if several servers share the file
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line)?;
+        file.flush()
+    }
+}
@@ -0,0 +1,468 @@
+// Benders decomposition: Two-stage models split into a master MIP and a subproblem
+// The master proposes first-stage values, the subproblem prices them and returns a
+// cut, and the loop repeats until the master's bound meets the best plan's cost
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use tracing::debug;
+
+use crate::domain::modeling::VarRef;
+use crate::domain::models::{Constraint, OptimizationProblem, Solution, Variable};
+use crate::domain::solver_service::{
+    CancellationToken, NullObserver, Result, SolverError, SolverService,
+};
+use crate::domain::value_objects::{ConstraintType, OptimizationType, SolutionStatus};
+
+const DEFAULT_MAX_ITERATIONS: usize = 200;
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Affine function `constant + sum(coefficient * x[column])` of master columns
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BendersCut {
+    pub constant: f64,
+    pub coefficients: Vec<(usize, f64)>,
+}
+
+impl BendersCut {
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        self.coefficients
+            .iter()
+            .fold(self.constant, |total, &(j, c)| total + c * values[j])
+    }
+}
+
+/// What the subproblem made of one master proposal
+#[derive(Debug, Clone)]
+pub enum SubproblemOutcome {
+    /// Second-stage cost of the proposal, and a cut the estimator must stay above
+    /// for every first-stage choice
+    Optimal { cost: f64, cut: BendersCut },
+    /// No second stage fits the proposal; every feasible first-stage choice keeps
+    /// the cut at or below zero
+    Infeasible { cut: BendersCut },
+}
+
+/// Second stage of a decomposed model, evaluated at master values
+pub trait BendersSubproblem: Send + Sync {
+    /// Price the master's values (one per master column)
+    fn evaluate(&self, master_values: &[f64]) -> Result<SubproblemOutcome>;
+}
+
+/// Master problem: first-stage decisions plus an estimator of the second-stage cost
+#[derive(Debug, Clone)]
+pub struct BendersMaster {
+    problem: OptimizationProblem,
+    estimator: usize,
+}
+
+impl BendersMaster {
+    /// `problem` must minimize, with `estimator` in the objective at a positive
+    /// coefficient and a lower bound (e.g. 0 for non-negative second-stage costs),
+    /// without which the first master solve is unbounded
+    pub fn new(problem: OptimizationProblem, estimator: VarRef) -> Result<Self> {
+        let estimator = estimator.index();
+        let coefficient = problem.objective.coefficients.get(estimator).copied();
+        if problem.objective.optimization_type != OptimizationType::Minimize {
+            return Err(SolverError::InvalidProblem(
+                "Benders master must minimize".to_string(),
+            ));
+        }
+        if !coefficient.is_some_and(|c| c > 0.0) {
+            return Err(SolverError::InvalidProblem(
+                "Benders estimator must have a positive objective coefficient".to_string(),
+            ));
+        }
+        Ok(Self { problem, estimator })
+    }
+
+    pub fn problem(&self) -> &OptimizationProblem {
+        &self.problem
+    }
+
+    fn estimator_cost(&self) -> f64 {
+        self.problem.objective.coefficients[self.estimator]
+    }
+
+    /// `estimator >= cut`
+    fn optimality_cut(&self, cut: &BendersCut, name: String) -> Constraint {
+        let mut coefficients = vec![0.0; self.problem.num_variables()];
+        for &(j, c) in &cut.coefficients {
+            coefficients[j] -= c;
+        }
+        coefficients[self.estimator] += 1.0;
+        Constraint::new(
+            ConstraintType::GreaterThanOrEqual,
+            coefficients,
+            cut.constant,
+        )
+        .with_name(name)
+    }
+
+    /// `cut <= 0`, or None if the cut doesn't depend on the master at all
+    fn feasibility_cut(&self, cut: &BendersCut, name: String) -> Option<Constraint> {
+        let mut coefficients = vec![0.0; self.problem.num_variables()];
+        for &(j, c) in &cut.coefficients {
+            coefficients[j] += c;
+        }
+        coefficients.iter().any(|&c| c != 0.0).then(|| {
+            Constraint::new(ConstraintType::LessThanOrEqual, coefficients, -cut.constant)
+                .with_name(name)
+        })
+    }
+}
+
+/// Continuous second stage whose row bounds depend linearly on the master
+///
+/// Row `r` of `problem` reads `W_r y (op) h_r - T_r x`, with `technology[r]` the
+/// `(master column, coefficient)` terms of `T_r`. Cuts come from the row duals
+/// of each solve; infeasible proposals are cut off with the duals of an elastic
+/// copy that minimizes the total violation.
+pub struct LinearSubproblem {
+    problem: OptimizationProblem,
+    technology: Vec<Vec<(usize, f64)>>,
+    solver: Arc<dyn SolverService>,
+}
+
+impl LinearSubproblem {
+    pub fn new(
+        problem: OptimizationProblem,
+        technology: Vec<Vec<(usize, f64)>>,
+        solver: Arc<dyn SolverService>,
+    ) -> Result<Self> {
+        if problem.objective.optimization_type != OptimizationType::Minimize {
+            return Err(SolverError::InvalidProblem(
+                "Benders subproblem must minimize".to_string(),
+            ));
+        }
+        if problem.is_mixed_integer() {
+            return Err(SolverError::InvalidProblem(
+                "Benders subproblem must be continuous to yield dual cuts".to_string(),
+            ));
+        }
+        if technology.len() != problem.constraints.len() {
+            return Err(SolverError::InvalidProblem(format!(
+                "Technology has {} rows, the subproblem {}",
+                technology.len(),
+                problem.constraints.len()
+            )));
+        }
+        Ok(Self {
+            problem,
+            technology,
+            solver,
+        })
+    }
+
+    /// `T_r x` for every row
+    fn shifts(&self, master_values: &[f64]) -> Vec<f64> {
+        self.technology
+            .iter()
+            .map(|terms| terms.iter().map(|&(j, t)| t * master_values[j]).sum())
+            .collect()
+    }
+
+    /// Cut `value + sum(dual_r * (T_r x_hat - T_r x))` through the solve's value
+    fn cut(
+        &self,
+        value: f64,
+        duals: &[f64],
+        shifts: &[f64],
+        master_len: usize,
+    ) -> Result<BendersCut> {
+        if duals.len() != self.technology.len() {
+            return Err(SolverError::ExecutionFailed(format!(
+                "{} returned no row duals for the Benders subproblem",
+                self.solver.name()
+            )));
+        }
+        let mut coefficients = vec![0.0; master_len];
+        let mut constant = value;
+        for ((terms, &dual), &shift) in self.technology.iter().zip(duals).zip(shifts) {
+            constant += dual * shift;
+            for &(j, t) in terms {
+                coefficients[j] -= dual * t;
+            }
+        }
+        Ok(BendersCut {
+            constant,
+            coefficients: coefficients
+                .into_iter()
+                .enumerate()
+                .filter(|&(_, c)| c != 0.0)
+                .collect(),
+        })
+    }
+
+    /// The subproblem with one violation variable per row direction, minimizing
+    /// their total
+    fn elastic(problem: &OptimizationProblem) -> OptimizationProblem {
+        let n = problem.num_variables();
+        let mut elastic = problem.clone();
+        let mut objective = vec![0.0; n];
+        let mut extra = Vec::new();
+        for (r, row) in problem.constraints.iter().enumerate() {
+            let directions: &[f64] = match row.constraint_type {
+                ConstraintType::LessThanOrEqual => &[-1.0],
+                ConstraintType::GreaterThanOrEqual => &[1.0],
+                ConstraintType::Equal => &[1.0, -1.0],
+            };
+            for &direction in directions {
+                extra.push((r, direction));
+            }
+        }
+        for row in &mut elastic.constraints {
+            row.coefficients.resize(n + extra.len());
+            row.variable_names.clear();
+        }
+        for (k, &(r, direction)) in extra.iter().enumerate() {
+            elastic.constraints[r].coefficients.set(n + k, direction);
+            elastic
+                .variables
+                .push(Variable::continuous(format!("violation_{}", k)));
+            objective.push(1.0);
+        }
+        elastic.objective.coefficients = objective;
+        elastic.objective.variable_names.clear();
+        elastic
+    }
+}
+
+impl BendersSubproblem for LinearSubproblem {
+    fn evaluate(&self, master_values: &[f64]) -> Result<SubproblemOutcome> {
+        let shifts = self.shifts(master_values);
+        let mut problem = self.problem.clone();
+        for (row, shift) in problem.constraints.iter_mut().zip(&shifts) {
+            row.bound -= shift;
+        }
+
+        let solution = self.solver.solve(&problem)?;
+        match (solution.status, solution.optimal_value) {
+            (SolutionStatus::Optimal, Some(cost)) => Ok(SubproblemOutcome::Optimal {
+                cost,
+                cut: self.cut(cost, &solution.dual_values, &shifts, master_values.len())?,
+            }),
+            (SolutionStatus::Infeasible, _) => {
+                let phase_one = self.solver.solve(&Self::elastic(&problem))?;
+                match (phase_one.status, phase_one.optimal_value) {
+                    (SolutionStatus::Optimal, Some(violation)) if violation > 0.0 => {
+                        Ok(SubproblemOutcome::Infeasible {
+                            cut: self.cut(
+                                violation,
+                                &phase_one.dual_values,
+                                &shifts,
+                                master_values.len(),
+                            )?,
+                        })
+                    }
+                    _ => Err(SolverError::ExecutionFailed(format!(
+                        "Benders subproblem is infeasible but its elastic form ended {}",
+                        phase_one.status
+                    ))),
+                }
+            }
+            (SolutionStatus::Unbounded, _) => Err(SolverError::InvalidProblem(
+                "Benders subproblem is unbounded, so the whole model is".to_string(),
+            )),
+            (status, _) => Err(SolverError::ExecutionFailed(format!(
+                "Benders subproblem ended {}: {}",
+                status, solution.message
+            ))),
+        }
+    }
+}
+
+/// Result of a Benders run
+#[derive(Debug, Clone)]
+pub struct BendersSolution {
+    /// Best plan found, master values with the estimator at its true cost; Optimal
+    /// when the bounds met, Feasible when the iteration limit stopped the loop
+    pub solution: Solution,
+    /// Master objective, a lower bound on the optimum
+    pub lower_bound: f64,
+    /// Cost of the best plan
+    pub upper_bound: f64,
+    pub iterations: usize,
+    pub optimality_cuts: usize,
+    pub feasibility_cuts: usize,
+}
+
+/// Runs the Benders loop, solving the master with any backend
+pub struct BendersSolver {
+    master_solver: Arc<dyn SolverService>,
+    max_iterations: usize,
+    tolerance: f64,
+}
+
+impl BendersSolver {
+    pub fn new(master_solver: Arc<dyn SolverService>) -> Self {
+        Self {
+            master_solver,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    /// Relative gap between the bounds at which the plan counts as optimal
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn solve(
+        &self,
+        master: &BendersMaster,
+        subproblem: &dyn BendersSubproblem,
+    ) -> Result<BendersSolution> {
+        self.solve_cancellable(master, subproblem, &CancellationToken::new())
+    }
+
+    /// Solve, stopping between or during master solves once `cancel` is set
+    ///
+    /// A master that is infeasible (possibly after feasibility cuts) ends the run
+    /// with that solution and infinite bounds.
+    pub fn solve_cancellable(
+        &self,
+        master: &BendersMaster,
+        subproblem: &dyn BendersSubproblem,
+        cancel: &CancellationToken,
+    ) -> Result<BendersSolution> {
+        let start_time = Instant::now();
+        let mut problem = master.problem.clone();
+        let estimator_cost = master.estimator_cost();
+        let mut lower_bound = f64::NEG_INFINITY;
+        let mut best: Option<(f64, Vec<f64>)> = None;
+        let (mut optimality_cuts, mut feasibility_cuts) = (0, 0);
+
+        let finish = |mut solution: Solution,
+                      lower_bound: f64,
+                      upper_bound: f64,
+                      iterations: usize,
+                      optimality_cuts: usize,
+                      feasibility_cuts: usize| {
+            solution.statistics.solve_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+            BendersSolution {
+                solution,
+                lower_bound,
+                upper_bound,
+                iterations,
+                optimality_cuts,
+                feasibility_cuts,
+            }
+        };
+
+        for iteration in 1..=self.max_iterations {
+            if cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            let proposal = self
+                .master_solver
+                .solve_cancellable(&problem, &NullObserver, cancel)?;
+            let Some(objective) = proposal.optimal_value.filter(|_| proposal.is_feasible()) else {
+                if proposal.status == SolutionStatus::Unbounded {
+                    return Err(SolverError::InvalidProblem(
+                        "Benders master is unbounded; give the estimator a lower bound".to_string(),
+                    ));
+                }
+                return Ok(finish(
+                    proposal,
+                    f64::INFINITY,
+                    f64::INFINITY,
+                    iteration,
+                    optimality_cuts,
+                    feasibility_cuts,
+                ));
+            };
+            lower_bound = lower_bound.max(proposal.best_bound.unwrap_or(objective));
+            let values = proposal.variable_values;
+            let estimate = values[master.estimator];
+
+            match subproblem.evaluate(&values)? {
+                SubproblemOutcome::Optimal { cost, cut } => {
+                    let plan_cost = objective + estimator_cost * (cost - estimate);
+                    if best.as_ref().is_none_or(|(upper, _)| plan_cost < *upper) {
+                        let mut plan = values.clone();
+                        plan[master.estimator] = cost;
+                        best = Some((plan_cost, plan));
+                    }
+                    let upper_bound = best.as_ref().map_or(f64::INFINITY, |(upper, _)| *upper);
+                    // A proposal whose estimate was already right costs no more
+                    // than the master objective, closing the gap
+                    if upper_bound - lower_bound <= self.tolerance * upper_bound.abs().max(1.0) {
+                        let (upper_bound, plan) = best.take().unwrap_or((plan_cost, values));
+                        let mut solution = Solution::optimal(upper_bound, plan);
+                        solution.best_bound = Some(lower_bound.min(upper_bound));
+                        solution.message =
+                            format!("Benders converged after {} iterations", iteration);
+                        return Ok(finish(
+                            solution,
+                            lower_bound,
+                            upper_bound,
+                            iteration,
+                            optimality_cuts,
+                            feasibility_cuts,
+                        ));
+                    }
+                    debug!(iteration, lower_bound, upper_bound, "Adding optimality cut");
+                    problem.constraints.push(
+                        master.optimality_cut(
+                            &cut,
+                            format!("benders_optimality_{}", optimality_cuts),
+                        ),
+                    );
+                    optimality_cuts += 1;
+                }
+                SubproblemOutcome::Infeasible { cut } => {
+                    debug!(iteration, "Adding feasibility cut");
+                    let Some(row) = master
+                        .feasibility_cut(&cut, format!("benders_feasibility_{}", feasibility_cuts))
+                    else {
+                        // No first-stage choice makes the second stage feasible
+                        let solution = Solution::new(
+                            SolutionStatus::Infeasible,
+                            "Second stage is infeasible for every first-stage choice",
+                        );
+                        return Ok(finish(
+                            solution,
+                            f64::INFINITY,
+                            f64::INFINITY,
+                            iteration,
+                            optimality_cuts,
+                            feasibility_cuts,
+                        ));
+                    };
+                    problem.constraints.push(row);
+                    feasibility_cuts += 1;
+                }
+            }
+        }
+
+        let Some((upper_bound, plan)) = best else {
+            return Err(SolverError::ExecutionFailed(format!(
+                "No feasible plan after {} Benders iterations",
+                self.max_iterations
+            )));
+        };
+        let mut solution = Solution::optimal(upper_bound, plan);
+        solution.status = SolutionStatus::Feasible;
+        solution.best_bound = Some(lower_bound);
+        solution.gap = Some((upper_bound - lower_bound) / upper_bound.abs().max(1.0));
+        solution.message = format!(
+            "Stopped after {} Benders iterations with bounds [{}, {}]",
+            self.max_iterations, lower_bound, upper_bound
+        );
+        Ok(finish(
+            solution,
+            lower_bound,
+            upper_bound,
+            self.max_iterations,
+            optimality_cuts,
+            feasibility_cuts,
+        ))
+    }
+}
@@ -0,0 +1,126 @@
+// Error details: Machine-readable causes attached to failed RPCs
+// Encodes an ErrorDetail the standard gRPC way, as a google.rpc.Status in the
+// grpc-status-details-bin trailer, so clients can act on errors without parsing text
+
+use prost::Message;
+use tonic::{Code, Status};
+
+use super::mappers::lp_solver::{error_detail::ErrorCode, ErrorDetail};
+
+/// Type URL the detail is packed under
+pub const ERROR_DETAIL_TYPE_URL: &str = "type.googleapis.com/lp_solver.ErrorDetail";
+
+/// Wire-compatible with `google.rpc.Status`
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<Any>,
+}
+
+/// Wire-compatible with `google.protobuf.Any`
+#[derive(Clone, PartialEq, Message)]
+struct Any {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+impl ErrorDetail {
+    pub fn with_code(code: ErrorCode) -> Self {
+        Self {
+            code: code as i32,
+            ..Default::default()
+        }
+    }
+
+    /// Point at the `index`-th `entity` of the request, e.g. variable 3
+    pub fn with_entity(mut self, entity: &str, index: Option<usize>) -> Self {
+        self.entity = entity.to_string();
+        self.index = index.map(|i| i as u32);
+        self
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_values(mut self, expected: impl ToString, actual: impl ToString) -> Self {
+        self.expected = expected.to_string();
+        self.actual = actual.to_string();
+        self
+    }
+
+    /// Error status with `message` that carries this detail
+    pub fn into_status(self, code: Code, message: impl Into<String>) -> Box<Status> {
+        let message = message.into();
+        let details = RpcStatus {
+            code: code as i32,
+            message: message.clone(),
+            details: vec![Any {
+                type_url: ERROR_DETAIL_TYPE_URL.to_string(),
+                value: self.encode_to_vec(),
+            }],
+        };
+        Box::new(Status::with_details(
+            code,
+            message,
+            details.encode_to_vec().into(),
+        ))
+    }
+
+    /// INVALID_ARGUMENT status with `message` that carries this detail
+    pub fn invalid_argument(self, message: impl Into<String>) -> Box<Status> {
+        self.into_status(Code::InvalidArgument, message)
+    }
+}
+
+/// The detail attached to `status`, if the server sent one
+pub fn error_detail(status: &Status) -> Option<ErrorDetail> {
+    let details = RpcStatus::decode(status.details()).ok()?;
+    details
+        .details
+        .into_iter()
+        .find(|any| any.type_url == ERROR_DETAIL_TYPE_URL)
+        .and_then(|any| ErrorDetail::decode(any.value.as_slice()).ok())
+}
+
+/// Add the name of the entity `status` is about to its detail
+pub fn named(status: Box<Status>, name: &str) -> Box<Status> {
+    match error_detail(&status) {
+        Some(detail) => detail
+            .with_name(name)
+            .into_status(status.code(), status.message()),
+        None => status,
+    }
+}
+
+/// Add the position of the entity `status` is about, when its detail lacks one
+///
+/// For conversions of a single message that don't know where it sits in the request.
+pub fn at_index(status: Box<Status>, index: usize) -> Box<Status> {
+    match error_detail(&status) {
+        Some(detail) if detail.index.is_none() && !detail.entity.is_empty() => {
+            let message = format!("{} ({} {})", status.message(), detail.entity, index);
+            let entity = detail.entity.clone();
+            detail
+                .with_entity(&entity, Some(index))
+                .into_status(status.code(), message)
+        }
+        _ => status,
+    }
+}
+
+/// Prefix `status`'s message with the stream chunk it came from, keeping its detail
+pub fn in_chunk(status: Box<Status>, chunk_index: usize) -> Box<Status> {
+    let message = format!("Chunk {}: {}", chunk_index, status.message());
+    match error_detail(&status) {
+        Some(detail) => detail.into_status(status.code(), message),
+        None => Box::new(Status::new(status.code(), message)),
+    }
+}
@@ -1,165 +1,1237 @@
-use tonic::{Request, Response, Status};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
+use futures::stream::{self, BoxStream, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Response, Status};
+use tracing::{debug, error, field, info, info_span, warn, Instrument, Span};
+
+use super::admin::AdminToken;
+use super::audit::{AuditRecord, AuditSink, Caller};
+use super::idempotency::{
+    IdempotencyCache, DEFAULT_IDEMPOTENCY_TTL, IDEMPOTENCY_KEY_HEADER, IDEMPOTENT_REPLAY_HEADER,
+};
+use super::jobs::{JobHandle, JobRegistry, JobState};
+use super::limiter::{OverloadPolicy, SolveLimiter, SolvePermit};
 use super::mappers::{self, lp_solver};
+use super::pareto::{ParetoFrontier, ParetoSolver};
+use super::problem_fetch::{ProblemFetcher, UrlFetchConfig};
+use super::problem_repository::{
+    is_valid_problem_id, ProblemRepository, SolveRecord, MAX_PROBLEM_ID_LEN,
+};
+use super::problem_stream::ProblemAssembler;
+use super::publishing::{ResultPublisher, SolveEvent};
+use super::quotas::{QuotaPolicy, QuotaTracker};
+use super::recording::SolveRecorder;
+use super::routing::RoutingPolicy;
+use super::server_stats::ServerStats;
+use super::sessions::SessionStore;
+use super::size_limits::ProblemSizeLimits;
+use super::submissions::{Submission, SubmittedResults};
+use super::webhook::{CompletionNotice, WebhookConfig, WebhookNotifier};
+use super::workers::{RemoteSolver, WorkerPool};
 
-use crate::solver::SolverFactory;
+use crate::domain::anonymize::MAX_PERTURBATION;
+use crate::domain::difficulty::estimate_difficulty;
+use crate::domain::explanation::explain_solution;
+use crate::domain::export::export_solution;
+use crate::domain::model_session::{ModelSession, RebuildingSession};
+use crate::domain::model_statistics::ModelStatistics;
+use crate::domain::models::{
+    tags_match, Constraint, ObjectiveFunction, OptimizationProblem, Solution, SolverConfig,
+    Variable,
+};
+use crate::domain::problem_file::ProblemFormat;
+use crate::domain::recording::RecordedSolve;
+use crate::domain::scenarios::Scenario;
+use crate::domain::solution_diff::DEFAULT_DIFF_THRESHOLD;
+use crate::domain::solver_service::{
+    validate_problem, CancellationToken, NullObserver, Result as SolverResult, SolverError,
+    SolverService,
+};
+use crate::domain::value_objects::{
+    ConstraintType, OptimizationType, SolutionStatus, SolvePriority, SolverBackend,
+};
+use crate::domain::verification::{assess_quality, verify_solution, DEFAULT_FEASIBILITY_TOLERANCE};
+use crate::solver::{
+    prepare_problem, wrap_solver, AutoSolver, FallbackSolver, IsolationConfig, RacingSolver,
+    ResourceMeter, SolverFactory, SolverRegistry, SubprocessSolver,
+};
 
-/// gRPC service implementation
-pub struct GrpcLpSolverService;
+use lp_solver::{error_detail::ErrorCode, ErrorDetail};
+
+/// Values per slice in SolveProblemChunked: 512 KiB of doubles, well under the
+/// 4 MiB message limit clients decode by default
+const SOLUTION_SLICE_LEN: usize = 65_536;
+
+/// Optimum of the LP `self_test` solves
+const SELF_TEST_OPTIMUM: f64 = 11.0;
+
+/// Time kept back from a deadline-capped time limit to return the result in
+const DEADLINE_MARGIN: Duration = Duration::from_millis(100);
+
+/// Clones share jobs, caches, limits and statistics, so a solve running after
+/// its RPC returned still counts against them
+#[derive(Clone)]
+pub struct GrpcLpSolverService {
+    jobs: Arc<JobRegistry>,
+    results: Arc<IdempotencyCache<lp_solver::OptimizationResult>>,
+    limiter: Arc<SolveLimiter>,
+    isolation: Option<IsolationConfig>,
+    memory_limit_mb: Option<u64>,
+    size_limits: ProblemSizeLimits,
+    default_backend: SolverBackend,
+    audit: Option<Arc<dyn AuditSink>>,
+    publishers: Vec<Arc<dyn ResultPublisher>>,
+    recorder: Option<Arc<SolveRecorder>>,
+    solvers: Arc<SolverRegistry>,
+    default_solver: Option<Arc<dyn SolverService>>,
+    routing: RoutingPolicy,
+    fetcher: Option<Arc<ProblemFetcher>>,
+    problems: Option<Arc<dyn ProblemRepository>>,
+    submissions: Arc<SubmittedResults>,
+    webhooks: Option<Arc<WebhookNotifier>>,
+    sessions: Arc<SessionStore>,
+    stats: Arc<ServerStats>,
+    quotas: Arc<QuotaTracker>,
+    preempt_low_priority: bool,
+    workers: Option<Arc<WorkerPool>>,
+    admin: AdminToken,
+}
 
 impl GrpcLpSolverService {
     pub fn new() -> Self {
-        Self
+        Self {
+            jobs: Arc::new(JobRegistry::new()),
+            results: Arc::new(IdempotencyCache::new(DEFAULT_IDEMPOTENCY_TTL)),
+            limiter: Arc::new(SolveLimiter::unlimited()),
+            isolation: None,
+            memory_limit_mb: None,
+            size_limits: ProblemSizeLimits::unlimited(),
+            default_backend: SolverBackend::Auto,
+            audit: None,
+            publishers: Vec::new(),
+            recorder: None,
+            solvers: Arc::new(SolverRegistry::builtin()),
+            default_solver: None,
+            routing: RoutingPolicy::new(),
+            fetcher: None,
+            problems: None,
+            submissions: Arc::default(),
+            webhooks: None,
+            sessions: Arc::default(),
+            stats: Arc::new(ServerStats::new()),
+            quotas: Arc::new(QuotaTracker::unlimited()),
+            preempt_low_priority: false,
+            workers: None,
+            admin: AdminToken::default(),
+        }
     }
-}
 
-impl Default for GrpcLpSolverService {
-    fn default() -> Self {
-        Self::new()
+    /// Run every solve in a child process with the given resource limits
+    pub fn with_subprocess_isolation(mut self, limits: IsolationConfig) -> Self {
+        self.isolation = Some(limits);
+        self
     }
-}
 
-#[tonic::async_trait]
-impl lp_solver::linear_programming_solver_server::LinearProgrammingSolver for GrpcLpSolverService {
-    async fn solve_problem(
+    /// Cap the memory of every solve, in megabytes
+    ///
+    /// Requests may ask for a lower limit but never a higher one. Neither backend
+    /// exposes a native memory option, so limited solves always run isolated.
+    pub fn with_memory_limit_mb(mut self, megabytes: u64) -> Self {
+        self.memory_limit_mb = Some(megabytes);
+        self
+    }
+
+    /// Run solves on the solvers in `registry` and report them from
+    /// GetAvailableSolvers
+    pub fn with_solver_registry(mut self, registry: Arc<SolverRegistry>) -> Self {
+        self.solvers = registry;
+        self
+    }
+
+    /// Solve AUTO requests with `solver` instead of picking a built-in backend
+    ///
+    /// Takes precedence over `with_default_backend`.
+    pub fn with_default_solver(mut self, solver: Arc<dyn SolverService>) -> Self {
+        self.default_solver = Some(solver);
+        self
+    }
+
+    /// Route AUTO requests to registered solvers by `policy`'s rules
+    pub fn with_routing_policy(mut self, policy: RoutingPolicy) -> Self {
+        self.routing = policy;
+        self
+    }
+
+    /// Write a record of every solve to `sink`
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    /// Announce every solve that returns a result through `publisher`, next to
+    /// any added before
+    pub fn with_publisher(mut self, publisher: Arc<dyn ResultPublisher>) -> Self {
+        self.publishers.push(publisher);
+        self
+    }
+
+    /// Record every solve that returns a result to `recorder`, for replaying later
+    pub fn with_recorder(mut self, recorder: Arc<SolveRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Let SolveFromUrl fetch problem files from the hosts `config` allows
+    ///
+    /// Without this, SolveFromUrl is refused.
+    pub fn with_url_fetching(mut self, config: UrlFetchConfig) -> Self {
+        self.fetcher = Some(Arc::new(ProblemFetcher::new(config)));
+        self
+    }
+
+    /// Serve SaveProblem, GetProblem, ListProblems and SolveStoredProblem from
+    /// `repository`
+    ///
+    /// Without this, those calls are refused.
+    pub fn with_problem_repository(mut self, repository: Arc<dyn ProblemRepository>) -> Self {
+        self.problems = Some(repository);
+        self
+    }
+
+    /// Let SubmitProblem requests name a callback URL on the hosts `config` allows
+    ///
+    /// Without this, submissions with a callback URL are refused.
+    pub fn with_webhooks(mut self, config: WebhookConfig) -> Self {
+        self.webhooks = Some(Arc::new(WebhookNotifier::new(config)));
+        self
+    }
+
+    /// Keep at most `max_sessions` model sessions, each until it has been unused
+    /// for `idle_timeout`
+    pub fn with_session_limits(mut self, max_sessions: usize, idle_timeout: Duration) -> Self {
+        self.sessions = Arc::new(SessionStore::new(max_sessions, idle_timeout));
+        self
+    }
+
+    /// Backend used for requests that ask for AUTO
+    pub fn with_default_backend(mut self, backend: SolverBackend) -> Self {
+        self.default_backend = backend;
+        self
+    }
+
+    /// Reject problems larger than `limits`
+    pub fn with_size_limits(mut self, limits: ProblemSizeLimits) -> Self {
+        self.size_limits = limits;
+        self
+    }
+
+    /// Allow at most `max_concurrent` solves at once; excess requests follow `policy`
+    pub fn with_concurrency_limit(mut self, max_concurrent: usize, policy: OverloadPolicy) -> Self {
+        self.limiter = Arc::new(SolveLimiter::new(max_concurrent, policy));
+        self
+    }
+
+    /// Limit how much each API key may solve
+    pub fn with_quotas(mut self, policy: QuotaPolicy) -> Self {
+        self.quotas = Arc::new(QuotaTracker::new(policy));
+        self
+    }
+
+    /// Let a HIGH-priority solve that finds every slot busy stop the most recently
+    /// started LOW-priority MIP; the stopped solve fails with ABORTED
+    pub fn with_preemption(mut self) -> Self {
+        self.preempt_low_priority = true;
+        self
+    }
+
+    /// Act as a dispatcher: accept worker registrations and send solves to the
+    /// registered workers, solving locally only while none is live
+    pub fn with_workers(mut self, workers: Arc<WorkerPool>) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Open admin RPCs such as ListJobs and SetSolverEnabled to callers sending `token` in
+    /// `x-admin-token`; without a token they are refused
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin = AdminToken::new(token);
+        self
+    }
+
+    /// Set how long results stay available for idempotent replay
+    pub fn with_idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.results = Arc::new(IdempotencyCache::new(ttl));
+        self
+    }
+
+    /// Registry of queued and running solves
+    pub fn jobs(&self) -> &Arc<JobRegistry> {
+        &self.jobs
+    }
+
+    /// Isolation limits for a problem, if it has to run in a worker process
+    ///
+    /// The effective memory limit is the smaller of the server cap and the request's.
+    fn isolation_for(&self, problem: &OptimizationProblem) -> Option<IsolationConfig> {
+        let memory_limit_mb = match (self.memory_limit_mb, problem.solver_config.memory_limit_mb) {
+            (Some(server), Some(request)) => Some(server.min(request)),
+            (server, request) => server.or(request),
+        };
+
+        let Some(megabytes) = memory_limit_mb else {
+            return self.isolation.clone();
+        };
+        let limits = self.isolation.clone().unwrap_or_default();
+        let megabytes = limits
+            .memory_limit_mb
+            .map_or(megabytes, |m| m.min(megabytes));
+        Some(limits.with_memory_limit_mb(megabytes))
+    }
+
+    /// Pick an injected solver for the problem, if one applies
+    ///
+    /// `solver_name` is resolved against the registry: names of built-in backends
+    /// are turned into that backend so they keep the usual isolation path, while
+    /// custom solvers are returned to run as-is. AUTO requests get the injected
+    /// default solver when there is one. Asking for a disabled solver, by name or
+    /// backend, fails with `FAILED_PRECONDITION`.
+    fn resolve_solver(
         &self,
-        request: Request<lp_solver::OptimizationProblem>,
-    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
-        let proto_problem = request.into_inner();
+        problem: &mut OptimizationProblem,
+    ) -> Result<Option<Arc<dyn SolverService>>, Box<Status>> {
+        let Some(name) = problem.solver_config.solver_name.as_deref() else {
+            let backend = problem.solver_config.backend;
+            if self.solvers.is_disabled(backend.key()) {
+                return Err(disabled_solver(backend.key()));
+            }
+            return Ok(match backend {
+                SolverBackend::Auto => self.default_solver.clone(),
+                _ => None,
+            });
+        };
+        if self.solvers.is_disabled(name) {
+            return Err(disabled_solver(name));
+        }
 
-        println!("📊 Solving problem: {}", proto_problem.problem_name);
-        if !proto_problem.description.is_empty() {
-            println!("   Description: {}", proto_problem.description);
+        if let Some(backend) = self.solvers.backend_for(name) {
+            problem.solver_config.backend = backend;
+            return Ok(None);
+        }
+        match self.solvers.get_named(name) {
+            Some(solver) => Ok(Some(solver)),
+            None => Err(Box::new(Status::invalid_argument(format!(
+                "Unknown solver '{}'; available: {}",
+                name,
+                self.solvers.keys().collect::<Vec<_>>().join(", ")
+            )))),
         }
+    }
 
-        // Convert protobuf to domain model
-        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+    /// Apply the routing policy to an AUTO request without an explicit solver
+    fn route(&self, problem: &mut OptimizationProblem, hint: Option<&str>) {
+        if problem.solver_config.backend != SolverBackend::Auto
+            || problem.solver_config.solver_name.is_some()
+        {
+            return;
+        }
+        let enabled = |solver: &str| !self.solvers.is_disabled(solver);
+        if let Some(solver) = self.routing.route(problem, hint, enabled) {
+            debug!(solver, "Routed solve");
+            problem.solver_config.solver_name = Some(solver.to_string());
+        }
+    }
+
+    /// Registered solver for the problem's backend
+    ///
+    /// RACE races every enabled backend, and AUTO picks among them per problem.
+    /// A backend that fails hands the solve to the other enabled backends. Falls
+    /// back to the factory for backends missing from the registry.
+    fn backend_solver(&self, problem: &OptimizationProblem) -> Arc<dyn SolverService> {
+        match problem.solver_config.backend {
+            SolverBackend::Race => Arc::new(RacingSolver::new(self.solvers.backend_solvers())),
+            SolverBackend::Auto if !self.solvers.has_backends() => {
+                SolverFactory::create_solver(problem)
+            }
+            SolverBackend::Auto => Arc::new(AutoSolver::new(self.solvers.backends())),
+            backend => match self.solvers.get(backend) {
+                Some(solver) => {
+                    let fallbacks = self
+                        .solvers
+                        .backends()
+                        .into_iter()
+                        .filter(|&(other, _)| other != backend)
+                        .map(|(_, solver)| solver)
+                        .collect();
+                    Arc::new(FallbackSolver::new(solver, fallbacks))
+                }
+                None => SolverFactory::create_solver(problem),
+            },
+        }
+    }
+
+    /// Solve a domain problem, tracking it in the job registry
+    ///
+    /// Waits for a free solve slot first, then runs the solver on the blocking pool
+    /// so long solves don't stall the async runtime.
+    async fn solve_domain_problem(
+        &self,
+        mut domain_problem: OptimizationProblem,
+        caller: &Caller,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let prepared = self.prepare_problem(&mut domain_problem, caller);
+        let record = self
+            .audit
+            .is_some()
+            .then(|| AuditRecord::new(caller, &domain_problem));
+        let event = (!self.publishers.is_empty()).then(|| SolveEvent::new(caller, &domain_problem));
+        let recorded_problem = self.recorder.is_some().then(|| domain_problem.clone());
+        let solve = async {
+            let injected_solver = prepared?;
+            self.solve_tracked(domain_problem, injected_solver).await
+        };
+        let outcome = self.audited(record, solve).await;
+        if let (Some(event), Ok(result)) = (event, &outcome) {
+            self.publish(event, result);
+        }
+        if let (Some(problem), Ok(result)) = (recorded_problem, &outcome) {
+            self.record_solve(&problem, result, caller);
+        }
+        outcome
+    }
+
+    /// Complete `event` with `result` and hand it to every publisher
+    fn publish(&self, mut event: SolveEvent, result: &lp_solver::OptimizationResult) {
+        event.complete(result);
+        for publisher in &self.publishers {
+            publisher.publish(&event);
+        }
+    }
+
+    /// Append a finished solve of `problem` to the recording, if there is one
+    fn record_solve(
+        &self,
+        problem: &OptimizationProblem,
+        result: &lp_solver::OptimizationResult,
+        caller: &Caller,
+    ) {
+        let Some(recorder) = &self.recorder else {
+            return;
+        };
+        let backend = result
+            .statistics
+            .as_ref()
+            .map(|stats| stats.solver_backend.clone())
+            .unwrap_or_default();
+        let recorded = mappers::proto_to_domain_solution(result.clone())
+            .map_err(|status| status.message().to_string())
+            .and_then(|solution| {
+                let solve = RecordedSolve::new(
+                    caller.request_id.to_string(),
+                    problem,
+                    SolverFactory::random_seed(problem),
+                    backend,
+                    solution,
+                );
+                recorder.record(&solve).map_err(|e| e.to_string())
+            });
+        if let Err(e) = recorded {
+            error!(path = %recorder.path().display(), error = %e, "Cannot record solve");
+        }
+    }
+
+    /// Route an AUTO request, or give it the default backend
+    fn pick_backend(&self, problem: &mut OptimizationProblem, caller: &Caller) {
+        self.route(problem, caller.solver_hint.as_deref());
+        if problem.solver_config.backend == SolverBackend::Auto
+            && self.default_solver.is_none()
+            && !self.solvers.is_disabled(self.default_backend.key())
+        {
+            problem.solver_config.backend = self.default_backend;
+        }
+    }
+
+    /// Make a request's problem ready to solve, returning the injected solver
+    /// that should run it, if any
+    ///
+    /// Picks the backend, resolves the solver, checks the size limits, relaxes
+    /// integrality when asked and fixes the variables in `fixed_values`. Every
+    /// path that solves a request goes through here.
+    fn prepare_problem(
+        &self,
+        problem: &mut OptimizationProblem,
+        caller: &Caller,
+    ) -> Result<Option<Arc<dyn SolverService>>, Box<Status>> {
+        self.pick_backend(problem, caller);
+        let injected_solver = self.resolve_solver(problem)?;
+        self.size_limits.check(problem)?;
+        prepare_problem(problem).map_err(invalid_problem)?;
+        Ok(injected_solver)
+    }
+
+    /// Run a solving RPC under the caller's quota
+    ///
+    /// Refuses it with RESOURCE_EXHAUSTED once the caller's API key has used up
+    /// its quota. Otherwise charges it the CPU time `cpu_secs` reads from the
+    /// response, or its wall time when there is none, and reports what is left
+    /// in the response metadata.
+    async fn metered<T>(
+        &self,
+        caller: &Caller,
+        rpc: impl std::future::Future<Output = Result<Response<T>, Status>>,
+        cpu_secs: impl FnOnce(&T) -> Option<f64>,
+    ) -> Result<Response<T>, Status> {
+        let api_key = caller.api_key.as_deref();
+        self.quotas.admit(api_key).map_err(|e| *e)?;
+
+        let started_at = Instant::now();
+        let outcome = rpc.await;
+        let used = outcome
+            .as_ref()
+            .ok()
+            .and_then(|response| cpu_secs(response.get_ref()))
+            .unwrap_or_else(|| started_at.elapsed().as_secs_f64());
+        let quota = self.quotas.charge(api_key, used);
+        match outcome {
+            Ok(mut response) => {
+                quota.insert_into(response.metadata_mut());
+                Ok(response)
+            }
+            Err(mut status) => {
+                quota.insert_into(status.metadata_mut());
+                Err(status)
+            }
+        }
+    }
+
+    /// Run `solve`, completing and writing `record` with its outcome
+    async fn audited(
+        &self,
+        record: Option<AuditRecord>,
+        solve: impl std::future::Future<Output = Result<lp_solver::OptimizationResult, Box<Status>>>,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let (Some(sink), Some(mut record)) = (&self.audit, record) else {
+            return solve.await;
+        };
+
+        let started_at = Instant::now();
+        let outcome = solve.await;
+
+        record.duration_ms = started_at.elapsed().as_secs_f64() * 1000.0;
+        match &outcome {
+            Ok(result) => {
+                record.status = lp_solver::SolutionStatus::try_from(result.status)
+                    .map(|s| s.as_str_name().to_string())
+                    .unwrap_or_default();
+                record.objective = result.optimal_value;
+                if let Some(stats) = &result.statistics {
+                    record.backend = stats.solver_backend.clone();
+                }
+            }
+            Err(status) => {
+                record.status = match status.code() {
+                    tonic::Code::InvalidArgument | tonic::Code::ResourceExhausted => "REJECTED",
+                    _ => "FAILED",
+                }
+                .to_string();
+                record.error = Some(status.message().to_string());
+            }
+        }
+        if let Err(e) = sink.record(&record) {
+            error!(error = %e, "Cannot write audit record");
+        }
+
+        outcome
+    }
+
+    /// Register a prepared problem as a job and solve it inside a `solve` span
+    async fn solve_tracked(
+        &self,
+        domain_problem: OptimizationProblem,
+        injected_solver: Option<Arc<dyn SolverService>>,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let job = self.jobs.register(&domain_problem);
+        let span = info_span!(
+            "solve",
+            job_id = job.id(),
+            problem = %domain_problem.name,
+            variables = domain_problem.num_variables(),
+            constraints = domain_problem.constraints.len(),
+            backend = field::Empty,
+            tags = field::Empty,
+        );
+        if !domain_problem.tags.is_empty() {
+            span.record("tags", field::debug(&domain_problem.tags));
+        }
+
+        self.run_job(job, domain_problem, injected_solver)
+            .instrument(span)
+            .await
+    }
+
+    /// Solver that will run the problem, wrapped for presolve, scaling, polishing,
+    /// isolation and dispatch to workers when configured
+    ///
+    /// Injected solvers run in-process: a worker process or remote worker can only
+    /// rebuild the built-in backends. `wall_time_limit` tightens the isolation
+    /// wall-clock limit.
+    fn solver_for(
+        &self,
+        problem: &OptimizationProblem,
+        injected_solver: Option<Arc<dyn SolverService>>,
+        wall_time_limit: Option<Duration>,
+    ) -> Arc<dyn SolverService> {
+        let dispatch = injected_solver.is_none();
+        let mut solver = self.isolated_solver_for(problem, injected_solver, wall_time_limit);
+        if let (true, Some(workers)) = (dispatch, &self.workers) {
+            solver = Arc::new(RemoteSolver::new(Arc::clone(workers), solver));
+        }
+        // Preprocessing happens here, so an isolated worker receives the reduced,
+        // scaled problem
+        wrap_solver(solver, &problem.solver_config)
+    }
+
+    /// Injected or backend solver, in a worker process when isolation applies
+    fn isolated_solver_for(
+        &self,
+        problem: &OptimizationProblem,
+        injected_solver: Option<Arc<dyn SolverService>>,
+        wall_time_limit: Option<Duration>,
+    ) -> Arc<dyn SolverService> {
+        if let Some(solver) = injected_solver {
+            if self.isolation_for(problem).is_some() {
+                warn!(
+                    solver = solver.name(),
+                    "Injected solver runs without process isolation or memory limits"
+                );
+            }
+            return solver;
+        }
+
+        let solver = self.backend_solver(problem);
+        let Some(mut limits) = self.isolation_for(problem) else {
+            return solver;
+        };
+        if let Some(limit) = wall_time_limit {
+            let limit = limits.wall_time_limit.map_or(limit, |l| l.min(limit));
+            limits = limits.with_wall_time_limit(limit);
+        }
+        Arc::new(SubprocessSolver::new(
+            solver.name(),
+            problem.solver_config.backend,
+            limits,
+        ))
+    }
+
+    /// Wait for a solve slot for `job`, behind queued solves of higher priority
+    /// or earlier deadline, then cap `config`'s time limit at its deadline
+    ///
+    /// With preemption on, a HIGH solve that finds every slot busy first stops
+    /// the most recently started LOW-priority MIP. A solve whose deadline passes
+    /// while it is queued fails with DEADLINE_EXCEEDED.
+    async fn solve_slot(
+        &self,
+        job: &JobHandle,
+        config: &mut SolverConfig,
+    ) -> Result<SolvePermit, Box<Status>> {
+        let priority = config.priority;
+        if self.preempt_low_priority
+            && priority == SolvePriority::High
+            && self.limiter.available() == Some(0)
+        {
+            if let Some(preempted) = self.jobs.preempt(SolvePriority::Low) {
+                info!(preempted_job = preempted, "Preempting a low-priority solve");
+            }
+        }
+
+        let deadline_exceeded = || {
+            Box::new(Status::deadline_exceeded(
+                "Solve deadline passed while it was queued",
+            ))
+        };
+        let acquire = self.limiter.acquire(priority, config.deadline);
+        let permit = match config.deadline {
+            Some(deadline) => {
+                let wait = deadline
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_default();
+                tokio::time::timeout(wait, acquire)
+                    .await
+                    .map_err(|_| deadline_exceeded())??
+            }
+            None => acquire.await?,
+        };
+        if job.cancellation().is_cancelled() {
+            return Err(Box::new(Status::cancelled(
+                "Solve was cancelled while queued",
+            )));
+        }
+        if !config.limit_to_deadline(DEADLINE_MARGIN) {
+            return Err(deadline_exceeded());
+        }
+        Ok(permit)
+    }
+
+    /// Wait for a solve slot, then run the solver for a registered job
+    ///
+    /// The solve stops early on CancelSolve, returning an INTERRUPTED result with
+    /// the best solution found so far, or when this future is dropped because the
+    /// client went away or its deadline passed.
+    async fn run_job(
+        &self,
+        job: JobHandle,
+        mut domain_problem: OptimizationProblem,
+        injected_solver: Option<Arc<dyn SolverService>>,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let cancel = job.cancellation();
+        let _cancel_on_drop = cancel.clone().cancel_on_drop();
+
+        let submitted_at = Instant::now();
+        let _permit = self
+            .solve_slot(&job, &mut domain_problem.solver_config)
+            .await?;
+        let queued_ms = submitted_at.elapsed().as_secs_f64() * 1000.0;
 
         // Create solver based on problem configuration
-        let solver = SolverFactory::create_solver(&domain_problem);
-        println!("   Using solver: {}", solver.name());
+        let solver = self.solver_for(&domain_problem, injected_solver, None);
+        Span::current().record("backend", solver.name());
+        job.mark_running(solver.name());
 
         // Solve using domain service
-        let solution = solver
-            .solve(&domain_problem)
-            .map_err(|e| Status::internal(format!("Solver error: {}", e)))?;
+        let started_at = Instant::now();
+        let problem = Arc::new(domain_problem);
+        let worker = Arc::clone(&solver);
+        let worker_problem = Arc::clone(&problem);
+        let progress = job.progress();
+        let worker_cancel = cancel.clone();
+        let span = Span::current();
+        let outcome = tokio::task::spawn_blocking(move || {
+            span.in_scope(|| {
+                measured(|| worker.solve_cancellable(&worker_problem, &progress, &worker_cancel))
+            })
+        })
+        .await
+        .map_err(|e| Box::new(Status::internal(format!("Solver task failed: {}", e))))
+        .and_then(|solved| solved.map_err(|e| solver_failure(e, &cancel)))
+        .map_err(|status| preemption(&job, status));
+        let solve_ms = started_at.elapsed().as_secs_f64() * 1000.0;
+        let mut solution = match outcome {
+            Ok(solution) => solution,
+            // A CancelSolve still hands back whatever the solver found so far
+            Err(status) if status.code() == Code::Cancelled => job.interrupted(&problem),
+            Err(status) => {
+                let outcome = match status.code() {
+                    Code::Aborted => "PREEMPTED",
+                    _ => "FAILED",
+                };
+                self.stats.record_solve(outcome, solver.name(), solve_ms);
+                return Err(status);
+            }
+        };
+        solution.tags = problem.tags.clone();
+
+        info!(
+            status = %solution.status,
+            objective = solution.optimal_value,
+            queued_ms,
+            solve_ms,
+            "Solve finished"
+        );
+        let result = solution_result(&problem, solution, solver.name());
+        let status = lp_solver::SolutionStatus::try_from(result.status)
+            .map_or("UNKNOWN", |status| status.as_str_name());
+        let backend = result
+            .statistics
+            .as_ref()
+            .map(|stats| stats.solver_backend.as_str())
+            .filter(|backend| !backend.is_empty())
+            .unwrap_or(solver.name());
+        self.stats.record_solve(status, backend, solve_ms);
+        Ok(result)
+    }
 
-        println!("✓ Status: {}", solution.status);
+    /// SolveProblem, with idempotent replay when the client sends a key
+    async fn handle_solve(
+        &self,
+        request: Request<lp_solver::OptimizationProblem>,
+        caller: &Caller,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let idempotency_key = request
+            .metadata()
+            .get(IDEMPOTENCY_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|key| !key.is_empty())
+            .map(str::to_string);
+        let proto_problem = request.into_inner();
 
-        // Convert domain solution to protobuf
-        let proto_result = mappers::domain_to_proto_solution(solution, solver.name());
+        info!(
+            problem = %proto_problem.problem_name,
+            description = %proto_problem.description,
+            "Received solve request"
+        );
 
-        Ok(Response::new(proto_result))
+        // Convert protobuf to domain model
+        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+
+        // Retried requests with a known key get the original result back, as long
+        // as they send the same problem under the same API key
+        let Some(key) = idempotency_key else {
+            let result = self.solve_domain_problem(domain_problem, caller).await;
+            return Ok(Response::new(result.map_err(|e| *e)?));
+        };
+
+        let fingerprint = domain_problem.fingerprint();
+        let solve = || self.solve_domain_problem(domain_problem, caller);
+        let (proto_result, replayed) = self
+            .results
+            .get_or_run(caller.api_key.as_deref(), &key, fingerprint, solve)
+            .await
+            .map_err(|e| *e)?;
+        self.stats.record_cache_lookup(replayed);
+        if replayed {
+            info!(idempotency_key = %key, "Replayed cached result");
+        }
+
+        let mut response = Response::new(proto_result);
+        response.metadata_mut().insert(
+            IDEMPOTENT_REPLAY_HEADER,
+            MetadataValue::from_static(if replayed { "true" } else { "false" }),
+        );
+        Ok(response)
     }
 
-    async fn solve_problem_stream(
+    /// SolveProblemStream: assemble the problem from chunks, then solve it
+    ///
+    /// The call fails at the first bad chunk rather than once the stream closes.
+    async fn handle_solve_stream(
         &self,
         request: Request<tonic::Streaming<lp_solver::ProblemChunk>>,
+        caller: &Caller,
     ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
         let mut stream = request.into_inner();
+        let mut assembler = ProblemAssembler::new(self.size_limits);
+
+        // Convert and check each chunk as it arrives, bailing out at the first bad one
+        while let Some(chunk) = stream.message().await? {
+            assembler.push(chunk).map_err(|e| *e)?;
+        }
+
+        let domain_problem = assembler.finish().map_err(|e| *e)?;
+        let proto_result = self
+            .solve_domain_problem(domain_problem, caller)
+            .await
+            .map_err(|e| *e)?;
+
+        Ok(Response::new(proto_result))
+    }
+
+    /// SolveFromUrl: fetch and read the problem file, then solve it like SolveProblem
+    async fn handle_solve_from_url(
+        &self,
+        request: lp_solver::UrlProblemRequest,
+        caller: &Caller,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let Some(fetcher) = &self.fetcher else {
+            return Err(Box::new(Status::failed_precondition(
+                "Solving from URLs is not enabled on this server",
+            )));
+        };
+        let url = request.url;
+        // Pre-signed URLs carry their credentials in the query
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let format = match mappers::proto_to_domain_problem_format(request.format)? {
+            Some(format) => format,
+            None => ProblemFormat::from_path(path).ok_or_else(|| {
+                Box::new(Status::invalid_argument(format!(
+                    "Cannot tell the format of '{}' from its extension; set the format",
+                    path
+                )))
+            })?,
+        };
+        info!(%url, %format, "Received URL solve request");
+
+        let started_at = Instant::now();
+        let bytes = fetcher.fetch(&url).await?;
+        let size = bytes.len();
+        let mut problem = tokio::task::spawn_blocking(move || {
+            let text = String::from_utf8(bytes).map_err(|_| {
+                SolverError::InvalidProblem("Problem file is not UTF-8 text".to_string())
+            })?;
+            OptimizationProblem::parse(&text, format)
+        })
+        .await
+        .map_err(|e| Box::new(Status::internal(format!("Problem reader panicked: {}", e))))?
+        .map_err(invalid_problem)?;
+        debug!(
+            bytes = size,
+            variables = problem.num_variables(),
+            constraints = problem.constraints.len(),
+            load_ms = started_at.elapsed().as_secs_f64() * 1000.0,
+            "Loaded problem file"
+        );
+
+        if problem.name.is_empty() {
+            problem.name = path.rsplit('/').next().unwrap_or_default().to_string();
+        }
+        problem.solver_config =
+            mappers::proto_to_domain_solver_config(request.config.unwrap_or_default());
+        self.solve_domain_problem(problem, caller).await
+    }
+
+    /// SubmitProblem: solve on a clone of the service after the RPC returns
+    ///
+    /// The solve is metered and audited like SolveProblem. Its outcome is kept for
+    /// GetResult before the callback, if any, is told about it.
+    fn handle_submit(
+        &self,
+        request: lp_solver::SubmitRequest,
+        caller: Caller,
+    ) -> Result<lp_solver::SubmittedSolve, Box<Status>> {
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| missing_field("problem", "Submit request has no problem"))?;
+        let problem = mappers::proto_to_domain_problem(proto_problem)?;
+        let callback = match request.callback_url.as_str() {
+            "" => None,
+            url => {
+                let notifier = self.webhooks.clone().ok_or_else(|| {
+                    Box::new(Status::failed_precondition(
+                        "Callback URLs are not enabled on this server",
+                    ))
+                })?;
+                Some((notifier.check(url)?, notifier))
+            }
+        };
+
+        let result_id = self.submissions.start();
+        info!(result_id, problem = %problem.name, "Received solve submission");
+        let service = self.clone();
+        let id = result_id.clone();
+        let span = info_span!("submission", result_id = %id, request_id = %caller.request_id);
+        tokio::spawn(
+            async move {
+                let solve = async {
+                    service
+                        .solve_domain_problem(problem, &caller)
+                        .await
+                        .map(Response::new)
+                        .map_err(|e| *e)
+                };
+                let outcome = service
+                    .metered(&caller, solve, result_cpu_secs)
+                    .await
+                    .map(Response::into_inner);
+                let notice = callback.as_ref().map(|_| {
+                    CompletionNotice::new(id.clone(), caller.request_id.to_string(), &outcome)
+                });
+                service.submissions.finish(&id, outcome);
+                if let (Some((uri, notifier)), Some(notice)) = (callback, notice) {
+                    notifier.notify(&uri, &notice).await;
+                }
+            }
+            .instrument(span),
+        );
+        Ok(lp_solver::SubmittedSolve { result_id })
+    }
+
+    /// The problem repository, or FAILED_PRECONDITION when none is configured
+    fn problem_repository(&self) -> Result<&dyn ProblemRepository, Box<Status>> {
+        self.problems.as_deref().ok_or_else(|| {
+            Box::new(Status::failed_precondition(
+                "The problem repository is not enabled on this server",
+            ))
+        })
+    }
+
+    /// The problem saved under `id`, or NOT_FOUND
+    fn load_stored_problem(&self, id: &str) -> Result<OptimizationProblem, Box<Status>> {
+        let repository = self.problem_repository()?;
+        if !is_valid_problem_id(id) {
+            return Err(Box::new(Status::invalid_argument(format!(
+                "'{}' is not a valid problem ID",
+                id
+            ))));
+        }
+        repository
+            .load(id)
+            .map_err(|e| {
+                Box::new(Status::internal(format!(
+                    "Cannot load problem '{}': {}",
+                    id, e
+                )))
+            })?
+            .ok_or_else(|| Box::new(Status::not_found(format!("No problem saved as '{}'", id))))
+    }
+
+    /// SolveStoredProblem: a saved problem with this request's config and changes
+    ///
+    /// The solution goes into the problem's history; failing to write it is
+    /// logged rather than failing the solve.
+    async fn handle_solve_stored(
+        &self,
+        request: lp_solver::StoredSolveRequest,
+        caller: &Caller,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let mut problem = self.load_stored_problem(&request.id)?;
+        info!(id = %request.id, "Received stored problem solve request");
+        if let Some(config) = request.config {
+            problem.solver_config = mappers::proto_to_domain_solver_config(config);
+        }
+        let overridden = request.overrides.is_some();
+        if let Some(overrides) = request.overrides {
+            mappers::proto_to_domain_scenario(overrides)
+                .apply_to(&mut problem)
+                .map_err(invalid_problem)?;
+        }
+        let config = problem.solver_config.clone();
+        let result = self.solve_domain_problem(problem, caller).await?;
 
+        let repository = self.problem_repository()?;
+        let recorded = mappers::proto_to_domain_solution(result.clone()).and_then(|solution| {
+            let record =
+                SolveRecord::new(caller.request_id.to_string(), config, overridden, &solution);
+            repository
+                .record_solve(&request.id, &record, &solution)
+                .map_err(|e| Box::new(Status::internal(e.to_string())))
+        });
+        if let Err(e) = recorded {
+            error!(id = %request.id, error = %e.message(), "Cannot record solve in problem history");
+        }
+        Ok(result)
+    }
+
+    /// ValidateProblemStream: check each chunk as it is described, then the assembled problem
+    ///
+    /// Unlike solving, a bad chunk doesn't end the call; the client gets every
+    /// problem found, tagged with the chunk it came from.
+    async fn handle_validate_stream(
+        &self,
+        mut stream: impl futures::Stream<Item = Result<lp_solver::ProblemChunk, Status>> + Unpin,
+    ) -> Result<Response<lp_solver::ValidationResult>, Status> {
         let mut objective: Option<lp_solver::ObjectiveFunction> = None;
         let mut constraints = Vec::new();
         let mut variables = Vec::new();
         let mut solver_config: Option<lp_solver::SolverConfig> = None;
-        let mut problem_name = String::new();
-        let mut description = String::new();
+        let mut metadata: Option<lp_solver::ProblemMetadata> = None;
+        let mut size = self.size_limits.tracker();
+        let mut chunk_errors = Vec::new();
+        let mut chunk_error = |chunk_index: usize, message: String| {
+            chunk_errors.push(lp_solver::ChunkError {
+                chunk_index: chunk_index as u32,
+                message,
+            })
+        };
 
-        // Collect all chunks
-        while let Some(chunk) = stream.message().await? {
-            match chunk.chunk {
+        let mut index = 0;
+        while let Some(chunk) = stream.next().await.transpose()? {
+            let grown = match chunk.chunk {
                 Some(lp_solver::problem_chunk::Chunk::Objective(obj)) => {
+                    if objective.is_some() {
+                        chunk_error(index, "Objective sent more than once".to_string());
+                    }
+                    let grown = size.add_objective(obj.coefficients.len());
                     objective = Some(obj);
+                    grown
                 }
                 Some(lp_solver::problem_chunk::Chunk::Constraint(c)) => {
-                    constraints.push(c);
+                    let grown = size.add_constraint(&c.coefficients);
+                    constraints.push((index, StreamedConstraint::Dense(c)));
+                    grown
+                }
+                Some(lp_solver::problem_chunk::Chunk::SparseConstraint(c)) => {
+                    let grown = size.add_constraint(&c.values);
+                    constraints.push((index, StreamedConstraint::Sparse(c)));
+                    grown
                 }
                 Some(lp_solver::problem_chunk::Chunk::Variable(v)) => {
-                    variables.push(v);
+                    let grown = size.add_variable();
+                    variables.push((index, v));
+                    grown
+                }
+                Some(lp_solver::problem_chunk::Chunk::ConstraintBatch(batch)) => {
+                    if batch.constraints.is_empty() && batch.sparse_constraints.is_empty() {
+                        chunk_error(index, "Constraint batch is empty".to_string());
+                    }
+                    batch
+                        .constraints
+                        .into_iter()
+                        .try_for_each(|c| {
+                            size.add_constraint(&c.coefficients)?;
+                            constraints.push((index, StreamedConstraint::Dense(c)));
+                            Ok(())
+                        })
+                        .and_then(|()| {
+                            batch.sparse_constraints.into_iter().try_for_each(|c| {
+                                size.add_constraint(&c.values)?;
+                                constraints.push((index, StreamedConstraint::Sparse(c)));
+                                Ok(())
+                            })
+                        })
+                }
+                Some(lp_solver::problem_chunk::Chunk::VariableBatch(batch)) => {
+                    if batch.variables.is_empty() {
+                        chunk_error(index, "Variable batch is empty".to_string());
+                    }
+                    batch.variables.into_iter().try_for_each(|v| {
+                        size.add_variable()?;
+                        variables.push((index, v));
+                        Ok(())
+                    })
                 }
                 Some(lp_solver::problem_chunk::Chunk::Metadata(m)) => {
-                    problem_name = m.problem_name;
-                    description = m.description;
+                    if metadata.replace(m).is_some() {
+                        chunk_error(index, "Metadata sent more than once".to_string());
+                    }
+                    Ok(())
                 }
                 Some(lp_solver::problem_chunk::Chunk::SolverConfig(sc)) => {
-                    solver_config = Some(sc);
+                    if solver_config.replace(sc).is_some() {
+                        chunk_error(index, "Solver config sent more than once".to_string());
+                    }
+                    Ok(())
                 }
-                None => {}
+                None => {
+                    chunk_error(index, "Chunk is empty".to_string());
+                    Ok(())
+                }
+            };
+
+            // Past the size limit the rest of the stream isn't worth buffering
+            if let Err(status) = grown {
+                chunk_error(index, status.message().to_string());
+                break;
+            }
+            index += 1;
+        }
+
+        // Rows are only checked now, since the objective that sizes them may come last
+        let num_vars = objective.as_ref().map(|obj| obj.coefficients.len());
+        let constraints: Vec<_> = {
+            let names = mappers::variable_name_index(
+                variables.iter().map(|(_, v)| v.name.as_str()),
+                objective
+                    .as_ref()
+                    .map_or(&[], |obj| obj.variable_names.as_slice()),
+            );
+            constraints
+                .into_iter()
+                .filter_map(|(chunk_index, c)| match c.into_domain(num_vars, &names) {
+                    Ok(c) => Some((chunk_index, c)),
+                    Err(status) => {
+                        chunk_error(chunk_index, status.message().to_string());
+                        None
+                    }
+                })
+                .collect()
+        };
+        for (chunk_index, constraint) in &constraints {
+            if let Some(num_vars) = num_vars.filter(|&n| n != constraint.coefficients.len()) {
+                chunk_error(
+                    *chunk_index,
+                    format!(
+                        "Constraint '{}' has {} coefficients but problem has {} variables",
+                        constraint.name,
+                        constraint.coefficients.len(),
+                        num_vars
+                    ),
+                );
+            }
+            if constraint
+                .coefficients
+                .values()
+                .iter()
+                .any(|c| !c.is_finite())
+                || constraint.bound.is_nan()
+            {
+                chunk_error(
+                    *chunk_index,
+                    format!("Constraint '{}' has non-finite values", constraint.name),
+                );
+            }
+        }
+        for (chunk_index, variable) in &variables {
+            if let Err(status) = mappers::proto_to_domain_variable_type(variable.r#type) {
+                chunk_error(*chunk_index, status.message().to_string());
+            }
+            if variable
+                .upper_bound
+                .is_some_and(|upper| variable.lower_bound > upper)
+            {
+                chunk_error(
+                    *chunk_index,
+                    format!(
+                        "Variable '{}' has lower bound above its upper bound",
+                        variable.name
+                    ),
+                );
             }
         }
 
-        // Build complete problem
+        // The rows are already converted, so only the rest goes through the mapper
+        let metadata = metadata.unwrap_or_default();
         let proto_problem = lp_solver::OptimizationProblem {
             objective,
-            constraints,
-            variables,
+            constraints: Vec::new(),
+            variables: variables.into_iter().map(|(_, v)| v).collect(),
             solver_config,
-            problem_name,
-            description,
+            problem_name: metadata.problem_name,
+            description: metadata.description,
+            tags: metadata.tags,
         };
+        let (num_constraints, num_variables) = (
+            constraints.len() as u32,
+            proto_problem.variables.len() as u32,
+        );
 
-        // Reuse solve_problem logic
-        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
-        let solver = SolverFactory::create_solver(&domain_problem);
-        let solution = solver
-            .solve(&domain_problem)
-            .map_err(|e| Status::internal(format!("Solver error: {}", e)))?;
-
-        let proto_result = mappers::domain_to_proto_solution(solution, solver.name());
-        Ok(Response::new(proto_result))
-    }
-
-    async fn get_available_solvers(
-        &self,
-        _request: Request<lp_solver::Empty>,
-    ) -> Result<Response<lp_solver::AvailableSolvers>, Status> {
-        let solvers = vec![
-            lp_solver::SolverInfo {
-                name: "COIN-OR CBC".to_string(),
-                version: "2.10+".to_string(),
-                supports_mip: true,
-                supports_lp: true,
-                capabilities: vec![
-                    "Mixed-Integer Programming".to_string(),
-                    "Branch and Bound".to_string(),
-                    "Cutting Planes".to_string(),
-                    "Primal/Dual Simplex".to_string(),
-                ],
-            },
-            lp_solver::SolverInfo {
-                name: "HiGHS".to_string(),
-                version: "1.7+".to_string(),
-                supports_mip: true,
-                supports_lp: true,
-                capabilities: vec![
-                    "Mixed-Integer Programming".to_string(),
-                    "Linear Programming".to_string(),
-                    "Primal/Dual Simplex".to_string(),
-                    "Interior Point Method".to_string(),
-                    "Presolve".to_string(),
-                ],
+        let mut result = match mappers::proto_to_domain_problem(proto_problem) {
+            Ok(mut domain_problem) => {
+                domain_problem.constraints = constraints.into_iter().map(|(_, c)| c).collect();
+                self.validate_domain_problem(domain_problem)
+            }
+            // Whatever broke the conversion is already reported against its chunk
+            Err(status) => lp_solver::ValidationResult {
+                errors: vec![status.message().to_string()],
+                num_variables: num_vars.map_or(num_variables, |n| n as u32),
+                num_constraints,
+                ..Default::default()
             },
-        ];
+        };
+        chunk_errors.sort_by_key(|e| e.chunk_index);
+        result.is_valid = result.errors.is_empty() && chunk_errors.is_empty();
+        result.chunk_errors = chunk_errors;
 
-        Ok(Response::new(lp_solver::AvailableSolvers { solvers }))
+        Ok(Response::new(result))
     }
 
-    async fn validate_problem(
+    /// Validation shared by the unary and streaming RPCs
+    fn validate_domain_problem(
         &self,
-        request: Request<lp_solver::OptimizationProblem>,
-    ) -> Result<Response<lp_solver::ValidationResult>, Status> {
-        let proto_problem = request.into_inner();
-        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
-
-        // Use the default solver for validation
-        let solver = SolverFactory::default_solver();
-
+        mut domain_problem: OptimizationProblem,
+    ) -> lp_solver::ValidationResult {
         let mut errors = Vec::new();
         let mut warnings = Vec::new();
 
+        // Validate with the solver that would run the problem
+        let solver = match self.resolve_solver(&mut domain_problem) {
+            Ok(Some(solver)) => solver,
+            Ok(None) => self.backend_solver(&domain_problem),
+            Err(status) => {
+                errors.push(status.message().to_string());
+                SolverFactory::default_solver()
+            }
+        };
+
+        if let Some(violation) = self.size_limits.violation(&domain_problem) {
+            errors.push(violation);
+        }
+
         // Use domain service validation
         match solver.validate(&domain_problem) {
-            Ok(_) => {
+            Ok(solver_warnings) => {
+                warnings.extend(solver_warnings);
                 // Additional warnings
                 if domain_problem.constraints.is_empty() {
                     warnings.push("Problem has no constraints (may be unbounded)".to_string());
@@ -178,20 +1250,1702 @@ impl lp_solver::linear_programming_solver_server::LinearProgrammingSolver for Gr
             }
         }
 
-        let estimated_difficulty = if domain_problem.is_mixed_integer() {
-            (domain_problem.num_integer_variables() as f64 / 1000.0).min(1.0)
-        } else {
-            (domain_problem.num_variables() as f64 / 10000.0).min(0.5)
-        };
+        let difficulty = estimate_difficulty(&domain_problem);
 
-        Ok(Response::new(lp_solver::ValidationResult {
+        lp_solver::ValidationResult {
             is_valid: errors.is_empty(),
             errors,
             warnings,
             num_variables: domain_problem.num_variables() as u32,
             num_constraints: domain_problem.constraints.len() as u32,
             num_integer_vars: domain_problem.num_integer_variables() as u32,
-            estimated_difficulty,
-        }))
+            estimated_difficulty: difficulty.score,
+            chunk_errors: Vec::new(),
+            difficulty_factors: difficulty
+                .factors
+                .into_iter()
+                .map(mappers::difficulty_factor_to_proto)
+                .collect(),
+        }
+    }
+
+    /// BenchmarkProblem: solve on every registered solver in turn
+    ///
+    /// The runs share one solve slot and go one after another so they don't skew
+    /// each other's timings by competing for CPU.
+    async fn handle_benchmark(
+        &self,
+        request: Request<lp_solver::BenchmarkRequest>,
+        caller: &Caller,
+    ) -> Result<Response<lp_solver::BenchmarkResult>, Box<Status>> {
+        let request = request.into_inner();
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| missing_field("problem", "Benchmark request has no problem"))?;
+        let mut problem = mappers::proto_to_domain_problem(proto_problem)?;
+        // Each run resolves the solver it names below
+        self.prepare_problem(&mut problem, caller)?;
+        let time_limit =
+            (request.time_limit > 0.0).then(|| Duration::from_secs_f64(request.time_limit));
+
+        let _permit = self
+            .limiter
+            .acquire(
+                problem.solver_config.priority,
+                problem.solver_config.deadline,
+            )
+            .await?;
+
+        let keys: Vec<String> = self.solvers.keys().map(str::to_string).collect();
+        let mut runs = Vec::with_capacity(keys.len());
+        for key in keys {
+            let mut run_problem = problem.clone();
+            run_problem.solver_config.solver_name = Some(key.clone());
+            if let Some(limit) = time_limit {
+                run_problem.solver_config.time_limit = Some(limit.as_secs_f64());
+            }
+            let injected_solver = self.resolve_solver(&mut run_problem)?;
+            let solver = self.solver_for(&run_problem, injected_solver, time_limit);
+            runs.push(benchmark_run(key, solver, run_problem, time_limit).await);
+        }
+
+        let fastest = runs
+            .iter()
+            .filter(|run| run.status == lp_solver::SolutionStatus::Optimal as i32)
+            .min_by(|a, b| a.solve_time_ms.total_cmp(&b.solve_time_ms))
+            .map(|run| run.key.clone())
+            .unwrap_or_default();
+
+        Ok(Response::new(lp_solver::BenchmarkResult { runs, fastest }))
+    }
+
+    /// Worker pool of a dispatcher; other servers refuse worker RPCs
+    fn dispatcher(&self) -> Result<&Arc<WorkerPool>, Box<Status>> {
+        self.workers.as_ref().ok_or_else(|| {
+            Box::new(Status::failed_precondition(
+                "This server is not a dispatcher; start it with --dispatch",
+            ))
+        })
+    }
+
+    /// Whether the server should get new traffic, or why not
+    ///
+    /// Not ready while every solver is out of rotation, or while more than
+    /// `max_queued` solves wait for a slot (None = any backlog is fine).
+    pub fn readiness(&self, max_queued: Option<usize>) -> Result<(), String> {
+        let solvers_enabled =
+            self.default_solver.is_some() || self.solvers.states().any(|(_, enabled)| enabled);
+        if !solvers_enabled {
+            return Err("No solver is in rotation".to_string());
+        }
+        let queued = self.limiter.queued();
+        match max_queued {
+            Some(max) if queued > max => Err(format!(
+                "{} solves are queued, more than the allowed {}",
+                queued, max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Solve a known LP the way an AUTO request would, and check the optimum
+    ///
+    /// Runs through routing, the default solver and any isolation, so a server that
+    /// passes can serve requests. Servers call it before reporting ready.
+    pub async fn self_test(&self) -> Result<(), String> {
+        // max 3x + 2y s.t. x + y <= 4, x + 3y <= 6, x <= 3: optimum 11 at (3, 1)
+        let mut problem = OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Maximize,
+            vec![3.0, 2.0],
+        ))
+        .with_name("self-test")
+        .add_constraint(Constraint::new(
+            ConstraintType::LessThanOrEqual,
+            vec![1.0, 1.0],
+            4.0,
+        ))
+        .add_constraint(Constraint::new(
+            ConstraintType::LessThanOrEqual,
+            vec![1.0, 3.0],
+            6.0,
+        ))
+        .with_variables(vec![
+            Variable::continuous("x").with_bounds(0.0, Some(3.0)),
+            Variable::continuous("y"),
+        ]);
+
+        let caller = Caller::from_request(&Request::new(()));
+        let outcome = match self.prepare_problem(&mut problem, &caller) {
+            Ok(injected_solver) => self.solve_tracked(problem, injected_solver).await,
+            Err(status) => Err(status),
+        };
+        let result = outcome.map_err(|status| status.message().to_string())?;
+
+        let optimal = result.status == lp_solver::SolutionStatus::Optimal as i32;
+        match result.optimal_value {
+            Some(value) if optimal && (value - SELF_TEST_OPTIMUM).abs() <= 1e-6 => Ok(()),
+            value => Err(format!(
+                "expected optimum {}, got {:?} ({})",
+                SELF_TEST_OPTIMUM, value, result.message
+            )),
+        }
+    }
+
+    /// SolveScenarios: solve the base problem under every scenario as one job
+    async fn handle_solve_scenarios(
+        &self,
+        request: Request<lp_solver::ScenarioRequest>,
+        caller: &Caller,
+    ) -> Result<Response<lp_solver::ScenarioResults>, Box<Status>> {
+        let request = request.into_inner();
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| missing_field("problem", "Scenario request has no problem"))?;
+        let problem = mappers::proto_to_domain_problem(proto_problem)?;
+        let scenarios: Vec<Scenario> = request
+            .scenarios
+            .into_iter()
+            .map(mappers::proto_to_domain_scenario)
+            .collect();
+        info!(
+            problem = %problem.name,
+            scenarios = scenarios.len(),
+            "Received scenario request"
+        );
+
+        let names: Vec<String> = scenarios.iter().map(|s| s.name.clone()).collect();
+        let (outcomes, solver_name) = self.solve_scenarios_job(problem, scenarios, caller).await?;
+        let results = names
+            .into_iter()
+            .zip(outcomes)
+            .map(|(name, outcome)| match outcome {
+                Ok(solution) => lp_solver::ScenarioResult {
+                    name,
+                    result: Some(mappers::domain_to_proto_solution(solution, &solver_name)),
+                    error: String::new(),
+                },
+                Err(e) => lp_solver::ScenarioResult {
+                    name,
+                    result: None,
+                    error: e.to_string(),
+                },
+            })
+            .collect();
+        Ok(Response::new(lp_solver::ScenarioResults { results }))
+    }
+
+    /// SweepParameter: solve one scenario per point of the swept range
+    async fn handle_sweep(
+        &self,
+        request: Request<lp_solver::SweepRequest>,
+        caller: &Caller,
+    ) -> Result<Response<lp_solver::SweepResult>, Box<Status>> {
+        let mut request = request.into_inner();
+        let proto_problem = request
+            .problem
+            .take()
+            .ok_or_else(|| missing_field("problem", "Sweep request has no problem"))?;
+        let problem = mappers::proto_to_domain_problem(proto_problem)?;
+        let include_solutions = request.include_solutions;
+        let sweep = mappers::proto_to_domain_sweep(request)?;
+        let scenarios = sweep.scenarios(&problem).map_err(invalid_problem)?;
+        info!(
+            problem = %problem.name,
+            target = ?sweep.target,
+            points = scenarios.len(),
+            "Received sweep request"
+        );
+
+        let (outcomes, solver_name) = self.solve_scenarios_job(problem, scenarios, caller).await?;
+        let points = sweep
+            .values()
+            .into_iter()
+            .zip(outcomes)
+            .map(|(value, outcome)| match outcome {
+                Ok(solution) => lp_solver::SweepPoint {
+                    value,
+                    status: mappers::solution_status_to_proto(solution.status) as i32,
+                    objective: solution.optimal_value,
+                    result: include_solutions
+                        .then(|| mappers::domain_to_proto_solution(solution, &solver_name)),
+                    error: String::new(),
+                },
+                Err(e) => lp_solver::SweepPoint {
+                    value,
+                    status: lp_solver::SolutionStatus::Error as i32,
+                    objective: None,
+                    result: None,
+                    error: e.to_string(),
+                },
+            })
+            .collect();
+        Ok(Response::new(lp_solver::SweepResult { points }))
+    }
+
+    /// ComputeParetoFrontier: epsilon-constraint solves of the problem's objective
+    /// against a second one, as one job
+    async fn handle_pareto(
+        &self,
+        request: Request<lp_solver::ParetoRequest>,
+        caller: &Caller,
+    ) -> Result<Response<lp_solver::ParetoFrontier>, Box<Status>> {
+        let request = request.into_inner();
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| missing_field("problem", "Pareto request has no problem"))?;
+        let problem = mappers::proto_to_domain_problem(proto_problem)?;
+        let second = request.second_objective.ok_or_else(|| {
+            Box::new(Status::invalid_argument(
+                "Pareto request has no second objective",
+            ))
+        })?;
+        let second = mappers::proto_to_domain_objective(second)?;
+        let points = request.points as usize;
+        info!(
+            problem = %problem.name,
+            points,
+            "Received Pareto frontier request"
+        );
+
+        // Not recorded: a frontier is no single solution of the problem to replay
+        let started_at = Instant::now();
+        let (frontier, solver_name) = self
+            .solver_job(
+                problem,
+                caller,
+                move |solver, problem, cancel| {
+                    let pareto = ParetoSolver::new(solver);
+                    let pareto = if points > 0 {
+                        pareto.with_points(points)
+                    } else {
+                        pareto
+                    };
+                    pareto.solve_cancellable(problem, &second, cancel)
+                },
+                |frontier: &ParetoFrontier| frontier.status,
+            )
+            .await?;
+        info!(
+            points = frontier.points.len(),
+            solves = frontier.solves,
+            status = %frontier.status,
+            solve_ms = started_at.elapsed().as_secs_f64() * 1000.0,
+            "Pareto frontier finished"
+        );
+        Ok(Response::new(mappers::domain_to_proto_pareto_frontier(
+            frontier,
+            &solver_name,
+            request.include_solutions,
+        )))
+    }
+
+    /// Solve the scenarios of a problem as one tracked job, returning their
+    /// outcomes and the solver's name
+    ///
+    /// The scenarios share a solve slot and one solver, which patches the model it
+    /// built between scenarios where the backend allows.
+    async fn solve_scenarios_job(
+        &self,
+        problem: OptimizationProblem,
+        scenarios: Vec<Scenario>,
+        caller: &Caller,
+    ) -> Result<(Vec<SolverResult<Solution>>, String), Box<Status>> {
+        let started_at = Instant::now();
+        let tags = problem.tags.clone();
+        let recorded = self
+            .recorder
+            .is_some()
+            .then(|| (problem.clone(), scenarios.clone()));
+        let (mut outcomes, solver_name) = self
+            .solver_job(
+                problem,
+                caller,
+                move |solver, problem, cancel| solver.solve_scenarios(problem, &scenarios, cancel),
+                |outcomes: &Vec<SolverResult<Solution>>| scenarios_status(outcomes),
+            )
+            .await?;
+
+        let failed = outcomes.iter().filter(|outcome| outcome.is_err()).count();
+        info!(
+            scenarios = outcomes.len(),
+            failed,
+            solve_ms = started_at.elapsed().as_secs_f64() * 1000.0,
+            "Scenarios finished"
+        );
+        for solution in outcomes.iter_mut().flatten() {
+            solution.tags = tags.clone();
+        }
+        // Each scenario is recorded as the problem it became, so it replays alone
+        if let Some((problem, scenarios)) = recorded {
+            for (scenario, outcome) in scenarios.iter().zip(&outcomes) {
+                if let (Ok(solution), Ok(problem)) = (outcome, scenario.apply(&problem)) {
+                    let result = mappers::domain_to_proto_solution(solution.clone(), &solver_name);
+                    self.record_solve(&problem, &result, caller);
+                }
+            }
+        }
+        Ok((outcomes, solver_name))
+    }
+
+    /// Run `work` with the problem's solver as one tracked job, returning its
+    /// output and the solver's name
+    ///
+    /// The problem is prepared and queued like a single solve; `work` runs on a
+    /// blocking thread and may solve it as many times as it needs. The job goes to
+    /// the audit log, the result publishers and the server stats as one solve,
+    /// with the status `status_of` reads from its output.
+    async fn solver_job<T, F>(
+        &self,
+        mut problem: OptimizationProblem,
+        caller: &Caller,
+        work: F,
+        status_of: impl FnOnce(&T) -> SolutionStatus,
+    ) -> Result<(T, String), Box<Status>>
+    where
+        T: Send + 'static,
+        F: FnOnce(
+                Arc<dyn SolverService>,
+                &OptimizationProblem,
+                &CancellationToken,
+            ) -> SolverResult<T>
+            + Send
+            + 'static,
+    {
+        let prepared = self.prepare_problem(&mut problem, caller);
+        let record = self
+            .audit
+            .is_some()
+            .then(|| AuditRecord::new(caller, &problem));
+        let event = (!self.publishers.is_empty()).then(|| SolveEvent::new(caller, &problem));
+
+        let mut output = None;
+        let run = async {
+            let injected_solver = prepared?;
+            let job = self.jobs.register(&problem);
+            let cancel = job.cancellation();
+            let _cancel_on_drop = cancel.clone().cancel_on_drop();
+            let _permit = self.solve_slot(&job, &mut problem.solver_config).await?;
+
+            let solver = self.solver_for(&problem, injected_solver, None);
+            job.mark_running(solver.name());
+            let solver_name = solver.name().to_string();
+            let started_at = Instant::now();
+            let worker_cancel = cancel.clone();
+            let span = Span::current();
+            let outcome = tokio::task::spawn_blocking(move || {
+                span.in_scope(|| work(solver, &problem, &worker_cancel))
+            })
+            .await
+            .map_err(|e| Box::new(Status::internal(format!("Solver task failed: {}", e))))
+            .and_then(|done| done.map_err(|e| preemption(&job, solver_failure(e, &cancel))));
+            let solve_ms = started_at.elapsed().as_secs_f64() * 1000.0;
+            let value = match outcome {
+                Ok(value) => value,
+                Err(status) => {
+                    let outcome = match status.code() {
+                        Code::Aborted => "PREEMPTED",
+                        _ => "FAILED",
+                    };
+                    self.stats.record_solve(outcome, &solver_name, solve_ms);
+                    return Err(status);
+                }
+            };
+
+            let status = mappers::solution_status_to_proto(status_of(&value));
+            self.stats
+                .record_solve(status.as_str_name(), &solver_name, solve_ms);
+            let result = job_result(status, &solver_name, solve_ms);
+            output = Some((value, solver_name));
+            Ok(result)
+        };
+        let outcome = self.audited(record, run).await;
+        if let (Some(event), Ok(result)) = (event, &outcome) {
+            self.publish(event, result);
+        }
+        outcome.map(|_| output.expect("a finished job leaves its output"))
+    }
+
+    /// CreateModelSession: build the problem on its solver and keep it
+    async fn handle_create_session(
+        &self,
+        proto_problem: lp_solver::OptimizationProblem,
+        caller: &Caller,
+    ) -> Result<lp_solver::ModelSessionInfo, Box<Status>> {
+        let mut problem = mappers::proto_to_domain_problem(proto_problem)?;
+        let injected_solver = self.prepare_problem(&mut problem, caller)?;
+
+        let (solver, editable) = self.session_solver(&problem, injected_solver);
+        let backend = solver.name().to_string();
+        let span = Span::current();
+        let session = tokio::task::spawn_blocking(move || {
+            span.in_scope(|| {
+                let session = match editable {
+                    true => solver.open_session(&problem)?,
+                    false => None,
+                };
+                Ok::<_, SolverError>(match session {
+                    Some(session) => session,
+                    None => Box::new(RebuildingSession::new(solver, problem)),
+                })
+            })
+        })
+        .await
+        .map_err(|e| Box::new(Status::internal(format!("Session task failed: {}", e))))?
+        .map_err(solver_error_status)?;
+
+        let info = mappers::session_to_proto("", &backend, session.as_ref());
+        let session_id = self.sessions.insert(session, &backend)?;
+        info!(
+            session_id,
+            backend,
+            incremental = info.incremental,
+            variables = info.num_variables,
+            constraints = info.num_constraints,
+            "Opened model session"
+        );
+        Ok(lp_solver::ModelSessionInfo { session_id, ..info })
+    }
+
+    /// Solver for a new session, and whether it may keep the model built
+    ///
+    /// Keeping it needs the plain solver in this process: presolve, scaling,
+    /// isolation and racing all start from the problem on every solve.
+    fn session_solver(
+        &self,
+        problem: &OptimizationProblem,
+        injected_solver: Option<Arc<dyn SolverService>>,
+    ) -> (Arc<dyn SolverService>, bool) {
+        let config = &problem.solver_config;
+        let editable = !config.presolve
+            && !config.auto_scale
+            && config.backend != SolverBackend::Race
+            && (injected_solver.is_some() || self.isolation_for(problem).is_none());
+        if !editable {
+            return (self.solver_for(problem, injected_solver, None), false);
+        }
+
+        // Specialized solvers only fit the structure the problem starts with
+        let solver = injected_solver.unwrap_or_else(|| match config.backend {
+            SolverBackend::Auto => AutoSolver::new(self.solvers.backends())
+                .select_backend(problem)
+                .map_or_else(|_| self.backend_solver(problem), |(solver, _)| solver),
+            _ => self.backend_solver(problem),
+        });
+        (solver, true)
+    }
+
+    /// UpdateModel: apply the edits to a session's model
+    async fn handle_update_model(
+        &self,
+        request: lp_solver::ModelUpdateRequest,
+    ) -> Result<lp_solver::ModelSessionInfo, Box<Status>> {
+        let session_id = request.session_id.clone();
+        let update = mappers::proto_to_domain_model_update(request)?;
+        let (session, backend) = self.sessions.get(&session_id)?;
+        let mut session = session.lock().await;
+
+        self.size_limits
+            .check_growth(session.problem(), &update.constraints)?;
+        session.update(&update).map_err(invalid_problem)?;
+        debug!(
+            session_id,
+            new_constraints = update.constraints.len(),
+            "Updated model session"
+        );
+        Ok(mappers::session_to_proto(
+            &session_id,
+            &backend,
+            session.as_ref(),
+        ))
+    }
+
+    /// ReSolve: solve a session's model as a tracked job
+    ///
+    /// The session stays locked for the solve, so updates sent meanwhile wait
+    /// for it to finish. Variables in `fixed_values` are fixed for this solve only.
+    async fn handle_resolve(
+        &self,
+        session_id: &str,
+        fixed_values: BTreeMap<String, f64>,
+        caller: &Caller,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let (session, backend) = self.sessions.get(session_id)?;
+        let session = session.lock_owned().await;
+        Scenario::fixing(session.problem(), &fixed_values).map_err(invalid_problem)?;
+        let record = self
+            .audit
+            .is_some()
+            .then(|| AuditRecord::new(caller, session.problem()));
+        let job = self.jobs.register(session.problem());
+        let span = info_span!(
+            "solve",
+            job_id = job.id(),
+            session_id,
+            problem = %session.problem().name,
+            variables = session.problem().num_variables(),
+            constraints = session.problem().constraints.len(),
+            backend = %backend,
+        );
+
+        self.audited(
+            record,
+            self.run_session_job(job, session, backend, fixed_values),
+        )
+        .instrument(span)
+        .await
+    }
+
+    /// Wait for a solve slot, then solve a locked session for a registered job
+    async fn run_session_job(
+        &self,
+        job: JobHandle,
+        mut session: tokio::sync::OwnedMutexGuard<Box<dyn ModelSession>>,
+        backend: String,
+        fixed_values: BTreeMap<String, f64>,
+    ) -> Result<lp_solver::OptimizationResult, Box<Status>> {
+        let cancel = job.cancellation();
+        let _cancel_on_drop = cancel.clone().cancel_on_drop();
+
+        let submitted_at = Instant::now();
+        // The session's backend keeps its own options, so only the queue order
+        // and the deadline check apply here
+        let mut config = session.problem().solver_config.clone();
+        let _permit = self.solve_slot(&job, &mut config).await?;
+        let queued_ms = submitted_at.elapsed().as_secs_f64() * 1000.0;
+        job.mark_running(&backend);
+
+        let started_at = Instant::now();
+        let progress = job.progress();
+        let worker_cancel = cancel.clone();
+        let span = Span::current();
+        let (session, outcome) = tokio::task::spawn_blocking(move || {
+            let outcome = span.in_scope(|| {
+                measured(|| session.solve_fixed(&fixed_values, &progress, &worker_cancel))
+            });
+            (session, outcome)
+        })
+        .await
+        .map_err(|e| Box::new(Status::internal(format!("Solver task failed: {}", e))))?;
+        let solution = outcome.map_err(|e| preemption(&job, solver_failure(e, &cancel)))?;
+
+        info!(
+            status = %solution.status,
+            objective = solution.optimal_value,
+            queued_ms,
+            solve_ms = started_at.elapsed().as_secs_f64() * 1000.0,
+            "Solve finished"
+        );
+        Ok(solution_result(session.problem(), solution, &backend))
+    }
+
+    /// CloseModelSession: forget a session, once any request on it is done
+    async fn handle_close_session(
+        &self,
+        session_id: &str,
+    ) -> Result<lp_solver::ModelSessionInfo, Box<Status>> {
+        let (session, backend) = self.sessions.remove(session_id)?;
+        let session = session.lock().await;
+        info!(session_id, "Closed model session");
+        Ok(mappers::session_to_proto(
+            session_id,
+            &backend,
+            session.as_ref(),
+        ))
+    }
+}
+
+/// Time one benchmark run, giving up once `time_limit` has passed
+///
+/// A run that is out of time is cancelled. CBC cannot be interrupted, so an
+/// in-process CBC run keeps going in the background; isolated solves are killed.
+async fn benchmark_run(
+    key: String,
+    solver: Arc<dyn SolverService>,
+    problem: OptimizationProblem,
+    time_limit: Option<Duration>,
+) -> lp_solver::BenchmarkRun {
+    let solver_name = solver.name().to_string();
+    let started_at = Instant::now();
+    let cancel = CancellationToken::new();
+    let _cancel_on_drop = cancel.clone().cancel_on_drop();
+    let span = Span::current();
+    let task = tokio::task::spawn_blocking(move || {
+        span.in_scope(|| solver.solve_cancellable(&problem, &NullObserver, &cancel))
+    });
+    let outcome = match time_limit {
+        Some(limit) => tokio::time::timeout(limit, task).await.ok(),
+        None => Some(task.await),
+    };
+    let elapsed = started_at.elapsed();
+    let out_of_time = time_limit.is_some_and(|limit| elapsed >= limit);
+
+    let (status, objective, error) = match outcome {
+        Some(Ok(Ok(solution))) => (
+            mappers::solution_status_to_proto(solution.status),
+            solution.optimal_value,
+            String::new(),
+        ),
+        Some(Ok(Err(_))) | None if out_of_time => {
+            (lp_solver::SolutionStatus::TimeLimit, None, String::new())
+        }
+        Some(Ok(Err(e))) => (lp_solver::SolutionStatus::Error, None, e.to_string()),
+        Some(Err(e)) => (
+            lp_solver::SolutionStatus::Error,
+            None,
+            format!("Solver task failed: {}", e),
+        ),
+        None => (lp_solver::SolutionStatus::TimeLimit, None, String::new()),
+    };
+
+    let solve_time_ms = elapsed.as_secs_f64() * 1000.0;
+    info!(
+        solver = %solver_name,
+        status = status.as_str_name(),
+        objective,
+        solve_ms = solve_time_ms,
+        "Benchmark run finished"
+    );
+
+    lp_solver::BenchmarkRun {
+        key,
+        solver: solver_name,
+        status: status as i32,
+        objective,
+        solve_time_ms,
+        error,
+    }
+}
+
+/// Run a solve on this thread, recording its resource usage unless the solver did
+fn measured(solve: impl FnOnce() -> SolverResult<Solution>) -> SolverResult<Solution> {
+    let meter = ResourceMeter::start();
+    let mut solution = solve()?;
+    solution
+        .statistics
+        .resources
+        .get_or_insert_with(|| meter.finish());
+    Ok(solution)
+}
+
+/// CPU time a solve reports, in seconds
+fn result_cpu_secs(result: &lp_solver::OptimizationResult) -> Option<f64> {
+    let resources = result.statistics.as_ref()?.resources.as_ref()?;
+    Some(resources.cpu_time_ms / 1000.0)
+}
+
+/// ABORTED instead of `status` for a job stopped to make room for a
+/// higher-priority solve
+fn preemption(job: &JobHandle, status: Box<Status>) -> Box<Status> {
+    if !job.was_preempted() {
+        return status;
+    }
+    Box::new(Status::aborted(
+        "Preempted by a higher-priority solve; retry later",
+    ))
+}
+
+/// Status for a request naming a solver an administrator took out of rotation
+fn disabled_solver(name: &str) -> Box<Status> {
+    Box::new(Status::failed_precondition(format!(
+        "Solver '{}' is disabled; choose another solver or AUTO",
+        name
+    )))
+}
+
+/// Status for a failed solve: CANCELLED if `cancel` stopped it
+fn solver_failure(error: SolverError, cancel: &CancellationToken) -> Box<Status> {
+    if cancel.is_cancelled() || matches!(error, SolverError::Cancelled) {
+        info!("Solve cancelled");
+        return solver_error_status(SolverError::Cancelled);
+    }
+    warn!(error = %error, "Solve failed");
+    solver_error_status(error)
+}
+
+/// INVALID_ARGUMENT for a problem or request the domain rejected
+fn invalid_problem(error: SolverError) -> Box<Status> {
+    ErrorDetail::with_code(ErrorCode::InvalidProblem).invalid_argument(error.to_string())
+}
+
+/// INVALID_ARGUMENT for a request without the message `field`
+fn missing_field(field: &str, message: &str) -> Box<Status> {
+    ErrorDetail::with_code(ErrorCode::MissingField)
+        .with_entity(field, None)
+        .invalid_argument(message)
+}
+
+/// Status for a solver error, with an error detail naming its kind
+fn solver_error_status(error: SolverError) -> Box<Status> {
+    let (code, kind, message) = match &error {
+        SolverError::InvalidProblem(_) => (
+            Code::InvalidArgument,
+            ErrorCode::InvalidProblem,
+            error.to_string(),
+        ),
+        SolverError::SolverNotAvailable(_) => (
+            Code::FailedPrecondition,
+            ErrorCode::SolverNotAvailable,
+            error.to_string(),
+        ),
+        SolverError::ExecutionFailed(_) => (
+            Code::Internal,
+            ErrorCode::SolverFailed,
+            format!("Solver error: {}", error),
+        ),
+        SolverError::Timeout(_) => (
+            Code::DeadlineExceeded,
+            ErrorCode::Timeout,
+            error.to_string(),
+        ),
+        // Retrying the same model won't help, so not INTERNAL
+        SolverError::NumericalDifficulty(_) => (
+            Code::FailedPrecondition,
+            ErrorCode::NumericalDifficulty,
+            error.to_string(),
+        ),
+        SolverError::MemoryLimit(_) => (
+            Code::ResourceExhausted,
+            ErrorCode::MemoryLimit,
+            error.to_string(),
+        ),
+        SolverError::Cancelled => (Code::Cancelled, ErrorCode::Cancelled, error.to_string()),
+        SolverError::UnsupportedFeature { feature } => {
+            return ErrorDetail::with_code(ErrorCode::UnsupportedFeature)
+                .with_entity("feature", None)
+                .with_name(feature)
+                .into_status(Code::Unimplemented, error.to_string())
+        }
+    };
+    ErrorDetail::with_code(kind).into_status(code, message)
+}
+
+/// Protobuf result of a solve of `problem`, with the extras its config asks for
+/// Result standing for a job of many solves in audit records, events and stats
+fn job_result(
+    status: lp_solver::SolutionStatus,
+    solver_name: &str,
+    solve_ms: f64,
+) -> lp_solver::OptimizationResult {
+    lp_solver::OptimizationResult {
+        status: status as i32,
+        statistics: Some(lp_solver::SolverStatistics {
+            solve_time_ms: solve_ms,
+            solver_backend: solver_name.to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Optimal when every scenario was; otherwise the status of the first that
+/// wasn't, ERROR for one that failed
+fn scenarios_status(outcomes: &[SolverResult<Solution>]) -> SolutionStatus {
+    outcomes
+        .iter()
+        .map(|outcome| outcome.as_ref().map_or(SolutionStatus::Error, |s| s.status))
+        .find(|&status| status != SolutionStatus::Optimal)
+        .unwrap_or(SolutionStatus::Optimal)
+}
+
+fn solution_result(
+    problem: &OptimizationProblem,
+    mut solution: Solution,
+    solver_name: &str,
+) -> lp_solver::OptimizationResult {
+    if problem.solver_config.relax_integrality {
+        solution.message = format!("LP relaxation: {}", solution.message);
+    }
+    // Named here rather than by the backend, after wrappers have mapped them back
+    if let Some(ray) = &solution.unbounded_ray {
+        solution.message = format!("{}, along {}", solution.message, ray.describe());
+    }
+    if let Some(certificate) = &solution.infeasibility_certificate {
+        solution.message = format!(
+            "{}; {} contradict each other",
+            solution.message,
+            certificate.describe()
+        );
+    }
+    solution.quality = assess_quality(problem, &solution);
+    let explanation = problem
+        .solver_config
+        .explain
+        .then(|| explain_solution(problem, &solution))
+        .flatten();
+
+    let export = problem
+        .solver_config
+        .export
+        .map(|format| export_solution(problem, &solution, format));
+    let by_name = problem.solver_config.values_by_name.then(|| {
+        (
+            solution.values_by_name(problem),
+            solution.duals_by_name(problem),
+            solution.slacks_by_name(problem),
+            solution.reduced_costs_by_name(problem),
+        )
+    });
+
+    // Convert domain solution to protobuf
+    let mut result = mappers::domain_to_proto_solution(solution, solver_name);
+    result.explanation = explanation.map(mappers::explanation_to_proto);
+    result.export = export.unwrap_or_default();
+    if let Some((values, duals, slacks, reduced_costs)) = by_name {
+        result.solution_by_name = values;
+        result.dual_by_name = duals;
+        result.slack_by_name = slacks;
+        result.reduced_cost_by_name = reduced_costs;
+    }
+    result
+}
+
+impl Default for GrpcLpSolverService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A streamed constraint, kept as sent until the objective fixes the row width
+enum StreamedConstraint {
+    Dense(lp_solver::Constraint),
+    Sparse(lp_solver::SparseConstraint),
+}
+
+impl StreamedConstraint {
+    fn into_domain(
+        self,
+        num_vars: Option<usize>,
+        names: &HashMap<&str, usize>,
+    ) -> Result<Constraint, Box<Status>> {
+        match self {
+            StreamedConstraint::Dense(c) => mappers::proto_to_domain_constraint(c),
+            // Without the objective there is no width to hold the indices to, and
+            // sizing the row from them would let one chunk claim gigabytes
+            StreamedConstraint::Sparse(c) => match num_vars {
+                Some(num_vars) => mappers::proto_to_domain_sparse_constraint(c, num_vars, names),
+                None => Err(ErrorDetail::with_code(ErrorCode::MissingField)
+                    .with_entity("constraint", None)
+                    .with_name(&c.name)
+                    .invalid_argument(format!(
+                        "Sparse constraint '{}' can't be checked without an objective",
+                        c.name
+                    ))),
+            },
+        }
+    }
+}
+
+/// gRPC service implementation
+#[tonic::async_trait]
+impl lp_solver::linear_programming_solver_server::LinearProgrammingSolver for GrpcLpSolverService {
+    async fn solve_problem(
+        &self,
+        request: Request<lp_solver::OptimizationProblem>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "SolveProblem", request_id = %caller.request_id);
+
+        let solve = self.handle_solve(request, &caller).instrument(span);
+        self.metered(&caller, solve, result_cpu_secs)
+            .await
+            .map(|response| caller.request_id.tag_result(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn solve_problem_stream(
+        &self,
+        request: Request<tonic::Streaming<lp_solver::ProblemChunk>>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span =
+            info_span!("rpc", method = "SolveProblemStream", request_id = %caller.request_id);
+
+        let solve = self.handle_solve_stream(request, &caller).instrument(span);
+        self.metered(&caller, solve, result_cpu_secs)
+            .await
+            .map(|response| caller.request_id.tag_result(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn solve_from_url(
+        &self,
+        request: Request<lp_solver::UrlProblemRequest>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "SolveFromUrl", request_id = %caller.request_id);
+
+        let solve = async {
+            self.handle_solve_from_url(request.into_inner(), &caller)
+                .await
+                .map(Response::new)
+                .map_err(|e| *e)
+        };
+        self.metered(&caller, solve.instrument(span), result_cpu_secs)
+            .await
+            .map(|response| caller.request_id.tag_result(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn submit_problem(
+        &self,
+        request: Request<lp_solver::SubmitRequest>,
+    ) -> Result<Response<lp_solver::SubmittedSolve>, Status> {
+        let caller = Caller::from_request(&request);
+        let _span =
+            info_span!("rpc", method = "SubmitProblem", request_id = %caller.request_id).entered();
+        let request_id = caller.request_id.clone();
+        self.handle_submit(request.into_inner(), caller)
+            .map(|submitted| request_id.tag_response(Response::new(submitted)))
+            .map_err(|status| request_id.tag_status(*status))
+    }
+
+    async fn get_result(
+        &self,
+        request: Request<lp_solver::ResultQuery>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let result_id = request.into_inner().result_id;
+        match self.submissions.get(&result_id) {
+            Some(Submission::Finished(outcome)) => (*outcome).map(Response::new),
+            Some(Submission::Running) => Err(Status::failed_precondition(format!(
+                "Submitted solve '{}' is still running",
+                result_id
+            ))),
+            None => Err(Status::not_found(format!(
+                "No submitted solve '{}', or its result has expired",
+                result_id
+            ))),
+        }
+    }
+
+    type SolveProblemChunkedStream =
+        tokio_stream::Iter<std::vec::IntoIter<Result<lp_solver::SolutionChunk, Status>>>;
+
+    async fn solve_problem_chunked(
+        &self,
+        request: Request<lp_solver::OptimizationProblem>,
+    ) -> Result<Response<Self::SolveProblemChunkedStream>, Status> {
+        let caller = Caller::from_request(&request);
+        let span =
+            info_span!("rpc", method = "SolveProblemChunked", request_id = %caller.request_id);
+
+        let solve = self.handle_solve(request, &caller).instrument(span);
+        let response = self
+            .metered(&caller, solve, result_cpu_secs)
+            .await
+            .map(|response| caller.request_id.tag_result(response))
+            .map_err(|status| caller.request_id.tag_status(status))?;
+
+        // Metadata such as the request ID and replay marker carries over to the stream
+        let (metadata, result, extensions) = response.into_parts();
+        let chunks: Vec<_> = mappers::result_to_chunks(result, SOLUTION_SLICE_LEN)
+            .into_iter()
+            .map(Ok)
+            .collect();
+        Ok(Response::from_parts(
+            metadata,
+            tokio_stream::iter(chunks),
+            extensions,
+        ))
+    }
+
+    async fn get_available_solvers(
+        &self,
+        _request: Request<lp_solver::Empty>,
+    ) -> Result<Response<lp_solver::AvailableSolvers>, Status> {
+        let solvers = self
+            .solvers
+            .infos()
+            .map(mappers::solver_info_to_proto)
+            .collect();
+
+        Ok(Response::new(lp_solver::AvailableSolvers { solvers }))
+    }
+
+    async fn get_solver_options(
+        &self,
+        _request: Request<lp_solver::Empty>,
+    ) -> Result<Response<lp_solver::SolverOptionsList>, Status> {
+        let solvers = self
+            .solvers
+            .infos()
+            .map(mappers::solver_options_to_proto)
+            .collect();
+
+        Ok(Response::new(lp_solver::SolverOptionsList { solvers }))
+    }
+
+    async fn validate_problem(
+        &self,
+        request: Request<lp_solver::OptimizationProblem>,
+    ) -> Result<Response<lp_solver::ValidationResult>, Status> {
+        let proto_problem = request.into_inner();
+        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+
+        Ok(Response::new(self.validate_domain_problem(domain_problem)))
+    }
+
+    async fn validate_problem_stream(
+        &self,
+        request: Request<tonic::Streaming<lp_solver::ProblemChunk>>,
+    ) -> Result<Response<lp_solver::ValidationResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span =
+            info_span!("rpc", method = "ValidateProblemStream", request_id = %caller.request_id);
+
+        self.handle_validate_stream(request.into_inner())
+            .instrument(span)
+            .await
+            .map(|response| caller.request_id.tag_response(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn create_model_session(
+        &self,
+        request: Request<lp_solver::OptimizationProblem>,
+    ) -> Result<Response<lp_solver::ModelSessionInfo>, Status> {
+        let caller = Caller::from_request(&request);
+        let span =
+            info_span!("rpc", method = "CreateModelSession", request_id = %caller.request_id);
+
+        self.handle_create_session(request.into_inner(), &caller)
+            .instrument(span)
+            .await
+            .map(|info| caller.request_id.tag_response(Response::new(info)))
+            .map_err(|status| caller.request_id.tag_status(*status))
+    }
+
+    async fn update_model(
+        &self,
+        request: Request<lp_solver::ModelUpdateRequest>,
+    ) -> Result<Response<lp_solver::ModelSessionInfo>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "UpdateModel", request_id = %caller.request_id);
+
+        self.handle_update_model(request.into_inner())
+            .instrument(span)
+            .await
+            .map(|info| caller.request_id.tag_response(Response::new(info)))
+            .map_err(|status| caller.request_id.tag_status(*status))
+    }
+
+    async fn re_solve(
+        &self,
+        request: Request<lp_solver::SessionQuery>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "ReSolve", request_id = %caller.request_id);
+        let query = request.into_inner();
+        let fixed_values = query.fixed_values.into_iter().collect();
+
+        let solve = async {
+            self.handle_resolve(&query.session_id, fixed_values, &caller)
+                .await
+                .map(Response::new)
+                .map_err(|e| *e)
+        };
+        self.metered(&caller, solve.instrument(span), result_cpu_secs)
+            .await
+            .map(|response| caller.request_id.tag_result(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn close_model_session(
+        &self,
+        request: Request<lp_solver::SessionQuery>,
+    ) -> Result<Response<lp_solver::ModelSessionInfo>, Status> {
+        let session_id = request.into_inner().session_id;
+        let info = self
+            .handle_close_session(&session_id)
+            .await
+            .map_err(|e| *e)?;
+        Ok(Response::new(info))
+    }
+
+    async fn benchmark_problem(
+        &self,
+        request: Request<lp_solver::BenchmarkRequest>,
+    ) -> Result<Response<lp_solver::BenchmarkResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "BenchmarkProblem", request_id = %caller.request_id);
+
+        let solve = async {
+            self.handle_benchmark(request, &caller)
+                .await
+                .map_err(|e| *e)
+        };
+        self.metered(&caller, solve.instrument(span), |_| None)
+            .await
+            .map(|response| caller.request_id.tag_response(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn solve_scenarios(
+        &self,
+        request: Request<lp_solver::ScenarioRequest>,
+    ) -> Result<Response<lp_solver::ScenarioResults>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "SolveScenarios", request_id = %caller.request_id);
+
+        let solve = async {
+            self.handle_solve_scenarios(request, &caller)
+                .await
+                .map_err(|e| *e)
+        };
+        self.metered(&caller, solve.instrument(span), |_| None)
+            .await
+            .map(|response| caller.request_id.tag_response(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn sweep_parameter(
+        &self,
+        request: Request<lp_solver::SweepRequest>,
+    ) -> Result<Response<lp_solver::SweepResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!("rpc", method = "SweepParameter", request_id = %caller.request_id);
+
+        let solve = async { self.handle_sweep(request, &caller).await.map_err(|e| *e) };
+        self.metered(&caller, solve.instrument(span), |_| None)
+            .await
+            .map(|response| caller.request_id.tag_response(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn compute_pareto_frontier(
+        &self,
+        request: Request<lp_solver::ParetoRequest>,
+    ) -> Result<Response<lp_solver::ParetoFrontier>, Status> {
+        let caller = Caller::from_request(&request);
+        let span = info_span!(
+            "rpc",
+            method = "ComputeParetoFrontier",
+            request_id = %caller.request_id
+        );
+
+        let solve = async { self.handle_pareto(request, &caller).await.map_err(|e| *e) };
+        self.metered(&caller, solve.instrument(span), |_| None)
+            .await
+            .map(|response| caller.request_id.tag_response(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn verify_solution(
+        &self,
+        request: Request<lp_solver::VerifyRequest>,
+    ) -> Result<Response<lp_solver::VerificationResult>, Status> {
+        let request = request.into_inner();
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| *missing_field("problem", "Verify request has no problem"))?;
+        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+        self.size_limits.check(&domain_problem).map_err(|e| *e)?;
+
+        let tolerance = if request.tolerance > 0.0 {
+            request.tolerance
+        } else {
+            DEFAULT_FEASIBILITY_TOLERANCE
+        };
+        let check = verify_solution(&domain_problem, &request.values, tolerance)
+            .map_err(|e| *invalid_problem(e))?;
+
+        Ok(Response::new(mappers::solution_check_to_proto(check)))
+    }
+
+    async fn diff_solutions(
+        &self,
+        request: Request<lp_solver::DiffRequest>,
+    ) -> Result<Response<lp_solver::SolutionDiff>, Status> {
+        let request = request.into_inner();
+        let before = request
+            .before
+            .ok_or_else(|| *missing_field("before", "Diff request has no before solution"))?;
+        let after = request
+            .after
+            .ok_or_else(|| *missing_field("after", "Diff request has no after solution"))?;
+        let before = mappers::proto_to_domain_solution(before).map_err(|e| *e)?;
+        let after = mappers::proto_to_domain_solution(after).map_err(|e| *e)?;
+        let threshold = if request.threshold > 0.0 {
+            request.threshold
+        } else {
+            DEFAULT_DIFF_THRESHOLD
+        };
+
+        let mut diff = before
+            .diff_above(&after, threshold)
+            .map_err(|e| *invalid_problem(e))?;
+        if let Some(proto_problem) = request.problem {
+            let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+            self.size_limits.check(&domain_problem).map_err(|e| *e)?;
+            if domain_problem.num_variables() != before.variable_values.len() {
+                return Err(Status::invalid_argument(format!(
+                    "Solutions have {} values for a problem of {} variables",
+                    before.variable_values.len(),
+                    domain_problem.num_variables()
+                )));
+            }
+            diff = diff.with_names(&domain_problem);
+        }
+
+        Ok(Response::new(mappers::solution_diff_to_proto(diff)))
+    }
+
+    async fn get_model_statistics(
+        &self,
+        request: Request<lp_solver::OptimizationProblem>,
+    ) -> Result<Response<lp_solver::ModelStatistics>, Status> {
+        let domain_problem =
+            mappers::proto_to_domain_problem(request.into_inner()).map_err(|e| *e)?;
+        self.size_limits.check(&domain_problem).map_err(|e| *e)?;
+
+        let stats = ModelStatistics::analyze(&domain_problem);
+        Ok(Response::new(mappers::model_statistics_to_proto(stats)))
+    }
+
+    async fn export_anonymized(
+        &self,
+        request: Request<lp_solver::AnonymizeRequest>,
+    ) -> Result<Response<lp_solver::AnonymizedModel>, Status> {
+        let request = request.into_inner();
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| *missing_field("problem", "Anonymize request has no problem"))?;
+        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+        self.size_limits.check(&domain_problem).map_err(|e| *e)?;
+        if !(0.0..=MAX_PERTURBATION).contains(&request.perturbation) {
+            return Err(Status::invalid_argument(format!(
+                "Perturbation must be between 0 and {}, got {}",
+                MAX_PERTURBATION, request.perturbation
+            )));
+        }
+
+        let anonymized = domain_problem.anonymized(request.perturbation, request.seed);
+        Ok(Response::new(lp_solver::AnonymizedModel {
+            mps: anonymized.to_mps(),
+        }))
+    }
+
+    async fn save_problem(
+        &self,
+        request: Request<lp_solver::SaveProblemRequest>,
+    ) -> Result<Response<lp_solver::StoredProblem>, Status> {
+        let repository = self.problem_repository().map_err(|e| *e)?;
+        let request = request.into_inner();
+        if !is_valid_problem_id(&request.id) {
+            return Err(Status::invalid_argument(format!(
+                "'{}' is not a valid problem ID: use up to {} letters, digits, '-', '_' and '.', not starting with '.'",
+                request.id, MAX_PROBLEM_ID_LEN
+            )));
+        }
+        let proto_problem = request
+            .problem
+            .ok_or_else(|| *missing_field("problem", "Save request has no problem"))?;
+        let domain_problem = mappers::proto_to_domain_problem(proto_problem).map_err(|e| *e)?;
+        self.size_limits.check(&domain_problem).map_err(|e| *e)?;
+        validate_problem(&domain_problem).map_err(|e| *invalid_problem(e))?;
+
+        let info = repository.save(&request.id, &domain_problem).map_err(|e| {
+            Status::internal(format!("Cannot save problem '{}': {}", request.id, e))
+        })?;
+        info!(id = %info.id, variables = info.num_variables, "Saved problem");
+        Ok(Response::new(mappers::stored_problem_to_proto(info)))
+    }
+
+    async fn get_problem(
+        &self,
+        request: Request<lp_solver::StoredProblemQuery>,
+    ) -> Result<Response<lp_solver::OptimizationProblem>, Status> {
+        let problem = self
+            .load_stored_problem(&request.into_inner().id)
+            .map_err(|e| *e)?;
+        Ok(Response::new(mappers::domain_to_proto_problem(&problem)))
+    }
+
+    async fn list_problems(
+        &self,
+        request: Request<lp_solver::ProblemListQuery>,
+    ) -> Result<Response<lp_solver::StoredProblemList>, Status> {
+        let repository = self.problem_repository().map_err(|e| *e)?;
+        let filter = request.into_inner().tags.into_iter().collect();
+        let problems = repository
+            .list()
+            .map_err(|e| Status::internal(format!("Cannot list saved problems: {}", e)))?
+            .into_iter()
+            .filter(|info| tags_match(&info.tags, &filter))
+            .map(mappers::stored_problem_to_proto)
+            .collect();
+        Ok(Response::new(lp_solver::StoredProblemList { problems }))
+    }
+
+    async fn solve_stored_problem(
+        &self,
+        request: Request<lp_solver::StoredSolveRequest>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let caller = Caller::from_request(&request);
+        let span =
+            info_span!("rpc", method = "SolveStoredProblem", request_id = %caller.request_id);
+
+        let solve = async {
+            self.handle_solve_stored(request.into_inner(), &caller)
+                .await
+                .map(Response::new)
+                .map_err(|e| *e)
+        };
+        self.metered(&caller, solve.instrument(span), result_cpu_secs)
+            .await
+            .map(|response| caller.request_id.tag_result(response))
+            .map_err(|status| caller.request_id.tag_status(status))
+    }
+
+    async fn get_solve_history(
+        &self,
+        request: Request<lp_solver::SolveHistoryQuery>,
+    ) -> Result<Response<lp_solver::SolveHistory>, Status> {
+        let request = request.into_inner();
+        self.load_stored_problem(&request.id).map_err(|e| *e)?;
+        let repository = self.problem_repository().map_err(|e| *e)?;
+        let mut records = repository.history(&request.id).map_err(|e| {
+            Status::internal(format!("Cannot read history of '{}': {}", request.id, e))
+        })?;
+        if request.limit > 0 {
+            let keep = records.len().saturating_sub(request.limit as usize);
+            records.drain(..keep);
+        }
+        let solves = records
+            .into_iter()
+            .map(mappers::solve_record_to_proto)
+            .collect();
+        Ok(Response::new(lp_solver::SolveHistory { solves }))
+    }
+
+    async fn get_stored_solution(
+        &self,
+        request: Request<lp_solver::StoredSolutionQuery>,
+    ) -> Result<Response<lp_solver::OptimizationResult>, Status> {
+        let request = request.into_inner();
+        self.load_stored_problem(&request.id).map_err(|e| *e)?;
+        let repository = self.problem_repository().map_err(|e| *e)?;
+        let not_found = || {
+            Status::not_found(format!(
+                "No solve '{}' in the history of '{}'",
+                request.solve_id, request.id
+            ))
+        };
+        // Solve IDs name files, so they follow the same rules as problem IDs
+        if !is_valid_problem_id(&request.solve_id) {
+            return Err(not_found());
+        }
+        let solution = repository
+            .load_solution(&request.id, &request.solve_id)
+            .map_err(|e| {
+                Status::internal(format!(
+                    "Cannot load solve '{}' of '{}': {}",
+                    request.solve_id, request.id, e
+                ))
+            })?
+            .ok_or_else(not_found)?;
+        Ok(Response::new(mappers::domain_to_proto_solution(
+            solution, "",
+        )))
+    }
+
+    async fn list_jobs(
+        &self,
+        request: Request<lp_solver::JobListQuery>,
+    ) -> Result<Response<lp_solver::JobList>, Status> {
+        self.admin
+            .authorize(request.metadata(), "ListJobs")
+            .map_err(|e| *e)?;
+        let filter = request.into_inner().tags.into_iter().collect();
+        let jobs = self
+            .jobs
+            .list()
+            .iter()
+            .filter(|job| tags_match(&job.tags, &filter))
+            .map(mappers::job_to_proto)
+            .collect();
+
+        Ok(Response::new(lp_solver::JobList { jobs }))
+    }
+
+    async fn get_job(
+        &self,
+        request: Request<lp_solver::JobQuery>,
+    ) -> Result<Response<lp_solver::JobInfo>, Status> {
+        self.admin
+            .authorize(request.metadata(), "GetJob")
+            .map_err(|e| *e)?;
+        let job_id = request.into_inner().job_id;
+
+        let job = self
+            .jobs
+            .get(job_id)
+            .ok_or_else(|| Status::not_found(format!("No active job with id {}", job_id)))?;
+
+        Ok(Response::new(mappers::job_to_proto(&job)))
+    }
+
+    async fn cancel_solve(
+        &self,
+        request: Request<lp_solver::JobQuery>,
+    ) -> Result<Response<lp_solver::JobInfo>, Status> {
+        self.admin
+            .authorize(request.metadata(), "CancelSolve")
+            .map_err(|e| *e)?;
+        let job_id = request.into_inner().job_id;
+
+        let job = self
+            .jobs
+            .cancel(job_id)
+            .ok_or_else(|| Status::not_found(format!("No active job with id {}", job_id)))?;
+        info!(job_id, "Cancel requested");
+
+        Ok(Response::new(mappers::job_to_proto(&job)))
+    }
+
+    type StreamSolverLogsStream = BoxStream<'static, Result<lp_solver::SolverLogLine, Status>>;
+
+    async fn stream_solver_logs(
+        &self,
+        request: Request<lp_solver::JobQuery>,
+    ) -> Result<Response<Self::StreamSolverLogsStream>, Status> {
+        self.admin
+            .authorize(request.metadata(), "StreamSolverLogs")
+            .map_err(|e| *e)?;
+        let job_id = request.into_inner().job_id;
+
+        let (backlog, receiver) = self
+            .jobs
+            .subscribe_log(job_id)
+            .ok_or_else(|| Status::not_found(format!("No active job with id {}", job_id)))?;
+        let backlog = stream::iter(backlog)
+            .map(|line| mappers::log_line_to_proto(line, 0))
+            .map(Ok);
+        let live = stream::unfold(receiver, |mut receiver| async move {
+            let mut dropped_lines = 0;
+            loop {
+                match receiver.recv().await {
+                    Ok(line) => {
+                        let line = mappers::log_line_to_proto(line, dropped_lines);
+                        return Some((Ok(line), receiver));
+                    }
+                    Err(RecvError::Lagged(missed)) => dropped_lines += missed,
+                    // The job finished and took its log with it
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(Response::new(backlog.chain(live).boxed()))
+    }
+
+    async fn get_server_stats(
+        &self,
+        _request: Request<lp_solver::Empty>,
+    ) -> Result<Response<lp_solver::ServerStats>, Status> {
+        let jobs = self.jobs.list();
+        let count = |state| jobs.iter().filter(|job| job.state == state).count() as u32;
+
+        Ok(Response::new(mappers::server_stats_to_proto(
+            &self.stats.snapshot(),
+            count(JobState::Queued),
+            count(JobState::Running),
+        )))
+    }
+
+    async fn set_solver_enabled(
+        &self,
+        request: Request<lp_solver::SolverToggle>,
+    ) -> Result<Response<lp_solver::SolverStates>, Status> {
+        self.admin
+            .authorize(request.metadata(), "SetSolverEnabled")
+            .map_err(|e| *e)?;
+        let toggle = request.into_inner();
+
+        if !self.solvers.set_enabled(&toggle.solver, toggle.enabled) {
+            return Err(Status::not_found(format!(
+                "No solver named '{}'",
+                toggle.solver
+            )));
+        }
+        info!(
+            solver = toggle.solver,
+            enabled = toggle.enabled,
+            "Solver rotation changed"
+        );
+
+        let solvers = self
+            .solvers
+            .states()
+            .map(|(key, enabled)| lp_solver::SolverState {
+                key: key.to_string(),
+                enabled,
+            })
+            .collect();
+        Ok(Response::new(lp_solver::SolverStates { solvers }))
+    }
+
+    async fn register_worker(
+        &self,
+        request: Request<lp_solver::WorkerRegistration>,
+    ) -> Result<Response<lp_solver::WorkerLease>, Status> {
+        let workers = self.dispatcher().map_err(|e| *e)?;
+        workers.authorize(request.metadata()).map_err(|e| *e)?;
+        let registration = request.into_inner();
+        let capacity = (registration.capacity > 0).then_some(registration.capacity as usize);
+
+        let worker_id = workers
+            .register(&registration.address, capacity)
+            .map_err(|e| *e)?;
+        Ok(Response::new(lp_solver::WorkerLease {
+            worker_id,
+            heartbeat_interval_secs: workers.heartbeat_interval().as_secs_f64(),
+        }))
+    }
+
+    async fn worker_heartbeat(
+        &self,
+        request: Request<lp_solver::WorkerHeartbeatRequest>,
+    ) -> Result<Response<lp_solver::WorkerLease>, Status> {
+        let workers = self.dispatcher().map_err(|e| *e)?;
+        workers.authorize(request.metadata()).map_err(|e| *e)?;
+        let worker_id = request.into_inner().worker_id;
+
+        workers.heartbeat(&worker_id).map_err(|e| *e)?;
+        Ok(Response::new(lp_solver::WorkerLease {
+            worker_id,
+            heartbeat_interval_secs: workers.heartbeat_interval().as_secs_f64(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lp_solver::problem_chunk::Chunk;
+
+    fn chunks(
+        chunks: Vec<Chunk>,
+    ) -> impl futures::Stream<Item = Result<lp_solver::ProblemChunk, Status>> + Unpin {
+        stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| lp_solver::ProblemChunk { chunk: Some(chunk) })
+                .map(Ok),
+        )
+    }
+
+    /// Answers every problem with all variables at zero
+    struct ZeroSolver;
+
+    impl SolverService for ZeroSolver {
+        fn solve(&self, problem: &OptimizationProblem) -> SolverResult<Solution> {
+            Ok(Solution::optimal(0.0, vec![0.0; problem.num_variables()]))
+        }
+
+        fn name(&self) -> &str {
+            "Zero"
+        }
+
+        fn supports_mip(&self) -> bool {
+            true
+        }
+    }
+
+    #[derive(Default)]
+    struct CollectingPublisher(std::sync::Mutex<Vec<SolveEvent>>);
+
+    impl ResultPublisher for CollectingPublisher {
+        fn publish(&self, event: &SolveEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn scenario_jobs_are_published_and_counted_as_one_solve() {
+        let publisher = Arc::new(CollectingPublisher::default());
+        let service = GrpcLpSolverService::new()
+            .with_default_solver(Arc::new(ZeroSolver))
+            .with_publisher(publisher.clone());
+        let problem = OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Minimize,
+            vec![1.0],
+        ))
+        .with_name("base")
+        .with_variables(vec![Variable::continuous("x")]);
+        let scenarios = ["low", "high"].map(|name| Scenario {
+            name: name.to_string(),
+            ..Default::default()
+        });
+        let request = Request::new(lp_solver::ScenarioRequest {
+            problem: Some(mappers::domain_to_proto_problem(&problem)),
+            scenarios: scenarios
+                .iter()
+                .map(mappers::domain_to_proto_scenario)
+                .collect(),
+        });
+
+        let results = service
+            .handle_solve_scenarios(request, &Caller::from_request(&Request::new(())))
+            .await
+            .unwrap()
+            .into_inner()
+            .results;
+        assert_eq!(results.len(), 2);
+
+        let stats = service.stats.snapshot();
+        assert_eq!(stats.solves, 1);
+        assert_eq!(stats.by_status.get("OPTIMAL"), Some(&1));
+        let events = publisher.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].problem_name, "base");
+        assert_eq!(events[0].status, "OPTIMAL");
+    }
+
+    #[test]
+    fn scenario_jobs_take_the_first_status_short_of_optimal() {
+        let optimal = || Ok(Solution::optimal(0.0, Vec::new()));
+        let infeasible = || Ok(Solution::new(SolutionStatus::Infeasible, "Infeasible"));
+        assert_eq!(
+            scenarios_status(&[optimal(), optimal()]),
+            SolutionStatus::Optimal
+        );
+        assert_eq!(
+            scenarios_status(&[optimal(), Err(SolverError::Cancelled), infeasible()]),
+            SolutionStatus::Error
+        );
+        assert_eq!(
+            scenarios_status(&[infeasible(), optimal()]),
+            SolutionStatus::Infeasible
+        );
+    }
+
+    #[tokio::test]
+    async fn sparse_rows_without_an_objective_are_not_sized_by_their_indices() {
+        // Sized by its index this row would need a 34 GB coefficient vector
+        let huge = lp_solver::SparseConstraint {
+            name: "huge".to_string(),
+            indices: vec![u32::MAX],
+            values: vec![1.0],
+            ..Default::default()
+        };
+
+        let result = GrpcLpSolverService::new()
+            .handle_validate_stream(chunks(vec![Chunk::SparseConstraint(huge)]))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!result.is_valid);
+        let errors: Vec<_> = result
+            .chunk_errors
+            .iter()
+            .map(|e| (e.chunk_index, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [(
+                0,
+                "Sparse constraint 'huge' can't be checked without an objective"
+            )]
+        );
     }
 }
@@ -0,0 +1,162 @@
+// HTTP client: One request over a fresh HTTP/1.1 connection, plain or TLS
+// Shared by problem downloads and completion webhooks; HTTPS servers are checked
+// against the system's root certificates
+
+use std::io::BufReader;
+use std::sync::Arc;
+
+use hyper::body::{Body, Incoming};
+use hyper::header::{HOST, USER_AGENT};
+use hyper::http::request::Builder;
+use hyper::{Request, Response, Uri};
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+use tracing::{debug, warn};
+
+/// Where root certificates usually live, after `SSL_CERT_FILE`
+const CA_BUNDLES: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/ssl/ca-bundle.pem",
+    "/etc/ssl/cert.pem",
+];
+
+/// Opens a connection per request to `http://` and `https://` URIs
+pub struct HttpConnector {
+    tls: TlsConnector,
+}
+
+impl HttpConnector {
+    pub fn new() -> Self {
+        let roots = load_root_certificates();
+        if roots.is_empty() {
+            warn!("No root certificates found; HTTPS requests will fail");
+        }
+        let tls = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Self {
+            tls: TlsConnector::from(Arc::new(tls)),
+        }
+    }
+
+    /// Send `request` with `body` to `uri` and return the response, whose body is
+    /// still to be read
+    ///
+    /// The connector fills in the path, `Host` and `User-Agent`; redirects are
+    /// left to the caller.
+    pub async fn send<B>(
+        &self,
+        uri: &Uri,
+        request: Builder,
+        body: B,
+    ) -> Result<Response<Incoming>, String>
+    where
+        B: Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let authority = uri.authority().map(|a| a.as_str()).unwrap_or_default();
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        let request = request
+            .uri(path)
+            .header(HOST, authority)
+            .header(USER_AGENT, concat!("letsopt/", env!("CARGO_PKG_VERSION")))
+            .body(body)
+            .map_err(|e| e.to_string())?;
+
+        let host = host_name(uri);
+        let https = uri.scheme_str() == Some("https");
+        let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+        let tcp = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| e.to_string())?;
+        if https {
+            let name = ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+            let tls = self
+                .tls
+                .connect(name, tcp)
+                .await
+                .map_err(|e| e.to_string())?;
+            exchange(tls, request).await
+        } else {
+            exchange(tcp, request).await
+        }
+    }
+}
+
+impl Default for HttpConnector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn exchange<S, B>(stream: S, request: Request<B>) -> Result<Response<Incoming>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            debug!(error = %e, "HTTP connection closed");
+        }
+    });
+    sender
+        .send_request(request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Host of `uri` as a name or address to connect to; `Uri::host` keeps the
+/// brackets around IPv6 addresses
+pub fn host_name(uri: &Uri) -> &str {
+    let host = uri.host().unwrap_or_default();
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Roots from `SSL_CERT_FILE`, or the first system bundle found
+fn load_root_certificates() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    let from_env = std::env::var("SSL_CERT_FILE").ok();
+    for path in from_env
+        .iter()
+        .map(String::as_str)
+        .chain(CA_BUNDLES.iter().copied())
+    {
+        let Ok(file) = std::fs::File::open(path) else {
+            continue;
+        };
+        let mut reader = BufReader::new(file);
+        let certs = rustls_pemfile::certs(&mut reader).filter_map(|cert| cert.ok());
+        let (added, _) = roots.add_parsable_certificates(certs);
+        if added > 0 {
+            debug!(path, certificates = added, "Loaded root certificates");
+            break;
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv6_hosts_lose_their_brackets() {
+        let uri: Uri = "http://[::1]:8080/a.mps".parse().unwrap();
+        assert_eq!(host_name(&uri), "::1");
+        let uri: Uri = "http://problems.example.com/a.mps".parse().unwrap();
+        assert_eq!(host_name(&uri), "problems.example.com");
+    }
+}
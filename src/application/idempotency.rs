@@ -0,0 +1,280 @@
+// Idempotency: Deduplicate retried requests carrying the same client-supplied key
+// A retry after a network blip gets the original result instead of triggering a new solve
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::OnceCell;
+use tonic::Status;
+
+/// Request metadata key carrying the client-supplied idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response metadata key set when a result was replayed from the cache
+pub const IDEMPOTENT_REPLAY_HEADER: &str = "idempotent-replay";
+
+/// Default time a completed result stays available for replay
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(600);
+
+/// Error for a key reused with a different problem than the one it first came with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyReused;
+
+impl From<KeyReused> for Box<Status> {
+    fn from(_: KeyReused) -> Self {
+        Box::new(Status::failed_precondition(
+            "Idempotency key was already used with a different problem",
+        ))
+    }
+}
+
+/// Idempotency key, scoped to the API key that sent it
+type Key = (Option<String>, String);
+
+struct Entry<T> {
+    /// The result, with when it was produced
+    cell: Arc<OnceCell<(T, Instant)>>,
+    fingerprint: u64,
+}
+
+/// Cache of results keyed by API key and idempotency key
+///
+/// Concurrent requests with the same key share a single execution: the first one
+/// runs the work and the others wait for its result. Failed or abandoned executions
+/// are not cached and leave no entry behind, so a later retry runs the work again
+/// and keys of failing requests don't pile up. Each key is bound to the
+/// fingerprint of the request that first used it, and a result is kept for the
+/// TTL from when it was produced.
+pub struct IdempotencyCache<T> {
+    ttl: Duration,
+    entries: Mutex<HashMap<Key, Entry<T>>>,
+}
+
+impl<T: Clone> IdempotencyCache<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Return the cached result for `key` sent with `api_key`, or run `work` to
+    /// produce it
+    ///
+    /// The boolean is `true` when the result was replayed rather than computed.
+    /// Fails with [`KeyReused`] if the key was first sent with another fingerprint.
+    pub async fn get_or_run<E, F, Fut>(
+        &self,
+        api_key: Option<&str>,
+        key: &str,
+        fingerprint: u64,
+        work: F,
+    ) -> Result<(T, bool), E>
+    where
+        E: From<KeyReused>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let key = (api_key.map(str::to_string), key.to_string());
+        let cell = self.cell_for(&key, fingerprint)?;
+
+        if let Some((value, _)) = cell.get() {
+            return Ok((value.clone(), true));
+        }
+
+        // Removes the entry unless the work succeeds, including when this future is dropped
+        let _pending = Pending {
+            cache: self,
+            key: &key,
+            cell: &cell,
+        };
+        let mut computed = false;
+        let (value, _) = cell
+            .get_or_try_init(|| {
+                computed = true;
+                let work = work();
+                async move { Ok::<_, E>((work.await?, Instant::now())) }
+            })
+            .await?;
+
+        Ok((value.clone(), !computed))
+    }
+
+    /// Number of keys currently retained
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn cell_for(
+        &self,
+        key: &Key,
+        fingerprint: u64,
+    ) -> Result<Arc<OnceCell<(T, Instant)>>, KeyReused> {
+        let mut entries = self.lock();
+
+        // Drop expired results; in-flight entries are kept until they complete
+        let ttl = self.ttl;
+        entries.retain(|_, entry| {
+            entry
+                .cell
+                .get()
+                .is_none_or(|(_, completed_at)| completed_at.elapsed() < ttl)
+        });
+
+        let entry = entries.entry(key.clone()).or_insert_with(|| Entry {
+            cell: Arc::new(OnceCell::new()),
+            fingerprint,
+        });
+        if entry.fingerprint != fingerprint {
+            return Err(KeyReused);
+        }
+        Ok(Arc::clone(&entry.cell))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Entry<T>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// An execution in progress for `key`; drops the entry if it ends without a result
+struct Pending<'a, T> {
+    cache: &'a IdempotencyCache<T>,
+    key: &'a Key,
+    cell: &'a Arc<OnceCell<(T, Instant)>>,
+}
+
+impl<T> Drop for Pending<'_, T> {
+    fn drop(&mut self) {
+        if self.cell.initialized() {
+            return;
+        }
+        let mut entries = self.cache.entries.lock().unwrap_or_else(|e| e.into_inner());
+        // A later request may already have replaced the entry with a fresh one, and
+        // requests still waiting on this one will run the work themselves; only the
+        // map and this execution may hold the cell
+        if entries
+            .get(self.key)
+            .is_some_and(|entry| Arc::ptr_eq(&entry.cell, self.cell))
+            && Arc::strong_count(self.cell) <= 2
+        {
+            entries.remove(self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Failure {
+        Work,
+        KeyReused,
+    }
+
+    impl From<KeyReused> for Failure {
+        fn from(_: KeyReused) -> Self {
+            Failure::KeyReused
+        }
+    }
+
+    fn cache() -> IdempotencyCache<u32> {
+        IdempotencyCache::new(DEFAULT_IDEMPOTENCY_TTL)
+    }
+
+    #[tokio::test]
+    async fn failed_work_leaves_no_entry() {
+        let cache = cache();
+
+        for i in 0..100 {
+            let key = format!("key-{}", i);
+            let outcome =
+                cache.get_or_run(None, &key, 0, || async { Err::<u32, _>(Failure::Work) });
+            assert_eq!(outcome.await, Err(Failure::Work));
+        }
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_retry_after_a_failure_runs_the_work_again() {
+        let cache = cache();
+
+        let failed = cache.get_or_run(None, "key", 0, || async { Err::<u32, _>(Failure::Work) });
+        assert!(failed.await.is_err());
+        let retried = cache.get_or_run(None, "key", 0, || async { Ok::<_, Failure>(7) });
+        assert_eq!(retried.await, Ok((7, false)));
+        let replayed = cache.get_or_run(None, "key", 0, || async { Ok::<_, Failure>(8) });
+        assert_eq!(replayed.await, Ok((7, true)));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn abandoned_work_leaves_no_entry() {
+        let cache = cache();
+
+        let work = cache.get_or_run(None, "key", 0, std::future::pending::<Result<u32, Failure>>);
+        let timed_out = tokio::time::timeout(Duration::from_millis(10), work).await;
+        assert!(timed_out.is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn keys_are_scoped_to_the_api_key() {
+        let cache = cache();
+
+        let first = cache.get_or_run(Some("alice"), "key", 0, || async { Ok::<_, Failure>(1) });
+        assert_eq!(first.await, Ok((1, false)));
+        let other = cache.get_or_run(Some("bob"), "key", 0, || async { Ok::<_, Failure>(2) });
+        assert_eq!(other.await, Ok((2, false)));
+        let anonymous = cache.get_or_run(None, "key", 0, || async { Ok::<_, Failure>(3) });
+        assert_eq!(anonymous.await, Ok((3, false)));
+    }
+
+    #[tokio::test]
+    async fn a_key_reused_for_another_problem_is_refused() {
+        let cache = cache();
+
+        let first = cache.get_or_run(None, "key", 1, || async { Ok::<_, Failure>(1) });
+        assert_eq!(first.await, Ok((1, false)));
+        let reused = cache.get_or_run(None, "key", 2, || async { Ok::<_, Failure>(2) });
+        assert_eq!(reused.await, Err(Failure::KeyReused));
+    }
+
+    #[tokio::test]
+    async fn abandoning_one_request_keeps_the_entry_its_waiters_share() {
+        let cache = cache();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+        let mut abandoned = Box::pin(cache.get_or_run(
+            None,
+            "key",
+            0,
+            std::future::pending::<Result<u32, Failure>>,
+        ));
+        let mut waiter = Box::pin(cache.get_or_run(None, "key", 0, || async move {
+            released.await.ok();
+            Ok::<_, Failure>(5)
+        }));
+
+        // Start both, so the waiter holds the shared cell, then drop the first
+        assert!(futures::poll!(abandoned.as_mut()).is_pending());
+        assert!(futures::poll!(waiter.as_mut()).is_pending());
+        drop(abandoned);
+        assert_eq!(cache.len(), 1);
+
+        release.send(()).unwrap();
+        assert_eq!(waiter.await, Ok((5, false)));
+        let replayed = cache.get_or_run(None, "key", 0, || async { Ok::<_, Failure>(6) });
+        assert_eq!(replayed.await, Ok((5, true)));
+    }
+}
@@ -0,0 +1,351 @@
+// Job tracking: Registry of queued and running solves
+// Gives operators visibility into what the server is currently working on
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::sync::broadcast;
+use tracing::debug;
+
+use crate::domain::models::{OptimizationProblem, Solution};
+use crate::domain::solver_service::{CancellationToken, SolveObserver};
+use crate::domain::value_objects::{SolutionStatus, SolvePriority};
+
+/// Log lines kept for subscribers that join a job after its solver started
+const LOG_BACKLOG_LINES: usize = 200;
+
+/// Lines a log subscriber may fall behind before it misses some
+const LOG_CHANNEL_CAPACITY: usize = 1024;
+
+/// One line of a job's backend log
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub text: String,
+    /// Time since the solver started on the job
+    pub elapsed: Duration,
+}
+
+/// Lifecycle state of an active job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    /// Waiting to be picked up by a solver
+    Queued,
+    /// Solver is working on it
+    Running,
+}
+
+/// Point-in-time view of an active job
+#[derive(Debug, Clone)]
+pub struct JobSnapshot {
+    pub id: u64,
+    pub state: JobState,
+    pub problem_name: String,
+    pub num_variables: usize,
+    pub num_constraints: usize,
+    pub num_integer_vars: usize,
+    pub elapsed: Duration,
+    pub running: Duration,
+    pub backend: String,
+    /// Objective of the best solution found so far
+    pub incumbent: Option<f64>,
+    /// Proven bound on the optimal objective so far
+    pub best_bound: Option<f64>,
+    pub nodes_explored: u64,
+    pub priority: SolvePriority,
+    pub deadline: Option<SystemTime>,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug)]
+struct JobEntry {
+    problem_name: String,
+    num_variables: usize,
+    num_constraints: usize,
+    num_integer_vars: usize,
+    submitted_at: Instant,
+    started_at: Option<Instant>,
+    backend: String,
+    incumbent: Option<f64>,
+    /// Variable values of the incumbent
+    incumbent_values: Vec<f64>,
+    best_bound: Option<f64>,
+    nodes_explored: u64,
+    priority: SolvePriority,
+    deadline: Option<SystemTime>,
+    tags: BTreeMap<String, String>,
+    cancel: CancellationToken,
+    /// Cancelled to free its slot for a higher-priority solve
+    preempted: bool,
+    /// Most recent log lines, oldest first
+    log_backlog: VecDeque<LogLine>,
+    /// Live log lines; subscribers see the channel close when the job ends
+    log: broadcast::Sender<LogLine>,
+}
+
+impl JobEntry {
+    fn snapshot(&self, id: u64) -> JobSnapshot {
+        JobSnapshot {
+            id,
+            state: if self.started_at.is_some() {
+                JobState::Running
+            } else {
+                JobState::Queued
+            },
+            problem_name: self.problem_name.clone(),
+            num_variables: self.num_variables,
+            num_constraints: self.num_constraints,
+            num_integer_vars: self.num_integer_vars,
+            elapsed: self.submitted_at.elapsed(),
+            running: self
+                .started_at
+                .map(|started| started.elapsed())
+                .unwrap_or_default(),
+            backend: self.backend.clone(),
+            incumbent: self.incumbent,
+            best_bound: self.best_bound,
+            nodes_explored: self.nodes_explored,
+            priority: self.priority,
+            deadline: self.deadline,
+            tags: self.tags.clone(),
+        }
+    }
+}
+
+/// Registry of all jobs currently queued or running on this server
+#[derive(Debug, Default)]
+pub struct JobRegistry {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, JobEntry>>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new job in the queued state
+    ///
+    /// The job is removed from the registry when the returned handle is dropped.
+    pub fn register(self: &Arc<Self>, problem: &OptimizationProblem) -> JobHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let entry = JobEntry {
+            problem_name: problem.name.clone(),
+            num_variables: problem.num_variables(),
+            num_constraints: problem.constraints.len(),
+            num_integer_vars: problem.num_integer_variables(),
+            submitted_at: Instant::now(),
+            started_at: None,
+            backend: String::new(),
+            incumbent: None,
+            incumbent_values: Vec::new(),
+            best_bound: None,
+            nodes_explored: 0,
+            priority: problem.solver_config.priority,
+            deadline: problem.solver_config.deadline,
+            tags: problem.tags.clone(),
+            cancel: CancellationToken::new(),
+            preempted: false,
+            log_backlog: VecDeque::new(),
+            log: broadcast::channel(LOG_CHANNEL_CAPACITY).0,
+        };
+
+        self.lock().insert(id, entry);
+
+        JobHandle {
+            registry: Arc::clone(self),
+            id,
+        }
+    }
+
+    /// List all active jobs, oldest first
+    pub fn list(&self) -> Vec<JobSnapshot> {
+        let mut jobs: Vec<JobSnapshot> = self
+            .lock()
+            .iter()
+            .map(|(&id, entry)| entry.snapshot(id))
+            .collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+
+    /// Look up a single active job
+    pub fn get(&self, id: u64) -> Option<JobSnapshot> {
+        self.lock().get(&id).map(|entry| entry.snapshot(id))
+    }
+
+    /// Ask an active job to stop, returning its state at the time of the request
+    pub fn cancel(&self, id: u64) -> Option<JobSnapshot> {
+        let jobs = self.lock();
+        let entry = jobs.get(&id)?;
+        entry.cancel.cancel();
+        Some(entry.snapshot(id))
+    }
+
+    /// Follow an active job's log: its recent lines, and a receiver for the rest
+    pub fn subscribe_log(&self, id: u64) -> Option<(Vec<LogLine>, broadcast::Receiver<LogLine>)> {
+        let jobs = self.lock();
+        let entry = jobs.get(&id)?;
+        // Lines are sent under the lock, so none falls between the two
+        Some((
+            entry.log_backlog.iter().cloned().collect(),
+            entry.log.subscribe(),
+        ))
+    }
+
+    /// Stop the most recently started running MIP of `priority`, to free its
+    /// solve slot; returns its ID
+    ///
+    /// LPs are left alone: they rarely hold a slot for long.
+    pub fn preempt(&self, priority: SolvePriority) -> Option<u64> {
+        let mut jobs = self.lock();
+        let (&id, entry) = jobs
+            .iter_mut()
+            .filter(|(_, entry)| {
+                entry.priority == priority
+                    && entry.num_integer_vars > 0
+                    && entry.started_at.is_some()
+                    && !entry.preempted
+            })
+            .max_by_key(|(_, entry)| entry.started_at)?;
+        entry.preempted = true;
+        entry.cancel.cancel();
+        Some(id)
+    }
+
+    /// Number of active jobs
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, JobEntry>> {
+        // A panic while holding the lock cannot leave the map inconsistent
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Handle to a registered job; deregisters the job on drop
+pub struct JobHandle {
+    registry: Arc<JobRegistry>,
+    id: u64,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Mark the job as picked up by the given solver backend
+    pub fn mark_running(&self, backend: &str) {
+        if let Some(entry) = self.registry.lock().get_mut(&self.id) {
+            entry.started_at = Some(Instant::now());
+            entry.backend = backend.to_string();
+        }
+    }
+
+    /// Token that is cancelled when someone cancels this job
+    pub fn cancellation(&self) -> CancellationToken {
+        self.registry
+            .lock()
+            .get(&self.id)
+            .map(|entry| entry.cancel.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether the job was cancelled to make room for a higher-priority solve
+    pub fn was_preempted(&self) -> bool {
+        self.registry
+            .lock()
+            .get(&self.id)
+            .is_some_and(|entry| entry.preempted)
+    }
+
+    /// INTERRUPTED solution holding the best solution and bound the job's solver
+    /// reported before it was stopped
+    pub fn interrupted(&self, problem: &OptimizationProblem) -> Solution {
+        let jobs = self.registry.lock();
+        let entry = jobs.get(&self.id);
+        let incumbent = entry
+            .and_then(|entry| Some((entry.incumbent?, entry.incumbent_values.clone())))
+            .filter(|(_, values)| !values.is_empty());
+        let solution = Solution::stopped(
+            SolutionStatus::Interrupted,
+            "Solve cancelled",
+            &problem.name,
+            incumbent,
+        );
+        match entry.and_then(|entry| entry.best_bound) {
+            Some(bound) => solution.with_bound(bound),
+            None => solution,
+        }
+    }
+
+    /// Observer that records solver progress on this job
+    pub fn progress(&self) -> JobProgress {
+        JobProgress {
+            registry: Arc::clone(&self.registry),
+            id: self.id,
+        }
+    }
+}
+
+/// Records a running job's incumbent, bound and node count in the registry
+pub struct JobProgress {
+    registry: Arc<JobRegistry>,
+    id: u64,
+}
+
+impl JobProgress {
+    fn update(&self, apply: impl FnOnce(&mut JobEntry)) {
+        if let Some(entry) = self.registry.lock().get_mut(&self.id) {
+            apply(entry);
+        }
+    }
+}
+
+impl SolveObserver for JobProgress {
+    fn on_incumbent(&self, objective: f64, values: &[f64]) {
+        debug!(job_id = self.id, objective, "New incumbent");
+        self.update(|entry| {
+            entry.incumbent = Some(objective);
+            entry.incumbent_values = values.to_vec();
+        });
+    }
+
+    fn on_bound_update(&self, bound: f64) {
+        self.update(|entry| entry.best_bound = Some(bound));
+    }
+
+    fn on_node(&self, nodes: u64) {
+        self.update(|entry| entry.nodes_explored = nodes);
+    }
+
+    fn on_log(&self, line: &str) {
+        self.update(|entry| {
+            let line = LogLine {
+                text: line.to_string(),
+                elapsed: entry
+                    .started_at
+                    .map(|started| started.elapsed())
+                    .unwrap_or_default(),
+            };
+            if entry.log_backlog.len() == LOG_BACKLOG_LINES {
+                entry.log_backlog.pop_front();
+            }
+            entry.log_backlog.push_back(line.clone());
+            // No subscribers is the usual case
+            let _ = entry.log.send(line);
+        });
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
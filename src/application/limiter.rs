@@ -0,0 +1,206 @@
+// Concurrency limiting: Cap the number of simultaneous solves
+// Prevents a burst of requests from launching more solves than the host can handle,
+// and hands freed slots to waiting solves by priority, then earliest deadline
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use tokio::sync::oneshot;
+use tonic::Status;
+
+use crate::domain::value_objects::SolvePriority;
+
+/// What to do with a solve request when all solve slots are busy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverloadPolicy {
+    /// Wait for a slot to free up
+    #[default]
+    Queue,
+    /// Fail immediately with RESOURCE_EXHAUSTED
+    Reject,
+}
+
+/// Queue position: higher priority first, then earliest deadline (solves
+/// without one last), then arrival order
+type Ticket = (Reverse<SolvePriority>, bool, Option<SystemTime>, u64);
+
+struct Slots {
+    free: usize,
+    next_ticket: u64,
+    /// Solves waiting for a slot; a slot is handed over by sending on the channel
+    waiting: BTreeMap<Ticket, oneshot::Sender<()>>,
+}
+
+impl Slots {
+    /// Give a slot to the first waiting solve, or put it back
+    fn release(&mut self) {
+        match self.waiting.pop_first() {
+            // A waiter's receiver outlives its place in the queue, so this
+            // cannot fail
+            Some((_, waiter)) => {
+                let _ = waiter.send(());
+            }
+            None => self.free += 1,
+        }
+    }
+}
+
+/// Shared slot accounting of a limited [`SolveLimiter`]
+struct SlotPool {
+    slots: Mutex<Slots>,
+}
+
+impl SlotPool {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Slots> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Permit to run one solve; the slot is released on drop
+pub struct SolvePermit {
+    pool: Option<Arc<SlotPool>>,
+}
+
+impl Drop for SolvePermit {
+    fn drop(&mut self) {
+        if let Some(pool) = &self.pool {
+            pool.lock().release();
+        }
+    }
+}
+
+/// A queued solve; leaving the queue early passes on a slot handed to it meanwhile
+struct Waiter {
+    pool: Arc<SlotPool>,
+    ticket: Ticket,
+    slot: oneshot::Receiver<()>,
+    granted: bool,
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        let mut slots = self.pool.lock();
+        if slots.waiting.remove(&self.ticket).is_none() {
+            slots.release();
+        }
+    }
+}
+
+/// Limits the number of solves running at the same time
+pub struct SolveLimiter {
+    pool: Option<Arc<SlotPool>>,
+    max_concurrent: Option<usize>,
+    policy: OverloadPolicy,
+}
+
+impl SolveLimiter {
+    /// Limiter that never blocks
+    pub fn unlimited() -> Self {
+        Self {
+            pool: None,
+            max_concurrent: None,
+            policy: OverloadPolicy::default(),
+        }
+    }
+
+    /// Limiter allowing at most `max_concurrent` simultaneous solves
+    pub fn new(max_concurrent: usize, policy: OverloadPolicy) -> Self {
+        let slots = Slots {
+            free: max_concurrent,
+            next_ticket: 0,
+            waiting: BTreeMap::new(),
+        };
+        Self {
+            pool: Some(Arc::new(SlotPool {
+                slots: Mutex::new(slots),
+            })),
+            max_concurrent: Some(max_concurrent),
+            policy,
+        }
+    }
+
+    pub fn max_concurrent(&self) -> Option<usize> {
+        self.max_concurrent
+    }
+
+    pub fn policy(&self) -> OverloadPolicy {
+        self.policy
+    }
+
+    /// Number of solve slots currently free (None when unlimited)
+    pub fn available(&self) -> Option<usize> {
+        self.pool.as_ref().map(|pool| pool.lock().free)
+    }
+
+    /// Number of solves waiting for a slot
+    pub fn queued(&self) -> usize {
+        self.pool
+            .as_ref()
+            .map_or(0, |pool| pool.lock().waiting.len())
+    }
+
+    /// Acquire a solve slot according to the overload policy
+    ///
+    /// Queued solves get freed slots in `priority` order, earliest `deadline`
+    /// first among equals, and oldest first after that.
+    pub async fn acquire(
+        &self,
+        priority: SolvePriority,
+        deadline: Option<SystemTime>,
+    ) -> Result<SolvePermit, Box<Status>> {
+        let Some(pool) = &self.pool else {
+            return Ok(SolvePermit { pool: None });
+        };
+
+        let (ticket, slot) = {
+            let mut slots = pool.lock();
+            if slots.free > 0 {
+                slots.free -= 1;
+                return Ok(SolvePermit {
+                    pool: Some(Arc::clone(pool)),
+                });
+            }
+            if self.policy == OverloadPolicy::Reject {
+                return Err(Box::new(Status::resource_exhausted(format!(
+                    "All {} solve slots are busy, retry later",
+                    self.max_concurrent.unwrap_or_default()
+                ))));
+            }
+            let ticket = (
+                Reverse(priority),
+                deadline.is_none(),
+                deadline,
+                slots.next_ticket,
+            );
+            slots.next_ticket += 1;
+            let (sender, slot) = oneshot::channel();
+            slots.waiting.insert(ticket, sender);
+            (ticket, slot)
+        };
+
+        let mut waiter = Waiter {
+            pool: Arc::clone(pool),
+            ticket,
+            slot,
+            granted: false,
+        };
+        (&mut waiter.slot)
+            .await
+            .map_err(|_| Box::new(Status::unavailable("Solver is shutting down")))?;
+        waiter.granted = true;
+        Ok(SolvePermit {
+            pool: Some(Arc::clone(pool)),
+        })
+    }
+}
+
+impl Default for SolveLimiter {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
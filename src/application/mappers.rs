@@ -1,85 +1,237 @@
 // Mappers: Convert between gRPC protobuf types and domain models
 // This keeps protobuf dependencies isolated from business logic (Dependency Inversion)
 
+#[cfg(feature = "server")]
+use crate::domain::value_objects::OptionKind;
 use crate::domain::{
+    difficulty::DifficultyFactor,
+    explanation::SolutionExplanation,
+    infeasibility::{CertificateRow, InfeasibilityCertificate},
+    model_session::{ModelSession, ModelUpdate},
+    model_statistics::{MagnitudeRange, ModelStatistics},
     models::{
-        Constraint, ObjectiveFunction, OptimizationProblem, Solution, SolverConfig, Variable,
+        BasisConditioning, Constraint, ObjectiveFunction, OptimizationProblem, ResourceUsage,
+        Solution, SolutionQuality, SolverConfig, SolverStatistics, Variable,
     },
+    presolve::PresolveReport,
+    problem_file::ProblemFormat,
+    scenarios::Scenario,
+    solution_diff::{SolutionDiff, VariableChange},
+    sparse::SparseVector,
+    sweep::{ParametricSweep, SweepTarget},
+    unbounded_ray::{RayComponent, UnboundedRay},
     value_objects::{
-        ConstraintType, OptimizationType, SolutionStatus, SolverBackend, VariableType,
+        ConstraintType, ExportFormat, IntegerRounding, LpAlgorithm, OptimizationType,
+        SolutionStatus, SolvePriority, SolverBackend, VariableType,
     },
+    verification::{SolutionCheck, VariableViolationKind},
 };
+#[cfg(feature = "server")]
+use crate::solver::SolverInfo;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tonic::Status;
 
+use super::error_details;
+#[cfg(feature = "server")]
+use super::jobs::{JobSnapshot, JobState, LogLine};
+use super::pareto::{ParetoFrontier, ParetoPoint};
+#[cfg(feature = "server")]
+use super::problem_repository::{SolveRecord, StoredProblemInfo};
+#[cfg(feature = "server")]
+use super::server_stats::StatsSnapshot;
+
 pub mod lp_solver {
     tonic::include_proto!("lp_solver");
 }
 
 use lp_solver as proto;
+use proto::{error_detail::ErrorCode, ErrorDetail};
+
+/// Convert a protobuf variable type to the domain one
+pub fn proto_to_domain_variable_type(
+    r#type: i32,
+) -> std::result::Result<VariableType, Box<Status>> {
+    match proto::variable::VariableType::try_from(r#type) {
+        Ok(proto::variable::VariableType::Continuous) => Ok(VariableType::Continuous),
+        Ok(proto::variable::VariableType::Integer) => Ok(VariableType::Integer),
+        Ok(proto::variable::VariableType::Binary) => Ok(VariableType::Binary),
+        Err(_) => Err(ErrorDetail::with_code(ErrorCode::InvalidVariableType)
+            .with_entity("variable", None)
+            .with_values("CONTINUOUS, INTEGER or BINARY", r#type)
+            .invalid_argument(format!("Invalid variable type {}", r#type))),
+    }
+}
 
 /// Convert protobuf Variable to domain Variable
 pub fn proto_to_domain_variable(
-    proto_var: &proto::Variable,
+    proto_var: proto::Variable,
 ) -> std::result::Result<Variable, Box<Status>> {
-    let variable_type = match proto::variable::VariableType::try_from(proto_var.r#type) {
-        Ok(proto::variable::VariableType::Continuous) => VariableType::Continuous,
-        Ok(proto::variable::VariableType::Integer) => VariableType::Integer,
-        Ok(proto::variable::VariableType::Binary) => VariableType::Binary,
-        Err(_) => return Err(Box::new(Status::invalid_argument("Invalid variable type"))),
-    };
-
+    let variable_type = proto_to_domain_variable_type(proto_var.r#type)
+        .map_err(|status| error_details::named(status, &proto_var.name))?;
     Ok(Variable {
         variable_type,
         lower_bound: proto_var.lower_bound,
         upper_bound: proto_var.upper_bound,
-        name: proto_var.name.clone(),
+        name: proto_var.name,
     })
 }
 
+/// Convert a protobuf constraint type to the domain one
+pub fn proto_to_domain_constraint_type(
+    r#type: i32,
+) -> std::result::Result<ConstraintType, Box<Status>> {
+    match proto::constraint::ConstraintType::try_from(r#type) {
+        Ok(proto::constraint::ConstraintType::LessThanOrEqual) => {
+            Ok(ConstraintType::LessThanOrEqual)
+        }
+        Ok(proto::constraint::ConstraintType::Equal) => Ok(ConstraintType::Equal),
+        Ok(proto::constraint::ConstraintType::GreaterThanOrEqual) => {
+            Ok(ConstraintType::GreaterThanOrEqual)
+        }
+        Err(_) => Err(ErrorDetail::with_code(ErrorCode::InvalidConstraintType)
+            .with_entity("constraint", None)
+            .with_values("LESS_THAN_OR_EQUAL, EQUAL or GREATER_THAN_OR_EQUAL", r#type)
+            .invalid_argument(format!("Invalid constraint type {}", r#type))),
+    }
+}
+
 /// Convert protobuf Constraint to domain Constraint
+///
+/// Takes the message by value so the name vector is moved, not copied; on
+/// multi-megabyte problems the copies used to dominate conversion. Only the
+/// non-zeros of the dense coefficients are kept.
 pub fn proto_to_domain_constraint(
-    proto_constr: &proto::Constraint,
+    proto_constr: proto::Constraint,
 ) -> std::result::Result<Constraint, Box<Status>> {
-    let constraint_type = match proto::constraint::ConstraintType::try_from(proto_constr.r#type) {
-        Ok(proto::constraint::ConstraintType::LessThanOrEqual) => ConstraintType::LessThanOrEqual,
-        Ok(proto::constraint::ConstraintType::Equal) => ConstraintType::Equal,
-        Ok(proto::constraint::ConstraintType::GreaterThanOrEqual) => {
-            ConstraintType::GreaterThanOrEqual
-        }
-        Err(_) => {
-            return Err(Box::new(Status::invalid_argument(
-                "Invalid constraint type",
-            )))
-        }
+    let constraint_type = proto_to_domain_constraint_type(proto_constr.r#type)
+        .map_err(|status| error_details::named(status, &proto_constr.name))?;
+    Ok(Constraint {
+        constraint_type,
+        coefficients: proto_constr.coefficients.into(),
+        bound: proto_constr.bound,
+        name: proto_constr.name,
+        variable_names: proto_constr.variable_names,
+    })
+}
+
+/// Convert a protobuf SparseConstraint to a domain Constraint over `num_vars` variables
+///
+/// Terms are located by `indices`, or by `variable_names` looked up in `names` when
+/// no indices are given. Only the listed terms are stored, and the width always
+/// comes from the problem, never from the indices, which are the client's to choose.
+pub fn proto_to_domain_sparse_constraint(
+    sparse: proto::SparseConstraint,
+    num_vars: usize,
+    names: &HashMap<&str, usize>,
+) -> std::result::Result<Constraint, Box<Status>> {
+    let invalid = |detail: ErrorDetail, message: String| {
+        detail
+            .with_entity("constraint", None)
+            .with_name(&sparse.name)
+            .invalid_argument(format!("Sparse constraint '{}' {}", sparse.name, message))
     };
 
+    let constraint_type = proto_to_domain_constraint_type(sparse.r#type)
+        .map_err(|status| error_details::named(status, &sparse.name))?;
+    let indices: Vec<usize> = if !sparse.indices.is_empty() {
+        sparse.indices.iter().map(|&j| j as usize).collect()
+    } else {
+        sparse
+            .variable_names
+            .iter()
+            .map(|name| {
+                names.get(name.as_str()).copied().ok_or_else(|| {
+                    invalid(
+                        ErrorDetail::with_code(ErrorCode::UnknownVariable).with_values("", name),
+                        format!("refers to unknown variable '{}'", name),
+                    )
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?
+    };
+    if indices.len() != sparse.values.len() {
+        return Err(invalid(
+            ErrorDetail::with_code(ErrorCode::LengthMismatch)
+                .with_values(indices.len(), sparse.values.len()),
+            format!(
+                "has {} terms but {} values",
+                indices.len(),
+                sparse.values.len()
+            ),
+        ));
+    }
+    if let Some(&j) = indices.iter().find(|&&j| j >= num_vars) {
+        return Err(invalid(
+            ErrorDetail::with_code(ErrorCode::IndexOutOfRange)
+                .with_values(format!("below {}", num_vars), j),
+            format!(
+                "refers to variable {} but problem has {} variables",
+                j, num_vars
+            ),
+        ));
+    }
+
+    let mut entries: Vec<(usize, f64)> = indices.into_iter().zip(sparse.values).collect();
+    entries.sort_by_key(|&(j, _)| j);
+    if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        let j = pair[0].0;
+        return Err(invalid(
+            ErrorDetail::with_code(ErrorCode::DuplicateVariable).with_values("", j),
+            format!("lists variable {} more than once", j),
+        ));
+    }
+
     Ok(Constraint {
         constraint_type,
-        coefficients: proto_constr.coefficients.clone(),
-        bound: proto_constr.bound,
-        name: proto_constr.name.clone(),
+        coefficients: SparseVector::from_entries(num_vars, entries),
+        bound: sparse.bound,
+        name: sparse.name,
+        variable_names: Vec::new(),
     })
 }
 
+/// Index of each variable name, from the declared variables or else the objective
+pub fn variable_name_index<'a>(
+    declared: impl IntoIterator<Item = &'a str>,
+    objective_names: &'a [String],
+) -> HashMap<&'a str, usize> {
+    let mut names: HashMap<&str, usize> = declared
+        .into_iter()
+        .enumerate()
+        .map(|(j, name)| (name, j))
+        .collect();
+    if names.is_empty() {
+        names = objective_names
+            .iter()
+            .enumerate()
+            .map(|(j, name)| (name.as_str(), j))
+            .collect();
+    }
+    names.remove("");
+    names
+}
+
 /// Convert protobuf ObjectiveFunction to domain ObjectiveFunction
 pub fn proto_to_domain_objective(
-    proto_obj: &proto::ObjectiveFunction,
+    proto_obj: proto::ObjectiveFunction,
 ) -> std::result::Result<ObjectiveFunction, Box<Status>> {
     let optimization_type =
         match proto::objective_function::OptimizationType::try_from(proto_obj.r#type) {
             Ok(proto::objective_function::OptimizationType::Minimize) => OptimizationType::Minimize,
             Ok(proto::objective_function::OptimizationType::Maximize) => OptimizationType::Maximize,
             Err(_) => {
-                return Err(Box::new(Status::invalid_argument(
-                    "Invalid optimization type",
-                )))
+                return Err(ErrorDetail::with_code(ErrorCode::InvalidOptimizationType)
+                    .with_entity("objective", None)
+                    .with_values("MINIMIZE or MAXIMIZE", proto_obj.r#type)
+                    .invalid_argument(format!("Invalid optimization type {}", proto_obj.r#type)))
             }
         };
 
     Ok(ObjectiveFunction {
         optimization_type,
-        coefficients: proto_obj.coefficients.clone(),
-        variable_names: proto_obj.variable_names.clone(),
+        coefficients: proto_obj.coefficients,
+        variable_names: proto_obj.variable_names,
     })
 }
 
@@ -87,58 +239,37 @@ pub fn proto_to_domain_objective(
 pub fn proto_to_domain_problem(
     proto_prob: proto::OptimizationProblem,
 ) -> std::result::Result<OptimizationProblem, Box<Status>> {
-    let objective = proto_prob
-        .objective
-        .ok_or_else(|| Box::new(Status::invalid_argument("Objective is required")))?;
-    let objective = proto_to_domain_objective(&objective)?;
+    let objective = proto_prob.objective.ok_or_else(missing_objective)?;
+    let objective = proto_to_domain_objective(objective)?;
 
     // Create default variables if none provided
     let variables = if proto_prob.variables.is_empty() {
-        // Create continuous non-negative variables by default
-        (0..objective.num_variables())
-            .map(|i| Variable::continuous(format!("x{}", i)))
-            .collect()
+        default_variables(&objective)
     } else {
         proto_prob
             .variables
-            .iter()
-            .map(proto_to_domain_variable)
+            .into_iter()
+            .enumerate()
+            .map(|(i, var)| {
+                proto_to_domain_variable(var).map_err(|status| error_details::at_index(status, i))
+            })
             .collect::<std::result::Result<Vec<_>, _>>()?
     };
 
     let constraints = proto_prob
         .constraints
-        .iter()
-        .map(proto_to_domain_constraint)
+        .into_iter()
+        .enumerate()
+        .map(|(i, constraint)| {
+            proto_to_domain_constraint(constraint)
+                .map_err(|status| error_details::at_index(status, i))
+        })
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
-    let solver_config = if let Some(cfg) = proto_prob.solver_config {
-        let backend = match proto::solver_config::SolverBackend::try_from(cfg.solver) {
-            Ok(proto::solver_config::SolverBackend::Auto) => SolverBackend::Auto,
-            Ok(proto::solver_config::SolverBackend::CoinCbc) => SolverBackend::CoinCbc,
-            Ok(proto::solver_config::SolverBackend::Highs) => SolverBackend::Highs,
-            Err(_) => SolverBackend::Auto,
-        };
-
-        SolverConfig {
-            backend,
-            time_limit: if cfg.time_limit > 0.0 {
-                Some(cfg.time_limit)
-            } else {
-                None
-            },
-            gap_tolerance: cfg.mip_options.as_ref().and_then(|m| {
-                if m.gap_tolerance > 0.0 {
-                    Some(m.gap_tolerance)
-                } else {
-                    None
-                }
-            }),
-            verbose: cfg.verbose,
-        }
-    } else {
-        SolverConfig::default()
-    };
+    let solver_config = proto_prob
+        .solver_config
+        .map(proto_to_domain_solver_config)
+        .unwrap_or_default();
 
     Ok(OptimizationProblem {
         name: proto_prob.problem_name,
@@ -147,28 +278,878 @@ pub fn proto_to_domain_problem(
         constraints,
         variables,
         solver_config,
+        tags: proto_prob.tags.into_iter().collect(),
     })
 }
 
+/// Error for a problem without an objective
+pub fn missing_objective() -> Box<Status> {
+    ErrorDetail::with_code(ErrorCode::MissingField)
+        .with_entity("objective", None)
+        .invalid_argument("Objective is required")
+}
+
+/// Continuous non-negative variables for a problem that declares none, named after
+/// the objective's terms
+pub fn default_variables(objective: &ObjectiveFunction) -> Vec<Variable> {
+    (0..objective.num_variables())
+        .map(|i| match objective.variable_names.get(i) {
+            Some(name) if !name.is_empty() => Variable::continuous(name.clone()),
+            _ => Variable::continuous(format!("x{}", i)),
+        })
+        .collect()
+}
+
+/// Convert protobuf SolverConfig to domain SolverConfig
+pub fn proto_to_domain_solver_config(cfg: proto::SolverConfig) -> SolverConfig {
+    let backend = match proto::solver_config::SolverBackend::try_from(cfg.solver) {
+        Ok(proto::solver_config::SolverBackend::Auto) => SolverBackend::Auto,
+        Ok(proto::solver_config::SolverBackend::CoinCbc) => SolverBackend::CoinCbc,
+        Ok(proto::solver_config::SolverBackend::Highs) => SolverBackend::Highs,
+        Ok(proto::solver_config::SolverBackend::Race) => SolverBackend::Race,
+        Ok(proto::solver_config::SolverBackend::Assignment) => SolverBackend::Assignment,
+        Err(_) => SolverBackend::Auto,
+    };
+    let presolve = cfg.presolve() == proto::solver_config::PresolveLevel::PresolveOn;
+    let priority = proto_to_domain_priority(cfg.priority());
+    let integer_rounding = match cfg.integer_rounding() {
+        proto::solver_config::IntegerRounding::RoundSnap => IntegerRounding::Snap,
+        proto::solver_config::IntegerRounding::RoundRepair => IntegerRounding::Repair,
+        proto::solver_config::IntegerRounding::RoundOff => IntegerRounding::Off,
+    };
+    let lp_algorithm = match cfg.lp_algorithm() {
+        proto::solver_config::LpAlgorithm::LpAuto => LpAlgorithm::Auto,
+        proto::solver_config::LpAlgorithm::LpDualSimplex => LpAlgorithm::DualSimplex,
+        proto::solver_config::LpAlgorithm::LpPrimalSimplex => LpAlgorithm::PrimalSimplex,
+        proto::solver_config::LpAlgorithm::LpInteriorPoint => LpAlgorithm::InteriorPoint,
+    };
+    let export = match cfg.export_format() {
+        proto::solver_config::ExportFormat::ExportNone => None,
+        proto::solver_config::ExportFormat::Csv => Some(ExportFormat::Csv),
+        proto::solver_config::ExportFormat::Json => Some(ExportFormat::Json),
+    };
+
+    SolverConfig {
+        backend,
+        time_limit: if cfg.time_limit > 0.0 {
+            Some(cfg.time_limit)
+        } else {
+            None
+        },
+        gap_tolerance: cfg.mip_options.as_ref().and_then(|m| {
+            if m.gap_tolerance > 0.0 {
+                Some(m.gap_tolerance)
+            } else {
+                None
+            }
+        }),
+        absolute_gap_tolerance: cfg
+            .mip_options
+            .as_ref()
+            .and_then(|m| (m.absolute_gap_tolerance > 0.0).then_some(m.absolute_gap_tolerance)),
+        max_iterations: (cfg.max_iterations > 0).then_some(cfg.max_iterations),
+        max_nodes: cfg
+            .mip_options
+            .as_ref()
+            .and_then(|m| (m.max_nodes > 0).then_some(m.max_nodes)),
+        stop_at_first_feasible: cfg
+            .mip_options
+            .as_ref()
+            .is_some_and(|m| m.stop_at_first_feasible),
+        verbose: cfg.verbose,
+        memory_limit_mb: if cfg.memory_limit_mb > 0 {
+            Some(cfg.memory_limit_mb)
+        } else {
+            None
+        },
+        solver_name: if cfg.solver_name.is_empty() {
+            None
+        } else {
+            Some(cfg.solver_name)
+        },
+        relax_integrality: cfg.relax_integrality,
+        explain: cfg.explain,
+        export,
+        values_by_name: cfg.values_by_name,
+        auto_scale: cfg.auto_scale,
+        presolve,
+        fixed_values: cfg.fixed_values.into_iter().collect(),
+        backend_options: cfg.backend_options.into_iter().collect(),
+        mip_start: cfg
+            .mip_options
+            .map(|m| m.start.into_iter().collect())
+            .unwrap_or_default(),
+        priority,
+        integer_rounding,
+        zero_threshold: (cfg.zero_threshold > 0.0).then_some(cfg.zero_threshold),
+        lp_algorithm,
+        skip_crossover: cfg.skip_crossover,
+        deadline: (cfg.deadline_unix_ms > 0)
+            .then(|| UNIX_EPOCH + Duration::from_millis(cfg.deadline_unix_ms)),
+    }
+}
+
+/// Convert protobuf solve priority to domain SolvePriority
+pub fn proto_to_domain_priority(priority: proto::solver_config::Priority) -> SolvePriority {
+    match priority {
+        proto::solver_config::Priority::Normal => SolvePriority::Normal,
+        proto::solver_config::Priority::High => SolvePriority::High,
+        proto::solver_config::Priority::Low => SolvePriority::Low,
+    }
+}
+
+/// Milliseconds since the Unix epoch of `time` (0 before it)
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+/// Convert domain SolvePriority to protobuf solve priority
+pub fn domain_to_proto_priority(priority: SolvePriority) -> proto::solver_config::Priority {
+    match priority {
+        SolvePriority::Normal => proto::solver_config::Priority::Normal,
+        SolvePriority::High => proto::solver_config::Priority::High,
+        SolvePriority::Low => proto::solver_config::Priority::Low,
+    }
+}
+
+/// Convert domain Variable to protobuf Variable
+pub fn domain_to_proto_variable(var: &Variable) -> proto::Variable {
+    let r#type = match var.variable_type {
+        VariableType::Continuous => proto::variable::VariableType::Continuous,
+        VariableType::Integer => proto::variable::VariableType::Integer,
+        VariableType::Binary => proto::variable::VariableType::Binary,
+    };
+
+    proto::Variable {
+        r#type: r#type as i32,
+        lower_bound: var.lower_bound,
+        upper_bound: var.upper_bound,
+        name: var.name.clone(),
+    }
+}
+
+fn domain_to_proto_constraint_type(constraint_type: ConstraintType) -> i32 {
+    let r#type = match constraint_type {
+        ConstraintType::LessThanOrEqual => proto::constraint::ConstraintType::LessThanOrEqual,
+        ConstraintType::Equal => proto::constraint::ConstraintType::Equal,
+        ConstraintType::GreaterThanOrEqual => proto::constraint::ConstraintType::GreaterThanOrEqual,
+    };
+    r#type as i32
+}
+
+/// Convert domain Constraint to protobuf Constraint
+pub fn domain_to_proto_constraint(constraint: &Constraint) -> proto::Constraint {
+    proto::Constraint {
+        r#type: domain_to_proto_constraint_type(constraint.constraint_type),
+        coefficients: constraint.coefficients.to_dense(),
+        bound: constraint.bound,
+        name: constraint.name.clone(),
+        variable_names: constraint.variable_names.clone(),
+    }
+}
+
+/// Convert domain Constraint to a protobuf SparseConstraint listing its non-zeros by index
+pub fn domain_to_proto_sparse_constraint(constraint: &Constraint) -> proto::SparseConstraint {
+    let (indices, values) = constraint
+        .coefficients
+        .iter()
+        .map(|(j, value)| (j as u32, value))
+        .unzip();
+
+    proto::SparseConstraint {
+        r#type: domain_to_proto_constraint_type(constraint.constraint_type),
+        indices,
+        variable_names: Vec::new(),
+        values,
+        bound: constraint.bound,
+        name: constraint.name.clone(),
+    }
+}
+
+/// Convert domain ObjectiveFunction to protobuf ObjectiveFunction
+pub fn domain_to_proto_objective(objective: &ObjectiveFunction) -> proto::ObjectiveFunction {
+    let r#type = match objective.optimization_type {
+        OptimizationType::Minimize => proto::objective_function::OptimizationType::Minimize,
+        OptimizationType::Maximize => proto::objective_function::OptimizationType::Maximize,
+    };
+
+    proto::ObjectiveFunction {
+        r#type: r#type as i32,
+        coefficients: objective.coefficients.clone(),
+        variable_names: objective.variable_names.clone(),
+    }
+}
+
+/// Convert domain SolverConfig to protobuf SolverConfig
+pub fn domain_to_proto_solver_config(cfg: &SolverConfig) -> proto::SolverConfig {
+    let solver = match cfg.backend {
+        SolverBackend::Auto => proto::solver_config::SolverBackend::Auto,
+        SolverBackend::CoinCbc => proto::solver_config::SolverBackend::CoinCbc,
+        SolverBackend::Highs => proto::solver_config::SolverBackend::Highs,
+        SolverBackend::Race => proto::solver_config::SolverBackend::Race,
+        SolverBackend::Assignment => proto::solver_config::SolverBackend::Assignment,
+    };
+    let presolve = if cfg.presolve {
+        proto::solver_config::PresolveLevel::PresolveOn
+    } else {
+        proto::solver_config::PresolveLevel::PresolveAuto
+    };
+
+    proto::SolverConfig {
+        solver: solver as i32,
+        time_limit: cfg.time_limit.unwrap_or(0.0),
+        max_iterations: cfg.max_iterations.unwrap_or(0),
+        verbose: cfg.verbose,
+        mip_options: (cfg.gap_tolerance.is_some()
+            || cfg.absolute_gap_tolerance.is_some()
+            || cfg.max_nodes.is_some()
+            || cfg.stop_at_first_feasible
+            || !cfg.mip_start.is_empty())
+        .then(|| proto::MipOptions {
+            gap_tolerance: cfg.gap_tolerance.unwrap_or(0.0),
+            max_nodes: cfg.max_nodes.unwrap_or(0),
+            stop_at_first_feasible: cfg.stop_at_first_feasible,
+            absolute_gap_tolerance: cfg.absolute_gap_tolerance.unwrap_or(0.0),
+            start: cfg
+                .mip_start
+                .iter()
+                .map(|(name, &value)| (name.clone(), value))
+                .collect(),
+            ..Default::default()
+        }),
+        presolve: presolve as i32,
+        memory_limit_mb: cfg.memory_limit_mb.unwrap_or(0),
+        solver_name: cfg.solver_name.clone().unwrap_or_default(),
+        relax_integrality: cfg.relax_integrality,
+        explain: cfg.explain,
+        auto_scale: cfg.auto_scale,
+        export_format: match cfg.export {
+            None => proto::solver_config::ExportFormat::ExportNone,
+            Some(ExportFormat::Csv) => proto::solver_config::ExportFormat::Csv,
+            Some(ExportFormat::Json) => proto::solver_config::ExportFormat::Json,
+        } as i32,
+        values_by_name: cfg.values_by_name,
+        fixed_values: cfg
+            .fixed_values
+            .iter()
+            .map(|(name, &value)| (name.clone(), value))
+            .collect(),
+        backend_options: cfg
+            .backend_options
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+        priority: domain_to_proto_priority(cfg.priority) as i32,
+        integer_rounding: match cfg.integer_rounding {
+            IntegerRounding::Snap => proto::solver_config::IntegerRounding::RoundSnap,
+            IntegerRounding::Repair => proto::solver_config::IntegerRounding::RoundRepair,
+            IntegerRounding::Off => proto::solver_config::IntegerRounding::RoundOff,
+        } as i32,
+        zero_threshold: cfg.zero_threshold.unwrap_or(0.0),
+        lp_algorithm: match cfg.lp_algorithm {
+            LpAlgorithm::Auto => proto::solver_config::LpAlgorithm::LpAuto,
+            LpAlgorithm::DualSimplex => proto::solver_config::LpAlgorithm::LpDualSimplex,
+            LpAlgorithm::PrimalSimplex => proto::solver_config::LpAlgorithm::LpPrimalSimplex,
+            LpAlgorithm::InteriorPoint => proto::solver_config::LpAlgorithm::LpInteriorPoint,
+        } as i32,
+        skip_crossover: cfg.skip_crossover,
+        deadline_unix_ms: cfg.deadline.map(unix_ms).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// Convert domain OptimizationProblem to protobuf OptimizationProblem
+///
+/// The inverse of [`proto_to_domain_problem`], except that a problem declaring no
+/// variables comes back with the default ones.
+pub fn domain_to_proto_problem(problem: &OptimizationProblem) -> proto::OptimizationProblem {
+    proto::OptimizationProblem {
+        objective: Some(domain_to_proto_objective(&problem.objective)),
+        constraints: problem
+            .constraints
+            .iter()
+            .map(domain_to_proto_constraint)
+            .collect(),
+        variables: problem
+            .variables
+            .iter()
+            .map(domain_to_proto_variable)
+            .collect(),
+        solver_config: Some(domain_to_proto_solver_config(&problem.solver_config)),
+        problem_name: problem.name.clone(),
+        description: problem.description.clone(),
+        tags: problem.tags.clone().into_iter().collect(),
+    }
+}
+
+/// Split a problem into stream chunks of roughly `chunk_bytes` each
+///
+/// Metadata, solver config and objective come first, each in one chunk, then
+/// variables and sparse constraints in batches, keeping constraint order. A single
+/// oversized entity still goes out alone in its own chunk. Assembling the chunks
+/// gives back the same problem as [`domain_to_proto_problem`].
+pub fn problem_to_chunks(
+    problem: &OptimizationProblem,
+    chunk_bytes: usize,
+) -> Vec<proto::ProblemChunk> {
+    use prost::Message;
+    use proto::problem_chunk::Chunk;
+
+    let mut chunks = vec![
+        Chunk::Metadata(proto::ProblemMetadata {
+            problem_name: problem.name.clone(),
+            description: problem.description.clone(),
+            tags: problem.tags.clone().into_iter().collect(),
+        }),
+        Chunk::SolverConfig(domain_to_proto_solver_config(&problem.solver_config)),
+        Chunk::Objective(domain_to_proto_objective(&problem.objective)),
+    ];
+
+    let mut batch = proto::VariableBatch::default();
+    for var in problem.variables.iter().map(domain_to_proto_variable) {
+        if !batch.variables.is_empty() && batch.encoded_len() + var.encoded_len() > chunk_bytes {
+            chunks.push(Chunk::VariableBatch(std::mem::take(&mut batch)));
+        }
+        batch.variables.push(var);
+    }
+    if !batch.variables.is_empty() {
+        chunks.push(Chunk::VariableBatch(batch));
+    }
+
+    let num_vars = problem.num_variables();
+    let mut batch = proto::ConstraintBatch::default();
+    for constraint in &problem.constraints {
+        // Sparse rows carry neither a length nor per-term names, so rows that need
+        // either go dense: a mis-sized row must still reach the server to be rejected
+        if constraint.num_variables() != num_vars || !constraint.variable_names.is_empty() {
+            if !batch.sparse_constraints.is_empty() {
+                chunks.push(Chunk::ConstraintBatch(std::mem::take(&mut batch)));
+            }
+            chunks.push(Chunk::Constraint(domain_to_proto_constraint(constraint)));
+            continue;
+        }
+
+        let row = domain_to_proto_sparse_constraint(constraint);
+        if !batch.sparse_constraints.is_empty()
+            && batch.encoded_len() + row.encoded_len() > chunk_bytes
+        {
+            chunks.push(Chunk::ConstraintBatch(std::mem::take(&mut batch)));
+        }
+        batch.sparse_constraints.push(row);
+    }
+    if !batch.sparse_constraints.is_empty() {
+        chunks.push(Chunk::ConstraintBatch(batch));
+    }
+
+    chunks
+        .into_iter()
+        .map(|chunk| proto::ProblemChunk { chunk: Some(chunk) })
+        .collect()
+}
+
+/// Split a result into a summary followed by slices of at most `slice_len` values
+///
+/// Vectors go out in field order: solution values, duals, reduced costs, slacks.
+pub fn result_to_chunks(
+    mut result: proto::OptimizationResult,
+    slice_len: usize,
+) -> Vec<proto::SolutionChunk> {
+    use proto::solution_slice::Vector;
+
+    let vectors = [
+        (
+            Vector::SolutionValues,
+            std::mem::take(&mut result.solution_values),
+        ),
+        (Vector::DualValues, std::mem::take(&mut result.dual_values)),
+        (
+            Vector::ReducedCosts,
+            std::mem::take(&mut result.reduced_costs),
+        ),
+        (
+            Vector::SlackValues,
+            std::mem::take(&mut result.slack_values),
+        ),
+    ];
+
+    let mut chunks = vec![proto::SolutionChunk {
+        chunk: Some(proto::solution_chunk::Chunk::Summary(Box::new(result))),
+    }];
+    for (vector, values) in vectors {
+        for (i, slice) in values.chunks(slice_len.max(1)).enumerate() {
+            chunks.push(proto::SolutionChunk {
+                chunk: Some(proto::solution_chunk::Chunk::Slice(proto::SolutionSlice {
+                    vector: vector as i32,
+                    offset: (i * slice_len.max(1)) as u32,
+                    values: slice.to_vec(),
+                })),
+            });
+        }
+    }
+    chunks
+}
+
+/// Convert a domain SolutionExplanation to protobuf
+pub fn explanation_to_proto(explanation: SolutionExplanation) -> proto::SolutionExplanation {
+    proto::SolutionExplanation {
+        binding_constraints: explanation
+            .binding_constraints
+            .into_iter()
+            .map(|c| proto::BindingConstraint {
+                index: c.index as u32,
+                name: c.name,
+                activity: c.activity,
+                bound: c.bound,
+                slack: c.slack,
+                dual: c.dual,
+            })
+            .collect(),
+        top_contributors: explanation
+            .top_contributors
+            .into_iter()
+            .map(|c| proto::ObjectiveContribution {
+                index: c.index as u32,
+                name: c.name,
+                value: c.value,
+                coefficient: c.coefficient,
+                contribution: c.contribution,
+            })
+            .collect(),
+    }
+}
+
+/// Convert a domain DifficultyFactor to protobuf
+pub fn difficulty_factor_to_proto(factor: DifficultyFactor) -> proto::DifficultyFactor {
+    proto::DifficultyFactor {
+        contribution: factor.contribution(),
+        name: factor.name,
+        score: factor.score,
+        weight: factor.weight,
+        detail: factor.detail,
+    }
+}
+
+/// Convert domain MagnitudeRange to protobuf, with its ratio filled in
+fn domain_to_proto_range(range: MagnitudeRange) -> proto::MagnitudeRange {
+    proto::MagnitudeRange {
+        min_abs: range.min_abs,
+        max_abs: range.max_abs,
+        ratio: range.ratio(),
+    }
+}
+
+/// Convert domain ModelStatistics to protobuf
+pub fn model_statistics_to_proto(stats: ModelStatistics) -> proto::ModelStatistics {
+    let range = domain_to_proto_range;
+
+    proto::ModelStatistics {
+        num_variables: stats.num_variables as u32,
+        num_constraints: stats.num_constraints as u32,
+        num_nonzeros: stats.num_nonzeros as u64,
+        density: stats.density,
+        num_continuous: stats.num_continuous as u32,
+        num_integer: stats.num_integer as u32,
+        num_binary: stats.num_binary as u32,
+        matrix_range: Some(range(stats.matrix_range)),
+        objective_range: Some(range(stats.objective_range)),
+        rhs_range: Some(range(stats.rhs_range)),
+        bound_range: Some(range(stats.bound_range)),
+        row_structure: Some(proto::RowStructure {
+            empty: stats.rows.empty as u32,
+            singleton: stats.rows.singleton as u32,
+            set_partitioning: stats.rows.set_partitioning as u32,
+            set_packing: stats.rows.set_packing as u32,
+            set_covering: stats.rows.set_covering as u32,
+            knapsack: stats.rows.knapsack as u32,
+            general: stats.rows.general as u32,
+        }),
+    }
+}
+
+/// Convert a domain SolutionCheck to protobuf
+pub fn solution_check_to_proto(check: SolutionCheck) -> proto::VerificationResult {
+    proto::VerificationResult {
+        is_feasible: check.is_feasible(),
+        objective_value: check.objective_value,
+        constraint_violations: check
+            .constraint_violations
+            .into_iter()
+            .map(|v| proto::ConstraintViolation {
+                index: v.index as u32,
+                name: v.name,
+                activity: v.activity,
+                bound: v.bound,
+                violation: v.violation,
+            })
+            .collect(),
+        variable_violations: check
+            .variable_violations
+            .into_iter()
+            .map(|v| proto::VariableViolation {
+                index: v.index as u32,
+                name: v.name,
+                value: v.value,
+                kind: match v.kind {
+                    VariableViolationKind::Bound => proto::variable_violation::Kind::Bound,
+                    VariableViolationKind::Integrality => {
+                        proto::variable_violation::Kind::Integrality
+                    }
+                } as i32,
+                violation: v.violation,
+            })
+            .collect(),
+        max_constraint_violation: check.max_constraint_violation,
+        max_bound_violation: check.max_bound_violation,
+        max_integrality_violation: check.max_integrality_violation,
+    }
+}
+
+/// Convert a domain SolutionDiff to protobuf, grouped by name prefix
+pub fn solution_diff_to_proto(diff: SolutionDiff) -> proto::SolutionDiff {
+    let groups = diff
+        .groups()
+        .into_iter()
+        .map(|group| proto::ChangeGroup {
+            net_change: group.net_change(),
+            movement: group.movement(),
+            prefix: group.prefix,
+            changes: group
+                .changes
+                .into_iter()
+                .map(|change| proto::VariableChange {
+                    index: change.index as u32,
+                    name: change.name,
+                    before: change.before,
+                    after: change.after,
+                })
+                .collect(),
+        })
+        .collect();
+    proto::SolutionDiff {
+        groups,
+        objective_change: diff.objective_change,
+        threshold: diff.threshold,
+    }
+}
+
+/// Convert a protobuf SolutionDiff to domain, its changes back in index order
+pub fn proto_to_domain_solution_diff(diff: proto::SolutionDiff) -> SolutionDiff {
+    let mut changes: Vec<VariableChange> = diff
+        .groups
+        .into_iter()
+        .flat_map(|group| group.changes)
+        .map(|change| VariableChange {
+            index: change.index as usize,
+            name: change.name,
+            before: change.before,
+            after: change.after,
+        })
+        .collect();
+    changes.sort_by_key(|change| change.index);
+    SolutionDiff {
+        changes,
+        objective_change: diff.objective_change,
+        threshold: diff.threshold,
+    }
+}
+
+/// Problem file format of a URL request; None leaves it to the URL's extension
+pub fn proto_to_domain_problem_format(
+    format: i32,
+) -> std::result::Result<Option<ProblemFormat>, Box<Status>> {
+    use proto::url_problem_request::Format;
+    match Format::try_from(format) {
+        Ok(Format::Auto) => Ok(None),
+        Ok(Format::Mps) => Ok(Some(ProblemFormat::Mps)),
+        Ok(Format::Lp) => Ok(Some(ProblemFormat::Lp)),
+        Ok(Format::Json) => Ok(Some(ProblemFormat::Json)),
+        Err(_) => Err(Box::new(Status::invalid_argument(format!(
+            "Unknown problem file format {}",
+            format
+        )))),
+    }
+}
+
+pub fn domain_to_proto_url_request(
+    url: &str,
+    format: Option<ProblemFormat>,
+    config: &SolverConfig,
+) -> proto::UrlProblemRequest {
+    use proto::url_problem_request::Format;
+    let format = match format {
+        None => Format::Auto,
+        Some(ProblemFormat::Mps) => Format::Mps,
+        Some(ProblemFormat::Lp) => Format::Lp,
+        Some(ProblemFormat::Json) => Format::Json,
+    };
+    proto::UrlProblemRequest {
+        url: url.to_string(),
+        format: format as i32,
+        config: Some(domain_to_proto_solver_config(config)),
+    }
+}
+
+/// Convert a protobuf scenario to the domain one
+pub fn proto_to_domain_scenario(scenario: proto::Scenario) -> Scenario {
+    Scenario {
+        name: scenario.name,
+        rhs: scenario
+            .rhs_changes
+            .into_iter()
+            .map(|change| (change.constraint_index as usize, change.bound))
+            .collect(),
+        bounds: scenario
+            .bound_changes
+            .into_iter()
+            .map(|change| {
+                (
+                    change.variable_index as usize,
+                    change.lower_bound,
+                    change.upper_bound,
+                )
+            })
+            .collect(),
+        objective: scenario
+            .objective_changes
+            .into_iter()
+            .map(|change| (change.variable_index as usize, change.coefficient))
+            .collect(),
+    }
+}
+
+/// Convert a domain scenario to protobuf
+pub fn domain_to_proto_scenario(scenario: &Scenario) -> proto::Scenario {
+    proto::Scenario {
+        name: scenario.name.clone(),
+        rhs_changes: scenario
+            .rhs
+            .iter()
+            .map(|&(row, bound)| proto::RhsChange {
+                constraint_index: row as u32,
+                bound,
+            })
+            .collect(),
+        bound_changes: scenario
+            .bounds
+            .iter()
+            .map(|&(column, lower, upper)| proto::BoundChange {
+                variable_index: column as u32,
+                lower_bound: lower,
+                upper_bound: upper,
+            })
+            .collect(),
+        objective_changes: scenario
+            .objective
+            .iter()
+            .map(|&(column, coefficient)| proto::CostChange {
+                variable_index: column as u32,
+                coefficient,
+            })
+            .collect(),
+    }
+}
+
+/// Convert a protobuf model update to the domain one, leaving out the session ID
+pub fn proto_to_domain_model_update(
+    request: proto::ModelUpdateRequest,
+) -> std::result::Result<ModelUpdate, Box<Status>> {
+    let changes = proto_to_domain_scenario(proto::Scenario {
+        name: String::new(),
+        rhs_changes: request.rhs_changes,
+        bound_changes: request.bound_changes,
+        objective_changes: request.objective_changes,
+    });
+    let constraints = request
+        .new_constraints
+        .into_iter()
+        .map(proto_to_domain_constraint)
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(ModelUpdate {
+        changes,
+        constraints,
+    })
+}
+
+/// Convert a domain model update to protobuf, for session `session_id`
+pub fn domain_to_proto_model_update(
+    session_id: &str,
+    update: &ModelUpdate,
+) -> proto::ModelUpdateRequest {
+    let changes = domain_to_proto_scenario(&update.changes);
+    proto::ModelUpdateRequest {
+        session_id: session_id.to_string(),
+        rhs_changes: changes.rhs_changes,
+        bound_changes: changes.bound_changes,
+        objective_changes: changes.objective_changes,
+        new_constraints: update
+            .constraints
+            .iter()
+            .map(domain_to_proto_constraint)
+            .collect(),
+    }
+}
+
+/// Describe an open session for the protobuf API
+pub fn session_to_proto(
+    session_id: &str,
+    backend: &str,
+    session: &dyn ModelSession,
+) -> proto::ModelSessionInfo {
+    let problem = session.problem();
+    proto::ModelSessionInfo {
+        session_id: session_id.to_string(),
+        num_variables: problem.num_variables() as u32,
+        num_constraints: problem.constraints.len() as u32,
+        solver_backend: backend.to_string(),
+        incremental: session.is_incremental(),
+    }
+}
+
+/// Convert a protobuf sweep request to the domain sweep, leaving out the problem
+pub fn proto_to_domain_sweep(
+    request: proto::SweepRequest,
+) -> std::result::Result<ParametricSweep, Box<Status>> {
+    let name = request.name;
+    let target = match proto::sweep_request::Target::try_from(request.target) {
+        Ok(proto::sweep_request::Target::ObjectiveCoefficient) => {
+            SweepTarget::ObjectiveCoefficient(name)
+        }
+        Ok(proto::sweep_request::Target::Rhs) => SweepTarget::Rhs(name),
+        Ok(proto::sweep_request::Target::LowerBound) => SweepTarget::LowerBound(name),
+        Ok(proto::sweep_request::Target::UpperBound) => SweepTarget::UpperBound(name),
+        Err(_) => {
+            return Err(Box::new(Status::invalid_argument(format!(
+                "Unknown sweep target {}",
+                request.target
+            ))))
+        }
+    };
+    Ok(ParametricSweep::new(
+        target,
+        request.from,
+        request.to,
+        request.steps as usize,
+    ))
+}
+
+/// Convert a domain sweep to a protobuf request for `problem`
+pub fn domain_to_proto_sweep(
+    problem: &OptimizationProblem,
+    sweep: &ParametricSweep,
+    include_solutions: bool,
+) -> proto::SweepRequest {
+    let target = match sweep.target {
+        SweepTarget::ObjectiveCoefficient(_) => proto::sweep_request::Target::ObjectiveCoefficient,
+        SweepTarget::Rhs(_) => proto::sweep_request::Target::Rhs,
+        SweepTarget::LowerBound(_) => proto::sweep_request::Target::LowerBound,
+        SweepTarget::UpperBound(_) => proto::sweep_request::Target::UpperBound,
+    };
+    proto::SweepRequest {
+        problem: Some(domain_to_proto_problem(problem)),
+        target: target as i32,
+        name: sweep.target.name().to_string(),
+        from: sweep.from,
+        to: sweep.to,
+        steps: sweep.steps as u32,
+        include_solutions,
+    }
+}
+
+/// Build the request for a frontier of `problem`'s objective against `second`
+pub fn domain_to_proto_pareto_request(
+    problem: &OptimizationProblem,
+    second: &ObjectiveFunction,
+    points: usize,
+    include_solutions: bool,
+) -> proto::ParetoRequest {
+    proto::ParetoRequest {
+        problem: Some(domain_to_proto_problem(problem)),
+        second_objective: Some(domain_to_proto_objective(second)),
+        points: points as u32,
+        include_solutions,
+    }
+}
+
+/// Convert a domain ParetoFrontier to protobuf, with each point's full result only
+/// if `include_solutions`
+pub fn domain_to_proto_pareto_frontier(
+    frontier: ParetoFrontier,
+    solver_name: &str,
+    include_solutions: bool,
+) -> proto::ParetoFrontier {
+    proto::ParetoFrontier {
+        points: frontier
+            .points
+            .into_iter()
+            .map(|point| proto::ParetoPoint {
+                first_objective: point.first,
+                second_objective: point.second,
+                status: solution_status_to_proto(point.solution.status) as i32,
+                result: include_solutions
+                    .then(|| domain_to_proto_solution(point.solution, solver_name)),
+            })
+            .collect(),
+        status: solution_status_to_proto(frontier.status) as i32,
+        solves: frontier.solves as u32,
+    }
+}
+
+/// Convert a protobuf ParetoFrontier to domain; points sent without their result
+/// keep just their status and first objective
+pub fn proto_to_domain_pareto_frontier(
+    frontier: proto::ParetoFrontier,
+) -> std::result::Result<ParetoFrontier, Box<Status>> {
+    let points = frontier
+        .points
+        .into_iter()
+        .map(|point| {
+            let solution = match point.result {
+                Some(result) => proto_to_domain_solution(result)?,
+                None => {
+                    let status = proto_to_domain_solution_status(point.status)?;
+                    let mut solution = Solution::new(status, String::new());
+                    solution.optimal_value = Some(point.first_objective);
+                    solution
+                }
+            };
+            Ok(ParetoPoint {
+                first: point.first_objective,
+                second: point.second_objective,
+                solution,
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, Box<Status>>>()?;
+    Ok(ParetoFrontier {
+        points,
+        status: proto_to_domain_solution_status(frontier.status)?,
+        solves: frontier.solves as usize,
+    })
+}
+
+/// Convert domain SolutionStatus to protobuf
+pub fn solution_status_to_proto(status: SolutionStatus) -> proto::SolutionStatus {
+    match status {
+        SolutionStatus::Optimal => proto::SolutionStatus::Optimal,
+        SolutionStatus::Feasible => proto::SolutionStatus::Feasible,
+        SolutionStatus::Infeasible => proto::SolutionStatus::Infeasible,
+        SolutionStatus::Unbounded => proto::SolutionStatus::Unbounded,
+        SolutionStatus::TimeLimit => proto::SolutionStatus::TimeLimit,
+        SolutionStatus::IterationLimit => proto::SolutionStatus::IterationLimit,
+        SolutionStatus::NodeLimit => proto::SolutionStatus::NodeLimit,
+        SolutionStatus::Error => proto::SolutionStatus::Error,
+        SolutionStatus::Interrupted => proto::SolutionStatus::Interrupted,
+        SolutionStatus::MemoryLimit => proto::SolutionStatus::MemoryLimit,
+    }
+}
+
 /// Convert domain Solution to protobuf OptimizationResult
 pub fn domain_to_proto_solution(
     solution: Solution,
     solver_name: &str,
 ) -> proto::OptimizationResult {
-    let status = match solution.status {
-        SolutionStatus::Optimal => proto::SolutionStatus::Optimal as i32,
-        SolutionStatus::Feasible => proto::SolutionStatus::Feasible as i32,
-        SolutionStatus::Infeasible => proto::SolutionStatus::Infeasible as i32,
-        SolutionStatus::Unbounded => proto::SolutionStatus::Unbounded as i32,
-        SolutionStatus::TimeLimit => proto::SolutionStatus::TimeLimit as i32,
-        SolutionStatus::IterationLimit => proto::SolutionStatus::IterationLimit as i32,
-        SolutionStatus::NodeLimit => proto::SolutionStatus::NodeLimit as i32,
-        SolutionStatus::Error => proto::SolutionStatus::Error as i32,
-        SolutionStatus::Interrupted => proto::SolutionStatus::Interrupted as i32,
-    };
-
     proto::OptimizationResult {
-        status,
+        status: solution_status_to_proto(solution.status) as i32,
         optimal_value: solution.optimal_value,
         best_bound: solution.best_bound,
         gap: solution.gap,
@@ -185,13 +1166,411 @@ pub fn domain_to_proto_solution(
             num_constraints: solution.statistics.num_constraints,
             num_integer_vars: solution.statistics.num_integer_vars,
             num_binary_vars: solution.statistics.num_binary_vars,
-            solver_backend: solver_name.to_string(),
+            presolve: solution
+                .statistics
+                .presolve
+                .as_ref()
+                .map(|report| proto::PresolveReport {
+                    removed_variables: report.removed_variables as u32,
+                    removed_constraints: report.removed_constraints as u32,
+                    tightened_bounds: report.tightened_bounds as u32,
+                }),
+            resources: solution
+                .statistics
+                .resources
+                .map(|usage| proto::ResourceUsage {
+                    wall_time_ms: usage.wall_time_ms,
+                    cpu_time_ms: usage.cpu_time_ms,
+                    peak_rss_bytes: usage.peak_rss_bytes,
+                }),
+            conditioning: solution
+                .statistics
+                .conditioning
+                .map(|basis| proto::BasisConditioning {
+                    condition_estimate: basis.condition_estimate,
+                    coefficient_range: Some(domain_to_proto_range(basis.coefficient_range)),
+                }),
+            solver_backend: solution
+                .statistics
+                .solved_by
+                .unwrap_or_else(|| solver_name.to_string()),
         }),
         quality: Some(proto::SolutionQuality {
             max_constraint_violation: solution.quality.max_constraint_violation,
-            max_relative_violation: 0.0,
+            max_relative_violation: solution.quality.max_relative_violation,
             max_integrality_violation: solution.quality.max_integrality_violation,
             reliability: solution.quality.reliability,
+            snapped_integers: solution.quality.snapped_integers as u32,
+            rounding_violation: solution.quality.rounding_violation,
+            zeroed_values: solution.quality.zeroed_values as u32,
         }),
+        explanation: None,
+        export: String::new(),
+        solution_by_name: HashMap::new(),
+        dual_by_name: HashMap::new(),
+        slack_by_name: HashMap::new(),
+        reduced_cost_by_name: HashMap::new(),
+        tags: solution.tags.into_iter().collect(),
+        infeasibility_certificate: solution.infeasibility_certificate.map(|certificate| {
+            proto::InfeasibilityCertificate {
+                multipliers: certificate.multipliers,
+                constraints: certificate
+                    .constraints
+                    .into_iter()
+                    .map(|row| proto::CertificateRow {
+                        index: row.index as u32,
+                        name: row.name,
+                        multiplier: row.multiplier,
+                    })
+                    .collect(),
+                infeasibility: certificate.infeasibility,
+            }
+        }),
+        unbounded_ray: solution.unbounded_ray.map(|ray| proto::UnboundedRay {
+            direction: ray.direction,
+            components: ray
+                .components
+                .into_iter()
+                .map(|component| proto::RayComponent {
+                    index: component.index as u32,
+                    name: component.name,
+                    direction: component.direction,
+                })
+                .collect(),
+            objective_rate: ray.objective_rate,
+        }),
+    }
+}
+
+/// Convert a protobuf solution status to the domain one
+pub fn proto_to_domain_solution_status(
+    status: i32,
+) -> std::result::Result<SolutionStatus, Box<Status>> {
+    let status = match proto::SolutionStatus::try_from(status) {
+        Ok(proto::SolutionStatus::Optimal) => SolutionStatus::Optimal,
+        Ok(proto::SolutionStatus::Feasible) => SolutionStatus::Feasible,
+        Ok(proto::SolutionStatus::Infeasible) => SolutionStatus::Infeasible,
+        Ok(proto::SolutionStatus::Unbounded) => SolutionStatus::Unbounded,
+        Ok(proto::SolutionStatus::TimeLimit) => SolutionStatus::TimeLimit,
+        Ok(proto::SolutionStatus::IterationLimit) => SolutionStatus::IterationLimit,
+        Ok(proto::SolutionStatus::NodeLimit) => SolutionStatus::NodeLimit,
+        Ok(proto::SolutionStatus::Error) => SolutionStatus::Error,
+        Ok(proto::SolutionStatus::Interrupted) => SolutionStatus::Interrupted,
+        Ok(proto::SolutionStatus::MemoryLimit) => SolutionStatus::MemoryLimit,
+        Err(_) => {
+            return Err(Box::new(Status::unknown(format!(
+                "Unknown solution status {}",
+                status
+            ))))
+        }
+    };
+    Ok(status)
+}
+
+/// Convert protobuf OptimizationResult to domain Solution
+///
+/// The inverse of [`domain_to_proto_solution`]. Reduced costs, slacks and the
+/// explanation have no place in the domain model and are dropped.
+pub fn proto_to_domain_solution(
+    result: proto::OptimizationResult,
+) -> std::result::Result<Solution, Box<Status>> {
+    let statistics = result
+        .statistics
+        .map(|stats| SolverStatistics {
+            simplex_iterations: stats.simplex_iterations,
+            nodes_explored: stats.nodes_explored,
+            solve_time_ms: stats.solve_time_ms,
+            num_variables: stats.num_variables,
+            num_constraints: stats.num_constraints,
+            num_integer_vars: stats.num_integer_vars,
+            num_binary_vars: stats.num_binary_vars,
+            solved_by: Some(stats.solver_backend).filter(|name| !name.is_empty()),
+            presolve: stats.presolve.map(|report| PresolveReport {
+                removed_variables: report.removed_variables as usize,
+                removed_constraints: report.removed_constraints as usize,
+                tightened_bounds: report.tightened_bounds as usize,
+            }),
+            resources: stats.resources.map(|usage| ResourceUsage {
+                wall_time_ms: usage.wall_time_ms,
+                cpu_time_ms: usage.cpu_time_ms,
+                peak_rss_bytes: usage.peak_rss_bytes,
+            }),
+            conditioning: stats.conditioning.map(|basis| BasisConditioning {
+                condition_estimate: basis.condition_estimate,
+                coefficient_range: basis
+                    .coefficient_range
+                    .map(|range| MagnitudeRange {
+                        min_abs: range.min_abs,
+                        max_abs: range.max_abs,
+                    })
+                    .unwrap_or_default(),
+            }),
+        })
+        .unwrap_or_default();
+    let quality = result
+        .quality
+        .map(|quality| SolutionQuality {
+            max_constraint_violation: quality.max_constraint_violation,
+            max_relative_violation: quality.max_relative_violation,
+            max_integrality_violation: quality.max_integrality_violation,
+            reliability: quality.reliability,
+            snapped_integers: quality.snapped_integers as usize,
+            rounding_violation: quality.rounding_violation,
+            zeroed_values: quality.zeroed_values as usize,
+        })
+        .unwrap_or_default();
+
+    Ok(Solution {
+        status: proto_to_domain_solution_status(result.status)?,
+        optimal_value: result.optimal_value,
+        best_bound: result.best_bound,
+        gap: result.gap,
+        variable_values: result.solution_values,
+        dual_values: result.dual_values,
+        message: result.message,
+        statistics,
+        quality,
+        tags: result.tags.into_iter().collect(),
+        infeasibility_certificate: result.infeasibility_certificate.map(|certificate| {
+            InfeasibilityCertificate {
+                multipliers: certificate.multipliers,
+                constraints: certificate
+                    .constraints
+                    .into_iter()
+                    .map(|row| CertificateRow {
+                        index: row.index as usize,
+                        name: row.name,
+                        multiplier: row.multiplier,
+                    })
+                    .collect(),
+                infeasibility: certificate.infeasibility,
+            }
+        }),
+        unbounded_ray: result.unbounded_ray.map(|ray| UnboundedRay {
+            direction: ray.direction,
+            components: ray
+                .components
+                .into_iter()
+                .map(|component| RayComponent {
+                    index: component.index as usize,
+                    name: component.name,
+                    direction: component.direction,
+                })
+                .collect(),
+            objective_rate: ray.objective_rate,
+        }),
+    })
+}
+
+/// Convert an active job snapshot to protobuf JobInfo
+#[cfg(feature = "server")]
+pub fn job_to_proto(job: &JobSnapshot) -> proto::JobInfo {
+    let state = match job.state {
+        JobState::Queued => proto::job_info::JobState::Queued as i32,
+        JobState::Running => proto::job_info::JobState::Running as i32,
+    };
+
+    proto::JobInfo {
+        job_id: job.id,
+        state,
+        problem_name: job.problem_name.clone(),
+        num_variables: job.num_variables as u32,
+        num_constraints: job.num_constraints as u32,
+        num_integer_vars: job.num_integer_vars as u32,
+        elapsed_ms: job.elapsed.as_secs_f64() * 1000.0,
+        running_ms: job.running.as_secs_f64() * 1000.0,
+        solver_backend: job.backend.clone(),
+        incumbent_objective: job.incumbent,
+        best_bound: job.best_bound,
+        nodes_explored: job.nodes_explored,
+        priority: domain_to_proto_priority(job.priority) as i32,
+        deadline_unix_ms: job.deadline.map(unix_ms).unwrap_or_default(),
+        tags: job.tags.clone().into_iter().collect(),
+    }
+}
+
+/// Convert a job's log line to protobuf SolverLogLine
+#[cfg(feature = "server")]
+pub fn log_line_to_proto(line: LogLine, dropped_lines: u64) -> proto::SolverLogLine {
+    proto::SolverLogLine {
+        text: line.text,
+        elapsed_secs: line.elapsed.as_secs_f64(),
+        dropped_lines,
+    }
+}
+
+/// Convert a saved problem's summary to protobuf StoredProblem
+#[cfg(feature = "server")]
+pub fn stored_problem_to_proto(info: StoredProblemInfo) -> proto::StoredProblem {
+    proto::StoredProblem {
+        id: info.id,
+        name: info.name,
+        num_variables: info.num_variables as u32,
+        num_constraints: info.num_constraints as u32,
+        num_integer_vars: info.num_integer_vars as u32,
+        saved_unix_ms: unix_ms(info.saved_at),
+        tags: info.tags.into_iter().collect(),
+    }
+}
+
+/// Convert a saved problem's solve record to protobuf SolveRecord
+#[cfg(feature = "server")]
+pub fn solve_record_to_proto(record: SolveRecord) -> proto::SolveRecord {
+    proto::SolveRecord {
+        solve_id: record.solve_id,
+        request_id: record.request_id,
+        solved_unix_ms: unix_ms(record.solved_at),
+        config: Some(domain_to_proto_solver_config(&record.config)),
+        overridden: record.overridden,
+        status: solution_status_to_proto(record.status) as i32,
+        objective: record.objective,
+        gap: record.gap,
+        backend: record.backend,
+        solve_time_ms: record.solve_time_ms,
+    }
+}
+
+/// Convert a statistics snapshot to protobuf ServerStats, with the current
+/// queue depth
+#[cfg(feature = "server")]
+pub fn server_stats_to_proto(
+    stats: &StatsSnapshot,
+    queued_solves: u32,
+    running_solves: u32,
+) -> proto::ServerStats {
+    proto::ServerStats {
+        uptime_secs: stats.uptime.as_secs_f64(),
+        total_solves: stats.solves,
+        solves_by_status: stats.by_status.clone().into_iter().collect(),
+        solves_by_backend: stats.by_backend.clone().into_iter().collect(),
+        mean_solve_ms: stats.mean_solve_ms,
+        p50_solve_ms: stats.p50_solve_ms,
+        p90_solve_ms: stats.p90_solve_ms,
+        p99_solve_ms: stats.p99_solve_ms,
+        cache_hits: stats.cache_hits,
+        cache_misses: stats.cache_misses,
+        cache_hit_rate: stats.cache_hit_rate(),
+        queued_solves,
+        running_solves,
+    }
+}
+
+/// Convert a registered solver description to protobuf SolverInfo
+#[cfg(feature = "server")]
+pub fn solver_info_to_proto(info: &SolverInfo) -> proto::SolverInfo {
+    proto::SolverInfo {
+        name: info.name.clone(),
+        version: info.version.clone(),
+        supports_mip: info.supports_mip,
+        supports_lp: info.supports_lp,
+        capabilities: info.capabilities.clone(),
+        key: info.key.clone(),
+    }
+}
+
+/// Convert a registered solver's options to protobuf SolverOptions
+#[cfg(feature = "server")]
+pub fn solver_options_to_proto(info: &SolverInfo) -> proto::SolverOptions {
+    let options = info
+        .options
+        .iter()
+        .map(|option| {
+            let kind = match option.kind {
+                OptionKind::Bool => proto::OptionType::BoolOption,
+                OptionKind::Int => proto::OptionType::IntOption,
+                OptionKind::Double => proto::OptionType::DoubleOption,
+                OptionKind::String => proto::OptionType::StringOption,
+            };
+            proto::SolverOption {
+                name: option.name.clone(),
+                r#type: kind as i32,
+                default_value: option.default.clone(),
+                min: option.min,
+                max: option.max,
+                choices: option.choices.clone(),
+                description: option.description.clone(),
+            }
+        })
+        .collect();
+
+    proto::SolverOptions {
+        key: info.key.clone(),
+        options,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_variables_take_the_objective_names() {
+        let objective = ObjectiveFunction::new(OptimizationType::Minimize, vec![1.0, 2.0, 3.0])
+            .with_names(vec!["a".to_string(), String::new()]);
+
+        let names: Vec<_> = default_variables(&objective)
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        assert_eq!(names, ["a", "x1", "x2"]);
+    }
+
+    #[test]
+    fn problem_conversion_moves_vectors_instead_of_copying() {
+        // Copying these vectors used to dominate converting large problems; rows
+        // are the exception, as only their non-zeros are kept
+        let objective = vec![1.0; 2000];
+        let mut row = vec![0.0; 2000];
+        row[1999] = 2.0;
+        let names = vec!["x".to_string(); 2000];
+        let (objective_ptr, names_ptr) = (objective.as_ptr(), names.as_ptr());
+        let proto = proto::OptimizationProblem {
+            objective: Some(proto::ObjectiveFunction {
+                coefficients: objective,
+                ..Default::default()
+            }),
+            constraints: vec![proto::Constraint {
+                coefficients: row,
+                variable_names: names,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let problem = proto_to_domain_problem(proto).unwrap();
+        assert_eq!(problem.objective.coefficients.as_ptr(), objective_ptr);
+        assert_eq!(
+            problem.constraints[0]
+                .coefficients
+                .iter()
+                .collect::<Vec<_>>(),
+            [(1999, 2.0)]
+        );
+        assert_eq!(problem.constraints[0].variable_names.as_ptr(), names_ptr);
+    }
+
+    #[test]
+    fn sparse_constraints_store_only_their_terms() {
+        let sparse = |indices: Vec<u32>| proto::SparseConstraint {
+            indices,
+            values: vec![2.0, -1.0],
+            name: "wide".to_string(),
+            ..Default::default()
+        };
+        let constraint =
+            proto_to_domain_sparse_constraint(sparse(vec![999_999, 5]), 1_000_000, &HashMap::new())
+                .unwrap();
+        assert_eq!(constraint.num_variables(), 1_000_000);
+        assert_eq!(
+            constraint.coefficients.iter().collect::<Vec<_>>(),
+            [(5, -1.0), (999_999, 2.0)]
+        );
+
+        let status =
+            proto_to_domain_sparse_constraint(sparse(vec![7, 7]), 10, &HashMap::new()).unwrap_err();
+        assert_eq!(
+            status.message(),
+            "Sparse constraint 'wide' lists variable 7 more than once"
+        );
     }
 }
@@ -1,6 +1,102 @@
 // Application layer module
+// Without `server`, only the protobuf mappers and the solver-agnostic Pareto
+// frontiers are built, so the messages can be used from wasm32
 
+#[cfg(feature = "server")]
+pub mod admin;
+#[cfg(feature = "server")]
+pub mod audit;
+#[cfg(feature = "server")]
+pub mod benders;
+pub mod error_details;
+#[cfg(feature = "server")]
 pub mod grpc_service;
+#[cfg(feature = "server")]
+pub mod http_client;
+#[cfg(any(feature = "server", feature = "client"))]
+pub mod idempotency;
+#[cfg(feature = "server")]
+pub mod jobs;
+#[cfg(feature = "server")]
+pub mod limiter;
 pub mod mappers;
+pub mod pareto;
+#[cfg(feature = "server")]
+pub mod problem_fetch;
+#[cfg(feature = "server")]
+pub mod problem_repository;
+#[cfg(feature = "server")]
+pub mod problem_stream;
+#[cfg(feature = "server")]
+pub mod publishing;
+#[cfg(feature = "server")]
+pub mod quotas;
+#[cfg(feature = "server")]
+pub mod recording;
+#[cfg(feature = "server")]
+pub mod request_id;
+#[cfg(feature = "server")]
+pub mod rolling_horizon;
+#[cfg(feature = "server")]
+pub mod routing;
+#[cfg(feature = "server")]
+pub mod server_stats;
+#[cfg(feature = "server")]
+pub mod sessions;
+#[cfg(feature = "server")]
+pub mod size_limits;
+#[cfg(feature = "server")]
+pub mod submissions;
+#[cfg(feature = "server")]
+pub mod tours;
+#[cfg(feature = "server")]
+pub mod webhook;
+#[cfg(feature = "server")]
+pub mod workers;
 
+#[cfg(feature = "server")]
+pub use audit::{AuditRecord, AuditSink, JsonlAuditLog};
+#[cfg(feature = "server")]
+pub use benders::{
+    BendersCut, BendersMaster, BendersSolution, BendersSolver, BendersSubproblem, LinearSubproblem,
+    SubproblemOutcome,
+};
+#[cfg(feature = "server")]
 pub use grpc_service::GrpcLpSolverService;
+#[cfg(feature = "server")]
+pub use jobs::{JobProgress, JobRegistry, JobSnapshot, JobState, LogLine};
+#[cfg(feature = "server")]
+pub use limiter::OverloadPolicy;
+pub use pareto::{ParetoFrontier, ParetoPoint, ParetoSolver};
+#[cfg(feature = "server")]
+pub use problem_fetch::{ProblemFetcher, UrlFetchConfig};
+#[cfg(feature = "server")]
+pub use problem_repository::{
+    DirectoryProblemRepository, ProblemRepository, SolveRecord, StoredProblemInfo,
+};
+#[cfg(feature = "server")]
+pub use problem_stream::ProblemAssembler;
+#[cfg(feature = "server")]
+pub use publishing::{ResultPublisher, SolveEvent};
+#[cfg(feature = "server")]
+pub use quotas::{QuotaLimits, QuotaPolicy, QuotaStatus, QuotaTracker};
+#[cfg(feature = "server")]
+pub use recording::SolveRecorder;
+#[cfg(feature = "server")]
+pub use rolling_horizon::{HorizonModel, RollingHorizonSolution, RollingHorizonSolver};
+#[cfg(feature = "server")]
+pub use routing::{ProblemKind, RoutingPolicy, RoutingRule};
+#[cfg(feature = "server")]
+pub use server_stats::{ServerStats, StatsSnapshot};
+#[cfg(feature = "server")]
+pub use sessions::SessionStore;
+#[cfg(feature = "server")]
+pub use size_limits::ProblemSizeLimits;
+#[cfg(feature = "server")]
+pub use submissions::{Submission, SubmittedResults};
+#[cfg(feature = "server")]
+pub use tours::{TourModel, TourSolution, TourSolver};
+#[cfg(feature = "server")]
+pub use webhook::{CompletionNotice, WebhookConfig, WebhookNotifier};
+#[cfg(feature = "server")]
+pub use workers::{RemoteSolver, WorkerPool};
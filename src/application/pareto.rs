@@ -0,0 +1,344 @@
+// Pareto frontiers: Trade-offs between two objectives by the epsilon-constraint method
+// The first objective is optimized while the second is held to a grid of levels
+// between its values at the two lexicographic optima, then dominated points are dropped
+
+use std::sync::Arc;
+
+use tracing::debug;
+
+use crate::domain::models::{Constraint, ObjectiveFunction, OptimizationProblem, Solution};
+use crate::domain::scenarios::Scenario;
+use crate::domain::solver_service::{
+    CancellationToken, NullObserver, Result, SolverError, SolverService,
+};
+use crate::domain::value_objects::{ConstraintType, OptimizationType, SolutionStatus};
+
+const DEFAULT_POINTS: usize = 10;
+
+/// Most levels one frontier may have
+pub const MAX_PARETO_POINTS: usize = 1_000;
+
+/// Slack on objective levels turned into constraints, relative to their size, so
+/// solver tolerances can't make a level infeasible
+const LEVEL_TOLERANCE: f64 = 1e-9;
+
+/// One non-dominated point
+#[derive(Debug, Clone)]
+pub struct ParetoPoint {
+    /// Value of the problem's own objective
+    pub first: f64,
+    /// Value of the second objective
+    pub second: f64,
+    pub solution: Solution,
+}
+
+/// Result of a frontier computation
+#[derive(Debug, Clone)]
+pub struct ParetoFrontier {
+    /// Non-dominated points, from the best first objective to the best second one
+    pub points: Vec<ParetoPoint>,
+    /// Optimal when every solve was; otherwise the status of the first that wasn't.
+    /// A first solve without a solution leaves no points.
+    pub status: SolutionStatus,
+    /// Number of solves made
+    pub solves: usize,
+}
+
+/// Computes two-objective Pareto frontiers with any backend
+///
+/// The two anchors are found lexicographically: each objective at its optimum
+/// with the other made as good as it can be there. The levels in between split the
+/// second objective's range evenly and are solved as scenarios of one problem, so
+/// backends that patch models reuse it.
+pub struct ParetoSolver {
+    solver: Arc<dyn SolverService>,
+    points: usize,
+}
+
+impl ParetoSolver {
+    pub fn new(solver: Arc<dyn SolverService>) -> Self {
+        Self {
+            solver,
+            points: DEFAULT_POINTS,
+        }
+    }
+
+    /// Levels of the second objective to try, both anchors included; at least 2
+    pub fn with_points(mut self, points: usize) -> Self {
+        self.points = points.max(2);
+        self
+    }
+
+    /// Frontier of the problem's objective against `second`
+    pub fn solve(
+        &self,
+        problem: &OptimizationProblem,
+        second: &ObjectiveFunction,
+    ) -> Result<ParetoFrontier> {
+        self.solve_cancellable(problem, second, &CancellationToken::new())
+    }
+
+    /// Compute, stopping between or during solves once `cancel` is set
+    pub fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        second: &ObjectiveFunction,
+        cancel: &CancellationToken,
+    ) -> Result<ParetoFrontier> {
+        let num_vars = problem.num_variables();
+        if second.coefficients.len() != num_vars {
+            return Err(SolverError::InvalidProblem(format!(
+                "Second objective has {} coefficients for {} variables",
+                second.coefficients.len(),
+                num_vars
+            )));
+        }
+        if second.coefficients.iter().any(|c| !c.is_finite()) {
+            return Err(SolverError::InvalidProblem(
+                "Second objective has non-finite coefficients".to_string(),
+            ));
+        }
+        if self.points > MAX_PARETO_POINTS {
+            return Err(SolverError::InvalidProblem(format!(
+                "Frontier has {} points, more than the {} allowed",
+                self.points, MAX_PARETO_POINTS
+            )));
+        }
+
+        let first = &problem.objective;
+        let mut run = Run {
+            solver: self.solver.as_ref(),
+            cancel,
+            status: SolutionStatus::Optimal,
+            solves: 0,
+        };
+        let frontier = |points: Vec<ParetoPoint>, run: Run| ParetoFrontier {
+            points: non_dominated(points, first, second),
+            status: run.status,
+            solves: run.solves,
+        };
+
+        // Best first objective, then the best second one that keeps it
+        let Some(best_first) = run.solve(problem)? else {
+            return Ok(frontier(Vec::new(), run));
+        };
+        let level = evaluate(first, &best_first.variable_values);
+        let anchor = with_objective(problem, second.clone()).add_constraint(level_row(
+            first,
+            level,
+            "pareto_first",
+        ));
+        let first_anchor = run.solve(&anchor)?.unwrap_or(best_first);
+
+        // Best second objective, then the best first one that keeps it
+        let Some(best_second) = run.solve(&with_objective(problem, second.clone()))? else {
+            return Ok(frontier(vec![point(first, second, first_anchor)], run));
+        };
+        let level = evaluate(second, &best_second.variable_values);
+        let anchor = problem
+            .clone()
+            .add_constraint(level_row(second, level, "pareto_second"));
+        let second_anchor = run.solve(&anchor)?.unwrap_or(best_second);
+
+        let from = evaluate(second, &first_anchor.variable_values);
+        let to = evaluate(second, &second_anchor.variable_values);
+        let mut points = vec![point(first, second, first_anchor)];
+        if self.points > 2 && (from - to).abs() > tolerance(from) {
+            // Levels strictly between the anchors, as right-hand sides of one row
+            let base = problem
+                .clone()
+                .add_constraint(level_row(second, from, "pareto_second"));
+            let row = base.constraints.len() - 1;
+            let last = (self.points - 1) as f64;
+            let scenarios: Vec<Scenario> = (1..self.points - 1)
+                .map(|k| {
+                    let level = from + (to - from) * k as f64 / last;
+                    Scenario::new(format!("second={}", level))
+                        .with_rhs(row, level_bound(second, level))
+                })
+                .collect();
+            debug!(
+                levels = scenarios.len(),
+                from, to, "Solving frontier levels"
+            );
+
+            let found: Vec<Solution> = run
+                .solve_scenarios(&base, &scenarios)?
+                .into_iter()
+                .flatten()
+                .collect();
+
+            // Each level's best first objective can come with a second one that isn't
+            // the best there, so improve the second with the first held
+            let base = with_objective(problem, second.clone()).add_constraint(level_row(
+                first,
+                points[0].first,
+                "pareto_first",
+            ));
+            let row = base.constraints.len() - 1;
+            let scenarios: Vec<Scenario> = found
+                .iter()
+                .map(|solution| {
+                    let level = evaluate(first, &solution.variable_values);
+                    Scenario::new(format!("first={}", level))
+                        .with_rhs(row, level_bound(first, level))
+                })
+                .collect();
+            let polished = run.solve_scenarios(&base, &scenarios)?;
+            for (solution, polished) in found.into_iter().zip(polished) {
+                points.push(point(first, second, polished.unwrap_or(solution)));
+            }
+        }
+        points.push(point(first, second, second_anchor));
+        Ok(frontier(points, run))
+    }
+}
+
+/// Solves made so far and the first status that wasn't optimal
+struct Run<'a> {
+    solver: &'a dyn SolverService,
+    cancel: &'a CancellationToken,
+    status: SolutionStatus,
+    solves: usize,
+}
+
+impl Run<'_> {
+    /// Solve `problem`, returning the solution if it has values
+    fn solve(&mut self, problem: &OptimizationProblem) -> Result<Option<Solution>> {
+        if self.cancel.is_cancelled() {
+            return Err(SolverError::Cancelled);
+        }
+        let solution = self
+            .solver
+            .solve_cancellable(problem, &NullObserver, self.cancel)?;
+        self.solves += 1;
+        self.note(solution.status);
+        Ok(solution.is_feasible().then_some(solution))
+    }
+
+    /// Solve `problem` under every scenario, returning the solutions with values
+    /// in order; a failed scenario counts as an error
+    fn solve_scenarios(
+        &mut self,
+        problem: &OptimizationProblem,
+        scenarios: &[Scenario],
+    ) -> Result<Vec<Option<Solution>>> {
+        if scenarios.is_empty() {
+            return Ok(Vec::new());
+        }
+        let outcomes = self
+            .solver
+            .solve_scenarios(problem, scenarios, self.cancel)?;
+        self.solves += outcomes.len();
+        Ok(outcomes
+            .into_iter()
+            .map(|outcome| match outcome {
+                Ok(solution) => {
+                    self.note(solution.status);
+                    solution.is_feasible().then_some(solution)
+                }
+                Err(_) => {
+                    self.note(SolutionStatus::Error);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    fn note(&mut self, status: SolutionStatus) {
+        if self.status == SolutionStatus::Optimal {
+            self.status = status;
+        }
+    }
+}
+
+fn with_objective(
+    problem: &OptimizationProblem,
+    objective: ObjectiveFunction,
+) -> OptimizationProblem {
+    let mut problem = problem.clone();
+    problem.objective = objective;
+    problem
+}
+
+fn evaluate(objective: &ObjectiveFunction, values: &[f64]) -> f64 {
+    objective
+        .coefficients
+        .iter()
+        .zip(values)
+        .map(|(c, x)| c * x)
+        .sum()
+}
+
+fn tolerance(level: f64) -> f64 {
+    LEVEL_TOLERANCE * level.abs().max(1.0)
+}
+
+/// Row keeping `objective` at least as good as `level`, give or take the tolerance
+fn level_row(objective: &ObjectiveFunction, level: f64, name: &str) -> Constraint {
+    let constraint_type = match objective.optimization_type {
+        OptimizationType::Minimize => ConstraintType::LessThanOrEqual,
+        OptimizationType::Maximize => ConstraintType::GreaterThanOrEqual,
+    };
+    Constraint::new(
+        constraint_type,
+        objective.coefficients.clone(),
+        level_bound(objective, level),
+    )
+    .with_name(name)
+}
+
+/// Right-hand side of the level row for `level`
+fn level_bound(objective: &ObjectiveFunction, level: f64) -> f64 {
+    match objective.optimization_type {
+        OptimizationType::Minimize => level + tolerance(level),
+        OptimizationType::Maximize => level - tolerance(level),
+    }
+}
+
+fn point(first: &ObjectiveFunction, second: &ObjectiveFunction, solution: Solution) -> ParetoPoint {
+    ParetoPoint {
+        first: evaluate(first, &solution.variable_values),
+        second: evaluate(second, &solution.variable_values),
+        solution,
+    }
+}
+
+/// Points no other point is at least as good as in both objectives, one per
+/// distinct pair of values, best first objective first
+fn non_dominated(
+    points: Vec<ParetoPoint>,
+    first: &ObjectiveFunction,
+    second: &ObjectiveFunction,
+) -> Vec<ParetoPoint> {
+    // Both objectives as costs to minimize
+    let cost = |point: &ParetoPoint| {
+        let sign = |objective: &ObjectiveFunction| match objective.optimization_type {
+            OptimizationType::Minimize => 1.0,
+            OptimizationType::Maximize => -1.0,
+        };
+        (sign(first) * point.first, sign(second) * point.second)
+    };
+    let no_worse = |a: f64, b: f64| a <= b + tolerance(b);
+
+    let mut points: Vec<(f64, f64, ParetoPoint)> = points
+        .into_iter()
+        .map(|point| {
+            let (a, b) = cost(&point);
+            (a, b, point)
+        })
+        .collect();
+    points.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.total_cmp(&y.1)));
+
+    // In that order a point is kept iff its second cost beats every kept one's
+    let mut kept: Vec<ParetoPoint> = Vec::new();
+    let mut best_second = f64::INFINITY;
+    for (_, b, point) in points {
+        if best_second.is_finite() && no_worse(best_second, b) {
+            continue;
+        }
+        best_second = b;
+        kept.push(point);
+    }
+    kept
+}
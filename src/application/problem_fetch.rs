@@ -0,0 +1,295 @@
+// Problem fetching: Downloading problem files for SolveFromUrl
+// HTTP and HTTPS (checked against the system's root certificates); s3:// URLs are
+// read through the bucket's HTTPS endpoint, so private objects need a pre-signed URL
+
+use std::io::Read;
+use std::time::Duration;
+
+use flate2::read::GzDecoder;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Bytes;
+use hyper::header::LOCATION;
+use hyper::{Method, Request, StatusCode, Uri};
+use tonic::Status;
+use tracing::debug;
+
+use super::http_client::{host_name, HttpConnector};
+
+/// Largest problem file downloaded, before and after decompression
+pub const DEFAULT_MAX_DOWNLOAD_BYTES: u64 = 1 << 30;
+
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(300);
+
+const MAX_REDIRECTS: usize = 5;
+
+/// Most buffer reserved up front from a server's Content-Length; the rest grows
+/// with the data actually received
+const MAX_INITIAL_CAPACITY: usize = 1 << 20;
+
+/// Which URLs problems may be fetched from, and how much of them
+///
+/// Fetching makes the server send requests on a client's behalf, so hosts have
+/// to be allowed explicitly.
+#[derive(Debug, Clone)]
+pub struct UrlFetchConfig {
+    /// Hosts problems may come from (`*` allows any); `s3://bucket/...` URLs come
+    /// from `bucket.s3.amazonaws.com`
+    pub allowed_hosts: Vec<String>,
+    pub max_bytes: u64,
+    pub timeout: Duration,
+}
+
+impl UrlFetchConfig {
+    pub fn new(allowed_hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            allowed_hosts: allowed_hosts.into_iter().map(Into::into).collect(),
+            max_bytes: DEFAULT_MAX_DOWNLOAD_BYTES,
+            timeout: DEFAULT_FETCH_TIMEOUT,
+        }
+    }
+
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn allows(&self, host: &str) -> bool {
+        self.allowed_hosts
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Downloads problem files
+pub struct ProblemFetcher {
+    config: UrlFetchConfig,
+    http: HttpConnector,
+}
+
+impl ProblemFetcher {
+    pub fn new(config: UrlFetchConfig) -> Self {
+        Self {
+            config,
+            http: HttpConnector::new(),
+        }
+    }
+
+    pub fn config(&self) -> &UrlFetchConfig {
+        &self.config
+    }
+
+    /// Contents of the file at `url`, decompressed if gzipped
+    pub async fn fetch(&self, url: &str) -> Result<Vec<u8>, Box<Status>> {
+        let uri = resolve(url)?;
+        let timeout = self.config.timeout;
+        let bytes = tokio::time::timeout(timeout, self.download(uri))
+            .await
+            .map_err(|_| {
+                Box::new(Status::deadline_exceeded(format!(
+                    "Downloading {} took longer than {:?}",
+                    url, timeout
+                )))
+            })??;
+
+        if !bytes.starts_with(&[0x1f, 0x8b]) {
+            return Ok(bytes);
+        }
+        let max_bytes = self.config.max_bytes;
+        tokio::task::spawn_blocking(move || gunzip(&bytes, max_bytes))
+            .await
+            .map_err(|e| Box::new(Status::internal(format!("Decompression panicked: {}", e))))?
+    }
+
+    /// Follow redirects to the file and read it
+    async fn download(&self, mut uri: Uri) -> Result<Vec<u8>, Box<Status>> {
+        for _ in 0..=MAX_REDIRECTS {
+            let host = host_name(&uri).to_string();
+            if !self.config.allows(&host) {
+                return Err(Box::new(Status::permission_denied(format!(
+                    "Fetching problems from '{}' is not allowed",
+                    host
+                ))));
+            }
+            debug!(url = %uri, "Fetching problem");
+            let response = self.get(&uri).await?;
+
+            match response {
+                Fetched::Body(bytes) => return Ok(bytes),
+                Fetched::Redirect(location) => uri = redirect_target(&uri, &location)?,
+            }
+        }
+        Err(Box::new(Status::unavailable(format!(
+            "More than {} redirects fetching the problem",
+            MAX_REDIRECTS
+        ))))
+    }
+
+    /// GET `uri`, reading the body unless it redirects
+    async fn get(&self, uri: &Uri) -> Result<Fetched, Box<Status>> {
+        let response = self
+            .http
+            .send(
+                uri,
+                Request::builder().method(Method::GET),
+                Empty::<Bytes>::new(),
+            )
+            .await
+            .map_err(|e| unavailable(uri, e))?;
+
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| unavailable(uri, "redirect without a location"))?;
+            return Ok(Fetched::Redirect(location.to_string()));
+        }
+        if !status.is_success() {
+            let message = format!("Fetching {} failed with HTTP {}", uri, status);
+            return Err(Box::new(match status {
+                StatusCode::NOT_FOUND => Status::not_found(message),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                    Status::permission_denied(message)
+                }
+                _ => Status::unavailable(message),
+            }));
+        }
+
+        let max_bytes = self.config.max_bytes;
+        let too_large = || {
+            Box::new(Status::resource_exhausted(format!(
+                "Problem file at {} is larger than {} bytes",
+                uri, max_bytes
+            )))
+        };
+        let length = response
+            .headers()
+            .get(hyper::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if length.is_some_and(|length| length > max_bytes) {
+            return Err(too_large());
+        }
+
+        let mut body = response.into_body();
+        let capacity = length.unwrap_or_default().min(MAX_INITIAL_CAPACITY as u64);
+        let mut bytes = Vec::with_capacity(capacity as usize);
+        while let Some(frame) = body.frame().await {
+            let frame = frame.map_err(|e| unavailable(uri, e))?;
+            if let Ok(data) = frame.into_data() {
+                if (bytes.len() + data.len()) as u64 > max_bytes {
+                    return Err(too_large());
+                }
+                bytes.extend_from_slice(&data);
+            }
+        }
+        Ok(Fetched::Body(bytes))
+    }
+}
+
+enum Fetched {
+    Body(Vec<u8>),
+    Redirect(String),
+}
+
+/// HTTP(S) URI for a problem URL; `s3://bucket/key` becomes the bucket's endpoint
+fn resolve(url: &str) -> Result<Uri, Box<Status>> {
+    let invalid = |message: String| Box::new(Status::invalid_argument(message));
+    let url = match url.strip_prefix("s3://") {
+        Some(rest) => {
+            let (bucket, key) = rest
+                .split_once('/')
+                .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+                .ok_or_else(|| invalid(format!("S3 URL '{}' needs a bucket and a key", url)))?;
+            format!("https://{}.s3.amazonaws.com/{}", bucket, key)
+        }
+        None => url.to_string(),
+    };
+    let uri: Uri = url
+        .parse()
+        .map_err(|e| invalid(format!("Bad problem URL '{}': {}", url, e)))?;
+    if !matches!(uri.scheme_str(), Some("http" | "https")) || uri.host().is_none() {
+        return Err(invalid(format!(
+            "Problem URL '{}' must be http://, https:// or s3://",
+            url
+        )));
+    }
+    Ok(uri)
+}
+
+/// Where a redirect from `from` leads, resolving paths against its origin
+///
+/// A redirect from HTTPS to plain HTTP is refused, so a download that started
+/// encrypted and authenticated stays that way.
+fn redirect_target(from: &Uri, location: &str) -> Result<Uri, Box<Status>> {
+    let target = if location.starts_with('/') {
+        let scheme = from.scheme_str().unwrap_or("http");
+        let authority = from.authority().map(|a| a.as_str()).unwrap_or_default();
+        resolve(&format!("{}://{}{}", scheme, authority, location))?
+    } else {
+        resolve(location)?
+    };
+    if from.scheme_str() == Some("https") && target.scheme_str() != Some("https") {
+        return Err(Box::new(Status::permission_denied(format!(
+            "Refusing redirect from {} to plain HTTP {}",
+            from, target
+        ))));
+    }
+    Ok(target)
+}
+
+fn unavailable(uri: &Uri, error: impl std::fmt::Display) -> Box<Status> {
+    Box::new(Status::unavailable(format!(
+        "Cannot fetch {}: {}",
+        uri, error
+    )))
+}
+
+fn gunzip(bytes: &[u8], max_bytes: u64) -> Result<Vec<u8>, Box<Status>> {
+    let mut text = Vec::new();
+    GzDecoder::new(bytes)
+        .take(max_bytes + 1)
+        .read_to_end(&mut text)
+        .map_err(|e| {
+            Box::new(Status::invalid_argument(format!(
+                "Cannot decompress problem file: {}",
+                e
+            )))
+        })?;
+    if text.len() as u64 > max_bytes {
+        return Err(Box::new(Status::resource_exhausted(format!(
+            "Decompressed problem file is larger than {} bytes",
+            max_bytes
+        ))));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirects_may_not_downgrade_to_http() {
+        let from: Uri = "https://problems.example.com/a.mps".parse().unwrap();
+
+        let refused = redirect_target(&from, "http://problems.example.com/a.mps").unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::PermissionDenied);
+        let upgraded = redirect_target(&from, "https://mirror.example.com/a.mps").unwrap();
+        assert_eq!(upgraded.host(), Some("mirror.example.com"));
+        let relative = redirect_target(&from, "/b.mps").unwrap();
+        assert_eq!(relative.to_string(), "https://problems.example.com/b.mps");
+    }
+
+    #[test]
+    fn http_may_redirect_to_https() {
+        let from: Uri = "http://problems.example.com/a.mps".parse().unwrap();
+        assert!(redirect_target(&from, "https://problems.example.com/a.mps").is_ok());
+    }
+}
@@ -0,0 +1,262 @@
+// Problem repository: Canonical models saved on the server by ID
+// SaveProblem registers one; GetProblem and SolveStoredProblem load it, so teams
+// can solve a shared model without sending it with every request. Each solve of a
+// saved model is kept in its history to track how the plan drifts over time
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::domain::models::{OptimizationProblem, Solution, SolverConfig};
+use crate::domain::value_objects::SolutionStatus;
+
+/// Longest ID a problem can be saved under
+pub const MAX_PROBLEM_ID_LEN: usize = 128;
+
+/// Summary of a saved problem
+#[derive(Debug, Clone)]
+pub struct StoredProblemInfo {
+    pub id: String,
+    pub name: String,
+    pub num_variables: usize,
+    pub num_constraints: usize,
+    pub num_integer_vars: usize,
+    pub saved_at: SystemTime,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl StoredProblemInfo {
+    pub fn new(id: impl Into<String>, problem: &OptimizationProblem, saved_at: SystemTime) -> Self {
+        Self {
+            id: id.into(),
+            name: problem.name.clone(),
+            num_variables: problem.num_variables(),
+            num_constraints: problem.constraints.len(),
+            num_integer_vars: problem.num_integer_variables(),
+            saved_at,
+            tags: problem.tags.clone(),
+        }
+    }
+}
+
+/// One solve of a saved problem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolveRecord {
+    /// Names the solution kept with the record
+    pub solve_id: String,
+    /// Request ID of the SolveStoredProblem call, as in the audit log
+    pub request_id: String,
+    pub solved_at: SystemTime,
+    /// Config the problem was solved with
+    pub config: SolverConfig,
+    /// Whether the request changed the saved problem for this solve
+    pub overridden: bool,
+    pub status: SolutionStatus,
+    pub objective: Option<f64>,
+    pub gap: Option<f64>,
+    pub backend: String,
+    pub solve_time_ms: f64,
+}
+
+impl SolveRecord {
+    /// Record of `solution`, found for `request_id` with `config`, under a new solve ID
+    pub fn new(
+        request_id: impl Into<String>,
+        config: SolverConfig,
+        overridden: bool,
+        solution: &Solution,
+    ) -> Self {
+        Self {
+            solve_id: Uuid::new_v4().simple().to_string(),
+            request_id: request_id.into(),
+            solved_at: SystemTime::now(),
+            config,
+            overridden,
+            status: solution.status,
+            objective: solution.optimal_value,
+            gap: solution.gap,
+            backend: solution.statistics.solved_by.clone().unwrap_or_default(),
+            solve_time_ms: solution.statistics.solve_time_ms,
+        }
+    }
+}
+
+/// Where saved problems are kept
+///
+/// Implement this to keep them somewhere other than a local directory, such as
+/// an object store or a database table.
+pub trait ProblemRepository: Send + Sync {
+    /// Save `problem` under `id`, replacing whatever was saved under it
+    fn save(&self, id: &str, problem: &OptimizationProblem) -> io::Result<StoredProblemInfo>;
+
+    /// The problem saved under `id`, if any
+    fn load(&self, id: &str) -> io::Result<Option<OptimizationProblem>>;
+
+    /// Every saved problem, ordered by ID
+    fn list(&self) -> io::Result<Vec<StoredProblemInfo>>;
+
+    /// Add a solve of the problem saved under `id` to its history, keeping
+    /// `solution` under the record's solve ID
+    fn record_solve(&self, id: &str, record: &SolveRecord, solution: &Solution) -> io::Result<()>;
+
+    /// Solves of the problem saved under `id`, oldest first
+    fn history(&self, id: &str) -> io::Result<Vec<SolveRecord>>;
+
+    /// The solution kept for `solve_id` in the history of `id`, if any
+    fn load_solution(&self, id: &str, solve_id: &str) -> io::Result<Option<Solution>>;
+}
+
+/// Whether `id` can name a saved problem: up to [`MAX_PROBLEM_ID_LEN`] ASCII
+/// letters, digits, `-`, `_` and `.`, not starting with `.`
+pub fn is_valid_problem_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_PROBLEM_ID_LEN
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// One JSON file per problem, `<id>.json`, in a directory
+///
+/// Saves write a temporary file and rename it into place, so readers never see
+/// half a problem and several servers can share the directory. Histories live
+/// under `history/`: one JSON Lines file per problem, `<id>.jsonl`, and its
+/// solutions in `<id>/<solve_id>.json`. Saving a problem again keeps its history.
+pub struct DirectoryProblemRepository {
+    dir: PathBuf,
+}
+
+impl DirectoryProblemRepository {
+    /// Use `dir`, creating it if needed
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn history_file(&self, id: &str) -> PathBuf {
+        self.dir.join("history").join(format!("{}.jsonl", id))
+    }
+
+    fn solution_file(&self, id: &str, solve_id: &str) -> PathBuf {
+        self.dir
+            .join("history")
+            .join(id)
+            .join(format!("{}.json", solve_id))
+    }
+}
+
+/// `value` read from a JSON file, or None when there is no such file
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<Option<T>> {
+    let json = match fs::read(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    serde_json::from_slice(&json)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl ProblemRepository for DirectoryProblemRepository {
+    fn save(&self, id: &str, problem: &OptimizationProblem) -> io::Result<StoredProblemInfo> {
+        let json = serde_json::to_vec(problem)?;
+        // IDs never start with '.', so temporary files can't shadow a problem
+        let temporary = self.dir.join(format!(".{}.{}.tmp", id, Uuid::new_v4()));
+        fs::write(&temporary, json)?;
+        if let Err(e) = fs::rename(&temporary, self.file(id)) {
+            let _ = fs::remove_file(&temporary);
+            return Err(e);
+        }
+        Ok(StoredProblemInfo::new(id, problem, SystemTime::now()))
+    }
+
+    fn load(&self, id: &str) -> io::Result<Option<OptimizationProblem>> {
+        read_json(&self.file(id))
+    }
+
+    /// Files that don't hold a problem are skipped with a warning rather than
+    /// failing the whole listing
+    fn list(&self) -> io::Result<Vec<StoredProblemInfo>> {
+        let mut problems = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(id) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .filter(|id| is_valid_problem_id(id))
+            else {
+                continue;
+            };
+            let saved_at = entry.metadata()?.modified()?;
+            match self.load(id) {
+                Ok(Some(problem)) => problems.push(StoredProblemInfo::new(id, &problem, saved_at)),
+                // Deleted since the directory was read
+                Ok(None) => {}
+                Err(e) => warn!(id, error = %e, "Skipping unreadable saved problem"),
+            }
+        }
+        problems.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(problems)
+    }
+
+    /// The solution is written before the record, so every record in a history
+    /// has its solution
+    fn record_solve(&self, id: &str, record: &SolveRecord, solution: &Solution) -> io::Result<()> {
+        let solution_file = self.solution_file(id, &record.solve_id);
+        if let Some(dir) = solution_file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&solution_file, serde_json::to_vec(solution)?)?;
+
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        // One write per record keeps lines whole even if several servers share the file
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.history_file(id))?
+            .write_all(&line)
+    }
+
+    /// Lines that don't hold a record are skipped with a warning
+    fn history(&self, id: &str) -> io::Result<Vec<SolveRecord>> {
+        let file = match fs::File::open(self.history_file(id)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => warn!(id, error = %e, "Skipping unreadable solve record"),
+            }
+        }
+        Ok(records)
+    }
+
+    fn load_solution(&self, id: &str, solve_id: &str) -> io::Result<Option<Solution>> {
+        read_json(&self.solution_file(id, solve_id))
+    }
+}
@@ -0,0 +1,272 @@
+// Problem stream: Build the domain model chunk by chunk as a stream arrives
+// Each chunk is converted as soon as it is read, so peak memory tracks the model
+// rather than the buffered messages plus the proto problem plus the domain copy
+
+use std::collections::BTreeMap;
+
+use tonic::Status;
+
+use super::error_details;
+use super::mappers::{self, lp_solver};
+use super::size_limits::{ProblemSizeLimits, SizeTracker};
+use crate::domain::models::{
+    Constraint, ObjectiveFunction, OptimizationProblem, SolverConfig, Variable,
+};
+
+use lp_solver::problem_chunk::Chunk;
+use lp_solver::{error_detail::ErrorCode, ErrorDetail};
+
+/// Incremental builder for a streamed problem
+///
+/// Objective, variables and dense constraints become domain values on arrival.
+/// Sparse constraints are converted, keeping only their listed terms, as soon as
+/// the objective has fixed the row width, except those naming their terms, which
+/// wait for the variables.
+/// Anything that can be judged from one chunk, such as an unknown type, a row of
+/// the wrong width or a second metadata chunk, fails the stream there and then.
+pub struct ProblemAssembler {
+    size: SizeTracker,
+    /// Index of the next chunk
+    chunks: usize,
+    has_metadata: bool,
+    objective: Option<ObjectiveFunction>,
+    constraints: Vec<PendingConstraint>,
+    variables: Vec<Variable>,
+    solver_config: Option<SolverConfig>,
+    name: String,
+    description: String,
+    tags: BTreeMap<String, String>,
+}
+
+enum PendingConstraint {
+    Ready(Constraint),
+    /// Waiting for the objective or the variable names its terms refer to
+    Sparse(lp_solver::SparseConstraint),
+}
+
+impl ProblemAssembler {
+    pub fn new(limits: ProblemSizeLimits) -> Self {
+        Self {
+            size: limits.tracker(),
+            chunks: 0,
+            has_metadata: false,
+            objective: None,
+            constraints: Vec::new(),
+            variables: Vec::new(),
+            solver_config: None,
+            name: String::new(),
+            description: String::new(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Fold one chunk into the model
+    ///
+    /// Fails on the first chunk that can't be converted, that conflicts with what
+    /// came before or that takes the problem past the size limits. The error names
+    /// the chunk's position in the stream.
+    pub fn push(&mut self, chunk: lp_solver::ProblemChunk) -> Result<(), Box<Status>> {
+        let index = self.chunks;
+        self.chunks += 1;
+        self.push_chunk(chunk)
+            .map_err(|status| error_details::in_chunk(status, index))
+    }
+
+    fn push_chunk(&mut self, chunk: lp_solver::ProblemChunk) -> Result<(), Box<Status>> {
+        match chunk.chunk {
+            Some(Chunk::Objective(obj)) => {
+                if self.objective.is_some() {
+                    return Err(duplicate_chunk("Objective"));
+                }
+                self.size.add_objective(obj.coefficients.len())?;
+                let objective = mappers::proto_to_domain_objective(obj)?;
+                self.check_width(&objective)?;
+                self.objective = Some(objective);
+            }
+            Some(Chunk::Constraint(c)) => self.add_constraint(c)?,
+            Some(Chunk::SparseConstraint(c)) => self.add_sparse_constraint(c)?,
+            Some(Chunk::Variable(v)) => self.add_variable(v)?,
+            Some(Chunk::ConstraintBatch(batch)) => {
+                if batch.constraints.is_empty() && batch.sparse_constraints.is_empty() {
+                    return Err(empty_chunk("Constraint batch is empty"));
+                }
+                for c in batch.constraints {
+                    self.add_constraint(c)?;
+                }
+                for c in batch.sparse_constraints {
+                    self.add_sparse_constraint(c)?;
+                }
+            }
+            Some(Chunk::VariableBatch(batch)) => {
+                if batch.variables.is_empty() {
+                    return Err(empty_chunk("Variable batch is empty"));
+                }
+                for v in batch.variables {
+                    self.add_variable(v)?;
+                }
+            }
+            Some(Chunk::Metadata(m)) => {
+                if std::mem::replace(&mut self.has_metadata, true) {
+                    return Err(duplicate_chunk("Metadata"));
+                }
+                self.name = m.problem_name;
+                self.description = m.description;
+                self.tags = m.tags.into_iter().collect();
+            }
+            Some(Chunk::SolverConfig(sc)) => {
+                if self.solver_config.is_some() {
+                    return Err(duplicate_chunk("Solver config"));
+                }
+                self.solver_config = Some(mappers::proto_to_domain_solver_config(sc));
+            }
+            None => return Err(empty_chunk("Chunk is empty")),
+        }
+        Ok(())
+    }
+
+    /// Check the rows and variables seen so far against the width `objective` sets
+    fn check_width(&self, objective: &ObjectiveFunction) -> Result<(), Box<Status>> {
+        let num_vars = objective.num_variables();
+        if self.variables.len() > num_vars {
+            return Err(ErrorDetail::with_code(ErrorCode::LengthMismatch)
+                .with_entity("variable", Some(num_vars))
+                .with_values(num_vars, self.variables.len())
+                .invalid_argument(format!(
+                    "{} variables declared but the objective has {} coefficients",
+                    self.variables.len(),
+                    num_vars
+                )));
+        }
+        self.constraints
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, pending)| match pending {
+                PendingConstraint::Ready(c) => check_row(i, c, num_vars),
+                PendingConstraint::Sparse(_) => Ok(()),
+            })
+    }
+
+    /// The assembled problem, once the stream has ended
+    pub fn finish(self) -> Result<OptimizationProblem, Box<Status>> {
+        let objective = self.objective.ok_or_else(mappers::missing_objective)?;
+
+        let variables = if self.variables.is_empty() {
+            mappers::default_variables(&objective)
+        } else {
+            self.variables
+        };
+
+        let constraints = {
+            let names = mappers::variable_name_index(
+                variables.iter().map(|v| v.name.as_str()),
+                &objective.variable_names,
+            );
+            self.constraints
+                .into_iter()
+                .map(|pending| match pending {
+                    PendingConstraint::Ready(c) => Ok(c),
+                    PendingConstraint::Sparse(c) => mappers::proto_to_domain_sparse_constraint(
+                        c,
+                        objective.num_variables(),
+                        &names,
+                    ),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        Ok(OptimizationProblem {
+            name: self.name,
+            description: self.description,
+            objective,
+            constraints,
+            variables,
+            solver_config: self.solver_config.unwrap_or_default(),
+            tags: self.tags,
+        })
+    }
+
+    fn add_constraint(&mut self, c: lp_solver::Constraint) -> Result<(), Box<Status>> {
+        self.size.add_constraint(&c.coefficients)?;
+        let constraint = mappers::proto_to_domain_constraint(c)?;
+        if let Some(objective) = &self.objective {
+            check_row(
+                self.constraints.len(),
+                &constraint,
+                objective.num_variables(),
+            )?;
+        }
+        self.constraints.push(PendingConstraint::Ready(constraint));
+        Ok(())
+    }
+
+    fn add_sparse_constraint(&mut self, c: lp_solver::SparseConstraint) -> Result<(), Box<Status>> {
+        self.size.add_constraint(&c.values)?;
+
+        // Name lookups have to wait, since later chunks may still declare variables
+        let by_name = c.indices.is_empty() && !c.variable_names.is_empty();
+        let pending = match &self.objective {
+            Some(objective) if !by_name => {
+                let constraint = mappers::proto_to_domain_sparse_constraint(
+                    c,
+                    objective.num_variables(),
+                    &Default::default(),
+                )
+                .map_err(|status| error_details::at_index(status, self.constraints.len()))?;
+                PendingConstraint::Ready(constraint)
+            }
+            _ => PendingConstraint::Sparse(c),
+        };
+        self.constraints.push(pending);
+        Ok(())
+    }
+
+    fn add_variable(&mut self, v: lp_solver::Variable) -> Result<(), Box<Status>> {
+        self.size.add_variable()?;
+        let variable = mappers::proto_to_domain_variable(v)
+            .map_err(|status| error_details::at_index(status, self.variables.len()))?;
+        if let Some(objective) = &self.objective {
+            let num_vars = objective.num_variables();
+            if self.variables.len() >= num_vars {
+                return Err(ErrorDetail::with_code(ErrorCode::LengthMismatch)
+                    .with_entity("variable", Some(self.variables.len()))
+                    .with_name(&variable.name)
+                    .with_values(num_vars, self.variables.len() + 1)
+                    .invalid_argument(format!(
+                        "Variable '{}' is past the {} the objective declares",
+                        variable.name, num_vars
+                    )));
+            }
+        }
+        self.variables.push(variable);
+        Ok(())
+    }
+}
+
+/// A dense row must have one coefficient per variable
+fn check_row(index: usize, constraint: &Constraint, num_vars: usize) -> Result<(), Box<Status>> {
+    if constraint.coefficients.len() == num_vars {
+        return Ok(());
+    }
+    Err(ErrorDetail::with_code(ErrorCode::LengthMismatch)
+        .with_entity("constraint", Some(index))
+        .with_name(&constraint.name)
+        .with_values(num_vars, constraint.coefficients.len())
+        .invalid_argument(format!(
+            "Constraint '{}' has {} coefficients but problem has {} variables",
+            constraint.name,
+            constraint.coefficients.len(),
+            num_vars
+        )))
+}
+
+fn duplicate_chunk(what: &str) -> Box<Status> {
+    ErrorDetail::with_code(ErrorCode::InvalidProblem)
+        .with_entity("chunk", None)
+        .invalid_argument(format!("{} sent more than once", what))
+}
+
+fn empty_chunk(message: &str) -> Box<Status> {
+    ErrorDetail::with_code(ErrorCode::MissingField)
+        .with_entity("chunk", None)
+        .invalid_argument(message)
+}
@@ -0,0 +1,74 @@
+// Result publishing: Announce finished solves to event-driven downstream systems
+// Each solve that returns a result produces one event; publishers decide which
+// broker carries it
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::audit::Caller;
+use super::mappers::lp_solver;
+use crate::domain::models::OptimizationProblem;
+
+/// A finished solve, as published
+#[derive(Debug, Clone, Serialize)]
+pub struct SolveEvent {
+    /// Milliseconds since the Unix epoch when the solve finished
+    pub timestamp_ms: u64,
+    pub request_id: String,
+    pub problem_name: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    pub num_variables: usize,
+    pub num_constraints: usize,
+    pub backend: String,
+    pub status: String,
+    pub objective: Option<f64>,
+    pub gap: Option<f64>,
+    pub solve_time_ms: f64,
+}
+
+impl SolveEvent {
+    /// Event for `problem` sent by `caller`; outcome fields start empty
+    pub fn new(caller: &Caller, problem: &OptimizationProblem) -> Self {
+        Self {
+            timestamp_ms: 0,
+            request_id: caller.request_id.to_string(),
+            problem_name: problem.name.clone(),
+            tags: problem.tags.clone(),
+            num_variables: problem.num_variables(),
+            num_constraints: problem.constraints.len(),
+            backend: problem.solver_config.backend.to_string(),
+            status: String::new(),
+            objective: None,
+            gap: None,
+            solve_time_ms: 0.0,
+        }
+    }
+
+    /// Fill in the outcome from `result`, timestamped now
+    pub fn complete(&mut self, result: &lp_solver::OptimizationResult) {
+        self.timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        self.status = lp_solver::SolutionStatus::try_from(result.status)
+            .map(|s| s.as_str_name().to_string())
+            .unwrap_or_default();
+        self.objective = result.optimal_value;
+        self.gap = result.gap;
+        if let Some(stats) = &result.statistics {
+            self.backend = stats.solver_backend.clone();
+            self.solve_time_ms = stats.solve_time_ms;
+        }
+    }
+}
+
+/// Destination for solve events
+///
+/// Publishing must not hold up the response: implementations hand the event to
+/// their broker client and log failures rather than return them.
+pub trait ResultPublisher: Send + Sync {
+    fn publish(&self, event: &SolveEvent);
+}
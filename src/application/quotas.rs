@@ -0,0 +1,377 @@
+// Usage quotas: Cap how much solving each API key may do
+// Solves per hour and CPU-seconds per day, counted in fixed windows per key so
+// one team can't monopolize a shared server
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::Status;
+
+/// Metadata key carrying the caller's API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Response metadata: solves left in the current hour
+pub const SOLVES_REMAINING_HEADER: &str = "x-quota-solves-remaining";
+/// Response metadata: seconds until the hourly solve count resets
+pub const SOLVES_RESET_HEADER: &str = "x-quota-solves-reset-secs";
+/// Response metadata: CPU-seconds left in the current day
+pub const CPU_SECS_REMAINING_HEADER: &str = "x-quota-cpu-secs-remaining";
+/// Response metadata: seconds until the daily CPU time resets
+pub const CPU_RESET_HEADER: &str = "x-quota-cpu-reset-secs";
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Keys tracked separately at most; usage of further keys goes to the anonymous
+/// quota, so a flood of made-up keys neither grows the map nor escapes the limits
+pub const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Limits for one API key (None = unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QuotaLimits {
+    pub solves_per_hour: Option<u64>,
+    pub cpu_secs_per_day: Option<f64>,
+}
+
+impl QuotaLimits {
+    /// No limits
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_solves_per_hour(mut self, solves: u64) -> Self {
+        self.solves_per_hour = Some(solves);
+        self
+    }
+
+    pub fn with_cpu_secs_per_day(mut self, secs: f64) -> Self {
+        self.cpu_secs_per_day = Some(secs);
+        self
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.solves_per_hour.is_none() && self.cpu_secs_per_day.is_none()
+    }
+}
+
+/// Quota limits by API key
+///
+/// Once any key has limits of its own, only those keys are told apart: requests
+/// with any other key, or none, share a single anonymous quota under the
+/// defaults, so rotating made-up keys doesn't buy a fresh quota. Without listed
+/// keys every key gets the defaults to itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuotaPolicy {
+    default: QuotaLimits,
+    keys: HashMap<String, QuotaLimits>,
+}
+
+impl QuotaPolicy {
+    /// Policy without any limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits for keys not listed with `with_key_limits`
+    pub fn with_default_limits(mut self, limits: QuotaLimits) -> Self {
+        self.default = limits;
+        self
+    }
+
+    /// Limits for one API key, replacing the defaults for it
+    pub fn with_key_limits(mut self, api_key: impl Into<String>, limits: QuotaLimits) -> Self {
+        self.keys.insert(api_key.into(), limits);
+        self
+    }
+
+    pub fn limits_for(&self, api_key: &str) -> QuotaLimits {
+        self.keys.get(api_key).copied().unwrap_or(self.default)
+    }
+
+    /// Key whose quota a request with `api_key` counts against; "" for the
+    /// anonymous quota
+    pub fn bucket_for<'a>(&self, api_key: Option<&'a str>) -> &'a str {
+        match api_key {
+            Some(key) if self.keys.is_empty() || self.keys.contains_key(key) => key,
+            _ => "",
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.default.is_unlimited() && self.keys.values().all(QuotaLimits::is_unlimited)
+    }
+}
+
+/// What is left of a key's quota, for the limits it has
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QuotaStatus {
+    pub solves_remaining: Option<u64>,
+    pub solves_reset: Option<Duration>,
+    pub cpu_secs_remaining: Option<f64>,
+    pub cpu_reset: Option<Duration>,
+}
+
+impl QuotaStatus {
+    /// Report the quota in response or error metadata
+    pub fn insert_into(&self, metadata: &mut MetadataMap) {
+        let mut insert = |key: &'static str, value: Option<String>| {
+            if let Some(value) = value.and_then(|v| MetadataValue::try_from(v).ok()) {
+                metadata.insert(key, value);
+            }
+        };
+        insert(
+            SOLVES_REMAINING_HEADER,
+            self.solves_remaining.map(|n| n.to_string()),
+        );
+        insert(
+            SOLVES_RESET_HEADER,
+            self.solves_reset.map(|d| d.as_secs().to_string()),
+        );
+        insert(
+            CPU_SECS_REMAINING_HEADER,
+            self.cpu_secs_remaining.map(|secs| format!("{:.3}", secs)),
+        );
+        insert(
+            CPU_RESET_HEADER,
+            self.cpu_reset.map(|d| d.as_secs().to_string()),
+        );
+    }
+}
+
+/// Usage of one key in its current windows
+struct Usage {
+    hour_start: Instant,
+    solves: u64,
+    day_start: Instant,
+    cpu_secs: f64,
+}
+
+impl Usage {
+    fn new(now: Instant) -> Self {
+        Self {
+            hour_start: now,
+            solves: 0,
+            day_start: now,
+            cpu_secs: 0.0,
+        }
+    }
+
+    /// Start new windows for those that have run out
+    fn roll(&mut self, now: Instant) {
+        if now.duration_since(self.hour_start) >= HOUR {
+            self.hour_start = now;
+            self.solves = 0;
+        }
+        if now.duration_since(self.day_start) >= DAY {
+            self.day_start = now;
+            self.cpu_secs = 0.0;
+        }
+    }
+
+    fn status(&self, limits: QuotaLimits, now: Instant) -> QuotaStatus {
+        let left = |start: Instant, window: Duration| window.saturating_sub(now - start);
+        QuotaStatus {
+            solves_remaining: limits
+                .solves_per_hour
+                .map(|max| max.saturating_sub(self.solves)),
+            solves_reset: limits.solves_per_hour.map(|_| left(self.hour_start, HOUR)),
+            cpu_secs_remaining: limits
+                .cpu_secs_per_day
+                .map(|max| (max - self.cpu_secs).max(0.0)),
+            cpu_reset: limits.cpu_secs_per_day.map(|_| left(self.day_start, DAY)),
+        }
+    }
+}
+
+/// Tracks each API key's usage against a [`QuotaPolicy`]
+pub struct QuotaTracker {
+    policy: QuotaPolicy,
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl QuotaTracker {
+    pub fn new(policy: QuotaPolicy) -> Self {
+        Self {
+            policy,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Tracker that admits everything
+    pub fn unlimited() -> Self {
+        Self::new(QuotaPolicy::new())
+    }
+
+    pub fn policy(&self) -> &QuotaPolicy {
+        &self.policy
+    }
+
+    /// Count one solve against `api_key`, or refuse it with RESOURCE_EXHAUSTED
+    /// when the key has used up its solves or CPU time
+    pub fn admit(&self, api_key: Option<&str>) -> Result<QuotaStatus, Box<Status>> {
+        let now = Instant::now();
+        let mut usage = self.lock();
+        // Forget keys whose day has run out; they would start afresh anyway
+        usage.retain(|_, usage| now.duration_since(usage.day_start) < DAY);
+        let key = self.tracked_key(&usage, api_key);
+        let limits = self.policy.limits_for(key);
+        if limits.is_unlimited() {
+            return Ok(QuotaStatus::default());
+        }
+        let entry = usage
+            .entry(key.to_string())
+            .or_insert_with(|| Usage::new(now));
+        entry.roll(now);
+
+        let exceeded = if limits
+            .solves_per_hour
+            .is_some_and(|max| entry.solves >= max)
+        {
+            Some(Exceeded::Solves)
+        } else if limits
+            .cpu_secs_per_day
+            .is_some_and(|max| entry.cpu_secs >= max)
+        {
+            Some(Exceeded::CpuTime)
+        } else {
+            None
+        };
+        if let Some(exceeded) = exceeded {
+            let status = entry.status(limits, now);
+            let reset = match exceeded {
+                Exceeded::Solves => status.solves_reset,
+                Exceeded::CpuTime => status.cpu_reset,
+            };
+            let mut error = Status::resource_exhausted(format!(
+                "Quota exceeded for {}: {}; resets in {}s",
+                if key.is_empty() {
+                    "requests without a known API key"
+                } else {
+                    "this API key"
+                },
+                exceeded.describe(limits),
+                reset.unwrap_or_default().as_secs()
+            ));
+            status.insert_into(error.metadata_mut());
+            return Err(Box::new(error));
+        }
+
+        entry.solves += 1;
+        Ok(entry.status(limits, now))
+    }
+
+    /// Charge `cpu_secs` of solving to `api_key` and report what is left
+    pub fn charge(&self, api_key: Option<&str>, cpu_secs: f64) -> QuotaStatus {
+        let now = Instant::now();
+        let mut usage = self.lock();
+        let key = self.tracked_key(&usage, api_key);
+        let limits = self.policy.limits_for(key);
+        if limits.is_unlimited() {
+            return QuotaStatus::default();
+        }
+        let entry = usage
+            .entry(key.to_string())
+            .or_insert_with(|| Usage::new(now));
+        entry.roll(now);
+        entry.cpu_secs += cpu_secs.max(0.0);
+        entry.status(limits, now)
+    }
+
+    /// Key `api_key`'s usage is kept under: its policy bucket, or the anonymous
+    /// one when the map is full of other keys
+    fn tracked_key<'a>(&self, usage: &HashMap<String, Usage>, api_key: Option<&'a str>) -> &'a str {
+        let key = self.policy.bucket_for(api_key);
+        if usage.len() >= MAX_TRACKED_KEYS && !usage.contains_key(key) {
+            ""
+        } else {
+            key
+        }
+    }
+
+    /// Number of keys whose usage is tracked
+    pub fn tracked_keys(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Usage>> {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for QuotaTracker {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+enum Exceeded {
+    Solves,
+    CpuTime,
+}
+
+impl Exceeded {
+    fn describe(&self, limits: QuotaLimits) -> String {
+        match self {
+            Self::Solves => format!(
+                "{} solves per hour used",
+                limits.solves_per_hour.unwrap_or_default()
+            ),
+            Self::CpuTime => format!(
+                "{} CPU-seconds per day used",
+                limits.cpu_secs_per_day.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one_solve_per_hour() -> QuotaLimits {
+        QuotaLimits::unlimited().with_solves_per_hour(1)
+    }
+
+    #[test]
+    fn unknown_keys_share_the_anonymous_quota_when_keys_are_listed() {
+        let tracker = QuotaTracker::new(
+            QuotaPolicy::new()
+                .with_default_limits(one_solve_per_hour())
+                .with_key_limits("team-a", one_solve_per_hour()),
+        );
+
+        assert!(tracker.admit(Some("made-up-1")).is_ok());
+        let refused = tracker.admit(Some("made-up-2")).unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+        assert!(tracker.admit(None).is_err());
+        assert!(tracker.admit(Some("team-a")).is_ok());
+        assert_eq!(tracker.tracked_keys(), 2);
+    }
+
+    #[test]
+    fn keys_get_their_own_defaults_when_none_are_listed() {
+        let tracker =
+            QuotaTracker::new(QuotaPolicy::new().with_default_limits(one_solve_per_hour()));
+
+        assert!(tracker.admit(Some("a")).is_ok());
+        assert!(tracker.admit(Some("b")).is_ok());
+        assert!(tracker.admit(Some("a")).is_err());
+    }
+
+    #[test]
+    fn tracked_keys_are_bounded() {
+        let tracker = QuotaTracker::new(
+            QuotaPolicy::new()
+                .with_default_limits(QuotaLimits::unlimited().with_solves_per_hour(2)),
+        );
+
+        for i in 0..MAX_TRACKED_KEYS + 10 {
+            let _ = tracker.admit(Some(&format!("key-{}", i)));
+        }
+        assert_eq!(tracker.tracked_keys(), MAX_TRACKED_KEYS + 1);
+        // Overflowing keys count against the anonymous quota, which is used up now
+        assert!(tracker.admit(Some("one-more")).is_err());
+    }
+}
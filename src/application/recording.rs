@@ -0,0 +1,43 @@
+// Solve recorder: Every solve's canonical problem, seed and outcome, on disk
+// `letsopt-replay` re-runs a recording against a chosen backend and reports
+// what changed
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::domain::recording::RecordedSolve;
+
+/// Append-only JSON Lines recording, one solve per line
+pub struct SolveRecorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl SolveRecorder {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, solve: &RecordedSolve) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(solve)?;
+        line.push(b'\n');
+
+        // One write per solve keeps lines whole even if several servers share the file
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line)?;
+        file.flush()
+    }
+}
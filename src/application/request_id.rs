@@ -0,0 +1,96 @@
+// Request IDs: Correlate one solve across client, server logs and response
+// Clients may supply their own ID; otherwise the server assigns one
+
+use std::fmt;
+
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Response, Status};
+use uuid::Uuid;
+
+use super::mappers::lp_solver;
+
+/// Metadata key carrying the request ID, in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied ID accepted as-is
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Identifier attached to every log event and response for one request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Use the client's `x-request-id`, or generate one if it is missing or unusable
+    pub fn from_metadata(metadata: &MetadataMap) -> Self {
+        metadata
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+            .map(|id| Self(id.to_string()))
+            .unwrap_or_else(Self::generate)
+    }
+
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Echo the ID in the response metadata and result message
+    pub fn tag_result(
+        &self,
+        mut response: Response<lp_solver::OptimizationResult>,
+    ) -> Response<lp_solver::OptimizationResult> {
+        let result = response.get_mut();
+        result.message = format!("{} [request id: {}]", result.message, self.0);
+        self.insert_into(response.metadata_mut());
+        response
+    }
+
+    /// Result of a response from another letsopt server, without the ID it
+    /// tagged the message with
+    pub fn untag_result(
+        response: Response<lp_solver::OptimizationResult>,
+    ) -> lp_solver::OptimizationResult {
+        let id = response
+            .metadata()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut result = response.into_inner();
+        if let Some(id) = id {
+            let tag = format!(" [request id: {}]", id);
+            if let Some(message) = result.message.strip_suffix(&tag) {
+                result.message = message.to_string();
+            }
+        }
+        result
+    }
+
+    /// Echo the ID in the metadata of any other response
+    pub fn tag_response<T>(&self, mut response: Response<T>) -> Response<T> {
+        self.insert_into(response.metadata_mut());
+        response
+    }
+
+    /// Echo the ID in the metadata of an error response
+    pub fn tag_status(&self, mut status: Status) -> Status {
+        self.insert_into(status.metadata_mut());
+        status
+    }
+
+    fn insert_into(&self, metadata: &mut MetadataMap) {
+        if let Ok(value) = MetadataValue::try_from(self.0.as_str()) {
+            metadata.insert(REQUEST_ID_HEADER, value);
+        }
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
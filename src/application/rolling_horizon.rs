@@ -0,0 +1,286 @@
+// Rolling horizon: Time-indexed models solved one overlapping window at a time
+// Each window fixes the periods earlier windows committed, decides its own, and the
+// committed parts are stitched into a solution of the whole model
+
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Instant;
+
+use tracing::debug;
+
+use crate::domain::modeling::VarRef;
+use crate::domain::models::{Constraint, ObjectiveFunction, OptimizationProblem, Solution};
+use crate::domain::solver_service::{
+    CancellationToken, NullObserver, Result, SolverError, SolverService,
+};
+use crate::domain::value_objects::SolutionStatus;
+
+/// A problem whose variables each belong to one time period
+///
+/// A row belongs to the latest period of the variables in it, so a window sees
+/// every row it can satisfy or break. Variables not given a period are in period
+/// 0, which suits design decisions shared by all periods: the first window fixes
+/// them.
+#[derive(Debug, Clone)]
+pub struct HorizonModel {
+    problem: OptimizationProblem,
+    variable_periods: Vec<usize>,
+    row_periods: Vec<usize>,
+    periods: usize,
+}
+
+impl HorizonModel {
+    /// Horizon over `problem`, with `(var, period)` for its time-indexed variables
+    pub fn new(
+        problem: OptimizationProblem,
+        periods: impl IntoIterator<Item = (VarRef, usize)>,
+    ) -> Result<Self> {
+        let mut variable_periods = vec![0; problem.num_variables()];
+        for (var, period) in periods {
+            let slot = variable_periods.get_mut(var.index()).ok_or_else(|| {
+                SolverError::InvalidProblem(format!(
+                    "Variable {} is not in the problem ({} variables)",
+                    var.index(),
+                    problem.num_variables()
+                ))
+            })?;
+            *slot = period;
+        }
+        let row_periods = problem
+            .constraints
+            .iter()
+            .map(|row| {
+                row.coefficients
+                    .indices()
+                    .iter()
+                    .map(|&j| variable_periods.get(j).copied().unwrap_or(0))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let periods = variable_periods.iter().max().map_or(1, |&last| last + 1);
+        Ok(Self {
+            problem,
+            variable_periods,
+            row_periods,
+            periods,
+        })
+    }
+
+    pub fn problem(&self) -> &OptimizationProblem {
+        &self.problem
+    }
+
+    /// One more than the latest period
+    pub fn num_periods(&self) -> usize {
+        self.periods
+    }
+
+    /// Problem over the variables of `window`, with those of earlier periods fixed
+    /// at `values` and later ones left out; returns it with its columns and rows
+    fn window_problem(
+        &self,
+        window: &Range<usize>,
+        values: &[f64],
+    ) -> (OptimizationProblem, Vec<usize>, Vec<usize>) {
+        let columns: Vec<usize> = (0..self.problem.num_variables())
+            .filter(|&j| window.contains(&self.variable_periods[j]))
+            .collect();
+        let rows: Vec<usize> = (0..self.problem.constraints.len())
+            .filter(|&r| window.contains(&self.row_periods[r]))
+            .collect();
+
+        let mut position = vec![None; self.problem.num_variables()];
+        for (k, &j) in columns.iter().enumerate() {
+            position[j] = Some(k);
+        }
+        let constraints = rows
+            .iter()
+            .map(|&r| {
+                let row = &self.problem.constraints[r];
+                let fixed: f64 = row
+                    .coefficients
+                    .iter()
+                    .filter(|&(j, _)| self.variable_periods[j] < window.start)
+                    .map(|(j, c)| c * values[j])
+                    .sum();
+                let coefficients = row.coefficients.remap(&position, columns.len());
+                Constraint::new(row.constraint_type, coefficients, row.bound - fixed)
+                    .with_name(row.name.clone())
+            })
+            .collect();
+        let objective = &self.problem.objective;
+        let mut problem = OptimizationProblem::new(ObjectiveFunction::new(
+            objective.optimization_type,
+            columns
+                .iter()
+                .map(|&j| objective.coefficients.get(j).copied().unwrap_or(0.0))
+                .collect(),
+        ))
+        .with_name(format!(
+            "{}[{}..{}]",
+            self.problem.name, window.start, window.end
+        ))
+        .with_variables(
+            columns
+                .iter()
+                .map(|&j| self.problem.variables[j].clone())
+                .collect(),
+        )
+        .with_config(self.problem.solver_config.clone());
+        problem.constraints = constraints;
+        (problem, columns, rows)
+    }
+}
+
+/// Result of a rolling-horizon solve
+#[derive(Debug, Clone)]
+pub struct RollingHorizonSolution {
+    /// Stitched values and row duals of the whole model, each taken from the
+    /// window that committed its period, and the objective they reach
+    ///
+    /// Feasible rather than Optimal unless one window spanned the horizon, since
+    /// no window sees past its end. A window that found no solution ends the
+    /// run with its status and no values.
+    pub solution: Solution,
+    /// Windows solved
+    pub windows: usize,
+}
+
+/// Solves [`HorizonModel`]s window by window with any backend
+///
+/// Windows are `window` periods long and overlap by `overlap`: each commits its
+/// first `window - overlap` periods and hands the rest, which only looked ahead,
+/// to the next. Solver settings such as the time limit apply to each window.
+pub struct RollingHorizonSolver {
+    solver: Arc<dyn SolverService>,
+    window: usize,
+    overlap: usize,
+}
+
+impl RollingHorizonSolver {
+    /// Solve `window` periods at a time, without overlap until
+    /// [`with_overlap`](Self::with_overlap)
+    pub fn new(solver: Arc<dyn SolverService>, window: usize) -> Self {
+        Self {
+            solver,
+            window: window.max(1),
+            overlap: 0,
+        }
+    }
+
+    /// Periods of lookahead each window shares with the next, capped so every
+    /// window commits at least one period
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap.min(self.window - 1);
+        self
+    }
+
+    pub fn solve(&self, model: &HorizonModel) -> Result<RollingHorizonSolution> {
+        self.solve_cancellable(model, &CancellationToken::new())
+    }
+
+    /// Solve, stopping between or during windows once `cancel` is set
+    pub fn solve_cancellable(
+        &self,
+        model: &HorizonModel,
+        cancel: &CancellationToken,
+    ) -> Result<RollingHorizonSolution> {
+        let start_time = Instant::now();
+        let step = self.window - self.overlap;
+        let mut values = vec![0.0; model.problem.num_variables()];
+        let mut duals = vec![0.0; model.problem.constraints.len()];
+        let mut simplex_iterations = 0;
+        let mut nodes_explored = 0;
+        let mut windows = 0;
+        let mut start = 0;
+
+        while start < model.periods {
+            if cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            let window = start..(start + self.window).min(model.periods);
+            let last = window.end == model.periods;
+            let commit = if last {
+                window.clone()
+            } else {
+                start..start + step
+            };
+            let (problem, columns, rows) = model.window_problem(&window, &values);
+            if columns.is_empty() {
+                start = commit.end;
+                continue;
+            }
+            debug!(
+                start = window.start,
+                end = window.end,
+                variables = columns.len(),
+                rows = rows.len(),
+                "Solving horizon window"
+            );
+
+            let solution = self
+                .solver
+                .solve_cancellable(&problem, &NullObserver, cancel)?;
+            windows += 1;
+            simplex_iterations += solution.statistics.simplex_iterations;
+            nodes_explored += solution.statistics.nodes_explored;
+            if !solution.is_feasible() {
+                let mut failed = Solution::new(
+                    solution.status,
+                    format!(
+                        "Window {}..{} ended {}: {}",
+                        window.start, window.end, solution.status, solution.message
+                    ),
+                );
+                failed.statistics.solve_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+                return Ok(RollingHorizonSolution {
+                    solution: failed,
+                    windows,
+                });
+            }
+
+            for (k, &j) in columns.iter().enumerate() {
+                if commit.contains(&model.variable_periods[j]) {
+                    let value = solution.variable_values[k];
+                    values[j] = if model.problem.variables[j].is_integer() {
+                        value.round()
+                    } else {
+                        value
+                    };
+                }
+            }
+            for (k, &r) in rows.iter().enumerate() {
+                if commit.contains(&model.row_periods[r]) {
+                    duals[r] = solution.dual_values.get(k).copied().unwrap_or(0.0);
+                }
+            }
+            if last && windows == 1 {
+                let mut solution = solution;
+                solution.statistics.solve_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+                return Ok(RollingHorizonSolution { solution, windows });
+            }
+            start = commit.end;
+        }
+
+        let objective: f64 = model
+            .problem
+            .objective
+            .coefficients
+            .iter()
+            .zip(&values)
+            .map(|(c, x)| c * x)
+            .sum();
+        let mut solution = Solution::new(
+            SolutionStatus::Feasible,
+            format!("Stitched from {} horizon windows", windows),
+        );
+        solution.optimal_value = Some(objective);
+        solution.variable_values = values;
+        solution.dual_values = duals;
+        solution.statistics.simplex_iterations = simplex_iterations;
+        solution.statistics.nodes_explored = nodes_explored;
+        solution.statistics.solve_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+        Ok(RollingHorizonSolution { solution, windows })
+    }
+}
@@ -0,0 +1,148 @@
+// Routing: Pick a solver for each AUTO request from operator-defined rules
+// Rules are tried in order; the first match names the solver, otherwise the default applies
+
+use crate::domain::models::OptimizationProblem;
+
+/// Metadata key carrying a client's routing hint (e.g. `large`, `interactive`)
+pub const SOLVER_HINT_HEADER: &str = "x-solver-hint";
+
+/// Which problems a rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProblemKind {
+    #[default]
+    Any,
+    /// Continuous problems only
+    Lp,
+    /// Problems with at least one integer or binary variable
+    Mip,
+}
+
+/// Send matching problems to the solver registered as `solver`
+///
+/// Every condition that is set must hold for the rule to match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingRule {
+    pub solver: String,
+    pub kind: ProblemKind,
+    pub min_variables: Option<usize>,
+    pub max_variables: Option<usize>,
+    pub min_constraints: Option<usize>,
+    pub max_constraints: Option<usize>,
+    /// Client hint that must be present (case-insensitive)
+    pub hint: Option<String>,
+}
+
+impl RoutingRule {
+    /// Rule matching every problem
+    pub fn to(solver: impl Into<String>) -> Self {
+        Self {
+            solver: solver.into(),
+            kind: ProblemKind::Any,
+            min_variables: None,
+            max_variables: None,
+            min_constraints: None,
+            max_constraints: None,
+            hint: None,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: ProblemKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_min_variables(mut self, min: usize) -> Self {
+        self.min_variables = Some(min);
+        self
+    }
+
+    pub fn with_max_variables(mut self, max: usize) -> Self {
+        self.max_variables = Some(max);
+        self
+    }
+
+    pub fn with_min_constraints(mut self, min: usize) -> Self {
+        self.min_constraints = Some(min);
+        self
+    }
+
+    pub fn with_max_constraints(mut self, max: usize) -> Self {
+        self.max_constraints = Some(max);
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn matches(&self, problem: &OptimizationProblem, hint: Option<&str>) -> bool {
+        let kind_matches = match self.kind {
+            ProblemKind::Any => true,
+            ProblemKind::Lp => !problem.is_mixed_integer(),
+            ProblemKind::Mip => problem.is_mixed_integer(),
+        };
+        let hint_matches = match (&self.hint, hint) {
+            (None, _) => true,
+            (Some(wanted), Some(hint)) => wanted.eq_ignore_ascii_case(hint),
+            (Some(_), None) => false,
+        };
+
+        kind_matches
+            && hint_matches
+            && within(
+                problem.num_variables(),
+                self.min_variables,
+                self.max_variables,
+            )
+            && within(
+                problem.constraints.len(),
+                self.min_constraints,
+                self.max_constraints,
+            )
+    }
+}
+
+/// Ordered routing rules
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingPolicy {
+    rules: Vec<RoutingRule>,
+}
+
+impl RoutingPolicy {
+    /// Policy with no rules; every AUTO request uses the default solver
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rule(mut self, rule: RoutingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rules(&self) -> &[RoutingRule] {
+        &self.rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Solver named by the first rule matching the problem whose solver is
+    /// `usable`, if any
+    pub fn route(
+        &self,
+        problem: &OptimizationProblem,
+        hint: Option<&str>,
+        usable: impl Fn(&str) -> bool,
+    ) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(problem, hint) && usable(&rule.solver))
+            .map(|rule| rule.solver.as_str())
+    }
+}
+
+fn within(count: usize, min: Option<usize>, max: Option<usize>) -> bool {
+    min.is_none_or(|min| count >= min) && max.is_none_or(|max| count <= max)
+}
@@ -0,0 +1,135 @@
+// Server statistics: Counters behind GetServerStats
+// Solve outcomes by status and backend, solve times and idempotency cache use,
+// kept in memory since startup for dashboards that don't scrape metrics
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Solves whose times the percentiles are taken over, most recent first out
+pub const SOLVE_TIME_WINDOW: usize = 10_000;
+
+#[derive(Default)]
+struct Counters {
+    by_status: BTreeMap<String, u64>,
+    by_backend: BTreeMap<String, u64>,
+    solves: u64,
+    total_solve_ms: f64,
+    /// Times of the last `SOLVE_TIME_WINDOW` solves, oldest first
+    recent_ms: VecDeque<f64>,
+}
+
+/// Point-in-time view of the server statistics
+#[derive(Debug, Clone, Default)]
+pub struct StatsSnapshot {
+    pub uptime: Duration,
+    pub solves: u64,
+    pub by_status: BTreeMap<String, u64>,
+    pub by_backend: BTreeMap<String, u64>,
+    /// Mean over every solve since startup
+    pub mean_solve_ms: f64,
+    /// Percentiles over the last `SOLVE_TIME_WINDOW` solves
+    pub p50_solve_ms: f64,
+    pub p90_solve_ms: f64,
+    pub p99_solve_ms: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+impl StatsSnapshot {
+    /// Share of idempotency-key lookups answered from the cache, 0 without any
+    pub fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Statistics collected since the server started
+pub struct ServerStats {
+    started_at: Instant,
+    counters: Mutex<Counters>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+impl ServerStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            counters: Mutex::new(Counters::default()),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a finished solve: `status` is its outcome (`OPTIMAL`, `FAILED`, ...)
+    /// and `backend` the solver that ran it
+    pub fn record_solve(&self, status: &str, backend: &str, solve_ms: f64) {
+        let mut counters = self.lock();
+        *counters.by_status.entry(status.to_string()).or_default() += 1;
+        *counters.by_backend.entry(backend.to_string()).or_default() += 1;
+        counters.solves += 1;
+        counters.total_solve_ms += solve_ms;
+        if counters.recent_ms.len() == SOLVE_TIME_WINDOW {
+            counters.recent_ms.pop_front();
+        }
+        counters.recent_ms.push_back(solve_ms);
+    }
+
+    /// Count an idempotency-key lookup, `hit` when it replayed a cached result
+    pub fn record_cache_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let counters = self.lock();
+        let mut recent: Vec<f64> = counters.recent_ms.iter().copied().collect();
+        recent.sort_by(f64::total_cmp);
+
+        StatsSnapshot {
+            uptime: self.started_at.elapsed(),
+            solves: counters.solves,
+            by_status: counters.by_status.clone(),
+            by_backend: counters.by_backend.clone(),
+            mean_solve_ms: if counters.solves == 0 {
+                0.0
+            } else {
+                counters.total_solve_ms / counters.solves as f64
+            },
+            p50_solve_ms: percentile(&recent, 0.50),
+            p90_solve_ms: percentile(&recent, 0.90),
+            p99_solve_ms: percentile(&recent, 0.99),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Nearest-rank percentile of sorted `values`, 0 when empty
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,127 @@
+// Model sessions: Problems kept built in server memory between requests
+// CreateModelSession builds one, UpdateModel edits it and ReSolve solves it as it stands
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tonic::Status;
+use uuid::Uuid;
+
+use crate::domain::model_session::ModelSession;
+
+/// Default number of sessions a server keeps at once
+pub const DEFAULT_MAX_SESSIONS: usize = 64;
+
+/// Default time an unused session is kept
+pub const DEFAULT_SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(1800);
+
+/// A session, locked while it is updated or solved so requests on it take turns
+pub type SharedSession = Arc<tokio::sync::Mutex<Box<dyn ModelSession>>>;
+
+struct StoredSession {
+    session: SharedSession,
+    backend: String,
+    last_used: Instant,
+}
+
+/// Open sessions by ID
+///
+/// Sessions unused for longer than the idle timeout are dropped, unless a
+/// request is still working on them.
+pub struct SessionStore {
+    max_sessions: usize,
+    idle_timeout: Duration,
+    sessions: Mutex<HashMap<String, StoredSession>>,
+}
+
+impl SessionStore {
+    pub fn new(max_sessions: usize, idle_timeout: Duration) -> Self {
+        Self {
+            max_sessions,
+            idle_timeout,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    /// Keep `session`, solved by `backend`, under a new ID
+    pub fn insert(
+        &self,
+        session: Box<dyn ModelSession>,
+        backend: &str,
+    ) -> Result<String, Box<Status>> {
+        let mut sessions = self.lock();
+        if sessions.len() >= self.max_sessions {
+            return Err(Box::new(Status::resource_exhausted(format!(
+                "Server already holds {} model sessions; close one first",
+                self.max_sessions
+            ))));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        sessions.insert(
+            id.clone(),
+            StoredSession {
+                session: Arc::new(tokio::sync::Mutex::new(session)),
+                backend: backend.to_string(),
+                last_used: Instant::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Session `id` and the name of its solver
+    pub fn get(&self, id: &str) -> Result<(SharedSession, String), Box<Status>> {
+        let mut sessions = self.lock();
+        let stored = sessions.get_mut(id).ok_or_else(|| unknown(id))?;
+        stored.last_used = Instant::now();
+        Ok((Arc::clone(&stored.session), stored.backend.clone()))
+    }
+
+    /// Forget session `id`; requests already working on it finish first
+    pub fn remove(&self, id: &str) -> Result<(SharedSession, String), Box<Status>> {
+        let stored = self.lock().remove(id).ok_or_else(|| unknown(id))?;
+        Ok((stored.session, stored.backend))
+    }
+
+    /// Number of open sessions
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The session map, without the sessions that have been idle too long
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, StoredSession>> {
+        // A panic while holding the lock cannot leave the map inconsistent
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let idle_timeout = self.idle_timeout;
+        sessions.retain(|_, stored| {
+            stored.last_used.elapsed() < idle_timeout || Arc::strong_count(&stored.session) > 1
+        });
+        sessions
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SESSIONS, DEFAULT_SESSION_IDLE_TIMEOUT)
+    }
+}
+
+fn unknown(id: &str) -> Box<Status> {
+    Box::new(Status::not_found(format!(
+        "No model session '{}'; it may have been closed or expired",
+        id
+    )))
+}
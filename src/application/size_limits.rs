@@ -0,0 +1,222 @@
+// Size limits: Cap how large a single problem may be
+// Oversized requests are rejected before they can exhaust server memory
+
+use std::fmt;
+
+use tonic::Status;
+
+use super::mappers::lp_solver::{error_detail::ErrorCode, ErrorDetail};
+
+use crate::domain::models::{Constraint, OptimizationProblem};
+
+/// Per-request caps on problem dimensions (None = unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProblemSizeLimits {
+    pub max_variables: Option<usize>,
+    pub max_constraints: Option<usize>,
+    /// Non-zero coefficients across all constraints
+    pub max_nonzeros: Option<usize>,
+}
+
+impl ProblemSizeLimits {
+    /// No limits
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_variables(mut self, max: usize) -> Self {
+        self.max_variables = Some(max);
+        self
+    }
+
+    pub fn with_max_constraints(mut self, max: usize) -> Self {
+        self.max_constraints = Some(max);
+        self
+    }
+
+    pub fn with_max_nonzeros(mut self, max: usize) -> Self {
+        self.max_nonzeros = Some(max);
+        self
+    }
+
+    /// Describe the first limit the problem exceeds, if any
+    pub fn violation(&self, problem: &OptimizationProblem) -> Option<String> {
+        self.excess(problem).map(|excess| excess.to_string())
+    }
+
+    fn excess(&self, problem: &OptimizationProblem) -> Option<Excess> {
+        let num_variables = problem.num_variables().max(problem.variables.len());
+
+        exceeds("variables", num_variables, self.max_variables)
+            .or_else(|| {
+                exceeds(
+                    "constraints",
+                    problem.constraints.len(),
+                    self.max_constraints,
+                )
+            })
+            .or_else(|| exceeds("non-zeros", problem.num_nonzeros(), self.max_nonzeros))
+    }
+
+    /// Reject a problem that exceeds any limit
+    pub fn check(&self, problem: &OptimizationProblem) -> Result<(), Box<Status>> {
+        match self.excess(problem) {
+            Some(excess) => Err(excess.into_status()),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject appending `constraints` to `problem` if that would exceed a limit
+    pub fn check_growth(
+        &self,
+        problem: &OptimizationProblem,
+        constraints: &[Constraint],
+    ) -> Result<(), Box<Status>> {
+        let mut tracker = self.tracker();
+        tracker.objective_terms = problem.num_variables();
+        tracker.constraints = problem.constraints.len();
+        tracker.nonzeros = problem.num_nonzeros();
+        constraints
+            .iter()
+            .try_for_each(|constraint| tracker.add_constraint(constraint.coefficients.values()))
+    }
+
+    /// Start tracking a problem that arrives in pieces
+    pub fn tracker(&self) -> SizeTracker {
+        SizeTracker {
+            limits: *self,
+            variables: 0,
+            objective_terms: 0,
+            constraints: 0,
+            nonzeros: 0,
+        }
+    }
+}
+
+/// Running size check for streamed problems
+///
+/// Fails as soon as a chunk pushes the problem past a limit, so the server stops
+/// buffering instead of collecting the whole stream first.
+pub struct SizeTracker {
+    limits: ProblemSizeLimits,
+    variables: usize,
+    objective_terms: usize,
+    constraints: usize,
+    nonzeros: usize,
+}
+
+impl SizeTracker {
+    pub fn add_objective(&mut self, num_terms: usize) -> Result<(), Box<Status>> {
+        self.objective_terms = num_terms;
+        self.check_variables()
+    }
+
+    pub fn add_variable(&mut self) -> Result<(), Box<Status>> {
+        self.variables += 1;
+        self.check_variables()
+    }
+
+    /// Count a row, sparse or dense, by the values it was sent with
+    pub fn add_constraint(&mut self, coefficients: &[f64]) -> Result<(), Box<Status>> {
+        self.constraints += 1;
+        self.nonzeros += coefficients.iter().filter(|&&c| c != 0.0).count();
+
+        match exceeds("constraints", self.constraints, self.limits.max_constraints)
+            .or_else(|| exceeds("non-zeros", self.nonzeros, self.limits.max_nonzeros))
+        {
+            Some(excess) => Err(excess.into_status()),
+            None => Ok(()),
+        }
+    }
+
+    fn num_variables(&self) -> usize {
+        self.variables.max(self.objective_terms)
+    }
+
+    fn check_variables(&self) -> Result<(), Box<Status>> {
+        match exceeds("variables", self.num_variables(), self.limits.max_variables) {
+            Some(excess) => Err(excess.into_status()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A count over its limit
+struct Excess {
+    what: &'static str,
+    count: usize,
+    max: usize,
+}
+
+impl Excess {
+    fn into_status(self) -> Box<Status> {
+        ErrorDetail::with_code(ErrorCode::ProblemTooLarge)
+            .with_entity(self.what, None)
+            .with_values(format!("at most {}", self.max), self.count)
+            .invalid_argument(self.to_string())
+    }
+}
+
+impl fmt::Display for Excess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Problem has {} {}, exceeding the server limit of {}",
+            self.count, self.what, self.max
+        )
+    }
+}
+
+fn exceeds(what: &'static str, count: usize, max: Option<usize>) -> Option<Excess> {
+    let max = max?;
+    (count > max).then_some(Excess { what, count, max })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ObjectiveFunction;
+    use crate::domain::value_objects::{ConstraintType, OptimizationType};
+
+    fn limits() -> ProblemSizeLimits {
+        ProblemSizeLimits::unlimited().with_max_nonzeros(1000)
+    }
+
+    #[test]
+    fn rows_count_their_non_zeros_however_wide() {
+        let mut tracker = limits().tracker();
+        tracker.add_objective(100_000).unwrap();
+        for _ in 0..500 {
+            tracker.add_constraint(&[1.0, 0.0, 2.0]).unwrap();
+        }
+        let status = tracker.add_constraint(&[1.0]).unwrap_err();
+        assert_eq!(
+            status.message(),
+            "Problem has 1001 non-zeros, exceeding the server limit of 1000"
+        );
+    }
+
+    #[test]
+    fn whole_problems_count_their_stored_non_zeros() {
+        let mut sparse = vec![0.0; 600];
+        sparse[7] = 1.0;
+        let row = |coefficients: Vec<f64>| {
+            Constraint::new(ConstraintType::LessThanOrEqual, coefficients, 1.0)
+        };
+        let problem = OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Minimize,
+            vec![1.0; 600],
+        ))
+        .add_constraint(row(sparse.clone()))
+        .add_constraint(row(sparse));
+        assert_eq!(limits().violation(&problem), None);
+
+        let problem = problem
+            .add_constraint(row(vec![1.0; 600]))
+            .add_constraint(row(vec![1.0; 600]));
+        assert_eq!(
+            limits().violation(&problem).unwrap(),
+            "Problem has 1202 non-zeros, exceeding the server limit of 1000"
+        );
+    }
+}
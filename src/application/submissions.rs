@@ -0,0 +1,90 @@
+// Submissions: Solves that keep running after the RPC that queued them returns
+// SubmitProblem hands back a result ID at once; GetResult collects the outcome
+// once the solve has finished
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tonic::Status;
+use uuid::Uuid;
+
+use super::mappers::lp_solver;
+
+/// Default time a finished submission's outcome stays available
+pub const DEFAULT_RESULT_TTL: Duration = Duration::from_secs(3600);
+
+/// Where a submitted solve stands
+#[derive(Debug, Clone)]
+pub enum Submission {
+    Running,
+    Finished(Box<Result<lp_solver::OptimizationResult, Status>>),
+}
+
+struct Entry {
+    submission: Submission,
+    finished_at: Option<Instant>,
+}
+
+/// Submitted solves by result ID
+///
+/// Outcomes are dropped `ttl` after they finish, whether or not anyone
+/// collected them.
+pub struct SubmittedResults {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl SubmittedResults {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Track a new running solve, returning its result ID
+    pub fn start(&self) -> String {
+        let id = Uuid::new_v4().to_string();
+        let mut entries = self.lock();
+        let ttl = self.ttl;
+        entries.retain(|_, entry| entry.finished_at.is_none_or(|at| at.elapsed() < ttl));
+        entries.insert(
+            id.clone(),
+            Entry {
+                submission: Submission::Running,
+                finished_at: None,
+            },
+        );
+        id
+    }
+
+    /// Record the outcome of the solve `id`
+    pub fn finish(&self, id: &str, outcome: Result<lp_solver::OptimizationResult, Status>) {
+        self.lock().insert(
+            id.to_string(),
+            Entry {
+                submission: Submission::Finished(Box::new(outcome)),
+                finished_at: Some(Instant::now()),
+            },
+        );
+    }
+
+    /// The solve `id`, unless it is unknown or its outcome has expired
+    pub fn get(&self, id: &str) -> Option<Submission> {
+        self.lock()
+            .get(id)
+            .filter(|entry| entry.finished_at.is_none_or(|at| at.elapsed() < self.ttl))
+            .map(|entry| entry.submission.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for SubmittedResults {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESULT_TTL)
+    }
+}
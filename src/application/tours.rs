@@ -0,0 +1,282 @@
+// Tours: Travelling salesman and other routing models, by lazy subtour elimination
+// Solve without subtour constraints, cut the subtours the incumbent contains, and
+// re-solve until it is a single tour; any SolverService can do the solving
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use tracing::debug;
+
+use crate::domain::modeling::{sum_over, Model, VarRef};
+use crate::domain::models::{Constraint, OptimizationProblem, Solution};
+use crate::domain::solver_service::{
+    CancellationToken, NullObserver, Result, SolverError, SolverService,
+};
+use crate::domain::value_objects::ConstraintType;
+
+/// Rounds of cutting before giving up
+const DEFAULT_MAX_ROUNDS: usize = 100;
+
+/// Arc values above this count as taken
+const TAKEN: f64 = 0.5;
+
+/// A problem whose binary arc variables must form one tour through every node
+///
+/// The problem itself only needs each node entered and left once; subtour
+/// elimination constraints are added lazily by [`TourSolver`]. Side constraints
+/// and extra variables are kept as they are.
+#[derive(Debug, Clone)]
+pub struct TourModel {
+    problem: OptimizationProblem,
+    nodes: usize,
+    /// `(column, from, to)` of each arc variable
+    arcs: Vec<(usize, usize, usize)>,
+}
+
+impl TourModel {
+    /// Tour over `nodes` nodes, whose arc variables are `(var, from, to)`
+    pub fn new(
+        problem: OptimizationProblem,
+        nodes: usize,
+        arcs: impl IntoIterator<Item = (VarRef, usize, usize)>,
+    ) -> Self {
+        let arcs = arcs
+            .into_iter()
+            .map(|(var, from, to)| (var.index(), from, to))
+            .collect();
+        Self {
+            problem,
+            nodes,
+            arcs,
+        }
+    }
+
+    /// Shortest tour visiting every node once, `distances[i][j]` being the length
+    /// of the arc from `i` to `j`
+    ///
+    /// Distances need not be symmetric; infinite ones mark arcs that don't exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix isn't square or has fewer than two nodes.
+    pub fn tsp(distances: &[Vec<f64>]) -> Self {
+        let n = distances.len();
+        assert!(n >= 2, "a tour needs at least two nodes");
+        assert!(
+            distances.iter().all(|row| row.len() == n),
+            "distance matrix must be square"
+        );
+
+        let mut model = Model::new("tsp");
+        let mut arcs = Vec::new();
+        for (i, row) in distances.iter().enumerate() {
+            for (j, &distance) in row.iter().enumerate() {
+                if i != j && distance.is_finite() {
+                    arcs.push((model.binary(format!("x_{}_{}", i, j)), i, j));
+                }
+            }
+        }
+        for node in 0..n {
+            let leaving = arcs.iter().filter(|&&(_, from, _)| from == node);
+            model.add(
+                sum_over(leaving.map(|&(var, _, _)| var))
+                    .equals(1)
+                    .with_name(format!("leave_{}", node)),
+            );
+            let entering = arcs.iter().filter(|&&(_, _, to)| to == node);
+            model.add(
+                sum_over(entering.map(|&(var, _, _)| var))
+                    .equals(1)
+                    .with_name(format!("enter_{}", node)),
+            );
+        }
+        model.minimize(sum_over(
+            arcs.iter()
+                .map(|&(var, from, to)| distances[from][to] * var),
+        ));
+
+        Self::new(model.build(), n, arcs)
+    }
+
+    /// The problem without any subtour constraints
+    pub fn problem(&self) -> &OptimizationProblem {
+        &self.problem
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Node sets of the cycles the taken arcs form
+    fn subtours(&self, values: &[f64]) -> Vec<Vec<usize>> {
+        let mut group: Vec<usize> = (0..self.nodes).collect();
+        for &(column, from, to) in &self.arcs {
+            if values[column] > TAKEN {
+                let (a, b) = (find(&mut group, from), find(&mut group, to));
+                group[a] = b;
+            }
+        }
+        let mut members = vec![Vec::new(); self.nodes];
+        for node in 0..self.nodes {
+            let root = find(&mut group, node);
+            members[root].push(node);
+        }
+        members.retain(|nodes| !nodes.is_empty());
+        members
+    }
+
+    /// At most `|S| - 1` arcs within the subtour's nodes `S`
+    fn elimination_cut(&self, nodes: &[usize], name: String) -> Option<Constraint> {
+        let mut inside = vec![false; self.nodes];
+        for &node in nodes {
+            inside[node] = true;
+        }
+        let mut coefficients = vec![0.0; self.problem.num_variables()];
+        let mut any = false;
+        for &(column, from, to) in &self.arcs {
+            if inside[from] && inside[to] {
+                coefficients[column] = 1.0;
+                any = true;
+            }
+        }
+        any.then(|| {
+            Constraint::new(
+                ConstraintType::LessThanOrEqual,
+                coefficients,
+                (nodes.len() - 1) as f64,
+            )
+            .with_name(name)
+        })
+    }
+
+    /// Nodes in visiting order from node 0, if the taken arcs are one tour
+    fn tour(&self, values: &[f64]) -> Option<Vec<usize>> {
+        let mut next = vec![None; self.nodes];
+        for &(column, from, to) in &self.arcs {
+            if values[column] > TAKEN {
+                if next[from].is_some() {
+                    return None;
+                }
+                next[from] = Some(to);
+            }
+        }
+        let mut order = vec![0];
+        let mut node = next[0]?;
+        while node != 0 {
+            if order.len() == self.nodes {
+                return None;
+            }
+            order.push(node);
+            node = next[node]?;
+        }
+        (order.len() == self.nodes).then_some(order)
+    }
+}
+
+/// Result of solving a [`TourModel`]
+#[derive(Debug, Clone)]
+pub struct TourSolution {
+    /// Solution of the last round, including the subtour constraints' rows
+    pub solution: Solution,
+    /// Nodes in visiting order from node 0; None unless a tour was found
+    pub tour: Option<Vec<usize>>,
+    /// Solves it took
+    pub rounds: usize,
+    /// Subtour elimination constraints added
+    pub cuts: usize,
+}
+
+/// Solves [`TourModel`]s by repeatedly solving and cutting off subtours
+pub struct TourSolver {
+    solver: Arc<dyn SolverService>,
+    max_rounds: usize,
+}
+
+impl TourSolver {
+    pub fn new(solver: Arc<dyn SolverService>) -> Self {
+        Self {
+            solver,
+            max_rounds: DEFAULT_MAX_ROUNDS,
+        }
+    }
+
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds.max(1);
+        self
+    }
+
+    pub fn solve(&self, model: &TourModel) -> Result<TourSolution> {
+        self.solve_cancellable(model, &CancellationToken::new())
+    }
+
+    /// Solve, stopping between or during rounds once `cancel` is set
+    ///
+    /// A round that ends without a feasible solution (infeasible, or stopped by the
+    /// time limit before finding one) ends the search, returned with no tour.
+    pub fn solve_cancellable(
+        &self,
+        model: &TourModel,
+        cancel: &CancellationToken,
+    ) -> Result<TourSolution> {
+        let start_time = Instant::now();
+        let mut problem = model.problem.clone();
+        let mut cuts = 0;
+
+        for round in 1..=self.max_rounds {
+            if cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            let mut solution = self
+                .solver
+                .solve_cancellable(&problem, &NullObserver, cancel)?;
+            solution.statistics.solve_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+            if !solution.is_feasible() {
+                return Ok(TourSolution {
+                    solution,
+                    tour: None,
+                    rounds: round,
+                    cuts,
+                });
+            }
+
+            let subtours = model.subtours(&solution.variable_values);
+            if subtours.len() == 1 {
+                return Ok(TourSolution {
+                    tour: model.tour(&solution.variable_values),
+                    solution,
+                    rounds: round,
+                    cuts,
+                });
+            }
+
+            debug!(round, subtours = subtours.len(), "Cutting subtours");
+            let before = cuts;
+            for nodes in &subtours {
+                if let Some(cut) = model.elimination_cut(nodes, format!("subtour_{}", cuts)) {
+                    problem.constraints.push(cut);
+                    cuts += 1;
+                }
+            }
+            if cuts == before {
+                return Err(SolverError::ExecutionFailed(
+                    "Incumbent visits nodes with no arcs; every node must be entered and left once"
+                        .to_string(),
+                ));
+            }
+        }
+
+        Err(SolverError::ExecutionFailed(format!(
+            "Subtours remained after {} rounds ({} cuts)",
+            self.max_rounds, cuts
+        )))
+    }
+}
+
+/// Union-find root of `x`, halving the path on the way
+fn find(group: &mut [usize], mut x: usize) -> usize {
+    while group[x] != x {
+        group[x] = group[group[x]];
+        x = group[x];
+    }
+    x
+}
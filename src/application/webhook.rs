@@ -0,0 +1,214 @@
+// Completion webhooks: Tell a submitted solve's callback URL that it finished
+// The server POSTs a JSON summary, so batch orchestrators can react to a
+// finished solve instead of polling GetResult
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Method, Request, Uri};
+use serde::Serialize;
+use tonic::Status;
+use tracing::{info, warn};
+
+use super::http_client::{host_name, HttpConnector};
+use super::mappers::lp_solver;
+
+/// Time allowed for each delivery attempt
+pub const DEFAULT_WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Deliveries tried before giving up, one second apart and then doubling
+pub const DEFAULT_WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// gRPC method that returns the full result a notice refers to
+pub const GET_RESULT_METHOD: &str = "/lp_solver.LinearProgrammingSolver/GetResult";
+
+/// Which callback URLs the server may POST to
+///
+/// Callbacks make the server send requests on a client's behalf, so hosts have
+/// to be allowed explicitly.
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Hosts callbacks may go to (`*` allows any)
+    pub allowed_hosts: Vec<String>,
+    pub timeout: Duration,
+    pub attempts: u32,
+}
+
+impl WebhookConfig {
+    pub fn new(allowed_hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            allowed_hosts: allowed_hosts.into_iter().map(Into::into).collect(),
+            timeout: DEFAULT_WEBHOOK_TIMEOUT,
+            attempts: DEFAULT_WEBHOOK_ATTEMPTS,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    pub fn allows(&self, host: &str) -> bool {
+        self.allowed_hosts
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Body POSTed to a callback URL
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionNotice {
+    /// Pass to GetResult for the full result
+    pub result_id: String,
+    /// Where to get it: the GetResult method
+    pub result_method: String,
+    pub request_id: String,
+    /// Solution status, or "REJECTED"/"FAILED" when no solution was produced, as
+    /// in the audit log
+    pub status: String,
+    pub objective: Option<f64>,
+    pub gap: Option<f64>,
+    pub message: String,
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl CompletionNotice {
+    pub fn new(
+        result_id: impl Into<String>,
+        request_id: impl Into<String>,
+        outcome: &Result<lp_solver::OptimizationResult, Status>,
+    ) -> Self {
+        let mut notice = Self {
+            result_id: result_id.into(),
+            result_method: GET_RESULT_METHOD.to_string(),
+            request_id: request_id.into(),
+            status: String::new(),
+            objective: None,
+            gap: None,
+            message: String::new(),
+            error: None,
+            tags: BTreeMap::new(),
+        };
+        match outcome {
+            Ok(result) => {
+                notice.status = lp_solver::SolutionStatus::try_from(result.status)
+                    .map(|s| s.as_str_name().to_string())
+                    .unwrap_or_default();
+                notice.objective = result.optimal_value;
+                notice.gap = result.gap;
+                notice.message = result.message.clone();
+                notice.tags = result.tags.clone().into_iter().collect();
+            }
+            Err(status) => {
+                notice.status = match status.code() {
+                    tonic::Code::InvalidArgument | tonic::Code::ResourceExhausted => "REJECTED",
+                    _ => "FAILED",
+                }
+                .to_string();
+                notice.error = Some(status.message().to_string());
+            }
+        }
+        notice
+    }
+}
+
+/// Delivers completion notices
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    http: HttpConnector,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self {
+            config,
+            http: HttpConnector::new(),
+        }
+    }
+
+    pub fn config(&self) -> &WebhookConfig {
+        &self.config
+    }
+
+    /// `url` as a callback, or why the server won't call it
+    pub fn check(&self, url: &str) -> Result<Uri, Box<Status>> {
+        let uri: Uri = url.parse().map_err(|e| {
+            Box::new(Status::invalid_argument(format!(
+                "Bad callback URL '{}': {}",
+                url, e
+            )))
+        })?;
+        let host = match (uri.scheme_str(), uri.host()) {
+            (Some("http" | "https"), Some(_)) => host_name(&uri),
+            _ => {
+                return Err(Box::new(Status::invalid_argument(format!(
+                    "Callback URL '{}' must be http:// or https://",
+                    url
+                ))))
+            }
+        };
+        if !self.config.allows(host) {
+            return Err(Box::new(Status::permission_denied(format!(
+                "Callbacks to '{}' are not allowed",
+                host
+            ))));
+        }
+        Ok(uri)
+    }
+
+    /// POST `notice` to `uri`, retrying failed deliveries; a notice that can't be
+    /// delivered is only logged
+    pub async fn notify(&self, uri: &Uri, notice: &CompletionNotice) {
+        let body = match serde_json::to_vec(notice) {
+            Ok(body) => Bytes::from(body),
+            Err(e) => {
+                warn!(error = %e, "Cannot encode completion notice");
+                return;
+            }
+        };
+        let mut backoff = Duration::from_secs(1);
+        for attempt in 1..=self.config.attempts {
+            let delivery = tokio::time::timeout(self.config.timeout, self.post(uri, body.clone()));
+            let error = match delivery.await {
+                Ok(Ok(())) => {
+                    info!(url = %uri, result_id = %notice.result_id, "Delivered completion notice");
+                    return;
+                }
+                Ok(Err(e)) => e,
+                Err(_) => format!("no response within {:?}", self.config.timeout),
+            };
+            warn!(url = %uri, attempt, error = %error, "Completion notice not delivered");
+            if attempt < self.config.attempts {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+
+    /// One delivery attempt; redirects are not followed
+    async fn post(&self, uri: &Uri, body: Bytes) -> Result<(), String> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json");
+        let status = self
+            .http
+            .send(uri, request, Full::new(body))
+            .await?
+            .status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {}", status))
+        }
+    }
+}
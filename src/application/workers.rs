@@ -0,0 +1,424 @@
+// Distributed solving: A dispatcher farms solves out to registered letsopt workers
+// Workers announce themselves with a shared token and send heartbeats; a solve goes
+// to the least busy live worker and moves to another one if its worker is lost
+// mid-solve
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Status};
+use tracing::{info, warn};
+
+use super::error_details;
+use super::mappers::{self, lp_solver};
+use super::request_id::RequestId;
+use crate::domain::models::{OptimizationProblem, Solution};
+use crate::domain::solver_service::{
+    report_solution, CancellationToken, NullObserver, Result as SolverResult, SolveObserver,
+    SolverError, SolverService,
+};
+use crate::domain::value_objects::IntegerRounding;
+
+use lp_solver::error_detail::ErrorCode;
+use lp_solver::linear_programming_solver_client::LinearProgrammingSolverClient;
+
+/// How long a worker may go without a heartbeat before it is considered lost
+pub const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Metadata header workers send the dispatcher's worker token in
+pub const WORKER_TOKEN_HEADER: &str = "x-worker-token";
+
+/// Workers a solve may lose before it fails; a problem that keeps taking workers
+/// down would take the dispatcher down too if it were solved there
+const MAX_WORKER_LOSSES: usize = 2;
+
+/// How often a dispatched solve checks for cancellation
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+struct Worker {
+    address: String,
+    /// Solves the worker runs at once (None = unlimited)
+    capacity: Option<usize>,
+    /// Solves dispatched to it that haven't returned yet
+    active: usize,
+    last_seen: Instant,
+    client: LinearProgrammingSolverClient<Channel>,
+}
+
+impl Worker {
+    /// Share of its capacity in use, for picking the least busy worker
+    fn load(&self) -> f64 {
+        match self.capacity {
+            Some(capacity) => self.active as f64 / capacity.max(1) as f64,
+            None => 0.0,
+        }
+    }
+}
+
+/// A worker checked out for one solve
+struct Assignment {
+    id: String,
+    address: String,
+    client: LinearProgrammingSolverClient<Channel>,
+}
+
+/// Workers registered with a dispatcher
+pub struct WorkerPool {
+    heartbeat_timeout: Duration,
+    /// Shared secret workers have to present to register and send heartbeats
+    token: String,
+    next_id: AtomicU64,
+    workers: Mutex<HashMap<String, Worker>>,
+}
+
+impl WorkerPool {
+    /// Pool that admits workers presenting `token`
+    pub fn new(heartbeat_timeout: Duration, token: impl Into<String>) -> Self {
+        Self {
+            heartbeat_timeout,
+            token: token.into(),
+            next_id: AtomicU64::new(0),
+            workers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Interval workers are told to send heartbeats at, well inside the timeout
+    pub fn heartbeat_interval(&self) -> Duration {
+        self.heartbeat_timeout / 3
+    }
+
+    /// Check the worker token in a registration or heartbeat's metadata
+    ///
+    /// Anyone who can register receives the problems dispatched to it and answers
+    /// for their solutions, so callers without the token get PERMISSION_DENIED.
+    pub fn authorize(&self, metadata: &MetadataMap) -> Result<(), Box<Status>> {
+        let presented = metadata
+            .get(WORKER_TOKEN_HEADER)
+            .map(|value| value.as_bytes())
+            .unwrap_or_default();
+        if !self.token.is_empty() && constant_time_eq(presented, self.token.as_bytes()) {
+            Ok(())
+        } else {
+            warn!("Worker call without a valid worker token refused");
+            Err(Box::new(Status::permission_denied(
+                "Missing or invalid worker token",
+            )))
+        }
+    }
+
+    /// Add a worker reachable at `address`, replacing any earlier registration
+    /// from that address, and return its id
+    pub fn register(&self, address: &str, capacity: Option<usize>) -> Result<String, Box<Status>> {
+        let endpoint = Endpoint::from_shared(address.to_string()).map_err(|e| {
+            Box::new(Status::invalid_argument(format!(
+                "Invalid worker address '{}': {}",
+                address, e
+            )))
+        })?;
+        let client = LinearProgrammingSolverClient::new(endpoint.connect_lazy())
+            .max_decoding_message_size(usize::MAX)
+            .max_encoding_message_size(usize::MAX);
+
+        let id = format!(
+            "worker-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let mut workers = self.lock();
+        workers.retain(|_, worker| worker.address != address);
+        workers.insert(
+            id.clone(),
+            Worker {
+                address: address.to_string(),
+                capacity,
+                active: 0,
+                last_seen: Instant::now(),
+                client,
+            },
+        );
+        info!(worker = id, address, capacity, "Worker registered");
+        Ok(id)
+    }
+
+    /// Record a heartbeat; NOT_FOUND tells a worker that was dropped to register again
+    pub fn heartbeat(&self, id: &str) -> Result<(), Box<Status>> {
+        let mut workers = self.lock();
+        self.expire(&mut workers);
+        match workers.get_mut(id) {
+            Some(worker) => {
+                worker.last_seen = Instant::now();
+                Ok(())
+            }
+            None => Err(Box::new(Status::not_found(format!(
+                "Unknown worker '{}'; register again",
+                id
+            )))),
+        }
+    }
+
+    /// Number of live workers
+    pub fn live(&self) -> usize {
+        let mut workers = self.lock();
+        self.expire(&mut workers);
+        workers.len()
+    }
+
+    /// Least busy live worker, if any; a full one queues the solve itself
+    fn checkout(&self) -> Option<Assignment> {
+        let mut workers = self.lock();
+        self.expire(&mut workers);
+        let (id, worker) = workers
+            .iter_mut()
+            .min_by(|(_, a), (_, b)| a.load().total_cmp(&b.load()))?;
+        worker.active += 1;
+        Some(Assignment {
+            id: id.clone(),
+            address: worker.address.clone(),
+            client: worker.client.clone(),
+        })
+    }
+
+    /// Hand back a worker after its solve returned
+    fn checkin(&self, assignment: &Assignment) {
+        if let Some(worker) = self.lock().get_mut(&assignment.id) {
+            worker.active = worker.active.saturating_sub(1);
+        }
+    }
+
+    /// Forget a worker that stopped answering
+    fn remove(&self, assignment: &Assignment) {
+        if self.lock().remove(&assignment.id).is_some() {
+            warn!(
+                worker = assignment.id,
+                address = assignment.address,
+                "Worker lost"
+            );
+        }
+    }
+
+    /// Drop workers whose heartbeats stopped
+    fn expire(&self, workers: &mut HashMap<String, Worker>) {
+        workers.retain(|id, worker| {
+            let alive = worker.last_seen.elapsed() < self.heartbeat_timeout;
+            if !alive {
+                warn!(
+                    worker = id,
+                    address = worker.address,
+                    "Worker missed its heartbeats"
+                );
+            }
+            alive
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Worker>> {
+        self.workers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Compare without stopping at the first differing byte, so response times don't
+/// give the token away
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Solver that runs each solve on a worker from the pool
+///
+/// While no worker is live the dispatcher's own `local` solver takes the solve,
+/// so a dispatcher keeps serving (and passes its self-test) before workers join.
+/// A solve that already lost a worker is never solved locally: it moves to one
+/// other worker at most, and fails once that one is lost too or none is left.
+pub struct RemoteSolver {
+    workers: Arc<WorkerPool>,
+    local: Arc<dyn SolverService>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl RemoteSolver {
+    /// Must be created inside the Tokio runtime the gRPC calls should run on
+    pub fn new(workers: Arc<WorkerPool>, local: Arc<dyn SolverService>) -> Self {
+        Self {
+            workers,
+            local,
+            runtime: tokio::runtime::Handle::current(),
+        }
+    }
+
+    /// Run one dispatched solve on a blocking thread, giving up once `cancel` is set
+    fn call<F>(&self, call: F, cancel: &CancellationToken) -> Option<Result<Solution, Box<Status>>>
+    where
+        F: Future<Output = Result<tonic::Response<lp_solver::OptimizationResult>, Status>>,
+    {
+        self.runtime.block_on(async {
+            let cancelled = async {
+                while !cancel.is_cancelled() {
+                    tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+                }
+            };
+            // Dropping the call resets the stream, which cancels the worker's job
+            tokio::select! {
+                outcome = call => Some(outcome.map_err(Box::new).and_then(|response| {
+                    mappers::proto_to_domain_solution(RequestId::untag_result(response))
+                })),
+                _ = cancelled => None,
+            }
+        })
+    }
+}
+
+impl SolverService for RemoteSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> SolverResult<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> SolverResult<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    /// Progress stays on the worker; the observer sees the final solution only
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> SolverResult<Solution> {
+        // Validate here so bad requests don't cost a round trip
+        self.validate(problem)?;
+
+        let request = worker_request(problem);
+        let mut losses = 0;
+        while losses < MAX_WORKER_LOSSES {
+            let Some(mut worker) = self.workers.checkout() else {
+                break;
+            };
+            let outcome = self.call(worker.client.solve_problem(request.clone()), cancel);
+            self.workers.checkin(&worker);
+            let solution = match outcome {
+                None => return Err(SolverError::Cancelled),
+                Some(Ok(solution)) => solution,
+                Some(Err(status)) if worker_lost(&status) => {
+                    warn!(
+                        worker = worker.id,
+                        error = status.message(),
+                        "Dispatched solve lost its worker; retrying"
+                    );
+                    self.workers.remove(&worker);
+                    losses += 1;
+                    continue;
+                }
+                Some(Err(status)) => return Err(solver_error(&status)),
+            };
+            info!(
+                worker = worker.id,
+                address = worker.address,
+                "Dispatched solve finished"
+            );
+            report_solution(observer, &solution);
+            return Ok(solution);
+        }
+
+        if cancel.is_cancelled() {
+            return Err(SolverError::Cancelled);
+        }
+        if losses > 0 {
+            return Err(SolverError::ExecutionFailed(format!(
+                "Solve lost {} worker{}; not retrying it",
+                losses,
+                if losses == 1 { "" } else { "s" }
+            )));
+        }
+        self.local.solve_cancellable(problem, observer, cancel)
+    }
+
+    fn name(&self) -> &str {
+        self.local.name()
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.local.supports_mip()
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.local.supports_lp()
+    }
+}
+
+/// The problem as a worker should solve it: the dispatcher already routed it and
+/// adds presolve, scaling, polishing and the result extras itself
+fn worker_request(problem: &OptimizationProblem) -> lp_solver::OptimizationProblem {
+    let mut problem = problem.clone();
+    let config = &mut problem.solver_config;
+    config.solver_name = None;
+    config.presolve = false;
+    config.auto_scale = false;
+    config.integer_rounding = IntegerRounding::Off;
+    config.zero_threshold = None;
+    config.explain = false;
+    config.export = None;
+    config.values_by_name = false;
+    mappers::domain_to_proto_problem(&problem)
+}
+
+/// Whether a failed call means the worker went away rather than the solve failing
+fn worker_lost(status: &Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::Unknown)
+}
+
+/// Domain error for a solve a worker refused or failed, by its error detail where
+/// the status code alone is ambiguous
+fn solver_error(status: &Status) -> SolverError {
+    let message = format!("Worker: {}", status.message());
+    let detail = error_details::error_detail(status).unwrap_or_default();
+    match (detail.code(), status.code()) {
+        (ErrorCode::NumericalDifficulty, _) => SolverError::NumericalDifficulty(message),
+        (ErrorCode::MemoryLimit, _) => SolverError::MemoryLimit(message),
+        (ErrorCode::UnsupportedFeature, _) => SolverError::UnsupportedFeature {
+            feature: detail.name,
+        },
+        (_, Code::InvalidArgument) => SolverError::InvalidProblem(message),
+        (_, Code::FailedPrecondition) => SolverError::SolverNotAvailable(message),
+        (_, Code::DeadlineExceeded) => SolverError::Timeout(message),
+        (_, Code::Cancelled) => SolverError::Cancelled,
+        _ => SolverError::ExecutionFailed(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(token: Option<&str>) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        if let Some(token) = token {
+            metadata.insert(WORKER_TOKEN_HEADER, token.parse().unwrap());
+        }
+        metadata
+    }
+
+    #[test]
+    fn workers_need_the_token() {
+        let pool = WorkerPool::new(DEFAULT_HEARTBEAT_TIMEOUT, "secret");
+
+        assert!(pool.authorize(&metadata(Some("secret"))).is_ok());
+        for token in [None, Some("wrong"), Some("secre"), Some("")] {
+            let status = pool.authorize(&metadata(token)).unwrap_err();
+            assert_eq!(status.code(), Code::PermissionDenied);
+        }
+    }
+
+    #[test]
+    fn an_empty_token_admits_nobody() {
+        let pool = WorkerPool::new(DEFAULT_HEARTBEAT_TIMEOUT, "");
+
+        assert!(pool.authorize(&metadata(None)).is_err());
+        assert!(pool.authorize(&metadata(Some(""))).is_err());
+    }
+}
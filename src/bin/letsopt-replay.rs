@@ -0,0 +1,123 @@
+// Replay a solve recording (`record-solves`) against a chosen backend and report
+// every solve whose status or objective changed
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use letsopt::domain::recording::{RecordedSolve, DEFAULT_REPLAY_TOLERANCE};
+use letsopt::infrastructure::config::BackendSetting;
+use letsopt::solver::replay;
+
+/// Variable changes listed per changed solve
+const MAX_LISTED_CHANGES: usize = 10;
+
+#[derive(Parser)]
+#[command(
+    name = "letsopt-replay",
+    about = "Re-run recorded solves and diff the results"
+)]
+struct Args {
+    /// Recording written by the server's `record-solves`
+    recording: PathBuf,
+
+    /// Backend to replay on; defaults to each solve's recorded backend choice
+    #[arg(long, value_enum)]
+    backend: Option<BackendSetting>,
+
+    /// Relative objective difference still counted as the same result
+    #[arg(long, default_value_t = DEFAULT_REPLAY_TOLERANCE)]
+    tolerance: f64,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let text = match std::fs::read_to_string(&args.recording) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error: Cannot read {}: {}", args.recording.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    // One solve per line
+    let solves: Vec<RecordedSolve> = match text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| (i + 1, e)))
+        .collect()
+    {
+        Ok(solves) => solves,
+        Err((line, e)) => {
+            eprintln!("Error: {} line {}: {}", args.recording.display(), line, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (mut reproduced, mut changed, mut failed) = (0, 0, 0);
+    for (i, recorded) in solves.iter().enumerate() {
+        let label = format!(
+            "[{}/{}] {} '{}'",
+            i + 1,
+            solves.len(),
+            recorded.request_id,
+            recorded.problem.name
+        );
+        let comparison = match replay(recorded, args.backend.map(Into::into), args.tolerance) {
+            Ok(comparison) => comparison,
+            Err(e) => {
+                failed += 1;
+                println!("{}: FAILED {}", label, e);
+                continue;
+            }
+        };
+        if comparison.reproduces() {
+            reproduced += 1;
+            println!("{}: same {}", label, comparison.replayed_status);
+            continue;
+        }
+
+        changed += 1;
+        println!("{}: CHANGED", label);
+        if comparison.recorded_status != comparison.replayed_status {
+            println!(
+                "    status {} -> {}",
+                comparison.recorded_status, comparison.replayed_status
+            );
+        }
+        if comparison.objective_differs {
+            println!(
+                "    objective {} -> {}",
+                objective_text(comparison.recorded_objective),
+                objective_text(comparison.replayed_objective)
+            );
+        }
+        if let Some(diff) = comparison.diff.filter(|diff| !diff.is_empty()) {
+            println!("    {} variables moved", diff.changes.len());
+            for change in diff.changes.iter().take(MAX_LISTED_CHANGES) {
+                println!(
+                    "      {}: {} -> {}",
+                    change.name, change.before, change.after
+                );
+            }
+        }
+    }
+
+    println!(
+        "{} solves: {} reproduced, {} changed, {} failed",
+        solves.len(),
+        reproduced,
+        changed,
+        failed
+    );
+    if changed + failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn objective_text(objective: Option<f64>) -> String {
+    objective.map_or_else(|| "none".to_string(), |value| value.to_string())
+}
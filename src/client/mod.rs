@@ -0,0 +1,689 @@
+// Client: Typed wrapper around the gRPC service for Rust consumers
+// Sends domain problems and returns domain solutions, streaming large models in
+// chunks and retrying with backoff when the server is briefly unavailable
+
+mod pool;
+mod retry;
+
+pub use pool::ChannelPool;
+pub use retry::RetryPolicy;
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use futures::{stream, StreamExt};
+use prost::Message;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status};
+use tracing::debug;
+
+use crate::application::error_details;
+use crate::application::idempotency::IDEMPOTENCY_KEY_HEADER;
+use crate::application::mappers::{self, lp_solver};
+use crate::application::pareto::ParetoFrontier;
+use crate::domain::model_session::ModelUpdate;
+use crate::domain::models::{ObjectiveFunction, OptimizationProblem, Solution, SolverConfig};
+use crate::domain::problem_file::ProblemFormat;
+use crate::domain::scenarios::Scenario;
+use crate::domain::solution_diff::SolutionDiff;
+use crate::domain::sweep::ParametricSweep;
+
+use lp_solver::linear_programming_solver_client::LinearProgrammingSolverClient;
+
+/// Encoded size above which problems are streamed in chunks of about this size,
+/// well under the 4 MiB message limit servers decode by default
+pub const DEFAULT_CHUNK_BYTES: usize = 1 << 20;
+
+/// Errors returned by [`LetsOptClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Cannot connect to server: {0}")]
+    Transport(#[from] tonic::transport::Error),
+
+    #[error("Server returned {}: {}", .0.code(), .0.message())]
+    Status(Box<Status>),
+
+    #[error("Problem failed validation: {}", .0.join("; "))]
+    InvalidProblem(Vec<String>),
+
+    #[error("Scenario '{name}' failed: {error}")]
+    ScenarioFailed { name: String, error: String },
+}
+
+impl ClientError {
+    /// Machine-readable cause the server attached to a failed call, if any
+    pub fn error_detail(&self) -> Option<lp_solver::ErrorDetail> {
+        match self {
+            ClientError::Status(status) => error_details::error_detail(status),
+            _ => None,
+        }
+    }
+}
+
+impl From<Status> for ClientError {
+    fn from(status: Status) -> Self {
+        ClientError::Status(Box::new(status))
+    }
+}
+
+impl From<Box<Status>> for ClientError {
+    fn from(status: Box<Status>) -> Self {
+        ClientError::Status(status)
+    }
+}
+
+/// LetsOpt client that speaks in domain models
+///
+/// Cloning is cheap and clones share the connections, so one client can serve
+/// many concurrent solves.
+#[derive(Debug, Clone)]
+pub struct LetsOptClient {
+    pool: ChannelPool,
+    chunk_bytes: usize,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+}
+
+impl LetsOptClient {
+    /// Connect to a server, e.g. `http://127.0.0.1:50051`
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self, ClientError> {
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        Ok(Self::new(channel))
+    }
+
+    /// Connect with `connections` connections, for many concurrent solves
+    pub async fn connect_pooled(
+        endpoint: impl Into<String>,
+        connections: usize,
+    ) -> Result<Self, ClientError> {
+        let endpoint = Endpoint::from_shared(endpoint.into())?;
+        Ok(Self::with_pool(
+            ChannelPool::connect(endpoint, connections).await?,
+        ))
+    }
+
+    /// Client over an existing channel, e.g. one configured for TLS
+    pub fn new(channel: Channel) -> Self {
+        Self::with_pool(channel.into())
+    }
+
+    /// Client that spreads its calls over a pool of channels
+    pub fn with_pool(pool: ChannelPool) -> Self {
+        Self {
+            pool,
+            chunk_bytes: DEFAULT_CHUNK_BYTES,
+            retry: RetryPolicy::default(),
+            timeout: None,
+        }
+    }
+
+    /// Stream problems whose encoding exceeds `bytes`, in chunks of about that size
+    pub fn with_chunk_bytes(mut self, bytes: usize) -> Self {
+        self.chunk_bytes = bytes;
+        self
+    }
+
+    /// Retry up to `retries` times, keeping the rest of the retry policy
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry = self.retry.with_max_retries(retries);
+        self
+    }
+
+    /// Decide which failures are retried and how long to wait in between
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Give every call this long to finish, retries included
+    ///
+    /// The server is told the remaining time, so it stops solving once the
+    /// caller has given up.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The generated client, for RPCs this wrapper doesn't cover
+    pub fn raw(&self) -> LinearProgrammingSolverClient<Channel> {
+        // Results grow with the model; the caller already trusts this server
+        LinearProgrammingSolverClient::new(self.pool.channel())
+            .max_decoding_message_size(usize::MAX)
+    }
+
+    /// Solve a problem on the server
+    ///
+    /// Small problems go in one request under an idempotency key, so a retry
+    /// after a lost response doesn't solve twice. Large ones are streamed.
+    pub async fn solve(&self, problem: &OptimizationProblem) -> Result<Solution, ClientError> {
+        self.solve_until(problem, self.timeout).await
+    }
+
+    /// Solve a problem, giving up after `timeout` instead of the client's default
+    pub async fn solve_within(
+        &self,
+        problem: &OptimizationProblem,
+        timeout: Duration,
+    ) -> Result<Solution, ClientError> {
+        self.solve_until(problem, Some(timeout)).await
+    }
+
+    /// Queue a solve on the server and return its result ID at once
+    ///
+    /// With `callback_url`, the server POSTs a JSON summary there when the solve
+    /// finishes; collect the result with [`LetsOptClient::result`].
+    pub async fn submit(
+        &self,
+        problem: &OptimizationProblem,
+        callback_url: Option<&str>,
+    ) -> Result<String, ClientError> {
+        let request = lp_solver::SubmitRequest {
+            problem: Some(mappers::domain_to_proto_problem(problem)),
+            callback_url: callback_url.unwrap_or_default().to_string(),
+        };
+        let submitted = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.submit_problem(request).await }
+            })
+            .await?;
+        Ok(submitted.result_id)
+    }
+
+    /// Solution of a submitted solve; fails with FAILED_PRECONDITION while it
+    /// is still running
+    pub async fn result(&self, result_id: &str) -> Result<Solution, ClientError> {
+        let query = lp_solver::ResultQuery {
+            result_id: result_id.to_string(),
+        };
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(query.clone(), remaining);
+                async move { client.get_result(request).await }
+            })
+            .await?;
+        Ok(mappers::proto_to_domain_solution(result)?)
+    }
+
+    /// Solve many problems with up to `concurrency` in flight at once
+    ///
+    /// Results come back in the order of `problems`; one failure doesn't stop
+    /// the others. Pair with [`LetsOptClient::connect_pooled`] when
+    /// `concurrency` runs into the hundreds.
+    pub async fn solve_batch(
+        &self,
+        problems: &[OptimizationProblem],
+        concurrency: usize,
+    ) -> Vec<Result<Solution, ClientError>> {
+        stream::iter(problems)
+            .map(|problem| self.solve(problem))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Have the server fetch the MPS, LP or JSON problem file at `url` and solve it
+    ///
+    /// Without `format` the server goes by the file extension. The server must
+    /// allow the URL's host.
+    pub async fn solve_url(
+        &self,
+        url: &str,
+        format: Option<ProblemFormat>,
+        config: &SolverConfig,
+    ) -> Result<Solution, ClientError> {
+        let request = mappers::domain_to_proto_url_request(url, format, config);
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.solve_from_url(request).await }
+            })
+            .await?;
+        Ok(mappers::proto_to_domain_solution(result)?)
+    }
+
+    /// Save `problem` on the server under `id`, replacing any problem saved under
+    /// it, so it can be solved by ID with [`solve_stored`]
+    ///
+    /// [`solve_stored`]: LetsOptClient::solve_stored
+    pub async fn save_problem(
+        &self,
+        id: &str,
+        problem: &OptimizationProblem,
+    ) -> Result<lp_solver::StoredProblem, ClientError> {
+        let request = lp_solver::SaveProblemRequest {
+            id: id.to_string(),
+            problem: Some(mappers::domain_to_proto_problem(problem)),
+        };
+        self.call(self.timeout, |mut client, remaining| {
+            let request = with_deadline(request.clone(), remaining);
+            async move { client.save_problem(request).await }
+        })
+        .await
+    }
+
+    /// The problem saved on the server under `id`
+    pub async fn get_problem(&self, id: &str) -> Result<OptimizationProblem, ClientError> {
+        let request = lp_solver::StoredProblemQuery { id: id.to_string() };
+        let problem = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.get_problem(request).await }
+            })
+            .await?;
+        Ok(mappers::proto_to_domain_problem(problem)?)
+    }
+
+    /// Summaries of the problems saved on the server with every one of `tags`, by
+    /// ID; no tags lists them all
+    pub async fn list_problems(
+        &self,
+        tags: &BTreeMap<String, String>,
+    ) -> Result<Vec<lp_solver::StoredProblem>, ClientError> {
+        let query = lp_solver::ProblemListQuery {
+            tags: tags.clone().into_iter().collect(),
+        };
+        let list = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(query.clone(), remaining);
+                async move { client.list_problems(request).await }
+            })
+            .await?;
+        Ok(list.problems)
+    }
+
+    /// Solve the problem saved under `id`
+    ///
+    /// `config` replaces the config saved with it, and `overrides` changes right-hand
+    /// sides, bounds and costs for this solve only.
+    pub async fn solve_stored(
+        &self,
+        id: &str,
+        config: Option<&SolverConfig>,
+        overrides: Option<&Scenario>,
+    ) -> Result<Solution, ClientError> {
+        let request = lp_solver::StoredSolveRequest {
+            id: id.to_string(),
+            config: config.map(mappers::domain_to_proto_solver_config),
+            overrides: overrides.map(mappers::domain_to_proto_scenario),
+        };
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.solve_stored_problem(request).await }
+            })
+            .await?;
+        Ok(mappers::proto_to_domain_solution(result)?)
+    }
+
+    /// Past solves of the problem saved under `id`, oldest first; `limit` keeps
+    /// only the most recent ones
+    pub async fn solve_history(
+        &self,
+        id: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<lp_solver::SolveRecord>, ClientError> {
+        let query = lp_solver::SolveHistoryQuery {
+            id: id.to_string(),
+            limit: limit.unwrap_or_default(),
+        };
+        let history = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(query.clone(), remaining);
+                async move { client.get_solve_history(request).await }
+            })
+            .await?;
+        Ok(history.solves)
+    }
+
+    /// Solution of the past solve `solve_id` of the problem saved under `id`
+    pub async fn stored_solution(&self, id: &str, solve_id: &str) -> Result<Solution, ClientError> {
+        let query = lp_solver::StoredSolutionQuery {
+            id: id.to_string(),
+            solve_id: solve_id.to_string(),
+        };
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(query.clone(), remaining);
+                async move { client.get_stored_solution(request).await }
+            })
+            .await?;
+        Ok(mappers::proto_to_domain_solution(result)?)
+    }
+
+    /// Solve `problem` under each scenario in one call
+    ///
+    /// Outcomes come back in the order of `scenarios`; a scenario the server
+    /// couldn't solve is [`ClientError::ScenarioFailed`] without failing the rest.
+    pub async fn solve_scenarios(
+        &self,
+        problem: &OptimizationProblem,
+        scenarios: &[Scenario],
+    ) -> Result<Vec<Result<Solution, ClientError>>, ClientError> {
+        let request = lp_solver::ScenarioRequest {
+            problem: Some(mappers::domain_to_proto_problem(problem)),
+            scenarios: scenarios
+                .iter()
+                .map(mappers::domain_to_proto_scenario)
+                .collect(),
+        };
+        let results = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.solve_scenarios(request).await }
+            })
+            .await?;
+
+        Ok(results
+            .results
+            .into_iter()
+            .map(|scenario| match scenario.result {
+                Some(result) => Ok(mappers::proto_to_domain_solution(result)?),
+                None => Err(ClientError::ScenarioFailed {
+                    name: scenario.name,
+                    error: scenario.error,
+                }),
+            })
+            .collect())
+    }
+
+    /// Solve `problem` at every point of `sweep`, returning each value with its
+    /// outcome
+    ///
+    /// Without `include_solutions` only the status and objective come back, which
+    /// is all a trade-off curve needs. Failed points are
+    /// [`ClientError::ScenarioFailed`].
+    pub async fn sweep(
+        &self,
+        problem: &OptimizationProblem,
+        sweep: &ParametricSweep,
+        include_solutions: bool,
+    ) -> Result<Vec<(f64, Result<Solution, ClientError>)>, ClientError> {
+        let request = mappers::domain_to_proto_sweep(problem, sweep, include_solutions);
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.sweep_parameter(request).await }
+            })
+            .await?;
+
+        result
+            .points
+            .into_iter()
+            .map(|point| {
+                let outcome = match point.result {
+                    Some(result) => Ok(mappers::proto_to_domain_solution(result)?),
+                    None if point.error.is_empty() => {
+                        let status = mappers::proto_to_domain_solution_status(point.status)?;
+                        let mut solution = Solution::new(status, String::new());
+                        solution.optimal_value = point.objective;
+                        Ok(solution)
+                    }
+                    None => Err(ClientError::ScenarioFailed {
+                        name: format!("{}={}", sweep.target.name(), point.value),
+                        error: point.error,
+                    }),
+                };
+                Ok((point.value, outcome))
+            })
+            .collect()
+    }
+
+    /// Pareto frontier of `problem`'s objective against `second`, trying `points`
+    /// levels of the second objective (0 for the server's default)
+    ///
+    /// Without `include_solutions` each point's solution holds only its status and
+    /// first objective.
+    pub async fn pareto_frontier(
+        &self,
+        problem: &OptimizationProblem,
+        second: &ObjectiveFunction,
+        points: usize,
+        include_solutions: bool,
+    ) -> Result<ParetoFrontier, ClientError> {
+        let request =
+            mappers::domain_to_proto_pareto_request(problem, second, points, include_solutions);
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.compute_pareto_frontier(request).await }
+            })
+            .await?;
+
+        Ok(mappers::proto_to_domain_pareto_frontier(result)?)
+    }
+
+    /// Compare two solutions on the server, naming the changed variables after
+    /// `problem`'s when given; a threshold of 0 takes the server's default
+    pub async fn diff_solutions(
+        &self,
+        problem: Option<&OptimizationProblem>,
+        before: &Solution,
+        after: &Solution,
+        threshold: f64,
+    ) -> Result<SolutionDiff, ClientError> {
+        let request = lp_solver::DiffRequest {
+            problem: problem.map(mappers::domain_to_proto_problem),
+            before: Some(mappers::domain_to_proto_solution(before.clone(), "")),
+            after: Some(mappers::domain_to_proto_solution(after.clone(), "")),
+            threshold,
+        };
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.diff_solutions(request).await }
+            })
+            .await?;
+
+        Ok(mappers::proto_to_domain_solution_diff(result))
+    }
+
+    /// `problem` as MPS with generic names and no settings, for sharing in a bug
+    /// report; see [`OptimizationProblem::anonymized`] for `perturbation` and `seed`
+    pub async fn export_anonymized(
+        &self,
+        problem: &OptimizationProblem,
+        perturbation: f64,
+        seed: u64,
+    ) -> Result<String, ClientError> {
+        let request = lp_solver::AnonymizeRequest {
+            problem: Some(mappers::domain_to_proto_problem(problem)),
+            perturbation,
+            seed,
+        };
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.export_anonymized(request).await }
+            })
+            .await?;
+
+        Ok(result.mps)
+    }
+
+    /// Build `problem` on the server and keep it for [`update_model`] and
+    /// [`resolve`], returning the session ID
+    ///
+    /// [`update_model`]: LetsOptClient::update_model
+    /// [`resolve`]: LetsOptClient::resolve
+    pub async fn create_session(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<String, ClientError> {
+        let request = mappers::domain_to_proto_problem(problem);
+        let info = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.create_model_session(request).await }
+            })
+            .await?;
+        Ok(info.session_id)
+    }
+
+    /// Apply `update` to a session's model
+    ///
+    /// Sent once: a retry after a lost response could add its constraints twice.
+    pub async fn update_model(
+        &self,
+        session_id: &str,
+        update: &ModelUpdate,
+    ) -> Result<(), ClientError> {
+        let request = mappers::domain_to_proto_model_update(session_id, update);
+        self.raw()
+            .update_model(with_deadline(request, self.timeout))
+            .await?;
+        Ok(())
+    }
+
+    /// Solve a session's model as it stands
+    pub async fn resolve(&self, session_id: &str) -> Result<Solution, ClientError> {
+        self.resolve_fixed(session_id, &BTreeMap::new()).await
+    }
+
+    /// Solve a session's model with the named variables held at the given values
+    ///
+    /// The fixing applies to this solve only; the session keeps its bounds.
+    pub async fn resolve_fixed(
+        &self,
+        session_id: &str,
+        fixed_values: &BTreeMap<String, f64>,
+    ) -> Result<Solution, ClientError> {
+        let request = lp_solver::SessionQuery {
+            session_id: session_id.to_string(),
+            fixed_values: fixed_values
+                .iter()
+                .map(|(name, &value)| (name.clone(), value))
+                .collect(),
+        };
+        let result = self
+            .call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.re_solve(request).await }
+            })
+            .await?;
+        Ok(mappers::proto_to_domain_solution(result)?)
+    }
+
+    /// Drop a session's model from the server
+    pub async fn close_session(&self, session_id: &str) -> Result<(), ClientError> {
+        let request = lp_solver::SessionQuery {
+            session_id: session_id.to_string(),
+            ..Default::default()
+        };
+        self.raw()
+            .close_model_session(with_deadline(request, self.timeout))
+            .await?;
+        Ok(())
+    }
+
+    /// Validate a problem on the server without solving it
+    ///
+    /// Returns the warnings for a solvable problem, or
+    /// [`ClientError::InvalidProblem`] with everything that is wrong.
+    pub async fn validate(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<Vec<String>, ClientError> {
+        let request = mappers::domain_to_proto_problem(problem);
+
+        let result = if request.encoded_len() > self.chunk_bytes {
+            let chunks = mappers::problem_to_chunks(problem, self.chunk_bytes);
+            self.call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(tokio_stream::iter(chunks.clone()), remaining);
+                async move { client.validate_problem_stream(request).await }
+            })
+            .await?
+        } else {
+            self.call(self.timeout, |mut client, remaining| {
+                let request = with_deadline(request.clone(), remaining);
+                async move { client.validate_problem(request).await }
+            })
+            .await?
+        };
+
+        if result.is_valid {
+            Ok(result.warnings)
+        } else {
+            Err(ClientError::InvalidProblem(result.errors))
+        }
+    }
+
+    async fn solve_until(
+        &self,
+        problem: &OptimizationProblem,
+        timeout: Option<Duration>,
+    ) -> Result<Solution, ClientError> {
+        let request = mappers::domain_to_proto_problem(problem);
+
+        let result = if request.encoded_len() > self.chunk_bytes {
+            let chunks = mappers::problem_to_chunks(problem, self.chunk_bytes);
+            self.call(timeout, |mut client, remaining| {
+                let request = with_deadline(tokio_stream::iter(chunks.clone()), remaining);
+                async move { client.solve_problem_stream(request).await }
+            })
+            .await?
+        } else {
+            let key = MetadataValue::try_from(uuid::Uuid::new_v4().to_string())
+                .expect("a UUID is valid metadata");
+            self.call(timeout, |mut client, remaining| {
+                let mut request = with_deadline(request.clone(), remaining);
+                request
+                    .metadata_mut()
+                    .insert(IDEMPOTENCY_KEY_HEADER, key.clone());
+                async move { client.solve_problem(request).await }
+            })
+            .await?
+        };
+
+        Ok(mappers::proto_to_domain_solution(result)?)
+    }
+
+    /// Make a call, retrying per the retry policy until `timeout` runs out
+    ///
+    /// Each attempt gets the generated client for the next pooled channel and the
+    /// time left before the deadline.
+    async fn call<T, F, Fut>(
+        &self,
+        timeout: Option<Duration>,
+        mut attempt: F,
+    ) -> Result<T, ClientError>
+    where
+        F: FnMut(LinearProgrammingSolverClient<Channel>, Option<Duration>) -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut retry = 0;
+        loop {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let status = match attempt(self.raw(), remaining).await {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(status) => status,
+            };
+
+            if retry >= self.retry.max_retries() || !self.retry.is_retryable(&status) {
+                return Err(status.into());
+            }
+            let pause = self.retry.backoff(retry);
+            // The retry couldn't finish in time, so report the real failure
+            if deadline.is_some_and(|deadline| Instant::now() + pause >= deadline) {
+                return Err(status.into());
+            }
+
+            debug!(code = ?status.code(), retry, ?pause, "Retrying call");
+            tokio::time::sleep(pause).await;
+            retry += 1;
+        }
+    }
+}
+
+/// Request carrying the caller's remaining time, if any, as its gRPC deadline
+fn with_deadline<T>(message: T, remaining: Option<Duration>) -> Request<T> {
+    let mut request = Request::new(message);
+    if let Some(remaining) = remaining {
+        request.set_timeout(remaining);
+    }
+    request
+}
@@ -0,0 +1,68 @@
+// Client pool: Several connections to one server, handed out round robin
+// A single HTTP/2 connection caps concurrent streams, so large batches spread out
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tonic::transport::{Channel, Endpoint};
+
+/// Fixed set of channels to the same server, used in turn
+///
+/// Cloning is cheap and clones share the channels and the rotation.
+#[derive(Debug, Clone)]
+pub struct ChannelPool {
+    channels: Arc<[Channel]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl ChannelPool {
+    /// Open `size` connections (at least one), failing if any can't be established
+    pub async fn connect(endpoint: Endpoint, size: usize) -> Result<Self, tonic::transport::Error> {
+        let mut channels = Vec::with_capacity(size.max(1));
+        for _ in 0..size.max(1) {
+            channels.push(endpoint.connect().await?);
+        }
+        Ok(Self::from_channels(channels))
+    }
+
+    /// Pool whose connections are opened on first use and reopened after a failure
+    pub fn lazy(endpoint: Endpoint, size: usize) -> Self {
+        Self::from_channels((0..size.max(1)).map(|_| endpoint.connect_lazy()).collect())
+    }
+
+    /// Pool over existing channels
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` is empty.
+    pub fn from_channels(channels: Vec<Channel>) -> Self {
+        assert!(
+            !channels.is_empty(),
+            "a channel pool needs at least one channel"
+        );
+        Self {
+            channels: channels.into(),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// The channel to use for the next call
+    pub fn channel(&self) -> Channel {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.channels.len();
+        self.channels[index].clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+impl From<Channel> for ChannelPool {
+    fn from(channel: Channel) -> Self {
+        Self::from_channels(vec![channel])
+    }
+}
@@ -0,0 +1,103 @@
+// Client retry: Which failures to retry and how long to back off between attempts
+// Spreads retries out so a fleet of batch clients doesn't hammer a recovering server
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use tonic::{Code, Status};
+
+/// Retry policy with exponential backoff
+///
+/// Retries calls that fail with UNAVAILABLE (the server is down, restarting or
+/// unreachable) or RESOURCE_EXHAUSTED (every solve slot is busy). The pause doubles
+/// after each attempt up to `max_backoff`, and half of it is randomized so clients
+/// that failed together don't retry together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Default policy: 3 retries, backing off from 100 ms up to 5 s
+    pub fn new() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self::new().with_max_retries(0)
+    }
+
+    /// Attempts after the first
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Pause before the first retry, and the cap on later pauses
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Growth of the pause from one retry to the next (at least 1)
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Use the exact backoff instead of randomizing half of it
+    pub fn without_jitter(mut self) -> Self {
+        self.jitter = false;
+        self
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Whether a failed call may succeed if tried again
+    pub fn is_retryable(&self, status: &Status) -> bool {
+        matches!(status.code(), Code::Unavailable | Code::ResourceExhausted)
+    }
+
+    /// Pause before retry number `retry` (0 for the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry).unwrap_or(i32::MAX);
+        let full = self
+            .initial_backoff
+            .mul_f64(self.multiplier.powi(exponent).min(f64::from(u32::MAX)))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let half = full / 2;
+            half + half.mul_f64(random_fraction())
+        } else {
+            full
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Uniform value in [0, 1) from the std hasher's per-instance random keys
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
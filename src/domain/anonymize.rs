@@ -0,0 +1,135 @@
+// Model anonymization: Problems stripped of what identifies them
+// Lets users share a model that reproduces a solver issue without revealing
+// names, descriptions or, when perturbed, exact costs and coefficients
+
+use std::collections::HashMap;
+
+use super::models::{OptimizationProblem, SolverConfig};
+
+/// Largest relative perturbation; beyond it a model drifts too far from the
+/// original to reproduce its behaviour
+pub const MAX_PERTURBATION: f64 = 0.1;
+
+impl OptimizationProblem {
+    /// Copy of the problem named `x0, x1, ...` and `c0, c1, ...`, without a name,
+    /// description, tags or solver settings
+    ///
+    /// With a `perturbation` above 0 (clamped to [`MAX_PERTURBATION`]), every
+    /// objective coefficient, constraint coefficient and right-hand side that
+    /// isn't a whole number is multiplied by a factor in `1 ± perturbation`, drawn
+    /// from a generator seeded with `seed`. Zeros, signs and whole numbers are
+    /// kept, so the sparsity pattern, the integer structure of MIP rows and the
+    /// scaling survive; variable bounds are never changed.
+    pub fn anonymized(&self, perturbation: f64, seed: u64) -> Self {
+        let perturbation = perturbation.clamp(0.0, MAX_PERTURBATION);
+        let mut noise = SplitMix64(seed);
+        let mut perturb = |value: &mut f64| {
+            if perturbation > 0.0 && value.fract() != 0.0 && value.is_finite() {
+                *value *= 1.0 + perturbation * (2.0 * noise.next_fraction() - 1.0);
+            }
+        };
+
+        // Names constraints use for their terms map through the variable they name
+        let renamed: HashMap<String, String> = (0..self.num_variables())
+            .map(|j| (self.variable_name(j), format!("x{}", j)))
+            .collect();
+
+        let mut problem = self.clone();
+        problem.name = String::new();
+        problem.description = String::new();
+        problem.tags.clear();
+        problem.solver_config = SolverConfig::default();
+        for (j, variable) in problem.variables.iter_mut().enumerate() {
+            variable.name = format!("x{}", j);
+        }
+        problem.objective.variable_names = (0..problem.num_variables())
+            .map(|j| format!("x{}", j))
+            .collect();
+        problem
+            .objective
+            .coefficients
+            .iter_mut()
+            .for_each(&mut perturb);
+        for (i, constraint) in problem.constraints.iter_mut().enumerate() {
+            constraint.name = format!("c{}", i);
+            for name in constraint.variable_names.iter_mut() {
+                *name = renamed.get(name.as_str()).cloned().unwrap_or_default();
+            }
+            constraint.coefficients.map_values(|_, mut a| {
+                perturb(&mut a);
+                a
+            });
+            perturb(&mut constraint.bound);
+        }
+        problem
+    }
+}
+
+/// Small seeded generator, so the same request always yields the same model
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in [0, 1)
+    fn next_fraction(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{Constraint, ObjectiveFunction, Variable};
+    use crate::domain::solver_service::validate_problem;
+    use crate::domain::value_objects::{ConstraintType, OptimizationType};
+
+    fn secret_problem() -> OptimizationProblem {
+        let names = vec!["secret_a".to_string(), "secret_b".to_string()];
+        OptimizationProblem::new(
+            ObjectiveFunction::new(OptimizationType::Maximize, vec![3.0, 2.5])
+                .with_names(names.clone()),
+        )
+        .with_name("secret_plan")
+        .with_description("secret description")
+        .with_tag("owner", "secret_team")
+        .with_variables(vec![
+            Variable::continuous("secret_a"),
+            Variable::integer("secret_b"),
+        ])
+        .add_constraint(
+            Constraint::new(ConstraintType::LessThanOrEqual, vec![1.0, 2.0], 10.0)
+                .with_name("secret_capacity")
+                .with_variable_names(names),
+        )
+        .add_constraint(
+            Constraint::new(ConstraintType::GreaterThanOrEqual, vec![0.0, 1.0], 1.0)
+                .with_variable_names(vec![String::new(), "secret_b".to_string()]),
+        )
+    }
+
+    #[test]
+    fn anonymized_problem_validates() {
+        let problem = secret_problem();
+        assert!(validate_problem(&problem).is_ok());
+
+        let anonymized = problem.anonymized(0.05, 7);
+        assert!(validate_problem(&anonymized).is_ok());
+        assert_eq!(anonymized.constraints[0].variable_names, ["x0", "x1"]);
+        assert_eq!(anonymized.constraints[1].variable_names, ["", "x1"]);
+    }
+
+    #[test]
+    fn anonymized_problem_keeps_no_original_names() {
+        let anonymized = secret_problem().anonymized(0.0, 0);
+
+        let serialized = serde_json::to_string(&anonymized).unwrap();
+        assert!(!serialized.contains("secret"), "{}", serialized);
+    }
+}
@@ -0,0 +1,184 @@
+// Bin packing: Assign items to as few equal bins as possible
+// Generates the textbook MIP, optionally with the lexicographic symmetry-breaking
+// rows that stop branch and bound from revisiting relabelings of the same packing
+
+use std::ops::Range;
+
+use super::modeling::{sum_over, LinearExpr, Model};
+use super::models::{Solution, Variable};
+use super::variable_grid::{VariableArray, VariableGrid};
+
+/// Items of given sizes and bins of one capacity
+#[derive(Debug, Clone)]
+pub struct BinPackingSpec {
+    sizes: Vec<f64>,
+    capacity: f64,
+    max_bins: Option<usize>,
+    symmetry_breaking: bool,
+}
+
+impl BinPackingSpec {
+    pub fn new(sizes: Vec<f64>, capacity: f64) -> Self {
+        Self {
+            sizes,
+            capacity,
+            max_bins: None,
+            symmetry_breaking: false,
+        }
+    }
+
+    /// Bins to create; by default as many as first-fit decreasing needs, which
+    /// is always enough
+    pub fn with_max_bins(mut self, bins: usize) -> Self {
+        self.max_bins = Some(bins);
+        self
+    }
+
+    /// Number bins by the first item each holds: bins are used in order, and
+    /// item `i` only goes in bin `b > 0` if an earlier item is in bin `b - 1`
+    pub fn with_symmetry_breaking(mut self, enabled: bool) -> Self {
+        self.symmetry_breaking = enabled;
+        self
+    }
+
+    pub fn num_items(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Bins the model gets
+    pub fn num_bins(&self) -> usize {
+        self.max_bins.unwrap_or_else(|| self.first_fit_decreasing())
+    }
+
+    /// Bins used by first-fit decreasing; oversized items get a bin each
+    fn first_fit_decreasing(&self) -> usize {
+        let mut sizes = self.sizes.clone();
+        sizes.sort_by(|a, b| b.total_cmp(a));
+        let mut loads: Vec<f64> = Vec::new();
+        for size in sizes {
+            match loads.iter_mut().find(|load| **load + size <= self.capacity) {
+                Some(load) => *load += size,
+                None => loads.push(size),
+            }
+        }
+        loads.len()
+    }
+}
+
+/// Variables and rows [`Model::add_bin_packing`] created
+#[derive(Debug, Clone)]
+pub struct BinPacking {
+    used: VariableArray,
+    assign: VariableGrid,
+    item_rows: Range<usize>,
+    capacity_rows: Range<usize>,
+}
+
+impl BinPacking {
+    /// Whether each bin is used
+    pub fn used(&self) -> &VariableArray {
+        &self.used
+    }
+
+    /// `assign[i][b]`: whether item `i` is in bin `b`
+    pub fn assign(&self) -> &VariableGrid {
+        &self.assign
+    }
+
+    /// Row putting each item in exactly one bin, in item order
+    pub fn item_rows(&self) -> Range<usize> {
+        self.item_rows.clone()
+    }
+
+    /// Capacity row of each bin, in bin order
+    pub fn capacity_rows(&self) -> Range<usize> {
+        self.capacity_rows.clone()
+    }
+
+    /// Number of bins used, to pass to [`Model::minimize`]
+    pub fn bins_used(&self) -> LinearExpr {
+        sum_over(self.used.iter())
+    }
+
+    /// Bin each item is in, None if the solution has no values for it
+    pub fn bin_of(&self, solution: &Solution) -> Vec<Option<usize>> {
+        self.assign
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .position(|var| var.value(solution).is_some_and(|v| v > 0.5))
+            })
+            .collect()
+    }
+}
+
+impl Model {
+    /// Add the bin-packing MIP: `name_used_b` per bin, `name_i_b` per item and bin
+    ///
+    /// Rows are `name_item_i` (each item in one bin), `name_capacity_b` (load within
+    /// capacity, and none unless the bin is used) and `name_link_i_b` (the same per
+    /// item, which tightens the relaxation). Symmetry breaking adds `name_order_b`
+    /// and `name_lex_i_b` and fixes item `i` out of bins after `i`. The objective is
+    /// left alone; minimize [`BinPacking::bins_used`].
+    pub fn add_bin_packing(&mut self, name: &str, spec: &BinPackingSpec) -> BinPacking {
+        let items = spec.num_items();
+        let bins = spec.num_bins();
+        let used = self.add_array(&format!("{}_used", name), bins, Variable::binary(""));
+        let assign = self.add_grid(name, (items, bins), Variable::binary(""));
+
+        let item_rows = self.add_all((0..items).map(|i| {
+            sum_over(assign.row(i))
+                .equals(1)
+                .with_name(format!("{}_item_{}", name, i))
+        }));
+        let capacity_rows = self.add_all((0..bins).map(|b| {
+            let load: LinearExpr = assign
+                .column(b)
+                .zip(&spec.sizes)
+                .map(|(var, &size)| size * var)
+                .sum();
+            load.leq(spec.capacity * used[b])
+                .with_name(format!("{}_capacity_{}", name, b))
+        }));
+        for i in 0..items {
+            for b in 0..bins {
+                self.add(
+                    assign[(i, b)]
+                        .leq(used[b])
+                        .with_name(format!("{}_link_{}_{}", name, i, b)),
+                );
+            }
+        }
+
+        if spec.symmetry_breaking {
+            for b in 1..bins {
+                self.add(
+                    used[b]
+                        .leq(used[b - 1])
+                        .with_name(format!("{}_order_{}", name, b)),
+                );
+            }
+            for i in 0..items {
+                for b in 1..bins {
+                    if b > i {
+                        self.set_bounds(assign[(i, b)], 0.0, Some(0.0));
+                        continue;
+                    }
+                    let earlier = (0..i).map(|j| assign[(j, b - 1)]);
+                    self.add(
+                        assign[(i, b)]
+                            .leq(sum_over(earlier))
+                            .with_name(format!("{}_lex_{}_{}", name, i, b)),
+                    );
+                }
+            }
+        }
+
+        BinPacking {
+            used,
+            assign,
+            item_rows,
+            capacity_rows,
+        }
+    }
+}
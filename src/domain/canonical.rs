@@ -0,0 +1,227 @@
+// Canonical form: One representation per model, whatever way it was written down
+// Lets callers cache, deduplicate and detect changes by comparing fingerprints
+
+use std::cmp::Ordering;
+
+use super::models::{Constraint, OptimizationProblem, Variable};
+use super::sparse::SparseVector;
+use super::value_objects::{ConstraintType, OptimizationType, VariableType};
+
+impl OptimizationProblem {
+    /// Equivalent problem in canonical form
+    ///
+    /// Variables are listed explicitly, binaries get exact 0-1 bounds, `>=` rows
+    /// are negated into `<=` rows, equality rows start with a positive coefficient,
+    /// rows are sorted, `-0.0` becomes `0.0` and names are trimmed. The feasible set
+    /// and optimum are unchanged, but constraint order and dual signs may differ.
+    pub fn canonicalize(&self) -> OptimizationProblem {
+        let num_vars = self.num_variables();
+
+        let variables = (0..num_vars)
+            .map(|j| {
+                let mut variable = self
+                    .variables
+                    .get(j)
+                    .cloned()
+                    .unwrap_or_else(|| Variable::continuous(String::new()));
+                variable.name = variable.name.trim().to_string();
+                variable.lower_bound = normalize(variable.lower_bound);
+                variable.upper_bound = variable
+                    .upper_bound
+                    .filter(|upper| *upper != f64::INFINITY)
+                    .map(normalize);
+                if variable.variable_type == VariableType::Binary {
+                    variable.lower_bound = variable.lower_bound.max(0.0);
+                    variable.upper_bound =
+                        Some(variable.upper_bound.map_or(1.0, |upper| upper.min(1.0)));
+                }
+                variable
+            })
+            .collect();
+
+        let mut objective = self.objective.clone();
+        objective.coefficients = objective
+            .coefficients
+            .iter()
+            .map(|&c| normalize(c))
+            .collect();
+        objective.variable_names = trimmed_names(&objective.variable_names);
+
+        let mut constraints: Vec<Constraint> = self
+            .constraints
+            .iter()
+            .map(|constraint| {
+                let mut constraint = constraint.clone();
+                let negate = match constraint.constraint_type {
+                    ConstraintType::GreaterThanOrEqual => true,
+                    ConstraintType::Equal => constraint
+                        .coefficients
+                        .values()
+                        .first()
+                        .is_some_and(|&c| c < 0.0),
+                    ConstraintType::LessThanOrEqual => false,
+                };
+                if negate {
+                    constraint.coefficients.map_values(|_, c| -c);
+                    constraint.bound = -constraint.bound;
+                    if constraint.constraint_type == ConstraintType::GreaterThanOrEqual {
+                        constraint.constraint_type = ConstraintType::LessThanOrEqual;
+                    }
+                }
+                // Zeros of either sign are never stored, so only the bound needs normalizing
+                constraint.bound = normalize(constraint.bound);
+                constraint.name = constraint.name.trim().to_string();
+                constraint.variable_names = trimmed_names(&constraint.variable_names);
+                constraint
+            })
+            .collect();
+        constraints.sort_by(compare_rows);
+
+        OptimizationProblem {
+            name: self.name.trim().to_string(),
+            description: self.description.trim().to_string(),
+            objective,
+            constraints,
+            variables,
+            solver_config: self.solver_config.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Stable 64-bit hash of the canonical model
+    ///
+    /// Covers the objective, constraints and variable types and bounds; names,
+    /// description and solver configuration are left out, so problems that differ
+    /// only in labels, row order or how rows are signed share a fingerprint. The
+    /// value does not depend on the platform or Rust version.
+    pub fn fingerprint(&self) -> u64 {
+        let canonical = self.canonicalize();
+        let mut hasher = Fnv1a::new();
+
+        hasher.write_u64(match canonical.objective.optimization_type {
+            OptimizationType::Minimize => 0,
+            OptimizationType::Maximize => 1,
+        });
+        hasher.write_floats(&canonical.objective.coefficients);
+
+        hasher.write_u64(canonical.variables.len() as u64);
+        for variable in &canonical.variables {
+            hasher.write_u64(match variable.variable_type {
+                VariableType::Continuous => 0,
+                VariableType::Integer => 1,
+                VariableType::Binary => 2,
+            });
+            hasher.write_u64(variable.lower_bound.to_bits());
+            match variable.upper_bound {
+                Some(upper) => hasher.write_u64(upper.to_bits()),
+                None => hasher.write_u64(f64::INFINITY.to_bits()),
+            }
+        }
+
+        hasher.write_u64(canonical.constraints.len() as u64);
+        for constraint in &canonical.constraints {
+            hasher.write_u64(relation_rank(constraint.constraint_type));
+            hasher.write_sparse(&constraint.coefficients);
+            hasher.write_u64(constraint.bound.to_bits());
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Rows order by relation, then coefficients, then bound, then name
+fn compare_rows(a: &Constraint, b: &Constraint) -> Ordering {
+    relation_rank(a.constraint_type)
+        .cmp(&relation_rank(b.constraint_type))
+        .then_with(|| compare_coefficients(&a.coefficients, &b.coefficients))
+        .then_with(|| a.bound.total_cmp(&b.bound))
+        .then_with(|| a.name.cmp(&b.name))
+}
+
+/// Rows compared as their dense coefficients would be, column by column, then by
+/// width, walking only the non-zeros
+fn compare_coefficients(a: &SparseVector, b: &SparseVector) -> Ordering {
+    let width = a.len().min(b.len());
+    let mut a_terms = a.iter().take_while(|&(j, _)| j < width).peekable();
+    let mut b_terms = b.iter().take_while(|&(j, _)| j < width).peekable();
+    loop {
+        let ordering = match (a_terms.peek().copied(), b_terms.peek().copied()) {
+            (None, None) => return a.len().cmp(&b.len()),
+            (Some((i, x)), Some((j, y))) if i == j => {
+                a_terms.next();
+                b_terms.next();
+                x.total_cmp(&y)
+            }
+            (Some((i, x)), Some((j, _))) if i < j => x.total_cmp(&0.0),
+            (Some((_, x)), None) => x.total_cmp(&0.0),
+            (_, Some((_, y))) => 0.0_f64.total_cmp(&y),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn relation_rank(relation: ConstraintType) -> u64 {
+    match relation {
+        ConstraintType::LessThanOrEqual => 0,
+        ConstraintType::Equal => 1,
+        ConstraintType::GreaterThanOrEqual => 2,
+    }
+}
+
+/// `-0.0` as `0.0`, so equal values hash equally
+fn normalize(value: f64) -> f64 {
+    if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Trimmed names, or none at all when every name is blank
+fn trimmed_names(names: &[String]) -> Vec<String> {
+    if names.iter().all(|name| name.trim().is_empty()) {
+        return Vec::new();
+    }
+    names.iter().map(|name| name.trim().to_string()).collect()
+}
+
+/// 64-bit FNV-1a, chosen over `DefaultHasher` because its output is specified
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_floats(&mut self, values: &[f64]) {
+        self.write_u64(values.len() as u64);
+        for value in values {
+            self.write_u64(value.to_bits());
+        }
+    }
+
+    fn write_sparse(&mut self, vector: &SparseVector) {
+        self.write_u64(vector.len() as u64);
+        self.write_u64(vector.num_nonzeros() as u64);
+        for (j, value) in vector.iter() {
+            self.write_u64(j as u64);
+            self.write_u64(value.to_bits());
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
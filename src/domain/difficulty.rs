@@ -0,0 +1,116 @@
+// Difficulty estimate: How hard a problem is likely to be, and why
+// A weighted blend of structural factors, reported individually so callers can see
+// what to change to make a model easier
+
+use serde::{Deserialize, Serialize};
+
+use super::model_statistics::ModelStatistics;
+use super::models::OptimizationProblem;
+
+/// Constraint count at which the size factor saturates
+const SATURATING_CONSTRAINTS: f64 = 1e6;
+
+/// Integer range beyond which bounds give branching little to work with
+pub const WIDE_INTEGER_RANGE: f64 = 100.0;
+
+const CONSTRAINTS_WEIGHT: f64 = 0.35;
+const DENSITY_WEIGHT: f64 = 0.15;
+const INTEGRALITY_WEIGHT: f64 = 0.3;
+const BOUNDS_WEIGHT: f64 = 0.2;
+
+/// One aspect of a problem and how much it adds to the difficulty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyFactor {
+    pub name: String,
+    /// How hard this aspect alone makes the problem, 0-1
+    pub score: f64,
+    pub weight: f64,
+    /// Human-readable figures behind the score
+    pub detail: String,
+}
+
+impl DifficultyFactor {
+    /// Share of the overall estimate, `score * weight`
+    pub fn contribution(&self) -> f64 {
+        self.score * self.weight
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DifficultyEstimate {
+    /// Sum of the factor contributions, 0-1
+    pub score: f64,
+    pub factors: Vec<DifficultyFactor>,
+}
+
+/// Estimate how hard `problem` is from constraint count, density, integrality and
+/// integer bound tightness
+pub fn estimate_difficulty(problem: &OptimizationProblem) -> DifficultyEstimate {
+    let stats = ModelStatistics::analyze(problem);
+    let num_integer = stats.num_integer + stats.num_binary;
+
+    let loose_integers = problem
+        .variables
+        .iter()
+        .filter(|v| v.is_integer())
+        .filter(|v| {
+            v.upper_bound
+                .is_none_or(|upper| upper - v.lower_bound > WIDE_INTEGER_RANGE)
+        })
+        .count();
+
+    let size =
+        ((1.0 + stats.num_constraints as f64).ln() / (1.0 + SATURATING_CONSTRAINTS).ln()).min(1.0);
+
+    let factors = vec![
+        DifficultyFactor {
+            name: "constraints".to_string(),
+            score: size,
+            weight: CONSTRAINTS_WEIGHT,
+            detail: format!("{} constraints", stats.num_constraints),
+        },
+        DifficultyFactor {
+            name: "density".to_string(),
+            // Small models are dense by nature, so density only counts in proportion
+            // to size; the square root lets moderately dense large models register
+            score: stats.density.sqrt() * size,
+            weight: DENSITY_WEIGHT,
+            detail: format!(
+                "{} non-zeros, {:.2}% of the matrix",
+                stats.num_nonzeros,
+                stats.density * 100.0
+            ),
+        },
+        DifficultyFactor {
+            name: "integrality".to_string(),
+            score: fraction(num_integer, stats.num_variables),
+            weight: INTEGRALITY_WEIGHT,
+            detail: format!(
+                "{} of {} variables are integer or binary",
+                num_integer, stats.num_variables
+            ),
+        },
+        DifficultyFactor {
+            name: "integer_bounds".to_string(),
+            score: fraction(loose_integers, num_integer),
+            weight: BOUNDS_WEIGHT,
+            detail: format!(
+                "{} of {} integer variables have no upper bound or a range over {}",
+                loose_integers, num_integer, WIDE_INTEGER_RANGE
+            ),
+        },
+    ];
+
+    DifficultyEstimate {
+        score: factors.iter().map(DifficultyFactor::contribution).sum(),
+        factors,
+    }
+}
+
+fn fraction(part: usize, whole: usize) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,106 @@
+// Solution explanation: Why the solution looks the way it does
+// Lists the constraints holding the solution in place and the variables driving
+// the objective, in terms a non-specialist can follow
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{OptimizationProblem, Solution};
+use super::value_objects::ConstraintType;
+
+/// Slack below which a constraint counts as binding
+pub const BINDING_TOLERANCE: f64 = 1e-6;
+
+/// Number of objective contributors reported
+pub const TOP_CONTRIBUTORS: usize = 10;
+
+/// A constraint that is tight at the solution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BindingConstraint {
+    pub index: usize,
+    pub name: String,
+    /// Left-hand side value at the solution
+    pub activity: f64,
+    pub bound: f64,
+    /// Distance to the bound (zero or within tolerance)
+    pub slack: f64,
+    /// Shadow price, when the solver reports duals
+    pub dual: Option<f64>,
+}
+
+/// A variable's share of the objective value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectiveContribution {
+    pub index: usize,
+    pub name: String,
+    pub value: f64,
+    pub coefficient: f64,
+    /// `coefficient * value`
+    pub contribution: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SolutionExplanation {
+    pub binding_constraints: Vec<BindingConstraint>,
+    /// Largest contributions by magnitude, biggest first
+    pub top_contributors: Vec<ObjectiveContribution>,
+}
+
+/// Explain a solution of `problem`
+///
+/// Returns None when the solution carries no variable values to explain.
+pub fn explain_solution(
+    problem: &OptimizationProblem,
+    solution: &Solution,
+) -> Option<SolutionExplanation> {
+    let values = &solution.variable_values;
+    if values.is_empty() {
+        return None;
+    }
+
+    let binding_constraints = problem
+        .constraints
+        .iter()
+        .enumerate()
+        .filter_map(|(index, constraint)| {
+            let activity = constraint.coefficients.dot(values);
+            let slack = match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => constraint.bound - activity,
+                ConstraintType::GreaterThanOrEqual => activity - constraint.bound,
+                ConstraintType::Equal => (constraint.bound - activity).abs(),
+            };
+            // Relative to the bound so large right-hand sides aren't judged too strictly
+            let tolerance = BINDING_TOLERANCE * constraint.bound.abs().max(1.0);
+            (slack.abs() <= tolerance).then(|| BindingConstraint {
+                index,
+                name: constraint.name.clone(),
+                activity,
+                bound: constraint.bound,
+                slack,
+                dual: solution.dual_values.get(index).copied(),
+            })
+        })
+        .collect();
+
+    let mut contributors: Vec<_> = problem
+        .objective
+        .coefficients
+        .iter()
+        .zip(values)
+        .enumerate()
+        .filter(|(_, (&coefficient, &value))| coefficient * value != 0.0)
+        .map(|(index, (&coefficient, &value))| ObjectiveContribution {
+            index,
+            name: problem.variable_name(index),
+            value,
+            coefficient,
+            contribution: coefficient * value,
+        })
+        .collect();
+    contributors.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+    contributors.truncate(TOP_CONTRIBUTORS);
+
+    Some(SolutionExplanation {
+        binding_constraints,
+        top_contributors: contributors,
+    })
+}
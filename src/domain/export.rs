@@ -0,0 +1,182 @@
+// Solution export: Values keyed by variable name, as CSV, JSON or a map
+// Lets results go straight into spreadsheets and ETL jobs without mapping
+// positions back to variables
+
+use std::collections::HashMap;
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+
+use super::models::{OptimizationProblem, Solution};
+use super::value_objects::{ConstraintType, ExportFormat};
+
+/// Render the values of a solution of `problem`, keyed by variable name
+///
+/// A name shared by several variables gets `#index` appended after its first use,
+/// so every key is unique. A solution without values exports just the header or
+/// an empty map.
+pub fn export_solution(
+    problem: &OptimizationProblem,
+    solution: &Solution,
+    format: ExportFormat,
+) -> String {
+    let names = unique_names(problem, solution.variable_values.len());
+    match format {
+        ExportFormat::Csv => {
+            let mut csv = String::from("variable,value\n");
+            for (name, value) in names.iter().zip(&solution.variable_values) {
+                csv.push_str(&csv_field(name));
+                csv.push(',');
+                csv.push_str(&value.to_string());
+                csv.push('\n');
+            }
+            csv
+        }
+        ExportFormat::Json => {
+            let export = JsonExport {
+                status: solution.status.to_string(),
+                objective: solution.optimal_value,
+                variables: Values {
+                    names: &names,
+                    values: &solution.variable_values,
+                },
+            };
+            serde_json::to_string(&export).expect("solution export is always valid JSON")
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    status: String,
+    objective: Option<f64>,
+    variables: Values<'a>,
+}
+
+/// Name-to-value map that keeps the problem's variable order
+struct Values<'a> {
+    names: &'a [String],
+    values: &'a [f64],
+}
+
+impl Serialize for Values<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len()))?;
+        for (name, value) in self.names.iter().zip(self.values) {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl Solution {
+    /// Values keyed by the names of `problem`'s variables
+    pub fn values_by_name(&self, problem: &OptimizationProblem) -> HashMap<String, f64> {
+        unique_names(problem, self.variable_values.len())
+            .into_iter()
+            .zip(self.variable_values.iter().copied())
+            .collect()
+    }
+
+    /// Duals keyed by the names of `problem`'s constraints; unnamed constraints and
+    /// names used by more than one are left out
+    pub fn duals_by_name(&self, problem: &OptimizationProblem) -> HashMap<String, f64> {
+        by_constraint_name(problem, self.dual_values.iter().copied())
+    }
+
+    /// Slack of each of `problem`'s constraints at the solution, keyed like
+    /// `duals_by_name`
+    ///
+    /// The distance from the left-hand side to the bound, positive when the
+    /// constraint holds with room to spare; for equalities, the absolute residual.
+    /// Empty when the solution has no values.
+    pub fn slacks_by_name(&self, problem: &OptimizationProblem) -> HashMap<String, f64> {
+        if self.variable_values.is_empty() {
+            return HashMap::new();
+        }
+        let slacks = problem.constraints.iter().map(|constraint| {
+            let activity = constraint.coefficients.dot(&self.variable_values);
+            match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => constraint.bound - activity,
+                ConstraintType::GreaterThanOrEqual => activity - constraint.bound,
+                ConstraintType::Equal => (constraint.bound - activity).abs(),
+            }
+        });
+        by_constraint_name(problem, slacks)
+    }
+
+    /// Reduced cost of each of `problem`'s variables, keyed like `values_by_name`
+    ///
+    /// The objective coefficient less what the duals price the variable's column
+    /// at, in the problem's own sense like the duals. Empty when the solver
+    /// reported no duals, as for MIPs.
+    pub fn reduced_costs_by_name(&self, problem: &OptimizationProblem) -> HashMap<String, f64> {
+        if self.dual_values.len() != problem.constraints.len() || self.dual_values.is_empty() {
+            return HashMap::new();
+        }
+        let mut priced = vec![0.0; problem.objective.coefficients.len()];
+        for (constraint, dual) in problem.constraints.iter().zip(&self.dual_values) {
+            for (j, a) in constraint.coefficients.iter() {
+                if let Some(price) = priced.get_mut(j) {
+                    *price += a * dual;
+                }
+            }
+        }
+        let costs = problem
+            .objective
+            .coefficients
+            .iter()
+            .zip(priced)
+            .map(|(&cost, priced)| cost - priced);
+        unique_names(problem, problem.objective.coefficients.len())
+            .into_iter()
+            .zip(costs)
+            .collect()
+    }
+}
+
+/// `values` keyed by the names of `problem`'s constraints, leaving out unnamed
+/// constraints and names used by more than one
+fn by_constraint_name(
+    problem: &OptimizationProblem,
+    values: impl IntoIterator<Item = f64>,
+) -> HashMap<String, f64> {
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for constraint in &problem.constraints {
+        *uses.entry(constraint.name.as_str()).or_insert(0) += 1;
+    }
+    problem
+        .constraints
+        .iter()
+        .zip(values)
+        .filter(|(constraint, _)| {
+            !constraint.name.is_empty() && uses[constraint.name.as_str()] == 1
+        })
+        .map(|(constraint, value)| (constraint.name.clone(), value))
+        .collect()
+}
+
+fn unique_names(problem: &OptimizationProblem, count: usize) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    (0..count)
+        .map(|j| {
+            let name = problem.variable_name(j);
+            let uses = seen.entry(name.clone()).or_insert(0);
+            *uses += 1;
+            if *uses == 1 {
+                name
+            } else {
+                format!("{}#{}", name, j)
+            }
+        })
+        .collect()
+}
+
+/// `field` quoted when it holds a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
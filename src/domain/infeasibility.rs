@@ -0,0 +1,153 @@
+// Infeasibility certificates: Which constraints contradict each other
+// Checks a Farkas certificate a backend reports against the model and names its
+// rows, so users can see why no solution exists rather than just that none does
+
+use serde::{Deserialize, Serialize};
+
+use super::models::OptimizationProblem;
+use super::value_objects::ConstraintType;
+use super::verification::DEFAULT_FEASIBILITY_TOLERANCE;
+
+/// Multipliers smaller than this, relative to the largest, are dropped as round-off
+pub const CERTIFICATE_TOLERANCE: f64 = 1e-9;
+
+/// Number of constraints named in a solution message
+const DESCRIBED_CONSTRAINTS: usize = 5;
+
+/// One constraint's weight in an infeasibility certificate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CertificateRow {
+    pub index: usize,
+    /// Empty for unnamed constraints
+    pub name: String,
+    pub multiplier: f64,
+}
+
+/// Farkas proof that a problem is infeasible
+///
+/// Adding up the constraints weighted by `multipliers` gives one row that no
+/// values within the variable bounds can satisfy: the values the combined row can
+/// reach and the values the weighted bounds require are `infeasibility` apart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InfeasibilityCertificate {
+    /// Multiplier per constraint, in model order, scaled so the largest is ±1
+    pub multipliers: Vec<f64>,
+    /// Constraints with a non-zero multiplier, largest first
+    pub constraints: Vec<CertificateRow>,
+    pub infeasibility: f64,
+}
+
+impl InfeasibilityCertificate {
+    /// Certificate for `problem` from row `multipliers`, or None when they don't
+    /// prove it infeasible
+    ///
+    /// The sign convention doesn't matter: negating the multipliers negates both
+    /// ranges, and they stay apart.
+    pub fn new(problem: &OptimizationProblem, multipliers: &[f64]) -> Option<Self> {
+        if multipliers.len() != problem.constraints.len() {
+            return None;
+        }
+        let scale = multipliers.iter().fold(0.0_f64, |max, y| max.max(y.abs()));
+        if scale == 0.0 || !scale.is_finite() {
+            return None;
+        }
+        let multipliers: Vec<f64> = multipliers
+            .iter()
+            .map(|y| y / scale)
+            .map(|y| {
+                if y.abs() < CERTIFICATE_TOLERANCE {
+                    0.0
+                } else {
+                    y
+                }
+            })
+            .collect();
+
+        // Values the weighted row bounds allow the combined row to take
+        let (mut required_low, mut required_high) = (0.0, 0.0);
+        let mut combined = vec![0.0; problem.num_variables()];
+        for (constraint, &y) in problem.constraints.iter().zip(&multipliers) {
+            if y == 0.0 {
+                continue;
+            }
+            for (j, a) in constraint.coefficients.iter() {
+                combined[j] += y * a;
+            }
+            let (low, high) = match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => (f64::NEG_INFINITY, constraint.bound),
+                ConstraintType::GreaterThanOrEqual => (constraint.bound, f64::INFINITY),
+                ConstraintType::Equal => (constraint.bound, constraint.bound),
+            };
+            let (low, high) = if y > 0.0 {
+                (y * low, y * high)
+            } else {
+                (y * high, y * low)
+            };
+            required_low += low;
+            required_high += high;
+        }
+
+        // Values the combined row can reach within the variable bounds
+        let (mut reachable_low, mut reachable_high) = (0.0, 0.0);
+        for (variable, &r) in problem.variables.iter().zip(&combined) {
+            if r.abs() < CERTIFICATE_TOLERANCE {
+                continue;
+            }
+            let (lower, upper) = (
+                variable.lower_bound,
+                variable.upper_bound.unwrap_or(f64::INFINITY),
+            );
+            let (low, high) = if r > 0.0 {
+                (r * lower, r * upper)
+            } else {
+                (r * upper, r * lower)
+            };
+            reachable_low += low;
+            reachable_high += high;
+        }
+
+        let infeasibility = (required_low - reachable_high).max(reachable_low - required_high);
+        let size = [required_low, required_high]
+            .into_iter()
+            .filter(|v| v.is_finite())
+            .fold(1.0_f64, |max, v| max.max(v.abs()));
+        if infeasibility.is_nan() || infeasibility <= DEFAULT_FEASIBILITY_TOLERANCE * size {
+            return None;
+        }
+
+        let mut constraints: Vec<CertificateRow> = multipliers
+            .iter()
+            .enumerate()
+            .filter(|(_, &y)| y != 0.0)
+            .map(|(index, &multiplier)| CertificateRow {
+                index,
+                name: problem.constraints[index].name.clone(),
+                multiplier,
+            })
+            .collect();
+        constraints.sort_by(|a, b| b.multiplier.abs().total_cmp(&a.multiplier.abs()));
+
+        Some(Self {
+            multipliers,
+            constraints,
+            infeasibility,
+        })
+    }
+
+    /// The heaviest constraints, as in "'supply', 'demand', constraint 4 (+2 more)"
+    pub fn describe(&self) -> String {
+        let described: Vec<String> = self
+            .constraints
+            .iter()
+            .take(DESCRIBED_CONSTRAINTS)
+            .map(|row| match row.name.as_str() {
+                "" => format!("constraint {}", row.index),
+                name => format!("'{}'", name),
+            })
+            .collect();
+        match self.constraints.len().checked_sub(DESCRIBED_CONSTRAINTS) {
+            Some(more) if more > 0 => format!("{} (+{} more)", described.join(", "), more),
+            _ => described.join(", "),
+        }
+    }
+}
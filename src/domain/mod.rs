@@ -1,9 +1,58 @@
 // Domain module: Business logic and models
 
+pub mod anonymize;
+pub mod bin_packing;
+pub mod canonical;
+pub mod difficulty;
+pub mod explanation;
+pub mod export;
+pub mod infeasibility;
+pub mod model_session;
+pub mod model_statistics;
+pub mod modeling;
 pub mod models;
+pub mod polish;
+pub mod presolve;
+pub mod problem_file;
+pub mod recording;
+pub mod robust;
+pub mod scaling;
+pub mod scenarios;
+pub mod set_cover;
+pub mod solution_diff;
 pub mod solver_service;
+pub mod sparse;
+pub mod standard_form;
+pub mod sweep;
+pub mod unbounded_ray;
 pub mod value_objects;
+pub mod variable_grid;
+pub mod verification;
 
+pub use anonymize::*;
+pub use bin_packing::*;
+pub use difficulty::*;
+pub use explanation::*;
+pub use export::*;
+pub use infeasibility::*;
+pub use model_session::*;
+pub use model_statistics::*;
+pub use modeling::*;
 pub use models::*;
+pub use polish::*;
+pub use presolve::*;
+pub use problem_file::*;
+pub use recording::*;
+pub use robust::*;
+pub use scaling::*;
+pub use scenarios::*;
+pub use set_cover::*;
+pub use solution_diff::*;
 pub use solver_service::*;
+pub use sparse::*;
+pub use standard_form::*;
+pub use sweep::*;
+pub use unbounded_ray::*;
 pub use value_objects::*;
+pub use variable_grid::*;
+pub use verification::*;
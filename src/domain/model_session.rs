@@ -0,0 +1,182 @@
+// Model sessions: A problem kept built between solves
+// Interactive tools change a few bounds or add a row, then solve again; a session
+// lets a backend apply those edits to the model it built instead of starting over
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{Constraint, OptimizationProblem, Solution};
+use super::scenarios::Scenario;
+use super::solver_service::{CancellationToken, Result, SolveObserver, SolverError, SolverService};
+
+/// Edits to a session's problem, applied in one step
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUpdate {
+    /// New right-hand sides, variable bounds and objective coefficients
+    pub changes: Scenario,
+    /// Rows to append, over the problem's variables
+    pub constraints: Vec<Constraint>,
+}
+
+impl ModelUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rhs(mut self, constraint: usize, bound: f64) -> Self {
+        self.changes = self.changes.with_rhs(constraint, bound);
+        self
+    }
+
+    pub fn with_bounds(mut self, variable: usize, lower: f64, upper: Option<f64>) -> Self {
+        self.changes = self.changes.with_bounds(variable, lower, upper);
+        self
+    }
+
+    pub fn with_objective_coefficient(mut self, variable: usize, coefficient: f64) -> Self {
+        self.changes = self
+            .changes
+            .with_objective_coefficient(variable, coefficient);
+        self
+    }
+
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.rhs.is_empty()
+            && self.changes.bounds.is_empty()
+            && self.changes.objective.is_empty()
+            && self.constraints.is_empty()
+    }
+
+    /// Make the edits to `problem`, leaving it untouched if any is invalid
+    ///
+    /// Changes to existing entries come first, so they can't refer to the new rows.
+    pub fn apply_to(&self, problem: &mut OptimizationProblem) -> Result<()> {
+        let columns = problem.num_variables();
+        let mut errors = self.changes.errors(problem);
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            let label = match constraint.name.as_str() {
+                "" => format!("New constraint {}", i),
+                name => format!("New constraint '{}'", name),
+            };
+            if constraint.num_variables() != columns {
+                errors.push(format!(
+                    "{} has {} coefficients but the problem has {} variables",
+                    label,
+                    constraint.num_variables(),
+                    columns
+                ));
+            } else if let Some((j, a)) =
+                constraint.coefficients.iter().find(|(_, a)| !a.is_finite())
+            {
+                errors.push(format!("{} coefficient {} is {}", label, j, a));
+            }
+            if constraint.bound.is_nan() {
+                errors.push(format!("{} bound is NaN", label));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(SolverError::InvalidProblem(format!(
+                "Model update is invalid: {}",
+                errors.join("; ")
+            )));
+        }
+
+        self.changes.apply_to(problem)?;
+        problem.constraints.extend(self.constraints.iter().cloned());
+        Ok(())
+    }
+}
+
+/// A problem held by a backend for repeated edits and solves
+pub trait ModelSession: Send {
+    /// The problem as it stands after every update so far
+    fn problem(&self) -> &OptimizationProblem;
+
+    /// Apply `update` to the problem and the backend's model
+    ///
+    /// An invalid update changes nothing.
+    fn update(&mut self, update: &ModelUpdate) -> Result<()>;
+
+    /// Solve the problem as it stands
+    fn solve(
+        &mut self,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution>;
+
+    /// Whether updates edit a built model, rather than each solve starting over
+    fn is_incremental(&self) -> bool;
+
+    /// Solve with the named variables held at the given values, then put their
+    /// bounds back
+    ///
+    /// The session is left as it was whether or not the solve succeeds.
+    fn solve_fixed(
+        &mut self,
+        values: &BTreeMap<String, f64>,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        if values.is_empty() {
+            return self.solve(observer, cancel);
+        }
+        let fixing = Scenario::fixing(self.problem(), values)?;
+        let mut restore = ModelUpdate::new();
+        for &(column, _, _) in &fixing.bounds {
+            restore = match self.problem().variables.get(column) {
+                Some(v) => restore.with_bounds(column, v.lower_bound, v.upper_bound),
+                None => restore.with_bounds(column, 0.0, None),
+            };
+        }
+
+        self.update(&ModelUpdate {
+            changes: fixing,
+            constraints: Vec::new(),
+        })?;
+        let solution = self.solve(observer, cancel);
+        self.update(&restore)?;
+        solution
+    }
+}
+
+/// Session for backends that can't edit a built model: every solve starts over
+pub struct RebuildingSession {
+    solver: Arc<dyn SolverService>,
+    problem: OptimizationProblem,
+}
+
+impl RebuildingSession {
+    pub fn new(solver: Arc<dyn SolverService>, problem: OptimizationProblem) -> Self {
+        Self { solver, problem }
+    }
+}
+
+impl ModelSession for RebuildingSession {
+    fn problem(&self) -> &OptimizationProblem {
+        &self.problem
+    }
+
+    fn update(&mut self, update: &ModelUpdate) -> Result<()> {
+        update.apply_to(&mut self.problem)
+    }
+
+    fn solve(
+        &mut self,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        self.solver
+            .solve_cancellable(&self.problem, observer, cancel)
+    }
+
+    fn is_incremental(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,201 @@
+// Model statistics: Describe a problem's size, numerics and structure
+// Computed without solving, for model-quality dashboards and formulation reviews
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{Constraint, OptimizationProblem};
+use super::value_objects::{ConstraintType, VariableType};
+
+/// Smallest and largest absolute value among non-zeros
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MagnitudeRange {
+    pub min_abs: f64,
+    pub max_abs: f64,
+}
+
+impl MagnitudeRange {
+    /// Range of the non-zero, finite values (all zero when there are none)
+    pub fn of(values: impl IntoIterator<Item = f64>) -> Self {
+        values
+            .into_iter()
+            .filter(|v| *v != 0.0 && v.is_finite())
+            .map(f64::abs)
+            .fold(None, |range: Option<Self>, v| {
+                Some(match range {
+                    Some(range) => Self {
+                        min_abs: range.min_abs.min(v),
+                        max_abs: range.max_abs.max(v),
+                    },
+                    None => Self {
+                        min_abs: v,
+                        max_abs: v,
+                    },
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Ratio of largest to smallest magnitude; large ratios hint at numerical trouble
+    pub fn ratio(&self) -> f64 {
+        if self.min_abs > 0.0 {
+            self.max_abs / self.min_abs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Structural class of a constraint row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RowKind {
+    /// No non-zero coefficients
+    Empty,
+    /// A single non-zero, i.e. a bound in disguise
+    Singleton,
+    /// Sum of binaries = 1
+    SetPartitioning,
+    /// Sum of binaries <= 1
+    SetPacking,
+    /// Sum of binaries >= 1
+    SetCovering,
+    /// Positive weights on integer variables <= positive capacity
+    Knapsack,
+    General,
+}
+
+/// Number of rows of each kind
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowStructure {
+    pub empty: usize,
+    pub singleton: usize,
+    pub set_partitioning: usize,
+    pub set_packing: usize,
+    pub set_covering: usize,
+    pub knapsack: usize,
+    pub general: usize,
+}
+
+impl RowStructure {
+    fn count(&mut self, kind: RowKind) {
+        let counter = match kind {
+            RowKind::Empty => &mut self.empty,
+            RowKind::Singleton => &mut self.singleton,
+            RowKind::SetPartitioning => &mut self.set_partitioning,
+            RowKind::SetPacking => &mut self.set_packing,
+            RowKind::SetCovering => &mut self.set_covering,
+            RowKind::Knapsack => &mut self.knapsack,
+            RowKind::General => &mut self.general,
+        };
+        *counter += 1;
+    }
+}
+
+/// Size, numerics and structure of a problem
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelStatistics {
+    pub num_variables: usize,
+    pub num_constraints: usize,
+    pub num_nonzeros: usize,
+    /// Non-zeros divided by rows times columns
+    pub density: f64,
+    pub num_continuous: usize,
+    pub num_integer: usize,
+    pub num_binary: usize,
+    pub matrix_range: MagnitudeRange,
+    pub objective_range: MagnitudeRange,
+    pub rhs_range: MagnitudeRange,
+    /// Finite variable bounds
+    pub bound_range: MagnitudeRange,
+    pub rows: RowStructure,
+}
+
+impl ModelStatistics {
+    pub fn analyze(problem: &OptimizationProblem) -> Self {
+        let num_variables = problem.num_variables();
+        let num_constraints = problem.constraints.len();
+        let num_nonzeros = problem.num_nonzeros();
+        let cells = num_variables * num_constraints;
+
+        let mut stats = Self {
+            num_variables,
+            num_constraints,
+            num_nonzeros,
+            density: if cells > 0 {
+                num_nonzeros as f64 / cells as f64
+            } else {
+                0.0
+            },
+            matrix_range: MagnitudeRange::of(
+                problem
+                    .constraints
+                    .iter()
+                    .flat_map(|c| c.coefficients.values().iter().copied()),
+            ),
+            objective_range: MagnitudeRange::of(problem.objective.coefficients.iter().copied()),
+            rhs_range: MagnitudeRange::of(problem.constraints.iter().map(|c| c.bound)),
+            bound_range: MagnitudeRange::of(
+                problem
+                    .variables
+                    .iter()
+                    .flat_map(|v| [Some(v.lower_bound), v.upper_bound])
+                    .flatten(),
+            ),
+            ..Self::default()
+        };
+
+        for index in 0..num_variables {
+            match variable_type(problem, index) {
+                VariableType::Continuous => stats.num_continuous += 1,
+                VariableType::Integer => stats.num_integer += 1,
+                VariableType::Binary => stats.num_binary += 1,
+            }
+        }
+        for constraint in &problem.constraints {
+            stats.rows.count(classify_row(problem, constraint));
+        }
+
+        stats
+    }
+}
+
+/// Classify a constraint by the structure it expresses
+pub fn classify_row(problem: &OptimizationProblem, constraint: &Constraint) -> RowKind {
+    let terms: Vec<(usize, f64)> = constraint.coefficients.iter().collect();
+
+    match terms.len() {
+        0 => return RowKind::Empty,
+        1 => return RowKind::Singleton,
+        _ => {}
+    }
+
+    let all_binary_ones = terms
+        .iter()
+        .all(|&(i, c)| c == 1.0 && variable_type(problem, i) == VariableType::Binary);
+    if all_binary_ones && constraint.bound == 1.0 {
+        return match constraint.constraint_type {
+            ConstraintType::Equal => RowKind::SetPartitioning,
+            ConstraintType::LessThanOrEqual => RowKind::SetPacking,
+            ConstraintType::GreaterThanOrEqual => RowKind::SetCovering,
+        };
+    }
+
+    let integer_positive = terms
+        .iter()
+        .all(|&(i, c)| c > 0.0 && variable_type(problem, i) != VariableType::Continuous);
+    if integer_positive
+        && constraint.constraint_type == ConstraintType::LessThanOrEqual
+        && constraint.bound > 0.0
+    {
+        return RowKind::Knapsack;
+    }
+
+    RowKind::General
+}
+
+/// Type of variable `index`; problems without a variable list are all continuous
+fn variable_type(problem: &OptimizationProblem, index: usize) -> VariableType {
+    problem
+        .variables
+        .get(index)
+        .map_or(VariableType::Continuous, |v| v.variable_type)
+}
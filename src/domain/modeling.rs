@@ -0,0 +1,595 @@
+// Modeling: Build problems from algebraic expressions instead of coefficient vectors
+// `model.add((2 * x + 3 * y).leq(100))` reads like the model on paper
+
+use std::collections::BTreeMap;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Range, Sub, SubAssign};
+
+use super::models::{
+    Constraint, ObjectiveFunction, OptimizationProblem, Solution, SolverConfig, Variable,
+};
+use super::value_objects::{ConstraintType, OptimizationType};
+
+/// Handle to a variable of a [`Model`]
+///
+/// Only meaningful for the model that created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VarRef(usize);
+
+impl VarRef {
+    /// Column of the variable in the built problem
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    /// Value of the variable in a solution, if it has one
+    pub fn value(self, solution: &Solution) -> Option<f64> {
+        solution.variable_values.get(self.0).copied()
+    }
+
+    pub fn leq(self, rhs: impl Into<LinearExpr>) -> LinearConstraint {
+        LinearExpr::from(self).leq(rhs)
+    }
+
+    pub fn geq(self, rhs: impl Into<LinearExpr>) -> LinearConstraint {
+        LinearExpr::from(self).geq(rhs)
+    }
+
+    pub fn equals(self, rhs: impl Into<LinearExpr>) -> LinearConstraint {
+        LinearExpr::from(self).equals(rhs)
+    }
+}
+
+/// Linear combination of variables plus a constant
+///
+/// Terms on the same variable are kept separate until the model is built, so
+/// adding to a large expression stays cheap.
+#[derive(Debug, Clone, Default)]
+pub struct LinearExpr {
+    terms: Vec<(usize, f64)>,
+    constant: f64,
+}
+
+impl LinearExpr {
+    /// The zero expression
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `coefficient * var` in place
+    pub fn add_term(&mut self, var: VarRef, coefficient: f64) {
+        self.terms.push((var.0, coefficient));
+    }
+
+    pub fn constant(&self) -> f64 {
+        self.constant
+    }
+
+    /// Non-zero coefficients by column, in increasing column order, with
+    /// repeated variables merged
+    pub fn coefficients(&self) -> Vec<(usize, f64)> {
+        let mut terms = self.terms.clone();
+        terms.sort_by_key(|&(j, _)| j);
+
+        let mut merged: Vec<(usize, f64)> = Vec::with_capacity(terms.len());
+        for (j, value) in terms {
+            match merged.last_mut() {
+                Some((last, sum)) if *last == j => *sum += value,
+                _ => merged.push((j, value)),
+            }
+        }
+        merged.retain(|&(_, value)| value != 0.0);
+        merged
+    }
+
+    /// Value of the expression at the given variable values
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        self.constant
+            + self
+                .terms
+                .iter()
+                .map(|&(j, a)| a * values.get(j).copied().unwrap_or(0.0))
+                .sum::<f64>()
+    }
+
+    /// `self <= rhs`
+    pub fn leq(self, rhs: impl Into<LinearExpr>) -> LinearConstraint {
+        LinearConstraint::new(self - rhs.into(), ConstraintType::LessThanOrEqual)
+    }
+
+    /// `self >= rhs`
+    pub fn geq(self, rhs: impl Into<LinearExpr>) -> LinearConstraint {
+        LinearConstraint::new(self - rhs.into(), ConstraintType::GreaterThanOrEqual)
+    }
+
+    /// `self == rhs`
+    pub fn equals(self, rhs: impl Into<LinearExpr>) -> LinearConstraint {
+        LinearConstraint::new(self - rhs.into(), ConstraintType::Equal)
+    }
+
+    fn scaled(mut self, factor: f64) -> Self {
+        for (_, value) in &mut self.terms {
+            *value *= factor;
+        }
+        self.constant *= factor;
+        self
+    }
+}
+
+impl From<VarRef> for LinearExpr {
+    fn from(var: VarRef) -> Self {
+        Self {
+            terms: vec![(var.0, 1.0)],
+            constant: 0.0,
+        }
+    }
+}
+
+impl From<&VarRef> for LinearExpr {
+    fn from(var: &VarRef) -> Self {
+        Self::from(*var)
+    }
+}
+
+impl From<f64> for LinearExpr {
+    fn from(constant: f64) -> Self {
+        Self {
+            terms: Vec::new(),
+            constant,
+        }
+    }
+}
+
+impl From<i32> for LinearExpr {
+    fn from(constant: i32) -> Self {
+        Self::from(f64::from(constant))
+    }
+}
+
+impl<T: Into<LinearExpr>> AddAssign<T> for LinearExpr {
+    fn add_assign(&mut self, rhs: T) {
+        let rhs = rhs.into();
+        self.terms.extend(rhs.terms);
+        self.constant += rhs.constant;
+    }
+}
+
+impl<T: Into<LinearExpr>> SubAssign<T> for LinearExpr {
+    fn sub_assign(&mut self, rhs: T) {
+        *self += rhs.into().scaled(-1.0);
+    }
+}
+
+impl<T: Into<LinearExpr>> Add<T> for LinearExpr {
+    type Output = LinearExpr;
+
+    fn add(mut self, rhs: T) -> LinearExpr {
+        self += rhs;
+        self
+    }
+}
+
+impl<T: Into<LinearExpr>> Sub<T> for LinearExpr {
+    type Output = LinearExpr;
+
+    fn sub(mut self, rhs: T) -> LinearExpr {
+        self -= rhs;
+        self
+    }
+}
+
+impl<T: Into<LinearExpr>> Add<T> for VarRef {
+    type Output = LinearExpr;
+
+    fn add(self, rhs: T) -> LinearExpr {
+        LinearExpr::from(self) + rhs
+    }
+}
+
+impl<T: Into<LinearExpr>> Sub<T> for VarRef {
+    type Output = LinearExpr;
+
+    fn sub(self, rhs: T) -> LinearExpr {
+        LinearExpr::from(self) - rhs
+    }
+}
+
+impl Neg for LinearExpr {
+    type Output = LinearExpr;
+
+    fn neg(self) -> LinearExpr {
+        self.scaled(-1.0)
+    }
+}
+
+impl Neg for VarRef {
+    type Output = LinearExpr;
+
+    fn neg(self) -> LinearExpr {
+        -LinearExpr::from(self)
+    }
+}
+
+impl<T: Into<LinearExpr>> Sum<T> for LinearExpr {
+    fn sum<I: Iterator<Item = T>>(iter: I) -> Self {
+        iter.fold(LinearExpr::new(), |total, item| total + item)
+    }
+}
+
+/// Sum of variables or expressions, e.g. `sum_over(x.row(i)).leq(capacity[i])`
+pub fn sum_over<T: Into<LinearExpr>>(items: impl IntoIterator<Item = T>) -> LinearExpr {
+    items.into_iter().sum()
+}
+
+/// Weighted sum `weights[0] * vars[0] + weights[1] * vars[1] + ...`
+///
+/// Stops at the shorter of the two.
+pub fn dot<'a>(
+    vars: impl IntoIterator<Item = &'a VarRef>,
+    weights: impl IntoIterator<Item = &'a f64>,
+) -> LinearExpr {
+    let mut expr = LinearExpr::new();
+    for (&var, &weight) in vars.into_iter().zip(weights) {
+        expr.add_term(var, weight);
+    }
+    expr
+}
+
+/// Scalar arithmetic in both operand orders, so `2 * x`, `x * 2.5` and `10 - x` work
+macro_rules! scalar_ops {
+    ($($scalar:ty),*) => {$(
+        impl Mul<$scalar> for LinearExpr {
+            type Output = LinearExpr;
+
+            fn mul(self, factor: $scalar) -> LinearExpr {
+                self.scaled(f64::from(factor))
+            }
+        }
+
+        impl Mul<$scalar> for VarRef {
+            type Output = LinearExpr;
+
+            fn mul(self, factor: $scalar) -> LinearExpr {
+                LinearExpr::from(self) * factor
+            }
+        }
+
+        impl Mul<LinearExpr> for $scalar {
+            type Output = LinearExpr;
+
+            fn mul(self, expr: LinearExpr) -> LinearExpr {
+                expr * self
+            }
+        }
+
+        impl Mul<VarRef> for $scalar {
+            type Output = LinearExpr;
+
+            fn mul(self, var: VarRef) -> LinearExpr {
+                var * self
+            }
+        }
+
+        impl Add<LinearExpr> for $scalar {
+            type Output = LinearExpr;
+
+            fn add(self, expr: LinearExpr) -> LinearExpr {
+                expr + self
+            }
+        }
+
+        impl Add<VarRef> for $scalar {
+            type Output = LinearExpr;
+
+            fn add(self, var: VarRef) -> LinearExpr {
+                var + self
+            }
+        }
+
+        impl Sub<LinearExpr> for $scalar {
+            type Output = LinearExpr;
+
+            fn sub(self, expr: LinearExpr) -> LinearExpr {
+                -expr + self
+            }
+        }
+
+        impl Sub<VarRef> for $scalar {
+            type Output = LinearExpr;
+
+            fn sub(self, var: VarRef) -> LinearExpr {
+                -var + self
+            }
+        }
+    )*};
+}
+
+scalar_ops!(f64, i32);
+
+/// Linear constraint built from expressions, kept as `expr (<=|>=|==) 0`
+#[derive(Debug, Clone)]
+pub struct LinearConstraint {
+    expr: LinearExpr,
+    constraint_type: ConstraintType,
+    name: String,
+}
+
+impl LinearConstraint {
+    fn new(expr: LinearExpr, constraint_type: ConstraintType) -> Self {
+        Self {
+            expr,
+            constraint_type,
+            name: String::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn constraint_type(&self) -> ConstraintType {
+        self.constraint_type
+    }
+
+    /// Non-zero coefficients by column, with all variables moved to the left side
+    pub fn coefficients(&self) -> Vec<(usize, f64)> {
+        self.expr.coefficients()
+    }
+
+    /// Right-hand side, with all constants moved to the right side
+    pub fn bound(&self) -> f64 {
+        // Subtracting keeps a zero constant from turning into -0
+        0.0 - self.expr.constant
+    }
+
+    /// Dense domain constraint over `num_variables` columns
+    fn to_constraint(&self, num_variables: usize) -> Constraint {
+        let mut coefficients = vec![0.0; num_variables];
+        for (j, value) in self.coefficients() {
+            coefficients[j] = value;
+        }
+        Constraint::new(self.constraint_type, coefficients, self.bound()).with_name(&self.name)
+    }
+}
+
+/// Problem under construction: variables first, then constraints and objective
+/// written in terms of them
+///
+/// ```
+/// use letsopt::Model;
+///
+/// let mut model = Model::new("Factory");
+/// let chairs = model.continuous("chairs");
+/// let tables = model.continuous("tables");
+/// model.add((2 * chairs + 3 * tables).leq(100).with_name("labor"));
+/// model.add((chairs + tables).leq(40).with_name("storage"));
+/// model.maximize(30 * chairs + 50 * tables);
+/// let problem = model.build();
+/// assert_eq!(problem.constraints.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Model {
+    name: String,
+    variables: Vec<Variable>,
+    constraints: Vec<LinearConstraint>,
+    objective: LinearExpr,
+    optimization_type: OptimizationType,
+    solver_config: SolverConfig,
+}
+
+impl Model {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            variables: Vec::new(),
+            constraints: Vec::new(),
+            objective: LinearExpr::new(),
+            optimization_type: OptimizationType::Minimize,
+            solver_config: SolverConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.solver_config = config;
+        self
+    }
+
+    /// Add a variable, unnamed ones are called `x{index}`
+    pub fn add_var(&mut self, mut variable: Variable) -> VarRef {
+        let var = VarRef(self.variables.len());
+        if variable.name.is_empty() {
+            variable.name = format!("x{}", var.0);
+        }
+        self.variables.push(variable);
+        var
+    }
+
+    /// Add a continuous variable with bounds [0, inf)
+    pub fn continuous(&mut self, name: impl Into<String>) -> VarRef {
+        self.add_var(Variable::continuous(name))
+    }
+
+    /// Add an integer variable with bounds [0, inf)
+    pub fn integer(&mut self, name: impl Into<String>) -> VarRef {
+        self.add_var(Variable::integer(name))
+    }
+
+    /// Add a binary variable
+    pub fn binary(&mut self, name: impl Into<String>) -> VarRef {
+        self.add_var(Variable::binary(name))
+    }
+
+    /// Add a constraint, returning its row index
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constraint uses a variable from another model.
+    pub fn add(&mut self, constraint: LinearConstraint) -> usize {
+        self.check_vars(&constraint.expr);
+        self.constraints.push(constraint);
+        self.constraints.len() - 1
+    }
+
+    /// Add a family of constraints, returning their row indices
+    pub fn add_all(
+        &mut self,
+        constraints: impl IntoIterator<Item = LinearConstraint>,
+    ) -> Range<usize> {
+        let start = self.constraints.len();
+        for constraint in constraints {
+            self.add(constraint);
+        }
+        start..self.constraints.len()
+    }
+
+    /// Minimize `expr`; a constant term only shifts the objective value and is dropped
+    pub fn minimize(&mut self, expr: impl Into<LinearExpr>) {
+        self.set_objective(OptimizationType::Minimize, expr.into());
+    }
+
+    /// Maximize `expr`; a constant term only shifts the objective value and is dropped
+    pub fn maximize(&mut self, expr: impl Into<LinearExpr>) {
+        self.set_objective(OptimizationType::Maximize, expr.into());
+    }
+
+    pub fn variable(&self, var: VarRef) -> &Variable {
+        &self.variables[var.0]
+    }
+
+    pub fn num_variables(&self) -> usize {
+        self.variables.len()
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// The problem as the solvers see it, one column per variable in creation order
+    pub fn build(&self) -> OptimizationProblem {
+        let n = self.variables.len();
+        let mut objective = vec![0.0; n];
+        for (j, value) in self.objective.coefficients() {
+            objective[j] = value;
+        }
+        let names = self.variables.iter().map(|v| v.name.clone()).collect();
+
+        OptimizationProblem {
+            name: self.name.clone(),
+            description: String::new(),
+            objective: ObjectiveFunction::new(self.optimization_type, objective).with_names(names),
+            constraints: self
+                .constraints
+                .iter()
+                .map(|constraint| constraint.to_constraint(n))
+                .collect(),
+            variables: self.variables.clone(),
+            solver_config: self.solver_config.clone(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Change a variable's bounds after it was added
+    pub(super) fn set_bounds(&mut self, var: VarRef, lower: f64, upper: Option<f64>) {
+        let variable = &mut self.variables[var.0];
+        variable.lower_bound = lower;
+        variable.upper_bound = upper;
+    }
+
+    fn set_objective(&mut self, optimization_type: OptimizationType, expr: LinearExpr) {
+        self.check_vars(&expr);
+        self.optimization_type = optimization_type;
+        self.objective = expr;
+    }
+
+    fn check_vars(&self, expr: &LinearExpr) {
+        assert!(
+            expr.terms.iter().all(|&(j, _)| j < self.variables.len()),
+            "expression uses a variable that does not belong to model '{}'",
+            self.name
+        );
+    }
+}
+
+/// Declare a whole problem in one block and get an [`OptimizationProblem`] back
+///
+/// Statements end in `;`. An optional `name: "...";` comes first, then in any
+/// order: `var` declarations (`continuous`, `integer` or `binary`, with optional
+/// `[lower, upper]` bounds), one `minimize` or `maximize`, and constraints, each
+/// optionally prefixed with `name:`. Constraints compare with `<=`, `>=` or `==`.
+///
+/// ```
+/// use letsopt::model;
+///
+/// let problem = model! {
+///     name: "Factory";
+///     var chairs, tables: continuous;
+///     var trucks: integer [0, 5];
+///     maximize 30 * chairs + 50 * tables - 20 * trucks;
+///     labor: 2 * chairs + 3 * tables <= 100;
+///     chairs + tables <= 40 + 10 * trucks;
+/// };
+/// assert_eq!(problem.name, "Factory");
+/// assert_eq!(problem.variables.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! model {
+    (@stmt $m:ident;) => {};
+    (@stmt $m:ident; var $($var:ident),+ : $kind:ident [$lower:expr, $upper:expr]; $($rest:tt)*) => {
+        $(
+            let $var = $m.add_var(
+                $crate::domain::models::Variable::$kind(stringify!($var))
+                    .with_bounds(f64::from($lower), Some(f64::from($upper))),
+            );
+        )+
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@stmt $m:ident; var $($var:ident),+ : $kind:ident; $($rest:tt)*) => {
+        $(
+            let $var = $m.add_var($crate::domain::models::Variable::$kind(stringify!($var)));
+        )+
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@stmt $m:ident; minimize $objective:expr; $($rest:tt)*) => {
+        $m.minimize($objective);
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@stmt $m:ident; maximize $objective:expr; $($rest:tt)*) => {
+        $m.maximize($objective);
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@stmt $m:ident; $name:ident : $($rest:tt)*) => {
+        $crate::model!(@constraint $m [stringify!($name)] [] $($rest)*);
+    };
+    (@stmt $m:ident; $($rest:tt)*) => {
+        $crate::model!(@constraint $m [""] [] $($rest)*);
+    };
+
+    // Collect the left-hand side one token at a time until the comparison
+    (@constraint $m:ident [$name:expr] [$($lhs:tt)+] <= $rhs:expr; $($rest:tt)*) => {
+        $m.add(($($lhs)+).leq($rhs).with_name($name));
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@constraint $m:ident [$name:expr] [$($lhs:tt)+] >= $rhs:expr; $($rest:tt)*) => {
+        $m.add(($($lhs)+).geq($rhs).with_name($name));
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@constraint $m:ident [$name:expr] [$($lhs:tt)+] == $rhs:expr; $($rest:tt)*) => {
+        $m.add(($($lhs)+).equals($rhs).with_name($name));
+        $crate::model!(@stmt $m; $($rest)*);
+    };
+    (@constraint $m:ident [$name:expr] [$($lhs:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::model!(@constraint $m [$name] [$($lhs)* $next] $($rest)*);
+    };
+
+    (name: $name:expr; $($body:tt)*) => {{
+        let mut model = $crate::domain::modeling::Model::new($name);
+        $crate::model!(@stmt model; $($body)*);
+        model.build()
+    }};
+    ($($body:tt)*) => {{
+        let mut model = $crate::domain::modeling::Model::new("");
+        $crate::model!(@stmt model; $($body)*);
+        model.build()
+    }};
+}
@@ -1,9 +1,22 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use super::infeasibility::InfeasibilityCertificate;
+use super::model_statistics::MagnitudeRange;
+use super::presolve::PresolveReport;
+use super::scenarios::Scenario;
+use super::solver_service::Result;
+use super::sparse::SparseVector;
+use super::unbounded_ray::UnboundedRay;
 use super::value_objects::{
-    ConstraintType, OptimizationType, SolutionStatus, SolverBackend, VariableType,
+    ConstraintType, ExportFormat, IntegerRounding, LpAlgorithm, OptimizationType, SolutionStatus,
+    SolvePriority, SolverBackend, VariableType,
 };
 
 /// Decision variable in an optimization problem
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Variable {
     pub variable_type: VariableType,
     pub lower_bound: f64,
@@ -54,7 +67,7 @@ impl Variable {
 }
 
 /// Objective function to minimize or maximize
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectiveFunction {
     pub optimization_type: OptimizationType,
     pub coefficients: Vec<f64>,
@@ -83,21 +96,30 @@ impl ObjectiveFunction {
 }
 
 /// Linear constraint on variables
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Constraint {
     pub constraint_type: ConstraintType,
-    pub coefficients: Vec<f64>,
+    /// One coefficient per variable, of which only the non-zeros are stored
+    pub coefficients: SparseVector,
     pub bound: f64,
     pub name: String,
+    /// Optional name of the variable each coefficient applies to
+    pub variable_names: Vec<String>,
 }
 
 impl Constraint {
-    pub fn new(constraint_type: ConstraintType, coefficients: Vec<f64>, bound: f64) -> Self {
+    /// Constraint with `coefficients` given densely, as a `Vec<f64>`, or sparsely
+    pub fn new(
+        constraint_type: ConstraintType,
+        coefficients: impl Into<SparseVector>,
+        bound: f64,
+    ) -> Self {
         Self {
             constraint_type,
-            coefficients,
+            coefficients: coefficients.into(),
             bound,
             name: String::new(),
+            variable_names: Vec::new(),
         }
     }
 
@@ -106,18 +128,86 @@ impl Constraint {
         self
     }
 
+    pub fn with_variable_names(mut self, names: Vec<String>) -> Self {
+        self.variable_names = names;
+        self
+    }
+
     pub fn num_variables(&self) -> usize {
         self.coefficients.len()
     }
+
+    /// Number of non-zero coefficients
+    pub fn num_nonzeros(&self) -> usize {
+        self.coefficients.num_nonzeros()
+    }
 }
 
 /// Configuration for the solver
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolverConfig {
     pub backend: SolverBackend,
     pub time_limit: Option<f64>,
     pub gap_tolerance: Option<f64>,
+    /// Stop a MIP once bound and incumbent are within this many objective units
+    #[serde(default)]
+    pub absolute_gap_tolerance: Option<f64>,
+    /// Simplex iteration limit; the solve stops with `IterationLimit` on it
+    #[serde(default)]
+    pub max_iterations: Option<u64>,
+    /// Branch-and-bound node limit for MIPs; the solve stops with `NodeLimit` on it
+    #[serde(default)]
+    pub max_nodes: Option<u64>,
     pub verbose: bool,
+    /// Memory limit for the solve in megabytes
+    pub memory_limit_mb: Option<u64>,
+    /// Registered solver to use by key; takes precedence over `backend`
+    pub solver_name: Option<String>,
+    /// Solve the LP relaxation instead of the problem as stated
+    pub relax_integrality: bool,
+    /// Explain the solution alongside the result
+    pub explain: bool,
+    /// Render the solution keyed by variable name alongside the result
+    pub export: Option<ExportFormat>,
+    /// Key values by variable name and duals by constraint name in the result too
+    pub values_by_name: bool,
+    /// Scale rows and columns before solving and unscale the solution afterwards
+    pub auto_scale: bool,
+    /// Run the domain presolve before handing the problem to the backend
+    pub presolve: bool,
+    /// Variables held at these values for this solve only, by name
+    #[serde(default)]
+    pub fixed_values: BTreeMap<String, f64>,
+    /// Starting values for a MIP, by name; may leave out any variable, and the
+    /// backend completes the rest
+    #[serde(default)]
+    pub mip_start: BTreeMap<String, f64>,
+    /// Raw backend options, keyed `highs.<option>` or `cbc.<parameter>`; they
+    /// override the presets a backend picks for the model
+    #[serde(default)]
+    pub backend_options: BTreeMap<String, String>,
+    /// Place in the queue for a solve slot
+    #[serde(default)]
+    pub priority: SolvePriority,
+    /// Cleanup of integer values the solver left within its tolerance of an integer
+    #[serde(default)]
+    pub integer_rounding: IntegerRounding,
+    /// Values smaller in magnitude than this come back as exactly 0
+    #[serde(default)]
+    pub zero_threshold: Option<f64>,
+    /// Algorithm for LP solves, including a MIP's node LPs where the backend allows
+    #[serde(default)]
+    pub lp_algorithm: LpAlgorithm,
+    /// With `LpAlgorithm::InteriorPoint`, return the interior solution without
+    /// crossing over to a basis
+    #[serde(default)]
+    pub skip_crossover: bool,
+    /// End a MIP solve at its first feasible solution and return it as `Feasible`
+    #[serde(default)]
+    pub stop_at_first_feasible: bool,
+    /// Time the caller needs an answer by
+    #[serde(default)]
+    pub deadline: Option<SystemTime>,
 }
 
 impl Default for SolverConfig {
@@ -126,13 +216,61 @@ impl Default for SolverConfig {
             backend: SolverBackend::Auto,
             time_limit: None,
             gap_tolerance: None,
+            absolute_gap_tolerance: None,
+            max_iterations: None,
+            max_nodes: None,
             verbose: false,
+            memory_limit_mb: None,
+            solver_name: None,
+            relax_integrality: false,
+            explain: false,
+            export: None,
+            values_by_name: false,
+            auto_scale: false,
+            presolve: false,
+            fixed_values: BTreeMap::new(),
+            mip_start: BTreeMap::new(),
+            backend_options: BTreeMap::new(),
+            priority: SolvePriority::Normal,
+            integer_rounding: IntegerRounding::Snap,
+            zero_threshold: None,
+            lp_algorithm: LpAlgorithm::Auto,
+            skip_crossover: false,
+            stop_at_first_feasible: false,
+            deadline: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Cap the time limit at what is left until the deadline, keeping back
+    /// `margin` to return the result in; false when the deadline has passed
+    pub fn limit_to_deadline(&mut self, margin: Duration) -> bool {
+        let Some(deadline) = self.deadline else {
+            return true;
+        };
+        let left = deadline
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+            .saturating_sub(margin);
+        if left.is_zero() {
+            return false;
         }
+        let left = left.as_secs_f64();
+        self.time_limit = Some(self.time_limit.map_or(left, |limit| limit.min(left)));
+        true
     }
 }
 
+/// Whether `tags` holds every key of `filter` with the same value
+pub fn tags_match(tags: &BTreeMap<String, String>, filter: &BTreeMap<String, String>) -> bool {
+    filter
+        .iter()
+        .all(|(key, value)| tags.get(key) == Some(value))
+}
+
 /// Complete optimization problem
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationProblem {
     pub name: String,
     pub description: String,
@@ -140,6 +278,10 @@ pub struct OptimizationProblem {
     pub constraints: Vec<Constraint>,
     pub variables: Vec<Variable>,
     pub solver_config: SolverConfig,
+    /// Labels such as tenant, run ID or cost center; copied to the solution and
+    /// used to filter job and saved problem listings
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
 }
 
 impl OptimizationProblem {
@@ -151,6 +293,7 @@ impl OptimizationProblem {
             constraints: Vec::new(),
             variables: Vec::new(),
             solver_config: SolverConfig::default(),
+            tags: BTreeMap::new(),
         }
     }
 
@@ -164,6 +307,11 @@ impl OptimizationProblem {
         self
     }
 
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
     pub fn add_constraint(mut self, constraint: Constraint) -> Self {
         self.constraints.push(constraint);
         self
@@ -190,10 +338,93 @@ impl OptimizationProblem {
     pub fn is_mixed_integer(&self) -> bool {
         self.num_integer_variables() > 0
     }
+
+    /// Whether the config asks for an interior-point LP solve that stops before
+    /// crossover, which only applies to continuous problems
+    pub fn skips_crossover(&self) -> bool {
+        self.solver_config.skip_crossover
+            && self.solver_config.lp_algorithm == LpAlgorithm::InteriorPoint
+            && !self.is_mixed_integer()
+    }
+
+    /// Whether every variable only takes the values 0 and 1
+    pub fn is_pure_binary(&self) -> bool {
+        !self.variables.is_empty()
+            && self.variables.iter().all(|v| match v.variable_type {
+                VariableType::Binary => true,
+                VariableType::Integer => {
+                    v.lower_bound >= 0.0 && v.upper_bound.is_some_and(|u| u <= 1.0)
+                }
+                VariableType::Continuous => false,
+            })
+    }
+
+    /// Number of non-zero coefficients in the constraint matrix
+    pub fn num_nonzeros(&self) -> usize {
+        self.constraints.iter().map(Constraint::num_nonzeros).sum()
+    }
+
+    /// Display name of variable `index`: its own name, else the objective's, else `x{index}`
+    pub fn variable_name(&self, index: usize) -> String {
+        self.variables
+            .get(index)
+            .map(|variable| variable.name.as_str())
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.objective.variable_names.get(index).map(String::as_str))
+            .map_or_else(|| format!("x{}", index), str::to_string)
+    }
+
+    /// Column of each variable name; the first wins when names repeat
+    pub fn columns_by_name(&self) -> HashMap<String, usize> {
+        let mut columns = HashMap::new();
+        for j in (0..self.num_variables()).rev() {
+            columns.insert(self.variable_name(j), j);
+        }
+        columns
+    }
+
+    /// `(column, value)` pairs of the MIP start, by column, leaving out names
+    /// that aren't variables
+    pub fn mip_start(&self) -> Vec<(usize, f64)> {
+        if self.solver_config.mip_start.is_empty() {
+            return Vec::new();
+        }
+        let columns = self.columns_by_name();
+        let mut start: Vec<(usize, f64)> = self
+            .solver_config
+            .mip_start
+            .iter()
+            .filter_map(|(name, &value)| Some((*columns.get(name)?, value)))
+            .collect();
+        start.sort_by_key(|&(column, _)| column);
+        start
+    }
+
+    /// Drop integrality, turning the problem into its LP relaxation
+    ///
+    /// Binary variables keep their 0-1 range as continuous bounds.
+    pub fn relax_integrality(&mut self) {
+        for variable in &mut self.variables {
+            if variable.variable_type == VariableType::Binary {
+                variable.lower_bound = variable.lower_bound.max(0.0);
+                variable.upper_bound = Some(variable.upper_bound.map_or(1.0, |ub| ub.min(1.0)));
+            }
+            variable.variable_type = VariableType::Continuous;
+        }
+    }
+
+    /// Tighten the bounds of the variables in `solver_config.fixed_values` to
+    /// their values
+    pub fn fix_variables(&mut self) -> Result<()> {
+        if self.solver_config.fixed_values.is_empty() {
+            return Ok(());
+        }
+        Scenario::fixing(self, &self.solver_config.fixed_values)?.apply_to(self)
+    }
 }
 
 /// Statistics about the solve process
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SolverStatistics {
     pub simplex_iterations: u64,
     pub nodes_explored: u64,
@@ -202,28 +433,93 @@ pub struct SolverStatistics {
     pub num_constraints: u32,
     pub num_integer_vars: u32,
     pub num_binary_vars: u32,
+    /// Solver that produced the solution, when the one asked for delegated
+    /// (e.g. the winner of a race)
+    pub solved_by: Option<String>,
+    /// What presolve removed before the backend ran, when it was enabled
+    pub presolve: Option<PresolveReport>,
+    /// Time and memory the solve took, when measured
+    pub resources: Option<ResourceUsage>,
+    /// Numerics of the final LP basis, when the backend exposes it
+    #[serde(default)]
+    pub conditioning: Option<BasisConditioning>,
+}
+
+/// Resources one solve used, measured around the backend call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub wall_time_ms: f64,
+    /// User plus system time of the solving thread, or of the whole worker
+    /// process for isolated solves
+    pub cpu_time_ms: f64,
+    /// Peak resident memory of the solving process, in bytes (0 = unknown)
+    pub peak_rss_bytes: u64,
+}
+
+/// How numerically fragile the final LP basis is
+///
+/// An optimal basis with a condition estimate beyond
+/// [`ILL_CONDITIONED_BASIS`](super::solver_service::ILL_CONDITIONED_BASIS) can lose
+/// enough digits that the values are off by more than the solver's tolerances.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BasisConditioning {
+    /// Estimate of the basis matrix's 1-norm condition number (a lower bound,
+    /// usually within a small factor)
+    pub condition_estimate: f64,
+    /// Magnitudes of the basis matrix's non-zeros, slack columns included
+    pub coefficient_range: MagnitudeRange,
 }
 
 /// Quality metrics for the solution
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SolutionQuality {
     pub max_constraint_violation: f64,
+    /// Largest violation relative to the size of the bound it breaks
+    #[serde(default)]
+    pub max_relative_violation: f64,
     pub max_integrality_violation: f64,
+    /// How far the solution can be trusted, from 0 to 1; see
+    /// [`assess_quality`](super::verification::assess_quality)
     pub reliability: f64,
+    /// Integer variables snapped to an exact integer after the solve
+    #[serde(default)]
+    pub snapped_integers: usize,
+    /// Largest constraint or bound violation the snapping caused and could not
+    /// repair (0 = none)
+    #[serde(default)]
+    pub rounding_violation: f64,
+    /// Values below `SolverConfig::zero_threshold` set to 0 after the solve
+    #[serde(default)]
+    pub zeroed_values: usize,
 }
 
 /// Solution to an optimization problem
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Solution {
     pub status: SolutionStatus,
     pub optimal_value: Option<f64>,
     pub best_bound: Option<f64>,
     pub gap: Option<f64>,
     pub variable_values: Vec<f64>,
+    /// Shadow price of each constraint: the change in the optimal objective per
+    /// unit increase of its bound, in the problem's own sense, so a binding `<=`
+    /// row of a maximization has a non-negative dual. Empty when the solver
+    /// reports none, as for MIPs
     pub dual_values: Vec<f64>,
     pub message: String,
     pub statistics: SolverStatistics,
     pub quality: SolutionQuality,
+    /// Direction the objective improves along without limit, for unbounded
+    /// problems whose backend certifies one
+    #[serde(default)]
+    pub unbounded_ray: Option<UnboundedRay>,
+    /// Constraints proven to contradict each other, for infeasible problems whose
+    /// backend certifies it
+    #[serde(default)]
+    pub infeasibility_certificate: Option<InfeasibilityCertificate>,
+    /// Tags of the problem it solves
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
 }
 
 impl Solution {
@@ -238,6 +534,9 @@ impl Solution {
             message: message.into(),
             statistics: SolverStatistics::default(),
             quality: SolutionQuality::default(),
+            unbounded_ray: None,
+            infeasibility_certificate: None,
+            tags: BTreeMap::new(),
         }
     }
 
@@ -252,9 +551,52 @@ impl Solution {
             message: "Optimal solution found".to_string(),
             statistics: SolverStatistics::default(),
             quality: SolutionQuality::default(),
+            unbounded_ray: None,
+            infeasibility_certificate: None,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Solution of a solve stopped short of proving optimality, e.g. on a limit
+    ///
+    /// Carries the best feasible point found as `incumbent` (objective, values),
+    /// when there is one. `reason` reads like "Time limit reached".
+    pub fn stopped(
+        status: SolutionStatus,
+        reason: &str,
+        problem_name: &str,
+        incumbent: Option<(f64, Vec<f64>)>,
+    ) -> Self {
+        match incumbent {
+            Some((objective, values)) => {
+                let mut solution = Self::new(
+                    status,
+                    format!(
+                        "{}; returning the best solution found for '{}'",
+                        reason, problem_name
+                    ),
+                );
+                solution.optimal_value = Some(objective);
+                solution.variable_values = values;
+                solution
+            }
+            None => Self::new(
+                status,
+                format!("{} before a feasible solution was found", reason),
+            ),
         }
     }
 
+    /// Record the solver's proven `bound` on the objective, and with an incumbent
+    /// the relative gap to it
+    pub fn with_bound(mut self, bound: f64) -> Self {
+        self.best_bound = Some(bound);
+        self.gap = self
+            .optimal_value
+            .map(|objective| (objective - bound).abs() / objective.abs().max(1.0));
+        self
+    }
+
     pub fn with_statistics(mut self, statistics: SolverStatistics) -> Self {
         self.statistics = statistics;
         self
@@ -265,6 +607,15 @@ impl Solution {
         self
     }
 
+    /// Set the row duals a backend reported, flipping them when it minimized the
+    /// negated objective of a maximization
+    pub fn with_duals(mut self, duals: &[f64], negated_objective: bool) -> Self {
+        let sign = if negated_objective { -1.0 } else { 1.0 };
+        // Adding zero turns the -0 of rows with no dual into 0
+        self.dual_values = duals.iter().map(|&dual| sign * dual + 0.0).collect();
+        self
+    }
+
     pub fn is_optimal(&self) -> bool {
         self.status == SolutionStatus::Optimal
     }
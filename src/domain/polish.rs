@@ -0,0 +1,96 @@
+// Solution polishing: Clean solver output into the exact values downstream systems expect
+// Solvers accept integer values within their integrality tolerance, e.g. 0.9999999 for
+// a binary, and leave round-off like 1e-9 in continuous ones; cleaning either up can
+// nudge a tight constraint out of tolerance
+
+use super::models::{OptimizationProblem, Solution};
+use super::verification::{verify_solution, DEFAULT_FEASIBILITY_TOLERANCE};
+
+/// Distance from an integer within which an integer variable's value is taken to be
+/// that integer; covers the integrality tolerances of the backends
+pub const INTEGRALITY_SNAP_TOLERANCE: f64 = 1e-5;
+
+/// Snap the integer variables of `solution` that lie within
+/// [`INTEGRALITY_SNAP_TOLERANCE`] of an integer to it, and update the objective
+///
+/// Returns how many values changed. Values further off are left for the
+/// verification to report.
+pub fn snap_integers(problem: &OptimizationProblem, solution: &mut Solution) -> usize {
+    let mut snapped = 0;
+    for (index, value) in solution.variable_values.iter_mut().enumerate() {
+        let integer = problem.variables.get(index).is_some_and(|v| v.is_integer());
+        let rounded = value.round();
+        if integer && *value != rounded && (*value - rounded).abs() <= INTEGRALITY_SNAP_TOLERANCE {
+            *value = rounded;
+            snapped += 1;
+        }
+    }
+    if snapped > 0 {
+        refresh_objective(problem, solution);
+    }
+    snapped
+}
+
+/// Set the values of `solution` smaller in magnitude than `threshold` to exactly 0,
+/// where the variable's bounds allow it, and update the objective
+///
+/// Returns how many values changed.
+pub fn zero_tiny_values(
+    problem: &OptimizationProblem,
+    solution: &mut Solution,
+    threshold: f64,
+) -> usize {
+    let mut zeroed = 0;
+    for (index, value) in solution.variable_values.iter_mut().enumerate() {
+        let allows_zero = problem
+            .variables
+            .get(index)
+            .is_none_or(|v| v.lower_bound <= 0.0 && v.upper_bound.is_none_or(|upper| upper >= 0.0));
+        if allows_zero && *value != 0.0 && value.abs() < threshold {
+            *value = 0.0;
+            zeroed += 1;
+        }
+    }
+    if zeroed > 0 {
+        refresh_objective(problem, solution);
+    }
+    zeroed
+}
+
+/// Largest constraint or bound violation of `values` beyond the feasibility
+/// tolerance (0 = feasible up to integrality)
+pub fn excess_violation(problem: &OptimizationProblem, values: &[f64]) -> f64 {
+    verify_solution(problem, values, DEFAULT_FEASIBILITY_TOLERANCE).map_or(0.0, |check| {
+        check
+            .max_constraint_violation
+            .max(check.max_bound_violation)
+    })
+}
+
+/// Recompute the objective of `solution` at its values, if it has one
+fn refresh_objective(problem: &OptimizationProblem, solution: &mut Solution) {
+    if solution.optimal_value.is_some() {
+        solution.optimal_value = Some(
+            problem
+                .objective
+                .coefficients
+                .iter()
+                .zip(&solution.variable_values)
+                .map(|(c, x)| c * x)
+                .sum(),
+        );
+    }
+}
+
+/// `problem` with every integer variable held at its value in `values`, so a
+/// re-solve only moves the continuous variables
+pub fn fix_integers(problem: &OptimizationProblem, values: &[f64]) -> OptimizationProblem {
+    let mut fixed = problem.clone();
+    for (variable, &value) in fixed.variables.iter_mut().zip(values) {
+        if variable.is_integer() {
+            variable.lower_bound = value;
+            variable.upper_bound = Some(value);
+        }
+    }
+    fixed
+}
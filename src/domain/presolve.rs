@@ -0,0 +1,528 @@
+// Presolve: Cheap reductions applied before a backend sees the problem
+// Removes fixed variables and rows that can't matter, tightens bounds from singleton
+// rows and catches infeasibility that needs no solver to spot
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{Constraint, OptimizationProblem, Solution, Variable};
+use super::value_objects::ConstraintType;
+
+/// Absolute slack allowed when comparing activities and bounds, relative to max(|rhs|, 1)
+pub const PRESOLVE_TOLERANCE: f64 = 1e-9;
+
+/// Reductions feed each other; stop after this many rounds even if more are possible
+const MAX_PRESOLVE_ROUNDS: usize = 8;
+
+/// Distance from a bound, relative to max(|bound|, 1), at which a solution value
+/// still counts as sitting on it
+const ACTIVE_BOUND_TOLERANCE: f64 = 1e-7;
+
+/// What presolve eliminated
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresolveReport {
+    /// Variables fixed by their bounds and substituted out
+    pub removed_variables: usize,
+    /// Empty, singleton and redundant rows
+    pub removed_constraints: usize,
+    /// Variable bounds tightened from singleton rows
+    pub tightened_bounds: usize,
+}
+
+/// Result of presolving a problem
+#[derive(Debug, Clone)]
+pub enum PresolveOutcome {
+    Reduced(Box<Presolved>),
+    /// The problem can't have a feasible solution; the reason names what conflicts
+    Infeasible(String),
+}
+
+/// A reduced problem and what's needed to map its solutions back
+#[derive(Debug, Clone)]
+pub struct Presolved {
+    pub problem: OptimizationProblem,
+    pub report: PresolveReport,
+    /// Objective contribution of the removed variables
+    pub objective_offset: f64,
+    /// Original index of each remaining variable
+    pub kept_variables: Vec<usize>,
+    /// Original index of each remaining constraint
+    pub kept_constraints: Vec<usize>,
+    /// Value of every variable presolve fixed, by original index
+    fixed_values: Vec<Option<f64>>,
+    /// Singleton rows whose bound on their variable was kept, in the order found
+    bound_rows: Vec<BoundRow>,
+    num_constraints: usize,
+}
+
+/// A removed singleton row that set one of its variable's final bounds
+#[derive(Debug, Clone)]
+struct BoundRow {
+    row: usize,
+    variable: usize,
+    /// The variable's coefficient in the row
+    coefficient: f64,
+    /// Bound the row put on the variable
+    bound: f64,
+    /// The variable's objective coefficient
+    cost: f64,
+    /// The variable's coefficients in the other rows, by original row index
+    column: Vec<(usize, f64)>,
+}
+
+impl Presolved {
+    /// Map a solution of the reduced problem back to the original problem
+    ///
+    /// Removed rows get a dual of zero, except singleton rows whose bound on their
+    /// variable is active: their dual is the variable's reduced cost over the
+    /// row's coefficient, the price the bound carried in the reduced problem.
+    pub fn postsolve(&self, mut solution: Solution) -> Solution {
+        let values = self.original_values(&solution.variable_values);
+        // An LP presolve removed every row from has no duals to report, but its
+        // singleton rows may still price their bounds
+        let rowless_lp = self.kept_constraints.is_empty()
+            && !self.problem.is_mixed_integer()
+            && values.is_some();
+        if !solution.dual_values.is_empty() || rowless_lp {
+            let mut duals = vec![0.0; self.num_constraints];
+            for (&i, &dual) in self.kept_constraints.iter().zip(&solution.dual_values) {
+                duals[i] = dual;
+            }
+            if let Some(values) = &values {
+                self.recover_bound_duals(values, &mut duals);
+            }
+            solution.dual_values = duals;
+        }
+        if let Some(values) = values {
+            solution.variable_values = values;
+        }
+        // Removed rows get no weight; the certificate may lean on bounds presolve
+        // tightened from them, so callers re-check it against the original problem
+        if let Some(certificate) = &mut solution.infeasibility_certificate {
+            let mut multipliers = vec![0.0; self.num_constraints];
+            for (&i, &y) in self.kept_constraints.iter().zip(&certificate.multipliers) {
+                multipliers[i] = y;
+            }
+            certificate.multipliers = multipliers;
+            for row in &mut certificate.constraints {
+                row.index = self
+                    .kept_constraints
+                    .get(row.index)
+                    .copied()
+                    .unwrap_or(row.index);
+            }
+        }
+        // Removed variables are fixed, so they stay put along a ray
+        solution.unbounded_ray = solution.unbounded_ray.take().and_then(|mut ray| {
+            let mut direction = vec![0.0; self.fixed_values.len()];
+            for (&j, &step) in self.kept_variables.iter().zip(&ray.direction) {
+                direction[j] = step;
+            }
+            for component in &mut ray.components {
+                component.index = *self.kept_variables.get(component.index)?;
+            }
+            ray.direction = direction;
+            Some(ray)
+        });
+
+        solution.optimal_value = solution.optimal_value.map(|v| v + self.objective_offset);
+        solution.best_bound = solution.best_bound.map(|v| v + self.objective_offset);
+        solution.statistics.presolve = Some(self.report.clone());
+        solution
+    }
+
+    /// Give the singleton rows whose bound is active at `values` their duals
+    ///
+    /// Later rows go first, since an earlier row may contain a variable that was
+    /// fixed when the later one was found.
+    fn recover_bound_duals(&self, values: &[f64], duals: &mut [f64]) {
+        for bound_row in self.bound_rows.iter().rev() {
+            let value = values[bound_row.variable];
+            let tolerance = ACTIVE_BOUND_TOLERANCE * bound_row.bound.abs().max(1.0);
+            if (value - bound_row.bound).abs() > tolerance {
+                continue;
+            }
+            let priced: f64 = bound_row.column.iter().map(|&(k, a)| a * duals[k]).sum();
+            duals[bound_row.row] = (bound_row.cost - priced) / bound_row.coefficient + 0.0;
+        }
+    }
+
+    /// Values of every original variable, given values of the remaining ones
+    ///
+    /// None when `values` doesn't cover exactly the remaining variables.
+    pub fn original_values(&self, values: &[f64]) -> Option<Vec<f64>> {
+        if values.len() != self.kept_variables.len() {
+            return None;
+        }
+        let mut original: Vec<f64> = self.fixed_values.iter().map(|v| v.unwrap_or(0.0)).collect();
+        for (&j, &value) in self.kept_variables.iter().zip(values) {
+            original[j] = value;
+        }
+        Some(original)
+    }
+}
+
+/// Presolve `problem`
+pub fn presolve(problem: &OptimizationProblem) -> PresolveOutcome {
+    let num_vars = problem.num_variables();
+    let variable_at = |j: usize| {
+        problem
+            .variables
+            .get(j)
+            .cloned()
+            .unwrap_or_else(|| Variable::continuous(String::new()))
+    };
+    let integer: Vec<bool> = (0..num_vars).map(|j| variable_at(j).is_integer()).collect();
+    let mut lower: Vec<f64> = (0..num_vars).map(|j| variable_at(j).lower_bound).collect();
+    let mut upper: Vec<f64> = (0..num_vars)
+        .map(|j| variable_at(j).upper_bound.unwrap_or(f64::INFINITY))
+        .collect();
+    for j in (0..num_vars).filter(|&j| integer[j]) {
+        lower[j] = lower[j].ceil();
+        upper[j] = upper[j].floor();
+    }
+
+    // Singleton row that set each variable's current lower and upper bound
+    let mut lower_row: Vec<Option<usize>> = vec![None; num_vars];
+    let mut upper_row: Vec<Option<usize>> = vec![None; num_vars];
+    // When each row was found to be a singleton, for unwinding them in reverse
+    let mut found_at: Vec<usize> = vec![usize::MAX; problem.constraints.len()];
+    let mut singletons = 0;
+    let mut fixed: Vec<Option<f64>> = vec![None; num_vars];
+    let mut row_alive = vec![true; problem.constraints.len()];
+    let mut report = PresolveReport::default();
+
+    for _ in 0..MAX_PRESOLVE_ROUNDS {
+        let mut changed = false;
+
+        for j in 0..num_vars {
+            if lower[j] > upper[j] + PRESOLVE_TOLERANCE {
+                return PresolveOutcome::Infeasible(format!(
+                    "Variable '{}' has lower bound {} above upper bound {}",
+                    problem.variable_name(j),
+                    lower[j],
+                    upper[j]
+                ));
+            }
+            if fixed[j].is_none() && upper[j] - lower[j] <= PRESOLVE_TOLERANCE {
+                fixed[j] = Some(lower[j]);
+                report.removed_variables += 1;
+                changed = true;
+            }
+        }
+
+        for (i, constraint) in problem.constraints.iter().enumerate() {
+            if !row_alive[i] {
+                continue;
+            }
+            let (terms, rhs) = free_terms(constraint, &fixed);
+            let tolerance = PRESOLVE_TOLERANCE * rhs.abs().max(1.0);
+            let (min_activity, max_activity) =
+                terms.iter().fold((0.0, 0.0), |(min, max), &(j, a)| {
+                    if a > 0.0 {
+                        (min + a * lower[j], max + a * upper[j])
+                    } else {
+                        (min + a * upper[j], max + a * lower[j])
+                    }
+                });
+
+            let (can_hold, always_holds) = match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => (
+                    min_activity <= rhs + tolerance,
+                    max_activity <= rhs + tolerance,
+                ),
+                ConstraintType::GreaterThanOrEqual => (
+                    max_activity >= rhs - tolerance,
+                    min_activity >= rhs - tolerance,
+                ),
+                ConstraintType::Equal => (
+                    min_activity <= rhs + tolerance && max_activity >= rhs - tolerance,
+                    (min_activity - rhs).abs() <= tolerance
+                        && (max_activity - rhs).abs() <= tolerance,
+                ),
+            };
+            if !can_hold {
+                return PresolveOutcome::Infeasible(format!(
+                    "{} can never hold within the variable bounds",
+                    constraint_label(constraint, i)
+                ));
+            }
+
+            if always_holds {
+                row_alive[i] = false;
+            } else if let [(j, a)] = terms[..] {
+                // A single variable: the row is just a bound on it
+                let value = rhs / a;
+                let (tightens_lower, tightens_upper) = match (constraint.constraint_type, a > 0.0) {
+                    (ConstraintType::Equal, _) => (true, true),
+                    (ConstraintType::LessThanOrEqual, true)
+                    | (ConstraintType::GreaterThanOrEqual, false) => (false, true),
+                    (ConstraintType::LessThanOrEqual, false)
+                    | (ConstraintType::GreaterThanOrEqual, true) => (true, false),
+                };
+                if tightens_lower {
+                    let bound = if integer[j] {
+                        (value - PRESOLVE_TOLERANCE).ceil()
+                    } else {
+                        value
+                    };
+                    if bound > lower[j] {
+                        lower[j] = bound;
+                        lower_row[j] = Some(i);
+                        report.tightened_bounds += 1;
+                    }
+                }
+                if tightens_upper {
+                    let bound = if integer[j] {
+                        (value + PRESOLVE_TOLERANCE).floor()
+                    } else {
+                        value
+                    };
+                    if bound < upper[j] {
+                        upper[j] = bound;
+                        upper_row[j] = Some(i);
+                        report.tightened_bounds += 1;
+                    }
+                }
+                row_alive[i] = false;
+                found_at[i] = singletons;
+                singletons += 1;
+            }
+
+            if !row_alive[i] {
+                report.removed_constraints += 1;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let mut bound_rows: Vec<BoundRow> = (0..num_vars)
+        .flat_map(|j| {
+            let upper_row = upper_row[j].filter(|&i| lower_row[j] != Some(i));
+            [(lower_row[j], lower[j]), (upper_row, upper[j])]
+                .into_iter()
+                .filter_map(move |(row, bound)| Some((row?, j, bound)))
+        })
+        .map(|(i, j, bound)| BoundRow {
+            row: i,
+            variable: j,
+            coefficient: problem.constraints[i].coefficients.get(j),
+            bound,
+            cost: problem
+                .objective
+                .coefficients
+                .get(j)
+                .copied()
+                .unwrap_or(0.0),
+            column: problem
+                .constraints
+                .iter()
+                .enumerate()
+                .filter(|&(k, _)| k != i)
+                .map(|(k, c)| (k, c.coefficients.get(j)))
+                .filter(|&(_, a)| a != 0.0)
+                .collect(),
+        })
+        .collect();
+    bound_rows.sort_by_key(|bound_row| found_at[bound_row.row]);
+
+    let mut presolved = reduce(problem, fixed, row_alive, &lower, &upper, report);
+    presolved.bound_rows = bound_rows;
+    PresolveOutcome::Reduced(Box::new(presolved))
+}
+
+/// Build the reduced problem from the surviving rows and columns
+fn reduce(
+    problem: &OptimizationProblem,
+    fixed: Vec<Option<f64>>,
+    row_alive: Vec<bool>,
+    lower: &[f64],
+    upper: &[f64],
+    report: PresolveReport,
+) -> Presolved {
+    let kept_variables: Vec<usize> = (0..fixed.len()).filter(|&j| fixed[j].is_none()).collect();
+    let kept_constraints: Vec<usize> = (0..row_alive.len()).filter(|&i| row_alive[i]).collect();
+    let mut new_columns = vec![None; fixed.len()];
+    for (k, &j) in kept_variables.iter().enumerate() {
+        new_columns[j] = Some(k);
+    }
+    let pick = |values: &[f64]| -> Vec<f64> {
+        kept_variables
+            .iter()
+            .map(|&j| values.get(j).copied().unwrap_or(0.0))
+            .collect()
+    };
+    let pick_names = |names: &[String]| -> Vec<String> {
+        if names.len() == fixed.len() {
+            kept_variables.iter().map(|&j| names[j].clone()).collect()
+        } else {
+            names.to_vec()
+        }
+    };
+
+    let objective_offset = problem
+        .objective
+        .coefficients
+        .iter()
+        .zip(&fixed)
+        .filter_map(|(c, value)| value.map(|v| c * v))
+        .sum();
+
+    let mut reduced = problem.clone();
+    reduced.objective.coefficients = pick(&problem.objective.coefficients);
+    reduced.objective.variable_names = pick_names(&problem.objective.variable_names);
+    reduced.constraints = kept_constraints
+        .iter()
+        .map(|&i| {
+            let constraint = &problem.constraints[i];
+            let (_, rhs) = free_terms(constraint, &fixed);
+            Constraint {
+                coefficients: constraint
+                    .coefficients
+                    .remap(&new_columns, kept_variables.len()),
+                bound: rhs,
+                variable_names: pick_names(&constraint.variable_names),
+                ..constraint.clone()
+            }
+        })
+        .collect();
+    reduced.variables = kept_variables
+        .iter()
+        .map(|&j| {
+            let variable = problem
+                .variables
+                .get(j)
+                .cloned()
+                .unwrap_or_else(|| Variable::continuous(String::new()));
+            Variable {
+                lower_bound: lower[j],
+                upper_bound: upper[j].is_finite().then_some(upper[j]),
+                ..variable
+            }
+        })
+        .collect();
+
+    Presolved {
+        problem: reduced,
+        report,
+        objective_offset,
+        kept_variables,
+        kept_constraints,
+        fixed_values: fixed,
+        bound_rows: Vec::new(),
+        num_constraints: row_alive.len(),
+    }
+}
+
+/// Non-zero terms on unfixed variables, and the right-hand side once fixed ones move over
+fn free_terms(constraint: &Constraint, fixed: &[Option<f64>]) -> (Vec<(usize, f64)>, f64) {
+    let mut rhs = constraint.bound;
+    let mut terms = Vec::new();
+    for (j, a) in constraint.coefficients.iter() {
+        match fixed.get(j).copied().flatten() {
+            Some(value) => rhs -= a * value,
+            None => terms.push((j, a)),
+        }
+    }
+    (terms, rhs)
+}
+
+fn constraint_label(constraint: &Constraint, index: usize) -> String {
+    if constraint.name.is_empty() {
+        format!("Constraint {}", index)
+    } else {
+        format!("Constraint '{}'", constraint.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::ObjectiveFunction;
+    use crate::domain::value_objects::{OptimizationType, SolutionStatus};
+
+    /// max 3x + 2y  s.t.  x + y <= 4,  x + 3y <= 6,  x <= `cap`
+    fn capped_problem(cap: f64) -> OptimizationProblem {
+        OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Maximize,
+            vec![3.0, 2.0],
+        ))
+        .add_constraint(Constraint::new(
+            ConstraintType::LessThanOrEqual,
+            vec![1.0, 1.0],
+            4.0,
+        ))
+        .add_constraint(Constraint::new(
+            ConstraintType::LessThanOrEqual,
+            vec![1.0, 3.0],
+            6.0,
+        ))
+        .add_constraint(Constraint::new(
+            ConstraintType::LessThanOrEqual,
+            vec![1.0, 0.0],
+            cap,
+        ))
+    }
+
+    fn reduced(problem: &OptimizationProblem) -> Presolved {
+        match presolve(problem) {
+            PresolveOutcome::Reduced(presolved) => *presolved,
+            PresolveOutcome::Infeasible(reason) => panic!("infeasible: {}", reason),
+        }
+    }
+
+    fn optimal(values: Vec<f64>, duals: Vec<f64>) -> Solution {
+        let mut solution = Solution::new(SolutionStatus::Optimal, "");
+        solution.variable_values = values;
+        solution.dual_values = duals;
+        solution
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn binding_singleton_row_gets_its_dual_back() {
+        let presolved = reduced(&capped_problem(2.0));
+        assert_eq!(presolved.kept_constraints, [0, 1]);
+        assert_eq!(presolved.problem.variables[0].upper_bound, Some(2.0));
+
+        // Optimum of the reduced problem: x = 2 on its bound, y = 4/3 on row 1
+        let solution = presolved.postsolve(optimal(vec![2.0, 4.0 / 3.0], vec![0.0, 2.0 / 3.0]));
+
+        // 3 = y1 + y2 and 2 = 3 y1 in the original problem
+        assert_close(&solution.dual_values, &[0.0, 2.0 / 3.0, 7.0 / 3.0]);
+    }
+
+    #[test]
+    fn slack_singleton_row_keeps_a_zero_dual() {
+        let presolved = reduced(&capped_problem(3.5));
+
+        // Optimum x = 3, y = 1 leaves x <= 3.5 slack
+        let solution = presolved.postsolve(optimal(vec![3.0, 1.0], vec![2.5, 0.5]));
+
+        assert_close(&solution.dual_values, &[2.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn singleton_row_on_a_fixed_variable_prices_the_fixing() {
+        // x == 2 fixes x, which leaves the singletons y <= 2 and 3y <= 4 as bounds
+        let mut problem = capped_problem(2.0);
+        problem.constraints[2].constraint_type = ConstraintType::Equal;
+        let presolved = reduced(&problem);
+        assert_eq!(presolved.kept_variables, [1]);
+        assert!(presolved.kept_constraints.is_empty());
+
+        let solution = presolved.postsolve(optimal(vec![4.0 / 3.0], vec![]));
+
+        assert_close(&solution.variable_values, &[2.0, 4.0 / 3.0]);
+        assert_close(&solution.dual_values, &[0.0, 2.0 / 3.0, 7.0 / 3.0]);
+    }
+}
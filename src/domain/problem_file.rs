@@ -0,0 +1,1134 @@
+// Problem files: Reading MPS, CPLEX LP and JSON models into a problem, and
+// writing MPS. MPS is read in free format, which also covers fixed-format files
+// whose names have no spaces; ranged rows become a pair of constraints
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{Constraint, ObjectiveFunction, OptimizationProblem, Variable};
+use super::solver_service::{Result, SolverError};
+use super::value_objects::{ConstraintType, OptimizationType, VariableType};
+
+/// Magnitude from which a number in a file counts as infinite
+pub const INFINITE_MAGNITUDE: f64 = 1e30;
+
+/// Suffix naming the upper half of a ranged row, which is split in two
+pub const RANGE_UPPER_SUFFIX: &str = ".upper";
+
+/// Format of a problem file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProblemFormat {
+    Mps,
+    /// CPLEX LP format
+    Lp,
+    /// `OptimizationProblem` serialized as JSON
+    Json,
+}
+
+impl ProblemFormat {
+    /// Format named by a file path's extension, looking past a `.gz` suffix
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = path.to_ascii_lowercase();
+        let path = path.strip_suffix(".gz").unwrap_or(&path);
+        match path.rsplit_once('.')?.1 {
+            "mps" => Some(Self::Mps),
+            "lp" => Some(Self::Lp),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProblemFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mps => write!(f, "MPS"),
+            Self::Lp => write!(f, "LP"),
+            Self::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+impl OptimizationProblem {
+    /// Read a problem file's text
+    pub fn parse(text: &str, format: ProblemFormat) -> Result<Self> {
+        match format {
+            ProblemFormat::Mps => Self::from_mps(text),
+            ProblemFormat::Lp => Self::from_lp(text),
+            ProblemFormat::Json => serde_json::from_str(text)
+                .map_err(|e| SolverError::InvalidProblem(format!("Invalid JSON problem: {}", e))),
+        }
+    }
+
+    /// Read a model in MPS format
+    ///
+    /// Supports the NAME, OBJSENSE, ROWS, COLUMNS (with integer markers), RHS,
+    /// RANGES and BOUNDS sections. Free rows other than the objective are dropped.
+    pub fn from_mps(text: &str) -> Result<Self> {
+        MpsReader::default().read(text)
+    }
+
+    /// Read a model in CPLEX LP format
+    ///
+    /// Supports the objective, constraint, bounds, general and binary sections.
+    pub fn from_lp(text: &str) -> Result<Self> {
+        LpReader::default().read(text)
+    }
+
+    /// Write the model in free MPS format, which [`Self::from_mps`] reads back
+    ///
+    /// Whitespace in names becomes `_`, unnamed columns and rows are called
+    /// `x<index>` and `c<index>`, and a name already taken gets `#<index>`
+    /// appended. Solver settings aren't part of MPS and are left out.
+    pub fn to_mps(&self) -> String {
+        let columns = mps_names(
+            (0..self.num_variables()).map(|j| (self.variable_name(j), format!("x{}", j))),
+            &[],
+        );
+        let rows = mps_names(
+            self.constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (c.name.clone(), format!("c{}", i))),
+            &[MPS_OBJECTIVE_ROW],
+        );
+
+        let mut mps = String::new();
+        let _ = writeln!(
+            mps,
+            "NAME {}",
+            self.name.split_whitespace().collect::<Vec<_>>().join("_")
+        );
+        if self.objective.optimization_type == OptimizationType::Maximize {
+            mps.push_str("OBJSENSE\n    MAX\n");
+        }
+        mps.push_str("ROWS\n");
+        let _ = writeln!(mps, " N  {}", MPS_OBJECTIVE_ROW);
+        for (constraint, name) in self.constraints.iter().zip(&rows) {
+            let kind = match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => 'L',
+                ConstraintType::GreaterThanOrEqual => 'G',
+                ConstraintType::Equal => 'E',
+            };
+            let _ = writeln!(mps, " {}  {}", kind, name);
+        }
+
+        mps.push_str("COLUMNS\n");
+        let mut in_integer_block = false;
+        let mut markers = 0;
+        for (j, column) in columns.iter().enumerate() {
+            let integer = self.variables.get(j).is_some_and(|v| v.is_integer());
+            if integer != in_integer_block {
+                let marker = if integer { "INTORG" } else { "INTEND" };
+                let _ = writeln!(mps, "    M{} 'MARKER' '{}'", markers, marker);
+                markers += 1;
+                in_integer_block = integer;
+            }
+            let cost = self.objective.coefficients.get(j).copied().unwrap_or(0.0);
+            let entries: Vec<(&str, f64)> = self
+                .constraints
+                .iter()
+                .zip(&rows)
+                .filter_map(|(c, row)| {
+                    let a = c.coefficients.get(j);
+                    (a != 0.0).then_some((row.as_str(), a))
+                })
+                .collect();
+            // A column is declared by its entries, so one without any lists its cost
+            if cost != 0.0 || entries.is_empty() {
+                let _ = writeln!(
+                    mps,
+                    "    {} {} {}",
+                    column,
+                    MPS_OBJECTIVE_ROW,
+                    mps_number(cost)
+                );
+            }
+            for (row, a) in entries {
+                let _ = writeln!(mps, "    {} {} {}", column, row, mps_number(a));
+            }
+        }
+        if in_integer_block {
+            let _ = writeln!(mps, "    M{} 'MARKER' 'INTEND'", markers);
+        }
+
+        mps.push_str("RHS\n");
+        for (constraint, name) in self.constraints.iter().zip(&rows) {
+            if constraint.bound != 0.0 {
+                let _ = writeln!(mps, "    RHS {} {}", name, mps_number(constraint.bound));
+            }
+        }
+
+        mps.push_str("BOUNDS\n");
+        for (variable, column) in self.variables.iter().zip(&columns) {
+            let lower = variable.lower_bound;
+            let upper = variable.upper_bound.unwrap_or(f64::INFINITY);
+            let mut bound = |kind: &str, value: Option<f64>| {
+                let _ = match value {
+                    Some(value) => writeln!(mps, " {} BND {} {}", kind, column, mps_number(value)),
+                    None => writeln!(mps, " {} BND {}", kind, column),
+                };
+            };
+            if variable.variable_type == VariableType::Binary && lower == 0.0 && upper == 1.0 {
+                bound("BV", None);
+            } else if lower == upper {
+                bound("FX", Some(lower));
+            } else if lower == f64::NEG_INFINITY && upper == f64::INFINITY {
+                bound("FR", None);
+            } else {
+                // A negative upper bound alone would also free the column below
+                if lower == f64::NEG_INFINITY {
+                    bound("MI", None);
+                } else if lower != 0.0 || upper < 0.0 {
+                    bound("LO", Some(lower));
+                }
+                if upper < f64::INFINITY {
+                    bound("UP", Some(upper));
+                } else if variable.is_integer() {
+                    // Some readers give integer columns without bounds an upper bound of 1
+                    bound("PL", None);
+                }
+            }
+        }
+        mps.push_str("ENDATA\n");
+        mps
+    }
+}
+
+/// Name of the objective row in written MPS files
+const MPS_OBJECTIVE_ROW: &str = "OBJ";
+
+/// Names usable in free MPS, one per `(name, fallback)`, all distinct from each
+/// other and from `reserved`
+fn mps_names(names: impl Iterator<Item = (String, String)>, reserved: &[&str]) -> Vec<String> {
+    let mut taken: HashSet<String> = reserved.iter().map(|name| name.to_string()).collect();
+    names
+        .enumerate()
+        .map(|(index, (name, fallback))| {
+            let name = name.split_whitespace().collect::<Vec<_>>().join("_");
+            let name = if name.is_empty() { fallback } else { name };
+            let name = if taken.contains(&name) {
+                format!("{}#{}", name, index)
+            } else {
+                name
+            };
+            taken.insert(name.clone());
+            name
+        })
+        .collect()
+}
+
+/// A number as MPS writes it: exactly, and in exponent form when very large or small
+fn mps_number(value: f64) -> String {
+    if value == 0.0 || (1e-4..1e15).contains(&value.abs()) {
+        value.to_string()
+    } else {
+        format!("{:e}", value)
+    }
+}
+
+fn parse_error(line: usize, message: impl fmt::Display) -> SolverError {
+    SolverError::InvalidProblem(format!("Line {}: {}", line, message))
+}
+
+/// Columns and rows gathered while reading, by name
+#[derive(Default)]
+struct ModelBuilder {
+    name: String,
+    sense: Option<OptimizationType>,
+    columns: Vec<Column>,
+    column_index: HashMap<String, usize>,
+    rows: Vec<Row>,
+    row_index: HashMap<String, usize>,
+}
+
+struct Column {
+    name: String,
+    cost: f64,
+    lower: f64,
+    upper: f64,
+    integer: bool,
+    binary: bool,
+}
+
+struct Row {
+    name: String,
+    lower: f64,
+    upper: f64,
+    entries: Vec<(usize, f64)>,
+}
+
+impl ModelBuilder {
+    fn column(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.column_index.get(name) {
+            return index;
+        }
+        let index = self.columns.len();
+        self.columns.push(Column {
+            name: name.to_string(),
+            cost: 0.0,
+            lower: 0.0,
+            upper: f64::INFINITY,
+            integer: false,
+            binary: false,
+        });
+        self.column_index.insert(name.to_string(), index);
+        index
+    }
+
+    fn add_row(&mut self, name: String, lower: f64, upper: f64, entries: Vec<(usize, f64)>) {
+        self.row_index.insert(name.clone(), self.rows.len());
+        self.rows.push(Row {
+            name,
+            lower,
+            upper,
+            entries,
+        });
+    }
+
+    fn finish(self) -> Result<OptimizationProblem> {
+        let num_vars = self.columns.len();
+        let names: Vec<String> = self.columns.iter().map(|c| c.name.clone()).collect();
+        let objective = ObjectiveFunction::new(
+            self.sense.unwrap_or(OptimizationType::Minimize),
+            self.columns.iter().map(|c| c.cost).collect(),
+        )
+        .with_names(names);
+
+        let variables = self
+            .columns
+            .into_iter()
+            .map(|column| {
+                let variable = if column.binary {
+                    Variable::binary(column.name)
+                } else if column.integer {
+                    Variable::integer(column.name)
+                } else {
+                    Variable::continuous(column.name)
+                };
+                variable.with_bounds(
+                    column.lower,
+                    column.upper.is_finite().then_some(column.upper),
+                )
+            })
+            .collect();
+
+        let mut constraints = Vec::with_capacity(self.rows.len());
+        for row in self.rows {
+            let mut coefficients = vec![0.0; num_vars];
+            for (column, value) in row.entries {
+                coefficients[column] += value;
+            }
+            let constraint = |constraint_type, bound, name: String| {
+                Constraint::new(constraint_type, coefficients.clone(), bound).with_name(name)
+            };
+            match (row.lower.is_finite(), row.upper.is_finite()) {
+                _ if row.lower == row.upper => {
+                    constraints.push(constraint(ConstraintType::Equal, row.lower, row.name))
+                }
+                (true, true) => {
+                    let upper_name = format!("{}{}", row.name, RANGE_UPPER_SUFFIX);
+                    constraints.push(constraint(
+                        ConstraintType::GreaterThanOrEqual,
+                        row.lower,
+                        row.name,
+                    ));
+                    constraints.push(constraint(
+                        ConstraintType::LessThanOrEqual,
+                        row.upper,
+                        upper_name,
+                    ));
+                }
+                (true, false) => constraints.push(constraint(
+                    ConstraintType::GreaterThanOrEqual,
+                    row.lower,
+                    row.name,
+                )),
+                (false, true) => constraints.push(constraint(
+                    ConstraintType::LessThanOrEqual,
+                    row.upper,
+                    row.name,
+                )),
+                // A row without bounds constrains nothing
+                (false, false) => {}
+            }
+        }
+
+        Ok(OptimizationProblem {
+            constraints,
+            variables,
+            ..OptimizationProblem::new(objective)
+        }
+        .with_name(self.name))
+    }
+}
+
+fn parse_number(token: &str, line: usize) -> Result<f64> {
+    let value = match token.to_ascii_lowercase().as_str() {
+        "inf" | "+inf" | "infinity" | "+infinity" => f64::INFINITY,
+        "-inf" | "-infinity" => f64::NEG_INFINITY,
+        _ => token
+            .parse()
+            .map_err(|_| parse_error(line, format!("Expected a number, found '{}'", token)))?,
+    };
+    if value.is_nan() {
+        return Err(parse_error(line, "NaN is not a valid number"));
+    }
+    // Writers spell infinite bounds as 1e30 and the like
+    Ok(if value.abs() >= INFINITE_MAGNITUDE {
+        value.signum() * f64::INFINITY
+    } else {
+        value
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MpsSection {
+    Header,
+    ObjSense,
+    Rows,
+    Columns,
+    Rhs,
+    Ranges,
+    Bounds,
+    End,
+}
+
+#[derive(Default)]
+struct MpsReader {
+    model: ModelBuilder,
+    objective_row: Option<String>,
+    /// Rows beyond the objective that constrain nothing, whose entries are dropped
+    free_rows: Vec<String>,
+    /// Type of each row, by name: 'L', 'G' or 'E'
+    row_types: HashMap<String, char>,
+    in_integer_block: bool,
+    /// Columns that already have a BOUNDS entry, so a later negative UP bound
+    /// leaves their lower bound alone
+    bounded: Vec<bool>,
+}
+
+impl MpsReader {
+    fn read(mut self, text: &str) -> Result<OptimizationProblem> {
+        let mut section = MpsSection::Header;
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            if line.trim().is_empty() || line.starts_with('*') {
+                continue;
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if !line.starts_with(char::is_whitespace) {
+                section = self.header(&tokens, number)?;
+                if section == MpsSection::End {
+                    break;
+                }
+                continue;
+            }
+            match section {
+                MpsSection::Header => {
+                    return Err(parse_error(number, "Data before the first section"))
+                }
+                MpsSection::ObjSense => self.model.sense = Some(mps_sense(tokens[0], number)?),
+                MpsSection::Rows => self.row(&tokens, number)?,
+                MpsSection::Columns => self.columns(&tokens, number)?,
+                MpsSection::Rhs => self.rhs(&tokens, number)?,
+                MpsSection::Ranges => self.ranges(&tokens, number)?,
+                MpsSection::Bounds => self.bounds(&tokens, number)?,
+                MpsSection::End => break,
+            }
+        }
+        if self.objective_row.is_none() && self.model.rows.is_empty() {
+            return Err(SolverError::InvalidProblem(
+                "MPS file has no ROWS section".to_string(),
+            ));
+        }
+        self.model.finish()
+    }
+
+    fn header(&mut self, tokens: &[&str], line: usize) -> Result<MpsSection> {
+        let section = match tokens[0].to_ascii_uppercase().as_str() {
+            "NAME" => {
+                self.model.name = tokens[1..].join(" ");
+                MpsSection::Header
+            }
+            "OBJSENSE" => match tokens.get(1) {
+                Some(sense) => {
+                    self.model.sense = Some(mps_sense(sense, line)?);
+                    MpsSection::Header
+                }
+                None => MpsSection::ObjSense,
+            },
+            "ROWS" => MpsSection::Rows,
+            "COLUMNS" => MpsSection::Columns,
+            "RHS" => MpsSection::Rhs,
+            "RANGES" => MpsSection::Ranges,
+            "BOUNDS" => MpsSection::Bounds,
+            "ENDATA" => MpsSection::End,
+            other => {
+                return Err(parse_error(
+                    line,
+                    format!("Unsupported MPS section '{}'", other),
+                ))
+            }
+        };
+        Ok(section)
+    }
+
+    fn row(&mut self, tokens: &[&str], line: usize) -> Result<()> {
+        let [kind, name] = tokens else {
+            return Err(parse_error(line, "Expected a row type and name"));
+        };
+        let name = name.to_string();
+        let kind = kind.to_ascii_uppercase();
+        if self.row_types.contains_key(&name)
+            || self.objective_row.as_ref() == Some(&name)
+            || self.free_rows.contains(&name)
+        {
+            return Err(parse_error(
+                line,
+                format!("Row '{}' is declared twice", name),
+            ));
+        }
+        let (lower, upper) = match kind.as_str() {
+            "N" if self.objective_row.is_none() => {
+                self.objective_row = Some(name);
+                return Ok(());
+            }
+            "N" => {
+                self.free_rows.push(name);
+                return Ok(());
+            }
+            "L" => (f64::NEG_INFINITY, 0.0),
+            "G" => (0.0, f64::INFINITY),
+            "E" => (0.0, 0.0),
+            other => return Err(parse_error(line, format!("Unknown row type '{}'", other))),
+        };
+        self.row_types
+            .insert(name.clone(), kind.chars().next().unwrap_or('E'));
+        self.model.add_row(name, lower, upper, Vec::new());
+        Ok(())
+    }
+
+    fn columns(&mut self, tokens: &[&str], line: usize) -> Result<()> {
+        if tokens.len() >= 3 && tokens[1].trim_matches('\'').eq_ignore_ascii_case("MARKER") {
+            match tokens[2].trim_matches('\'').to_ascii_uppercase().as_str() {
+                "INTORG" => self.in_integer_block = true,
+                "INTEND" => self.in_integer_block = false,
+                other => return Err(parse_error(line, format!("Unknown marker '{}'", other))),
+            }
+            return Ok(());
+        }
+        if tokens.len() != 3 && tokens.len() != 5 {
+            return Err(parse_error(
+                line,
+                "Expected a column name and one or two row/value pairs",
+            ));
+        }
+        let column = self.model.column(tokens[0]);
+        if column == self.bounded.len() {
+            self.bounded.push(false);
+            self.model.columns[column].integer = self.in_integer_block;
+        }
+        for pair in tokens[1..].chunks(2) {
+            let value = parse_number(pair[1], line)?;
+            self.entry(column, pair[0], value, line)?;
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, column: usize, row: &str, value: f64, line: usize) -> Result<()> {
+        if self.objective_row.as_deref() == Some(row) {
+            self.model.columns[column].cost += value;
+        } else if let Some(&index) = self.model.row_index.get(row) {
+            self.model.rows[index].entries.push((column, value));
+        } else if !self.free_rows.iter().any(|free| free == row) {
+            return Err(parse_error(line, format!("Unknown row '{}'", row)));
+        }
+        Ok(())
+    }
+
+    fn rhs(&mut self, tokens: &[&str], line: usize) -> Result<()> {
+        // The RHS vector's name is optional in free MPS
+        let pairs = if tokens.len() % 2 == 1 {
+            &tokens[1..]
+        } else {
+            tokens
+        };
+        for pair in pairs.chunks(2) {
+            let value = parse_number(pair[1], line)?;
+            if self.objective_row.as_deref() == Some(pair[0]) {
+                if value != 0.0 {
+                    return Err(parse_error(line, "Objective constants are not supported"));
+                }
+                continue;
+            }
+            let Some(&index) = self.model.row_index.get(pair[0]) else {
+                if self.free_rows.iter().any(|free| free == pair[0]) {
+                    continue;
+                }
+                return Err(parse_error(line, format!("Unknown row '{}'", pair[0])));
+            };
+            let row = &mut self.model.rows[index];
+            match self.row_types[pair[0]] {
+                'L' => row.upper = value,
+                'G' => row.lower = value,
+                _ => {
+                    row.lower = value;
+                    row.upper = value;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ranges are applied after RHS, which MPS files list first
+    fn ranges(&mut self, tokens: &[&str], line: usize) -> Result<()> {
+        let pairs = if tokens.len() % 2 == 1 {
+            &tokens[1..]
+        } else {
+            tokens
+        };
+        for pair in pairs.chunks(2) {
+            let range = parse_number(pair[1], line)?;
+            let Some(&index) = self.model.row_index.get(pair[0]) else {
+                return Err(parse_error(line, format!("Unknown row '{}'", pair[0])));
+            };
+            let row = &mut self.model.rows[index];
+            match self.row_types[pair[0]] {
+                'L' => row.lower = row.upper - range.abs(),
+                'G' => row.upper = row.lower + range.abs(),
+                _ if range >= 0.0 => row.upper = row.lower + range,
+                _ => row.lower = row.upper + range,
+            }
+        }
+        Ok(())
+    }
+
+    fn bounds(&mut self, tokens: &[&str], line: usize) -> Result<()> {
+        let kind = tokens[0].to_ascii_uppercase();
+        let takes_value = !matches!(kind.as_str(), "FR" | "MI" | "PL" | "BV");
+        // The bound vector's name is optional in free MPS
+        let (name, value) = match (takes_value, tokens.len()) {
+            (true, 4) => (tokens[2], Some(parse_number(tokens[3], line)?)),
+            (true, 3) => (tokens[1], Some(parse_number(tokens[2], line)?)),
+            (false, 3) if kind != "BV" => (tokens[2], None),
+            (false, 2) => (tokens[1], None),
+            // BV may carry a value, which is ignored
+            (false, 3 | 4) => (tokens[2], None),
+            _ => return Err(parse_error(line, "Malformed bound")),
+        };
+        let Some(&index) = self.model.column_index.get(name) else {
+            return Err(parse_error(line, format!("Unknown column '{}'", name)));
+        };
+        let value = value.unwrap_or_default();
+        let column = &mut self.model.columns[index];
+        match kind.as_str() {
+            "UP" | "UI" => {
+                // An old convention: a negative upper bound alone makes the column free below
+                if value < 0.0 && column.lower == 0.0 && !self.bounded[index] {
+                    column.lower = f64::NEG_INFINITY;
+                }
+                column.upper = value;
+            }
+            "LO" | "LI" => column.lower = value,
+            "FX" => {
+                column.lower = value;
+                column.upper = value;
+            }
+            "FR" => {
+                column.lower = f64::NEG_INFINITY;
+                column.upper = f64::INFINITY;
+            }
+            "MI" => column.lower = f64::NEG_INFINITY,
+            "PL" => column.upper = f64::INFINITY,
+            "BV" => {
+                column.binary = true;
+                column.lower = 0.0;
+                column.upper = 1.0;
+            }
+            other => {
+                return Err(parse_error(
+                    line,
+                    format!("Unsupported bound type '{}'", other),
+                ))
+            }
+        }
+        if matches!(kind.as_str(), "UI" | "LI") {
+            column.integer = true;
+        }
+        self.bounded[index] = true;
+        Ok(())
+    }
+}
+
+fn mps_sense(token: &str, line: usize) -> Result<OptimizationType> {
+    match token.to_ascii_uppercase().as_str() {
+        "MIN" | "MINIMIZE" => Ok(OptimizationType::Minimize),
+        "MAX" | "MAXIMIZE" => Ok(OptimizationType::Maximize),
+        other => Err(parse_error(
+            line,
+            format!("Unknown objective sense '{}'", other),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LpSection {
+    Objective,
+    Constraints,
+    Bounds,
+    General,
+    Binary,
+    /// Semi-continuous variables, which problems can't express
+    SemiContinuous,
+    /// Special ordered sets, which problems can't express
+    Sos,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Sign(f64),
+    Colon,
+    Relation(Relation),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Relation {
+    Le,
+    Ge,
+    Eq,
+}
+
+/// Tokens of one section, each with the line it is on
+type Tokens = Vec<(Token, usize)>;
+
+#[derive(Default)]
+struct LpReader {
+    model: ModelBuilder,
+}
+
+impl LpReader {
+    fn read(mut self, text: &str) -> Result<OptimizationProblem> {
+        let mut sections: Vec<(LpSection, Tokens)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let line = line.split('\\').next().unwrap_or_default();
+            let (section, rest) = match lp_section(line) {
+                Some((LpSection::Objective, _, _)) if self.model.sense.is_some() => {
+                    return Err(parse_error(number, "A second objective section"))
+                }
+                Some((section, sense, rest)) => {
+                    if section == LpSection::Objective {
+                        self.model.sense = sense;
+                    }
+                    sections.push((section, Vec::new()));
+                    (section, rest)
+                }
+                None if line.trim().eq_ignore_ascii_case("end") => break,
+                None => match sections.last() {
+                    Some((section, _)) => (*section, line),
+                    None if line.trim().is_empty() => continue,
+                    None => return Err(parse_error(number, "Expected MINIMIZE or MAXIMIZE")),
+                },
+            };
+            let tokens = &mut sections.last_mut().expect("section pushed above").1;
+            if section == LpSection::SemiContinuous || section == LpSection::Sos {
+                // Writers emit these sections empty; only their contents are unsupported
+                if !rest.trim().is_empty() {
+                    let what = match section {
+                        LpSection::Sos => "SOS constraints are",
+                        _ => "Semi-continuous variables are",
+                    };
+                    return Err(parse_error(number, format!("{} not supported", what)));
+                }
+            } else if matches!(section, LpSection::General | LpSection::Binary) {
+                tokens.extend(
+                    rest.split_whitespace()
+                        .map(|name| (Token::Name(name.to_string()), number)),
+                );
+            } else {
+                tokenize(rest, number, tokens)?;
+            }
+        }
+        if self.model.sense.is_none() {
+            return Err(SolverError::InvalidProblem(
+                "LP file has no objective section".to_string(),
+            ));
+        }
+
+        for (section, tokens) in sections {
+            match section {
+                LpSection::Objective => self.objective(&tokens)?,
+                LpSection::Constraints => self.constraints(&tokens)?,
+                LpSection::Bounds => self.bounds(&tokens)?,
+                LpSection::SemiContinuous | LpSection::Sos => {}
+                LpSection::General | LpSection::Binary => {
+                    for (token, _) in tokens {
+                        let Token::Name(name) = token else { continue };
+                        let index = self.model.column(&name);
+                        let column = &mut self.model.columns[index];
+                        if section == LpSection::Binary {
+                            column.binary = true;
+                            column.lower = column.lower.max(0.0);
+                            column.upper = column.upper.min(1.0);
+                        } else {
+                            column.integer = true;
+                        }
+                    }
+                }
+            }
+        }
+        self.model.finish()
+    }
+
+    fn objective(&mut self, tokens: &[(Token, usize)]) -> Result<()> {
+        let mut position = 0;
+        label(tokens, &mut position);
+        let (terms, constant) = self.expression(tokens, &mut position)?;
+        if let Some((_, line)) = tokens.get(position) {
+            return Err(parse_error(*line, "Unexpected token in the objective"));
+        }
+        if constant != 0.0 {
+            let line = tokens.first().map_or(0, |(_, line)| *line);
+            return Err(parse_error(line, "Objective constants are not supported"));
+        }
+        for (column, value) in terms {
+            self.model.columns[column].cost += value;
+        }
+        Ok(())
+    }
+
+    fn constraints(&mut self, tokens: &[(Token, usize)]) -> Result<()> {
+        let mut position = 0;
+        while position < tokens.len() {
+            let line = tokens[position].1;
+            let name = label(tokens, &mut position)
+                .unwrap_or_else(|| format!("R{}", self.model.rows.len() + 1));
+            let (terms, constant) = self.expression(tokens, &mut position)?;
+            let Some((Token::Relation(relation), _)) = tokens.get(position) else {
+                return Err(parse_error(line, "Expected <=, >= or = in a constraint"));
+            };
+            position += 1;
+            let rhs = signed_number(tokens, &mut position, line)? - constant;
+            let (lower, upper) = match relation {
+                Relation::Le => (f64::NEG_INFINITY, rhs),
+                Relation::Ge => (rhs, f64::INFINITY),
+                Relation::Eq => (rhs, rhs),
+            };
+            if self.model.row_index.contains_key(&name) {
+                return Err(parse_error(
+                    line,
+                    format!("Constraint '{}' is declared twice", name),
+                ));
+            }
+            self.model.add_row(name, lower, upper, terms);
+        }
+        Ok(())
+    }
+
+    /// `x <= u`, `l <= x`, `l <= x <= u`, `x = v` and `x free`, with the
+    /// relations either way round
+    fn bounds(&mut self, tokens: &[(Token, usize)]) -> Result<()> {
+        let mut position = 0;
+        while position < tokens.len() {
+            let line = tokens[position].1;
+            let lead = if matches!(tokens[position].0, Token::Name(ref name) if !is_infinity(name))
+            {
+                None
+            } else {
+                let value = signed_number(tokens, &mut position, line)?;
+                let relation = relation(tokens, &mut position, line)?;
+                Some((value, relation))
+            };
+            let Some((Token::Name(name), _)) = tokens.get(position) else {
+                return Err(parse_error(line, "Expected a variable in a bound"));
+            };
+            position += 1;
+            let index = self.model.column(name);
+
+            if let Some((value, relation)) = lead {
+                // `value <= x` bounds x below
+                self.bound(index, flip(relation), value);
+            }
+            match tokens.get(position) {
+                Some((Token::Name(word), _)) if word.eq_ignore_ascii_case("free") => {
+                    position += 1;
+                    let column = &mut self.model.columns[index];
+                    column.lower = f64::NEG_INFINITY;
+                    column.upper = f64::INFINITY;
+                }
+                Some((Token::Relation(_), _)) => {
+                    let relation = relation(tokens, &mut position, line)?;
+                    let value = signed_number(tokens, &mut position, line)?;
+                    self.bound(index, relation, value);
+                }
+                _ if lead.is_some() => {}
+                _ => return Err(parse_error(line, format!("Incomplete bound on '{}'", name))),
+            }
+        }
+        Ok(())
+    }
+
+    fn bound(&mut self, column: usize, relation: Relation, value: f64) {
+        let column = &mut self.model.columns[column];
+        match relation {
+            Relation::Le => column.upper = value,
+            Relation::Ge => column.lower = value,
+            Relation::Eq => {
+                column.lower = value;
+                column.upper = value;
+            }
+        }
+    }
+
+    /// Linear terms up to the next relation, label or end, plus any constant
+    fn expression(
+        &mut self,
+        tokens: &[(Token, usize)],
+        position: &mut usize,
+    ) -> Result<(Vec<(usize, f64)>, f64)> {
+        let mut terms = Vec::new();
+        let mut constant = 0.0;
+        loop {
+            let start = *position;
+            let mut sign = 1.0;
+            while let Some((Token::Sign(s), _)) = tokens.get(*position) {
+                sign *= s;
+                *position += 1;
+            }
+            let coefficient = match tokens.get(*position) {
+                Some((Token::Number(value), _)) => {
+                    *position += 1;
+                    Some(*value)
+                }
+                _ => None,
+            };
+            // A name followed by a colon labels the next constraint
+            let is_label = matches!(tokens.get(*position + 1), Some((Token::Colon, _)));
+            match (tokens.get(*position), coefficient) {
+                (Some((Token::Name(name), _)), _) if !is_label && !is_infinity(name) => {
+                    *position += 1;
+                    let column = self.model.column(name);
+                    terms.push((column, sign * coefficient.unwrap_or(1.0)));
+                }
+                (_, Some(value)) => constant += sign * value,
+                _ if *position > start => {
+                    return Err(parse_error(tokens[start].1, "Sign without a term"))
+                }
+                _ => return Ok((terms, constant)),
+            }
+            // Terms are joined by signs; anything else ends the expression
+            if !matches!(tokens.get(*position), Some((Token::Sign(_), _))) {
+                return Ok((terms, constant));
+            }
+        }
+    }
+}
+
+/// The section a line starts, its objective sense, and the rest of the line
+fn lp_section(line: &str) -> Option<(LpSection, Option<OptimizationType>, &str)> {
+    let trimmed = line.trim_start();
+    let lower = trimmed.to_ascii_lowercase();
+    let keywords: &[(&str, LpSection, Option<OptimizationType>)] = &[
+        (
+            "minimize",
+            LpSection::Objective,
+            Some(OptimizationType::Minimize),
+        ),
+        (
+            "minimise",
+            LpSection::Objective,
+            Some(OptimizationType::Minimize),
+        ),
+        (
+            "minimum",
+            LpSection::Objective,
+            Some(OptimizationType::Minimize),
+        ),
+        (
+            "min",
+            LpSection::Objective,
+            Some(OptimizationType::Minimize),
+        ),
+        (
+            "maximize",
+            LpSection::Objective,
+            Some(OptimizationType::Maximize),
+        ),
+        (
+            "maximise",
+            LpSection::Objective,
+            Some(OptimizationType::Maximize),
+        ),
+        (
+            "maximum",
+            LpSection::Objective,
+            Some(OptimizationType::Maximize),
+        ),
+        (
+            "max",
+            LpSection::Objective,
+            Some(OptimizationType::Maximize),
+        ),
+        ("subject to", LpSection::Constraints, None),
+        ("such that", LpSection::Constraints, None),
+        ("s.t.", LpSection::Constraints, None),
+        ("st", LpSection::Constraints, None),
+        ("bounds", LpSection::Bounds, None),
+        ("bound", LpSection::Bounds, None),
+        ("generals", LpSection::General, None),
+        ("general", LpSection::General, None),
+        ("gen", LpSection::General, None),
+        ("integers", LpSection::General, None),
+        ("integer", LpSection::General, None),
+        ("binaries", LpSection::Binary, None),
+        ("binary", LpSection::Binary, None),
+        ("bin", LpSection::Binary, None),
+        ("semi-continuous", LpSection::SemiContinuous, None),
+        ("semis", LpSection::SemiContinuous, None),
+        ("semi", LpSection::SemiContinuous, None),
+        ("sos", LpSection::Sos, None),
+    ];
+    keywords.iter().find_map(|&(keyword, section, sense)| {
+        let rest = lower.strip_prefix(keyword)?;
+        // The keyword must be a whole word, optionally followed by a colon
+        let rest = rest.strip_prefix(':').unwrap_or(rest);
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        Some((section, sense, &trimmed[trimmed.len() - rest.len()..]))
+    })
+}
+
+/// Split LP text into tokens; coefficients may touch their variable (`3x`)
+fn tokenize(text: &str, line: usize, tokens: &mut Tokens) -> Result<()> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let token = match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '+' | '-' => {
+                i += 1;
+                Token::Sign(if c == '-' { -1.0 } else { 1.0 })
+            }
+            ':' => {
+                i += 1;
+                Token::Colon
+            }
+            '<' | '>' | '=' => {
+                let mut relation = match c {
+                    '<' => Relation::Le,
+                    '>' => Relation::Ge,
+                    _ => Relation::Eq,
+                };
+                i += 1;
+                // `<=`, `=<`, `>=` and `=>` are the same as `<`, `>`
+                if let Some(&next) = chars.get(i) {
+                    match (c, next) {
+                        ('<' | '>', '=') => i += 1,
+                        ('=', '<') => {
+                            relation = Relation::Le;
+                            i += 1;
+                        }
+                        ('=', '>') => {
+                            relation = Relation::Ge;
+                            i += 1;
+                        }
+                        _ => {}
+                    }
+                }
+                Token::Relation(relation)
+            }
+            _ if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // An exponent only if digits follow, so `2e` stays a coefficient and a name
+                if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && matches!(chars[j], '+' | '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let number: String = chars[start..i].iter().collect();
+                Token::Number(parse_number(&number, line)?)
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(chars[i], '+' | '-' | ':' | '<' | '>' | '=')
+                {
+                    i += 1;
+                }
+                Token::Name(chars[start..i].iter().collect())
+            }
+        };
+        tokens.push((token, line));
+    }
+    Ok(())
+}
+
+/// Consume `name:` if it is next
+fn label(tokens: &[(Token, usize)], position: &mut usize) -> Option<String> {
+    match (tokens.get(*position), tokens.get(*position + 1)) {
+        (Some((Token::Name(name), _)), Some((Token::Colon, _))) => {
+            *position += 2;
+            Some(name.clone())
+        }
+        _ => None,
+    }
+}
+
+fn is_infinity(name: &str) -> bool {
+    name.eq_ignore_ascii_case("inf") || name.eq_ignore_ascii_case("infinity")
+}
+
+/// A number with optional signs; `inf` and `infinity` included
+fn signed_number(tokens: &[(Token, usize)], position: &mut usize, line: usize) -> Result<f64> {
+    let mut sign = 1.0;
+    while let Some((Token::Sign(s), _)) = tokens.get(*position) {
+        sign *= s;
+        *position += 1;
+    }
+    let value = match tokens.get(*position) {
+        Some((Token::Number(value), _)) => *value,
+        Some((Token::Name(name), _)) if is_infinity(name) => f64::INFINITY,
+        _ => return Err(parse_error(line, "Expected a number")),
+    };
+    *position += 1;
+    Ok(sign * value)
+}
+
+fn relation(tokens: &[(Token, usize)], position: &mut usize, line: usize) -> Result<Relation> {
+    match tokens.get(*position) {
+        Some((Token::Relation(relation), _)) => {
+            *position += 1;
+            Ok(*relation)
+        }
+        _ => Err(parse_error(line, "Expected <=, >= or =")),
+    }
+}
+
+fn flip(relation: Relation) -> Relation {
+    match relation {
+        Relation::Le => Relation::Ge,
+        Relation::Ge => Relation::Le,
+        Relation::Eq => Relation::Eq,
+    }
+}
@@ -0,0 +1,109 @@
+// Solve recording: Requests and their outcomes kept to re-run later
+// Replaying a recording against another backend or solver version shows what an
+// upgrade would change before it reaches production
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{OptimizationProblem, Solution};
+use super::solution_diff::{SolutionDiff, DEFAULT_DIFF_THRESHOLD};
+use super::value_objects::SolutionStatus;
+
+/// Relative objective difference below which a replay counts as reproducing it
+pub const DEFAULT_REPLAY_TOLERANCE: f64 = 1e-6;
+
+/// One recorded solve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSolve {
+    /// Request ID of the solve, as in the audit log
+    pub request_id: String,
+    pub recorded_at: SystemTime,
+    /// Canonical form of the problem, with the settings it was solved with
+    pub problem: OptimizationProblem,
+    /// Random seed the backend was given; None when it used its default
+    pub seed: Option<i64>,
+    /// Backend that found the recorded solution
+    pub backend: String,
+    /// As returned; its duals follow the constraint order the caller sent
+    pub solution: Solution,
+}
+
+impl RecordedSolve {
+    /// Record of `problem` solved as `solution` by `backend`; the problem is kept
+    /// canonical
+    pub fn new(
+        request_id: impl Into<String>,
+        problem: &OptimizationProblem,
+        seed: Option<i64>,
+        backend: impl Into<String>,
+        solution: Solution,
+    ) -> Self {
+        Self {
+            request_id: request_id.into(),
+            recorded_at: SystemTime::now(),
+            problem: problem.canonicalize(),
+            seed,
+            backend: backend.into(),
+            solution,
+        }
+    }
+}
+
+/// How a replayed solve compares with its recording
+#[derive(Debug, Clone)]
+pub struct ReplayComparison {
+    pub recorded_status: SolutionStatus,
+    pub replayed_status: SolutionStatus,
+    pub recorded_objective: Option<f64>,
+    pub replayed_objective: Option<f64>,
+    /// Whether the objective changed by more than the tolerance, relative to
+    /// the recorded objective's size (at least 1); a missing objective on one
+    /// side only counts as a change
+    pub objective_differs: bool,
+    /// Variables that moved, named after the problem's variables; None when the
+    /// solutions can't be compared value by value
+    pub diff: Option<SolutionDiff>,
+}
+
+impl ReplayComparison {
+    /// Compare `replayed` with the `recorded` solution of `problem`
+    pub fn new(
+        problem: &OptimizationProblem,
+        recorded: &Solution,
+        replayed: &Solution,
+        tolerance: f64,
+    ) -> Self {
+        let objective_differs = match (recorded.optimal_value, replayed.optimal_value) {
+            (Some(before), Some(after)) => {
+                (after - before).abs() > tolerance * before.abs().max(1.0)
+            }
+            (before, after) => before.is_some() != after.is_some(),
+        };
+        let diff = recorded
+            .diff_above(replayed, DEFAULT_DIFF_THRESHOLD)
+            .ok()
+            .map(|diff| diff.with_names(problem));
+
+        Self {
+            recorded_status: recorded.status,
+            replayed_status: replayed.status,
+            recorded_objective: recorded.optimal_value,
+            replayed_objective: replayed.optimal_value,
+            objective_differs,
+            diff,
+        }
+    }
+
+    /// Replayed minus recorded objective, when both have one
+    pub fn objective_change(&self) -> Option<f64> {
+        self.recorded_objective
+            .zip(self.replayed_objective)
+            .map(|(before, after)| after - before)
+    }
+
+    /// Same status and objective; the values may still differ between optima
+    pub fn reproduces(&self) -> bool {
+        self.recorded_status == self.replayed_status && !self.objective_differs
+    }
+}
@@ -0,0 +1,304 @@
+// Robust optimization: Bertsimas-Sim counterpart for interval coefficient uncertainty
+// Each uncertain row is protected against up to a budget of its coefficients taking
+// their worst value at once, which stays linear and keeps integrality as it was
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{Constraint, OptimizationProblem, Solution, Variable};
+use super::solver_service::{Result, SolverError};
+use super::sparse::SparseVector;
+use super::value_objects::ConstraintType;
+
+/// Intervals some constraint coefficients may fall in, and how many of each row's
+/// coefficients may deviate at once
+///
+/// A budget of 0 ignores the uncertainty; one at least the number of uncertain
+/// coefficients in the row protects against all of them (Soyster). Fractional
+/// budgets let one coefficient deviate partially.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntervalUncertainty {
+    /// `(row, column, center, deviation)`; no center keeps the problem's coefficient
+    coefficients: Vec<(usize, usize, Option<f64>, f64)>,
+    budget: f64,
+    row_budgets: Vec<(usize, f64)>,
+}
+
+impl IntervalUncertainty {
+    /// Uncertainty with `budget` as every row's budget
+    pub fn new(budget: f64) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    /// The coefficient of `column` in `row` may be off by up to `deviation` either way
+    pub fn with_deviation(mut self, row: usize, column: usize, deviation: f64) -> Self {
+        self.coefficients.push((row, column, None, deviation));
+        self
+    }
+
+    /// The coefficient of `column` in `row` lies in `[low, high]`; the counterpart
+    /// uses the midpoint as its nominal value
+    pub fn with_interval(mut self, row: usize, column: usize, low: f64, high: f64) -> Self {
+        self.coefficients
+            .push((row, column, Some((low + high) / 2.0), (high - low) / 2.0));
+        self
+    }
+
+    /// Budget of `row`, instead of the default one
+    pub fn with_row_budget(mut self, row: usize, budget: f64) -> Self {
+        self.row_budgets.push((row, budget));
+        self
+    }
+
+    /// Checked coefficients grouped by row, in column order; later entries for a
+    /// coefficient win
+    fn rows(&self, problem: &OptimizationProblem) -> Result<BTreeMap<usize, Vec<Entry>>> {
+        let mut errors = Vec::new();
+        let rows = problem.constraints.len();
+        let columns = problem.num_variables();
+        let mut by_row: BTreeMap<usize, BTreeMap<usize, (Option<f64>, f64)>> = BTreeMap::new();
+        for &(row, column, center, deviation) in &self.coefficients {
+            if row >= rows || column >= columns {
+                errors.push(format!(
+                    "coefficient ({}, {}) is outside the {}x{} problem",
+                    row, column, rows, columns
+                ));
+            } else if !deviation.is_finite()
+                || deviation < 0.0
+                || center.is_some_and(|c| !c.is_finite())
+            {
+                errors.push(format!(
+                    "coefficient ({}, {}) has an invalid interval",
+                    row, column
+                ));
+            } else {
+                by_row
+                    .entry(row)
+                    .or_default()
+                    .insert(column, (center, deviation));
+            }
+        }
+        for &(row, budget) in &self.row_budgets {
+            if row >= rows {
+                errors.push(format!("budget for row {} of {}", row, rows));
+            } else if !budget.is_finite() || budget < 0.0 {
+                errors.push(format!("row {} has budget {}", row, budget));
+            }
+        }
+        if !self.budget.is_finite() || self.budget < 0.0 {
+            errors.push(format!("budget {}", self.budget));
+        }
+        if !errors.is_empty() {
+            return Err(SolverError::InvalidProblem(format!(
+                "Invalid uncertainty: {}",
+                errors.join("; ")
+            )));
+        }
+
+        let mut result = BTreeMap::new();
+        for (row, entries) in by_row {
+            let entries: Vec<Entry> = entries
+                .into_iter()
+                .map(|(column, (center, deviation))| Entry {
+                    column,
+                    center,
+                    deviation,
+                })
+                .collect();
+            let uncertain = entries.iter().any(|entry| entry.deviation > 0.0);
+            if uncertain && problem.constraints[row].constraint_type == ConstraintType::Equal {
+                return Err(SolverError::InvalidProblem(format!(
+                    "Row {} is an equality with uncertain coefficients, which no point can \
+                     satisfy robustly; write it as two inequalities or fix the coefficients",
+                    row
+                )));
+            }
+            result.insert(row, entries);
+        }
+        Ok(result)
+    }
+
+    fn budget_of(&self, row: usize) -> f64 {
+        self.row_budgets
+            .iter()
+            .rev()
+            .find(|&&(r, _)| r == row)
+            .map_or(self.budget, |&(_, budget)| budget)
+    }
+}
+
+/// One uncertain coefficient of a row
+struct Entry {
+    column: usize,
+    center: Option<f64>,
+    deviation: f64,
+}
+
+/// Robust counterpart of a problem, with what it takes to read its solutions
+///
+/// The original variables and constraints keep their positions; the protection
+/// variables and rows come after them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustCounterpart {
+    pub problem: OptimizationProblem,
+    /// Number of variables in the original problem
+    pub num_variables: usize,
+    /// Number of constraints in the original problem
+    pub num_constraints: usize,
+}
+
+impl RobustCounterpart {
+    /// Drop the protection variables and rows from a solution of the counterpart,
+    /// leaving one for the original problem
+    pub fn restore(&self, solution: &mut Solution) {
+        solution.variable_values.truncate(self.num_variables);
+        solution.dual_values.truncate(self.num_constraints);
+    }
+}
+
+impl OptimizationProblem {
+    /// Bertsimas-Sim robust counterpart under `uncertainty`
+    ///
+    /// Row `a x <= b` with uncertain coefficients `J` and budget `G` becomes
+    /// `a x + G z + sum(p_j) <= b` with `z + p_j >= d_j |x_j|` for `j` in `J`, and
+    /// `>=` rows likewise with the signs turned. `|x_j|` is `x_j` itself for
+    /// variables that can't go negative, and an extra variable otherwise. Fails
+    /// for equality rows with uncertain coefficients.
+    pub fn robust_counterpart(
+        &self,
+        uncertainty: &IntervalUncertainty,
+    ) -> Result<RobustCounterpart> {
+        let rows = uncertainty.rows(self)?;
+        let num_vars = self.num_variables();
+        let bounds = |j: usize| {
+            self.variables
+                .get(j)
+                .map_or((0.0, None), |var| (var.lower_bound, var.upper_bound))
+        };
+
+        let mut robust = self.clone();
+        if robust.variables.is_empty() {
+            robust.variables = (0..num_vars)
+                .map(|j| Variable::continuous(self.variable_name(j)))
+                .collect();
+        }
+
+        // `|x_j|` as (column, sign) terms, with an extra variable for columns that
+        // can take either sign
+        let mut magnitude: BTreeMap<usize, Vec<(usize, f64)>> = BTreeMap::new();
+        let mut new_rows: Vec<Constraint> = Vec::new();
+        let mut new_columns: Vec<Variable> = Vec::new();
+
+        for (&row, entries) in &rows {
+            let constraint = &mut robust.constraints[row];
+            for entry in entries {
+                if let Some(center) = entry.center {
+                    constraint.coefficients.set(entry.column, center);
+                }
+            }
+            let uncertain: Vec<&Entry> = entries.iter().filter(|e| e.deviation > 0.0).collect();
+            let budget = uncertainty.budget_of(row).min(uncertain.len() as f64);
+            if budget == 0.0 {
+                continue;
+            }
+            let sign = match constraint.constraint_type {
+                ConstraintType::GreaterThanOrEqual => -1.0,
+                _ => 1.0,
+            };
+
+            let z = num_vars + new_columns.len();
+            new_columns.push(Variable::continuous(format!("robust_z_{}", row)));
+            let mut protection = vec![(z, sign * budget)];
+            for entry in uncertain {
+                let j = entry.column;
+                let terms = magnitude.entry(j).or_insert_with(|| match bounds(j) {
+                    (lower, _) if lower >= 0.0 => vec![(j, 1.0)],
+                    (_, Some(upper)) if upper <= 0.0 => vec![(j, -1.0)],
+                    _ => {
+                        let y = num_vars + new_columns.len();
+                        new_columns.push(Variable::continuous(format!("robust_abs_{}", j)));
+                        for side in [1.0, -1.0] {
+                            new_rows.push(
+                                Constraint::new(
+                                    ConstraintType::GreaterThanOrEqual,
+                                    row_of(&[(y, 1.0), (j, -side)]),
+                                    0.0,
+                                )
+                                .with_name(format!(
+                                    "robust_abs_{}_{}",
+                                    j,
+                                    if side > 0.0 { "pos" } else { "neg" }
+                                )),
+                            );
+                        }
+                        vec![(y, 1.0)]
+                    }
+                });
+
+                let p = num_vars + new_columns.len();
+                new_columns.push(Variable::continuous(format!("robust_p_{}_{}", row, j)));
+                protection.push((p, sign));
+                let mut terms: Vec<(usize, f64)> = terms
+                    .iter()
+                    .map(|&(column, s)| (column, -s * entry.deviation))
+                    .collect();
+                terms.extend([(z, 1.0), (p, 1.0)]);
+                new_rows.push(
+                    Constraint::new(ConstraintType::GreaterThanOrEqual, row_of(&terms), 0.0)
+                        .with_name(format!("robust_{}_{}", row, j)),
+                );
+            }
+            let coefficients = &mut robust.constraints[row].coefficients;
+            coefficients.resize(num_vars + new_columns.len());
+            for (column, value) in protection {
+                coefficients.set(column, coefficients.get(column) + value);
+            }
+        }
+
+        // Widen every row to the final number of columns
+        let width = num_vars + new_columns.len();
+        let names: Vec<String> = new_columns.iter().map(|var| var.name.clone()).collect();
+        robust.constraints.extend(new_rows);
+        for constraint in &mut robust.constraints {
+            constraint.coefficients.resize(width);
+            if !constraint.variable_names.is_empty() {
+                constraint.variable_names.resize(num_vars, String::new());
+                constraint.variable_names.extend(names.iter().cloned());
+            }
+        }
+        robust.objective.coefficients.resize(width, 0.0);
+        if !robust.objective.variable_names.is_empty() {
+            robust
+                .objective
+                .variable_names
+                .resize(num_vars, String::new());
+            robust.objective.variable_names.extend(names);
+        }
+        robust.variables.extend(new_columns);
+        robust.name = format!("{} (robust)", self.name);
+
+        Ok(RobustCounterpart {
+            problem: robust,
+            num_variables: num_vars,
+            num_constraints: self.constraints.len(),
+        })
+    }
+}
+
+/// Row holding `terms`, as wide as its last column; rows are widened at the end
+fn row_of(terms: &[(usize, f64)]) -> SparseVector {
+    let width = terms
+        .iter()
+        .map(|&(column, _)| column + 1)
+        .max()
+        .unwrap_or(0);
+    let mut row = SparseVector::new(width);
+    for &(column, value) in terms {
+        row.set(column, row.get(column) + value);
+    }
+    row
+}
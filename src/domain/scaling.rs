@@ -0,0 +1,142 @@
+// Scaling: Geometric-mean row and column scaling of the constraint matrix
+// Brings coefficient magnitudes towards 1 before a solve and maps the solution back,
+// so solver tolerances mean the same thing in every row
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{OptimizationProblem, Solution};
+
+/// Row/column passes; more rarely helps once the factors settle
+pub const DEFAULT_SCALING_PASSES: usize = 4;
+
+/// Row and column factors, applied as `A' = R A C` with `x = C x'`
+///
+/// Factors are powers of two, so scaling and unscaling lose no precision.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scaling {
+    pub row_factors: Vec<f64>,
+    pub column_factors: Vec<f64>,
+}
+
+impl Scaling {
+    /// Geometric-mean scaling of `problem`'s constraint matrix
+    ///
+    /// Each pass scales every row, then every column, by one over the geometric
+    /// mean of its smallest and largest magnitude. Integer and binary columns keep
+    /// a factor of 1 so their values stay integral.
+    pub fn geometric(problem: &OptimizationProblem, passes: usize) -> Self {
+        let num_vars = problem.num_variables();
+        let mut scaling = Self {
+            row_factors: vec![1.0; problem.constraints.len()],
+            column_factors: vec![1.0; num_vars],
+        };
+        let fixed: Vec<bool> = (0..num_vars)
+            .map(|j| problem.variables.get(j).is_some_and(|v| v.is_integer()))
+            .collect();
+
+        for _ in 0..passes {
+            for (i, constraint) in problem.constraints.iter().enumerate() {
+                let magnitudes = constraint
+                    .coefficients
+                    .iter()
+                    .map(|(j, a)| a * scaling.column_factors[j]);
+                scaling.row_factors[i] = balancing_factor(magnitudes);
+            }
+            // One pass over the non-zeros gathers every column's range
+            let mut ranges = vec![EMPTY_RANGE; num_vars];
+            for (constraint, r) in problem.constraints.iter().zip(&scaling.row_factors) {
+                for (j, a) in constraint.coefficients.iter() {
+                    ranges[j] = widen(ranges[j], a * r);
+                }
+            }
+            for j in (0..num_vars).filter(|&j| !fixed[j]) {
+                scaling.column_factors[j] = range_factor(ranges[j]);
+            }
+        }
+
+        scaling
+    }
+
+    /// Whether every factor is 1, i.e. scaling would change nothing
+    pub fn is_identity(&self) -> bool {
+        self.row_factors
+            .iter()
+            .chain(&self.column_factors)
+            .all(|&f| f == 1.0)
+    }
+
+    /// The scaled problem: same optimal objective value, variables `x' = x / c`
+    pub fn scale(&self, problem: &OptimizationProblem) -> OptimizationProblem {
+        let mut scaled = problem.clone();
+
+        for (coefficient, c) in scaled
+            .objective
+            .coefficients
+            .iter_mut()
+            .zip(&self.column_factors)
+        {
+            *coefficient *= c;
+        }
+        for (constraint, r) in scaled.constraints.iter_mut().zip(&self.row_factors) {
+            constraint
+                .coefficients
+                .map_values(|j, a| a * r * self.column_factors[j]);
+            constraint.bound *= r;
+        }
+        for (variable, c) in scaled.variables.iter_mut().zip(&self.column_factors) {
+            variable.lower_bound /= c;
+            variable.upper_bound = variable.upper_bound.map(|upper| upper / c);
+        }
+        if !scaled.solver_config.mip_start.is_empty() {
+            let columns = problem.columns_by_name();
+            for (name, value) in scaled.solver_config.mip_start.iter_mut() {
+                if let Some(&j) = columns.get(name) {
+                    *value /= self.column_factors[j];
+                }
+            }
+        }
+
+        scaled
+    }
+
+    /// Map a solution of the scaled problem back to the original variables and rows
+    pub fn unscale(&self, solution: &mut Solution) {
+        self.unscale_values(&mut solution.variable_values);
+        for (dual, r) in solution.dual_values.iter_mut().zip(&self.row_factors) {
+            *dual *= r;
+        }
+    }
+
+    /// Map values of the scaled variables back to the original ones
+    pub fn unscale_values(&self, values: &mut [f64]) {
+        for (value, c) in values.iter_mut().zip(&self.column_factors) {
+            *value *= c;
+        }
+    }
+}
+
+/// Power of two closest to `1 / sqrt(min * max)` over the non-zero magnitudes
+fn balancing_factor(values: impl Iterator<Item = f64>) -> f64 {
+    range_factor(values.fold(EMPTY_RANGE, widen))
+}
+
+/// `(min, max)` magnitude of no values
+const EMPTY_RANGE: (f64, f64) = (f64::INFINITY, 0.0);
+
+/// `range` grown to take in `value`, unless it is zero or not finite
+fn widen((min, max): (f64, f64), value: f64) -> (f64, f64) {
+    let value = value.abs();
+    if value > 0.0 && value.is_finite() {
+        (min.min(value), max.max(value))
+    } else {
+        (min, max)
+    }
+}
+
+/// Power of two bringing the geometric mean of `(min, max)` nearest to 1
+fn range_factor((min, max): (f64, f64)) -> f64 {
+    if max == 0.0 {
+        return 1.0;
+    }
+    (-(min * max).sqrt().log2()).round().exp2()
+}
@@ -0,0 +1,192 @@
+// Scenarios: Variations of one base problem for batch solving
+// A scenario only lists what differs from the base (right-hand sides, variable
+// bounds, objective coefficients), so a backend can patch a model it already built
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{OptimizationProblem, Variable};
+use super::solver_service::{Result, SolverError};
+use super::value_objects::VariableType;
+
+/// Changes that turn a base problem into one scenario
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// `(constraint, bound)`: new right-hand sides
+    pub rhs: Vec<(usize, f64)>,
+    /// `(variable, lower, upper)`: new bounds, no upper bound meaning unbounded
+    pub bounds: Vec<(usize, f64, Option<f64>)>,
+    /// `(variable, coefficient)`: new objective coefficients
+    pub objective: Vec<(usize, f64)>,
+}
+
+impl Scenario {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_rhs(mut self, constraint: usize, bound: f64) -> Self {
+        self.rhs.push((constraint, bound));
+        self
+    }
+
+    pub fn with_bounds(mut self, variable: usize, lower: f64, upper: Option<f64>) -> Self {
+        self.bounds.push((variable, lower, upper));
+        self
+    }
+
+    pub fn with_objective_coefficient(mut self, variable: usize, coefficient: f64) -> Self {
+        self.objective.push((variable, coefficient));
+        self
+    }
+
+    /// Bounds that hold each named variable at its value
+    ///
+    /// A value must lie within the variable's own bounds, and be whole for an
+    /// integer variable; fixing can only narrow the problem, never widen it.
+    pub fn fixing(problem: &OptimizationProblem, values: &BTreeMap<String, f64>) -> Result<Self> {
+        let columns = problem.columns_by_name();
+        let mut scenario = Self::new("fixed values");
+        let mut errors = Vec::new();
+        for (name, &value) in values {
+            let Some(&column) = columns.get(name) else {
+                errors.push(format!("'{}' is not a variable", name));
+                continue;
+            };
+            let (variable_type, lower, upper) = match problem.variables.get(column) {
+                Some(v) => (v.variable_type, v.lower_bound, v.upper_bound),
+                None => (VariableType::Continuous, 0.0, None),
+            };
+            let (lower, upper) = match variable_type {
+                VariableType::Binary => (lower.max(0.0), Some(upper.map_or(1.0, |u| u.min(1.0)))),
+                _ => (lower, upper),
+            };
+            if !value.is_finite() {
+                errors.push(format!("'{}' = {} is not a finite value", name, value));
+            } else if value < lower || upper.is_some_and(|upper| value > upper) {
+                errors.push(format!(
+                    "'{}' = {} is outside its bounds [{}, {}]",
+                    name,
+                    value,
+                    lower,
+                    upper.map_or("inf".to_string(), |u| u.to_string())
+                ));
+            } else if variable_type != VariableType::Continuous && value.fract() != 0.0 {
+                errors.push(format!(
+                    "'{}' = {} is fractional but the variable is integer",
+                    name, value
+                ));
+            } else {
+                scenario = scenario.with_bounds(column, value, Some(value));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(scenario)
+        } else {
+            Err(SolverError::InvalidProblem(format!(
+                "Cannot fix variables: {}",
+                errors.join("; ")
+            )))
+        }
+    }
+
+    /// Check that every change refers to something in `problem` and is well formed
+    pub fn check(&self, problem: &OptimizationProblem) -> Result<()> {
+        let errors = self.errors(problem);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(SolverError::InvalidProblem(format!(
+                "Scenario '{}' has invalid changes: {}",
+                self.name,
+                errors.join("; ")
+            )))
+        }
+    }
+
+    /// What is wrong with each invalid change
+    pub fn errors(&self, problem: &OptimizationProblem) -> Vec<String> {
+        let mut errors = Vec::new();
+        let rows = problem.constraints.len();
+        let columns = problem.num_variables();
+        for &(row, bound) in &self.rhs {
+            if row >= rows {
+                errors.push(format!(
+                    "right-hand side of constraint {} (problem has {})",
+                    row, rows
+                ));
+            } else if !bound.is_finite() {
+                errors.push(format!(
+                    "right-hand side of constraint {} is {}",
+                    row, bound
+                ));
+            }
+        }
+        for &(column, lower, upper) in &self.bounds {
+            if column >= columns {
+                errors.push(format!(
+                    "bounds of variable {} (problem has {})",
+                    column, columns
+                ));
+            } else if lower.is_nan() || upper.is_some_and(|upper| upper.is_nan() || upper < lower) {
+                errors.push(format!(
+                    "bounds of variable {} are [{}, {:?}]",
+                    column, lower, upper
+                ));
+            }
+        }
+        for &(column, coefficient) in &self.objective {
+            if column >= columns {
+                errors.push(format!(
+                    "objective coefficient of variable {} (problem has {})",
+                    column, columns
+                ));
+            } else if !coefficient.is_finite() {
+                errors.push(format!(
+                    "objective coefficient of variable {} is {}",
+                    column, coefficient
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Copy of `problem` with the changes made; later changes to the same entry win
+    pub fn apply(&self, problem: &OptimizationProblem) -> Result<OptimizationProblem> {
+        let mut scenario = problem.clone();
+        self.apply_to(&mut scenario)?;
+        if !self.name.is_empty() {
+            scenario.name = format!("{} [{}]", problem.name, self.name);
+        }
+        Ok(scenario)
+    }
+
+    /// Make the changes to `problem` itself, leaving it untouched if any is invalid
+    pub fn apply_to(&self, problem: &mut OptimizationProblem) -> Result<()> {
+        self.check(problem)?;
+        for &(row, bound) in &self.rhs {
+            problem.constraints[row].bound = bound;
+        }
+        if !self.bounds.is_empty() && problem.variables.is_empty() {
+            problem.variables = (0..problem.num_variables())
+                .map(|j| Variable::continuous(problem.variable_name(j)))
+                .collect();
+        }
+        for &(column, lower, upper) in &self.bounds {
+            let variable = &mut problem.variables[column];
+            variable.lower_bound = lower;
+            variable.upper_bound = upper;
+        }
+        for &(column, coefficient) in &self.objective {
+            problem.objective.coefficients[column] = coefficient;
+        }
+        Ok(())
+    }
+}
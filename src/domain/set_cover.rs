@@ -0,0 +1,225 @@
+// Set covering and partitioning: One binary per candidate set, one row per element
+// Crew scheduling and similar models start from membership lists; this builds the
+// rows from them, optionally breaking the symmetry between identical sets
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::modeling::{dot, sum_over, LinearExpr, Model, VarRef};
+use super::models::{Solution, Variable};
+use super::variable_grid::VariableArray;
+
+/// How many chosen sets must contain each element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coverage {
+    /// At least one (set covering)
+    #[default]
+    Cover,
+    /// Exactly one (set partitioning)
+    Partition,
+}
+
+/// Handling of sets with exactly the same members
+///
+/// Identical sets make the solver explore every way of swapping one for another;
+/// both options remove that without changing the optimal cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymmetryBreaking {
+    #[default]
+    None,
+    /// Order identical sets by cost: a costlier copy is only taken with every
+    /// cheaper one
+    OrderIdentical,
+    /// Keep only the cheapest of identical sets, fixing the others at 0
+    ///
+    /// Exact for partitioning, and for covering as long as costs aren't negative.
+    DropIdentical,
+}
+
+/// Set covering or partitioning problem, described by which elements each set holds
+#[derive(Debug, Clone)]
+pub struct SetCoverSpec {
+    elements: usize,
+    sets: Vec<Vec<usize>>,
+    costs: Vec<f64>,
+    coverage: Coverage,
+    symmetry: SymmetryBreaking,
+}
+
+impl SetCoverSpec {
+    /// Cover `elements` elements with `sets`, each listing the elements it holds;
+    /// every set costs 1 until [`with_costs`](Self::with_costs)
+    pub fn new(elements: usize, sets: Vec<Vec<usize>>) -> Self {
+        let sets: Vec<Vec<usize>> = sets
+            .into_iter()
+            .map(|mut members| {
+                members.sort_unstable();
+                members.dedup();
+                members
+            })
+            .collect();
+        assert!(
+            sets.iter().flatten().all(|&e| e < elements),
+            "set member out of range for {} elements",
+            elements
+        );
+        Self {
+            elements,
+            costs: vec![1.0; sets.len()],
+            sets,
+            coverage: Coverage::Cover,
+            symmetry: SymmetryBreaking::None,
+        }
+    }
+
+    /// Same problem described the other way round: `memberships[e]` lists the
+    /// sets (out of `num_sets`) that hold element `e`
+    pub fn from_memberships(num_sets: usize, memberships: &[Vec<usize>]) -> Self {
+        let mut sets = vec![Vec::new(); num_sets];
+        for (element, holders) in memberships.iter().enumerate() {
+            for &set in holders {
+                assert!(
+                    set < num_sets,
+                    "set {} out of range for {} sets",
+                    set,
+                    num_sets
+                );
+                sets[set].push(element);
+            }
+        }
+        Self::new(memberships.len(), sets)
+    }
+
+    /// # Panics
+    ///
+    /// Panics unless there is one cost per set.
+    pub fn with_costs(mut self, costs: Vec<f64>) -> Self {
+        assert_eq!(costs.len(), self.sets.len(), "one cost per set");
+        self.costs = costs;
+        self
+    }
+
+    pub fn with_coverage(mut self, coverage: Coverage) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    pub fn with_symmetry_breaking(mut self, symmetry: SymmetryBreaking) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    pub fn num_sets(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.elements
+    }
+
+    /// Groups of two or more identical sets, cheapest first
+    fn identical_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: HashMap<&[usize], Vec<usize>> = HashMap::new();
+        for (k, members) in self.sets.iter().enumerate() {
+            groups.entry(members).or_default().push(k);
+        }
+        let mut groups: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        for group in &mut groups {
+            group.sort_by(|&a, &b| self.costs[a].total_cmp(&self.costs[b]).then(a.cmp(&b)));
+        }
+        groups.sort_unstable();
+        groups
+    }
+}
+
+/// Variables and rows [`Model::add_set_cover`] created
+#[derive(Debug, Clone)]
+pub struct SetCover {
+    sets: VariableArray,
+    element_rows: Range<usize>,
+    cost: LinearExpr,
+}
+
+impl SetCover {
+    /// One binary per set, in the order of the spec
+    pub fn sets(&self) -> &VariableArray {
+        &self.sets
+    }
+
+    /// Coverage row of each element, in element order
+    pub fn element_rows(&self) -> Range<usize> {
+        self.element_rows.clone()
+    }
+
+    /// Total cost of the chosen sets, to pass to [`Model::minimize`]
+    pub fn cost(&self) -> LinearExpr {
+        self.cost.clone()
+    }
+
+    /// Indices of the sets a solution takes
+    pub fn chosen(&self, solution: &Solution) -> Vec<usize> {
+        self.sets
+            .iter()
+            .enumerate()
+            .filter(|(_, var)| var.value(solution).is_some_and(|v| v > 0.5))
+            .map(|(k, _)| k)
+            .collect()
+    }
+}
+
+impl Model {
+    /// Add a binary `name_k` per set and a row `name_cover_e` per element
+    ///
+    /// The objective is left alone; minimize [`SetCover::cost`], possibly plus
+    /// other terms. Symmetry-breaking rows are named `name_order_i`.
+    pub fn add_set_cover(&mut self, name: &str, spec: &SetCoverSpec) -> SetCover {
+        let sets = self.add_array(name, spec.sets.len(), Variable::binary(""));
+
+        let mut holders: Vec<Vec<VarRef>> = vec![Vec::new(); spec.elements];
+        for (k, members) in spec.sets.iter().enumerate() {
+            for &e in members {
+                holders[e].push(sets[k]);
+            }
+        }
+        let element_rows = self.add_all(holders.into_iter().enumerate().map(|(e, vars)| {
+            let covered = sum_over(vars);
+            match spec.coverage {
+                Coverage::Cover => covered.geq(1),
+                Coverage::Partition => covered.equals(1),
+            }
+            .with_name(format!("{}_cover_{}", name, e))
+        }));
+
+        let mut order = 0;
+        for group in spec.identical_groups() {
+            match spec.symmetry {
+                SymmetryBreaking::None => {}
+                SymmetryBreaking::OrderIdentical => {
+                    for pair in group.windows(2) {
+                        self.add(
+                            sets[pair[1]]
+                                .leq(sets[pair[0]])
+                                .with_name(format!("{}_order_{}", name, order)),
+                        );
+                        order += 1;
+                    }
+                }
+                SymmetryBreaking::DropIdentical => {
+                    for &k in &group[1..] {
+                        self.set_bounds(sets[k], 0.0, Some(0.0));
+                    }
+                }
+            }
+        }
+
+        let cost = dot(&sets, &spec.costs);
+        SetCover {
+            sets,
+            element_rows,
+            cost,
+        }
+    }
+}
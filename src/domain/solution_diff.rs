@@ -0,0 +1,154 @@
+// Solution diff: What changed between two solutions of the same model
+// Variables that moved by more than a threshold, grouped by the prefix of their
+// names so a plan change reads per family of decisions rather than per index
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::models::{OptimizationProblem, Solution};
+use super::solver_service::{Result, SolverError};
+
+/// Change below which a variable counts as unchanged
+pub const DEFAULT_DIFF_THRESHOLD: f64 = 1e-6;
+
+/// Characters ending a name's prefix: `ship_3_7` and `ship[3]` are both `ship`
+pub const PREFIX_SEPARATORS: &[char] = &['_', '[', '.'];
+
+/// A variable whose value moved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableChange {
+    pub index: usize,
+    pub name: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl VariableChange {
+    /// `after - before`
+    pub fn change(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+/// Changed variables sharing a name prefix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeGroup {
+    pub prefix: String,
+    /// In index order
+    pub changes: Vec<VariableChange>,
+}
+
+impl ChangeGroup {
+    /// Sum of the changes, which cancel out when a quantity only moved around
+    pub fn net_change(&self) -> f64 {
+        self.changes.iter().map(VariableChange::change).sum()
+    }
+
+    /// Sum of the changes' sizes
+    pub fn movement(&self) -> f64 {
+        self.changes
+            .iter()
+            .map(|change| change.change().abs())
+            .sum()
+    }
+}
+
+/// Differences from one solution to another
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SolutionDiff {
+    /// Variables that moved by more than the threshold, in index order
+    pub changes: Vec<VariableChange>,
+    /// After minus before, when both solutions have an objective value
+    pub objective_change: Option<f64>,
+    pub threshold: f64,
+}
+
+impl SolutionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Name the changes after `problem`'s variables instead of `x0`, `x1`, ...
+    pub fn with_names(mut self, problem: &OptimizationProblem) -> Self {
+        for change in &mut self.changes {
+            change.name = problem.variable_name(change.index);
+        }
+        self
+    }
+
+    /// Changes grouped by name prefix, the group that moved most first
+    pub fn groups(&self) -> Vec<ChangeGroup> {
+        let mut by_prefix: BTreeMap<&str, Vec<VariableChange>> = BTreeMap::new();
+        for change in &self.changes {
+            by_prefix
+                .entry(name_prefix(&change.name))
+                .or_default()
+                .push(change.clone());
+        }
+        let mut groups: Vec<ChangeGroup> = by_prefix
+            .into_iter()
+            .map(|(prefix, changes)| ChangeGroup {
+                prefix: prefix.to_string(),
+                changes,
+            })
+            .collect();
+        groups.sort_by(|a, b| b.movement().total_cmp(&a.movement()));
+        groups
+    }
+}
+
+/// Part of `name` before its first [`PREFIX_SEPARATORS`] character, or all of it
+pub fn name_prefix(name: &str) -> &str {
+    name.split(PREFIX_SEPARATORS).next().unwrap_or(name)
+}
+
+impl Solution {
+    /// Variables that moved from this solution to `other` by more than
+    /// [`DEFAULT_DIFF_THRESHOLD`]
+    ///
+    /// Fails if the solutions don't have the same number of values.
+    pub fn diff(&self, other: &Solution) -> Result<SolutionDiff> {
+        self.diff_above(other, DEFAULT_DIFF_THRESHOLD)
+    }
+
+    /// Variables that moved from this solution to `other` by more than `threshold`
+    pub fn diff_above(&self, other: &Solution, threshold: f64) -> Result<SolutionDiff> {
+        if self.variable_values.len() != other.variable_values.len() {
+            return Err(SolverError::InvalidProblem(format!(
+                "Cannot compare a solution of {} variables with one of {}",
+                self.variable_values.len(),
+                other.variable_values.len()
+            )));
+        }
+        if threshold.is_nan() || threshold < 0.0 {
+            return Err(SolverError::InvalidProblem(format!(
+                "Diff threshold {} must be at least 0",
+                threshold
+            )));
+        }
+
+        let changes = self
+            .variable_values
+            .iter()
+            .zip(&other.variable_values)
+            .enumerate()
+            .filter(|(_, (before, after))| (*after - *before).abs() > threshold)
+            .map(|(index, (&before, &after))| VariableChange {
+                index,
+                name: format!("x{}", index),
+                before,
+                after,
+            })
+            .collect();
+        let objective_change = self
+            .optimal_value
+            .zip(other.optimal_value)
+            .map(|(before, after)| after - before);
+        Ok(SolutionDiff {
+            changes,
+            objective_change,
+            threshold,
+        })
+    }
+}
@@ -1,10 +1,43 @@
 // Domain service interface for solving optimization problems
 // Defines the contract that any solver implementation must follow (Dependency Inversion Principle)
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::model_session::ModelSession;
+use super::model_statistics::MagnitudeRange;
 use super::models::{OptimizationProblem, Solution};
+use super::scenarios::Scenario;
+use super::value_objects::{ConstraintType, OptionKind, VariableType};
+
+/// Coefficient magnitude ratio beyond which solvers tend to lose precision
+pub const MAX_COEFFICIENT_RATIO: f64 = 1e9;
+
+/// Basis condition estimate beyond which round-off in double precision can exceed
+/// the default feasibility tolerance
+pub const ILL_CONDITIONED_BASIS: f64 = 1e10;
+
+/// An option a backend accepts through `SolverConfig.backend_options`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolverOption {
+    /// Key to set it under, including the backend prefix (`highs.presolve`)
+    pub name: String,
+    pub kind: OptionKind,
+    /// Value the backend uses when the option is not set
+    pub default: String,
+    /// Inclusive bounds of numeric options, None where unbounded
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Accepted values of string options that take one of a fixed set
+    pub choices: Vec<String>,
+    pub description: String,
+}
 
 /// Error types for the solver service
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, thiserror::Error, Serialize, Deserialize)]
 pub enum SolverError {
     #[error("Invalid problem: {0}")]
     InvalidProblem(String),
@@ -14,10 +47,130 @@ pub enum SolverError {
 
     #[error("Solver execution failed: {0}")]
     ExecutionFailed(String),
+
+    /// A time limit ran out before the solver had anything to return
+    #[error("Solve timed out: {0}")]
+    Timeout(String),
+
+    /// The solver gave up on ill-conditioned data; rescaling the model may help
+    #[error("Numerical difficulties: {0}")]
+    NumericalDifficulty(String),
+
+    #[error("Memory limit exceeded: {0}")]
+    MemoryLimit(String),
+
+    /// Stopped through its [`CancellationToken`]
+    #[error("Solve was cancelled")]
+    Cancelled,
+
+    /// The problem uses something the chosen solver can't handle
+    #[error("Unsupported feature: {feature}")]
+    UnsupportedFeature { feature: String },
+}
+
+impl SolverError {
+    /// Error for a MIP handed to solvers that only solve continuous problems
+    pub fn integers_unsupported() -> Self {
+        SolverError::UnsupportedFeature {
+            feature: "integer variables".to_string(),
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, SolverError>;
 
+/// Shared flag asking a running solve to stop
+///
+/// Clones share the flag, so one can be handed to the solver while another is
+/// kept to cancel it from a different thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    parent: Option<CancellationToken>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token that is cancelled along with this one, but can also be cancelled alone
+    pub fn child_token(&self) -> Self {
+        Self {
+            state: Arc::new(TokenState {
+                cancelled: AtomicBool::new(false),
+                parent: Some(self.clone()),
+            }),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+            || self
+                .state
+                .parent
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Guard that cancels the token when dropped, e.g. with an abandoned request
+    pub fn cancel_on_drop(self) -> CancelOnDrop {
+        CancelOnDrop(self)
+    }
+}
+
+/// Cancels its token when dropped
+#[derive(Debug)]
+pub struct CancelOnDrop(CancellationToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Receiver for progress events while a solve runs
+///
+/// Every method does nothing by default. Events arrive on the solving thread,
+/// possibly many per second, so implementations should be cheap and never block.
+pub trait SolveObserver: Send + Sync {
+    /// A new best feasible solution, in the caller's variables
+    fn on_incumbent(&self, _objective: f64, _values: &[f64]) {}
+
+    /// The proven bound on the optimal objective moved
+    fn on_bound_update(&self, _bound: f64) {}
+
+    /// Branch and bound has explored `nodes` nodes so far
+    fn on_node(&self, _nodes: u64) {}
+
+    /// One line of the backend's own log, without its line ending
+    fn on_log(&self, _line: &str) {}
+}
+
+/// Observer that ignores every event
+pub struct NullObserver;
+
+impl SolveObserver for NullObserver {}
+
+/// Report a finished solve's solution and bound, for solvers without live progress
+pub fn report_solution(observer: &dyn SolveObserver, solution: &Solution) {
+    if let (true, Some(objective)) = (solution.is_feasible(), solution.optimal_value) {
+        observer.on_incumbent(objective, &solution.variable_values);
+    }
+    if let Some(bound) = solution.best_bound {
+        observer.on_bound_update(bound);
+    }
+}
+
 /// Domain service interface for optimization solvers
 ///
 /// This trait defines the contract that all solver implementations must follow.
@@ -26,60 +179,508 @@ pub trait SolverService: Send + Sync {
     /// Solve an optimization problem
     fn solve(&self, problem: &OptimizationProblem) -> Result<Solution>;
 
+    /// Solve while reporting progress to `observer`
+    ///
+    /// Adapters whose backend has progress callbacks feed them through. The
+    /// default solves as usual and reports the final solution and bound only.
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        let solution = self.solve(problem)?;
+        report_solution(observer, &solution);
+        Ok(solution)
+    }
+
+    /// Solve with progress reporting, giving up once `cancel` is set
+    ///
+    /// Adapters forward the token to their backend's abort mechanism where it has
+    /// one; the default only checks it before starting. Adapters that override
+    /// this route `solve` and `solve_observed` through it with a fresh token.
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        if cancel.is_cancelled() {
+            return Err(SolverError::Cancelled);
+        }
+        self.solve_observed(problem, observer)
+    }
+
+    /// Solve `problem` once per scenario, each outcome standing for that scenario
+    ///
+    /// Adapters that can patch a built model override this to build it once; the
+    /// default applies each scenario to a copy and solves that from scratch. A
+    /// scenario that can't be applied or solved fails on its own, while
+    /// cancellation fails the whole batch.
+    fn solve_scenarios(
+        &self,
+        problem: &OptimizationProblem,
+        scenarios: &[Scenario],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Result<Solution>>> {
+        let mut outcomes = Vec::with_capacity(scenarios.len());
+        for scenario in scenarios {
+            let outcome = scenario
+                .apply(problem)
+                .and_then(|problem| self.solve_cancellable(&problem, &NullObserver, cancel));
+            if cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+
+    /// Build `problem` into a session that later updates edit in place
+    ///
+    /// None when the backend can't edit a built model; callers then fall back to a
+    /// [`RebuildingSession`](super::model_session::RebuildingSession).
+    fn open_session(
+        &self,
+        _problem: &OptimizationProblem,
+    ) -> Result<Option<Box<dyn ModelSession>>> {
+        Ok(None)
+    }
+
     /// Validate a problem without solving it
+    ///
+    /// Returns warnings about problems that can still be solved, or an error
+    /// listing everything that makes the problem unsolvable. The default runs
+    /// [`validate_problem`].
     fn validate(&self, problem: &OptimizationProblem) -> Result<Vec<String>> {
-        let mut errors = Vec::new();
+        validate_problem(problem)
+    }
 
-        // Check objective has coefficients
-        if problem.objective.coefficients.is_empty() {
-            errors.push("Objective must have at least one coefficient".to_string());
-        }
+    /// Get the name of this solver backend
+    fn name(&self) -> &str;
 
-        let num_vars = problem.num_variables();
+    /// Check if this solver supports mixed-integer programming
+    fn supports_mip(&self) -> bool;
+
+    /// Check if this solver supports continuous linear programming
+    fn supports_lp(&self) -> bool {
+        true
+    }
+
+    /// Version of the underlying solver library
+    fn version(&self) -> String {
+        "unknown".to_string()
+    }
+
+    /// Human-readable list of algorithms and features
+    fn capabilities(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Options this solver accepts in `SolverConfig.backend_options`
+    fn options(&self) -> Vec<SolverOption> {
+        Vec::new()
+    }
+}
+
+/// The structural, naming and numeric checks solvers run before solving, for
+/// callers without a solver at hand
+pub fn validate_problem(problem: &OptimizationProblem) -> Result<Vec<String>> {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
 
-        // Check variables match objective
-        if !problem.variables.is_empty() && problem.variables.len() != num_vars {
+    // Check objective has coefficients
+    if problem.objective.coefficients.is_empty() {
+        errors.push("Objective must have at least one coefficient".to_string());
+    }
+
+    let num_vars = problem.num_variables();
+
+    // Check variables match objective
+    if !problem.variables.is_empty() && problem.variables.len() != num_vars {
+        errors.push(format!(
+            "Number of variables ({}) doesn't match objective coefficients ({})",
+            problem.variables.len(),
+            num_vars
+        ));
+    }
+
+    // Check constraints
+    for (i, constraint) in problem.constraints.iter().enumerate() {
+        if constraint.num_variables() != num_vars {
             errors.push(format!(
-                "Number of variables ({}) doesn't match objective coefficients ({})",
-                problem.variables.len(),
+                "Constraint {} has {} coefficients but problem has {} variables",
+                i,
+                constraint.num_variables(),
                 num_vars
             ));
         }
+    }
 
-        // Check constraints
-        for (i, constraint) in problem.constraints.iter().enumerate() {
-            if constraint.num_variables() != num_vars {
+    // Check variable bounds
+    for (i, var) in problem.variables.iter().enumerate() {
+        if let Some(upper) = var.upper_bound {
+            if var.lower_bound > upper {
                 errors.push(format!(
-                    "Constraint {} has {} coefficients but problem has {} variables",
-                    i,
-                    constraint.num_variables(),
-                    num_vars
+                    "Variable {} '{}' has lower bound ({}) > upper bound ({})",
+                    i, var.name, var.lower_bound, upper
                 ));
             }
         }
+    }
 
-        // Check variable bounds
-        for (i, var) in problem.variables.iter().enumerate() {
-            if let Some(upper) = var.upper_bound {
-                if var.lower_bound > upper {
-                    errors.push(format!(
-                        "Variable {} '{}' has lower bound ({}) > upper bound ({})",
-                        i, var.name, var.lower_bound, upper
-                    ));
-                }
+    check_names(problem, &mut errors);
+    check_numerics(problem, &mut errors, &mut warnings);
+    check_integer_bounds(problem, &mut warnings);
+    check_mip_start(problem, &mut errors, &mut warnings);
+    for key in problem.solver_config.backend_options.keys() {
+        if !key.starts_with("highs.") && !key.starts_with("cbc.") {
+            warnings.push(format!(
+                "Backend option '{}' is for no backend (keys start with 'highs.' or 'cbc.'); it is ignored",
+                key
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(SolverError::InvalidProblem(errors.join("; ")))
+    }
+}
+
+/// Flag variable names that are duplicated or disagree with each other
+///
+/// Names are only labels to the solvers, so a mix-up here silently solves a
+/// different problem than the caller meant.
+fn check_names(problem: &OptimizationProblem, errors: &mut Vec<String>) {
+    let num_vars = problem.num_variables();
+
+    let declared = index_names(
+        problem.variables.iter().map(|var| var.name.as_str()),
+        "variable",
+        errors,
+    );
+
+    let objective_names = &problem.objective.variable_names;
+    if !objective_names.is_empty() && objective_names.len() != num_vars {
+        errors.push(format!(
+            "Objective has {} variable names but {} coefficients",
+            objective_names.len(),
+            num_vars
+        ));
+    }
+    for (i, (name, var)) in objective_names.iter().zip(&problem.variables).enumerate() {
+        // `ObjectiveFunction::new` fills in `x{i}` placeholders, which aren't a claim about the variable
+        let placeholder = *name == format!("x{}", i);
+        if !name.is_empty() && !var.name.is_empty() && *name != var.name && !placeholder {
+            errors.push(format!(
+                "Objective names variable {} '{}' but it is declared as '{}'",
+                i, name, var.name
+            ));
+        }
+    }
+
+    // Without declared variables, the objective's names are the only reference
+    let known = if problem.variables.is_empty() {
+        index_names(
+            objective_names.iter().map(String::as_str),
+            "objective variable",
+            errors,
+        )
+    } else {
+        declared
+    };
+
+    for (i, constraint) in problem.constraints.iter().enumerate() {
+        let names = &constraint.variable_names;
+        if names.is_empty() {
+            continue;
+        }
+        if names.len() != constraint.num_variables() {
+            errors.push(format!(
+                "Constraint {} has {} variable names but {} coefficients",
+                i,
+                names.len(),
+                constraint.num_variables()
+            ));
+        }
+        for (j, name) in names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !name.is_empty())
+        {
+            match known.get(name.as_str()) {
+                None => errors.push(format!(
+                    "Constraint {} refers to unknown variable '{}'",
+                    i, name
+                )),
+                Some(&index) if index != j => errors.push(format!(
+                    "Constraint {} names coefficient {} '{}', which is variable {}",
+                    i, j, name, index
+                )),
+                Some(_) => {}
             }
         }
+    }
+}
 
-        if errors.is_empty() {
-            Ok(Vec::new())
+/// Map each non-empty name to its first index, flagging names that repeat
+fn index_names<'a>(
+    names: impl Iterator<Item = &'a str>,
+    what: &str,
+    errors: &mut Vec<String>,
+) -> HashMap<&'a str, usize> {
+    let mut first_seen = HashMap::new();
+    for (i, name) in names.enumerate().filter(|(_, name)| !name.is_empty()) {
+        if let Some(&first) = first_seen.get(name) {
+            errors.push(format!(
+                "Variable name '{}' is used by both {} {} and {} {}",
+                name, what, first, what, i
+            ));
         } else {
-            Err(SolverError::InvalidProblem(errors.join("; ")))
+            first_seen.insert(name, i);
         }
     }
+    first_seen
+}
 
-    /// Get the name of this solver backend
-    fn name(&self) -> &str;
+/// Flag values the solvers can't handle and numerics they handle badly
+fn check_numerics(
+    problem: &OptimizationProblem,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    if let Some(j) = problem
+        .objective
+        .coefficients
+        .iter()
+        .position(|c| !c.is_finite())
+    {
+        errors.push(format!(
+            "Objective coefficient {} is {}",
+            j, problem.objective.coefficients[j]
+        ));
+    }
 
-    /// Check if this solver supports mixed-integer programming
-    fn supports_mip(&self) -> bool;
+    for (i, constraint) in problem.constraints.iter().enumerate() {
+        if let Some((j, a)) = constraint.coefficients.iter().find(|(_, a)| !a.is_finite()) {
+            errors.push(format!("Constraint {} coefficient {} is {}", i, j, a));
+        }
+        if constraint.bound.is_nan() {
+            errors.push(format!("Constraint {} bound is NaN", i));
+        } else if constraint.bound.is_infinite() {
+            warnings.push(format!(
+                "Constraint {} has an infinite bound ({})",
+                i, constraint.bound
+            ));
+        }
+
+        if constraint.num_nonzeros() == 0 {
+            let satisfied = match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => constraint.bound >= 0.0,
+                ConstraintType::Equal => constraint.bound == 0.0,
+                ConstraintType::GreaterThanOrEqual => constraint.bound <= 0.0,
+            };
+            if satisfied {
+                warnings.push(format!("Constraint {} has no non-zero coefficients", i));
+            } else {
+                errors.push(format!(
+                    "Constraint {} has no non-zero coefficients and can never hold (bound {})",
+                    i, constraint.bound
+                ));
+            }
+        }
+    }
+
+    // A free variable has lower bound -inf and no upper bound, so only NaN and
+    // bounds on the wrong side of infinity are errors
+    for (i, var) in problem.variables.iter().enumerate() {
+        if var.lower_bound.is_nan() || var.lower_bound == f64::INFINITY {
+            errors.push(format!(
+                "Variable {} '{}' has lower bound {}",
+                i, var.name, var.lower_bound
+            ));
+        }
+        if let Some(upper) = var.upper_bound {
+            if upper.is_nan() || upper == f64::NEG_INFINITY {
+                errors.push(format!(
+                    "Variable {} '{}' has upper bound {}",
+                    i, var.name, upper
+                ));
+            }
+        }
+    }
+
+    let matrix = MagnitudeRange::of(
+        problem
+            .constraints
+            .iter()
+            .flat_map(|c| c.coefficients.values().iter().copied()),
+    );
+    if matrix.ratio() > MAX_COEFFICIENT_RATIO {
+        warnings.push(format!(
+            "Constraint coefficients range from {:e} to {:e}; consider rescaling",
+            matrix.min_abs, matrix.max_abs
+        ));
+    }
+    let objective = MagnitudeRange::of(problem.objective.coefficients.iter().copied());
+    if objective.ratio() > MAX_COEFFICIENT_RATIO {
+        warnings.push(format!(
+            "Objective coefficients range from {:e} to {:e}; consider rescaling",
+            objective.min_abs, objective.max_abs
+        ));
+    }
+}
+
+/// Flag fractional bounds on integer variables, which solvers round inward
+///
+/// An upper bound of 2.5 quietly becomes 2, which has changed results for callers
+/// who expected the bound to hold as written.
+fn check_integer_bounds(problem: &OptimizationProblem, warnings: &mut Vec<String>) {
+    let fractional = |bound: f64| bound.is_finite() && bound.fract() != 0.0;
+    for (i, var) in problem.variables.iter().enumerate() {
+        if !var.is_integer() {
+            continue;
+        }
+        let kind = match var.variable_type {
+            VariableType::Binary => "Binary",
+            _ => "Integer",
+        };
+        let lower = var.lower_bound;
+        let upper = var.upper_bound.unwrap_or(f64::INFINITY);
+        if fractional(lower) {
+            warnings.push(format!(
+                "{} variable {} '{}' has fractional lower bound {}; solvers round it up to {}",
+                kind,
+                i,
+                var.name,
+                lower,
+                lower.ceil()
+            ));
+        }
+        if fractional(upper) {
+            warnings.push(format!(
+                "{} variable {} '{}' has fractional upper bound {}; solvers round it down to {}",
+                kind,
+                i,
+                var.name,
+                upper,
+                upper.floor()
+            ));
+        }
+        if (fractional(lower) || fractional(upper))
+            && lower <= upper
+            && lower.ceil() > upper.floor()
+        {
+            warnings.push(format!(
+                "{} variable {} '{}' has no integer value within [{}, {}], so the problem is infeasible",
+                kind, i, var.name, lower, upper
+            ));
+        }
+    }
+}
+
+/// Check the MIP start: values must be finite, and names not in the problem are
+/// only warned about, since a plan from an older model may mention dropped ones
+fn check_mip_start(
+    problem: &OptimizationProblem,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    if problem.solver_config.mip_start.is_empty() {
+        return;
+    }
+    let columns = problem.columns_by_name();
+    for (name, value) in &problem.solver_config.mip_start {
+        if !value.is_finite() {
+            errors.push(format!("MIP start value of '{}' is {}", name, value));
+        } else if !columns.contains_key(name) {
+            warnings.push(format!(
+                "MIP start names '{}', which is not a variable; it is ignored",
+                name
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{Constraint, ObjectiveFunction, Variable};
+    use crate::domain::value_objects::OptimizationType;
+
+    fn problem(variables: Vec<Variable>) -> OptimizationProblem {
+        OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Minimize,
+            vec![1.0; variables.len()],
+        ))
+        .with_variables(variables)
+    }
+
+    fn name_errors(problem: &OptimizationProblem) -> Vec<String> {
+        let mut errors = Vec::new();
+        check_names(problem, &mut errors);
+        errors
+    }
+
+    #[test]
+    fn duplicate_variable_names_are_flagged() {
+        let problem = problem(vec![
+            Variable::continuous("a"),
+            Variable::continuous("b"),
+            Variable::continuous("a"),
+        ]);
+
+        let errors = name_errors(&problem);
+        assert_eq!(
+            errors,
+            vec!["Variable name 'a' is used by both variable 0 and variable 2"]
+        );
+        assert!(validate_problem(&problem).is_err());
+    }
+
+    #[test]
+    fn duplicate_objective_names_are_flagged_without_declared_variables() {
+        let problem = OptimizationProblem::new(
+            ObjectiveFunction::new(OptimizationType::Minimize, vec![1.0, 1.0])
+                .with_names(vec!["a".to_string(), "a".to_string()]),
+        );
+
+        assert_eq!(
+            name_errors(&problem),
+            vec!["Variable name 'a' is used by both objective variable 0 and objective variable 1"]
+        );
+    }
+
+    #[test]
+    fn empty_names_are_not_duplicates() {
+        let problem = problem(vec![
+            Variable::continuous(""),
+            Variable::continuous(""),
+            Variable::continuous("c"),
+        ])
+        .add_constraint(
+            Constraint::new(ConstraintType::LessThanOrEqual, vec![1.0, 1.0, 1.0], 4.0)
+                .with_variable_names(vec![String::new(), String::new(), "c".to_string()]),
+        );
+
+        assert!(name_errors(&problem).is_empty());
+    }
+
+    #[test]
+    fn constraint_names_must_match_declared_variables() {
+        let problem = problem(vec![Variable::continuous("a"), Variable::continuous("b")])
+            .add_constraint(
+                Constraint::new(ConstraintType::Equal, vec![1.0, 2.0], 3.0)
+                    .with_variable_names(vec!["b".to_string(), "z".to_string()]),
+            );
+
+        assert_eq!(
+            name_errors(&problem),
+            vec![
+                "Constraint 0 names coefficient 0 'b', which is variable 1",
+                "Constraint 0 refers to unknown variable 'z'",
+            ]
+        );
+    }
 }
@@ -0,0 +1,303 @@
+// Sparse vector: Storage for one constraint row that keeps only its non-zeros
+// Rows of large models touch a handful of their variables, so memory and every
+// pass over the matrix scale with the non-zeros rather than rows times columns
+
+use std::fmt;
+
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Coefficients over `len` variables, of which only the non-zeros are stored
+///
+/// Entries are kept in increasing column order. Non-finite values count as
+/// non-zeros, so validation still sees them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SparseVector {
+    len: usize,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SparseVector {
+    /// All zeros over `len` variables
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            indices: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Vector of `(column, value)` pairs in any order
+    ///
+    /// Zeros are dropped and a column listed twice keeps its last value.
+    ///
+    /// # Panics
+    /// If a column is not below `len`.
+    pub fn from_entries(len: usize, entries: impl IntoIterator<Item = (usize, f64)>) -> Self {
+        let mut entries: Vec<(usize, f64)> = entries.into_iter().collect();
+        if !entries.is_sorted_by(|a, b| a.0 < b.0) {
+            entries.sort_by_key(|&(j, _)| j);
+            entries.reverse();
+            entries.dedup_by_key(|&mut (j, _)| j);
+            entries.reverse();
+        }
+        let mut vector = Self::new(len);
+        for (j, value) in entries {
+            assert!(j < len, "column {} out of range for {} variables", j, len);
+            if value != 0.0 {
+                vector.indices.push(j);
+                vector.values.push(value);
+            }
+        }
+        vector
+    }
+
+    /// Number of variables, zeros included
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn num_nonzeros(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Coefficient of column `j`, zero if not stored
+    pub fn get(&self, j: usize) -> f64 {
+        match self.indices.binary_search(&j) {
+            Ok(k) => self.values[k],
+            Err(_) => 0.0,
+        }
+    }
+
+    /// `(column, value)` pairs of the non-zeros, in column order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.indices
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Set column `j`, which must be below `len`
+    pub fn set(&mut self, j: usize, value: f64) {
+        assert!(
+            j < self.len,
+            "column {} out of range for {} variables",
+            j,
+            self.len
+        );
+        match (self.indices.binary_search(&j), value != 0.0) {
+            (Ok(k), true) => self.values[k] = value,
+            (Ok(k), false) => {
+                self.indices.remove(k);
+                self.values.remove(k);
+            }
+            (Err(k), true) => {
+                self.indices.insert(k, j);
+                self.values.insert(k, value);
+            }
+            (Err(_), false) => {}
+        }
+    }
+
+    /// Widen with zero columns, or narrow dropping the columns past `len`
+    pub fn resize(&mut self, len: usize) {
+        let kept = self.indices.partition_point(|&j| j < len);
+        self.indices.truncate(kept);
+        self.values.truncate(kept);
+        self.len = len;
+    }
+
+    /// Replace each non-zero `a` in column `j` with `f(j, a)`, dropping new zeros
+    pub fn map_values(&mut self, mut f: impl FnMut(usize, f64) -> f64) {
+        let mut kept = 0;
+        for k in 0..self.values.len() {
+            let value = f(self.indices[k], self.values[k]);
+            if value != 0.0 {
+                self.indices[kept] = self.indices[k];
+                self.values[kept] = value;
+                kept += 1;
+            }
+        }
+        self.indices.truncate(kept);
+        self.values.truncate(kept);
+    }
+
+    /// Vector over `len` variables holding the entries of the columns that
+    /// `new_columns` maps to `Some`, each moved to the column it maps to
+    pub fn remap(&self, new_columns: &[Option<usize>], len: usize) -> Self {
+        Self::from_entries(
+            len,
+            self.iter()
+                .filter_map(|(j, a)| Some((new_columns.get(j).copied().flatten()?, a))),
+        )
+    }
+
+    /// Dot product with a dense vector, whose missing entries count as zero
+    pub fn dot(&self, x: &[f64]) -> f64 {
+        self.iter()
+            .map(|(j, a)| a * x.get(j).copied().unwrap_or(0.0))
+            .sum()
+    }
+
+    /// All `len` coefficients, zeros included
+    pub fn to_dense(&self) -> Vec<f64> {
+        let mut dense = vec![0.0; self.len];
+        for (j, a) in self.iter() {
+            dense[j] = a;
+        }
+        dense
+    }
+}
+
+impl From<&[f64]> for SparseVector {
+    fn from(dense: &[f64]) -> Self {
+        let mut vector = Self::new(dense.len());
+        for (j, &value) in dense.iter().enumerate() {
+            if value != 0.0 {
+                vector.indices.push(j);
+                vector.values.push(value);
+            }
+        }
+        vector
+    }
+}
+
+impl From<Vec<f64>> for SparseVector {
+    fn from(dense: Vec<f64>) -> Self {
+        Self::from(dense.as_slice())
+    }
+}
+
+/// Serialized form, checked before it becomes a vector
+#[derive(Deserialize)]
+#[serde(rename = "SparseVector")]
+struct Parts {
+    len: usize,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl TryFrom<Parts> for SparseVector {
+    type Error = String;
+
+    fn try_from(parts: Parts) -> Result<Self, String> {
+        if parts.indices.len() != parts.values.len() {
+            return Err(format!(
+                "{} indices but {} values",
+                parts.indices.len(),
+                parts.values.len()
+            ));
+        }
+        if !parts.indices.is_sorted_by(|a, b| a < b) {
+            return Err("indices are not strictly increasing".to_string());
+        }
+        if let Some(&j) = parts.indices.last().filter(|&&j| j >= parts.len) {
+            return Err(format!(
+                "index {} out of range for {} variables",
+                j, parts.len
+            ));
+        }
+        Ok(Self::from_entries(
+            parts.len,
+            parts.indices.into_iter().zip(parts.values),
+        ))
+    }
+}
+
+/// Reads the `{len, indices, values}` form, and in self-describing formats such
+/// as JSON also a plain dense array, which problem files and older recordings use
+impl<'de> Deserialize<'de> for SparseVector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DenseOrSparse;
+
+        impl<'de> Visitor<'de> for DenseOrSparse {
+            type Value = SparseVector;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an array of coefficients or {len, indices, values}")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<SparseVector, A::Error> {
+                Vec::<f64>::deserialize(SeqAccessDeserializer::new(seq)).map(SparseVector::from)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<SparseVector, A::Error> {
+                let parts = Parts::deserialize(MapAccessDeserializer::new(map))?;
+                SparseVector::try_from(parts).map_err(de::Error::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(DenseOrSparse)
+        } else {
+            let parts = Parts::deserialize(deserializer)?;
+            SparseVector::try_from(parts).map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_sorted_and_zeros_dropped() {
+        let vector = SparseVector::from_entries(5, [(3, 2.0), (0, 0.0), (1, -1.0), (3, 4.0)]);
+        assert_eq!(vector.indices(), [1, 3]);
+        assert_eq!(vector.values(), [-1.0, 4.0]);
+        assert_eq!(vector.to_dense(), [0.0, -1.0, 0.0, 4.0, 0.0]);
+    }
+
+    #[test]
+    fn set_inserts_updates_and_removes() {
+        let mut vector = SparseVector::from(vec![1.0, 0.0, 3.0]);
+        vector.set(1, 2.0);
+        vector.set(2, 0.0);
+        vector.set(0, 5.0);
+        assert_eq!(vector.iter().collect::<Vec<_>>(), [(0, 5.0), (1, 2.0)]);
+        assert_eq!(vector.get(2), 0.0);
+    }
+
+    #[test]
+    fn resize_drops_columns_past_the_new_width() {
+        let mut vector = SparseVector::from(vec![1.0, 2.0, 3.0]);
+        vector.resize(5);
+        assert_eq!(vector.len(), 5);
+        vector.resize(2);
+        assert_eq!(vector.to_dense(), [1.0, 2.0]);
+    }
+
+    #[test]
+    fn json_takes_dense_arrays_and_the_sparse_form() {
+        let dense: SparseVector = serde_json::from_str("[0.0, 1.5, 0.0]").unwrap();
+        let json = serde_json::to_string(&dense).unwrap();
+        assert_eq!(json, r#"{"len":3,"indices":[1],"values":[1.5]}"#);
+        assert_eq!(serde_json::from_str::<SparseVector>(&json).unwrap(), dense);
+
+        for bad in [
+            r#"{"len":3,"indices":[1,1],"values":[1.0,2.0]}"#,
+            r#"{"len":3,"indices":[3],"values":[1.0]}"#,
+            r#"{"len":3,"indices":[1],"values":[]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<SparseVector>(bad).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// Standard form: Rewrite a problem as minimize c^T y subject to A y = b, y >= 0
+// Keeps a mapping from every standard-form column and row back to the original
+// model so results can be translated back
+
+use serde::{Deserialize, Serialize};
+
+use super::models::OptimizationProblem;
+use super::value_objects::{ConstraintType, OptimizationType};
+
+/// Where a standard-form column comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ColumnOrigin {
+    /// `x[variable] = offset + y`, for variables with a finite lower bound
+    Shifted { variable: usize, offset: f64 },
+    /// `x[variable] = offset - y`, for variables bounded only from above
+    Mirrored { variable: usize, offset: f64 },
+    /// Positive part of a free variable, `x = y+ - y-`
+    FreePositive { variable: usize },
+    /// Negative part of a free variable
+    FreeNegative { variable: usize },
+    /// Slack or surplus of standard-form row `row`
+    Slack { row: usize },
+}
+
+/// Where a standard-form row comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RowOrigin {
+    Constraint(usize),
+    /// Upper bound of a variable with both bounds finite
+    UpperBound(usize),
+}
+
+/// A problem in standard form, with its mapping back to the original model
+///
+/// Integrality is not carried over; the standard form describes the LP relaxation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandardForm {
+    /// Cost of each column, already negated for maximization problems
+    pub objective: Vec<f64>,
+    /// Constant picked up from shifting variables, in minimization terms
+    pub objective_offset: f64,
+    /// Whether the original problem maximized, so objective values flip sign on the way back
+    pub negated_objective: bool,
+    /// Dense rows of A, one entry per column
+    pub rows: Vec<Vec<f64>>,
+    /// Right-hand sides, all non-negative
+    pub rhs: Vec<f64>,
+    pub columns: Vec<ColumnOrigin>,
+    pub row_origins: Vec<RowOrigin>,
+    /// Rows multiplied by -1 to make their right-hand side non-negative
+    pub negated_rows: Vec<bool>,
+    /// Number of variables in the original problem
+    pub num_variables: usize,
+}
+
+impl StandardForm {
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Values of the original variables for a standard-form point `y`
+    pub fn original_values(&self, y: &[f64]) -> Vec<f64> {
+        let mut x = vec![0.0; self.num_variables];
+        for (origin, &value) in self.columns.iter().zip(y) {
+            match *origin {
+                ColumnOrigin::Shifted { variable, offset } => x[variable] = offset + value,
+                ColumnOrigin::Mirrored { variable, offset } => x[variable] = offset - value,
+                ColumnOrigin::FreePositive { variable } => x[variable] += value,
+                ColumnOrigin::FreeNegative { variable } => x[variable] -= value,
+                ColumnOrigin::Slack { .. } => {}
+            }
+        }
+        x
+    }
+
+    /// Original objective value for a standard-form objective value
+    pub fn original_objective(&self, standard_objective: f64) -> f64 {
+        let value = standard_objective + self.objective_offset;
+        if self.negated_objective {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl OptimizationProblem {
+    /// Rewrite the problem in standard form
+    ///
+    /// Maximization becomes minimization, inequality rows gain slack or surplus
+    /// columns, variables are shifted onto `[0, inf)` (free ones split in two) and
+    /// finite upper bounds become rows of their own.
+    pub fn to_standard_form(&self) -> StandardForm {
+        let num_vars = self.num_variables();
+        let sign = match self.objective.optimization_type {
+            OptimizationType::Minimize => 1.0,
+            OptimizationType::Maximize => -1.0,
+        };
+
+        // Columns for the original variables
+        let mut columns = Vec::with_capacity(num_vars);
+        let mut upper_rows = Vec::new();
+        for variable in 0..num_vars {
+            let (lower, upper) = self
+                .variables
+                .get(variable)
+                .map_or((0.0, None), |v| (v.lower_bound, v.upper_bound));
+
+            if lower.is_finite() {
+                columns.push(ColumnOrigin::Shifted {
+                    variable,
+                    offset: lower,
+                });
+                if let Some(upper) = upper.filter(|u| u.is_finite()) {
+                    upper_rows.push((variable, columns.len() - 1, upper - lower));
+                }
+            } else if let Some(upper) = upper.filter(|u| u.is_finite()) {
+                columns.push(ColumnOrigin::Mirrored {
+                    variable,
+                    offset: upper,
+                });
+            } else {
+                columns.push(ColumnOrigin::FreePositive { variable });
+                columns.push(ColumnOrigin::FreeNegative { variable });
+            }
+        }
+        let num_structural = columns.len();
+
+        // Rewrite a row over the original variables as a row over the columns, plus
+        // the constant the variables' offsets contribute
+        let expand = |coefficient: &dyn Fn(usize) -> f64| -> (Vec<f64>, f64) {
+            let mut row = vec![0.0; num_structural];
+            let mut constant = 0.0;
+            for (column, origin) in columns.iter().enumerate() {
+                let (variable, scale, offset) = match *origin {
+                    ColumnOrigin::Shifted { variable, offset } => (variable, 1.0, offset),
+                    ColumnOrigin::Mirrored { variable, offset } => (variable, -1.0, offset),
+                    ColumnOrigin::FreePositive { variable } => (variable, 1.0, 0.0),
+                    ColumnOrigin::FreeNegative { variable } => (variable, -1.0, 0.0),
+                    ColumnOrigin::Slack { .. } => continue,
+                };
+                let c = coefficient(variable);
+                if c == 0.0 {
+                    continue;
+                }
+                row[column] = scale * c;
+                constant += c * offset;
+            }
+            (row, constant)
+        };
+
+        let (objective, objective_constant) =
+            expand(&|j| self.objective.coefficients.get(j).copied().unwrap_or(0.0));
+        let objective: Vec<f64> = objective.into_iter().map(|c| sign * c).collect();
+
+        // Rows as (coefficients, relation, right-hand side, origin)
+        let mut rows = Vec::new();
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            let (row, constant) = expand(&|j| constraint.coefficients.get(j));
+            rows.push((
+                row,
+                constraint.constraint_type,
+                constraint.bound - constant,
+                RowOrigin::Constraint(index),
+            ));
+        }
+        for (variable, column, range) in upper_rows {
+            let mut row = vec![0.0; num_structural];
+            row[column] = 1.0;
+            rows.push((
+                row,
+                ConstraintType::LessThanOrEqual,
+                range,
+                RowOrigin::UpperBound(variable),
+            ));
+        }
+
+        // One slack or surplus column per inequality row
+        let mut form = StandardForm {
+            objective,
+            objective_offset: sign * objective_constant,
+            negated_objective: sign < 0.0,
+            rows: Vec::with_capacity(rows.len()),
+            rhs: Vec::with_capacity(rows.len()),
+            columns,
+            row_origins: Vec::with_capacity(rows.len()),
+            negated_rows: Vec::with_capacity(rows.len()),
+            num_variables: num_vars,
+        };
+        let num_slacks = rows
+            .iter()
+            .filter(|(_, relation, _, _)| *relation != ConstraintType::Equal)
+            .count();
+        let num_columns = num_structural + num_slacks;
+
+        for (row_index, (mut row, relation, rhs, origin)) in rows.into_iter().enumerate() {
+            row.resize(num_columns, 0.0);
+            let slack = match relation {
+                ConstraintType::LessThanOrEqual => Some(1.0),
+                ConstraintType::GreaterThanOrEqual => Some(-1.0),
+                ConstraintType::Equal => None,
+            };
+            if let Some(slack) = slack {
+                row[form.columns.len()] = slack;
+                form.columns.push(ColumnOrigin::Slack { row: row_index });
+            }
+
+            let negate = rhs < 0.0;
+            if negate {
+                row.iter_mut().filter(|c| **c != 0.0).for_each(|c| *c = -*c);
+            }
+            form.rows.push(row);
+            form.rhs.push(if negate { -rhs } else { rhs });
+            form.row_origins.push(origin);
+            form.negated_rows.push(negate);
+        }
+        form.objective.resize(num_columns, 0.0);
+
+        form
+    }
+}
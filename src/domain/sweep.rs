@@ -0,0 +1,143 @@
+// Parametric sweep: One coefficient or bound varied over a range
+// Each point of the range becomes a scenario of the base problem, so a sweep is
+// solved like any scenario batch and yields a trade-off curve
+
+use serde::{Deserialize, Serialize};
+
+use super::models::OptimizationProblem;
+use super::scenarios::Scenario;
+use super::solver_service::{Result, SolverError};
+
+/// Most points one sweep may have
+pub const MAX_SWEEP_STEPS: usize = 10_000;
+
+/// What a sweep varies, by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SweepTarget {
+    /// Objective coefficient of a variable
+    ObjectiveCoefficient(String),
+    /// Right-hand side of a constraint
+    Rhs(String),
+    /// Lower bound of a variable
+    LowerBound(String),
+    /// Upper bound of a variable
+    UpperBound(String),
+}
+
+impl SweepTarget {
+    /// Name of the variable or constraint
+    pub fn name(&self) -> &str {
+        match self {
+            SweepTarget::ObjectiveCoefficient(name)
+            | SweepTarget::Rhs(name)
+            | SweepTarget::LowerBound(name)
+            | SweepTarget::UpperBound(name) => name,
+        }
+    }
+}
+
+/// `steps` evenly spaced values from `from` to `to`, both included
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParametricSweep {
+    pub target: SweepTarget,
+    pub from: f64,
+    pub to: f64,
+    pub steps: usize,
+}
+
+impl ParametricSweep {
+    pub fn new(target: SweepTarget, from: f64, to: f64, steps: usize) -> Self {
+        Self {
+            target,
+            from,
+            to,
+            steps,
+        }
+    }
+
+    /// Values the target takes; just `from` for fewer than two steps
+    pub fn values(&self) -> Vec<f64> {
+        if self.steps < 2 {
+            return vec![self.from];
+        }
+        let last = (self.steps - 1) as f64;
+        (0..self.steps)
+            .map(|k| self.from + (self.to - self.from) * k as f64 / last)
+            .collect()
+    }
+
+    /// One scenario of `problem` per value, named after the value
+    ///
+    /// Fails if the target doesn't name exactly one variable or constraint, the
+    /// range isn't finite, or there are more than [`MAX_SWEEP_STEPS`] steps.
+    pub fn scenarios(&self, problem: &OptimizationProblem) -> Result<Vec<Scenario>> {
+        if !self.from.is_finite() || !self.to.is_finite() {
+            return Err(SolverError::InvalidProblem(format!(
+                "Sweep range [{}, {}] must be finite",
+                self.from, self.to
+            )));
+        }
+        if self.steps > MAX_SWEEP_STEPS {
+            return Err(SolverError::InvalidProblem(format!(
+                "Sweep has {} steps, more than the {} allowed",
+                self.steps, MAX_SWEEP_STEPS
+            )));
+        }
+
+        let name = self.target.name();
+        let index = match self.target {
+            SweepTarget::Rhs(_) => unique(
+                problem.constraints.iter().map(|row| row.name.as_str()),
+                name,
+                "constraint",
+            )?,
+            _ => unique(
+                (0..problem.num_variables()).map(|j| problem.variable_name(j)),
+                name,
+                "variable",
+            )?,
+        };
+        let (lower, upper) = problem
+            .variables
+            .get(index)
+            .map_or((0.0, None), |var| (var.lower_bound, var.upper_bound));
+
+        Ok(self
+            .values()
+            .into_iter()
+            .map(|value| {
+                let scenario = Scenario::new(format!("{}={}", name, value));
+                match self.target {
+                    SweepTarget::ObjectiveCoefficient(_) => {
+                        scenario.with_objective_coefficient(index, value)
+                    }
+                    SweepTarget::Rhs(_) => scenario.with_rhs(index, value),
+                    SweepTarget::LowerBound(_) => scenario.with_bounds(index, value, upper),
+                    SweepTarget::UpperBound(_) => scenario.with_bounds(index, lower, Some(value)),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Position of the only item called `name`
+fn unique<S: AsRef<str>>(names: impl Iterator<Item = S>, name: &str, kind: &str) -> Result<usize> {
+    let matches: Vec<usize> = names
+        .enumerate()
+        .filter(|(_, candidate)| candidate.as_ref() == name)
+        .map(|(k, _)| k)
+        .collect();
+    match matches[..] {
+        [index] => Ok(index),
+        [] => Err(SolverError::InvalidProblem(format!(
+            "No {} named '{}' to sweep",
+            kind, name
+        ))),
+        _ => Err(SolverError::InvalidProblem(format!(
+            "{} {}s are named '{}'; sweep targets must be unique",
+            matches.len(),
+            kind,
+            name
+        ))),
+    }
+}
@@ -0,0 +1,129 @@
+// Unbounded rays: Which variables run away when a problem is unbounded
+// Checks the direction a backend certifies against the model and names its
+// components, so users can find the missing bound or constraint
+
+use serde::{Deserialize, Serialize};
+
+use super::models::OptimizationProblem;
+use super::value_objects::{ConstraintType, OptimizationType};
+use super::verification::DEFAULT_FEASIBILITY_TOLERANCE;
+
+/// Steps smaller than this, relative to the largest, are dropped as round-off
+pub const RAY_TOLERANCE: f64 = 1e-9;
+
+/// Number of components named in a solution message
+const DESCRIBED_COMPONENTS: usize = 5;
+
+/// One variable's step along an unbounded ray
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RayComponent {
+    pub index: usize,
+    pub name: String,
+    pub direction: f64,
+}
+
+/// Direction along which every constraint stays satisfied while the objective
+/// improves without limit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnboundedRay {
+    /// Step per variable, in model order, scaled so the largest is ±1
+    pub direction: Vec<f64>,
+    /// Variables with a non-zero step, largest first
+    pub components: Vec<RayComponent>,
+    /// Objective change per unit step: positive when maximizing, negative when
+    /// minimizing
+    pub objective_rate: f64,
+}
+
+impl UnboundedRay {
+    /// Ray of `problem` along `direction`, or None when it isn't one: a step along
+    /// it would leave some constraint or bound, or not improve the objective
+    pub fn new(problem: &OptimizationProblem, direction: &[f64]) -> Option<Self> {
+        if direction.len() != problem.num_variables() {
+            return None;
+        }
+        let scale = direction.iter().fold(0.0_f64, |max, d| max.max(d.abs()));
+        if scale == 0.0 || !scale.is_finite() {
+            return None;
+        }
+        let direction: Vec<f64> = direction
+            .iter()
+            .map(|d| d / scale)
+            .map(|d| if d.abs() < RAY_TOLERANCE { 0.0 } else { d })
+            .collect();
+
+        let stays_feasible = problem.constraints.iter().all(|constraint| {
+            let (change, size) = rate(constraint.coefficients.iter(), &direction);
+            let tolerance = DEFAULT_FEASIBILITY_TOLERANCE * size.max(1.0);
+            match constraint.constraint_type {
+                ConstraintType::LessThanOrEqual => change <= tolerance,
+                ConstraintType::GreaterThanOrEqual => change >= -tolerance,
+                ConstraintType::Equal => change.abs() <= tolerance,
+            }
+        });
+        let within_bounds = problem.variables.iter().zip(&direction).all(|(var, &d)| {
+            let bounded_below = var.lower_bound > f64::NEG_INFINITY;
+            let bounded_above = var.upper_bound.is_some_and(|upper| upper < f64::INFINITY);
+            (d >= -DEFAULT_FEASIBILITY_TOLERANCE || !bounded_below)
+                && (d <= DEFAULT_FEASIBILITY_TOLERANCE || !bounded_above)
+        });
+        let objective_terms = problem.objective.coefficients.iter().copied().enumerate();
+        let (objective_rate, _) = rate(objective_terms, &direction);
+        let improves = match problem.objective.optimization_type {
+            OptimizationType::Maximize => objective_rate > DEFAULT_FEASIBILITY_TOLERANCE,
+            OptimizationType::Minimize => objective_rate < -DEFAULT_FEASIBILITY_TOLERANCE,
+        };
+        if !(stays_feasible && within_bounds && improves) {
+            return None;
+        }
+
+        let mut components: Vec<RayComponent> = direction
+            .iter()
+            .enumerate()
+            .filter(|(_, &d)| d != 0.0)
+            .map(|(index, &direction)| RayComponent {
+                index,
+                name: problem.variable_name(index),
+                direction,
+            })
+            .collect();
+        components.sort_by(|a, b| b.direction.abs().total_cmp(&a.direction.abs()));
+
+        Some(Self {
+            direction,
+            components,
+            objective_rate,
+        })
+    }
+
+    /// The largest components, as in "x increasing, y decreasing (+3 more)"
+    pub fn describe(&self) -> String {
+        let described: Vec<String> = self
+            .components
+            .iter()
+            .take(DESCRIBED_COMPONENTS)
+            .map(|component| {
+                let trend = if component.direction > 0.0 {
+                    "increasing"
+                } else {
+                    "decreasing"
+                };
+                format!("{} {}", component.name, trend)
+            })
+            .collect();
+        match self.components.len().checked_sub(DESCRIBED_COMPONENTS) {
+            Some(more) if more > 0 => format!("{} (+{} more)", described.join(", "), more),
+            _ => described.join(", "),
+        }
+    }
+}
+
+/// How fast the `(column, coefficient)` terms change along `direction`, and the
+/// size of the changes summed, to scale the tolerance by
+fn rate(terms: impl Iterator<Item = (usize, f64)>, direction: &[f64]) -> (f64, f64) {
+    terms
+        .map(|(j, a)| a * direction[j])
+        .fold((0.0, 0.0), |(sum, size), term| {
+            (sum + term, size + term.abs())
+        })
+}
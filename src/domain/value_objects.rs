@@ -1,9 +1,10 @@
 // Domain value objects representing core business concepts
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Type of decision variable in the optimization problem
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VariableType {
     /// Continuous real number (x ∈ ℝ)
     Continuous,
@@ -14,7 +15,7 @@ pub enum VariableType {
 }
 
 /// Type of constraint comparison
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConstraintType {
     /// Less than or equal (≤)
     LessThanOrEqual,
@@ -25,7 +26,7 @@ pub enum ConstraintType {
 }
 
 /// Direction of optimization
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OptimizationType {
     /// Minimize the objective function
     Minimize,
@@ -34,7 +35,7 @@ pub enum OptimizationType {
 }
 
 /// Status of the optimization solution
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SolutionStatus {
     /// Found optimal solution
     Optimal,
@@ -54,6 +55,8 @@ pub enum SolutionStatus {
     Error,
     /// Solve interrupted by user
     Interrupted,
+    /// Memory limit reached
+    MemoryLimit,
 }
 
 impl fmt::Display for SolutionStatus {
@@ -68,12 +71,81 @@ impl fmt::Display for SolutionStatus {
             SolutionStatus::NodeLimit => write!(f, "Node Limit Reached"),
             SolutionStatus::Error => write!(f, "Error"),
             SolutionStatus::Interrupted => write!(f, "Interrupted"),
+            SolutionStatus::MemoryLimit => write!(f, "Memory Limit Reached"),
+        }
+    }
+}
+
+/// Text format for a solution keyed by variable name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// `variable,value` rows under a header
+    Csv,
+    /// Object with the status, objective and a name-to-value map
+    Json,
+}
+
+/// What to do with integer values a solver returned a hair off an integer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegerRounding {
+    /// Snap them to the integer and flag any constraint that breaks
+    #[default]
+    Snap,
+    /// Snap them, and when that breaks a constraint re-solve the continuous
+    /// variables with the integers held at their snapped values
+    Repair,
+    /// Leave them as the solver returned them
+    Off,
+}
+
+/// Algorithm a backend uses for LP solves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LpAlgorithm {
+    /// The backend's own choice
+    #[default]
+    Auto,
+    /// Often much faster on degenerate models
+    DualSimplex,
+    PrimalSimplex,
+    /// Interior point (barrier), for large sparse LPs; MIPs keep using simplex
+    InteriorPoint,
+}
+
+/// Type of a backend option's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptionKind {
+    Bool,
+    Int,
+    Double,
+    /// Free text, or one of `SolverOption::choices` when it has any
+    String,
+}
+
+/// How soon a solve starts when every solve slot is busy
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum SolvePriority {
+    /// Batch work, started after everything else
+    Low,
+    #[default]
+    Normal,
+    /// Interactive solves someone is waiting on
+    High,
+}
+
+impl fmt::Display for SolvePriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolvePriority::Low => write!(f, "low"),
+            SolvePriority::Normal => write!(f, "normal"),
+            SolvePriority::High => write!(f, "high"),
         }
     }
 }
 
 /// Solver backend to use
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SolverBackend {
     /// Automatically select best solver
     Auto,
@@ -81,6 +153,23 @@ pub enum SolverBackend {
     CoinCbc,
     /// HiGHS solver
     Highs,
+    /// Run every available backend concurrently and keep the first proven result
+    Race,
+    /// Hungarian algorithm for assignment problems, others go to the default solver
+    Assignment,
+}
+
+impl SolverBackend {
+    /// Short lowercase identifier, as used in configuration and solver names
+    pub fn key(&self) -> &'static str {
+        match self {
+            SolverBackend::Auto => "auto",
+            SolverBackend::CoinCbc => "cbc",
+            SolverBackend::Highs => "highs",
+            SolverBackend::Race => "race",
+            SolverBackend::Assignment => "assignment",
+        }
+    }
 }
 
 impl fmt::Display for SolverBackend {
@@ -89,6 +178,8 @@ impl fmt::Display for SolverBackend {
             SolverBackend::Auto => write!(f, "Auto"),
             SolverBackend::CoinCbc => write!(f, "COIN-OR CBC"),
             SolverBackend::Highs => write!(f, "HiGHS"),
+            SolverBackend::Race => write!(f, "Race"),
+            SolverBackend::Assignment => write!(f, "Assignment"),
         }
     }
 }
@@ -0,0 +1,228 @@
+// Variable families: Indexed 1D, 2D and 3D collections of model variables
+// Facility location and transport models address `x[i][j]`, not column numbers
+
+use std::ops::{Deref, Index};
+
+use super::modeling::{sum_over, LinearExpr, Model, VarRef};
+use super::models::Variable;
+
+/// Variables `name_i`, in consecutive columns
+#[derive(Debug, Clone)]
+pub struct VariableArray {
+    vars: Vec<VarRef>,
+}
+
+impl VariableArray {
+    fn new(model: &mut Model, name: &str, len: usize, template: &Variable) -> Self {
+        let vars = (0..len)
+            .map(|i| model.add_var(named(template, format!("{}_{}", name, i))))
+            .collect();
+        Self { vars }
+    }
+
+    /// Index of a variable in this array
+    pub fn position(&self, var: VarRef) -> Option<usize> {
+        offset(&self.vars, var)
+    }
+}
+
+impl<'a> IntoIterator for &'a VariableArray {
+    type Item = &'a VarRef;
+    type IntoIter = std::slice::Iter<'a, VarRef>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vars.iter()
+    }
+}
+
+impl Deref for VariableArray {
+    type Target = [VarRef];
+
+    fn deref(&self) -> &[VarRef] {
+        &self.vars
+    }
+}
+
+/// Variables `name_i_j`, row by row in consecutive columns
+///
+/// `x[i]` is row `i`, so `x[i][j]` and `x[(i, j)]` both address one variable.
+#[derive(Debug, Clone)]
+pub struct VariableGrid {
+    rows: usize,
+    cols: usize,
+    vars: Vec<VarRef>,
+}
+
+impl VariableGrid {
+    fn new(
+        model: &mut Model,
+        name: &str,
+        (rows, cols): (usize, usize),
+        template: &Variable,
+    ) -> Self {
+        let mut vars = Vec::with_capacity(rows * cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                vars.push(model.add_var(named(template, format!("{}_{}_{}", name, i, j))));
+            }
+        }
+        Self { rows, cols, vars }
+    }
+
+    /// `(rows, columns)`
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn row(&self, i: usize) -> &[VarRef] {
+        &self.vars[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = VarRef> + '_ {
+        assert!(
+            j < self.cols,
+            "column {} out of range for {} columns",
+            j,
+            self.cols
+        );
+        self.vars.iter().skip(j).step_by(self.cols).copied()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[VarRef]> {
+        self.vars.chunks(self.cols.max(1))
+    }
+
+    /// All variables, row by row
+    pub fn iter(&self) -> impl Iterator<Item = VarRef> + '_ {
+        self.vars.iter().copied()
+    }
+
+    /// Sum of each row, e.g. for one capacity constraint per warehouse
+    pub fn row_sums(&self) -> Vec<LinearExpr> {
+        (0..self.rows).map(|i| sum_over(self.row(i))).collect()
+    }
+
+    /// Sum of each column, e.g. for one demand constraint per customer
+    pub fn column_sums(&self) -> Vec<LinearExpr> {
+        (0..self.cols).map(|j| sum_over(self.column(j))).collect()
+    }
+
+    /// `(row, column)` of a variable in this grid
+    pub fn position(&self, var: VarRef) -> Option<(usize, usize)> {
+        offset(&self.vars, var).map(|k| (k / self.cols, k % self.cols))
+    }
+}
+
+impl Index<usize> for VariableGrid {
+    type Output = [VarRef];
+
+    fn index(&self, i: usize) -> &[VarRef] {
+        self.row(i)
+    }
+}
+
+impl Index<(usize, usize)> for VariableGrid {
+    type Output = VarRef;
+
+    fn index(&self, (i, j): (usize, usize)) -> &VarRef {
+        &self.row(i)[j]
+    }
+}
+
+/// Variables `name_i_j_k`, as a stack of [`VariableGrid`]s in consecutive columns
+///
+/// `x[i]` is the grid for `i`, so `x[i][j][k]` and `x[(i, j, k)]` both address
+/// one variable.
+#[derive(Debug, Clone)]
+pub struct VariableGrid3 {
+    planes: Vec<VariableGrid>,
+    shape: (usize, usize, usize),
+}
+
+impl VariableGrid3 {
+    fn new(
+        model: &mut Model,
+        name: &str,
+        shape: (usize, usize, usize),
+        template: &Variable,
+    ) -> Self {
+        let (planes, rows, cols) = shape;
+        let planes = (0..planes)
+            .map(|i| VariableGrid::new(model, &format!("{}_{}", name, i), (rows, cols), template))
+            .collect();
+        Self { planes, shape }
+    }
+
+    pub fn shape(&self) -> (usize, usize, usize) {
+        self.shape
+    }
+
+    /// All variables in index order
+    pub fn iter(&self) -> impl Iterator<Item = VarRef> + '_ {
+        self.planes.iter().flat_map(VariableGrid::iter)
+    }
+
+    /// `(i, j, k)` of a variable in this grid
+    pub fn position(&self, var: VarRef) -> Option<(usize, usize, usize)> {
+        self.planes
+            .iter()
+            .enumerate()
+            .find_map(|(i, plane)| plane.position(var).map(|(j, k)| (i, j, k)))
+    }
+}
+
+impl Index<usize> for VariableGrid3 {
+    type Output = VariableGrid;
+
+    fn index(&self, i: usize) -> &VariableGrid {
+        &self.planes[i]
+    }
+}
+
+impl Index<(usize, usize, usize)> for VariableGrid3 {
+    type Output = VarRef;
+
+    fn index(&self, (i, j, k): (usize, usize, usize)) -> &VarRef {
+        &self.planes[i][(j, k)]
+    }
+}
+
+impl Model {
+    /// Add `len` variables like `template`, named `name_i`
+    pub fn add_array(&mut self, name: &str, len: usize, template: Variable) -> VariableArray {
+        VariableArray::new(self, name, len, &template)
+    }
+
+    /// Add a `rows` x `cols` grid of variables like `template`, named `name_i_j`
+    pub fn add_grid(
+        &mut self,
+        name: &str,
+        shape: (usize, usize),
+        template: Variable,
+    ) -> VariableGrid {
+        VariableGrid::new(self, name, shape, &template)
+    }
+
+    /// Add a 3D family of variables like `template`, named `name_i_j_k`
+    pub fn add_grid3(
+        &mut self,
+        name: &str,
+        shape: (usize, usize, usize),
+        template: Variable,
+    ) -> VariableGrid3 {
+        VariableGrid3::new(self, name, shape, &template)
+    }
+}
+
+fn named(template: &Variable, name: String) -> Variable {
+    Variable {
+        name,
+        ..template.clone()
+    }
+}
+
+/// Offset of `var` in a run of consecutive columns
+fn offset(vars: &[VarRef], var: VarRef) -> Option<usize> {
+    let first = vars.first()?.index();
+    var.index().checked_sub(first).filter(|&k| k < vars.len())
+}
@@ -0,0 +1,242 @@
+// Solution verification: Check a candidate assignment against a problem
+// Pure model arithmetic, no solver involved, so external solutions are judged by
+// the same definition the solvers use
+
+use serde::{Deserialize, Serialize};
+
+use super::model_statistics::MagnitudeRange;
+use super::models::{OptimizationProblem, Solution, SolutionQuality};
+use super::solver_service::{Result, SolverError, ILL_CONDITIONED_BASIS, MAX_COEFFICIENT_RATIO};
+use super::value_objects::{ConstraintType, SolutionStatus};
+
+/// Default absolute tolerance for violations
+pub const DEFAULT_FEASIBILITY_TOLERANCE: f64 = 1e-6;
+
+/// Relative violation at which a solution stops counting as reliable at all
+pub const UNRELIABLE_VIOLATION: f64 = 1e-3;
+
+/// Reliability at or above which a solution is safe to act on without review:
+/// proven optimal, within tolerance and on a well-scaled model
+pub const TRUSTED_RELIABILITY: f64 = 0.99;
+
+/// A constraint the candidate does not satisfy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstraintViolation {
+    pub index: usize,
+    pub name: String,
+    /// Left-hand side value at the candidate
+    pub activity: f64,
+    pub bound: f64,
+    /// How far the activity is on the wrong side of the bound
+    pub violation: f64,
+}
+
+/// What a variable violation is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VariableViolationKind {
+    /// Value outside the variable's bounds
+    Bound,
+    /// Fractional value for an integer or binary variable
+    Integrality,
+}
+
+/// A variable whose candidate value is not allowed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableViolation {
+    pub index: usize,
+    pub name: String,
+    pub value: f64,
+    pub kind: VariableViolationKind,
+    pub violation: f64,
+}
+
+/// Result of checking a candidate assignment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SolutionCheck {
+    pub objective_value: f64,
+    pub constraint_violations: Vec<ConstraintViolation>,
+    pub variable_violations: Vec<VariableViolation>,
+    pub max_constraint_violation: f64,
+    pub max_bound_violation: f64,
+    pub max_integrality_violation: f64,
+}
+
+impl SolutionCheck {
+    /// Whether the candidate satisfies every constraint, bound and integrality requirement
+    pub fn is_feasible(&self) -> bool {
+        self.constraint_violations.is_empty() && self.variable_violations.is_empty()
+    }
+}
+
+/// Check `values` against every constraint, bound and integrality requirement
+///
+/// Violations no larger than `tolerance` are ignored. Problems without explicit
+/// variables are checked as continuous and non-negative, like the solvers treat them.
+pub fn verify_solution(
+    problem: &OptimizationProblem,
+    values: &[f64],
+    tolerance: f64,
+) -> Result<SolutionCheck> {
+    let num_vars = problem.num_variables();
+    if values.len() != num_vars {
+        return Err(SolverError::InvalidProblem(format!(
+            "Candidate has {} values but problem has {} variables",
+            values.len(),
+            num_vars
+        )));
+    }
+
+    let mut check = SolutionCheck {
+        objective_value: dot(&problem.objective.coefficients, values),
+        ..SolutionCheck::default()
+    };
+
+    for (index, constraint) in problem.constraints.iter().enumerate() {
+        let activity = constraint.coefficients.dot(values);
+        let violation = match constraint.constraint_type {
+            ConstraintType::LessThanOrEqual => activity - constraint.bound,
+            ConstraintType::Equal => (activity - constraint.bound).abs(),
+            ConstraintType::GreaterThanOrEqual => constraint.bound - activity,
+        };
+        if violation > tolerance {
+            check.max_constraint_violation = check.max_constraint_violation.max(violation);
+            check.constraint_violations.push(ConstraintViolation {
+                index,
+                name: constraint.name.clone(),
+                activity,
+                bound: constraint.bound,
+                violation,
+            });
+        }
+    }
+
+    for (index, &value) in values.iter().enumerate() {
+        let (lower, upper, integer) = problem
+            .variables
+            .get(index)
+            .map_or((0.0, None, false), |v| {
+                (v.lower_bound, v.upper_bound, v.is_integer())
+            });
+
+        let below = lower - value;
+        let above = upper.map_or(0.0, |upper| value - upper);
+        let bound_violation = below.max(above);
+        if bound_violation > tolerance {
+            check.max_bound_violation = check.max_bound_violation.max(bound_violation);
+            check.variable_violations.push(VariableViolation {
+                index,
+                name: problem.variable_name(index),
+                value,
+                kind: VariableViolationKind::Bound,
+                violation: bound_violation,
+            });
+        }
+
+        if integer {
+            let fractionality = (value - value.round()).abs();
+            if fractionality > tolerance {
+                check.max_integrality_violation =
+                    check.max_integrality_violation.max(fractionality);
+                check.variable_violations.push(VariableViolation {
+                    index,
+                    name: problem.variable_name(index),
+                    value,
+                    kind: VariableViolationKind::Integrality,
+                    violation: fractionality,
+                });
+            }
+        }
+    }
+
+    Ok(check)
+}
+
+/// Worst violations of `solution` and how far it can be trusted, keeping its
+/// polishing record
+///
+/// Reliability multiplies four factors, each from 0 to 1:
+/// - feasibility: 1 up to a relative violation of [`DEFAULT_FEASIBILITY_TOLERANCE`],
+///   falling log-linearly to 0 at [`UNRELIABLE_VIOLATION`]
+/// - integrality: the same scale, for the largest fractional part of an integer variable
+/// - optimality: 1 when proven optimal, otherwise `1 / (1 + 100 * gap)` (a 1% gap
+///   halves it), and 0.5 when there is no gap to go by
+/// - numerics: 0.5 when coefficients span more than [`MAX_COEFFICIENT_RATIO`] or the
+///   final basis is estimated worse conditioned than [`ILL_CONDITIONED_BASIS`]
+///
+/// A solution without values scores 0.
+pub fn assess_quality(problem: &OptimizationProblem, solution: &Solution) -> SolutionQuality {
+    let Ok(check) = verify_solution(problem, &solution.variable_values, 0.0) else {
+        return SolutionQuality::default();
+    };
+    let relative = |violation: f64, size: f64| violation / size.abs().max(1.0);
+    let max_relative_violation = check
+        .constraint_violations
+        .iter()
+        .map(|v| relative(v.violation, v.bound))
+        .chain(
+            check
+                .variable_violations
+                .iter()
+                .filter(|v| v.kind == VariableViolationKind::Bound)
+                .map(|v| relative(v.violation, v.value)),
+        )
+        .fold(0.0, f64::max);
+
+    let optimality = match (solution.status, solution.gap) {
+        (SolutionStatus::Optimal, _) => 1.0,
+        (_, Some(gap)) => 1.0 / (1.0 + 100.0 * gap.max(0.0)),
+        (_, None) => 0.5,
+    };
+    let well_scaled = [
+        MagnitudeRange::of(
+            problem
+                .constraints
+                .iter()
+                .flat_map(|c| c.coefficients.values().iter().copied()),
+        ),
+        MagnitudeRange::of(problem.objective.coefficients.iter().copied()),
+    ]
+    .iter()
+    .all(|range| range.ratio() <= MAX_COEFFICIENT_RATIO);
+    let well_conditioned = solution
+        .statistics
+        .conditioning
+        .is_none_or(|basis| basis.condition_estimate <= ILL_CONDITIONED_BASIS);
+    let numerics = if well_scaled && well_conditioned {
+        1.0
+    } else {
+        0.5
+    };
+
+    let reliability = if solution.variable_values.is_empty() {
+        0.0
+    } else {
+        violation_score(max_relative_violation)
+            * violation_score(check.max_integrality_violation)
+            * optimality
+            * numerics
+    };
+
+    SolutionQuality {
+        max_constraint_violation: check.max_constraint_violation,
+        max_relative_violation,
+        max_integrality_violation: check.max_integrality_violation,
+        reliability,
+        snapped_integers: solution.quality.snapped_integers,
+        rounding_violation: solution.quality.rounding_violation,
+        zeroed_values: solution.quality.zeroed_values,
+    }
+}
+
+/// 1 within tolerance, 0 at [`UNRELIABLE_VIOLATION`], log-linear in between
+fn violation_score(violation: f64) -> f64 {
+    if violation <= DEFAULT_FEASIBILITY_TOLERANCE {
+        return 1.0;
+    }
+    let decades = (UNRELIABLE_VIOLATION / DEFAULT_FEASIBILITY_TOLERANCE).log10();
+    (1.0 - (violation / DEFAULT_FEASIBILITY_TOLERANCE).log10() / decades).max(0.0)
+}
+
+fn dot(coefficients: &[f64], values: &[f64]) -> f64 {
+    coefficients.iter().zip(values).map(|(c, x)| c * x).sum()
+}
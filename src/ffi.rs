@@ -0,0 +1,465 @@
+// C API: The solver layer behind a C ABI, for services that embed it in-process
+// `letsopt_solve` takes and returns JSON; the model functions build a problem column
+// by column. Declarations and pointer rules are in include/letsopt.h
+// Build the shared library with
+// `cargo rustc --release --lib --features ffi --crate-type cdylib`
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::OnceLock;
+
+use crate::domain::modeling::{LinearExpr, Model, VarRef};
+use crate::domain::models::{OptimizationProblem, Solution, SolverConfig, Variable};
+use crate::domain::problem_file::ProblemFormat;
+use crate::domain::solver_service::{Result, SolverError};
+use crate::domain::value_objects::{ConstraintType, SolutionStatus};
+use crate::solver::{prepare_problem, wrap_solver, ResourceMeter, SolverFactory, SolverRegistry};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Problem under construction, with handles to its columns in creation order
+pub struct LetsOptModel {
+    model: Model,
+    columns: Vec<VarRef>,
+    config: SolverConfig,
+}
+
+/// A finished solve, with its message kept as a C string
+pub struct LetsOptSolution {
+    solution: Solution,
+    message: CString,
+}
+
+/// Solve a problem given as JSON, returning the solution as JSON
+///
+/// The problem uses the same JSON form as problem files; NULL on failure.
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solve(problem_json: *const c_char) -> *mut c_char {
+    guarded(ptr::null_mut(), || {
+        let text = string_arg(problem_json, "problem_json")?;
+        let problem = OptimizationProblem::parse(&text, ProblemFormat::Json)?;
+        let solution = solve(problem)?;
+        to_json(&solution)
+    })
+}
+
+/// Free a string returned by this library
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Why the last call on this thread failed, or NULL
+#[no_mangle]
+pub extern "C" fn letsopt_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn letsopt_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_new(name: *const c_char) -> *mut LetsOptModel {
+    guarded(ptr::null_mut(), || {
+        let name = optional_string_arg(name, "name")?;
+        Ok(Box::into_raw(Box::new(LetsOptModel {
+            model: Model::new(name),
+            columns: Vec::new(),
+            config: SolverConfig::default(),
+        })))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_free(model: *mut LetsOptModel) {
+    if !model.is_null() {
+        drop(Box::from_raw(model));
+    }
+}
+
+/// Add a column, returning its index or -1
+///
+/// An infinite `upper` leaves the variable unbounded above.
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_add_variable(
+    model: *mut LetsOptModel,
+    name: *const c_char,
+    variable_type: c_int,
+    lower: f64,
+    upper: f64,
+) -> i64 {
+    guarded(-1, || {
+        let model = model_arg(model)?;
+        let name = optional_string_arg(name, "name")?;
+        let variable = match variable_type {
+            0 => Variable::continuous(name),
+            1 => Variable::integer(name),
+            2 => Variable::binary(name),
+            other => return Err(invalid(format!("Unknown variable type {}", other))),
+        };
+        let upper = (upper != f64::INFINITY).then_some(upper);
+        let column = model.model.add_var(variable.with_bounds(lower, upper));
+        model.columns.push(column);
+        Ok(column.index() as i64)
+    })
+}
+
+/// Add the row `sum(coefficients[k] * x[columns[k]]) (<=|=|>=) rhs`, returning its
+/// index or -1
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_add_constraint(
+    model: *mut LetsOptModel,
+    name: *const c_char,
+    count: usize,
+    columns: *const usize,
+    coefficients: *const f64,
+    sense: c_int,
+    rhs: f64,
+) -> i64 {
+    guarded(-1, || {
+        let model = model_arg(model)?;
+        let name = optional_string_arg(name, "name")?;
+        let expr = model.expr(count, columns, coefficients)?;
+        let constraint = match constraint_type(sense)? {
+            ConstraintType::LessThanOrEqual => expr.leq(rhs),
+            ConstraintType::Equal => expr.equals(rhs),
+            ConstraintType::GreaterThanOrEqual => expr.geq(rhs),
+        };
+        Ok(model.model.add(constraint.with_name(name)) as i64)
+    })
+}
+
+/// Set the objective to `sum(coefficients[k] * x[columns[k]])`; 0 on success, -1 on
+/// failure
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_set_objective(
+    model: *mut LetsOptModel,
+    maximize: c_int,
+    count: usize,
+    columns: *const usize,
+    coefficients: *const f64,
+) -> c_int {
+    guarded(-1, || {
+        let model = model_arg(model)?;
+        let expr = model.expr(count, columns, coefficients)?;
+        if maximize != 0 {
+            model.model.maximize(expr);
+        } else {
+            model.model.minimize(expr);
+        }
+        Ok(0)
+    })
+}
+
+/// Stop after `seconds`; 0 or less removes the limit
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_set_time_limit(
+    model: *mut LetsOptModel,
+    seconds: f64,
+) -> c_int {
+    guarded(-1, || {
+        model_arg(model)?.config.time_limit = (seconds > 0.0).then_some(seconds);
+        Ok(0)
+    })
+}
+
+/// Stop once the relative MIP gap is below `gap`; 0 or less uses the default
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_set_gap_tolerance(
+    model: *mut LetsOptModel,
+    gap: f64,
+) -> c_int {
+    guarded(-1, || {
+        model_arg(model)?.config.gap_tolerance = (gap > 0.0).then_some(gap);
+        Ok(0)
+    })
+}
+
+/// Stop once bound and incumbent are within `gap` objective units; 0 or less
+/// uses the default
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_set_absolute_gap(
+    model: *mut LetsOptModel,
+    gap: f64,
+) -> c_int {
+    guarded(-1, || {
+        model_arg(model)?.config.absolute_gap_tolerance = (gap > 0.0).then_some(gap);
+        Ok(0)
+    })
+}
+
+/// Solve with the built-in solver named `name` (`highs`, `cbc`); NULL picks one
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_set_solver(
+    model: *mut LetsOptModel,
+    name: *const c_char,
+) -> c_int {
+    guarded(-1, || {
+        let model = model_arg(model)?;
+        model.config.solver_name = if name.is_null() {
+            None
+        } else {
+            Some(string_arg(name, "name")?)
+        };
+        Ok(0)
+    })
+}
+
+/// Solve the model as it stands, returning NULL if it can't be solved
+///
+/// Infeasible and unbounded problems still return a solution with that status.
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_model_solve(model: *const LetsOptModel) -> *mut LetsOptSolution {
+    guarded(ptr::null_mut(), || {
+        let model = model.as_ref().ok_or_else(|| null_arg("model"))?;
+        let problem = model.model.build();
+        let solution = solve(OptimizationProblem {
+            solver_config: model.config.clone(),
+            ..problem
+        })?;
+        let message = CString::new(solution.message.replace('\0', " ")).unwrap_or_default();
+        Ok(Box::into_raw(Box::new(LetsOptSolution {
+            solution,
+            message,
+        })))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_free(solution: *mut LetsOptSolution) {
+    if !solution.is_null() {
+        drop(Box::from_raw(solution));
+    }
+}
+
+/// Status code, in `SolutionStatus` order (0 = optimal); -1 for a NULL solution
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_status(solution: *const LetsOptSolution) -> c_int {
+    solution
+        .as_ref()
+        .map_or(-1, |s| status_code(s.solution.status))
+}
+
+/// Objective value, or NaN when the solve found no solution
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_objective(solution: *const LetsOptSolution) -> f64 {
+    solution
+        .as_ref()
+        .and_then(|s| s.solution.optimal_value)
+        .unwrap_or(f64::NAN)
+}
+
+/// Number of values `letsopt_solution_values` points to, one per column
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_num_values(solution: *const LetsOptSolution) -> usize {
+    solution
+        .as_ref()
+        .map_or(0, |s| s.solution.variable_values.len())
+}
+
+/// Column values, valid until the solution is freed
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_values(solution: *const LetsOptSolution) -> *const f64 {
+    solution
+        .as_ref()
+        .map_or(ptr::null(), |s| s.solution.variable_values.as_ptr())
+}
+
+/// Number of duals `letsopt_solution_duals` points to: one per row when the solver
+/// reports them, as it does for LPs
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_num_duals(solution: *const LetsOptSolution) -> usize {
+    solution
+        .as_ref()
+        .map_or(0, |s| s.solution.dual_values.len())
+}
+
+/// Row duals, valid until the solution is freed
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_duals(solution: *const LetsOptSolution) -> *const f64 {
+    solution
+        .as_ref()
+        .map_or(ptr::null(), |s| s.solution.dual_values.as_ptr())
+}
+
+/// The solver's message, valid until the solution is freed
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_message(
+    solution: *const LetsOptSolution,
+) -> *const c_char {
+    solution
+        .as_ref()
+        .map_or(ptr::null(), |s| s.message.as_ptr())
+}
+
+/// The whole solution as JSON, as `letsopt_solve` returns it
+#[no_mangle]
+pub unsafe extern "C" fn letsopt_solution_json(solution: *const LetsOptSolution) -> *mut c_char {
+    guarded(ptr::null_mut(), || {
+        let solution = solution.as_ref().ok_or_else(|| null_arg("solution"))?;
+        to_json(&solution.solution)
+    })
+}
+
+impl LetsOptModel {
+    /// Sparse expression over this model's columns
+    unsafe fn expr(
+        &self,
+        count: usize,
+        columns: *const usize,
+        coefficients: *const f64,
+    ) -> Result<LinearExpr> {
+        let mut expr = LinearExpr::new();
+        if count == 0 {
+            return Ok(expr);
+        }
+        if columns.is_null() || coefficients.is_null() {
+            return Err(null_arg("columns and coefficients"));
+        }
+        let columns = std::slice::from_raw_parts(columns, count);
+        let coefficients = std::slice::from_raw_parts(coefficients, count);
+        for (&column, &coefficient) in columns.iter().zip(coefficients) {
+            let var = self.columns.get(column).ok_or_else(|| {
+                invalid(format!(
+                    "Column {} is out of range for a model with {} variables",
+                    column,
+                    self.columns.len()
+                ))
+            })?;
+            expr.add_term(*var, coefficient);
+        }
+        Ok(expr)
+    }
+}
+
+/// Solve `problem` the way the server would for a request without a caller
+///
+/// Any solver named in the config must be one of the built-in ones, and solves
+/// always run in-process.
+fn solve(mut problem: OptimizationProblem) -> Result<Solution> {
+    let solver = match problem.solver_config.solver_name.as_deref() {
+        Some(name) => builtin_solvers().get_named(name).ok_or_else(|| {
+            invalid(format!(
+                "Unknown solver '{}'; available: {}",
+                name,
+                builtin_solvers().keys().collect::<Vec<_>>().join(", ")
+            ))
+        })?,
+        None => SolverFactory::create_solver(&problem),
+    };
+    let solver = wrap_solver(solver, &problem.solver_config);
+    prepare_problem(&mut problem)?;
+
+    let meter = ResourceMeter::start();
+    let mut solution = solver.solve(&problem)?;
+    solution
+        .statistics
+        .resources
+        .get_or_insert_with(|| meter.finish());
+    if problem.solver_config.relax_integrality {
+        solution.message = format!("LP relaxation: {}", solution.message);
+    }
+    Ok(solution)
+}
+
+fn builtin_solvers() -> &'static SolverRegistry {
+    static SOLVERS: OnceLock<SolverRegistry> = OnceLock::new();
+    SOLVERS.get_or_init(SolverRegistry::builtin)
+}
+
+/// Run `call`, turning its error or panic into the thread's last error and `failed`
+///
+/// Panics must not unwind into the caller's C frames.
+fn guarded<T>(failed: T, call: impl FnOnce() -> Result<T>) -> T {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|panic| {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(SolverError::ExecutionFailed(format!(
+            "Panicked: {}",
+            reason
+        )))
+    });
+    let error = outcome.as_ref().err().map(|e| e.to_string());
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = error.map(|e| CString::new(e.replace('\0', " ")).unwrap_or_default())
+    });
+    outcome.unwrap_or(failed)
+}
+
+unsafe fn model_arg<'a>(model: *mut LetsOptModel) -> Result<&'a mut LetsOptModel> {
+    model.as_mut().ok_or_else(|| null_arg("model"))
+}
+
+unsafe fn string_arg(string: *const c_char, what: &str) -> Result<String> {
+    if string.is_null() {
+        return Err(null_arg(what));
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| invalid(format!("{} is not valid UTF-8", what)))
+}
+
+/// String argument where NULL means empty
+unsafe fn optional_string_arg(string: *const c_char, what: &str) -> Result<String> {
+    if string.is_null() {
+        return Ok(String::new());
+    }
+    string_arg(string, what)
+}
+
+fn to_json(solution: &Solution) -> Result<*mut c_char> {
+    let json = serde_json::to_string(solution)
+        .map_err(|e| SolverError::ExecutionFailed(format!("Cannot encode solution: {}", e)))?;
+    // JSON escapes control characters, so it never contains a NUL
+    Ok(CString::new(json).unwrap_or_default().into_raw())
+}
+
+fn constraint_type(sense: c_int) -> Result<ConstraintType> {
+    match sense {
+        0 => Ok(ConstraintType::LessThanOrEqual),
+        1 => Ok(ConstraintType::Equal),
+        2 => Ok(ConstraintType::GreaterThanOrEqual),
+        other => Err(invalid(format!("Unknown constraint sense {}", other))),
+    }
+}
+
+fn status_code(status: SolutionStatus) -> c_int {
+    match status {
+        SolutionStatus::Optimal => 0,
+        SolutionStatus::Feasible => 1,
+        SolutionStatus::Infeasible => 2,
+        SolutionStatus::Unbounded => 3,
+        SolutionStatus::TimeLimit => 4,
+        SolutionStatus::IterationLimit => 5,
+        SolutionStatus::NodeLimit => 6,
+        SolutionStatus::Error => 7,
+        SolutionStatus::Interrupted => 8,
+        SolutionStatus::MemoryLimit => 9,
+    }
+}
+
+fn invalid(message: String) -> SolverError {
+    SolverError::InvalidProblem(message)
+}
+
+fn null_arg(what: &str) -> SolverError {
+    invalid(format!("{} must not be NULL", what))
+}
@@ -0,0 +1,683 @@
+// Infrastructure: Server settings from CLI flags, environment and a TOML file
+// Precedence: command line > environment > config file > built-in defaults
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+
+use super::logging::{LogFormat, LoggingConfig};
+use super::publishers::PublishTarget;
+use super::server::{ServerConfig, TlsConfig};
+use super::worker::DispatcherLink;
+use crate::application::workers::DEFAULT_HEARTBEAT_TIMEOUT;
+use crate::application::{
+    OverloadPolicy, ProblemKind, ProblemSizeLimits, QuotaLimits, QuotaPolicy, RoutingPolicy,
+    RoutingRule, UrlFetchConfig, WebhookConfig,
+};
+use crate::domain::value_objects::SolverBackend;
+use crate::solver::IsolationConfig;
+use tonic::codec::CompressionEncoding;
+
+/// Address used when none is configured
+pub const DEFAULT_ADDRESS: &str = "0.0.0.0:50051";
+
+/// Errors while assembling the server configuration
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Cannot read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackendSetting {
+    Auto,
+    Highs,
+    Cbc,
+    Race,
+    Assignment,
+}
+
+impl From<BackendSetting> for SolverBackend {
+    fn from(setting: BackendSetting) -> Self {
+        match setting {
+            BackendSetting::Auto => SolverBackend::Auto,
+            BackendSetting::Highs => SolverBackend::Highs,
+            BackendSetting::Cbc => SolverBackend::CoinCbc,
+            BackendSetting::Race => SolverBackend::Race,
+            BackendSetting::Assignment => SolverBackend::Assignment,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverloadSetting {
+    Queue,
+    Reject,
+}
+
+impl From<OverloadSetting> for OverloadPolicy {
+    fn from(setting: OverloadSetting) -> Self {
+        match setting {
+            OverloadSetting::Queue => OverloadPolicy::Queue,
+            OverloadSetting::Reject => OverloadPolicy::Reject,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompressionSetting {
+    Gzip,
+    Zstd,
+}
+
+impl From<CompressionSetting> for CompressionEncoding {
+    fn from(setting: CompressionSetting) -> Self {
+        match setting {
+            CompressionSetting::Gzip => CompressionEncoding::Gzip,
+            CompressionSetting::Zstd => CompressionEncoding::Zstd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProblemKindSetting {
+    #[default]
+    Any,
+    Lp,
+    Mip,
+}
+
+impl From<ProblemKindSetting> for ProblemKind {
+    fn from(setting: ProblemKindSetting) -> Self {
+        match setting {
+            ProblemKindSetting::Any => ProblemKind::Any,
+            ProblemKindSetting::Lp => ProblemKind::Lp,
+            ProblemKindSetting::Mip => ProblemKind::Mip,
+        }
+    }
+}
+
+/// One `[[route]]` table in the config file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RouteSetting {
+    pub solver: String,
+    #[serde(default)]
+    pub kind: ProblemKindSetting,
+    pub min_variables: Option<usize>,
+    pub max_variables: Option<usize>,
+    pub min_constraints: Option<usize>,
+    pub max_constraints: Option<usize>,
+    pub hint: Option<String>,
+}
+
+impl From<RouteSetting> for RoutingRule {
+    fn from(setting: RouteSetting) -> Self {
+        RoutingRule {
+            solver: setting.solver,
+            kind: setting.kind.into(),
+            min_variables: setting.min_variables,
+            max_variables: setting.max_variables,
+            min_constraints: setting.min_constraints,
+            max_constraints: setting.max_constraints,
+            hint: setting.hint,
+        }
+    }
+}
+
+/// One `[[quota]]` table in the config file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct QuotaSetting {
+    pub api_key: String,
+    pub solves_per_hour: Option<u64>,
+    pub cpu_secs_per_day: Option<f64>,
+}
+
+/// Server settings, read from the command line and environment or from a TOML file
+///
+/// Every field is optional so the two sources can be layered; unset fields fall
+/// back to [`ServerConfig`]'s defaults.
+#[derive(Debug, Default, Parser, Deserialize)]
+#[command(
+    name = "letsopt-server",
+    version,
+    about = "LetsOpt optimization solver server",
+    long_about = None
+)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ServerSettings {
+    /// TOML file with server settings
+    #[arg(long, env = "LETSOPT_CONFIG")]
+    #[serde(skip)]
+    pub config: Option<PathBuf>,
+
+    /// Address to listen on; repeat or comma-separate to listen on several
+    #[arg(long, env = "LETSOPT_ADDRESS", value_delimiter = ',')]
+    #[serde(deserialize_with = "one_or_many")]
+    pub address: Option<Vec<SocketAddr>>,
+
+    /// Backend used for requests that ask for AUTO
+    #[arg(long, env = "LETSOPT_DEFAULT_BACKEND", value_enum)]
+    pub default_backend: Option<BackendSetting>,
+
+    /// Maximum number of solves running at once
+    #[arg(long, env = "LETSOPT_MAX_CONCURRENT_SOLVES")]
+    pub max_concurrent_solves: Option<usize>,
+
+    /// What to do with solves beyond the concurrency limit
+    #[arg(long, env = "LETSOPT_OVERLOAD_POLICY", value_enum)]
+    pub overload_policy: Option<OverloadSetting>,
+
+    /// Let HIGH-priority solves stop running LOW-priority MIPs when every slot is busy
+    #[arg(long, env = "LETSOPT_PREEMPT_LOW_PRIORITY", num_args = 0..=1, default_missing_value = "true")]
+    pub preempt_low_priority: Option<bool>,
+
+    /// Report not ready to health checks while more solves than this are queued
+    #[arg(long, env = "LETSOPT_READINESS_MAX_QUEUED")]
+    pub readiness_max_queued: Option<usize>,
+
+    /// Backend instances kept warm between solves, per backend
+    #[arg(long, env = "LETSOPT_SOLVER_POOL_SIZE")]
+    pub solver_pool_size: Option<usize>,
+
+    /// Seconds a result stays available for idempotent replay
+    #[arg(long, env = "LETSOPT_IDEMPOTENCY_TTL_SECS")]
+    pub idempotency_ttl_secs: Option<u64>,
+
+    /// Most model sessions kept at once
+    #[arg(long, env = "LETSOPT_MAX_MODEL_SESSIONS")]
+    pub max_model_sessions: Option<usize>,
+
+    /// Seconds an unused model session is kept
+    #[arg(long, env = "LETSOPT_SESSION_IDLE_TIMEOUT_SECS")]
+    pub session_idle_timeout_secs: Option<u64>,
+
+    /// Run each solve in a resource-limited child process
+    #[arg(long, env = "LETSOPT_ISOLATE", num_args = 0..=1, default_missing_value = "true")]
+    pub isolate: Option<bool>,
+
+    /// Memory cap for every solve, in megabytes (implies isolation)
+    #[arg(long, env = "LETSOPT_MEMORY_LIMIT_MB")]
+    pub memory_limit_mb: Option<u64>,
+
+    /// CPU time limit for isolated solves, in seconds
+    #[arg(long, env = "LETSOPT_CPU_TIME_LIMIT_SECS")]
+    pub cpu_time_limit_secs: Option<u64>,
+
+    /// Wall-clock limit for isolated solves, in seconds
+    #[arg(long, env = "LETSOPT_WALL_TIME_LIMIT_SECS")]
+    pub wall_time_limit_secs: Option<u64>,
+
+    /// Maximum number of variables per problem
+    #[arg(long, env = "LETSOPT_MAX_VARIABLES")]
+    pub max_variables: Option<usize>,
+
+    /// Maximum number of constraints per problem
+    #[arg(long, env = "LETSOPT_MAX_CONSTRAINTS")]
+    pub max_constraints: Option<usize>,
+
+    /// Maximum number of non-zero constraint coefficients per problem
+    #[arg(long, env = "LETSOPT_MAX_NONZEROS")]
+    pub max_nonzeros: Option<usize>,
+
+    /// Largest request or response message, in bytes
+    #[arg(long, env = "LETSOPT_MAX_MESSAGE_SIZE")]
+    pub max_message_size: Option<usize>,
+
+    /// Compression encodings to enable (comma separated)
+    #[arg(long, env = "LETSOPT_COMPRESSION", value_enum, value_delimiter = ',')]
+    pub compression: Option<Vec<CompressionSetting>>,
+
+    /// PEM certificate chain for TLS
+    #[arg(long, env = "LETSOPT_TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key for TLS
+    #[arg(long, env = "LETSOPT_TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM CA bundle for verifying client certificates (enables mutual TLS)
+    #[arg(long, env = "LETSOPT_TLS_CLIENT_CA")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// Append a JSON line per solve to this audit file
+    #[arg(long, env = "LETSOPT_AUDIT_LOG")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Append each solve's canonical problem, seed and result to this file, for
+    /// `letsopt-replay`
+    #[arg(long, env = "LETSOPT_RECORD_SOLVES")]
+    pub record_solves: Option<PathBuf>,
+
+    /// Keep problems saved with SaveProblem in this directory; unset disables the
+    /// problem repository
+    #[arg(long, env = "LETSOPT_PROBLEM_DIR")]
+    pub problem_dir: Option<PathBuf>,
+
+    /// Hosts SolveFromUrl may fetch problems from (comma separated, `*` for any);
+    /// unset disables SolveFromUrl
+    #[arg(long, env = "LETSOPT_URL_ALLOWED_HOSTS", value_delimiter = ',')]
+    pub url_allowed_hosts: Option<Vec<String>>,
+
+    /// Largest problem file SolveFromUrl downloads, in bytes
+    #[arg(long, env = "LETSOPT_URL_MAX_BYTES")]
+    pub url_max_bytes: Option<u64>,
+
+    /// Hosts SubmitProblem callback URLs may point at (comma separated, `*` for
+    /// any); unset refuses submissions with a callback URL
+    #[arg(long, env = "LETSOPT_WEBHOOK_ALLOWED_HOSTS", value_delimiter = ',')]
+    pub webhook_allowed_hosts: Option<Vec<String>>,
+
+    /// Publish finished solves to this NATS server (requires the `nats` feature)
+    #[arg(long, env = "LETSOPT_PUBLISH_NATS_URL")]
+    pub publish_nats_url: Option<String>,
+
+    /// NATS subject for finished solves
+    #[arg(long, env = "LETSOPT_PUBLISH_NATS_SUBJECT")]
+    pub publish_nats_subject: Option<String>,
+
+    /// Publish finished solves to these Kafka brokers (comma separated; requires
+    /// the `kafka` feature)
+    #[arg(long, env = "LETSOPT_PUBLISH_KAFKA_BROKERS")]
+    pub publish_kafka_brokers: Option<String>,
+
+    /// Kafka topic for finished solves
+    #[arg(long, env = "LETSOPT_PUBLISH_KAFKA_TOPIC")]
+    pub publish_kafka_topic: Option<String>,
+
+    /// Log filter, e.g. `info` or `letsopt=debug` (defaults to RUST_LOG, then `info`)
+    #[arg(long, env = "LETSOPT_LOG_LEVEL")]
+    pub log_level: Option<String>,
+
+    /// Log output format
+    #[arg(long, env = "LETSOPT_LOG_FORMAT", value_enum)]
+    pub log_format: Option<LogFormat>,
+
+    /// Routing rules for AUTO requests, tried in order (config file only)
+    #[arg(skip)]
+    pub route: Option<Vec<RouteSetting>>,
+
+    /// Solves each API key may start per hour, unless it has its own quota
+    #[arg(long, env = "LETSOPT_QUOTA_SOLVES_PER_HOUR")]
+    pub quota_solves_per_hour: Option<u64>,
+
+    /// CPU-seconds of solving each API key may use per day, unless it has its own quota
+    #[arg(long, env = "LETSOPT_QUOTA_CPU_SECS_PER_DAY")]
+    pub quota_cpu_secs_per_day: Option<f64>,
+
+    /// Quotas for individual API keys (config file only)
+    #[arg(skip)]
+    pub quota: Option<Vec<QuotaSetting>>,
+
+    /// Run as a dispatcher that farms solves out to registered workers
+    #[arg(long, env = "LETSOPT_DISPATCH", num_args = 0..=1, default_missing_value = "true")]
+    pub dispatch: Option<bool>,
+
+    /// Seconds a worker may go without a heartbeat before the dispatcher drops it
+    #[arg(long, env = "LETSOPT_WORKER_HEARTBEAT_TIMEOUT_SECS")]
+    pub worker_heartbeat_timeout_secs: Option<u64>,
+
+    /// Dispatcher to register with as a worker, e.g. `http://dispatcher:50051`
+    #[arg(long, env = "LETSOPT_DISPATCHER_URL")]
+    pub dispatcher_url: Option<String>,
+
+    /// This worker's gRPC endpoint as the dispatcher reaches it
+    #[arg(long, env = "LETSOPT_ADVERTISE_URL")]
+    pub advertise_url: Option<String>,
+
+    /// Shared secret workers present to their dispatcher; required on both sides
+    #[arg(long, env = "LETSOPT_WORKER_TOKEN", hide_env_values = true)]
+    pub worker_token: Option<String>,
+
+    /// Secret admin RPCs such as ListJobs require; without one they are refused
+    #[arg(long, env = "LETSOPT_ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<String>,
+}
+
+impl ServerSettings {
+    /// Load settings from the process arguments, environment and config file
+    pub fn load() -> Result<Self, ConfigError> {
+        let cli = Self::parse();
+        let file = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        Ok(cli.or(file))
+    }
+
+    /// Read settings from a TOML file
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Fill every unset field from `fallback`
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            config: self.config.or(fallback.config),
+            address: self.address.or(fallback.address),
+            default_backend: self.default_backend.or(fallback.default_backend),
+            max_concurrent_solves: self
+                .max_concurrent_solves
+                .or(fallback.max_concurrent_solves),
+            overload_policy: self.overload_policy.or(fallback.overload_policy),
+            preempt_low_priority: self.preempt_low_priority.or(fallback.preempt_low_priority),
+            readiness_max_queued: self.readiness_max_queued.or(fallback.readiness_max_queued),
+            solver_pool_size: self.solver_pool_size.or(fallback.solver_pool_size),
+            idempotency_ttl_secs: self.idempotency_ttl_secs.or(fallback.idempotency_ttl_secs),
+            max_model_sessions: self.max_model_sessions.or(fallback.max_model_sessions),
+            session_idle_timeout_secs: self
+                .session_idle_timeout_secs
+                .or(fallback.session_idle_timeout_secs),
+            isolate: self.isolate.or(fallback.isolate),
+            memory_limit_mb: self.memory_limit_mb.or(fallback.memory_limit_mb),
+            cpu_time_limit_secs: self.cpu_time_limit_secs.or(fallback.cpu_time_limit_secs),
+            wall_time_limit_secs: self.wall_time_limit_secs.or(fallback.wall_time_limit_secs),
+            max_variables: self.max_variables.or(fallback.max_variables),
+            max_constraints: self.max_constraints.or(fallback.max_constraints),
+            max_nonzeros: self.max_nonzeros.or(fallback.max_nonzeros),
+            max_message_size: self.max_message_size.or(fallback.max_message_size),
+            compression: self.compression.or(fallback.compression),
+            tls_cert: self.tls_cert.or(fallback.tls_cert),
+            tls_key: self.tls_key.or(fallback.tls_key),
+            tls_client_ca: self.tls_client_ca.or(fallback.tls_client_ca),
+            audit_log: self.audit_log.or(fallback.audit_log),
+            record_solves: self.record_solves.or(fallback.record_solves),
+            problem_dir: self.problem_dir.or(fallback.problem_dir),
+            url_allowed_hosts: self.url_allowed_hosts.or(fallback.url_allowed_hosts),
+            url_max_bytes: self.url_max_bytes.or(fallback.url_max_bytes),
+            webhook_allowed_hosts: self
+                .webhook_allowed_hosts
+                .or(fallback.webhook_allowed_hosts),
+            publish_nats_url: self.publish_nats_url.or(fallback.publish_nats_url),
+            publish_nats_subject: self.publish_nats_subject.or(fallback.publish_nats_subject),
+            publish_kafka_brokers: self
+                .publish_kafka_brokers
+                .or(fallback.publish_kafka_brokers),
+            publish_kafka_topic: self.publish_kafka_topic.or(fallback.publish_kafka_topic),
+            log_level: self.log_level.or(fallback.log_level),
+            log_format: self.log_format.or(fallback.log_format),
+            route: self.route.or(fallback.route),
+            quota_solves_per_hour: self
+                .quota_solves_per_hour
+                .or(fallback.quota_solves_per_hour),
+            quota_cpu_secs_per_day: self
+                .quota_cpu_secs_per_day
+                .or(fallback.quota_cpu_secs_per_day),
+            quota: self.quota.or(fallback.quota),
+            dispatch: self.dispatch.or(fallback.dispatch),
+            worker_heartbeat_timeout_secs: self
+                .worker_heartbeat_timeout_secs
+                .or(fallback.worker_heartbeat_timeout_secs),
+            dispatcher_url: self.dispatcher_url.or(fallback.dispatcher_url),
+            advertise_url: self.advertise_url.or(fallback.advertise_url),
+            worker_token: self.worker_token.or(fallback.worker_token),
+            admin_token: self.admin_token.or(fallback.admin_token),
+        }
+    }
+
+    /// Logging setup, using defaults for anything left unset
+    pub fn logging_config(&self) -> LoggingConfig {
+        let mut logging = LoggingConfig::default();
+        if let Some(filter) = &self.log_level {
+            logging.filter = filter.clone();
+        }
+        if let Some(format) = self.log_format {
+            logging.format = format;
+        }
+        logging
+    }
+
+    /// Build a [`ServerConfig`], using defaults for anything left unset
+    pub fn into_server_config(self) -> Result<ServerConfig, ConfigError> {
+        let addresses = match self.address {
+            Some(addresses) if !addresses.is_empty() => addresses,
+            _ => vec![DEFAULT_ADDRESS
+                .parse()
+                .map_err(|e| ConfigError::Invalid(format!("Bad default address: {}", e)))?],
+        };
+        let mut config = ServerConfig::new(addresses[0]);
+        for &address in &addresses[1..] {
+            config = config.with_address(address);
+        }
+
+        if let Some(backend) = self.default_backend {
+            config = config.with_default_backend(backend.into());
+        }
+        if let Some(max) = self.max_concurrent_solves {
+            let policy = self.overload_policy.map(Into::into).unwrap_or_default();
+            config = config.with_max_concurrent_solves(max, policy);
+        }
+        if self.preempt_low_priority.unwrap_or(false) {
+            config = config.with_preemption();
+        }
+        if let Some(max) = self.readiness_max_queued {
+            config = config.with_readiness_max_queued(max);
+        }
+        if let Some(token) = self.admin_token {
+            config = config.with_admin_token(token);
+        }
+        if let Some(instances) = self.solver_pool_size {
+            config = config.with_solver_pool(instances);
+        }
+        if let Some(secs) = self.idempotency_ttl_secs {
+            config = config.with_idempotency_ttl(Duration::from_secs(secs));
+        }
+        if self.max_model_sessions.is_some() || self.session_idle_timeout_secs.is_some() {
+            let max_sessions = self.max_model_sessions.unwrap_or(config.max_model_sessions);
+            let idle_timeout = self
+                .session_idle_timeout_secs
+                .map_or(config.session_idle_timeout, Duration::from_secs);
+            config = config.with_session_limits(max_sessions, idle_timeout);
+        }
+        if let Some(megabytes) = self.memory_limit_mb {
+            config = config.with_memory_limit_mb(megabytes);
+        }
+
+        let isolation_limits =
+            self.cpu_time_limit_secs.is_some() || self.wall_time_limit_secs.is_some();
+        if self.isolate.unwrap_or(isolation_limits) {
+            let mut limits = IsolationConfig::new();
+            if let Some(secs) = self.cpu_time_limit_secs {
+                limits = limits.with_cpu_time_limit_secs(secs);
+            }
+            if let Some(secs) = self.wall_time_limit_secs {
+                limits = limits.with_wall_time_limit(Duration::from_secs(secs));
+            }
+            config = config.with_subprocess_isolation(limits);
+        } else if isolation_limits {
+            return Err(ConfigError::Invalid(
+                "CPU and wall-time limits require isolation to be enabled".to_string(),
+            ));
+        }
+
+        let mut size_limits = ProblemSizeLimits::unlimited();
+        if let Some(max) = self.max_variables {
+            size_limits = size_limits.with_max_variables(max);
+        }
+        if let Some(max) = self.max_constraints {
+            size_limits = size_limits.with_max_constraints(max);
+        }
+        if let Some(max) = self.max_nonzeros {
+            size_limits = size_limits.with_max_nonzeros(max);
+        }
+        config = config.with_size_limits(size_limits);
+
+        if let Some(bytes) = self.max_message_size {
+            config = config.with_max_message_size(bytes);
+        }
+        for encoding in self.compression.unwrap_or_default() {
+            config = config.with_compression(encoding.into());
+        }
+
+        if let Some(path) = self.audit_log {
+            config = config.with_audit_log(path);
+        }
+        if let Some(path) = self.record_solves {
+            config = config.with_solve_recording(path);
+        }
+        if let Some(dir) = self.problem_dir {
+            config = config.with_problem_dir(dir);
+        }
+        if let Some(hosts) = self.url_allowed_hosts {
+            let mut fetching = UrlFetchConfig::new(hosts);
+            if let Some(bytes) = self.url_max_bytes {
+                fetching = fetching.with_max_bytes(bytes);
+            }
+            config = config.with_url_fetching(fetching);
+        }
+        if let Some(hosts) = self.webhook_allowed_hosts {
+            config = config.with_webhooks(WebhookConfig::new(hosts));
+        }
+        if let Some(url) = self.publish_nats_url {
+            let mut target = PublishTarget::nats(url);
+            if let Some(subject) = self.publish_nats_subject {
+                target = target.with_destination(subject);
+            }
+            config = config.with_publishing(target);
+        } else if self.publish_nats_subject.is_some() {
+            return Err(ConfigError::Invalid(
+                "A NATS subject requires a NATS URL to publish to".to_string(),
+            ));
+        }
+        if let Some(brokers) = self.publish_kafka_brokers {
+            let mut target = PublishTarget::kafka(brokers);
+            if let Some(topic) = self.publish_kafka_topic {
+                target = target.with_destination(topic);
+            }
+            config = config.with_publishing(target);
+        } else if self.publish_kafka_topic.is_some() {
+            return Err(ConfigError::Invalid(
+                "A Kafka topic requires Kafka brokers to publish to".to_string(),
+            ));
+        }
+
+        let routing = self
+            .route
+            .unwrap_or_default()
+            .into_iter()
+            .fold(RoutingPolicy::new(), |policy, route| {
+                policy.with_rule(route.into())
+            });
+        config = config.with_routing_policy(routing);
+
+        let limits = |solves_per_hour: Option<u64>, cpu_secs_per_day: Option<f64>| {
+            if cpu_secs_per_day.is_some_and(|secs| secs.is_nan() || secs < 0.0) {
+                return Err(ConfigError::Invalid(
+                    "CPU-seconds per day must be a non-negative number".to_string(),
+                ));
+            }
+            Ok(QuotaLimits {
+                solves_per_hour,
+                cpu_secs_per_day,
+            })
+        };
+        let mut quotas = QuotaPolicy::new().with_default_limits(limits(
+            self.quota_solves_per_hour,
+            self.quota_cpu_secs_per_day,
+        )?);
+        for quota in self.quota.unwrap_or_default() {
+            if quota.api_key.is_empty() {
+                return Err(ConfigError::Invalid(
+                    "A quota needs a non-empty api-key".to_string(),
+                ));
+            }
+            let key_limits = limits(quota.solves_per_hour, quota.cpu_secs_per_day)?;
+            quotas = quotas.with_key_limits(quota.api_key, key_limits);
+        }
+        config = config.with_quotas(quotas);
+
+        let worker_token = self.worker_token.filter(|token| !token.is_empty());
+        if self.dispatch.unwrap_or(false) {
+            let timeout = self
+                .worker_heartbeat_timeout_secs
+                .map_or(DEFAULT_HEARTBEAT_TIMEOUT, Duration::from_secs);
+            let token = worker_token.clone().ok_or_else(|| {
+                ConfigError::Invalid("Dispatch requires a worker token".to_string())
+            })?;
+            config = config.with_dispatch(timeout, token);
+        } else if self.worker_heartbeat_timeout_secs.is_some() {
+            return Err(ConfigError::Invalid(
+                "A worker heartbeat timeout requires dispatch to be enabled".to_string(),
+            ));
+        }
+        match (self.dispatcher_url, self.advertise_url) {
+            (Some(dispatcher), Some(advertise)) => {
+                let token = worker_token.ok_or_else(|| {
+                    ConfigError::Invalid(
+                        "A worker requires the dispatcher's worker token".to_string(),
+                    )
+                })?;
+                config = config.with_dispatcher(DispatcherLink::new(dispatcher, advertise, token));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(ConfigError::Invalid(
+                    "A worker needs both a dispatcher URL and its own advertise URL".to_string(),
+                ))
+            }
+        }
+
+        match (self.tls_cert, self.tls_key) {
+            (Some(cert), Some(key)) => {
+                let mut tls = TlsConfig::new(cert, key);
+                if let Some(ca) = self.tls_client_ca {
+                    tls = tls.with_client_ca(ca);
+                }
+                config = config.with_tls(tls);
+            }
+            (None, None) if self.tls_client_ca.is_none() => {}
+            _ => {
+                return Err(ConfigError::Invalid(
+                    "TLS needs both a certificate and a private key".to_string(),
+                ))
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Accept either `address = "..."` or `address = ["...", "..."]` in the config file
+fn one_or_many<'de, D>(deserializer: D) -> Result<Option<Vec<SocketAddr>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(SocketAddr),
+        Many(Vec<SocketAddr>),
+    }
+
+    Ok(Some(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(address) => vec![address],
+        OneOrMany::Many(addresses) => addresses,
+    }))
+}
@@ -0,0 +1,50 @@
+// Infrastructure: Logging setup
+// Structured events via `tracing`, as human-readable text or one JSON object per line
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when neither the settings nor RUST_LOG specify one
+pub const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Output format for log events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per event, for log aggregation
+    Json,
+}
+
+/// How the server should log
+#[derive(Debug, Clone)]
+pub struct LoggingConfig {
+    /// `tracing` filter directive, e.g. `info` or `letsopt=debug,tower=warn`
+    pub filter: String,
+    pub format: LogFormat,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            filter: std::env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_LOG_FILTER.to_string()),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+/// Install the global `tracing` subscriber
+pub fn init_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = EnvFilter::try_new(&config.filter)
+        .map_err(|e| format!("Invalid log filter '{}': {}", config.filter, e))?;
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let installed = match config.format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).try_init(),
+    };
+    installed.map_err(|e| format!("Cannot install logger: {}", e).into())
+}
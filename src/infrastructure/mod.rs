@@ -1,5 +1,14 @@
 // Infrastructure module
 
+pub mod config;
+pub mod logging;
+pub mod publishers;
 pub mod server;
+pub mod worker;
 
-pub use server::{start_server, ServerConfig};
+pub use config::{ConfigError, ServerSettings};
+pub use logging::{init_logging, LogFormat, LoggingConfig};
+pub use publishers::{connect_publisher, PublishTarget};
+pub use server::{start_server, ServerConfig, TlsConfig};
+pub use tonic::codec::CompressionEncoding;
+pub use worker::DispatcherLink;
@@ -0,0 +1,177 @@
+// Infrastructure: Broker clients for result publishing
+// Each target connects once at startup; solve events are serialized to JSON and
+// handed to the broker without waiting for delivery
+
+use std::sync::Arc;
+
+use crate::application::publishing::ResultPublisher;
+
+/// NATS subject used when none is configured
+pub const DEFAULT_NATS_SUBJECT: &str = "letsopt.solves";
+
+/// Kafka topic used when none is configured
+pub const DEFAULT_KAFKA_TOPIC: &str = "letsopt-solves";
+
+/// A broker destination for finished solves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublishTarget {
+    /// NATS server URL, e.g. `nats://localhost:4222`, and the subject to publish on
+    Nats { url: String, subject: String },
+    /// Comma-separated Kafka bootstrap servers and the topic to produce to
+    Kafka { brokers: String, topic: String },
+}
+
+impl PublishTarget {
+    pub fn nats(url: impl Into<String>) -> Self {
+        Self::Nats {
+            url: url.into(),
+            subject: DEFAULT_NATS_SUBJECT.to_string(),
+        }
+    }
+
+    pub fn kafka(brokers: impl Into<String>) -> Self {
+        Self::Kafka {
+            brokers: brokers.into(),
+            topic: DEFAULT_KAFKA_TOPIC.to_string(),
+        }
+    }
+
+    /// Publish on `subject` (NATS) or `topic` (Kafka) instead of the default
+    pub fn with_destination(mut self, destination: impl Into<String>) -> Self {
+        match &mut self {
+            Self::Nats { subject, .. } => *subject = destination.into(),
+            Self::Kafka { topic, .. } => *topic = destination.into(),
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for PublishTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nats { url, subject } => write!(f, "NATS {} ({})", url, subject),
+            Self::Kafka { brokers, topic } => write!(f, "Kafka {} ({})", brokers, topic),
+        }
+    }
+}
+
+/// Connect to `target`
+///
+/// Fails if the broker can't be reached or the server was built without the
+/// matching feature.
+pub async fn connect_publisher(target: &PublishTarget) -> Result<Arc<dyn ResultPublisher>, String> {
+    match target {
+        #[cfg(feature = "nats")]
+        PublishTarget::Nats { url, subject } => Ok(Arc::new(
+            nats::NatsPublisher::connect(url, subject.clone()).await?,
+        )),
+        #[cfg(feature = "kafka")]
+        PublishTarget::Kafka { brokers, topic } => Ok(Arc::new(kafka::KafkaPublisher::new(
+            brokers,
+            topic.clone(),
+        )?)),
+        #[allow(unreachable_patterns)]
+        target => Err(format!(
+            "Cannot publish to {}: built without the `{}` feature",
+            target,
+            match target {
+                PublishTarget::Nats { .. } => "nats",
+                PublishTarget::Kafka { .. } => "kafka",
+            }
+        )),
+    }
+}
+
+#[cfg(feature = "nats")]
+mod nats {
+    use tracing::warn;
+
+    use crate::application::publishing::{ResultPublisher, SolveEvent};
+
+    pub struct NatsPublisher {
+        client: async_nats::Client,
+        subject: String,
+    }
+
+    impl NatsPublisher {
+        pub async fn connect(url: &str, subject: String) -> Result<Self, String> {
+            let client = async_nats::connect(url)
+                .await
+                .map_err(|e| format!("Cannot connect to NATS at {}: {}", url, e))?;
+            Ok(Self { client, subject })
+        }
+    }
+
+    impl ResultPublisher for NatsPublisher {
+        fn publish(&self, event: &SolveEvent) {
+            let payload = match serde_json::to_vec(event) {
+                Ok(payload) => payload,
+                Err(e) => return warn!(error = %e, "Cannot serialize solve event"),
+            };
+            let client = self.client.clone();
+            let subject = self.subject.clone();
+            tokio::spawn(async move {
+                if let Err(e) = client.publish(subject.clone(), payload.into()).await {
+                    warn!(subject = %subject, error = %e, "Cannot publish solve event to NATS");
+                }
+            });
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{BaseRecord, DeliveryResult, ProducerContext, ThreadedProducer};
+    use rdkafka::ClientContext;
+    use tracing::warn;
+
+    use crate::application::publishing::{ResultPublisher, SolveEvent};
+
+    /// Logs messages the brokers reject once delivery has been attempted
+    struct LoggingContext;
+
+    impl ClientContext for LoggingContext {}
+
+    impl ProducerContext for LoggingContext {
+        type DeliveryOpaque = ();
+
+        fn delivery(&self, result: &DeliveryResult<'_>, _: ()) {
+            if let Err((e, _)) = result {
+                warn!(error = %e, "Cannot deliver solve event to Kafka");
+            }
+        }
+    }
+
+    pub struct KafkaPublisher {
+        producer: ThreadedProducer<LoggingContext>,
+        topic: String,
+    }
+
+    impl KafkaPublisher {
+        /// Kafka producers connect lazily, so an unreachable broker only shows up
+        /// as delivery failures in the log
+        pub fn new(brokers: &str, topic: String) -> Result<Self, String> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .create_with_context(LoggingContext)
+                .map_err(|e| format!("Cannot create Kafka producer for {}: {}", brokers, e))?;
+            Ok(Self { producer, topic })
+        }
+    }
+
+    impl ResultPublisher for KafkaPublisher {
+        fn publish(&self, event: &SolveEvent) {
+            let payload = match serde_json::to_vec(event) {
+                Ok(payload) => payload,
+                Err(e) => return warn!(error = %e, "Cannot serialize solve event"),
+            };
+            let record = BaseRecord::to(&self.topic)
+                .key(&event.request_id)
+                .payload(&payload);
+            if let Err((e, _)) = self.producer.send(record) {
+                warn!(topic = %self.topic, error = %e, "Cannot queue solve event for Kafka");
+            }
+        }
+    }
+}
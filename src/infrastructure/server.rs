@@ -1,48 +1,599 @@
 // Infrastructure: Server setup and configuration
 // Single Responsibility: Manage server lifecycle and configuration
 
+use socket2::{Domain, Protocol, Socket, Type};
 use std::net::SocketAddr;
-use tonic::transport::Server;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tonic::codec::CompressionEncoding;
+use tonic::server::NamedService;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::{info, warn};
 
+use crate::application::idempotency::DEFAULT_IDEMPOTENCY_TTL;
 use crate::application::mappers::lp_solver::linear_programming_solver_server::LinearProgrammingSolverServer;
-use crate::application::GrpcLpSolverService;
+use crate::application::sessions::{DEFAULT_MAX_SESSIONS, DEFAULT_SESSION_IDLE_TIMEOUT};
+use crate::application::{
+    DirectoryProblemRepository, GrpcLpSolverService, JsonlAuditLog, OverloadPolicy,
+    ProblemSizeLimits, QuotaPolicy, RoutingPolicy, SolveRecorder, UrlFetchConfig, WebhookConfig,
+    WorkerPool,
+};
+use crate::domain::solver_service::SolverService;
+use crate::domain::value_objects::SolverBackend;
+use crate::solver::{IsolationConfig, SolverRegistry};
+
+use super::publishers::{connect_publisher, PublishTarget};
+use super::worker::{work_for_dispatcher, DispatcherLink};
+
+/// Health service name that reports whether the process is up
+pub const LIVENESS_SERVICE: &str = "liveness";
+
+/// Health service name that reports whether the server takes new solves
+pub const READINESS_SERVICE: &str = "readiness";
+
+/// How often readiness is re-evaluated
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// PEM files for serving over TLS
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// CA used to verify client certificates; enables mutual TLS when set
+    pub client_ca_path: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            client_ca_path: None,
+        }
+    }
+
+    pub fn with_client_ca(mut self, ca_path: impl Into<PathBuf>) -> Self {
+        self.client_ca_path = Some(ca_path.into());
+        self
+    }
+
+    fn load(&self) -> Result<ServerTlsConfig, Box<dyn std::error::Error>> {
+        let read = |path: &PathBuf| {
+            std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))
+        };
+
+        let identity = Identity::from_pem(read(&self.cert_path)?, read(&self.key_path)?);
+        let mut tls = ServerTlsConfig::new().identity(identity);
+        if let Some(ca_path) = &self.client_ca_path {
+            tls = tls.client_ca_root(Certificate::from_pem(read(ca_path)?));
+        }
+        Ok(tls)
+    }
+}
 
 pub struct ServerConfig {
-    pub address: SocketAddr,
+    /// Addresses to listen on; the same service is served on all of them
+    pub addresses: Vec<SocketAddr>,
+    /// How long solve results stay available for idempotent replay
+    pub idempotency_ttl: Duration,
+    /// Maximum number of solves running at once (None = unlimited)
+    pub max_concurrent_solves: Option<usize>,
+    /// What to do with solve requests beyond the concurrency limit
+    pub overload_policy: OverloadPolicy,
+    /// Backend instances kept warm between solves, per backend (None = a new
+    /// instance per solve)
+    pub solver_pool_size: Option<usize>,
+    /// Run each solve in a resource-limited child process (None = in-process)
+    pub isolation: Option<IsolationConfig>,
+    /// Memory cap for every solve, in megabytes (None = unlimited)
+    pub memory_limit_mb: Option<u64>,
+    /// Caps on variables, constraints and non-zeros per request
+    pub size_limits: ProblemSizeLimits,
+    /// Largest request the server will decode, in bytes (None = tonic's 4 MB default)
+    pub max_decoding_message_size: Option<usize>,
+    /// Largest response the server will encode, in bytes (None = unlimited)
+    pub max_encoding_message_size: Option<usize>,
+    /// Compression encodings accepted from clients and used for responses when
+    /// the client advertises support
+    pub compression: Vec<CompressionEncoding>,
+    /// Backend used when a request asks for AUTO
+    pub default_backend: SolverBackend,
+    /// Serve over TLS (None = plaintext)
+    pub tls: Option<TlsConfig>,
+    /// Append a JSON line per solve to this file (None = no audit log)
+    pub audit_log: Option<PathBuf>,
+    /// Append each solve's problem and result to this file (None = no recording)
+    pub record_solves: Option<PathBuf>,
+    /// Directory the problem repository keeps saved problems in (None = disabled)
+    pub problem_dir: Option<PathBuf>,
+    /// Extra solvers clients can select by name, next to the built-in backends
+    pub custom_solvers: Vec<(String, Arc<dyn SolverService>)>,
+    /// Solver for AUTO requests, overriding `default_backend` (None = built-in)
+    pub default_solver: Option<Arc<dyn SolverService>>,
+    /// Rules sending AUTO requests to specific solvers; unmatched requests use
+    /// the default solver
+    pub routing: RoutingPolicy,
+    /// Hosts and limits for SolveFromUrl (None = SolveFromUrl is refused)
+    pub url_fetching: Option<UrlFetchConfig>,
+    /// Hosts SubmitProblem callbacks may go to (None = callbacks are refused)
+    pub webhooks: Option<WebhookConfig>,
+    /// Brokers every finished solve is announced on
+    pub publish: Vec<PublishTarget>,
+    /// Most model sessions kept at once
+    pub max_model_sessions: usize,
+    /// How long an unused model session is kept
+    pub session_idle_timeout: Duration,
+    /// Solves per hour and CPU-seconds per day allowed per API key
+    pub quotas: QuotaPolicy,
+    /// Let HIGH-priority solves stop running LOW-priority MIPs when every slot is busy
+    pub preempt_low_priority: bool,
+    /// Dispatch solves to registered workers, dropping those silent for this long
+    /// (None = solve locally)
+    pub dispatch_heartbeat_timeout: Option<Duration>,
+    /// Token workers have to present to register with this dispatcher
+    pub worker_token: String,
+    /// Dispatcher to register with as a worker (None = standalone)
+    pub dispatcher: Option<DispatcherLink>,
+    /// Report not ready while more solves than this are queued (None = never)
+    pub readiness_max_queued: Option<usize>,
+    /// Token admin RPCs such as ListJobs require (empty = refused)
+    pub admin_token: String,
 }
 
 impl ServerConfig {
     pub fn new(address: SocketAddr) -> Self {
-        Self { address }
+        Self {
+            addresses: vec![address],
+            idempotency_ttl: DEFAULT_IDEMPOTENCY_TTL,
+            max_concurrent_solves: None,
+            overload_policy: OverloadPolicy::default(),
+            solver_pool_size: None,
+            isolation: None,
+            memory_limit_mb: None,
+            size_limits: ProblemSizeLimits::unlimited(),
+            max_decoding_message_size: None,
+            max_encoding_message_size: None,
+            compression: Vec::new(),
+            default_backend: SolverBackend::Auto,
+            tls: None,
+            audit_log: None,
+            record_solves: None,
+            problem_dir: None,
+            custom_solvers: Vec::new(),
+            default_solver: None,
+            routing: RoutingPolicy::new(),
+            url_fetching: None,
+            webhooks: None,
+            publish: Vec::new(),
+            max_model_sessions: DEFAULT_MAX_SESSIONS,
+            session_idle_timeout: DEFAULT_SESSION_IDLE_TIMEOUT,
+            quotas: QuotaPolicy::new(),
+            preempt_low_priority: false,
+            dispatch_heartbeat_timeout: None,
+            worker_token: String::new(),
+            dispatcher: None,
+            readiness_max_queued: None,
+            admin_token: String::new(),
+        }
+    }
+
+    /// Also listen on `address`, e.g. an IPv6 address next to an IPv4 one
+    pub fn with_address(mut self, address: SocketAddr) -> Self {
+        if !self.addresses.contains(&address) {
+            self.addresses.push(address);
+        }
+        self
+    }
+
+    pub fn with_idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    pub fn with_max_concurrent_solves(mut self, max: usize, policy: OverloadPolicy) -> Self {
+        self.max_concurrent_solves = Some(max);
+        self.overload_policy = policy;
+        self
+    }
+
+    /// Reuse up to `instances` initialized instances of each backend across solves,
+    /// for workloads of many small problems
+    ///
+    /// Isolated solves run in their own process and don't use the pool.
+    pub fn with_solver_pool(mut self, instances: usize) -> Self {
+        self.solver_pool_size = Some(instances);
+        self
+    }
+
+    /// Keep at most `max_sessions` model sessions, each until it has been unused
+    /// for `idle_timeout`
+    pub fn with_session_limits(mut self, max_sessions: usize, idle_timeout: Duration) -> Self {
+        self.max_model_sessions = max_sessions;
+        self.session_idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn with_subprocess_isolation(mut self, limits: IsolationConfig) -> Self {
+        self.isolation = Some(limits);
+        self
+    }
+
+    pub fn with_memory_limit_mb(mut self, megabytes: u64) -> Self {
+        self.memory_limit_mb = Some(megabytes);
+        self
+    }
+
+    pub fn with_size_limits(mut self, limits: ProblemSizeLimits) -> Self {
+        self.size_limits = limits;
+        self
+    }
+
+    /// Raise (or lower) both the request and response size limits
+    pub fn with_max_message_size(mut self, bytes: usize) -> Self {
+        self.max_decoding_message_size = Some(bytes);
+        self.max_encoding_message_size = Some(bytes);
+        self
+    }
+
+    pub fn with_max_decoding_message_size(mut self, bytes: usize) -> Self {
+        self.max_decoding_message_size = Some(bytes);
+        self
+    }
+
+    pub fn with_max_encoding_message_size(mut self, bytes: usize) -> Self {
+        self.max_encoding_message_size = Some(bytes);
+        self
+    }
+
+    /// Enable a compression encoding (gzip or zstd)
+    pub fn with_compression(mut self, encoding: CompressionEncoding) -> Self {
+        if !self.compression.contains(&encoding) {
+            self.compression.push(encoding);
+        }
+        self
+    }
+
+    pub fn with_default_backend(mut self, backend: SolverBackend) -> Self {
+        self.default_backend = backend;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    pub fn with_solve_recording(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_solves = Some(path.into());
+        self
+    }
+
+    pub fn with_problem_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.problem_dir = Some(dir.into());
+        self
+    }
+
+    /// Register a custom solver that clients select with `solver_name`
+    ///
+    /// Reusing a built-in key (`cbc`, `highs`) replaces that backend for named
+    /// requests only.
+    pub fn with_solver(mut self, name: impl Into<String>, solver: Arc<dyn SolverService>) -> Self {
+        self.custom_solvers.push((name.into(), solver));
+        self
+    }
+
+    /// Solve AUTO requests with `solver`, e.g. a preconfigured `CoinCbcSolver`
+    pub fn with_default_solver(mut self, solver: Arc<dyn SolverService>) -> Self {
+        self.default_solver = Some(solver);
+        self
+    }
+
+    pub fn with_routing_policy(mut self, policy: RoutingPolicy) -> Self {
+        self.routing = policy;
+        self
+    }
+
+    /// Let clients submit problems by URL from the hosts `config` allows
+    pub fn with_url_fetching(mut self, config: UrlFetchConfig) -> Self {
+        self.url_fetching = Some(config);
+        self
+    }
+
+    /// Let SubmitProblem callbacks go to the hosts `config` allows
+    pub fn with_webhooks(mut self, config: WebhookConfig) -> Self {
+        self.webhooks = Some(config);
+        self
+    }
+
+    /// Announce finished solves on `target`, next to any added before
+    pub fn with_publishing(mut self, target: PublishTarget) -> Self {
+        self.publish.push(target);
+        self
+    }
+
+    /// When every solve slot is busy, let a HIGH-priority solve stop the most
+    /// recently started LOW-priority MIP, which fails with ABORTED
+    pub fn with_preemption(mut self) -> Self {
+        self.preempt_low_priority = true;
+        self
+    }
+
+    /// Limit how much each API key, sent as `x-api-key`, may solve
+    pub fn with_quotas(mut self, policy: QuotaPolicy) -> Self {
+        self.quotas = policy;
+        self
+    }
+
+    /// Run as a dispatcher: accept registrations from workers presenting
+    /// `worker_token` and farm solves out to them, forgetting any that miss
+    /// heartbeats for `heartbeat_timeout`
+    pub fn with_dispatch(
+        mut self,
+        heartbeat_timeout: Duration,
+        worker_token: impl Into<String>,
+    ) -> Self {
+        self.dispatch_heartbeat_timeout = Some(heartbeat_timeout);
+        self.worker_token = worker_token.into();
+        self
+    }
+
+    /// Run as a worker of a dispatcher, once the self-test passes
+    pub fn with_dispatcher(mut self, link: DispatcherLink) -> Self {
+        self.dispatcher = Some(link);
+        self
+    }
+
+    /// Report not ready while more than `max` solves wait for a slot, so load
+    /// balancers send new requests to other replicas
+    pub fn with_readiness_max_queued(mut self, max: usize) -> Self {
+        self.readiness_max_queued = Some(max);
+        self
+    }
+
+    /// Accept admin RPCs from callers presenting `token` in `x-admin-token`
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = token.into();
+        self
     }
 }
 
 pub async fn start_server(config: ServerConfig) -> Result<(), Box<dyn std::error::Error>> {
-    let service = GrpcLpSolverService::new();
+    let mut solvers = match config.solver_pool_size {
+        Some(instances) => SolverRegistry::builtin_pooled(instances),
+        None => SolverRegistry::builtin(),
+    };
+    solvers.remove_broken();
+    if solvers.is_empty() {
+        warn!("No solver backend passed its startup probe; every solve will fail");
+    }
+    for (name, solver) in &config.custom_solvers {
+        solvers.register_named(name.clone(), Arc::clone(solver));
+    }
+    // A typo in a rule would otherwise only show up as failed solves
+    for rule in config.routing.rules() {
+        if solvers.get_named(&rule.solver).is_none() {
+            return Err(format!(
+                "Routing rule refers to unknown solver '{}'; available: {}",
+                rule.solver,
+                solvers.keys().collect::<Vec<_>>().join(", ")
+            )
+            .into());
+        }
+    }
+
+    let mut service = GrpcLpSolverService::new()
+        .with_solver_registry(Arc::new(solvers))
+        .with_routing_policy(config.routing.clone())
+        .with_quotas(config.quotas.clone())
+        .with_idempotency_ttl(config.idempotency_ttl)
+        .with_session_limits(config.max_model_sessions, config.session_idle_timeout)
+        .with_size_limits(config.size_limits)
+        .with_default_backend(config.default_backend)
+        .with_admin_token(config.admin_token.clone());
+    if let Some(solver) = &config.default_solver {
+        service = service.with_default_solver(Arc::clone(solver));
+    }
+    if let Some(max) = config.max_concurrent_solves {
+        service = service.with_concurrency_limit(max, config.overload_policy);
+    }
+    if config.preempt_low_priority {
+        service = service.with_preemption();
+    }
+    if let Some(timeout) = config.dispatch_heartbeat_timeout {
+        service = service.with_workers(Arc::new(WorkerPool::new(
+            timeout,
+            config.worker_token.clone(),
+        )));
+    }
+    if let Some(limits) = config.isolation.clone() {
+        service = service.with_subprocess_isolation(limits);
+    }
+    if let Some(megabytes) = config.memory_limit_mb {
+        service = service.with_memory_limit_mb(megabytes);
+    }
+    if let Some(fetching) = &config.url_fetching {
+        service = service.with_url_fetching(fetching.clone());
+    }
+    if let Some(webhooks) = &config.webhooks {
+        service = service.with_webhooks(webhooks.clone());
+    }
+    for target in &config.publish {
+        service = service.with_publisher(connect_publisher(target).await?);
+    }
+    if let Some(path) = &config.audit_log {
+        let log = JsonlAuditLog::open(path)
+            .map_err(|e| format!("Cannot open audit log {}: {}", path.display(), e))?;
+        service = service.with_audit_sink(Arc::new(log));
+    }
+    if let Some(path) = &config.record_solves {
+        let recorder = SolveRecorder::open(path)
+            .map_err(|e| format!("Cannot open solve recording {}: {}", path.display(), e))?;
+        service = service.with_recorder(Arc::new(recorder));
+    }
+    if let Some(dir) = &config.problem_dir {
+        let repository = DirectoryProblemRepository::open(dir)
+            .map_err(|e| format!("Cannot open problem directory {}: {}", dir.display(), e))?;
+        service = service.with_problem_repository(Arc::new(repository));
+    }
+
+    let service = Arc::new(service);
+    let mut grpc = LinearProgrammingSolverServer::from_arc(Arc::clone(&service));
+    if let Some(bytes) = config.max_decoding_message_size {
+        grpc = grpc.max_decoding_message_size(bytes);
+    }
+    if let Some(bytes) = config.max_encoding_message_size {
+        grpc = grpc.max_encoding_message_size(bytes);
+    }
+    for &encoding in &config.compression {
+        grpc = grpc.accept_compressed(encoding).send_compressed(encoding);
+    }
+
+    let mut builder = Server::builder();
+    if let Some(tls) = &config.tls {
+        builder = builder.tls_config(tls.load()?)?;
+    }
 
-    print_banner(&config.address);
+    // Bind everything up front so a bad address fails startup instead of one listener
+    let mut incoming = Vec::with_capacity(config.addresses.len());
+    for &address in &config.addresses {
+        let listen_error =
+            |e: &dyn std::fmt::Display| format!("Cannot listen on {}: {}", address, e);
+        let listener = bind_listener(address, &config.addresses).map_err(|e| listen_error(&e))?;
+        incoming
+            .push(TcpIncoming::from_listener(listener, true, None).map_err(|e| listen_error(&e))?);
+    }
 
-    Server::builder()
-        .add_service(LinearProgrammingSolverServer::new(service))
-        .serve(config.address)
-        .await?;
+    info!(
+        addresses = ?config.addresses,
+        tls = config.tls.is_some(),
+        default_backend = %config.default_backend,
+        default_solver = config.default_solver.as_ref().map(|solver| solver.name()),
+        max_concurrent_solves = config.max_concurrent_solves,
+        solver_pool_size = config.solver_pool_size,
+        isolated = config.isolation.is_some(),
+        routing_rules = config.routing.rules().len(),
+        url_fetching = config.url_fetching.is_some(),
+        webhooks = config.webhooks.is_some(),
+        publish = ?config.publish.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        quotas = !config.quotas.is_unlimited(),
+        dispatcher = config.dispatch_heartbeat_timeout.is_some(),
+        worker_of = config.dispatcher.as_ref().map(|link| link.dispatcher_url.as_str()),
+        readiness_max_queued = config.readiness_max_queued,
+        "LetsOpt server listening"
+    );
+
+    // "liveness" is up as long as the process serves; readiness ("", "readiness" and
+    // the solver service) waits for the self-test and then follows `readiness`
+    let (mut health, health_service) = tonic_health::server::health_reporter();
+    health
+        .set_service_status(LIVENESS_SERVICE, ServingStatus::Serving)
+        .await;
+    set_readiness(&mut health, ServingStatus::NotServing).await;
+
+    let servers = incoming.into_iter().map(|incoming| {
+        builder
+            .clone()
+            .add_service(health_service.clone())
+            .add_service(grpc.clone())
+            .serve_with_incoming(incoming)
+    });
+    let ready = async {
+        service
+            .self_test()
+            .await
+            .map_err(|e| format!("Startup self-test failed: {}", e))?;
+        info!("Self-test passed");
+        let track_readiness = track_readiness(&service, health, config.readiness_max_queued);
+        // Only a server that can solve joins a dispatcher's rotation
+        match config.dispatcher.clone() {
+            Some(link) => {
+                let work = work_for_dispatcher(link, config.max_concurrent_solves);
+                futures::future::join(track_readiness, work).await.1?;
+            }
+            None => track_readiness.await,
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+    let serve = async {
+        futures::future::try_join_all(servers).await?;
+        Ok(())
+    };
+    futures::future::try_join(ready, serve).await?;
 
     Ok(())
 }
 
-fn print_banner(address: &SocketAddr) {
-    println!("\n╔═══════════════════════════════════════════════════════════╗");
-    println!("║  LetsOpt - Linear & Mixed-Integer Programming Solver      ║");
-    println!("╠═══════════════════════════════════════════════════════════╣");
-    println!("║  Powered by: COIN-OR CBC & HiGHS                          ║");
-    println!("║  Listening on: {:42} ║", address);
-    println!("╠═══════════════════════════════════════════════════════════╣");
-    println!("║  Supported Features:                                      ║");
-    println!("║    ✓ Linear Programming (LP)                              ║");
-    println!("║    ✓ Mixed-Integer Programming (MIP)                      ║");
-    println!("║    ✓ Binary Variables                                     ║");
-    println!("║    ✓ Integer Variables                                    ║");
-    println!("║    ✓ Dynamic Solver Selection                             ║");
-    println!("╚═══════════════════════════════════════════════════════════╝");
-    println!("\n🚀 Ready to solve optimization problems!\n");
+/// Set the readiness of the server as a whole and of the solver service
+async fn set_readiness(health: &mut HealthReporter, status: ServingStatus) {
+    health.set_service_status("", status).await;
+    health.set_service_status(READINESS_SERVICE, status).await;
+    health
+        .set_service_status(
+            LinearProgrammingSolverServer::<GrpcLpSolverService>::NAME,
+            status,
+        )
+        .await;
+}
+
+/// Keep the readiness status in line with the service, logging each flip
+async fn track_readiness(
+    service: &GrpcLpSolverService,
+    mut health: HealthReporter,
+    max_queued: Option<usize>,
+) {
+    let mut ready = None;
+    loop {
+        let readiness = service.readiness(max_queued);
+        if ready != Some(readiness.is_ok()) {
+            ready = Some(readiness.is_ok());
+            match &readiness {
+                Ok(()) => {
+                    info!("Ready");
+                    set_readiness(&mut health, ServingStatus::Serving).await;
+                }
+                Err(reason) => {
+                    warn!(reason, "Not ready");
+                    set_readiness(&mut health, ServingStatus::NotServing).await;
+                }
+            }
+        }
+        tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+    }
+}
+
+/// Bind a listening socket for `address`
+///
+/// IPv6 sockets are made IPv6-only when an IPv4 address on the same port is also
+/// configured, so explicit dual-stack setups don't collide; otherwise the OS
+/// default applies.
+fn bind_listener(address: SocketAddr, all: &[SocketAddr]) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    if address.is_ipv6() {
+        let ipv4_on_port = all
+            .iter()
+            .any(|other| other.is_ipv4() && other.port() == address.port());
+        if ipv4_on_port {
+            socket.set_only_v6(true)?;
+        }
+    }
+    socket.set_reuse_address(true)?;
+    socket.bind(&address.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+
+    TcpListener::from_std(socket.into())
 }
@@ -0,0 +1,121 @@
+// Infrastructure: Worker side of distributed mode
+// Registers this server with a dispatcher and keeps it in rotation with heartbeats,
+// registering again whenever the dispatcher forgets it
+
+use std::time::Duration;
+
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request};
+use tracing::{info, warn};
+
+use crate::application::mappers::lp_solver;
+use crate::application::workers::WORKER_TOKEN_HEADER;
+
+use lp_solver::linear_programming_solver_client::LinearProgrammingSolverClient;
+
+/// Wait before retrying when the dispatcher can't be reached
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// A dispatcher to work for, and the address it reaches this server at
+#[derive(Debug, Clone)]
+pub struct DispatcherLink {
+    /// gRPC endpoint of the dispatcher, e.g. `http://dispatcher:50051`
+    pub dispatcher_url: String,
+    /// gRPC endpoint of this server as the dispatcher sees it
+    pub advertise_url: String,
+    /// Shared secret the dispatcher admits workers by
+    pub token: String,
+}
+
+impl DispatcherLink {
+    pub fn new(
+        dispatcher_url: impl Into<String>,
+        advertise_url: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            dispatcher_url: dispatcher_url.into(),
+            advertise_url: advertise_url.into(),
+            token: token.into(),
+        }
+    }
+
+    /// `message` with the worker token attached
+    fn request<T>(&self, message: T) -> Result<Request<T>, Box<dyn std::error::Error>> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert(WORKER_TOKEN_HEADER, self.token.parse()?);
+        Ok(request)
+    }
+}
+
+/// Register with the dispatcher and send heartbeats for as long as the server runs
+///
+/// Only fails on a malformed dispatcher URL or token; an unreachable dispatcher is
+/// retried.
+pub async fn work_for_dispatcher(
+    link: DispatcherLink,
+    capacity: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let endpoint = Endpoint::from_shared(link.dispatcher_url.clone())
+        .map_err(|e| format!("Invalid dispatcher URL '{}': {}", link.dispatcher_url, e))?;
+    let mut dispatcher = LinearProgrammingSolverClient::new(endpoint.connect_lazy());
+    let registration = lp_solver::WorkerRegistration {
+        address: link.advertise_url.clone(),
+        capacity: capacity.unwrap_or_default() as u32,
+    };
+
+    loop {
+        let lease = match dispatcher
+            .register_worker(link.request(registration.clone())?)
+            .await
+        {
+            Ok(lease) => lease.into_inner(),
+            Err(status) => {
+                warn!(
+                    dispatcher = link.dispatcher_url,
+                    error = status.message(),
+                    "Cannot register with dispatcher; retrying"
+                );
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        info!(
+            dispatcher = link.dispatcher_url,
+            worker_id = lease.worker_id,
+            "Registered with dispatcher"
+        );
+        keep_alive(&mut dispatcher, &link, lease).await?;
+    }
+}
+
+/// Send heartbeats until the dispatcher no longer knows this worker
+async fn keep_alive(
+    dispatcher: &mut LinearProgrammingSolverClient<Channel>,
+    link: &DispatcherLink,
+    mut lease: lp_solver::WorkerLease,
+) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        let interval = Duration::from_secs_f64(lease.heartbeat_interval_secs.max(0.1));
+        tokio::time::sleep(interval).await;
+
+        let heartbeat = lp_solver::WorkerHeartbeatRequest {
+            worker_id: lease.worker_id.clone(),
+        };
+        match dispatcher.worker_heartbeat(link.request(heartbeat)?).await {
+            Ok(renewed) => lease = renewed.into_inner(),
+            Err(status) if status.code() == Code::NotFound => {
+                warn!(
+                    worker_id = lease.worker_id,
+                    "Dispatcher dropped this worker; registering again"
+                );
+                return Ok(());
+            }
+            Err(status) => {
+                warn!(error = status.message(), "Heartbeat to dispatcher failed");
+            }
+        }
+    }
+}
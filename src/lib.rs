@@ -1,23 +1,66 @@
 // Domain layer: Business logic and rules
 pub mod domain;
 
-// Application layer: Use cases and service orchestration
+// Application layer: Use cases and service orchestration; without `server`, only the
+// protobuf messages and their mappers
+#[cfg(feature = "proto")]
 pub mod application;
 
 // Infrastructure layer: External concerns (gRPC, server)
+#[cfg(feature = "server")]
 pub mod infrastructure;
 
-// Solver adapters: Concrete implementations of SolverService
+// Solver adapters: Concrete implementations of SolverService, usable without gRPC
+#[cfg(feature = "solvers")]
 pub mod solver;
 
+// Client: Typed wrapper around the gRPC service
+#[cfg(feature = "client")]
+pub mod client;
+
+// C API: The solver layer for in-process embedding from C, C++ or C#
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 // Re-export commonly used types
 pub use domain::{
-    Constraint, ConstraintType, ObjectiveFunction, OptimizationProblem, OptimizationType, Solution,
-    SolutionStatus, SolverError, SolverService, Variable, VariableType,
+    dot, export_solution, sum_over, validate_problem, BinPacking, BinPackingSpec, Constraint,
+    ConstraintType, Coverage, ExportFormat, IntervalUncertainty, LinearConstraint, LinearExpr,
+    Model, ModelSession, ModelUpdate, ObjectiveFunction, OptimizationProblem, OptimizationType,
+    ParametricSweep, ProblemFormat, RobustCounterpart, Scenario, SetCover, SetCoverSpec, Solution,
+    SolutionDiff, SolutionStatus, SolverError, SolverService, SweepTarget, SymmetryBreaking,
+    VarRef, Variable, VariableArray, VariableGrid, VariableGrid3, VariableType,
+};
+
+#[cfg(feature = "proto")]
+pub use application::{ParetoFrontier, ParetoPoint, ParetoSolver};
+
+#[cfg(feature = "server")]
+pub use application::{
+    AuditRecord, AuditSink, BendersCut, BendersMaster, BendersSolution, BendersSolver,
+    BendersSubproblem, GrpcLpSolverService, HorizonModel, JsonlAuditLog, LinearSubproblem,
+    OverloadPolicy, ProblemKind, ProblemSizeLimits, QuotaLimits, QuotaPolicy, ResultPublisher,
+    RollingHorizonSolution, RollingHorizonSolver, RoutingPolicy, RoutingRule, SolveEvent,
+    TourModel, TourSolution, TourSolver, UrlFetchConfig,
+};
+
+#[cfg(feature = "server")]
+pub use infrastructure::{
+    init_logging, start_server, CompressionEncoding, DispatcherLink, LogFormat, LoggingConfig,
+    PublishTarget, ServerConfig, ServerSettings, TlsConfig,
 };
 
-pub use application::GrpcLpSolverService;
+#[cfg(feature = "solvers")]
+pub use solver::{
+    run_solve_worker, AssignmentSolver, AutoSolver, BackendSelection, FallbackSolver,
+    IsolationConfig, KnapsackSolver, NetworkFlowSolver, SolverFactory, SolverInfo, SolverRegistry,
+    SubprocessSolver,
+};
 
-pub use infrastructure::{start_server, ServerConfig};
+#[cfg(feature = "client")]
+pub use client::{ChannelPool, ClientError, LetsOptClient, RetryPolicy};
 
-pub use solver::{CoinCbcSolver, HighsSolver, SolverFactory};
+#[cfg(feature = "cbc")]
+pub use solver::CoinCbcSolver;
+#[cfg(feature = "highs")]
+pub use solver::HighsSolver;
@@ -1,13 +1,31 @@
-use letsopt::{start_server, ServerConfig};
+use std::process::ExitCode;
+
+use letsopt::{
+    init_logging, run_solve_worker, solver::SOLVE_WORKER_ARG, start_server, ServerSettings,
+};
+
+fn main() -> ExitCode {
+    // Isolated solves re-launch this binary as a one-shot worker
+    if std::env::args().nth(1).as_deref() == Some(SOLVE_WORKER_ARG) {
+        return run_solve_worker();
+    }
+
+    match serve() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Parse address
-    let address = "0.0.0.0:50051".parse()?;
+async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    // Merge CLI flags, environment and config file
+    let settings = ServerSettings::load()?;
+    init_logging(&settings.logging_config())?;
 
-    // Configure and start server
-    let config = ServerConfig::new(address);
-    start_server(config).await?;
+    start_server(settings.into_server_config()?).await?;
 
     Ok(())
 }
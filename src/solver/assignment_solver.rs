@@ -0,0 +1,477 @@
+// Assignment Solver
+// Solves assignment problems (each agent does exactly one task, each task gets
+// exactly one agent) with the Hungarian algorithm in O(n^3), without branching
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution, SolverStatistics},
+    solver_service::{
+        report_solution, CancellationToken, NullObserver, Result, SolveObserver, SolverError,
+        SolverService,
+    },
+    value_objects::{ConstraintType, OptimizationType, VariableType},
+};
+use crate::solver::knapsack_solver::{count, is_binary};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
+
+/// Solver for binary problems whose rows are all `== 1` and split into one set
+/// of agent rows and one set of task rows, every variable pairing one of each
+///
+/// Anything else, and assignments that turn out to need a pair with no variable,
+/// is passed to the fallback solver, so it is safe to use for every problem.
+pub struct AssignmentSolver {
+    fallback: Arc<dyn SolverService>,
+}
+
+impl AssignmentSolver {
+    pub fn new(fallback: Arc<dyn SolverService>) -> Self {
+        Self { fallback }
+    }
+
+    /// Whether `problem` has assignment structure this solver handles itself
+    pub fn recognizes(problem: &OptimizationProblem) -> bool {
+        Assignment::detect(problem).is_some()
+    }
+
+    fn delegate(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        let mut solution = self.fallback.solve_cancellable(problem, observer, cancel)?;
+        solution
+            .statistics
+            .solved_by
+            .get_or_insert_with(|| self.fallback.name().to_string());
+        Ok(solution)
+    }
+}
+
+impl SolverService for AssignmentSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        self.validate(problem)?;
+        let Some(assignment) = Assignment::detect(problem) else {
+            debug!(
+                fallback = self.fallback.name(),
+                "Not an assignment problem, delegating"
+            );
+            return self.delegate(problem, observer, cancel);
+        };
+
+        let start_time = Instant::now();
+        let Some(values) = assignment.solve(problem.num_variables(), cancel)? else {
+            // Every optimum pairs an agent with a task it has no variable for, so
+            // there is no feasible assignment; let the fallback prove it
+            debug!("No complete assignment, delegating");
+            return self.delegate(problem, observer, cancel);
+        };
+        let objective = problem
+            .objective
+            .coefficients
+            .iter()
+            .zip(&values)
+            .map(|(c, x)| c * x)
+            .sum();
+
+        let mut solution = Solution::optimal(objective, values);
+        solution.message = format!("Optimal assignment found for '{}'", problem.name);
+        solution.statistics = SolverStatistics {
+            solve_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+            num_variables: problem.num_variables() as u32,
+            num_constraints: problem.constraints.len() as u32,
+            num_integer_vars: count(problem, VariableType::Integer),
+            num_binary_vars: count(problem, VariableType::Binary),
+            ..SolverStatistics::default()
+        };
+        report_solution(observer, &solution);
+        Ok(solution)
+    }
+
+    fn name(&self) -> &str {
+        "Assignment"
+    }
+
+    fn supports_mip(&self) -> bool {
+        true
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.fallback.supports_lp()
+    }
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec!["Assignment (Hungarian algorithm)".to_string()]
+    }
+}
+
+/// A square assignment problem, restated as minimizing cost
+struct Assignment {
+    /// `costs[a][t]`: cheapest `(column, cost)` pairing agent `a` with task `t`
+    costs: Vec<Vec<Option<(usize, f64)>>>,
+}
+
+impl Assignment {
+    /// Extract the cost matrix, if the problem is an assignment problem
+    fn detect(problem: &OptimizationProblem) -> Option<Self> {
+        let n = problem.num_variables();
+        let rows = &problem.constraints;
+        if rows.is_empty()
+            || problem.variables.len() != n
+            || !problem.variables.iter().all(is_binary)
+            || !rows.iter().all(|row| {
+                row.constraint_type == ConstraintType::Equal
+                    && row.bound == 1.0
+                    && row.num_variables() == n
+                    && row.coefficients.values().iter().all(|&a| a == 1.0)
+            })
+        {
+            return None;
+        }
+
+        // Each variable is an edge between the two rows it appears in
+        let mut edges = vec![Vec::with_capacity(2); n];
+        for (r, row) in rows.iter().enumerate() {
+            for &j in row.coefficients.indices() {
+                edges[j].push(r);
+            }
+        }
+        if edges.iter().any(|rows| rows.len() != 2) {
+            return None;
+        }
+        let mut neighbours = vec![Vec::new(); rows.len()];
+        for rows in &edges {
+            neighbours[rows[0]].push(rows[1]);
+            neighbours[rows[1]].push(rows[0]);
+        }
+
+        // Two-colour the rows into agents and tasks, numbering each side
+        let mut side: Vec<Option<(bool, usize)>> = vec![None; rows.len()];
+        let mut sizes = [0, 0];
+        for start in 0..rows.len() {
+            if side[start].is_some() {
+                continue;
+            }
+            side[start] = Some((false, sizes[0]));
+            sizes[0] += 1;
+            let mut queue = VecDeque::from([start]);
+            while let Some(r) = queue.pop_front() {
+                let (colour, _) = side[r]?;
+                for &s in &neighbours[r] {
+                    match side[s] {
+                        Some((other, _)) if other == colour => return None,
+                        Some(_) => {}
+                        None => {
+                            let other = !colour;
+                            side[s] = Some((other, sizes[other as usize]));
+                            sizes[other as usize] += 1;
+                            queue.push_back(s);
+                        }
+                    }
+                }
+            }
+        }
+        // Rows nothing appears in make the problem infeasible, and so does a
+        // component with more agents than tasks; leave those to the fallback
+        if sizes[0] != sizes[1] || neighbours.iter().any(Vec::is_empty) {
+            return None;
+        }
+
+        let sign = match problem.objective.optimization_type {
+            OptimizationType::Minimize => 1.0,
+            OptimizationType::Maximize => -1.0,
+        };
+        let size = sizes[0];
+        let mut costs = vec![vec![None; size]; size];
+        for (j, rows) in edges.iter().enumerate() {
+            let cost = sign * problem.objective.coefficients[j];
+            if !cost.is_finite() {
+                return None;
+            }
+            let (mut a, mut t) = (side[rows[0]]?, side[rows[1]]?);
+            if a.0 {
+                std::mem::swap(&mut a, &mut t);
+            }
+            let cell: &mut Option<(usize, f64)> = &mut costs[a.1][t.1];
+            if cell.is_none_or(|(_, best)| cost < best) {
+                *cell = Some((j, cost));
+            }
+        }
+
+        Some(Self { costs })
+    }
+
+    /// Optimal 0/1 values for all `n` columns, or None if no assignment uses
+    /// only pairs that have a variable
+    fn solve(&self, n: usize, cancel: &CancellationToken) -> Result<Option<Vec<f64>>> {
+        let size = self.costs.len();
+        // Missing pairs cost more than any assignment made of existing pairs
+        let total: f64 = self
+            .costs
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|(_, cost)| cost.abs())
+            .sum();
+        let missing = 2.0 * total + 1.0;
+        let cost = |a: usize, t: usize| self.costs[a][t].map_or(missing, |(_, cost)| cost);
+
+        // Shortest augmenting paths with potentials, 1-based with 0 as a sentinel;
+        // agent_of[t] is the agent currently given task t
+        let mut u = vec![0.0; size + 1];
+        let mut v = vec![0.0; size + 1];
+        let mut agent_of = vec![0; size + 1];
+        let mut way = vec![0; size + 1];
+        for a in 1..=size {
+            if cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            agent_of[0] = a;
+            let mut t0 = 0;
+            let mut min_slack = vec![f64::INFINITY; size + 1];
+            let mut used = vec![false; size + 1];
+            loop {
+                used[t0] = true;
+                let a0 = agent_of[t0];
+                let mut delta = f64::INFINITY;
+                let mut t1 = 0;
+                for t in 1..=size {
+                    if used[t] {
+                        continue;
+                    }
+                    let slack = cost(a0 - 1, t - 1) - u[a0] - v[t];
+                    if slack < min_slack[t] {
+                        min_slack[t] = slack;
+                        way[t] = t0;
+                    }
+                    if min_slack[t] < delta {
+                        delta = min_slack[t];
+                        t1 = t;
+                    }
+                }
+                for t in 0..=size {
+                    if used[t] {
+                        u[agent_of[t]] += delta;
+                        v[t] -= delta;
+                    } else {
+                        min_slack[t] -= delta;
+                    }
+                }
+                t0 = t1;
+                if agent_of[t0] == 0 {
+                    break;
+                }
+            }
+            while t0 != 0 {
+                let t1 = way[t0];
+                agent_of[t0] = agent_of[t1];
+                t0 = t1;
+            }
+        }
+
+        let mut values = vec![0.0; n];
+        for (t, &a) in agent_of.iter().enumerate().skip(1) {
+            match self.costs[a - 1][t - 1] {
+                Some((j, _)) => values[j] = 1.0,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{Constraint, ObjectiveFunction, Variable};
+    use crate::domain::value_objects::SolutionStatus;
+    use crate::solver::testing::StubFallback;
+    use crate::solver::NetworkFlowSolver;
+
+    /// Assignment of agents to tasks with `costs[a][t]`, None for a pair that has
+    /// no variable; one `== 1` row per agent, then one per task
+    fn assignment_problem(
+        optimization_type: OptimizationType,
+        costs: &[Vec<Option<f64>>],
+    ) -> OptimizationProblem {
+        let tasks = costs[0].len();
+        let pairs: Vec<(usize, usize, f64)> = costs
+            .iter()
+            .enumerate()
+            .flat_map(|(a, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(t, cost)| cost.map(|cost| (a, t, cost)))
+            })
+            .collect();
+        let objective = pairs.iter().map(|&(_, _, cost)| cost).collect();
+        let variables = pairs
+            .iter()
+            .map(|&(a, t, _)| Variable::binary(format!("x_{}_{}", a, t)))
+            .collect();
+        let row = |member: &dyn Fn(usize, usize) -> bool| {
+            let coefficients: Vec<f64> = pairs
+                .iter()
+                .map(|&(a, t, _)| if member(a, t) { 1.0 } else { 0.0 })
+                .collect();
+            Constraint::new(ConstraintType::Equal, coefficients, 1.0)
+        };
+
+        let mut problem =
+            OptimizationProblem::new(ObjectiveFunction::new(optimization_type, objective))
+                .with_variables(variables);
+        for agent in 0..costs.len() {
+            problem = problem.add_constraint(row(&|a, _| a == agent));
+        }
+        for task in 0..tasks {
+            problem = problem.add_constraint(row(&|_, t| t == task));
+        }
+        problem
+    }
+
+    fn full(costs: &[&[f64]]) -> Vec<Vec<Option<f64>>> {
+        costs
+            .iter()
+            .map(|row| row.iter().copied().map(Some).collect())
+            .collect()
+    }
+
+    fn solver() -> AssignmentSolver {
+        AssignmentSolver::new(Arc::new(StubFallback))
+    }
+
+    #[test]
+    fn square_assignment_finds_the_known_optimum() {
+        // 0->1, 1->0, 2->2 at 2 + 2 + 1 = 5 is the cheapest of the six orders
+        let costs = full(&[&[4.0, 2.0, 8.0], &[2.0, 3.0, 7.0], &[3.0, 1.0, 1.0]]);
+        let problem = assignment_problem(OptimizationType::Minimize, &costs);
+        assert!(AssignmentSolver::recognizes(&problem));
+
+        let solution = solver().solve(&problem).unwrap();
+        assert_eq!(solution.status, SolutionStatus::Optimal);
+        assert_eq!(solution.optimal_value, Some(5.0));
+        assert_eq!(
+            solution.variable_values,
+            [0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]
+        );
+
+        let problem = assignment_problem(OptimizationType::Maximize, &costs);
+        let solution = solver().solve(&problem).unwrap();
+        assert_eq!(solution.optimal_value, Some(8.0 + 3.0 + 3.0));
+    }
+
+    #[test]
+    fn forbidden_pairs_are_avoided() {
+        // Without 0->1 the cheapest order left is the diagonal at 4 + 3 + 1 = 8
+        let mut costs = full(&[&[4.0, 2.0, 8.0], &[2.0, 3.0, 7.0], &[3.0, 1.0, 1.0]]);
+        costs[0][1] = None;
+        let problem = assignment_problem(OptimizationType::Minimize, &costs);
+        assert!(AssignmentSolver::recognizes(&problem));
+
+        let solution = solver().solve(&problem).unwrap();
+        assert_eq!(solution.optimal_value, Some(8.0));
+        assert_eq!(
+            solution.variable_values,
+            [1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn forbidden_pairs_that_leave_no_assignment_go_to_the_fallback() {
+        // Agents 0 and 1 can both only do task 0
+        let costs = vec![
+            vec![Some(1.0), None, None],
+            vec![Some(1.0), None, None],
+            vec![Some(1.0), Some(1.0), Some(1.0)],
+        ];
+        let problem = assignment_problem(OptimizationType::Minimize, &costs);
+        assert!(AssignmentSolver::recognizes(&problem));
+
+        let solution = solver().solve(&problem).unwrap();
+        assert_eq!(solution.message, StubFallback::MESSAGE);
+    }
+
+    #[test]
+    fn infinite_costs_are_rejected() {
+        let costs = full(&[&[1.0, f64::INFINITY], &[2.0, 3.0]]);
+        let problem = assignment_problem(OptimizationType::Minimize, &costs);
+
+        assert!(matches!(
+            solver().solve(&problem),
+            Err(SolverError::InvalidProblem(_))
+        ));
+    }
+
+    #[test]
+    fn rectangular_assignments_go_to_the_fallback() {
+        // Three tasks for two agents can't all get exactly one agent
+        let costs = full(&[&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]]);
+        let problem = assignment_problem(OptimizationType::Minimize, &costs);
+        assert!(!AssignmentSolver::recognizes(&problem));
+
+        let solution = solver().solve(&problem).unwrap();
+        assert_eq!(solution.message, StubFallback::MESSAGE);
+    }
+
+    #[test]
+    fn optimum_matches_the_lp_relaxation() {
+        // The assignment polytope is integral, so the LP optimum over 0 <= x <= 1,
+        // here from the network simplex, must equal the Hungarian one
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) as f64 % 100.0
+        };
+        for size in 2..=7 {
+            let costs: Vec<Vec<Option<f64>>> = (0..size)
+                .map(|_| (0..size).map(|_| Some(next())).collect())
+                .collect();
+            let problem = assignment_problem(OptimizationType::Minimize, &costs);
+            let mut relaxed = problem.clone();
+            for variable in &mut relaxed.variables {
+                *variable = Variable::continuous(variable.name.clone()).with_bounds(0.0, Some(1.0));
+            }
+
+            assert!(NetworkFlowSolver::recognizes(&relaxed));
+
+            let hungarian = solver().solve(&problem).unwrap();
+            let lp = NetworkFlowSolver::new(Arc::new(StubFallback))
+                .solve(&relaxed)
+                .unwrap();
+            assert_eq!(lp.status, SolutionStatus::Optimal);
+            let (hungarian, lp) = (hungarian.optimal_value.unwrap(), lp.optimal_value.unwrap());
+            assert!(
+                (hungarian - lp).abs() < 1e-9,
+                "size {}: {} vs {}",
+                size,
+                hungarian,
+                lp
+            );
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Auto Solver
+// Picks a solver for each problem: a specialized one when the structure fits,
+// otherwise the backend the factory selects from size and integrality
+
+use crate::domain::{
+    model_session::ModelSession,
+    models::{OptimizationProblem, Solution},
+    scenarios::Scenario,
+    solver_service::{
+        CancellationToken, NullObserver, Result, SolveObserver, SolverError, SolverService,
+    },
+    value_objects::SolverBackend,
+};
+use crate::solver::{BackendSelection, FallbackSolver, SolverFactory};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Solver behind AUTO: chooses among its backends per problem
+///
+/// The result message ends with the choice and the reason for it, and
+/// `solved_by` names the solver that ran.
+pub struct AutoSolver {
+    backends: Vec<(SolverBackend, Arc<dyn SolverService>)>,
+}
+
+impl AutoSolver {
+    pub fn new(backends: Vec<(SolverBackend, Arc<dyn SolverService>)>) -> Self {
+        Self { backends }
+    }
+
+    /// Solver for `problem` and why it was chosen
+    ///
+    /// The other backends stand behind it, in order, in case it fails.
+    pub fn select(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<(Arc<dyn SolverService>, String)> {
+        let (solver, selection) = self.select_backend(problem)?;
+        let fallbacks = self
+            .backends
+            .iter()
+            .filter(|&&(backend, _)| backend != selection.backend)
+            .map(|(_, solver)| Arc::clone(solver))
+            .collect();
+        let (preferred, reason) =
+            match SolverFactory::specialized_solver_over(problem, solver.clone()) {
+                Some(specialized) => (specialized, "its structure fits".to_string()),
+                None => (solver, selection.reason),
+            };
+        Ok((Arc::new(FallbackSolver::new(preferred, fallbacks)), reason))
+    }
+
+    /// Backend solver for `problem`, leaving out the specialized solvers
+    pub fn select_backend(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<(Arc<dyn SolverService>, BackendSelection)> {
+        let available: Vec<SolverBackend> = self
+            .backends
+            .iter()
+            .filter(|(_, solver)| !problem.is_mixed_integer() || solver.supports_mip())
+            .map(|&(backend, _)| backend)
+            .collect();
+        let selection = SolverFactory::select_backend(problem, &available);
+        self.backends
+            .iter()
+            .find(|&&(backend, _)| backend == selection.backend)
+            .map(|(_, solver)| (Arc::clone(solver), selection))
+            .ok_or_else(|| {
+                if problem.is_mixed_integer() && !self.backends.is_empty() {
+                    SolverError::integers_unsupported()
+                } else {
+                    SolverError::SolverNotAvailable("No backend available for AUTO".to_string())
+                }
+            })
+    }
+}
+
+impl SolverService for AutoSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        let (solver, reason) = self.select(problem)?;
+        debug!(solver = solver.name(), reason, "AUTO selected a solver");
+        let mut solution = solver.solve_cancellable(problem, observer, cancel)?;
+        solution.message = format!(
+            "{} (AUTO chose {}: {})",
+            solution.message,
+            solver.name(),
+            reason
+        );
+        solution
+            .statistics
+            .solved_by
+            .get_or_insert_with(|| solver.name().to_string());
+        Ok(solution)
+    }
+
+    fn solve_scenarios(
+        &self,
+        problem: &OptimizationProblem,
+        scenarios: &[Scenario],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Result<Solution>>> {
+        let (solver, _) = self.select(problem)?;
+        solver.solve_scenarios(problem, scenarios, cancel)
+    }
+
+    fn open_session(&self, problem: &OptimizationProblem) -> Result<Option<Box<dyn ModelSession>>> {
+        let (solver, _) = self.select(problem)?;
+        solver.open_session(problem)
+    }
+
+    fn name(&self) -> &str {
+        "Auto"
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.backends
+            .iter()
+            .any(|(_, solver)| solver.supports_mip())
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.backends.iter().any(|(_, solver)| solver.supports_lp())
+    }
+}
@@ -1,16 +1,184 @@
+use crate::domain::verification::{verify_solution, DEFAULT_FEASIBILITY_TOLERANCE};
 use crate::domain::{
     models::{OptimizationProblem, Solution as DomainSolution, SolverStatistics},
-    solver_service::{Result, SolverError, SolverService},
+    scenarios::Scenario,
+    solver_service::{Result, SolverError, SolverOption, SolverService},
     value_objects::{
-        ConstraintType, OptimizationType, SolutionStatus as DomainSolutionStatus, VariableType,
+        ConstraintType, OptimizationType, OptionKind, SolutionStatus as DomainSolutionStatus,
+        SolverBackend, VariableType,
     },
 };
+use crate::solver::{factory::CBC_LP_ACTIONS, SolverFactory};
+use ::coin_cbc::raw::{SecondaryStatus, Status};
 use good_lp::{
-    solvers::coin_cbc, variable, variables, Expression, ResolutionError,
-    Solution as GoodLpSolutionTrait, SolverModel, Variable as GoodLpVariable,
+    solvers::coin_cbc, variable, variables, Expression, SolverModel, Variable as GoodLpVariable,
+    WithInitialSolution,
 };
+use std::collections::BTreeMap;
 use std::time::Instant;
 
+/// Settings of CBC's cut generators
+const CUT_SETTINGS: &[&str] = &["off", "on", "root", "ifmove", "forceOn"];
+
+/// CBC parameters clients can set as `cbc.<name>`: name, default, accepted values
+/// and description
+///
+/// CBC has no way to list its parameters, so these are the ones worth tuning,
+/// with the defaults CBC documents.
+const CBC_SWITCHES: &[(&str, &str, &[&str], &str)] = &[
+    (
+        "cuts",
+        "on",
+        CUT_SETTINGS,
+        "Switch all cut generators at once",
+    ),
+    ("gomoryCuts", "ifmove", CUT_SETTINGS, "Gomory cuts"),
+    (
+        "knapsackCuts",
+        "ifmove",
+        CUT_SETTINGS,
+        "Lifted knapsack cover cuts",
+    ),
+    (
+        "probingCuts",
+        "ifmove",
+        CUT_SETTINGS,
+        "Cuts from probing variables",
+    ),
+    ("cliqueCuts", "ifmove", CUT_SETTINGS, "Clique cuts"),
+    (
+        "mixedIntegerRoundingCuts",
+        "ifmove",
+        CUT_SETTINGS,
+        "Mixed-integer rounding cuts",
+    ),
+    (
+        "flowCoverCuts",
+        "ifmove",
+        CUT_SETTINGS,
+        "Lifted flow cover cuts",
+    ),
+    (
+        "twoMirCuts",
+        "root",
+        CUT_SETTINGS,
+        "Two-phase mixed-integer rounding cuts",
+    ),
+    (
+        "heuristicsOnOff",
+        "on",
+        &["off", "on"],
+        "Switch all primal heuristics at once",
+    ),
+    (
+        "feaspump",
+        "on",
+        &["off", "on"],
+        "Feasibility pump heuristic",
+    ),
+    ("greedyHeuristic", "on", &["off", "on"], "Greedy heuristic"),
+    (
+        "roundingHeuristic",
+        "on",
+        &["off", "on"],
+        "Rounding heuristic",
+    ),
+    (
+        "combineSolutions",
+        "on",
+        &["off", "on"],
+        "Combine solutions found so far",
+    ),
+    (
+        "Rins",
+        "off",
+        &["off", "on"],
+        "Relaxation induced neighborhood search",
+    ),
+    (
+        "Rens",
+        "off",
+        &["off", "on"],
+        "Relaxation enforced neighborhood search",
+    ),
+    (
+        "proximitySearch",
+        "off",
+        &["off", "on"],
+        "Proximity search heuristic",
+    ),
+    (
+        "localTreeSearch",
+        "off",
+        &["off", "on"],
+        "Local tree search heuristic",
+    ),
+    (
+        "presolve",
+        "on",
+        &["off", "on", "more"],
+        "Presolve the LP relaxation",
+    ),
+    (
+        "preprocess",
+        "sos",
+        &[
+            "off", "on", "save", "equal", "sos", "trysos", "equalall", "strategy",
+        ],
+        "Integer preprocessing",
+    ),
+    (
+        "nodeStrategy",
+        "fewest",
+        &[
+            "hybrid",
+            "fewest",
+            "depth",
+            "upfewest",
+            "downfewest",
+            "updepth",
+            "downdepth",
+        ],
+        "Which node to explore next",
+    ),
+];
+
+/// Integer CBC parameters: name, default, range and description
+const CBC_INTEGERS: &[(&str, i64, i64, i64, &str)] = &[
+    (
+        "strongBranching",
+        5,
+        0,
+        i32::MAX as i64,
+        "Variables to strong branch on at each node",
+    ),
+    (
+        "trustPseudoCosts",
+        10,
+        -3,
+        i32::MAX as i64,
+        "Strong branching evaluations before pseudo costs are trusted",
+    ),
+    (
+        "maxNodes",
+        i32::MAX as i64,
+        0,
+        i32::MAX as i64,
+        "Maximum number of nodes to explore",
+    ),
+    (
+        "randomCbcSeed",
+        -1,
+        -1,
+        i32::MAX as i64,
+        "Random seed, -1 for CBC's own",
+    ),
+];
+
+/// CBC through good_lp
+///
+/// CBC offers no way to interrupt a running solve, so cancellation is only
+/// honored before it starts.
 pub struct CoinCbcSolver;
 
 impl CoinCbcSolver {
@@ -19,6 +187,46 @@ impl CoinCbcSolver {
     }
 }
 
+impl CoinCbcSolver {
+    /// The problem's MIP start as a value for every column
+    ///
+    /// good_lp hands CBC a full starting vector, so a partial start is completed
+    /// the way CBC's own MIP start reader does: the given integer values, rounded,
+    /// are fixed and the rest of the problem is solved. None without a start, or
+    /// when the fixed values leave nothing feasible.
+    fn full_start(&self, problem: &OptimizationProblem) -> Result<Option<Vec<f64>>> {
+        if problem.mip_start().is_empty() || !problem.is_mixed_integer() {
+            return Ok(None);
+        }
+
+        let columns = problem.columns_by_name();
+        let integers: BTreeMap<String, f64> = problem
+            .solver_config
+            .mip_start
+            .iter()
+            .filter(|(name, _)| {
+                columns
+                    .get(*name)
+                    .and_then(|&j| problem.variables.get(j))
+                    .is_some_and(|v| v.is_integer())
+            })
+            .map(|(name, &value)| (name.clone(), value.round()))
+            .collect();
+        let mut restricted = problem.clone();
+        restricted.solver_config.mip_start.clear();
+        let fixed = Scenario::fixing(problem, &integers)
+            .and_then(|fixing| fixing.apply_to(&mut restricted));
+        if fixed.is_err() {
+            return Ok(None);
+        }
+        let completed = self.solve(&restricted)?;
+        Ok(completed
+            .optimal_value
+            .is_some()
+            .then_some(completed.variable_values))
+    }
+}
+
 impl Default for CoinCbcSolver {
     fn default() -> Self {
         Self::new()
@@ -76,7 +284,8 @@ impl SolverService for CoinCbcSolver {
 
         for (i, &coeff) in problem.objective.coefficients.iter().enumerate() {
             if coeff != 0.0 {
-                // good_lp minimizes, so negate for maximization
+                // good_lp minimizes, so negate for maximization. CBC's C API gives no
+                // row prices; any added later must be flipped with `with_duals`
                 let c = if is_maximize { -coeff } else { coeff };
                 obj_expr += c * lp_variables[i];
             }
@@ -84,13 +293,21 @@ impl SolverService for CoinCbcSolver {
 
         // Build constraints
         let mut lp_model = vars.minimise(obj_expr).using(coin_cbc::coin_cbc);
+        let (actions, parameters): (Vec<_>, Vec<_>) =
+            SolverFactory::backend_options(SolverBackend::CoinCbc, problem)
+                .into_iter()
+                .partition(|(name, _)| CBC_LP_ACTIONS.contains(&name.as_str()));
+        for (name, value) in parameters.into_iter().chain(actions) {
+            lp_model.set_parameter(&name, &value);
+        }
+        if let Some(start) = self.full_start(problem)? {
+            lp_model = lp_model.with_initial_solution(lp_variables.iter().copied().zip(start));
+        }
 
         for constraint in &problem.constraints {
             let mut lhs: Expression = 0.into();
-            for (i, &coeff) in constraint.coefficients.iter().enumerate() {
-                if coeff != 0.0 {
-                    lhs += coeff * lp_variables[i];
-                }
+            for (i, coeff) in constraint.coefficients.iter() {
+                lhs += coeff * lp_variables[i];
             }
 
             match constraint.constraint_type {
@@ -106,60 +323,108 @@ impl SolverService for CoinCbcSolver {
             }
         }
 
-        // Solve the problem
-        let solution_result = lp_model.solve();
+        // Solve the raw model rather than through good_lp, which folds most of
+        // CBC's stopping reasons into one opaque error
+        let solved = lp_model.as_inner().solve();
+        let raw = solved.raw();
         let solve_time = start_time.elapsed().as_secs_f64() * 1000.0;
 
         // Build statistics
         let statistics = SolverStatistics {
-            simplex_iterations: 0,
+            simplex_iterations: raw.iteration_count().max(0) as u64,
             nodes_explored: 0,
             solve_time_ms: solve_time,
             num_variables: num_vars as u32,
             num_constraints: problem.constraints.len() as u32,
             num_integer_vars: num_integer,
             num_binary_vars: num_binary,
+            solved_by: None,
+            presolve: None,
+            resources: None,
+            conditioning: None,
         };
 
-        // Process result
-        match solution_result {
-            Ok(sol) => {
-                // Extract variable values
-                let mut variable_values = vec![0.0; num_vars];
-                for (i, &var) in lp_variables.iter().enumerate() {
-                    variable_values[i] = sol.value(var);
-                }
-
-                // Calculate actual objective value
-                let mut actual_obj = 0.0;
-                for (i, &coeff) in problem.objective.coefficients.iter().enumerate() {
-                    actual_obj += coeff * variable_values[i];
-                }
-
-                let mut solution = DomainSolution::optimal(actual_obj, variable_values);
-                solution.statistics = statistics;
-                solution.message = format!("Optimal solution found for '{}'", problem.name);
-
-                Ok(solution)
+        // Columns were added in variable order
+        let variable_values = raw.col_solution()[..num_vars].to_vec();
+        // CBC minimises the negated objective of a maximization
+        let bound = match raw.best_possible_value() {
+            bound if is_maximize => -bound,
+            bound => bound,
+        };
+        let solution = match (raw.status(), raw.secondary_status()) {
+            (Status::Abandoned, _) => {
+                return Err(SolverError::NumericalDifficulty(
+                    "CBC abandoned the solve".to_string(),
+                ))
             }
-            Err(ResolutionError::Infeasible) => {
-                let mut solution = DomainSolution::new(
-                    DomainSolutionStatus::Infeasible,
-                    "Problem is infeasible: no solution satisfies all constraints",
-                );
-                solution.statistics = statistics;
-                Ok(solution)
+            _ if raw.is_continuous_unbounded() => DomainSolution::new(
+                DomainSolutionStatus::Unbounded,
+                "Problem is unbounded: objective can be improved infinitely",
+            ),
+            _ if raw.is_proven_infeasible() => DomainSolution::new(
+                DomainSolutionStatus::Infeasible,
+                "Problem is infeasible: no solution satisfies all constraints",
+            ),
+            (Status::UserEvent, _) | (_, SecondaryStatus::StoppedOnUserEvent) => stopped(
+                problem,
+                variable_values,
+                bound,
+                DomainSolutionStatus::Interrupted,
+                "Solve interrupted",
+            ),
+            (Status::Stopped, SecondaryStatus::StoppedOnTime) => stopped(
+                problem,
+                variable_values,
+                bound,
+                DomainSolutionStatus::TimeLimit,
+                "Time limit reached",
+            ),
+            (Status::Stopped, SecondaryStatus::StoppedOnNodes) => stopped(
+                problem,
+                variable_values,
+                bound,
+                DomainSolutionStatus::NodeLimit,
+                "Node limit reached",
+            ),
+            (Status::Stopped, SecondaryStatus::StoppedOnIterationLimit) => stopped(
+                problem,
+                variable_values,
+                bound,
+                DomainSolutionStatus::IterationLimit,
+                "Iteration limit reached",
+            ),
+            (_, SecondaryStatus::StoppedOnSolutions)
+                if problem.solver_config.stop_at_first_feasible =>
+            {
+                stopped(
+                    problem,
+                    variable_values,
+                    bound,
+                    DomainSolutionStatus::Feasible,
+                    "Stopped at the first feasible solution",
+                )
             }
-            Err(ResolutionError::Unbounded) => {
-                let mut solution = DomainSolution::new(
-                    DomainSolutionStatus::Unbounded,
-                    "Problem is unbounded: objective can be improved infinitely",
-                );
-                solution.statistics = statistics;
-                Ok(solution)
+            (_, SecondaryStatus::StoppedOnGap | SecondaryStatus::StoppedOnSolutions) => stopped(
+                problem,
+                variable_values,
+                bound,
+                DomainSolutionStatus::Feasible,
+                "Gap or solution limit reached",
+            ),
+            (Status::Stopped, secondary) => {
+                return Err(SolverError::ExecutionFailed(format!(
+                    "CBC stopped: {:?}",
+                    secondary
+                )))
             }
-            Err(e) => Err(SolverError::ExecutionFailed(format!("{:?}", e))),
-        }
+            _ => {
+                let actual_obj = objective_value(problem, &variable_values);
+                let mut solution = DomainSolution::optimal(actual_obj, variable_values);
+                solution.message = format!("Optimal solution found for '{}'", problem.name);
+                solution
+            }
+        };
+        Ok(solution.with_statistics(statistics))
     }
 
     fn name(&self) -> &str {
@@ -169,4 +434,75 @@ impl SolverService for CoinCbcSolver {
     fn supports_mip(&self) -> bool {
         true
     }
+
+    fn version(&self) -> String {
+        ::coin_cbc::raw::Model::version().to_string()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec![
+            "Mixed-Integer Programming".to_string(),
+            "Branch and Bound".to_string(),
+            "Cutting Planes".to_string(),
+            "Primal/Dual Simplex".to_string(),
+        ]
+    }
+
+    fn options(&self) -> Vec<SolverOption> {
+        let switches = CBC_SWITCHES
+            .iter()
+            .map(|&(name, default, choices, description)| SolverOption {
+                name: format!("cbc.{}", name),
+                kind: OptionKind::String,
+                default: default.to_string(),
+                min: None,
+                max: None,
+                choices: choices.iter().map(|choice| choice.to_string()).collect(),
+                description: description.to_string(),
+            });
+        let integers = CBC_INTEGERS
+            .iter()
+            .map(|&(name, default, min, max, description)| SolverOption {
+                name: format!("cbc.{}", name),
+                kind: OptionKind::Int,
+                default: default.to_string(),
+                min: Some(min as f64),
+                max: Some(max as f64),
+                choices: Vec::new(),
+                description: description.to_string(),
+            });
+        switches.chain(integers).collect()
+    }
+}
+
+/// Objective of `problem` at `values`
+fn objective_value(problem: &OptimizationProblem, values: &[f64]) -> f64 {
+    values
+        .iter()
+        .zip(&problem.objective.coefficients)
+        .map(|(value, coeff)| coeff * value)
+        .sum()
+}
+
+/// Solution of a solve CBC stopped early; CBC leaves its last iterate in the
+/// columns either way, so `values` only count as an incumbent if they're feasible
+///
+/// `bound` is CBC's best possible objective; it's only reported for MIPs, where
+/// CBC's branch and bound tracks one.
+fn stopped(
+    problem: &OptimizationProblem,
+    values: Vec<f64>,
+    bound: f64,
+    status: DomainSolutionStatus,
+    reason: &str,
+) -> DomainSolution {
+    let feasible = verify_solution(problem, &values, DEFAULT_FEASIBILITY_TOLERANCE)
+        .is_ok_and(|check| check.is_feasible());
+    let incumbent = feasible.then(|| (objective_value(problem, &values), values));
+    let solution = DomainSolution::stopped(status, reason, &problem.name, incumbent);
+    if problem.is_mixed_integer() && bound.is_finite() {
+        solution.with_bound(bound)
+    } else {
+        solution
+    }
 }
@@ -1,29 +1,397 @@
 use crate::domain::{
-    models::OptimizationProblem, solver_service::SolverService, value_objects::SolverBackend,
+    models::{OptimizationProblem, Solution},
+    solver_service::{Result, SolverError, SolverService},
+    value_objects::{LpAlgorithm, SolverBackend},
 };
-use crate::solver::{CoinCbcSolver, HighsSolver};
+#[cfg(feature = "cbc")]
+use crate::solver::CoinCbcSolver;
+#[cfg(feature = "highs")]
+use crate::solver::HighsSolver;
+use crate::solver::{
+    AssignmentSolver, AutoSolver, KnapsackSolver, NetworkFlowSolver, RacingSolver,
+};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+/// HiGHS options for pure-binary models: more effort on primal heuristics, and
+/// symmetry detection, which covering and packing models are prone to
+const HIGHS_BINARY_PRESET: &[(&str, &str)] = &[
+    ("mip_heuristic_effort", "0.2"),
+    ("mip_detect_symmetry", "true"),
+];
+
+/// CBC parameters for pure-binary models: clique, knapsack and probing cuts, and
+/// the RINS and proximity search heuristics
+const CBC_BINARY_PRESET: &[(&str, &str)] = &[
+    ("clique", "on"),
+    ("knapsack", "on"),
+    ("probing", "on"),
+    ("rins", "on"),
+    ("proximity", "on"),
+];
+
+/// A backend's names for the options `SolverConfig` settings map to
+struct ConfigOptionNames {
+    time_limit: &'static str,
+    iteration_limit: &'static str,
+    node_limit: &'static str,
+    solution_limit: &'static str,
+    relative_gap: &'static str,
+    absolute_gap: &'static str,
+    crossover: &'static str,
+    random_seed: &'static str,
+}
+
+const HIGHS_CONFIG_OPTIONS: ConfigOptionNames = ConfigOptionNames {
+    time_limit: "time_limit",
+    iteration_limit: "simplex_iteration_limit",
+    node_limit: "mip_max_nodes",
+    solution_limit: "mip_max_improving_sols",
+    relative_gap: "mip_rel_gap",
+    absolute_gap: "mip_abs_gap",
+    crossover: "run_crossover",
+    random_seed: "random_seed",
+};
+
+const CBC_CONFIG_OPTIONS: ConfigOptionNames = ConfigOptionNames {
+    time_limit: "seconds",
+    iteration_limit: "maxIterations",
+    node_limit: "maxNodes",
+    solution_limit: "maxSolutions",
+    relative_gap: "ratioGap",
+    absolute_gap: "allowableGap",
+    crossover: "crossover",
+    random_seed: "randomCbcSeed",
+};
+
+/// Pure-integer models up to this many variables, and as many constraints, go to
+/// CBC when it is available; its cut generators close such models quickly
+const SMALL_MODEL_SIZE: usize = 500;
+
+/// Backend AUTO picks for a problem, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendSelection {
+    pub backend: SolverBackend,
+    pub reason: String,
+}
+
 /// Factory for creating solver instances based on configuration
 pub struct SolverFactory;
 
 impl SolverFactory {
     /// Create a solver based on the problem configuration
     pub fn create_solver(problem: &OptimizationProblem) -> Arc<dyn SolverService> {
-        Self::create_from_backend(problem.solver_config.backend, problem.is_mixed_integer())
+        Self::create_for(problem.solver_config.backend, problem)
+    }
+
+    /// Create a solver for `backend`; AUTO picks per problem, see [`AutoSolver`]
+    pub fn create_for(
+        backend: SolverBackend,
+        problem: &OptimizationProblem,
+    ) -> Arc<dyn SolverService> {
+        Self::create_from_backend(backend, problem.is_mixed_integer())
+    }
+
+    /// Pick one of `available` for `problem` from its size and integrality
+    ///
+    /// LPs and larger or mixed MIPs go to HiGHS, small pure-integer models to CBC.
+    /// Backends missing from `available` are skipped; with none, the selection is
+    /// AUTO itself.
+    pub fn select_backend(
+        problem: &OptimizationProblem,
+        available: &[SolverBackend],
+    ) -> BackendSelection {
+        let selection =
+            |backend: SolverBackend, reason: String| BackendSelection { backend, reason };
+        match available {
+            [] => return selection(SolverBackend::Auto, "no backend is available".to_string()),
+            [only] => return selection(*only, "the only backend available".to_string()),
+            _ => {}
+        }
+        let prefer = |first: SolverBackend| {
+            if available.contains(&first) {
+                first
+            } else {
+                available[0]
+            }
+        };
+
+        let variables = problem.num_variables();
+        let constraints = problem.constraints.len();
+        let integers = problem.num_integer_variables();
+        if integers == 0 {
+            return selection(prefer(SolverBackend::Highs), "continuous model".to_string());
+        }
+        if integers == variables && variables <= SMALL_MODEL_SIZE && constraints <= SMALL_MODEL_SIZE
+        {
+            return selection(
+                prefer(SolverBackend::CoinCbc),
+                format!(
+                    "small pure-integer model, {} variables and {} constraints",
+                    variables, constraints
+                ),
+            );
+        }
+        selection(
+            prefer(SolverBackend::Highs),
+            format!(
+                "mixed-integer model, {} of {} variables integer and {} constraints",
+                integers, variables, constraints
+            ),
+        )
+    }
+
+    /// Specialized solver for the problem's structure, falling back to the
+    /// default solver for whatever it is handed that doesn't fit
+    pub fn specialized_solver(problem: &OptimizationProblem) -> Option<Arc<dyn SolverService>> {
+        Self::specialized_solver_over(problem, Self::default_solver())
+    }
+
+    /// Specialized solver for the problem's structure, falling back to `fallback`
+    pub fn specialized_solver_over(
+        problem: &OptimizationProblem,
+        fallback: Arc<dyn SolverService>,
+    ) -> Option<Arc<dyn SolverService>> {
+        if KnapsackSolver::recognizes(problem) {
+            return Some(Arc::new(KnapsackSolver::new(fallback)));
+        }
+        if AssignmentSolver::recognizes(problem) {
+            return Some(Arc::new(AssignmentSolver::new(fallback)));
+        }
+        if NetworkFlowSolver::recognizes(problem) {
+            return Some(Arc::new(NetworkFlowSolver::new(fallback)));
+        }
+        None
     }
 
     /// Create a solver for a specific backend
+    ///
+    /// Backends that were not compiled in yield a solver that fails every solve
+    /// with `SolverNotAvailable`.
     pub fn create_from_backend(backend: SolverBackend, _is_mip: bool) -> Arc<dyn SolverService> {
         match backend {
-            SolverBackend::Auto => Arc::new(HighsSolver::new()),
+            SolverBackend::Auto => Arc::new(AutoSolver::new(
+                Self::compiled_backends()
+                    .into_iter()
+                    .map(|backend| (backend, Self::create_from_backend(backend, _is_mip)))
+                    .collect(),
+            )),
+            SolverBackend::Race => Arc::new(RacingSolver::new(
+                Self::compiled_backends()
+                    .into_iter()
+                    .map(|backend| Self::create_from_backend(backend, _is_mip))
+                    .collect(),
+            )),
+            SolverBackend::Assignment => Arc::new(AssignmentSolver::new(Self::default_solver())),
+            #[cfg(feature = "cbc")]
             SolverBackend::CoinCbc => Arc::new(CoinCbcSolver::new()),
+            #[cfg(feature = "highs")]
             SolverBackend::Highs => Arc::new(HighsSolver::new()),
+            #[allow(unreachable_patterns)]
+            other => Arc::new(UnavailableSolver(other)),
+        }
+    }
+
+    /// Create a solver for `backend` that keeps up to `instances` backend instances
+    /// warm between solves
+    ///
+    /// Only HiGHS supports this; other backends are created as usual.
+    #[cfg_attr(not(feature = "highs"), allow(unused_variables))]
+    pub fn create_pooled(backend: SolverBackend, instances: usize) -> Arc<dyn SolverService> {
+        match backend {
+            #[cfg(feature = "highs")]
+            SolverBackend::Highs => Arc::new(HighsSolver::pooled(instances)),
+            other => Self::create_from_backend(other, false),
         }
     }
 
-    /// Get the default solver (HiGHS)
+    /// Backend of the default solver, Auto when no backend is compiled in
+    pub fn default_backend() -> SolverBackend {
+        #[cfg(feature = "highs")]
+        return SolverBackend::Highs;
+
+        #[cfg(all(feature = "cbc", not(feature = "highs")))]
+        return SolverBackend::CoinCbc;
+
+        #[cfg(not(any(feature = "cbc", feature = "highs")))]
+        return SolverBackend::Auto;
+    }
+
+    /// Get the default solver (HiGHS, or CBC when HiGHS is not compiled in)
     pub fn default_solver() -> Arc<dyn SolverService> {
-        Arc::new(HighsSolver::new())
+        #[cfg(feature = "highs")]
+        return Arc::new(HighsSolver::new());
+
+        #[cfg(all(feature = "cbc", not(feature = "highs")))]
+        return Arc::new(CoinCbcSolver::new());
+
+        #[cfg(not(any(feature = "cbc", feature = "highs")))]
+        return Arc::new(UnavailableSolver(SolverBackend::Auto));
+    }
+
+    /// Options to set on `backend` for `problem`
+    ///
+    /// The config's time, iteration, node and MIP solution limits and gap
+    /// tolerances map to the backend's own, and pure-binary models get a preset
+    /// known to suit them; the caller's `backend_options` for this backend (keys
+    /// `highs.<name>` or `cbc.<name>`) are applied on top, so any of these values
+    /// can be overridden.
+    pub fn backend_options(
+        backend: SolverBackend,
+        problem: &OptimizationProblem,
+    ) -> BTreeMap<String, String> {
+        let (prefix, names, preset) = match backend {
+            SolverBackend::Highs => ("highs.", &HIGHS_CONFIG_OPTIONS, HIGHS_BINARY_PRESET),
+            SolverBackend::CoinCbc => ("cbc.", &CBC_CONFIG_OPTIONS, CBC_BINARY_PRESET),
+            _ => return BTreeMap::new(),
+        };
+        let config = &problem.solver_config;
+        let mut options = BTreeMap::new();
+        if let Some(secs) = config.time_limit {
+            options.insert(names.time_limit.to_string(), secs.to_string());
+        }
+        if let Some(iterations) = config.max_iterations {
+            options.insert(names.iteration_limit.to_string(), iterations.to_string());
+        }
+        if let Some(nodes) = config.max_nodes.filter(|_| problem.is_mixed_integer()) {
+            options.insert(names.node_limit.to_string(), nodes.to_string());
+        }
+        if config.stop_at_first_feasible && problem.is_mixed_integer() {
+            options.insert(names.solution_limit.to_string(), "1".to_string());
+        }
+        if problem.is_mixed_integer() {
+            if let Some(gap) = config.gap_tolerance {
+                options.insert(names.relative_gap.to_string(), gap.to_string());
+            }
+            if let Some(gap) = config.absolute_gap_tolerance {
+                options.insert(names.absolute_gap.to_string(), gap.to_string());
+            }
+        }
+        for &(name, value) in lp_algorithm_options(backend, config.lp_algorithm, problem) {
+            options.insert(name.to_string(), value.to_string());
+        }
+        if problem.skips_crossover() {
+            options.insert(names.crossover.to_string(), "off".to_string());
+        }
+        if problem.is_pure_binary() {
+            for &(name, value) in preset {
+                options.insert(name.to_string(), value.to_string());
+            }
+        }
+        for (key, value) in &config.backend_options {
+            if let Some(name) = key.strip_prefix(prefix) {
+                options.insert(name.to_string(), value.clone());
+            }
+        }
+        options
+    }
+
+    /// Random seed the caller gave `problem`'s backend in `backend_options`; for
+    /// AUTO and the other multi-backend choices, the first one given to either
+    /// backend
+    pub fn random_seed(problem: &OptimizationProblem) -> Option<i64> {
+        let backends = match problem.solver_config.backend {
+            backend @ (SolverBackend::Highs | SolverBackend::CoinCbc) => vec![backend],
+            _ => vec![SolverBackend::Highs, SolverBackend::CoinCbc],
+        };
+        backends.into_iter().find_map(|backend| {
+            problem
+                .solver_config
+                .backend_options
+                .get(&seed_option(backend)?)?
+                .trim()
+                .parse()
+                .ok()
+        })
+    }
+
+    /// Give `backend` `seed` as its random seed, in `problem`'s `backend_options`
+    pub fn set_random_seed(backend: SolverBackend, problem: &mut OptimizationProblem, seed: i64) {
+        if let Some(key) = seed_option(backend) {
+            problem
+                .solver_config
+                .backend_options
+                .insert(key, seed.to_string());
+        }
+    }
+
+    /// Backends compiled into this build
+    pub fn compiled_backends() -> Vec<SolverBackend> {
+        [
+            #[cfg(feature = "cbc")]
+            SolverBackend::CoinCbc,
+            #[cfg(feature = "highs")]
+            SolverBackend::Highs,
+        ]
+        .to_vec()
+    }
+}
+
+/// `backend_options` key of `backend`'s random seed
+fn seed_option(backend: SolverBackend) -> Option<String> {
+    match backend {
+        SolverBackend::Highs => Some(format!("highs.{}", HIGHS_CONFIG_OPTIONS.random_seed)),
+        SolverBackend::CoinCbc => Some(format!("cbc.{}", CBC_CONFIG_OPTIONS.random_seed)),
+        _ => None,
+    }
+}
+
+/// CBC actions that solve the LP as soon as CBC reads them
+#[cfg(feature = "cbc")]
+pub(crate) const CBC_LP_ACTIONS: [&str; 3] = ["dualS", "primalS", "barrier"];
+
+/// Backend options selecting `algorithm` for the LP solves of `problem`
+fn lp_algorithm_options(
+    backend: SolverBackend,
+    algorithm: LpAlgorithm,
+    problem: &OptimizationProblem,
+) -> &'static [(&'static str, &'static str)] {
+    let mip = problem.is_mixed_integer();
+    match (backend, algorithm) {
+        (_, LpAlgorithm::Auto) => &[],
+        // HiGHS solves only the relaxation of a MIP told to use simplex or IPM, so
+        // MIPs just pick the simplex variant for their node LPs
+        (SolverBackend::Highs, LpAlgorithm::DualSimplex) if mip => &[("simplex_strategy", "1")],
+        (SolverBackend::Highs, LpAlgorithm::PrimalSimplex) if mip => &[("simplex_strategy", "4")],
+        (SolverBackend::Highs, LpAlgorithm::InteriorPoint) if mip => &[],
+        (SolverBackend::Highs, LpAlgorithm::DualSimplex) => {
+            &[("solver", "simplex"), ("simplex_strategy", "1")]
+        }
+        (SolverBackend::Highs, LpAlgorithm::PrimalSimplex) => {
+            &[("solver", "simplex"), ("simplex_strategy", "4")]
+        }
+        (SolverBackend::Highs, LpAlgorithm::InteriorPoint) => &[("solver", "ipm")],
+        // CBC picks the method with an action run before the solve. Its C API sends a
+        // value after every name, which CBC reads as the next command, so the value
+        // repeats the action: a no-op on the LP it just solved. The adapter sets these
+        // after the parameters, which CBC must have read by the time they run
+        (SolverBackend::CoinCbc, LpAlgorithm::DualSimplex) => &[("dualS", "dualS")],
+        (SolverBackend::CoinCbc, LpAlgorithm::PrimalSimplex) => &[("primalS", "primalS")],
+        (SolverBackend::CoinCbc, LpAlgorithm::InteriorPoint) => &[("barrier", "barrier")],
+        _ => &[],
+    }
+}
+
+/// Stand-in for a backend that this build does not include
+struct UnavailableSolver(SolverBackend);
+
+impl SolverService for UnavailableSolver {
+    fn solve(&self, _problem: &OptimizationProblem) -> Result<Solution> {
+        Err(SolverError::SolverNotAvailable(format!(
+            "{} support is not compiled into this server",
+            self.0
+        )))
+    }
+
+    fn name(&self) -> &str {
+        "Unavailable"
+    }
+
+    fn supports_mip(&self) -> bool {
+        false
+    }
+
+    fn supports_lp(&self) -> bool {
+        false
     }
 }
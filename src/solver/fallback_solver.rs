@@ -0,0 +1,194 @@
+// Fallback Solver
+// Tries a preferred solver, then the others in turn when it fails, so one broken or
+// missing backend doesn't fail a request another could solve
+
+use crate::domain::{
+    model_session::ModelSession,
+    models::{OptimizationProblem, Solution},
+    scenarios::Scenario,
+    solver_service::{
+        CancellationToken, NullObserver, Result, SolveObserver, SolverError, SolverOption,
+        SolverService,
+    },
+    value_objects::SolutionStatus,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Solver that falls back along a chain when a solve errors, panics or ends with
+/// an ERROR status
+///
+/// Invalid problems, timeouts and cancelled solves end the chain, since no other
+/// solver would do better. A result from a fallback says which solvers failed before it,
+/// and `solved_by` names the one that produced it.
+pub struct FallbackSolver {
+    chain: Vec<Arc<dyn SolverService>>,
+}
+
+impl FallbackSolver {
+    /// `preferred` first, then each of `fallbacks` in order
+    pub fn new(preferred: Arc<dyn SolverService>, fallbacks: Vec<Arc<dyn SolverService>>) -> Self {
+        let mut chain = vec![preferred];
+        chain.extend(fallbacks);
+        Self { chain }
+    }
+
+    fn preferred(&self) -> &Arc<dyn SolverService> {
+        &self.chain[0]
+    }
+}
+
+impl SolverService for FallbackSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        let mut failures = Vec::new();
+        let mut error_solution = None;
+        let solvers = self
+            .chain
+            .iter()
+            .filter(|solver| !problem.is_mixed_integer() || solver.supports_mip());
+        for solver in solvers {
+            let failure = match attempt(|| solver.solve_cancellable(problem, observer, cancel)) {
+                Ok(solution) if solution.status != SolutionStatus::Error => {
+                    return Ok(credit(solution, solver.name(), &failures));
+                }
+                Ok(solution) => {
+                    let failure = format!("{} failed: {}", solver.name(), solution.message);
+                    error_solution = Some(solution);
+                    failure
+                }
+                Err(e) if ends_chain(&e, cancel) => return Err(e),
+                Err(e) => format!("{} failed: {}", solver.name(), e),
+            };
+            warn!(failure, "Solver failed, trying the next one");
+            failures.push(failure);
+        }
+
+        match error_solution {
+            Some(solution) => Ok(solution),
+            None if failures.is_empty() && problem.is_mixed_integer() => {
+                Err(SolverError::integers_unsupported())
+            }
+            None if failures.is_empty() => Err(SolverError::SolverNotAvailable(
+                "No solver in the fallback chain can solve this problem".to_string(),
+            )),
+            None => Err(SolverError::ExecutionFailed(failures.join("; "))),
+        }
+    }
+
+    fn solve_scenarios(
+        &self,
+        problem: &OptimizationProblem,
+        scenarios: &[Scenario],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Result<Solution>>> {
+        let mut failures = Vec::new();
+        let solvers = self
+            .chain
+            .iter()
+            .filter(|solver| !problem.is_mixed_integer() || solver.supports_mip());
+        for solver in solvers {
+            match attempt(|| solver.solve_scenarios(problem, scenarios, cancel)) {
+                Ok(outcomes) => return Ok(outcomes),
+                Err(e) if ends_chain(&e, cancel) => return Err(e),
+                Err(e) => {
+                    warn!(solver = solver.name(), error = %e, "Solver failed, trying the next one");
+                    failures.push(format!("{} failed: {}", solver.name(), e));
+                }
+            }
+        }
+        Err(SolverError::ExecutionFailed(failures.join("; ")))
+    }
+
+    /// Sessions stay on the preferred solver, which keeps the model built
+    fn open_session(&self, problem: &OptimizationProblem) -> Result<Option<Box<dyn ModelSession>>> {
+        self.preferred().open_session(problem)
+    }
+
+    fn validate(&self, problem: &OptimizationProblem) -> Result<Vec<String>> {
+        self.preferred().validate(problem)
+    }
+
+    fn name(&self) -> &str {
+        self.preferred().name()
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.chain.iter().any(|solver| solver.supports_mip())
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.chain.iter().any(|solver| solver.supports_lp())
+    }
+
+    fn version(&self) -> String {
+        self.preferred().version()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        self.preferred().capabilities()
+    }
+
+    fn options(&self) -> Vec<SolverOption> {
+        self.preferred().options()
+    }
+}
+
+/// Run one solver, turning a panic into an error
+pub(crate) fn attempt<T>(solve: impl FnOnce() -> Result<T>) -> Result<T> {
+    catch_unwind(AssertUnwindSafe(solve)).unwrap_or_else(|panic| {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(SolverError::ExecutionFailed(format!(
+            "panicked: {}",
+            reason
+        )))
+    })
+}
+
+/// Whether no other solver should be tried after `error`
+///
+/// A timeout ends it too: the time it ran out of is the request's, not the solver's.
+fn ends_chain(error: &SolverError, cancel: &CancellationToken) -> bool {
+    matches!(
+        error,
+        SolverError::InvalidProblem(_) | SolverError::Cancelled | SolverError::Timeout(_)
+    ) || cancel.is_cancelled()
+}
+
+/// Note which solvers failed before `name` produced the solution
+fn credit(mut solution: Solution, name: &str, failures: &[String]) -> Solution {
+    if !failures.is_empty() {
+        solution.message = format!(
+            "{} ({}; solved by {})",
+            solution.message,
+            failures.join("; "),
+            name
+        );
+        solution
+            .statistics
+            .solved_by
+            .get_or_insert_with(|| name.to_string());
+    }
+    solution
+}
@@ -0,0 +1,211 @@
+// HiGHS option catalogue
+// Reads the names, types, defaults and ranges of HiGHS's options from the library,
+// and their descriptions from the options file it writes
+
+use crate::domain::{solver_service::SolverOption, value_objects::OptionKind};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::sync::OnceLock;
+
+/// HiGHS's documented options, keyed `highs.<name>`
+///
+/// Advanced options, which HiGHS leaves out of its options file, are left out here
+/// too. Read once per process.
+pub(crate) fn highs_options() -> Vec<SolverOption> {
+    static OPTIONS: OnceLock<Vec<SolverOption>> = OnceLock::new();
+    OPTIONS
+        .get_or_init(|| {
+            // SAFETY: the instance is created here and destroyed after use
+            unsafe {
+                let highs = highs_sys::Highs_create();
+                highs_sys::Highs_setBoolOptionValue(highs, c"output_flag".as_ptr(), 0);
+                let options = read_options(highs);
+                highs_sys::Highs_destroy(highs);
+                options
+            }
+        })
+        .clone()
+}
+
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn read_options(highs: *mut c_void) -> Vec<SolverOption> {
+    let descriptions = descriptions(highs);
+    (0..highs_sys::Highs_getNumOptions(highs))
+        .filter_map(|index| {
+            let name = option_name(highs, index)?;
+            let description = descriptions.get(&name)?;
+            describe_option(highs, &name, description)
+        })
+        .collect()
+}
+
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn option_name(highs: *mut c_void, index: highs_sys::HighsInt) -> Option<String> {
+    let mut name: *mut c_char = std::ptr::null_mut();
+    if highs_sys::Highs_getOptionName(highs, index, &mut name) == highs_sys::kHighsStatusError
+        || name.is_null()
+    {
+        return None;
+    }
+    let owned = CStr::from_ptr(name).to_string_lossy().into_owned();
+    // HiGHS allocates the name with malloc and leaves freeing it to the caller
+    libc::free(name.cast());
+    Some(owned)
+}
+
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn describe_option(
+    highs: *mut c_void,
+    name: &str,
+    description: &str,
+) -> Option<SolverOption> {
+    use highs_sys::HighsInt;
+
+    let c_name = CString::new(name).ok()?;
+    let mut kind: HighsInt = 0;
+    if highs_sys::Highs_getOptionType(highs, c_name.as_ptr(), &mut kind)
+        == highs_sys::kHighsStatusError
+    {
+        return None;
+    }
+    let option = |kind, default: String, min: Option<f64>, max: Option<f64>| SolverOption {
+        name: format!("highs.{}", name),
+        kind,
+        default,
+        min,
+        max,
+        choices: Vec::new(),
+        description: description.to_string(),
+    };
+    let ok = |status| status != highs_sys::kHighsStatusError;
+    match kind {
+        highs_sys::kHighsOptionTypeBool => {
+            let (mut current, mut default): (HighsInt, HighsInt) = (0, 0);
+            ok(highs_sys::Highs_getBoolOptionValues(
+                highs,
+                c_name.as_ptr(),
+                &mut current,
+                &mut default,
+            ))
+            .then(|| option(OptionKind::Bool, (default != 0).to_string(), None, None))
+        }
+        highs_sys::kHighsOptionTypeInt => {
+            let (mut current, mut min, mut max, mut default): (
+                HighsInt,
+                HighsInt,
+                HighsInt,
+                HighsInt,
+            ) = (0, 0, 0, 0);
+            ok(highs_sys::Highs_getIntOptionValues(
+                highs,
+                c_name.as_ptr(),
+                &mut current,
+                &mut min,
+                &mut max,
+                &mut default,
+            ))
+            .then(|| {
+                option(
+                    OptionKind::Int,
+                    default.to_string(),
+                    Some(min as f64),
+                    Some(max as f64),
+                )
+            })
+        }
+        highs_sys::kHighsOptionTypeDouble => {
+            let (mut current, mut min, mut max, mut default) = (0.0, 0.0, 0.0, 0.0);
+            ok(highs_sys::Highs_getDoubleOptionValues(
+                highs,
+                c_name.as_ptr(),
+                &mut current,
+                &mut min,
+                &mut max,
+                &mut default,
+            ))
+            .then(|| {
+                // Debug keeps large defaults short (1e20) in a form HiGHS parses
+                option(
+                    OptionKind::Double,
+                    format!("{:?}", default),
+                    min.is_finite().then_some(min),
+                    max.is_finite().then_some(max),
+                )
+            })
+        }
+        highs_sys::kHighsOptionTypeString => {
+            let length = highs_sys::kHighsMaximumStringLength as usize;
+            let mut current = vec![0 as c_char; length];
+            let mut default = vec![0 as c_char; length];
+            ok(highs_sys::Highs_getStringOptionValues(
+                highs,
+                c_name.as_ptr(),
+                current.as_mut_ptr(),
+                default.as_mut_ptr(),
+            ))
+            .then(|| {
+                let default = CStr::from_ptr(default.as_ptr())
+                    .to_string_lossy()
+                    .into_owned();
+                SolverOption {
+                    choices: quoted_words(description),
+                    ..option(OptionKind::String, default, None, None)
+                }
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Option descriptions from the options file HiGHS writes, by option name
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn descriptions(highs: *mut c_void) -> HashMap<String, String> {
+    let path = std::env::temp_dir().join(format!("letsopt-highs-options-{}", std::process::id()));
+    let Some(c_path) = path.to_str().and_then(|path| CString::new(path).ok()) else {
+        return HashMap::new();
+    };
+    let written = highs_sys::Highs_writeOptions(highs, c_path.as_ptr());
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let _ = std::fs::remove_file(&path);
+    if written == highs_sys::kHighsStatusError {
+        return HashMap::new();
+    }
+    parse_descriptions(&text)
+}
+
+/// Each option in the file is a `# description` line, a `# [type: ...]` line and
+/// a `name = value` line
+fn parse_descriptions(text: &str) -> HashMap<String, String> {
+    let mut descriptions = HashMap::new();
+    let mut description = None;
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            if !comment.starts_with('[') {
+                description = Some(comment.trim().to_string());
+            }
+        } else if let Some((name, _)) = line.split_once(" = ") {
+            if let Some(description) = description.take() {
+                descriptions.insert(name.trim().to_string(), description);
+            }
+        }
+    }
+    descriptions
+}
+
+/// Values a string option's description lists in quotes, like `"off", "choose" or "on"`
+fn quoted_words(description: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in description.split('"').skip(1).step_by(2) {
+        let is_word =
+            !word.is_empty() && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_word && !words.iter().any(|known| known == word) {
+            words.push(word.to_string());
+        }
+    }
+    words
+}
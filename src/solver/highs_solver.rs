@@ -3,19 +3,188 @@
 // This is an adapter pattern - translates our domain models to HiGHS API
 
 use crate::domain::{
-    models::{OptimizationProblem, Solution as DomainSolution, SolverStatistics},
-    solver_service::{Result, SolverError, SolverService},
+    infeasibility::InfeasibilityCertificate,
+    model_session::{ModelSession, ModelUpdate},
+    model_statistics::MagnitudeRange,
+    models::{
+        BasisConditioning, Constraint, OptimizationProblem, Solution as DomainSolution,
+        SolverStatistics,
+    },
+    scenarios::Scenario,
+    solver_service::{
+        CancellationToken, NullObserver, Result, SolveObserver, SolverError, SolverOption,
+        SolverService, ILL_CONDITIONED_BASIS,
+    },
+    unbounded_ray::UnboundedRay,
     value_objects::{
-        ConstraintType, OptimizationType, SolutionStatus as DomainSolutionStatus, VariableType,
+        ConstraintType, OptimizationType, SolutionStatus as DomainSolutionStatus, SolverBackend,
+        VariableType,
     },
 };
+use crate::solver::{highs_options::highs_options, SolverFactory};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::Instant;
 
-pub struct HighsSolver;
+pub struct HighsSolver {
+    /// Idle instances kept between solves (None = a new instance per solve)
+    pool: Option<InstancePool>,
+}
 
 impl HighsSolver {
     pub fn new() -> Self {
-        Self
+        Self { pool: None }
+    }
+
+    /// Solver keeping up to `instances` HiGHS instances warm between solves
+    ///
+    /// Creating an instance costs about as much as solving a tiny LP, so services
+    /// solving many small problems should reuse them. Each solve loads its model
+    /// into an idle instance; concurrent solves beyond the idle ones create their own.
+    pub fn pooled(instances: usize) -> Self {
+        Self {
+            pool: (instances > 0).then(|| InstancePool {
+                idle: Mutex::new(Vec::with_capacity(instances)),
+                capacity: instances,
+            }),
+        }
+    }
+
+    /// HiGHS model of `problem` with its options set, in an idle instance when the
+    /// pool has one
+    fn model(&self, problem: &OptimizationProblem) -> Result<highs::Model> {
+        let mut model = self
+            .pool
+            .as_ref()
+            .and_then(InstancePool::take)
+            .and_then(|Idle(mut model)| {
+                // SAFETY: `model` owns a live HiGHS instance with no model loaded
+                unsafe { load_model(model.as_mut_ptr(), problem) }.then_some(model)
+            })
+            .unwrap_or_else(|| build_model(problem));
+        // SAFETY: `model` owns a live HiGHS instance
+        match unsafe { set_options(model.as_mut_ptr(), problem) } {
+            Ok(()) => Ok(model),
+            Err(e) => {
+                self.release(model);
+                Err(e)
+            }
+        }
+    }
+
+    /// Return a model's instance to the pool, emptied, if there is room
+    fn release(&self, mut model: highs::Model) {
+        if let Some(pool) = &self.pool {
+            // SAFETY: `model` owns a live HiGHS instance
+            unsafe {
+                ProgressContext::detach(model.as_mut_ptr());
+                highs_sys::Highs_clearModel(model.as_mut_ptr());
+            }
+            pool.put(Idle(model));
+        }
+    }
+}
+
+/// HiGHS instances waiting for their next solve
+struct InstancePool {
+    idle: Mutex<Vec<Idle>>,
+    capacity: usize,
+}
+
+impl InstancePool {
+    fn take(&self) -> Option<Idle> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop()
+    }
+
+    fn put(&self, instance: Idle) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < self.capacity {
+            idle.push(instance);
+        }
+    }
+}
+
+/// An instance with no model loaded and no callback attached
+struct Idle(highs::Model);
+
+// SAFETY: a HiGHS instance isn't tied to the thread that created it, and the pool
+// hands each one to a single solve at a time
+unsafe impl Send for Idle {}
+
+/// A built HiGHS model that updates edit in place
+pub struct HighsSession {
+    /// None once a failed solve took the instance with it; the next solve rebuilds
+    model: Option<highs::Model>,
+    problem: OptimizationProblem,
+}
+
+// SAFETY: as for `Idle`; `&mut self` methods keep the instance to one thread at a time
+unsafe impl Send for HighsSession {}
+
+impl ModelSession for HighsSession {
+    fn problem(&self) -> &OptimizationProblem {
+        &self.problem
+    }
+
+    fn update(&mut self, update: &ModelUpdate) -> Result<()> {
+        let first_new_row = self.problem.constraints.len();
+        update.apply_to(&mut self.problem)?;
+        if let Some(model) = &mut self.model {
+            // SAFETY: `model` owns a live HiGHS instance holding the problem before
+            // the update, which only appends rows
+            let added = unsafe {
+                patch_model(model.as_mut_ptr(), &self.problem, &update.changes);
+                self.problem.constraints[first_new_row..]
+                    .iter()
+                    .all(|constraint| add_row(model.as_mut_ptr(), constraint))
+            };
+            if !added {
+                self.model = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn solve(
+        &mut self,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<DomainSolution> {
+        if cancel.is_cancelled() {
+            return Err(SolverError::Cancelled);
+        }
+
+        let start_time = Instant::now();
+        let mut model = match self.model.take() {
+            Some(model) => model,
+            None => {
+                let mut model = build_model(&self.problem);
+                // SAFETY: `model` owns a live HiGHS instance
+                unsafe { set_options(model.as_mut_ptr(), &self.problem)? };
+                model
+            }
+        };
+        let progress = ProgressContext::new(observer, &self.problem.objective.coefficients, cancel);
+        // SAFETY: `progress` outlives the solve, and is detached before the model is kept
+        unsafe {
+            progress.attach(model.as_mut_ptr(), self.problem.is_mixed_integer());
+            set_mip_start(model.as_mut_ptr(), &self.problem);
+        }
+        let solved = model.try_solve().map_err(|status| {
+            SolverError::ExecutionFailed(format!("HiGHS solver failed: {:?}", status))
+        })?;
+        let solution = read_solution(&self.problem, &solved, observer, start_time);
+        let mut model: highs::Model = solved.into();
+        // SAFETY: `model` owns a live HiGHS instance
+        unsafe { ProgressContext::detach(model.as_mut_ptr()) };
+        self.model = Some(model);
+        solution
+    }
+
+    fn is_incremental(&self) -> bool {
+        true
     }
 }
 
@@ -27,150 +196,1054 @@ impl Default for HighsSolver {
 
 impl SolverService for HighsSolver {
     fn solve(&self, problem: &OptimizationProblem) -> Result<DomainSolution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<DomainSolution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    /// MIP progress comes from HiGHS callbacks; LPs report their final solution.
+    /// Cancellation is polled from the simplex, IPM and MIP interrupt callbacks.
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<DomainSolution> {
         // Validate first
         self.validate(problem)?;
+        if cancel.is_cancelled() {
+            return Err(SolverError::Cancelled);
+        }
 
         let start_time = Instant::now();
-        let num_vars = problem.num_variables();
+        let mut model = self.model(problem)?;
+        let progress = ProgressContext::new(observer, &problem.objective.coefficients, cancel);
+        // SAFETY: `progress` outlives the solve, the only time HiGHS calls back
+        unsafe {
+            progress.attach(model.as_mut_ptr(), problem.is_mixed_integer());
+            set_mip_start(model.as_mut_ptr(), problem);
+        }
+        let solved = model.try_solve().map_err(|status| {
+            SolverError::ExecutionFailed(format!("HiGHS solver failed: {:?}", status))
+        })?;
+        let solution = read_solution(problem, &solved, observer, start_time);
+        self.release(solved.into());
+        solution
+    }
 
-        // Count integer variables
-        let num_integer = problem
-            .variables
-            .iter()
-            .filter(|v| matches!(v.variable_type, VariableType::Integer))
-            .count() as u32;
-        let num_binary = problem
-            .variables
-            .iter()
-            .filter(|v| matches!(v.variable_type, VariableType::Binary))
-            .count() as u32;
-
-        // Use HiGHS RowProblem (add variables first, then constraints)
-        use highs::{HighsModelStatus, RowProblem, Sense};
-
-        let mut pb = RowProblem::default();
-        let mut vars = Vec::new();
-
-        // Add variables
-        for var_def in &problem.variables {
-            let lower = var_def.lower_bound;
-            let upper = var_def.upper_bound.unwrap_or(f64::INFINITY);
-
-            let obj_coeff = problem
-                .objective
-                .coefficients
-                .get(vars.len())
-                .copied()
-                .unwrap_or(0.0);
-
-            let col = match var_def.variable_type {
-                VariableType::Integer | VariableType::Binary => {
-                    pb.add_integer_column(obj_coeff, lower..upper)
+    /// Builds the base model once and patches its bounds and costs for each
+    /// scenario, so LPs restart from the previous scenario's basis
+    fn solve_scenarios(
+        &self,
+        problem: &OptimizationProblem,
+        scenarios: &[Scenario],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Result<DomainSolution>>> {
+        self.validate(problem)?;
+
+        let mut outcomes = Vec::with_capacity(scenarios.len());
+        let mut built: Option<(highs::Model, &Scenario)> = None;
+        for scenario in scenarios {
+            if cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            let scenario_problem = match scenario.apply(problem) {
+                Ok(scenario_problem) => scenario_problem,
+                Err(e) => {
+                    outcomes.push(Err(e));
+                    continue;
                 }
-                VariableType::Continuous => pb.add_column(obj_coeff, lower..upper),
             };
+
+            let start_time = Instant::now();
+            let mut model = match built.take() {
+                Some((mut model, previous)) => {
+                    // SAFETY: `model` owns a live HiGHS instance
+                    unsafe { patch_model(model.as_mut_ptr(), problem, previous) };
+                    model
+                }
+                None => self.model(problem)?,
+            };
+            // SAFETY: as above
+            unsafe { patch_model(model.as_mut_ptr(), &scenario_problem, scenario) };
+            let progress = ProgressContext::new(
+                &NullObserver,
+                &scenario_problem.objective.coefficients,
+                cancel,
+            );
+            // SAFETY: `progress` outlives the solve, the only time HiGHS calls back
+            unsafe { progress.attach(model.as_mut_ptr(), scenario_problem.is_mixed_integer()) };
+            match model.try_solve() {
+                Ok(solved) => {
+                    let outcome =
+                        read_solution(&scenario_problem, &solved, &NullObserver, start_time);
+                    if cancel.is_cancelled() {
+                        self.release(solved.into());
+                        return Err(SolverError::Cancelled);
+                    }
+                    outcomes.push(outcome);
+                    built = Some((solved.into(), scenario));
+                }
+                // The model is gone with the failed solve; the next scenario rebuilds it
+                Err(status) => outcomes.push(Err(SolverError::ExecutionFailed(format!(
+                    "HiGHS solver failed: {:?}",
+                    status
+                )))),
+            }
+        }
+        if let Some((model, _)) = built {
+            self.release(model);
+        }
+        Ok(outcomes)
+    }
+
+    /// Keeps the HiGHS instance, so LP re-solves start from the previous basis
+    fn open_session(&self, problem: &OptimizationProblem) -> Result<Option<Box<dyn ModelSession>>> {
+        self.validate(problem)?;
+        Ok(Some(Box::new(HighsSession {
+            model: Some(self.model(problem)?),
+            problem: problem.clone(),
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "HiGHS"
+    }
+
+    fn supports_mip(&self) -> bool {
+        true
+    }
+
+    fn version(&self) -> String {
+        // SAFETY: Highs_version returns a pointer to a static NUL-terminated string
+        unsafe { CStr::from_ptr(highs_sys::Highs_version()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec![
+            "Mixed-Integer Programming".to_string(),
+            "Linear Programming".to_string(),
+            "Primal/Dual Simplex".to_string(),
+            "Interior Point Method".to_string(),
+            "Presolve".to_string(),
+        ]
+    }
+
+    fn options(&self) -> Vec<SolverOption> {
+        highs_options()
+    }
+}
+
+/// HiGHS model of `problem`, ready to solve
+fn build_model(problem: &OptimizationProblem) -> highs::Model {
+    // Use HiGHS RowProblem (add variables first, then constraints)
+    use highs::{RowProblem, Sense};
+
+    let mut pb = RowProblem::default();
+    let mut vars = Vec::new();
+
+    // Add variables
+    for var_def in &problem.variables {
+        let lower = var_def.lower_bound;
+        let upper = var_def.upper_bound.unwrap_or(f64::INFINITY);
+
+        let obj_coeff = problem
+            .objective
+            .coefficients
+            .get(vars.len())
+            .copied()
+            .unwrap_or(0.0);
+
+        let col = match var_def.variable_type {
+            VariableType::Integer | VariableType::Binary => {
+                pb.add_integer_column(obj_coeff, lower..upper)
+            }
+            VariableType::Continuous => pb.add_column(obj_coeff, lower..upper),
+        };
+        vars.push(col);
+    }
+
+    // If no variables specified, create defaults
+    if problem.variables.is_empty() {
+        for &coeff in problem.objective.coefficients.iter() {
+            let col = pb.add_column(coeff, 0..);
             vars.push(col);
         }
+    }
 
-        // If no variables specified, create defaults
-        if problem.variables.is_empty() {
-            for &coeff in problem.objective.coefficients.iter() {
-                let col = pb.add_column(coeff, 0..);
-                vars.push(col);
+    // Add constraints from their stored non-zeros
+    for constraint in &problem.constraints {
+        let terms = constraint
+            .coefficients
+            .iter()
+            .map(|(i, coeff)| (vars[i], coeff));
+
+        match constraint.constraint_type {
+            ConstraintType::LessThanOrEqual => {
+                pb.add_row(..=constraint.bound, terms);
+            }
+            ConstraintType::Equal => {
+                pb.add_row(constraint.bound..=constraint.bound, terms);
+            }
+            ConstraintType::GreaterThanOrEqual => {
+                pb.add_row(constraint.bound.., terms);
             }
         }
+    }
 
-        // Add constraints
-        for constraint in &problem.constraints {
-            let mut terms = Vec::new();
-            for (i, &coeff) in constraint.coefficients.iter().enumerate() {
-                if coeff != 0.0 && i < vars.len() {
-                    terms.push((vars[i], coeff));
-                }
+    // Solve the problem
+    let sense = if problem.objective.optimization_type == OptimizationType::Maximize {
+        Sense::Maximise
+    } else {
+        Sense::Minimise
+    };
+
+    pb.optimise(sense)
+}
+
+/// Load `problem` into an instance without a model, as [`build_model`] would
+/// build it; false if HiGHS rejects it
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn load_model(highs: *mut c_void, problem: &OptimizationProblem) -> bool {
+    use highs_sys::HighsInt;
+
+    let num_cols = problem.num_variables();
+    let mut lower = Vec::with_capacity(num_cols);
+    let mut upper = Vec::with_capacity(num_cols);
+    let mut integrality = Vec::with_capacity(num_cols);
+    for column in 0..num_cols {
+        let var = problem.variables.get(column);
+        lower.push(var.map_or(0.0, |var| var.lower_bound));
+        upper.push(var.and_then(|var| var.upper_bound).unwrap_or(f64::INFINITY));
+        let integer = var.is_some_and(|var| {
+            matches!(
+                var.variable_type,
+                VariableType::Integer | VariableType::Binary
+            )
+        });
+        integrality.push(if integer {
+            highs_sys::kHighsVarTypeInteger
+        } else {
+            highs_sys::kHighsVarTypeContinuous
+        });
+    }
+
+    let (row_lower, row_upper): (Vec<f64>, Vec<f64>) =
+        problem.constraints.iter().map(row_bounds).unzip();
+    // HiGHS takes the rows as one row-wise matrix with its own index type
+    let num_nonzeros = problem.num_nonzeros();
+    let mut starts: Vec<HighsInt> = Vec::with_capacity(problem.constraints.len());
+    let mut indices: Vec<HighsInt> = Vec::with_capacity(num_nonzeros);
+    let mut values: Vec<f64> = Vec::with_capacity(num_nonzeros);
+    for constraint in &problem.constraints {
+        starts.push(indices.len() as HighsInt);
+        let row = &constraint.coefficients;
+        indices.extend(row.indices().iter().map(|&j| j as HighsInt));
+        values.extend_from_slice(row.values());
+    }
+    let sense = match problem.objective.optimization_type {
+        OptimizationType::Minimize => highs_sys::kHighsObjSenseMinimize,
+        OptimizationType::Maximize => highs_sys::kHighsObjSenseMaximize,
+    };
+
+    let (num_rows, num_nonzeros) = (row_lower.len() as HighsInt, num_nonzeros as HighsInt);
+    let format = highs_sys::kHighsMatrixFormatRowwise;
+    let status = if problem.is_mixed_integer() {
+        highs_sys::Highs_passMip(
+            highs,
+            num_cols as HighsInt,
+            num_rows,
+            num_nonzeros,
+            format,
+            sense,
+            0.0,
+            problem.objective.coefficients.as_ptr(),
+            lower.as_ptr(),
+            upper.as_ptr(),
+            row_lower.as_ptr(),
+            row_upper.as_ptr(),
+            starts.as_ptr(),
+            indices.as_ptr(),
+            values.as_ptr(),
+            integrality.as_ptr(),
+        )
+    } else {
+        highs_sys::Highs_passLp(
+            highs,
+            num_cols as HighsInt,
+            num_rows,
+            num_nonzeros,
+            format,
+            sense,
+            0.0,
+            problem.objective.coefficients.as_ptr(),
+            lower.as_ptr(),
+            upper.as_ptr(),
+            row_lower.as_ptr(),
+            row_upper.as_ptr(),
+            starts.as_ptr(),
+            indices.as_ptr(),
+            values.as_ptr(),
+        )
+    };
+    status != highs_sys::kHighsStatusError
+}
+
+/// Set the entries `scenario` changes to their values in `problem`
+///
+/// With the scenario applied to `problem` this applies it; with the base problem
+/// it undoes it.
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance holding a model of `problem`'s shape.
+unsafe fn patch_model(highs: *mut c_void, problem: &OptimizationProblem, scenario: &Scenario) {
+    for &(row, _) in &scenario.rhs {
+        let (lower, upper) = row_bounds(&problem.constraints[row]);
+        highs_sys::Highs_changeRowBounds(highs, row as highs_sys::HighsInt, lower, upper);
+    }
+    for &(column, _, _) in &scenario.bounds {
+        let (lower, upper) = problem
+            .variables
+            .get(column)
+            .map_or((0.0, None), |var| (var.lower_bound, var.upper_bound));
+        highs_sys::Highs_changeColBounds(
+            highs,
+            column as highs_sys::HighsInt,
+            lower,
+            upper.unwrap_or(f64::INFINITY),
+        );
+    }
+    let sign = if minimization_form(problem) {
+        -1.0
+    } else {
+        1.0
+    };
+    for &(column, _) in &scenario.objective {
+        highs_sys::Highs_changeColCost(
+            highs,
+            column as highs_sys::HighsInt,
+            sign * problem.objective.coefficients[column],
+        );
+    }
+}
+
+/// Append `constraint` to the model in `highs`; false if HiGHS rejects it
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance with a column for each of the
+/// constraint's coefficients.
+unsafe fn add_row(highs: *mut c_void, constraint: &Constraint) -> bool {
+    use highs_sys::HighsInt;
+
+    let row = &constraint.coefficients;
+    let indices: Vec<HighsInt> = row.indices().iter().map(|&j| j as HighsInt).collect();
+    let (lower, upper) = row_bounds(constraint);
+    let status = highs_sys::Highs_addRow(
+        highs,
+        lower,
+        upper,
+        indices.len() as HighsInt,
+        indices.as_ptr(),
+        row.values().as_ptr(),
+    );
+    status != highs_sys::kHighsStatusError
+}
+
+/// Reset the instance's options, then set those the factory picks for `problem`
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn set_options(highs: *mut c_void, problem: &OptimizationProblem) -> Result<()> {
+    highs_sys::Highs_resetOptions(highs);
+    highs_sys::Highs_setBoolOptionValue(highs, c"output_flag".as_ptr(), 0);
+    for (name, value) in SolverFactory::backend_options(SolverBackend::Highs, problem) {
+        let rejected = match (CString::new(name.as_str()), CString::new(value.as_str())) {
+            (Ok(c_name), Ok(c_value)) => {
+                highs_sys::Highs_setOptionValue(highs, c_name.as_ptr(), c_value.as_ptr())
+                    == highs_sys::kHighsStatusError
             }
+            _ => true,
+        };
+        if rejected {
+            return Err(SolverError::InvalidProblem(format!(
+                "HiGHS rejects option {} = '{}'",
+                name, value
+            )));
+        }
+    }
+    set_objective_form(highs, problem);
+    Ok(())
+}
 
-            match constraint.constraint_type {
-                ConstraintType::LessThanOrEqual => {
-                    pb.add_row(..=constraint.bound, &terms);
-                }
-                ConstraintType::Equal => {
-                    pb.add_row(constraint.bound..=constraint.bound, &terms);
+/// Whether HiGHS gets `problem` as a minimization of the negated objective
+///
+/// HiGHS's interior point without crossover gets the duals of a maximization
+/// backwards and then rejects its own solution as not optimal, so those solves
+/// minimize `-c` instead.
+fn minimization_form(problem: &OptimizationProblem) -> bool {
+    problem.objective.optimization_type == OptimizationType::Maximize && problem.skips_crossover()
+}
+
+/// Give a loaded maximization the objective form [`minimization_form`] asks for,
+/// whichever form the instance holds
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance holding `problem`.
+unsafe fn set_objective_form(highs: *mut c_void, problem: &OptimizationProblem) {
+    if problem.objective.optimization_type != OptimizationType::Maximize {
+        return;
+    }
+    let minimize = minimization_form(problem);
+    let mut sense: highs_sys::HighsInt = 0;
+    highs_sys::Highs_getObjectiveSense(highs, &mut sense);
+    if minimize == (sense == highs_sys::kHighsObjSenseMinimize) {
+        return;
+    }
+    let (sense, sign) = if minimize {
+        (highs_sys::kHighsObjSenseMinimize, -1.0)
+    } else {
+        (highs_sys::kHighsObjSenseMaximize, 1.0)
+    };
+    let costs: Vec<f64> = problem
+        .objective
+        .coefficients
+        .iter()
+        .map(|c| sign * c)
+        .collect();
+    highs_sys::Highs_changeObjectiveSense(highs, sense);
+    if !costs.is_empty() {
+        highs_sys::Highs_changeColsCostByRange(
+            highs,
+            0,
+            costs.len() as highs_sys::HighsInt - 1,
+            costs.as_ptr(),
+        );
+    }
+}
+
+/// Hand HiGHS the problem's MIP start, which it completes itself when partial
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance holding a model of `problem`.
+unsafe fn set_mip_start(highs: *mut c_void, problem: &OptimizationProblem) {
+    use highs_sys::HighsInt;
+
+    if !problem.is_mixed_integer() {
+        return;
+    }
+    let (indices, values): (Vec<HighsInt>, Vec<f64>) = problem
+        .mip_start()
+        .into_iter()
+        .map(|(j, value)| (j as HighsInt, value))
+        .unzip();
+    if !indices.is_empty() {
+        highs_sys::Highs_setSparseSolution(
+            highs,
+            indices.len() as HighsInt,
+            indices.as_ptr(),
+            values.as_ptr(),
+        );
+    }
+}
+
+/// HiGHS row bounds of a constraint
+fn row_bounds(constraint: &Constraint) -> (f64, f64) {
+    match constraint.constraint_type {
+        ConstraintType::LessThanOrEqual => (f64::NEG_INFINITY, constraint.bound),
+        ConstraintType::Equal => (constraint.bound, constraint.bound),
+        ConstraintType::GreaterThanOrEqual => (constraint.bound, f64::INFINITY),
+    }
+}
+
+/// Domain solution of a solved model of `problem`
+fn read_solution(
+    problem: &OptimizationProblem,
+    solved: &highs::SolvedModel,
+    observer: &dyn SolveObserver,
+    start_time: Instant,
+) -> Result<DomainSolution> {
+    use highs::HighsModelStatus;
+
+    // SAFETY: `solved` owns a live HiGHS instance
+    let model_status = unsafe { highs_sys::Highs_getModelStatus(solved.as_ptr()) };
+    if model_status == highs_sys::kHighsModelStatusInterrupt {
+        return Err(SolverError::Cancelled);
+    }
+    if model_status == highs_sys::MODEL_STATUS_REACHED_MEMORY_LIMIT {
+        return Err(SolverError::MemoryLimit(
+            "HiGHS ran out of memory".to_string(),
+        ));
+    }
+    let solve_time = start_time.elapsed().as_secs_f64() * 1000.0;
+
+    // Count integer variables
+    let num_integer = problem
+        .variables
+        .iter()
+        .filter(|v| matches!(v.variable_type, VariableType::Integer))
+        .count() as u32;
+    let num_binary = problem
+        .variables
+        .iter()
+        .filter(|v| matches!(v.variable_type, VariableType::Binary))
+        .count() as u32;
+
+    // Build statistics
+    let statistics = SolverStatistics {
+        simplex_iterations: 0,
+        nodes_explored: 0,
+        solve_time_ms: solve_time,
+        num_variables: problem.num_variables() as u32,
+        num_constraints: problem.constraints.len() as u32,
+        num_integer_vars: num_integer,
+        num_binary_vars: num_binary,
+        solved_by: None,
+        presolve: None,
+        resources: None,
+        conditioning: None,
+    };
+
+    // HiGHS reports its MIP node, leaf and improving-solution limits as a solution
+    // limit, a status the highs crate can't represent. With a solution limit of
+    // one, stopping with an incumbent means it stopped on the first one
+    if model_status == highs_sys::kHighsModelStatusSolutionLimit {
+        // SAFETY: `solved` owns a live HiGHS instance
+        let first_feasible = problem.solver_config.stop_at_first_feasible
+            && unsafe { has_feasible_solution(solved.as_ptr()) };
+        let (status, reason) = if first_feasible {
+            (
+                DomainSolutionStatus::Feasible,
+                "Stopped at the first feasible solution",
+            )
+        } else {
+            (DomainSolutionStatus::NodeLimit, "Node limit reached")
+        };
+        return Ok(stopped(problem, solved, status, reason).with_statistics(statistics));
+    }
+
+    // Process result
+    match solved.status() {
+        HighsModelStatus::Optimal => {
+            let solution_data = solved.get_solution();
+            let variable_values = solution_data.columns().to_vec();
+            let actual_obj = objective_value(problem, &variable_values);
+
+            if !problem.is_mixed_integer() {
+                observer.on_incumbent(actual_obj, &variable_values);
+            }
+
+            let mut solution = DomainSolution::optimal(actual_obj, variable_values);
+            solution.statistics = statistics;
+            // HiGHS calls a MIP optimal once it is within the gap tolerances, so
+            // report the bound it actually proved
+            if problem.is_mixed_integer() {
+                // SAFETY: `solved` owns a live HiGHS instance
+                if let Some(bound) = unsafe { dual_bound(solved.as_ptr()) } {
+                    solution = solution.with_bound(bound);
                 }
-                ConstraintType::GreaterThanOrEqual => {
-                    pb.add_row(constraint.bound.., &terms);
+            }
+            // Row duals are only meaningful for continuous problems. HiGHS solves in
+            // the problem's own sense, so they follow the domain convention unless
+            // it was given the minimization form
+            if !problem.is_mixed_integer() {
+                solution =
+                    solution.with_duals(solution_data.dual_rows(), minimization_form(problem));
+                // SAFETY: `solved` owns a live HiGHS instance
+                solution.statistics.conditioning =
+                    unsafe { basis_conditioning(solved.as_ptr(), problem) };
+            }
+            solution.message = format!("Optimal solution found for '{}'", problem.name);
+            if let Some(basis) = solution.statistics.conditioning {
+                if basis.condition_estimate > ILL_CONDITIONED_BASIS {
+                    solution.message = format!(
+                        "{}; the final basis is ill-conditioned (condition estimate {:.1e}), so the values may be numerically fragile",
+                        solution.message, basis.condition_estimate
+                    );
                 }
             }
+
+            Ok(solution)
+        }
+        HighsModelStatus::Infeasible => {
+            // SAFETY: `solved` owns a live HiGHS instance
+            let certificate = unsafe { dual_ray(solved.as_ptr(), problem) };
+            Ok(infeasible(certificate).with_statistics(statistics))
+        }
+        HighsModelStatus::Unbounded => {
+            // SAFETY: `solved` owns a live HiGHS instance
+            let ray = unsafe { primal_ray(solved.as_ptr(), problem) };
+            Ok(unbounded(ray).with_statistics(statistics))
+        }
+        HighsModelStatus::UnboundedOrInfeasible => {
+            Ok(unbounded_or_infeasible(problem, solved).with_statistics(statistics))
+        }
+        HighsModelStatus::ReachedTimeLimit => Ok(stopped(
+            problem,
+            solved,
+            DomainSolutionStatus::TimeLimit,
+            "Time limit reached",
+        )
+        .with_statistics(statistics)),
+        HighsModelStatus::ReachedIterationLimit => Ok(stopped(
+            problem,
+            solved,
+            DomainSolutionStatus::IterationLimit,
+            "Iteration limit reached",
+        )
+        .with_statistics(statistics)),
+        // Stops requested through `objective_bound`/`objective_target` options
+        HighsModelStatus::ObjectiveBound | HighsModelStatus::ObjectiveTarget => Ok(stopped(
+            problem,
+            solved,
+            DomainSolutionStatus::Feasible,
+            "Objective target reached",
+        )
+        .with_statistics(statistics)),
+        // HiGHS reports these when it gives up on an ill-conditioned model
+        status @ (HighsModelStatus::SolveError
+        | HighsModelStatus::PostsolveError
+        | HighsModelStatus::Unknown) => Err(SolverError::NumericalDifficulty(format!(
+            "HiGHS solver returned status: {:?}",
+            status
+        ))),
+        status => Err(SolverError::ExecutionFailed(format!(
+            "HiGHS solver returned status: {:?}",
+            status
+        ))),
+    }
+}
+
+/// Objective of `problem` at `values`
+fn objective_value(problem: &OptimizationProblem, values: &[f64]) -> f64 {
+    values
+        .iter()
+        .zip(&problem.objective.coefficients)
+        .map(|(value, coeff)| coeff * value)
+        .sum()
+}
+
+/// Solution of an infeasible problem, with the certificate HiGHS found if any
+fn infeasible(certificate: Option<InfeasibilityCertificate>) -> DomainSolution {
+    let mut solution = DomainSolution::new(
+        DomainSolutionStatus::Infeasible,
+        "Problem is infeasible: no solution satisfies all constraints",
+    );
+    solution.infeasibility_certificate = certificate;
+    solution
+}
+
+/// Solution of an unbounded problem, with the ray HiGHS found if any
+fn unbounded(ray: Option<UnboundedRay>) -> DomainSolution {
+    let mut solution = DomainSolution::new(
+        DomainSolutionStatus::Unbounded,
+        "Problem is unbounded: objective can be improved infinitely",
+    );
+    solution.unbounded_ray = ray;
+    solution
+}
+
+/// Solution of a problem HiGHS found unbounded or infeasible without saying which,
+/// as its presolve often does
+///
+/// A Farkas certificate settles it. Failing that, the problem is solved again
+/// without its objective, which can't be unbounded: if that finds a feasible point,
+/// the problem is unbounded.
+fn unbounded_or_infeasible(
+    problem: &OptimizationProblem,
+    solved: &highs::SolvedModel,
+) -> DomainSolution {
+    use highs::HighsModelStatus;
+
+    // SAFETY: `solved` owns a live HiGHS instance
+    if let Some(certificate) = unsafe { dual_ray(solved.as_ptr(), problem) } {
+        return infeasible(Some(certificate));
+    }
+    let mut feasibility = problem.clone();
+    feasibility.objective.coefficients.fill(0.0);
+    feasibility.solver_config.stop_at_first_feasible = false;
+    let mut model = build_model(&feasibility);
+    // SAFETY: `model` owns a live HiGHS instance
+    let checked = match unsafe { set_options(model.as_mut_ptr(), &feasibility) } {
+        Ok(()) => model.try_solve().ok(),
+        Err(_) => None,
+    };
+    match checked {
+        // SAFETY: `solved` and `checked` own live HiGHS instances
+        Some(checked) if checked.status() == HighsModelStatus::Optimal => {
+            unbounded(unsafe { primal_ray(solved.as_ptr(), problem) })
+        }
+        Some(checked) if checked.status() == HighsModelStatus::Infeasible => {
+            infeasible(unsafe { dual_ray(checked.as_ptr(), problem) })
+        }
+        _ => DomainSolution::new(
+            DomainSolutionStatus::Unbounded,
+            "Problem is unbounded or infeasible: HiGHS could not tell which, so check \
+             the constraints for a conflict as well as for a missing bound",
+        ),
+    }
+}
+
+/// Solution of a solve HiGHS stopped early, with its incumbent if it has one
+fn stopped(
+    problem: &OptimizationProblem,
+    solved: &highs::SolvedModel,
+    status: DomainSolutionStatus,
+    reason: &str,
+) -> DomainSolution {
+    // SAFETY: `solved` owns a live HiGHS instance
+    let incumbent = unsafe { has_feasible_solution(solved.as_ptr()) }.then(|| {
+        let values = solved.get_solution().columns().to_vec();
+        (objective_value(problem, &values), values)
+    });
+    let solution = DomainSolution::stopped(status, reason, &problem.name, incumbent);
+    // HiGHS leaves its MIP bound at zero for LPs
+    if !problem.is_mixed_integer() {
+        return solution;
+    }
+    // SAFETY: as above
+    match unsafe { dual_bound(solved.as_ptr()) } {
+        Some(bound) => solution.with_bound(bound),
+        None => solution,
+    }
+}
+
+/// Best bound the MIP search proved, in the problem's sense
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn dual_bound(highs: *const c_void) -> Option<f64> {
+    let mut bound = f64::NAN;
+    let ok = highs_sys::Highs_getDoubleInfoValue(highs, c"mip_dual_bound".as_ptr(), &mut bound)
+        != highs_sys::kHighsStatusError;
+    (ok && bound.is_finite()).then_some(bound)
+}
+
+/// Ray certifying that `problem` is unbounded, if HiGHS can find one
+///
+/// HiGHS solves an extra LP for it when the solve that detected unboundedness
+/// (e.g. presolve) didn't leave one.
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance holding a solved `problem`.
+unsafe fn primal_ray(highs: *const c_void, problem: &OptimizationProblem) -> Option<UnboundedRay> {
+    let mut has_ray: highs_sys::HighsInt = 0;
+    let mut direction = vec![0.0; problem.num_variables()];
+    let status = highs_sys::Highs_getPrimalRay(highs, &mut has_ray, direction.as_mut_ptr());
+    if status == highs_sys::kHighsStatusError || has_ray == 0 {
+        return None;
+    }
+    UnboundedRay::new(problem, &direction)
+}
+
+/// Farkas certificate that `problem` is infeasible, if HiGHS can find one
+///
+/// Like [`primal_ray`], HiGHS solves an extra LP when presolve detected the
+/// infeasibility. MIPs are certified through their LP relaxation, when it is
+/// infeasible too.
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance holding a solved `problem`.
+unsafe fn dual_ray(
+    highs: *const c_void,
+    problem: &OptimizationProblem,
+) -> Option<InfeasibilityCertificate> {
+    let mut has_ray: highs_sys::HighsInt = 0;
+    let mut multipliers = vec![0.0; problem.constraints.len()];
+    let status = highs_sys::Highs_getDualRay(highs, &mut has_ray, multipliers.as_mut_ptr());
+    if status == highs_sys::kHighsStatusError || has_ray == 0 {
+        return None;
+    }
+    InfeasibilityCertificate::new(problem, &multipliers)
+}
+
+/// Whether HiGHS holds a feasible primal solution, as after a MIP stopped early
+/// with an incumbent
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance.
+unsafe fn has_feasible_solution(highs: *const c_void) -> bool {
+    let mut status: highs_sys::HighsInt = 0;
+    highs_sys::Highs_getIntInfoValue(highs, c"primal_solution_status".as_ptr(), &mut status)
+        != highs_sys::kHighsStatusError
+        && status == highs_sys::kHighsSolutionStatusFeasible
+}
+
+/// Condition estimate and coefficient range of the basis HiGHS ended an LP with
+///
+/// `‖B‖₁` is exact; `‖B⁻¹‖₁` comes from Hager's estimator, which needs a handful of
+/// solves with the factored basis rather than its inverse.
+///
+/// # Safety
+/// `highs` must be a live HiGHS instance holding a solved `problem`.
+unsafe fn basis_conditioning(
+    highs: *const c_void,
+    problem: &OptimizationProblem,
+) -> Option<BasisConditioning> {
+    const MAX_STEPS: usize = 5;
+
+    let rows = problem.constraints.len();
+    if rows == 0 || highs_sys::Highs_getNumRow(highs) as usize != rows {
+        return None;
+    }
+    let mut basic = vec![0 as highs_sys::HighsInt; rows];
+    if highs_sys::Highs_getBasicVariables(highs, basic.as_mut_ptr()) == highs_sys::kHighsStatusError
+    {
+        return None;
+    }
+
+    // Basic columns of [A | I]; a slack's column is a unit vector
+    let column = |index: highs_sys::HighsInt| -> Vec<f64> {
+        match usize::try_from(index) {
+            Ok(col) => problem
+                .constraints
+                .iter()
+                .map(|c| c.coefficients.get(col))
+                .collect(),
+            Err(_) => vec![1.0],
         }
+    };
+    let columns: Vec<Vec<f64>> = basic.iter().map(|&index| column(index)).collect();
+    let norm = columns
+        .iter()
+        .map(|col| col.iter().map(|v| v.abs()).sum::<f64>())
+        .fold(0.0, f64::max);
+    let coefficient_range = MagnitudeRange::of(columns.iter().flatten().copied());
 
-        // Solve the problem
-        let sense = if problem.objective.optimization_type == OptimizationType::Maximize {
-            Sense::Maximise
+    let solve = |rhs: &[f64], transpose: bool| -> Option<Vec<f64>> {
+        let mut solution = vec![0.0; rows];
+        let mut index = vec![0 as highs_sys::HighsInt; rows];
+        let mut nonzeros: highs_sys::HighsInt = 0;
+        let solve = if transpose {
+            highs_sys::Highs_getBasisTransposeSolve
         } else {
-            Sense::Minimise
+            highs_sys::Highs_getBasisSolve
         };
+        let status = solve(
+            highs,
+            rhs.as_ptr(),
+            solution.as_mut_ptr(),
+            &mut nonzeros,
+            index.as_mut_ptr(),
+        );
+        (status != highs_sys::kHighsStatusError).then_some(solution)
+    };
 
-        let solved = pb.optimise(sense).solve();
-        let solve_time = start_time.elapsed().as_secs_f64() * 1000.0;
-
-        // Build statistics
-        let statistics = SolverStatistics {
-            simplex_iterations: 0,
-            nodes_explored: 0,
-            solve_time_ms: solve_time,
-            num_variables: num_vars as u32,
-            num_constraints: problem.constraints.len() as u32,
-            num_integer_vars: num_integer,
-            num_binary_vars: num_binary,
-        };
+    // Hager: climb ‖B⁻¹x‖₁ over the unit ball from the uniform vector
+    let mut x = vec![1.0 / rows as f64; rows];
+    let mut inverse_norm = 0.0_f64;
+    for _ in 0..MAX_STEPS {
+        let y = solve(&x, false)?;
+        inverse_norm = inverse_norm.max(y.iter().map(|v| v.abs()).sum());
+        let signs: Vec<f64> = y
+            .iter()
+            .map(|v| if *v < 0.0 { -1.0 } else { 1.0 })
+            .collect();
+        let z = solve(&signs, true)?;
+        let (steepest, slope) = z
+            .iter()
+            .map(|v| v.abs())
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let current: f64 = z.iter().zip(&x).map(|(z, x)| z * x).sum();
+        if slope <= current {
+            break;
+        }
+        x = vec![0.0; rows];
+        x[steepest] = 1.0;
+    }
 
-        // Process result
-        match solved.status() {
-            HighsModelStatus::Optimal => {
-                let solution_data = solved.get_solution();
-                let variable_values = solution_data.columns().to_vec();
-
-                // Calculate objective value
-                let mut actual_obj = 0.0;
-                for (i, &val) in variable_values.iter().enumerate() {
-                    if let Some(&coeff) = problem.objective.coefficients.get(i) {
-                        actual_obj += coeff * val;
-                    }
-                }
+    inverse_norm.is_finite().then_some(BasisConditioning {
+        condition_estimate: norm * inverse_norm,
+        coefficient_range,
+    })
+}
 
-                let mut solution = DomainSolution::optimal(actual_obj, variable_values);
-                solution.statistics = statistics;
-                solution.message = format!("Optimal solution found for '{}'", problem.name);
+type HighsCallback =
+    unsafe extern "C" fn(c_int, *const c_char, *const c_void, *mut c_void, *mut c_void);
 
-                Ok(solution)
+// Part of the HiGHS C API, but not covered by the highs-sys bindings
+extern "C" {
+    fn Highs_setCallback(
+        highs: *mut c_void,
+        callback: Option<HighsCallback>,
+        user_data: *mut c_void,
+    ) -> highs_sys::HighsInt;
+    fn Highs_getCallbackDataOutItem(
+        data_out: *const c_void,
+        item_name: *const c_char,
+    ) -> *const c_void;
+}
+
+/// What the HiGHS callback needs to forward log lines, MIP progress and
+/// cancellation
+struct ProgressContext<'a> {
+    observer: &'a dyn SolveObserver,
+    objective: &'a [f64],
+    cancel: &'a CancellationToken,
+    nodes: AtomicU64,
+    /// Bits of the last reported dual bound, so only changes are forwarded
+    bound: AtomicU64,
+    /// Log output since the last line ending; HiGHS sometimes writes a line in pieces
+    partial_line: Mutex<String>,
+}
+
+impl<'a> ProgressContext<'a> {
+    fn new(
+        observer: &'a dyn SolveObserver,
+        objective: &'a [f64],
+        cancel: &'a CancellationToken,
+    ) -> Self {
+        Self {
+            observer,
+            objective,
+            cancel,
+            nodes: AtomicU64::new(0),
+            bound: AtomicU64::new(f64::NAN.to_bits()),
+            partial_line: Mutex::new(String::new()),
+        }
+    }
+
+    /// Register for the log and interrupt checks on `highs`, plus improving
+    /// solutions for MIPs
+    ///
+    /// With the logging callback active HiGHS hands its log to the callback
+    /// instead of stdout, so output is turned on for the solve.
+    ///
+    /// # Safety
+    /// `highs` must be a live HiGHS instance, and `self` must outlive every call
+    /// HiGHS makes to the callback.
+    unsafe fn attach(&self, highs: *mut c_void, mip: bool) {
+        Highs_setCallback(
+            highs,
+            Some(progress_callback),
+            self as *const Self as *mut c_void,
+        );
+        highs_sys::Highs_startCallback(highs, highs_sys::kHighsCallbackLogging);
+        highs_sys::Highs_setBoolOptionValue(highs, c"output_flag".as_ptr(), 1);
+        highs_sys::Highs_startCallback(highs, highs_sys::kHighsCallbackSimplexInterrupt);
+        highs_sys::Highs_startCallback(highs, highs_sys::kHighsCallbackIpmInterrupt);
+        if mip {
+            highs_sys::Highs_startCallback(highs, highs_sys::kHighsCallbackMipImprovingSolution);
+            highs_sys::Highs_startCallback(highs, highs_sys::kHighsCallbackMipInterrupt);
+        }
+    }
+
+    /// Stop every callback [`attach`](Self::attach) may have registered on `highs`
+    ///
+    /// # Safety
+    /// `highs` must be a live HiGHS instance.
+    unsafe fn detach(highs: *mut c_void) {
+        // Without the callback HiGHS would write its log to stdout
+        highs_sys::Highs_setBoolOptionValue(highs, c"output_flag".as_ptr(), 0);
+        for callback in [
+            highs_sys::kHighsCallbackLogging,
+            highs_sys::kHighsCallbackSimplexInterrupt,
+            highs_sys::kHighsCallbackIpmInterrupt,
+            highs_sys::kHighsCallbackMipImprovingSolution,
+            highs_sys::kHighsCallbackMipInterrupt,
+        ] {
+            highs_sys::Highs_stopCallback(highs, callback);
+        }
+        Highs_setCallback(highs, None, std::ptr::null_mut());
+    }
+
+    /// # Safety
+    /// `message`, `data_out` and `data_in` must be the callback data HiGHS passed in
+    unsafe fn dispatch(
+        &self,
+        callback_type: c_int,
+        message: *const c_char,
+        data_out: *const c_void,
+        data_in: *mut c_void,
+    ) {
+        if callback_type == highs_sys::kHighsCallbackLogging as c_int {
+            if !message.is_null() {
+                self.log(&CStr::from_ptr(message).to_string_lossy());
             }
-            HighsModelStatus::Infeasible => {
-                let mut solution = DomainSolution::new(
-                    DomainSolutionStatus::Infeasible,
-                    "Problem is infeasible: no solution satisfies all constraints",
-                );
-                solution.statistics = statistics;
-                Ok(solution)
+            return;
+        }
+
+        let interrupt = [
+            highs_sys::kHighsCallbackSimplexInterrupt,
+            highs_sys::kHighsCallbackIpmInterrupt,
+            highs_sys::kHighsCallbackMipInterrupt,
+        ]
+        .contains(&(callback_type as highs_sys::HighsInt));
+        if interrupt && !data_in.is_null() && self.cancel.is_cancelled() {
+            // `user_interrupt` is the first field of HighsCallbackDataIn
+            *(data_in as *mut c_int) = 1;
+            return;
+        }
+
+        if callback_type == highs_sys::kHighsCallbackMipImprovingSolution as c_int {
+            let solution = data_item(data_out, c"mip_solution") as *const f64;
+            if !solution.is_null() {
+                let values = std::slice::from_raw_parts(solution, self.objective.len());
+                // Computed here, since HiGHS may report it in its internal sense
+                let objective = self.objective.iter().zip(values).map(|(c, x)| c * x).sum();
+                self.observer.on_incumbent(objective, values);
             }
-            HighsModelStatus::Unbounded | HighsModelStatus::UnboundedOrInfeasible => {
-                let mut solution = DomainSolution::new(
-                    DomainSolutionStatus::Unbounded,
-                    "Problem is unbounded: objective can be improved infinitely",
-                );
-                solution.statistics = statistics;
-                Ok(solution)
+        } else if callback_type != highs_sys::kHighsCallbackMipInterrupt as c_int {
+            return;
+        }
+
+        let nodes = data_item(data_out, c"mip_node_count") as *const i64;
+        if let Some(&nodes) = nodes.as_ref() {
+            let nodes = nodes.max(0) as u64;
+            if self.nodes.swap(nodes, Ordering::Relaxed) != nodes {
+                self.observer.on_node(nodes);
+            }
+        }
+        let bound = data_item(data_out, c"mip_dual_bound") as *const f64;
+        if let Some(&bound) = bound.as_ref().filter(|b| b.is_finite()) {
+            if self.bound.swap(bound.to_bits(), Ordering::Relaxed) != bound.to_bits() {
+                self.observer.on_bound_update(bound);
             }
-            status => Err(SolverError::ExecutionFailed(format!(
-                "HiGHS solver returned status: {:?}",
-                status
-            ))),
         }
     }
 
-    fn name(&self) -> &str {
-        "HiGHS"
+    /// Forward each line `message` completes, keeping any unfinished tail
+    fn log(&self, message: &str) {
+        let mut partial = self.partial_line.lock().unwrap_or_else(|e| e.into_inner());
+        partial.push_str(message);
+        let Some(end) = partial.rfind('\n') else {
+            return;
+        };
+        for line in partial[..end].lines() {
+            let line = line.trim_end();
+            if !line.is_empty() {
+                self.observer.on_log(line);
+            }
+        }
+        partial.drain(..=end);
     }
+}
 
-    fn supports_mip(&self) -> bool {
-        true
-    }
+unsafe fn data_item(data_out: *const c_void, name: &CStr) -> *const c_void {
+    Highs_getCallbackDataOutItem(data_out, name.as_ptr())
+}
+
+unsafe extern "C" fn progress_callback(
+    callback_type: c_int,
+    message: *const c_char,
+    data_out: *const c_void,
+    data_in: *mut c_void,
+    user_data: *mut c_void,
+) {
+    // SAFETY: `user_data` is the context registered in `attach`
+    let context = &*(user_data as *const ProgressContext);
+    // Unwinding into C++ is undefined behavior; a panicking observer loses the event
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        context.dispatch(callback_type, message, data_out, data_in)
+    }));
 }
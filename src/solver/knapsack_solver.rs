@@ -0,0 +1,330 @@
+// Knapsack Solver
+// Solves single 0/1 knapsack problems exactly by dynamic programming, which takes
+// milliseconds where branch and bound can take minutes
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution, SolverStatistics, Variable},
+    solver_service::{
+        report_solution, CancellationToken, NullObserver, Result, SolveObserver, SolverError,
+        SolverService,
+    },
+    value_objects::{ConstraintType, OptimizationType, VariableType},
+};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
+
+/// Largest DP table (items times capacity) worth filling; bigger knapsacks go to
+/// the fallback solver, whose branch and bound doesn't grow with the capacity
+const MAX_DP_CELLS: usize = 1 << 28;
+
+/// Solver for problems with one `<=` row over binary variables with integer weights
+///
+/// Anything else, including knapsacks too large for the DP table, is passed to
+/// the fallback solver, so it is safe to use for every problem.
+pub struct KnapsackSolver {
+    fallback: Arc<dyn SolverService>,
+}
+
+impl KnapsackSolver {
+    pub fn new(fallback: Arc<dyn SolverService>) -> Self {
+        Self { fallback }
+    }
+
+    /// Whether `problem` is a knapsack this solver handles itself
+    pub fn recognizes(problem: &OptimizationProblem) -> bool {
+        Knapsack::detect(problem).is_some()
+    }
+}
+
+impl SolverService for KnapsackSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        self.validate(problem)?;
+        let Some(knapsack) = Knapsack::detect(problem) else {
+            debug!(
+                fallback = self.fallback.name(),
+                "Not a knapsack, delegating"
+            );
+            let mut solution = self.fallback.solve_cancellable(problem, observer, cancel)?;
+            solution
+                .statistics
+                .solved_by
+                .get_or_insert_with(|| self.fallback.name().to_string());
+            return Ok(solution);
+        };
+
+        let start_time = Instant::now();
+        let values = knapsack.solve(problem.num_variables(), cancel)?;
+        let objective = problem
+            .objective
+            .coefficients
+            .iter()
+            .zip(&values)
+            .map(|(c, x)| c * x)
+            .sum();
+
+        let mut solution = Solution::optimal(objective, values);
+        solution.message = format!("Optimal knapsack found for '{}'", problem.name);
+        solution.statistics = SolverStatistics {
+            solve_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+            num_variables: problem.num_variables() as u32,
+            num_constraints: problem.constraints.len() as u32,
+            num_integer_vars: count(problem, VariableType::Integer),
+            num_binary_vars: count(problem, VariableType::Binary),
+            ..SolverStatistics::default()
+        };
+        report_solution(observer, &solution);
+        Ok(solution)
+    }
+
+    fn name(&self) -> &str {
+        "Knapsack"
+    }
+
+    fn supports_mip(&self) -> bool {
+        true
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.fallback.supports_lp()
+    }
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec!["0/1 Knapsack (dynamic programming)".to_string()]
+    }
+}
+
+/// A single 0/1 knapsack, restated as maximizing value
+struct Knapsack {
+    /// `(column, weight, value)` of items that may be packed
+    items: Vec<(usize, usize, f64)>,
+    /// Weightless items worth packing, always taken
+    free: Vec<usize>,
+    capacity: usize,
+}
+
+impl Knapsack {
+    /// Extract the knapsack, if the problem is one and its DP table is small enough
+    fn detect(problem: &OptimizationProblem) -> Option<Self> {
+        let [row] = problem.constraints.as_slice() else {
+            return None;
+        };
+        let n = problem.num_variables();
+        if row.constraint_type != ConstraintType::LessThanOrEqual
+            || !(row.bound >= 0.0 && row.bound.is_finite())
+            || row.num_variables() != n
+            || problem.variables.len() != n
+            || !problem.variables.iter().all(is_binary)
+        {
+            return None;
+        }
+
+        let sign = match problem.objective.optimization_type {
+            OptimizationType::Maximize => 1.0,
+            OptimizationType::Minimize => -1.0,
+        };
+        let mut items = Vec::new();
+        let mut free = Vec::new();
+        for (j, &cost) in problem.objective.coefficients.iter().enumerate() {
+            let weight = row.coefficients.get(j);
+            if !(weight >= 0.0 && weight.fract() == 0.0 && cost.is_finite()) {
+                return None;
+            }
+            let value = sign * cost;
+            if value <= 0.0 || weight > row.bound {
+                continue;
+            }
+            if weight == 0.0 {
+                free.push(j);
+            } else {
+                items.push((j, weight as usize, value));
+            }
+        }
+
+        // Capacity beyond what every item together weighs is never used
+        let total_weight = items.iter().fold(0usize, |total, &(_, weight, _)| {
+            total.saturating_add(weight)
+        });
+        let capacity = total_weight.min(row.bound.floor() as usize);
+        if items.len().saturating_mul(capacity.saturating_add(1)) > MAX_DP_CELLS {
+            return None;
+        }
+
+        Some(Self {
+            items,
+            free,
+            capacity,
+        })
+    }
+
+    /// Optimal 0/1 values for all `n` columns
+    fn solve(&self, n: usize, cancel: &CancellationToken) -> Result<Vec<f64>> {
+        let width = self.capacity + 1;
+        // best[c]: most value packable within capacity c using the items so far
+        let mut best = vec![0.0; width];
+        let mut taken = BitTable::new(self.items.len(), width);
+
+        for (k, &(_, weight, value)) in self.items.iter().enumerate() {
+            if k % 64 == 0 && cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            for c in (weight..width).rev() {
+                let packed = best[c - weight] + value;
+                if packed > best[c] {
+                    best[c] = packed;
+                    taken.set(k, c);
+                }
+            }
+        }
+
+        let mut values = vec![0.0; n];
+        for &j in &self.free {
+            values[j] = 1.0;
+        }
+        let mut c = self.capacity;
+        for (k, &(j, weight, _)) in self.items.iter().enumerate().rev() {
+            if taken.get(k, c) {
+                values[j] = 1.0;
+                c -= weight;
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// One bit per DP cell, recording whether the item was packed at that capacity
+struct BitTable {
+    width: usize,
+    bits: Vec<u64>,
+}
+
+impl BitTable {
+    fn new(rows: usize, width: usize) -> Self {
+        Self {
+            width,
+            bits: vec![0; (rows * width).div_ceil(64)],
+        }
+    }
+
+    fn set(&mut self, row: usize, col: usize) {
+        let bit = row * self.width + col;
+        self.bits[bit / 64] |= 1 << (bit % 64);
+    }
+
+    fn get(&self, row: usize, col: usize) -> bool {
+        let bit = row * self.width + col;
+        self.bits[bit / 64] & (1 << (bit % 64)) != 0
+    }
+}
+
+/// Binary, or integer with bounds that only admit 0 and 1
+pub(super) fn is_binary(variable: &Variable) -> bool {
+    let upper = variable.upper_bound.unwrap_or(f64::INFINITY);
+    variable.lower_bound == 0.0
+        && match variable.variable_type {
+            VariableType::Binary => upper >= 1.0,
+            VariableType::Integer => (1.0..2.0).contains(&upper),
+            VariableType::Continuous => false,
+        }
+}
+
+pub(super) fn count(problem: &OptimizationProblem, variable_type: VariableType) -> u32 {
+    problem
+        .variables
+        .iter()
+        .filter(|v| v.variable_type == variable_type)
+        .count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{Constraint, ObjectiveFunction};
+    use crate::domain::value_objects::SolutionStatus;
+    use crate::solver::testing::StubFallback;
+
+    /// max `values · x`  s.t.  `weights · x <= capacity`,  x binary
+    fn knapsack(values: &[f64], weights: &[f64], capacity: f64) -> OptimizationProblem {
+        let variables = (0..values.len())
+            .map(|j| Variable::binary(format!("x{}", j)))
+            .collect();
+        OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Maximize,
+            values.to_vec(),
+        ))
+        .with_variables(variables)
+        .add_constraint(Constraint::new(
+            ConstraintType::LessThanOrEqual,
+            weights.to_vec(),
+            capacity,
+        ))
+    }
+
+    fn solve(problem: &OptimizationProblem) -> Solution {
+        KnapsackSolver::new(Arc::new(StubFallback))
+            .solve(problem)
+            .unwrap()
+    }
+
+    #[test]
+    fn knapsack_finds_the_known_optimum() {
+        // Items 1 and 3 weigh 7 and are worth 90; every other pair that fits is
+        // worth less, and no three items fit
+        let problem = knapsack(&[10.0, 40.0, 30.0, 50.0], &[5.0, 4.0, 6.0, 3.0], 10.0);
+        assert!(KnapsackSolver::recognizes(&problem));
+
+        let solution = solve(&problem);
+        assert_eq!(solution.status, SolutionStatus::Optimal);
+        assert_eq!(solution.optimal_value, Some(90.0));
+        assert_eq!(solution.variable_values, [0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn zero_capacity_packs_only_weightless_items() {
+        let problem = knapsack(&[5.0, 3.0, -1.0], &[2.0, 0.0, 0.0], 0.0);
+        assert!(KnapsackSolver::recognizes(&problem));
+
+        let solution = solve(&problem);
+        assert_eq!(solution.optimal_value, Some(3.0));
+        assert_eq!(solution.variable_values, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn items_heavier_than_the_capacity_are_never_packed() {
+        let problem = knapsack(&[100.0, 1.0, 50.0], &[6.0, 2.0, 9.0], 5.0);
+
+        let solution = solve(&problem);
+        assert_eq!(solution.optimal_value, Some(1.0));
+        assert_eq!(solution.variable_values, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn non_integer_weights_go_to_the_fallback() {
+        let problem = knapsack(&[3.0, 4.0], &[1.5, 2.0], 3.0);
+        assert!(!KnapsackSolver::recognizes(&problem));
+
+        let solution = solve(&problem);
+        assert_eq!(solution.message, StubFallback::MESSAGE);
+    }
+}
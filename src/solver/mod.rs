@@ -1,9 +1,47 @@
 // Solver adapters module
 
+pub mod assignment_solver;
+pub mod auto_solver;
+#[cfg(feature = "cbc")]
 pub mod coin_cbc_solver;
 pub mod factory;
+pub mod fallback_solver;
+#[cfg(feature = "highs")]
+mod highs_options;
+#[cfg(feature = "highs")]
 pub mod highs_solver;
+pub mod knapsack_solver;
+pub mod network_solver;
+pub mod pipeline;
+pub mod polishing_solver;
+pub mod presolving_solver;
+pub mod racing_solver;
+pub mod registry;
+pub mod replay;
+pub mod resource_usage;
+pub mod scaling_solver;
+pub mod subprocess_solver;
+#[cfg(test)]
+mod testing;
 
+pub use assignment_solver::AssignmentSolver;
+pub use auto_solver::AutoSolver;
+#[cfg(feature = "cbc")]
 pub use coin_cbc_solver::CoinCbcSolver;
-pub use factory::SolverFactory;
-pub use highs_solver::HighsSolver;
+pub use factory::{BackendSelection, SolverFactory};
+pub use fallback_solver::FallbackSolver;
+#[cfg(feature = "highs")]
+pub use highs_solver::{HighsSession, HighsSolver};
+pub use knapsack_solver::KnapsackSolver;
+pub use network_solver::NetworkFlowSolver;
+pub use pipeline::{prepare_problem, wrap_solver};
+pub use polishing_solver::PolishingSolver;
+pub use presolving_solver::PresolvingSolver;
+pub use racing_solver::RacingSolver;
+pub use registry::{SolverInfo, SolverRegistry};
+pub use replay::replay;
+pub use resource_usage::ResourceMeter;
+pub use scaling_solver::ScalingSolver;
+pub use subprocess_solver::{
+    run_solve_worker, IsolationConfig, SubprocessSolver, SOLVE_WORKER_ARG,
+};
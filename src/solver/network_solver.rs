@@ -0,0 +1,807 @@
+// Network Flow Solver
+// Solves transportation and other pure min-cost-flow models with the network
+// simplex method, which pivots on a spanning tree instead of a factorized basis
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution, SolverStatistics, Variable},
+    solver_service::{
+        report_solution, CancellationToken, NullObserver, Result, SolveObserver, SolverError,
+        SolverService,
+    },
+    value_objects::{ConstraintType, OptimizationType, SolutionStatus, VariableType},
+};
+use crate::solver::knapsack_solver::count;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
+
+/// Arc states: at its lower bound, at its upper bound, or in the spanning tree
+const LOWER: i8 = 1;
+const UPPER: i8 = -1;
+const TREE: i8 = 0;
+
+/// Pivots between cancellation checks
+const CANCEL_CHECK_INTERVAL: u64 = 256;
+
+/// Solver for problems whose constraint matrix is a network: every column has at
+/// most two nonzeros, both +1 or -1, and the rows can be signed so that each column
+/// leaves one row and enters the other
+///
+/// Transportation, transshipment and min-cost-flow models have this shape. With
+/// integral supplies and bounds the optimal flows are integral, so integer
+/// variables need no branching. Anything else is passed to the fallback solver,
+/// so it is safe to use for every problem.
+pub struct NetworkFlowSolver {
+    fallback: Arc<dyn SolverService>,
+}
+
+impl NetworkFlowSolver {
+    pub fn new(fallback: Arc<dyn SolverService>) -> Self {
+        Self { fallback }
+    }
+
+    /// Whether `problem` is a flow model this solver handles itself
+    pub fn recognizes(problem: &OptimizationProblem) -> bool {
+        Network::detect(problem).is_some()
+    }
+}
+
+impl SolverService for NetworkFlowSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        self.validate(problem)?;
+        let Some(network) = Network::detect(problem) else {
+            debug!(
+                fallback = self.fallback.name(),
+                "Not a network flow problem, delegating"
+            );
+            let mut solution = self.fallback.solve_cancellable(problem, observer, cancel)?;
+            solution
+                .statistics
+                .solved_by
+                .get_or_insert_with(|| self.fallback.name().to_string());
+            return Ok(solution);
+        };
+
+        let start_time = Instant::now();
+        let (result, pivots) = network.solve(cancel)?;
+        let mut solution = match result {
+            FlowResult::Optimal { values, duals } => {
+                let objective = problem
+                    .objective
+                    .coefficients
+                    .iter()
+                    .zip(&values)
+                    .map(|(c, x)| c * x)
+                    .sum();
+                let mut solution = Solution::optimal(objective, values);
+                // Row duals are only meaningful for continuous problems
+                if !problem.is_mixed_integer() {
+                    solution.dual_values = duals;
+                }
+                solution.message = format!("Optimal flow found for '{}'", problem.name);
+                solution
+            }
+            FlowResult::Infeasible => Solution::new(
+                SolutionStatus::Infeasible,
+                "Problem is infeasible: no flow meets every supply and demand",
+            ),
+            FlowResult::Unbounded => Solution::new(
+                SolutionStatus::Unbounded,
+                "Problem is unbounded: a cycle of uncapacitated arcs improves the objective forever",
+            ),
+        };
+        solution.statistics = SolverStatistics {
+            simplex_iterations: pivots,
+            solve_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+            num_variables: problem.num_variables() as u32,
+            num_constraints: problem.constraints.len() as u32,
+            num_integer_vars: count(problem, VariableType::Integer),
+            num_binary_vars: count(problem, VariableType::Binary),
+            ..SolverStatistics::default()
+        };
+        report_solution(observer, &solution);
+        Ok(solution)
+    }
+
+    fn name(&self) -> &str {
+        "Network Simplex"
+    }
+
+    fn supports_mip(&self) -> bool {
+        true
+    }
+
+    fn supports_lp(&self) -> bool {
+        true
+    }
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec!["Min-cost flow (network simplex)".to_string()]
+    }
+}
+
+enum FlowResult {
+    Optimal { values: Vec<f64>, duals: Vec<f64> },
+    Infeasible,
+    Unbounded,
+}
+
+/// A min-cost-flow problem with one node per row plus a ground node
+///
+/// Columns in two rows are arcs between them; columns in one row, and the slack
+/// of inequality rows, are arcs to or from ground.
+struct Network {
+    /// `(tail, head)` of each arc; arc `j` is column `j` for every column
+    ends: Vec<(usize, usize)>,
+    cost: Vec<f64>,
+    /// Capacity above the column's lower bound, possibly infinite
+    cap: Vec<f64>,
+    /// Net outflow each node must have
+    supply: Vec<f64>,
+    /// Lower bound of each column, which its arc's flow is measured from
+    lower: Vec<f64>,
+    /// +1 or -1 per row, making the row read outflow minus inflow
+    row_sign: Vec<f64>,
+    /// +1 or -1, making the objective a minimization
+    sense: f64,
+    /// Whether values must be rounded to integers
+    integral: bool,
+}
+
+impl Network {
+    /// Extract the network, if the constraint matrix is one
+    fn detect(problem: &OptimizationProblem) -> Option<Self> {
+        let n = problem.num_variables();
+        let rows = &problem.constraints;
+        let m = rows.len();
+        if m == 0
+            || problem.variables.len() != n
+            || rows
+                .iter()
+                .any(|row| row.num_variables() != n || !row.bound.is_finite())
+        {
+            return None;
+        }
+
+        // Rows each column appears in, with its coefficient
+        let mut entries: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        for (r, row) in rows.iter().enumerate() {
+            for (j, a) in row.coefficients.iter() {
+                if a.abs() != 1.0 || entries[j].len() == 2 {
+                    return None;
+                }
+                entries[j].push((r, a));
+            }
+        }
+
+        // Sign rows so every column in two rows has opposite signs in them; a
+        // transportation problem negates its demand rows
+        let mut neighbours = vec![Vec::new(); m];
+        for column in &entries {
+            if let &[(r, a), (s, b)] = column.as_slice() {
+                neighbours[r].push((s, -a * b));
+                neighbours[s].push((r, -a * b));
+            }
+        }
+        let mut row_sign = vec![0.0; m];
+        for start in 0..m {
+            if row_sign[start] != 0.0 {
+                continue;
+            }
+            row_sign[start] = 1.0;
+            let mut stack = vec![start];
+            while let Some(r) = stack.pop() {
+                for &(s, relation) in &neighbours[r] {
+                    let sign = relation * row_sign[r];
+                    if row_sign[s] == 0.0 {
+                        row_sign[s] = sign;
+                        stack.push(s);
+                    } else if row_sign[s] != sign {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        let sense = match problem.objective.optimization_type {
+            OptimizationType::Minimize => 1.0,
+            OptimizationType::Maximize => -1.0,
+        };
+        let ground = m;
+        let mut network = Self {
+            ends: Vec::with_capacity(n + m),
+            cost: Vec::with_capacity(n + m),
+            cap: Vec::with_capacity(n + m),
+            supply: vec![0.0; m + 1],
+            lower: Vec::with_capacity(n),
+            row_sign,
+            sense,
+            integral: problem.variables.iter().any(Variable::is_integer),
+        };
+
+        for (j, (variable, column)) in problem.variables.iter().zip(&entries).enumerate() {
+            let (lower, upper) = bounds(variable)?;
+            let cost = sense * problem.objective.coefficients[j];
+            if !cost.is_finite() {
+                return None;
+            }
+            // A column in no row is a loop at ground, pushed to whichever bound is cheaper
+            let (mut tail, mut head) = (ground, ground);
+            for &(r, a) in column {
+                if a * network.row_sign[r] > 0.0 {
+                    tail = r;
+                } else {
+                    head = r;
+                }
+            }
+            network.add_arc((tail, head), cost, upper - lower);
+            network.supply[tail] -= lower;
+            network.supply[head] += lower;
+            network.lower.push(lower);
+        }
+
+        for (r, row) in rows.iter().enumerate() {
+            let sign = network.row_sign[r];
+            network.supply[r] += sign * row.bound;
+            // Slack carries the difference an inequality allows to or from ground
+            match (row.constraint_type, sign > 0.0) {
+                (ConstraintType::Equal, _) => {}
+                (ConstraintType::LessThanOrEqual, true)
+                | (ConstraintType::GreaterThanOrEqual, false) => {
+                    network.add_arc((r, ground), 0.0, f64::INFINITY)
+                }
+                _ => network.add_arc((ground, r), 0.0, f64::INFINITY),
+            }
+        }
+        network.supply[ground] = -network.supply[..m].iter().sum::<f64>();
+
+        // Integral data keeps every pivot, and so the optimal flow, integral
+        if network.integral
+            && !network
+                .supply
+                .iter()
+                .chain(&network.lower)
+                .chain(network.cap.iter().filter(|c| c.is_finite()))
+                .all(|x| x.fract() == 0.0)
+        {
+            return None;
+        }
+        Some(network)
+    }
+
+    fn add_arc(&mut self, ends: (usize, usize), cost: f64, cap: f64) {
+        self.ends.push(ends);
+        self.cost.push(cost);
+        self.cap.push(cap);
+    }
+
+    /// Run the network simplex, returning the result and the number of pivots
+    fn solve(&self, cancel: &CancellationToken) -> Result<(FlowResult, u64)> {
+        let mut tree = SpanningTree::new(self);
+        let mut pivots = 0;
+        loop {
+            if pivots % CANCEL_CHECK_INTERVAL == 0 && cancel.is_cancelled() {
+                return Err(SolverError::Cancelled);
+            }
+            let Some(entering) = tree.entering_arc() else {
+                break;
+            };
+            if !tree.pivot(entering) {
+                return Ok((FlowResult::Unbounded, pivots));
+            }
+            pivots += 1;
+        }
+
+        let scale = self.supply.iter().fold(1.0, |m: f64, s| m.max(s.abs()));
+        if tree.flow[self.ends.len()..]
+            .iter()
+            .any(|&f| f > 1e-9 * scale)
+        {
+            return Ok((FlowResult::Infeasible, pivots));
+        }
+
+        let values = self
+            .lower
+            .iter()
+            .zip(&tree.flow)
+            .map(|(lower, flow)| {
+                let value = lower + flow;
+                if self.integral {
+                    value.round()
+                } else {
+                    value
+                }
+            })
+            .collect();
+        Ok((
+            FlowResult::Optimal {
+                values,
+                duals: self.duals(&tree),
+            },
+            pivots,
+        ))
+    }
+
+    /// Shadow price of each row, from the node potentials
+    fn duals(&self, tree: &SpanningTree) -> Vec<f64> {
+        let m = self.row_sign.len();
+        let ground = m;
+        let mut pi = tree.pi.clone();
+
+        // Rows not linked to ground only have potentials relative to each other,
+        // which the artificial start leaves offset by its big costs; pin the first
+        // row of each such group to a zero dual
+        let mut group: Vec<usize> = (0..=m).collect();
+        for &(tail, head) in &self.ends {
+            let (a, b) = (find(&mut group, tail), find(&mut group, head));
+            group[a] = b;
+        }
+        let grounded = find(&mut group, ground);
+        let mut shift: Vec<Option<f64>> = vec![None; m + 1];
+        for r in 0..m {
+            let g = find(&mut group, r);
+            if g != grounded {
+                let offset = *shift[g].get_or_insert(pi[ground] - pi[r]);
+                pi[r] += offset;
+            }
+        }
+
+        (0..m)
+            .map(|r| -self.sense * self.row_sign[r] * (pi[r] - pi[ground]))
+            .collect()
+    }
+}
+
+/// Bounds of a variable, tightened to integers for integer variables
+fn bounds(variable: &Variable) -> Option<(f64, f64)> {
+    let mut lower = variable.lower_bound;
+    let mut upper = variable.upper_bound.unwrap_or(f64::INFINITY);
+    if variable.variable_type == VariableType::Binary {
+        lower = lower.max(0.0);
+        upper = upper.min(1.0);
+    }
+    if variable.is_integer() {
+        lower = lower.ceil();
+        upper = upper.floor();
+    }
+    (lower.is_finite() && upper >= lower).then_some((lower, upper))
+}
+
+/// Union-find root of `x`, halving the path on the way
+fn find(group: &mut [usize], mut x: usize) -> usize {
+    while group[x] != x {
+        group[x] = group[group[x]];
+        x = group[x];
+    }
+    x
+}
+
+/// Basis of the network simplex: a spanning tree over the nodes and an extra
+/// root, with every other arc at one of its bounds
+///
+/// The tree starts as a star of artificial arcs to the root, costly enough that
+/// any flow left on them at the end means the supplies can't be met. Leaving arcs
+/// are picked so the tree stays strongly feasible, which rules out cycling.
+struct SpanningTree {
+    ends: Vec<(usize, usize)>,
+    cost: Vec<f64>,
+    cap: Vec<f64>,
+    flow: Vec<f64>,
+    state: Vec<i8>,
+    /// Tree arcs, one per non-root node
+    arcs: Vec<usize>,
+    /// Position of each tree arc in `arcs`
+    slot: Vec<usize>,
+    root: usize,
+    parent: Vec<usize>,
+    /// Arc to the parent, and whether it points up towards the root
+    pred: Vec<usize>,
+    up: Vec<bool>,
+    depth: Vec<usize>,
+    /// Potentials making every tree arc's reduced cost zero
+    pi: Vec<f64>,
+    adjacent: Vec<Vec<usize>>,
+    /// Arc where the next pricing scan starts, and how many arcs make a block
+    next_arc: usize,
+    block: usize,
+    tolerance: f64,
+}
+
+impl SpanningTree {
+    fn new(network: &Network) -> Self {
+        let nodes = network.supply.len();
+        let root = nodes;
+        let mut ends = network.ends.clone();
+        let mut cost = network.cost.clone();
+        let mut cap = network.cap.clone();
+        let mut flow = vec![0.0; ends.len()];
+        let mut state = vec![LOWER; ends.len()];
+        let mut arcs = Vec::with_capacity(nodes);
+        let mut slot = vec![usize::MAX; ends.len()];
+
+        let max_cost = cost.iter().fold(0.0, |m: f64, c| m.max(c.abs()));
+        let artificial_cost = (max_cost + 1.0) * (nodes + 1) as f64;
+        for (v, &supply) in network.supply.iter().enumerate() {
+            slot.push(arcs.len());
+            arcs.push(ends.len());
+            ends.push(if supply >= 0.0 { (v, root) } else { (root, v) });
+            flow.push(supply.abs());
+            cost.push(artificial_cost);
+            cap.push(f64::INFINITY);
+            state.push(TREE);
+        }
+
+        let arc_count = ends.len();
+        let mut tree = Self {
+            ends,
+            cost,
+            cap,
+            flow,
+            state,
+            arcs,
+            slot,
+            root,
+            parent: vec![root; nodes + 1],
+            pred: vec![usize::MAX; nodes + 1],
+            up: vec![false; nodes + 1],
+            depth: vec![0; nodes + 1],
+            pi: vec![0.0; nodes + 1],
+            adjacent: vec![Vec::new(); nodes + 1],
+            next_arc: 0,
+            block: ((arc_count as f64).sqrt() as usize).max(10),
+            tolerance: 1e-9 * (max_cost + 1.0),
+        };
+        tree.rebuild();
+        tree
+    }
+
+    fn reduced_cost(&self, arc: usize) -> f64 {
+        let (tail, head) = self.ends[arc];
+        self.cost[arc] + self.pi[tail] - self.pi[head]
+    }
+
+    /// Best improving arc within the first block that has one, scanning round
+    /// from where the last search stopped
+    fn entering_arc(&mut self) -> Option<usize> {
+        let arc_count = self.ends.len();
+        let mut best = None;
+        let mut best_violation = -self.tolerance;
+        let mut arc = self.next_arc;
+        for scanned in 1..=arc_count {
+            let violation = f64::from(self.state[arc]) * self.reduced_cost(arc);
+            if violation < best_violation {
+                best_violation = violation;
+                best = Some(arc);
+            }
+            arc = (arc + 1) % arc_count;
+            if scanned % self.block == 0 && best.is_some() {
+                break;
+            }
+        }
+        self.next_arc = arc;
+        best
+    }
+
+    /// Push flow round the cycle `entering` closes, returning false if nothing
+    /// limits it
+    fn pivot(&mut self, entering: usize) -> bool {
+        let (tail, head) = self.ends[entering];
+        let (first, second) = if self.state[entering] == LOWER {
+            (tail, head)
+        } else {
+            (head, tail)
+        };
+        let join = self.join(first, second);
+
+        // Flow runs down from the join to `first`, over the entering arc, then up
+        // from `second` to the join. Ties go to the last blocking arc in that
+        // order, which keeps the tree strongly feasible.
+        let mut delta = self.cap[entering];
+        let mut leaving = None;
+        let mut u = first;
+        while u != join {
+            let arc = self.pred[u];
+            let residual = if self.up[u] {
+                self.flow[arc]
+            } else {
+                self.cap[arc] - self.flow[arc]
+            };
+            if residual < delta {
+                delta = residual;
+                leaving = Some((u, !self.up[u]));
+            }
+            u = self.parent[u];
+        }
+        let mut u = second;
+        while u != join {
+            let arc = self.pred[u];
+            let residual = if self.up[u] {
+                self.cap[arc] - self.flow[arc]
+            } else {
+                self.flow[arc]
+            };
+            if residual <= delta {
+                delta = residual;
+                leaving = Some((u, self.up[u]));
+            }
+            u = self.parent[u];
+        }
+        if delta == f64::INFINITY {
+            return false;
+        }
+
+        let change = f64::from(self.state[entering]) * delta.max(0.0);
+        self.flow[entering] += change;
+        let mut u = tail;
+        while u != join {
+            let direction = if self.up[u] { 1.0 } else { -1.0 };
+            self.flow[self.pred[u]] -= direction * change;
+            u = self.parent[u];
+        }
+        let mut u = head;
+        while u != join {
+            let direction = if self.up[u] { 1.0 } else { -1.0 };
+            self.flow[self.pred[u]] += direction * change;
+            u = self.parent[u];
+        }
+
+        match leaving {
+            // The entering arc is the bottleneck and just moves to its other bound
+            None => {
+                self.state[entering] = -self.state[entering];
+                self.flow[entering] = if self.state[entering] == UPPER {
+                    self.cap[entering]
+                } else {
+                    0.0
+                };
+            }
+            Some((u, to_upper)) => {
+                let out = self.pred[u];
+                (self.state[out], self.flow[out]) = if to_upper {
+                    (UPPER, self.cap[out])
+                } else {
+                    (LOWER, 0.0)
+                };
+                self.state[entering] = TREE;
+                let position = self.slot[out];
+                self.arcs[position] = entering;
+                self.slot[entering] = position;
+                self.slot[out] = usize::MAX;
+                self.rebuild();
+            }
+        }
+        true
+    }
+
+    /// Deepest common ancestor of two nodes
+    fn join(&self, mut a: usize, mut b: usize) -> usize {
+        while a != b {
+            if self.depth[a] >= self.depth[b] {
+                a = self.parent[a];
+            } else {
+                b = self.parent[b];
+            }
+        }
+        a
+    }
+
+    /// Recompute parents, depths and potentials from the tree arcs
+    fn rebuild(&mut self) {
+        for list in &mut self.adjacent {
+            list.clear();
+        }
+        for &arc in &self.arcs {
+            let (tail, head) = self.ends[arc];
+            self.adjacent[tail].push(arc);
+            self.adjacent[head].push(arc);
+        }
+
+        let mut stack = vec![self.root];
+        self.parent[self.root] = self.root;
+        self.depth[self.root] = 0;
+        self.pi[self.root] = 0.0;
+        while let Some(v) = stack.pop() {
+            for i in 0..self.adjacent[v].len() {
+                let arc = self.adjacent[v][i];
+                if self.pred[v] == arc && v != self.root {
+                    continue;
+                }
+                let (tail, head) = self.ends[arc];
+                let (w, up) = if tail == v {
+                    (head, false)
+                } else {
+                    (tail, true)
+                };
+                self.parent[w] = v;
+                self.pred[w] = arc;
+                self.up[w] = up;
+                self.depth[w] = self.depth[v] + 1;
+                self.pi[w] = if up {
+                    self.pi[v] - self.cost[arc]
+                } else {
+                    self.pi[v] + self.cost[arc]
+                };
+                stack.push(w);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{Constraint, ObjectiveFunction};
+    use crate::solver::testing::StubFallback;
+
+    /// Min-cost flow over `arcs` as `(tail, head, cost, capacity)`, one `==` row
+    /// per node reading outflow minus inflow equals its supply
+    fn flow_problem(
+        supply: &[f64],
+        arcs: &[(usize, usize, f64, Option<f64>)],
+    ) -> OptimizationProblem {
+        let costs = arcs.iter().map(|&(_, _, cost, _)| cost).collect();
+        let variables = arcs
+            .iter()
+            .enumerate()
+            .map(|(j, &(_, _, _, cap))| {
+                Variable::continuous(format!("x{}", j)).with_bounds(0.0, cap)
+            })
+            .collect();
+        supply.iter().enumerate().fold(
+            OptimizationProblem::new(ObjectiveFunction::new(OptimizationType::Minimize, costs))
+                .with_variables(variables),
+            |problem, (node, &supply)| {
+                let row: Vec<f64> = arcs
+                    .iter()
+                    .map(|&(tail, head, _, _)| {
+                        if tail == node {
+                            1.0
+                        } else if head == node {
+                            -1.0
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect();
+                problem.add_constraint(Constraint::new(ConstraintType::Equal, row, supply))
+            },
+        )
+    }
+
+    fn solve(problem: &OptimizationProblem) -> Solution {
+        assert!(NetworkFlowSolver::recognizes(problem));
+        NetworkFlowSolver::new(Arc::new(StubFallback))
+            .solve(problem)
+            .unwrap()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "{:?} vs {:?}",
+            actual,
+            expected
+        );
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} vs {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn min_cost_flow_finds_the_known_optimum() {
+        // Four units from s to t; s->a->b->t is cheapest but s->a carries only 3,
+        // so the last unit goes s->b->t
+        let (s, a, b, t) = (0, 1, 2, 3);
+        let problem = flow_problem(
+            &[4.0, 0.0, 0.0, -4.0],
+            &[
+                (s, a, 2.0, Some(3.0)),
+                (s, b, 4.0, None),
+                (a, b, 1.0, None),
+                (a, t, 6.0, None),
+                (b, t, 2.0, None),
+            ],
+        );
+
+        let solution = solve(&problem);
+        assert_eq!(solution.status, SolutionStatus::Optimal);
+        assert_eq!(solution.optimal_value, Some(21.0));
+        assert_close(&solution.variable_values, &[3.0, 1.0, 3.0, 0.0, 4.0]);
+        // Duals drop by the cost of every arc in the tree; the saturated s->a arc
+        // is left priced below its cost of 2
+        assert_close(&solution.dual_values, &[0.0, -3.0, -4.0, -6.0]);
+    }
+
+    #[test]
+    fn unmet_supplies_and_demands_are_infeasible() {
+        // Demand exceeds supply
+        let unbalanced = flow_problem(&[4.0, -5.0], &[(0, 1, 1.0, None)]);
+        assert_eq!(solve(&unbalanced).status, SolutionStatus::Infeasible);
+
+        // Balanced, but the only arc is too small
+        let capped = flow_problem(&[4.0, -4.0], &[(0, 1, 1.0, Some(3.0))]);
+        assert_eq!(solve(&capped).status, SolutionStatus::Infeasible);
+    }
+
+    #[test]
+    fn degenerate_cycle_is_saturated_without_cycling() {
+        // No supplies at all, so every pivot from the artificial start is
+        // degenerate; the negative cycle a->b->c->a still has to carry 5 units
+        let problem = flow_problem(
+            &[0.0, 0.0, 0.0],
+            &[
+                (0, 1, 1.0, None),
+                (1, 2, 1.0, None),
+                (2, 0, -3.0, Some(5.0)),
+                (1, 0, 0.0, None),
+            ],
+        );
+
+        let solution = solve(&problem);
+        assert_eq!(solution.status, SolutionStatus::Optimal);
+        assert_eq!(solution.optimal_value, Some(-5.0));
+        assert_close(&solution.variable_values, &[5.0, 5.0, 5.0, 0.0]);
+    }
+
+    #[test]
+    fn degenerate_transportation_problem_is_solved() {
+        // Supplies 2 and 3 match demands 2 and 3 exactly, so a basis has a
+        // tree arc at zero flow
+        let problem = flow_problem(
+            &[2.0, 3.0, -2.0, -3.0],
+            &[
+                (0, 2, 1.0, None),
+                (0, 3, 3.0, None),
+                (1, 2, 4.0, None),
+                (1, 3, 1.0, None),
+            ],
+        );
+
+        let solution = solve(&problem);
+        assert_eq!(solution.status, SolutionStatus::Optimal);
+        assert_eq!(solution.optimal_value, Some(5.0));
+        assert_close(&solution.variable_values, &[2.0, 0.0, 0.0, 3.0]);
+    }
+
+    #[test]
+    fn non_network_problems_go_to_the_fallback() {
+        let problem = OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Minimize,
+            vec![1.0, 1.0],
+        ))
+        .with_variables(vec![Variable::continuous("x"), Variable::continuous("y")])
+        .add_constraint(Constraint::new(
+            ConstraintType::GreaterThanOrEqual,
+            vec![2.0, 1.0],
+            3.0,
+        ));
+
+        assert!(!NetworkFlowSolver::recognizes(&problem));
+        let solution = NetworkFlowSolver::new(Arc::new(StubFallback))
+            .solve(&problem)
+            .unwrap();
+        assert_eq!(solution.message, StubFallback::MESSAGE);
+    }
+}
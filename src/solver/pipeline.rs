@@ -0,0 +1,65 @@
+// Solve pipeline: The preparation and wrapping every solve goes through
+// The server, the C API and replays share it, so the same problem is solved the
+// same way whichever of them runs it
+
+use std::sync::Arc;
+
+use crate::domain::models::{OptimizationProblem, SolverConfig};
+use crate::domain::solver_service::{Result, SolverService};
+
+use super::{PolishingSolver, PresolvingSolver, ScalingSolver};
+
+/// Relax integrality when the config asks for it, and fix the variables in
+/// `fixed_values` at their values
+pub fn prepare_problem(problem: &mut OptimizationProblem) -> Result<()> {
+    if problem.solver_config.relax_integrality {
+        problem.relax_integrality();
+    }
+    problem.fix_variables()
+}
+
+/// Wrap `solver` in the presolve and scaling `config` asks for, and in polishing
+///
+/// Presolve goes outside scaling, so scaling sees only what is left. Polishing
+/// goes outermost, so it checks the result against the problem as it was sent.
+pub fn wrap_solver(
+    mut solver: Arc<dyn SolverService>,
+    config: &SolverConfig,
+) -> Arc<dyn SolverService> {
+    if config.auto_scale {
+        solver = Arc::new(ScalingSolver::new(solver));
+    }
+    if config.presolve {
+        solver = Arc::new(PresolvingSolver::new(solver));
+    }
+    Arc::new(PolishingSolver::new(solver))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{ObjectiveFunction, Variable};
+    use crate::domain::value_objects::{OptimizationType, VariableType};
+
+    #[test]
+    fn problems_are_relaxed_and_fixed_as_their_config_asks() {
+        let mut problem = OptimizationProblem::new(ObjectiveFunction::new(
+            OptimizationType::Maximize,
+            vec![1.0, 1.0],
+        ))
+        .with_variables(vec![
+            Variable::integer("x").with_bounds(0.0, Some(10.0)),
+            Variable::continuous("y").with_bounds(0.0, Some(5.0)),
+        ]);
+        problem.solver_config.relax_integrality = true;
+        problem
+            .solver_config
+            .fixed_values
+            .insert("y".to_string(), 2.0);
+
+        prepare_problem(&mut problem).unwrap();
+        assert_eq!(problem.variables[0].variable_type, VariableType::Continuous);
+        assert_eq!(problem.variables[1].lower_bound, 2.0);
+        assert_eq!(problem.variables[1].upper_bound, Some(2.0));
+    }
+}
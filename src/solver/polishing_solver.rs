@@ -0,0 +1,190 @@
+// Polishing Solver
+// Wraps another solver and cleans up what it returns, so integer variables come back
+// as exact integers and round-off like 1e-9 as 0 rather than within tolerance of them
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution},
+    polish::{excess_violation, fix_integers, snap_integers, zero_tiny_values},
+    solver_service::{
+        CancellationToken, NullObserver, Result, SolveObserver, SolverOption, SolverService,
+    },
+    value_objects::IntegerRounding,
+};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Solver that cleans up the `inner` solver's result: values below
+/// `SolverConfig::zero_threshold` become 0, and near-integer values become exact
+/// integers as `SolverConfig::integer_rounding` asks
+///
+/// Either can push a tight constraint out of tolerance. Tiny values that can't be
+/// zeroed safely are kept; broken constraints after snapping are flagged in the
+/// solution quality, or with `IntegerRounding::Repair` fixed by re-solving the
+/// continuous variables around the snapped integers.
+pub struct PolishingSolver {
+    inner: Arc<dyn SolverService>,
+}
+
+impl PolishingSolver {
+    pub fn new(inner: Arc<dyn SolverService>) -> Self {
+        Self { inner }
+    }
+
+    /// Zero the values below `threshold`, unless that breaks a constraint the
+    /// solver's values satisfied
+    fn zero(
+        &self,
+        problem: &OptimizationProblem,
+        solution: &mut Solution,
+        threshold: f64,
+        baseline: f64,
+    ) {
+        let original = solution.clone();
+        let zeroed = zero_tiny_values(problem, solution, threshold);
+        if zeroed == 0 {
+            return;
+        }
+        let violation = excess_violation(problem, &solution.variable_values);
+        if violation > baseline {
+            warn!(zeroed, violation, "Zeroing tiny values broke a constraint");
+            *solution = Solution {
+                message: format!(
+                    "{}; tiny values kept, since zeroing them violates constraints by up to {:e}",
+                    original.message, violation
+                ),
+                ..original
+            };
+            return;
+        }
+        debug!(zeroed, "Zeroed tiny values");
+        solution.quality.zeroed_values = zeroed;
+    }
+
+    /// Snap near-integer values, then flag or repair what that breaks
+    fn round(
+        &self,
+        problem: &OptimizationProblem,
+        mut solution: Solution,
+        baseline: f64,
+        cancel: &CancellationToken,
+    ) -> Solution {
+        let snapped = snap_integers(problem, &mut solution);
+        if snapped == 0 {
+            return solution;
+        }
+        solution.quality.snapped_integers = snapped;
+        let violation = excess_violation(problem, &solution.variable_values);
+        debug!(snapped, violation, "Snapped integer values");
+        if violation <= baseline {
+            return solution;
+        }
+
+        if problem.solver_config.integer_rounding == IntegerRounding::Repair {
+            if let Some(repaired) = self.repair(problem, &solution, baseline, cancel) {
+                debug!("Repaired rounded solution");
+                return repaired;
+            }
+        }
+        warn!(snapped, violation, "Snapping integers broke a constraint");
+        solution.quality.rounding_violation = violation;
+        solution.message = format!(
+            "{}; snapping integer values to exact integers ({} changed) violates constraints by up to {:e}",
+            solution.message, snapped, violation
+        );
+        solution
+    }
+
+    /// The solution re-solved with its integers fixed, if that is back in tolerance
+    fn repair(
+        &self,
+        problem: &OptimizationProblem,
+        solution: &Solution,
+        baseline: f64,
+        cancel: &CancellationToken,
+    ) -> Option<Solution> {
+        let fixed = fix_integers(problem, &solution.variable_values);
+        let mut repaired = self
+            .inner
+            .solve_cancellable(&fixed, &NullObserver, cancel)
+            .ok()
+            .filter(|repaired| repaired.is_feasible())?;
+        snap_integers(problem, &mut repaired);
+        if excess_violation(problem, &repaired.variable_values) > baseline {
+            return None;
+        }
+        Some(Solution {
+            optimal_value: repaired.optimal_value,
+            variable_values: repaired.variable_values,
+            message: format!(
+                "{}; continuous variables re-solved around the snapped integers",
+                solution.message
+            ),
+            ..solution.clone()
+        })
+    }
+}
+
+impl SolverService for PolishingSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        let mut solution = self.inner.solve_cancellable(problem, observer, cancel)?;
+        if solution.variable_values.is_empty() {
+            return Ok(solution);
+        }
+        let config = &problem.solver_config;
+        // Only violations beyond what the solver's own values have count as broken
+        let baseline = excess_violation(problem, &solution.variable_values);
+
+        if let Some(threshold) = config.zero_threshold {
+            self.zero(problem, &mut solution, threshold, baseline);
+        }
+        if config.integer_rounding != IntegerRounding::Off && problem.is_mixed_integer() {
+            solution = self.round(problem, solution, baseline, cancel);
+        }
+        Ok(solution)
+    }
+
+    fn validate(&self, problem: &OptimizationProblem) -> Result<Vec<String>> {
+        self.inner.validate(problem)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.inner.supports_mip()
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.inner.supports_lp()
+    }
+
+    fn version(&self) -> String {
+        self.inner.version()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        self.inner.capabilities()
+    }
+
+    fn options(&self) -> Vec<SolverOption> {
+        self.inner.options()
+    }
+}
@@ -0,0 +1,146 @@
+// Presolving Solver
+// Wraps another solver with the domain presolve, so the backend only sees what is
+// left after the cheap reductions
+
+use crate::domain::{
+    infeasibility::InfeasibilityCertificate,
+    models::{OptimizationProblem, Solution},
+    presolve::{presolve, PresolveOutcome, Presolved},
+    solver_service::{
+        CancellationToken, NullObserver, Result, SolveObserver, SolverOption, SolverService,
+    },
+    value_objects::SolutionStatus,
+};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Solver that presolves the problem, solves the rest with `inner` and postsolves
+///
+/// Problems presolve proves infeasible, or reduces to nothing, never reach the
+/// backend.
+pub struct PresolvingSolver {
+    inner: Arc<dyn SolverService>,
+}
+
+impl PresolvingSolver {
+    pub fn new(inner: Arc<dyn SolverService>) -> Self {
+        Self { inner }
+    }
+}
+
+impl SolverService for PresolvingSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        let presolved = match presolve(problem) {
+            PresolveOutcome::Reduced(presolved) => presolved,
+            PresolveOutcome::Infeasible(reason) => {
+                debug!(%reason, "Presolve proved infeasibility");
+                return Ok(Solution::new(
+                    SolutionStatus::Infeasible,
+                    format!("Infeasible (detected by presolve): {}", reason),
+                ));
+            }
+        };
+        debug!(
+            removed_variables = presolved.report.removed_variables,
+            removed_constraints = presolved.report.removed_constraints,
+            tightened_bounds = presolved.report.tightened_bounds,
+            "Presolve finished"
+        );
+
+        // Every variable fixed: the fixed values are the only, hence optimal, solution
+        let solution = if presolved.problem.num_variables() == 0 {
+            let mut solution = Solution::optimal(0.0, Vec::new());
+            solution.message = "Solved by presolve".to_string();
+            solution
+        } else {
+            let postsolving = PostsolvingObserver {
+                inner: observer,
+                presolved: &presolved,
+            };
+            self.inner
+                .solve_cancellable(&presolved.problem, &postsolving, cancel)?
+        };
+        let mut solution = presolved.postsolve(solution);
+        solution.infeasibility_certificate =
+            solution
+                .infeasibility_certificate
+                .take()
+                .and_then(|certificate| {
+                    InfeasibilityCertificate::new(problem, &certificate.multipliers)
+                });
+        Ok(solution)
+    }
+
+    fn validate(&self, problem: &OptimizationProblem) -> Result<Vec<String>> {
+        self.inner.validate(problem)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.inner.supports_mip()
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.inner.supports_lp()
+    }
+
+    fn version(&self) -> String {
+        self.inner.version()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        self.inner.capabilities()
+    }
+
+    fn options(&self) -> Vec<SolverOption> {
+        self.inner.options()
+    }
+}
+
+/// Reports the inner solver's progress in terms of the original problem
+struct PostsolvingObserver<'a> {
+    inner: &'a dyn SolveObserver,
+    presolved: &'a Presolved,
+}
+
+impl SolveObserver for PostsolvingObserver<'_> {
+    fn on_incumbent(&self, objective: f64, values: &[f64]) {
+        if let Some(values) = self.presolved.original_values(values) {
+            self.inner
+                .on_incumbent(objective + self.presolved.objective_offset, &values);
+        }
+    }
+
+    fn on_bound_update(&self, bound: f64) {
+        self.inner
+            .on_bound_update(bound + self.presolved.objective_offset);
+    }
+
+    fn on_node(&self, nodes: u64) {
+        self.inner.on_node(nodes);
+    }
+
+    fn on_log(&self, line: &str) {
+        self.inner.on_log(line);
+    }
+}
@@ -0,0 +1,165 @@
+// Racing Solver
+// Runs several solvers on the same problem at once and keeps the first proven
+// result, so whichever backend suits the problem best sets the latency
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution},
+    solver_service::{
+        report_solution, CancellationToken, NullObserver, Result, SolveObserver, SolverError,
+        SolverService,
+    },
+    value_objects::SolutionStatus,
+};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, Span};
+
+/// Solver that races its entrants and returns the first proven result
+///
+/// A result is proven when it is optimal, infeasible or unbounded. When no entrant
+/// proves anything, the best remaining result is returned once all have finished.
+///
+/// Losing entrants are cancelled once a winner is in. CBC cannot be interrupted,
+/// so a losing CBC keeps running in the background until it finishes. Under
+/// subprocess isolation the whole race runs in one worker process, which exits
+/// (killing the losers) once it replies.
+pub struct RacingSolver {
+    entrants: Vec<Arc<dyn SolverService>>,
+}
+
+impl RacingSolver {
+    pub fn new(entrants: Vec<Arc<dyn SolverService>>) -> Self {
+        Self { entrants }
+    }
+
+    pub fn entrants(&self) -> &[Arc<dyn SolverService>] {
+        &self.entrants
+    }
+}
+
+impl SolverService for RacingSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    /// Progress is only reported for the winning result
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        self.validate(problem)?;
+
+        let entrants: Vec<_> = self
+            .entrants
+            .iter()
+            .filter(|entrant| !problem.is_mixed_integer() || entrant.supports_mip())
+            .cloned()
+            .collect();
+        if entrants.is_empty() && !self.entrants.is_empty() {
+            return Err(SolverError::integers_unsupported());
+        }
+        if entrants.is_empty() {
+            return Err(SolverError::SolverNotAvailable(
+                "No solver available to race".to_string(),
+            ));
+        }
+
+        let problem = Arc::new(problem.clone());
+        // Stops the losers however the race ends
+        let race = cancel.child_token();
+        let _stop_entrants = race.clone().cancel_on_drop();
+        let (sender, receiver) = mpsc::channel();
+        for entrant in entrants {
+            let sender = sender.clone();
+            let problem = Arc::clone(&problem);
+            let race = race.clone();
+            let span = Span::current();
+            // A panicking entrant just drops its sender and drops out of the race
+            thread::spawn(move || {
+                let outcome =
+                    span.in_scope(|| entrant.solve_cancellable(&problem, &NullObserver, &race));
+                let _ = sender.send((entrant.name().to_string(), outcome));
+            });
+        }
+        drop(sender);
+
+        let mut best: Option<(String, Solution)> = None;
+        let mut first_error = None;
+        loop {
+            let (name, outcome) = match receiver.recv_timeout(Duration::from_millis(10)) {
+                Ok(entry) => entry,
+                Err(mpsc::RecvTimeoutError::Timeout) if cancel.is_cancelled() => {
+                    return Err(SolverError::Cancelled);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+            match outcome {
+                Ok(solution) if is_proven(solution.status) => {
+                    debug!(winner = %name, status = %solution.status, "Race won");
+                    report_solution(observer, &solution);
+                    return Ok(credit(name, solution));
+                }
+                Ok(solution) => {
+                    if best
+                        .as_ref()
+                        .is_none_or(|(_, kept)| !kept.is_feasible() && solution.is_feasible())
+                    {
+                        best = Some((name, solution));
+                    }
+                }
+                Err(e) => {
+                    debug!(entrant = %name, error = %e, "Race entrant failed");
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match (best, first_error) {
+            (Some((name, solution)), _) => {
+                report_solution(observer, &solution);
+                Ok(credit(name, solution))
+            }
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(SolverError::ExecutionFailed(
+                "Every racing solver crashed".to_string(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Race"
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.entrants.iter().any(|entrant| entrant.supports_mip())
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.entrants.iter().any(|entrant| entrant.supports_lp())
+    }
+}
+
+fn is_proven(status: SolutionStatus) -> bool {
+    matches!(
+        status,
+        SolutionStatus::Optimal | SolutionStatus::Infeasible | SolutionStatus::Unbounded
+    )
+}
+
+/// Record which entrant produced the solution
+fn credit(name: String, mut solution: Solution) -> Solution {
+    solution.statistics.solved_by = Some(name);
+    solution
+}
@@ -0,0 +1,294 @@
+// Solver registry: The solvers this server can actually run
+// Built at startup from the compiled-in backends, probing each for its version and
+// with a smoke solve; library users can add their own solvers under a name
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::domain::{
+    models::{Constraint, ObjectiveFunction, OptimizationProblem, Variable},
+    solver_service::{SolverOption, SolverService},
+    value_objects::{ConstraintType, OptimizationType, SolutionStatus, SolverBackend},
+};
+use crate::solver::{fallback_solver::attempt, SolverFactory};
+
+/// Optima of the smoke problem, max x + y s.t. x + 2y <= 4 and 3x + y <= 6, with y
+/// integer and without
+const SMOKE_MIP_OPTIMUM: f64 = 8.0 / 3.0;
+const SMOKE_LP_OPTIMUM: f64 = 2.8;
+
+/// Description of a registered solver, as reported to clients
+#[derive(Debug, Clone)]
+pub struct SolverInfo {
+    /// Registration key clients use to select this solver
+    pub key: String,
+    pub name: String,
+    pub version: String,
+    pub supports_lp: bool,
+    pub supports_mip: bool,
+    pub capabilities: Vec<String>,
+    /// Options it accepts in `SolverConfig.backend_options`
+    pub options: Vec<SolverOption>,
+}
+
+impl SolverInfo {
+    /// Ask a solver to describe itself
+    pub fn probe(key: impl Into<String>, solver: &dyn SolverService) -> Self {
+        Self {
+            key: key.into(),
+            name: solver.name().to_string(),
+            version: solver.version(),
+            supports_lp: solver.supports_lp(),
+            supports_mip: solver.supports_mip(),
+            capabilities: solver.capabilities(),
+            options: solver.options(),
+        }
+    }
+}
+
+struct Entry {
+    /// Built-in backend this entry serves, None for custom solvers
+    backend: Option<SolverBackend>,
+    info: SolverInfo,
+    solver: Arc<dyn SolverService>,
+    /// Cleared by an administrator to take the solver out of rotation
+    enabled: AtomicBool,
+}
+
+impl Entry {
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+/// Solvers available to the service, keyed by backend and by name
+///
+/// Disabled solvers stay registered but are left out of every lookup except by
+/// name, so AUTO, RACE, fallbacks and listings skip them until they are enabled
+/// again.
+#[derive(Default)]
+pub struct SolverRegistry {
+    entries: Vec<Entry>,
+}
+
+impl SolverRegistry {
+    /// Registry with no solvers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with every backend compiled into this build
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for backend in SolverFactory::compiled_backends() {
+            registry.register(backend, SolverFactory::create_from_backend(backend, false));
+        }
+        registry
+    }
+
+    /// Registry with every backend compiled into this build, each keeping up to
+    /// `instances` backend instances warm between solves where it can
+    pub fn builtin_pooled(instances: usize) -> Self {
+        let mut registry = Self::new();
+        for backend in SolverFactory::compiled_backends() {
+            registry.register(backend, SolverFactory::create_pooled(backend, instances));
+        }
+        registry
+    }
+
+    /// Drop the built-in backends that fail a smoke solve, logging why
+    ///
+    /// Each backend solves a two-variable problem, a MIP if it supports them. A
+    /// backend that errors, panics or gets the wrong answer would fail real
+    /// requests too, so clients are better off not seeing it.
+    pub fn remove_broken(&mut self) {
+        self.entries.retain(|entry| {
+            let Some(backend) = entry.backend else {
+                return true;
+            };
+            match smoke_test(entry.solver.as_ref()) {
+                Ok(()) => {
+                    info!(
+                        backend = backend.key(),
+                        version = %entry.info.version,
+                        "Backend available"
+                    );
+                    true
+                }
+                Err(reason) => {
+                    warn!(
+                        backend = backend.key(),
+                        reason, "Backend failed its startup probe"
+                    );
+                    false
+                }
+            }
+        });
+    }
+
+    /// Register `solver` for `backend`, replacing any previous registration
+    ///
+    /// The solver is also selectable by the backend's key (`cbc`, `highs`).
+    pub fn register(&mut self, backend: SolverBackend, solver: Arc<dyn SolverService>) {
+        self.insert(backend.key().to_string(), Some(backend), solver);
+    }
+
+    /// Register a custom solver under `name`, replacing any solver with that name
+    ///
+    /// Clients select it with `SolverConfig.solver_name`.
+    pub fn register_named(&mut self, name: impl Into<String>, solver: Arc<dyn SolverService>) {
+        self.insert(name.into(), None, solver);
+    }
+
+    pub fn with_solver(mut self, name: impl Into<String>, solver: Arc<dyn SolverService>) -> Self {
+        self.register_named(name, solver);
+        self
+    }
+
+    fn insert(
+        &mut self,
+        key: String,
+        backend: Option<SolverBackend>,
+        solver: Arc<dyn SolverService>,
+    ) {
+        self.entries.retain(|entry| {
+            !entry.info.key.eq_ignore_ascii_case(&key)
+                && (backend.is_none() || entry.backend != backend)
+        });
+        self.entries.push(Entry {
+            backend,
+            info: SolverInfo::probe(key, solver.as_ref()),
+            solver,
+            enabled: AtomicBool::new(true),
+        });
+    }
+
+    /// Take the solver registered under `name` out of rotation, or put it back
+    ///
+    /// Returns false when no solver has that name.
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> bool {
+        let Some(entry) = self.find_named(name) else {
+            return false;
+        };
+        entry.enabled.store(enabled, Ordering::Relaxed);
+        true
+    }
+
+    /// Whether `name` is registered but taken out of rotation
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.find_named(name)
+            .is_some_and(|entry| !entry.is_enabled())
+    }
+
+    /// Registration keys with whether each is enabled, in registration order
+    pub fn states(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.info.key.as_str(), entry.is_enabled()))
+    }
+
+    pub fn get(&self, backend: SolverBackend) -> Option<Arc<dyn SolverService>> {
+        self.enabled()
+            .find(|entry| entry.backend == Some(backend))
+            .map(|entry| Arc::clone(&entry.solver))
+    }
+
+    /// Solvers registered for built-in backends, in registration order
+    pub fn backend_solvers(&self) -> Vec<Arc<dyn SolverService>> {
+        self.enabled()
+            .filter(|entry| entry.backend.is_some())
+            .map(|entry| Arc::clone(&entry.solver))
+            .collect()
+    }
+
+    /// Whether any built-in backend is registered, enabled or not
+    pub fn has_backends(&self) -> bool {
+        self.entries.iter().any(|entry| entry.backend.is_some())
+    }
+
+    /// Built-in backends with their registered solvers, in registration order
+    pub fn backends(&self) -> Vec<(SolverBackend, Arc<dyn SolverService>)> {
+        self.enabled()
+            .filter_map(|entry| Some((entry.backend?, Arc::clone(&entry.solver))))
+            .collect()
+    }
+
+    /// Look up a solver by key (case-insensitive)
+    pub fn get_named(&self, name: &str) -> Option<Arc<dyn SolverService>> {
+        self.find_named(name).map(|entry| Arc::clone(&entry.solver))
+    }
+
+    /// Built-in backend registered under `name`, if it is one
+    pub fn backend_for(&self, name: &str) -> Option<SolverBackend> {
+        self.find_named(name).and_then(|entry| entry.backend)
+    }
+
+    /// Registration keys of the enabled solvers, in registration order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.enabled().map(|entry| entry.info.key.as_str())
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(|entry| entry.is_enabled())
+    }
+
+    fn find_named(&self, name: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.info.key.eq_ignore_ascii_case(name))
+    }
+
+    /// Descriptions of the enabled solvers, in registration order
+    pub fn infos(&self) -> impl Iterator<Item = &SolverInfo> {
+        self.enabled().map(|entry| &entry.info)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Solve the smoke problem with `solver`, describing what went wrong if it fails
+fn smoke_test(solver: &dyn SolverService) -> Result<(), String> {
+    let (y, expected) = if solver.supports_mip() {
+        (Variable::integer("y"), SMOKE_MIP_OPTIMUM)
+    } else {
+        (Variable::continuous("y"), SMOKE_LP_OPTIMUM)
+    };
+    let problem = OptimizationProblem::new(ObjectiveFunction::new(
+        OptimizationType::Maximize,
+        vec![1.0, 1.0],
+    ))
+    .add_constraint(Constraint::new(
+        ConstraintType::LessThanOrEqual,
+        vec![1.0, 2.0],
+        4.0,
+    ))
+    .add_constraint(Constraint::new(
+        ConstraintType::LessThanOrEqual,
+        vec![3.0, 1.0],
+        6.0,
+    ))
+    .with_variables(vec![Variable::continuous("x"), y]);
+
+    let solution = attempt(|| solver.solve(&problem)).map_err(|e| e.to_string())?;
+    if solution.status != SolutionStatus::Optimal {
+        return Err(format!(
+            "smoke solve ended {}: {}",
+            solution.status, solution.message
+        ));
+    }
+    match solution.optimal_value {
+        Some(value) if (value - expected).abs() <= 1e-6 => Ok(()),
+        value => Err(format!(
+            "smoke solve found {:?}, expected {}",
+            value, expected
+        )),
+    }
+}
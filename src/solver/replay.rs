@@ -0,0 +1,45 @@
+// Replay: Re-run recorded solves and compare the outcomes
+// Run a recording against a new backend or solver version before rolling it out
+
+use crate::domain::recording::{RecordedSolve, ReplayComparison};
+use crate::domain::solver_service::Result;
+use crate::domain::value_objects::SolverBackend;
+
+use super::{prepare_problem, wrap_solver, SolverFactory};
+
+/// Solve `recorded`'s problem again and compare the result with the recorded one
+///
+/// `backend` replaces the recorded backend choice (None keeps it), and is given
+/// the recorded seed. The problem goes through the same relaxation, fixing,
+/// presolve, scaling and polishing as on the server, but in-process and without
+/// the server's queue or isolation. Objectives count as equal within `tolerance`,
+/// relative to their size.
+pub fn replay(
+    recorded: &RecordedSolve,
+    backend: Option<SolverBackend>,
+    tolerance: f64,
+) -> Result<ReplayComparison> {
+    let mut problem = recorded.problem.clone();
+    if let Some(backend) = backend {
+        problem.solver_config.backend = backend;
+    }
+    if let Some(seed) = recorded.seed {
+        for backend in [SolverBackend::Highs, SolverBackend::CoinCbc] {
+            SolverFactory::set_random_seed(backend, &mut problem, seed);
+        }
+    }
+    prepare_problem(&mut problem)?;
+
+    let solver = wrap_solver(
+        SolverFactory::create_solver(&problem),
+        &problem.solver_config,
+    );
+    let solution = solver.solve(&problem)?;
+
+    Ok(ReplayComparison::new(
+        &recorded.problem,
+        &recorded.solution,
+        &solution,
+        tolerance,
+    ))
+}
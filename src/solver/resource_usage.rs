@@ -0,0 +1,98 @@
+// Resource usage: CPU time, wall time and peak memory of a solve
+// Read from the CPU-time clocks and getrusage, so only Unix builds report CPU time
+// and memory
+
+use std::time::{Duration, Instant};
+
+use crate::domain::models::ResourceUsage;
+
+/// Measures the resources the current thread uses from its start until `finish`
+///
+/// Helper threads a backend starts aren't counted in the CPU time.
+pub struct ResourceMeter {
+    started_at: Instant,
+    cpu_at_start: Duration,
+}
+
+impl ResourceMeter {
+    pub fn start() -> Self {
+        Self {
+            started_at: Instant::now(),
+            cpu_at_start: cpu_time(Who::Thread),
+        }
+    }
+
+    /// Usage since `start`, with the process's peak memory so far
+    pub fn finish(&self) -> ResourceUsage {
+        ResourceUsage {
+            wall_time_ms: self.started_at.elapsed().as_secs_f64() * 1000.0,
+            cpu_time_ms: cpu_time(Who::Thread)
+                .saturating_sub(self.cpu_at_start)
+                .as_secs_f64()
+                * 1000.0,
+            peak_rss_bytes: peak_rss_bytes(),
+        }
+    }
+}
+
+/// Wall time since `started_at`, with the CPU time and peak memory of the whole
+/// process, for isolated solve workers
+pub fn process_usage(started_at: Instant) -> ResourceUsage {
+    ResourceUsage {
+        wall_time_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+        cpu_time_ms: cpu_time(Who::Process).as_secs_f64() * 1000.0,
+        peak_rss_bytes: peak_rss_bytes(),
+    }
+}
+
+/// Whose CPU time to read
+#[derive(Clone, Copy)]
+enum Who {
+    Thread,
+    Process,
+}
+
+#[cfg(unix)]
+fn cpu_time(who: Who) -> Duration {
+    let clock = match who {
+        Who::Thread => libc::CLOCK_THREAD_CPUTIME_ID,
+        Who::Process => libc::CLOCK_PROCESS_CPUTIME_ID,
+    };
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: clock_gettime only writes to the struct it is given
+    if unsafe { libc::clock_gettime(clock, &mut time) } != 0 {
+        return Duration::ZERO;
+    }
+    Duration::new(
+        time.tv_sec.max(0) as u64,
+        time.tv_nsec.clamp(0, 999_999_999) as u32,
+    )
+}
+
+#[cfg(unix)]
+fn peak_rss_bytes() -> u64 {
+    // SAFETY: getrusage only writes to the struct it is given
+    let usage = unsafe {
+        let mut usage: libc::rusage = std::mem::zeroed();
+        if libc::getrusage(libc::RUSAGE_SELF, &mut usage) != 0 {
+            return 0;
+        }
+        usage
+    };
+    // Linux reports kilobytes, macOS bytes
+    let unit = if cfg!(target_os = "macos") { 1 } else { 1024 };
+    usage.ru_maxrss.max(0) as u64 * unit
+}
+
+#[cfg(not(unix))]
+fn cpu_time(_who: Who) -> Duration {
+    Duration::ZERO
+}
+
+#[cfg(not(unix))]
+fn peak_rss_bytes() -> u64 {
+    0
+}
@@ -0,0 +1,159 @@
+// Scaling Solver
+// Wraps another solver with geometric-mean scaling, so badly scaled models are
+// solved in balanced units and reported in the caller's
+
+use crate::domain::{
+    infeasibility::InfeasibilityCertificate,
+    models::{OptimizationProblem, Solution},
+    scaling::{Scaling, DEFAULT_SCALING_PASSES},
+    solver_service::{
+        CancellationToken, NullObserver, Result, SolveObserver, SolverOption, SolverService,
+    },
+    unbounded_ray::UnboundedRay,
+    verification::verify_solution,
+};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Solver that scales the problem, solves it with `inner` and unscales the result
+///
+/// Solution quality is measured against the original problem, so violations the
+/// backend hid behind its own scaled tolerances show up in the result.
+pub struct ScalingSolver {
+    inner: Arc<dyn SolverService>,
+    passes: usize,
+}
+
+impl ScalingSolver {
+    pub fn new(inner: Arc<dyn SolverService>) -> Self {
+        Self {
+            inner,
+            passes: DEFAULT_SCALING_PASSES,
+        }
+    }
+
+    pub fn with_passes(mut self, passes: usize) -> Self {
+        self.passes = passes;
+        self
+    }
+}
+
+impl SolverService for ScalingSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<Solution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<Solution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<Solution> {
+        let scaling = Scaling::geometric(problem, self.passes);
+        if scaling.is_identity() {
+            return self.inner.solve_cancellable(problem, observer, cancel);
+        }
+        debug!(passes = self.passes, "Solving scaled problem");
+
+        let unscaling = UnscalingObserver {
+            inner: observer,
+            scaling: &scaling,
+        };
+        let mut solution =
+            self.inner
+                .solve_cancellable(&scaling.scale(problem), &unscaling, cancel)?;
+        scaling.unscale(&mut solution);
+        // Scaled rows carry their factor, so the certificate's multipliers take it
+        // back like duals
+        solution.infeasibility_certificate =
+            solution
+                .infeasibility_certificate
+                .take()
+                .and_then(|certificate| {
+                    let multipliers: Vec<f64> = certificate
+                        .multipliers
+                        .iter()
+                        .zip(&scaling.row_factors)
+                        .map(|(y, r)| y * r)
+                        .collect();
+                    InfeasibilityCertificate::new(problem, &multipliers)
+                });
+        // Unscaling changes the steps' relative sizes, so the ray is rebuilt
+        solution.unbounded_ray = solution.unbounded_ray.take().and_then(|ray| {
+            let mut direction = ray.direction;
+            scaling.unscale_values(&mut direction);
+            UnboundedRay::new(problem, &direction)
+        });
+
+        if let Ok(check) = verify_solution(problem, &solution.variable_values, 0.0) {
+            solution.quality.max_constraint_violation = check.max_constraint_violation;
+            solution.quality.max_integrality_violation = check.max_integrality_violation;
+        }
+        Ok(solution)
+    }
+
+    fn validate(&self, problem: &OptimizationProblem) -> Result<Vec<String>> {
+        self.inner.validate(problem)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn supports_mip(&self) -> bool {
+        self.inner.supports_mip()
+    }
+
+    fn supports_lp(&self) -> bool {
+        self.inner.supports_lp()
+    }
+
+    fn version(&self) -> String {
+        self.inner.version()
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        self.inner.capabilities()
+    }
+
+    fn options(&self) -> Vec<SolverOption> {
+        self.inner.options()
+    }
+}
+
+/// Reports the inner solver's incumbents in the original variables
+///
+/// Column scaling substitutes variables without changing the objective, so only
+/// the values need mapping back.
+struct UnscalingObserver<'a> {
+    inner: &'a dyn SolveObserver,
+    scaling: &'a Scaling,
+}
+
+impl SolveObserver for UnscalingObserver<'_> {
+    fn on_incumbent(&self, objective: f64, values: &[f64]) {
+        let mut values = values.to_vec();
+        self.scaling.unscale_values(&mut values);
+        self.inner.on_incumbent(objective, &values);
+    }
+
+    fn on_bound_update(&self, bound: f64) {
+        self.inner.on_bound_update(bound);
+    }
+
+    fn on_node(&self, nodes: u64) {
+        self.inner.on_node(nodes);
+    }
+
+    fn on_log(&self, line: &str) {
+        self.inner.on_log(line);
+    }
+}
@@ -0,0 +1,509 @@
+// Subprocess Solver Adapter
+// Runs each solve in a child process with resource limits, so a backend crash or
+// memory blowup only kills that job instead of the whole server
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution as DomainSolution},
+    solver_service::{
+        report_solution, CancellationToken, NullObserver, Result, SolveObserver, SolverError,
+        SolverService,
+    },
+    value_objects::{SolutionStatus, SolverBackend},
+};
+use crate::solver::resource_usage::process_usage;
+use crate::solver::SolverFactory;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitCode, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, Span};
+
+/// Command-line argument that switches the server binary into solve-worker mode
+pub const SOLVE_WORKER_ARG: &str = "--solve-worker";
+
+/// What the runtimes print on stderr before aborting over a failed allocation: the
+/// Rust allocator, and `std::bad_alloc` reaching `std::terminate` in the C++ backends
+const ALLOCATION_FAILURE_MARKERS: [&str; 2] = ["memory allocation of", "std::bad_alloc"];
+
+/// Resource limits applied to each isolated solve
+#[derive(Debug, Clone, Default)]
+pub struct IsolationConfig {
+    /// Address-space limit for the child process, in megabytes
+    pub memory_limit_mb: Option<u64>,
+    /// CPU time limit for the child process, in seconds
+    pub cpu_time_limit_secs: Option<u64>,
+    /// Wall-clock limit after which the child is killed
+    pub wall_time_limit: Option<Duration>,
+}
+
+impl IsolationConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_memory_limit_mb(mut self, megabytes: u64) -> Self {
+        self.memory_limit_mb = Some(megabytes);
+        self
+    }
+
+    pub fn with_cpu_time_limit_secs(mut self, seconds: u64) -> Self {
+        self.cpu_time_limit_secs = Some(seconds);
+        self
+    }
+
+    pub fn with_wall_time_limit(mut self, limit: Duration) -> Self {
+        self.wall_time_limit = Some(limit);
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkerRequest {
+    backend: SolverBackend,
+    problem: OptimizationProblem,
+}
+
+type WorkerReply = std::result::Result<DomainSolution, SolverError>;
+
+/// Solver that delegates to a worker child process
+pub struct SubprocessSolver {
+    name: String,
+    backend: SolverBackend,
+    limits: IsolationConfig,
+    program: Option<PathBuf>,
+}
+
+impl SubprocessSolver {
+    /// Isolate solves for `backend`, reporting results under `name`
+    pub fn new(name: impl Into<String>, backend: SolverBackend, limits: IsolationConfig) -> Self {
+        Self {
+            name: name.into(),
+            backend,
+            limits,
+            program: None,
+        }
+    }
+
+    /// Use a specific worker executable instead of the current binary
+    ///
+    /// The program must call [`run_solve_worker`] when invoked with [`SOLVE_WORKER_ARG`].
+    pub fn with_worker_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = Some(program.into());
+        self
+    }
+
+    fn spawn_worker(&self) -> Result<Child> {
+        let program = match &self.program {
+            Some(program) => program.clone(),
+            None => std::env::current_exe().map_err(|e| {
+                SolverError::SolverNotAvailable(format!("Cannot locate worker binary: {}", e))
+            })?,
+        };
+
+        let mut command = Command::new(program);
+        command
+            .arg(SOLVE_WORKER_ARG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        apply_rlimits(&mut command, &self.limits);
+
+        command
+            .spawn()
+            .map_err(|e| SolverError::SolverNotAvailable(format!("Cannot start worker: {}", e)))
+    }
+
+    /// Whether an abnormal exit was the worker running out of its memory allowance
+    ///
+    /// Only an allocation failure the worker reported on stderr counts: a crash or
+    /// kill on its own may be a plain backend bug, which is reported as such.
+    fn hit_memory_limit(&self, status: ExitStatus, allocation_failed: bool) -> bool {
+        self.limits.memory_limit_mb.is_some() && allocation_failed && !status.success()
+    }
+
+    fn describe_failure(&self, status: ExitStatus, stopped: Stopped) -> SolverError {
+        match stopped {
+            Stopped::Cancelled => return SolverError::Cancelled,
+            Stopped::TimedOut => {
+                let limit = self.limits.wall_time_limit.unwrap_or_default();
+                return SolverError::Timeout(format!(
+                    "Isolated solve exceeded wall-clock limit of {:.1}s and was killed",
+                    limit.as_secs_f64()
+                ));
+            }
+            Stopped::Finished => {}
+        }
+
+        match termination_signal(status) {
+            Some(Signal::CpuLimit) => SolverError::Timeout(format!(
+                "Isolated solve exceeded CPU time limit of {}s",
+                self.limits.cpu_time_limit_secs.unwrap_or_default()
+            )),
+            Some(Signal::Crash(signal)) => SolverError::ExecutionFailed(format!(
+                "Solver backend crashed (signal {}) in isolated worker",
+                signal
+            )),
+            Some(Signal::Abort) => SolverError::ExecutionFailed(
+                "Solver backend aborted (SIGABRT) in isolated worker".to_string(),
+            ),
+            Some(Signal::Killed) => {
+                SolverError::ExecutionFailed("Isolated worker was killed (SIGKILL)".to_string())
+            }
+            Some(Signal::Other(signal)) => SolverError::ExecutionFailed(format!(
+                "Isolated worker terminated by signal {}",
+                signal
+            )),
+            None => SolverError::ExecutionFailed(format!(
+                "Isolated worker exited unexpectedly ({})",
+                status
+            )),
+        }
+    }
+}
+
+impl SolverService for SubprocessSolver {
+    fn solve(&self, problem: &OptimizationProblem) -> Result<DomainSolution> {
+        self.solve_cancellable(problem, &NullObserver, &CancellationToken::new())
+    }
+
+    fn solve_observed(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+    ) -> Result<DomainSolution> {
+        self.solve_cancellable(problem, observer, &CancellationToken::new())
+    }
+
+    /// Progress is only reported at the end, the worker's stderr line by line as
+    /// the log; cancelling kills the worker
+    fn solve_cancellable(
+        &self,
+        problem: &OptimizationProblem,
+        observer: &dyn SolveObserver,
+        cancel: &CancellationToken,
+    ) -> Result<DomainSolution> {
+        // Validate in-process so bad requests don't pay for a process spawn
+        self.validate(problem)?;
+
+        let request = bincode::serialize(&WorkerRequest {
+            backend: self.backend,
+            problem: problem.clone(),
+        })
+        .map_err(|e| SolverError::ExecutionFailed(format!("Cannot encode problem: {}", e)))?;
+
+        let mut child = self.spawn_worker()?;
+
+        // The worker reads the whole request before replying, so write it up front
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(&request) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(SolverError::ExecutionFailed(format!(
+                    "Cannot send problem to worker: {}",
+                    e
+                )));
+            }
+        }
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| SolverError::ExecutionFailed("Worker stdout unavailable".to_string()))?;
+        let reader = thread::spawn(move || {
+            let mut reply = Vec::new();
+            stdout.read_to_end(&mut reply).map(|_| reply)
+        });
+        let stderr = child.stderr.take();
+        let deadline = self
+            .limits
+            .wall_time_limit
+            .map(|limit| Instant::now() + limit);
+        let span = Span::current();
+        let (status, stopped, allocation_failed) = thread::scope(|scope| {
+            let diagnostics = scope.spawn(|| {
+                span.in_scope(|| stderr.is_some_and(|stderr| forward_diagnostics(stderr, observer)))
+            });
+            let waited = wait_for_worker(&mut child, deadline, cancel);
+            if waited.is_err() {
+                // Its stderr only closes once it is gone
+                let _ = child.kill();
+            }
+            let allocation_failed = diagnostics.join().unwrap_or(false);
+            waited.map(|(status, stopped)| (status, stopped, allocation_failed))
+        })?;
+
+        let reply = reader
+            .join()
+            .map_err(|_| SolverError::ExecutionFailed("Worker reader panicked".to_string()))?
+            .map_err(|e| SolverError::ExecutionFailed(format!("Cannot read reply: {}", e)))?;
+
+        if stopped == Stopped::Finished && self.hit_memory_limit(status, allocation_failed) {
+            return Ok(DomainSolution::new(
+                SolutionStatus::MemoryLimit,
+                format!(
+                    "Solve exceeded the memory limit of {} MB",
+                    self.limits.memory_limit_mb.unwrap_or_default()
+                ),
+            ));
+        }
+        if stopped != Stopped::Finished || !status.success() {
+            return Err(self.describe_failure(status, stopped));
+        }
+
+        let reply: WorkerReply = bincode::deserialize(&reply)
+            .map_err(|e| SolverError::ExecutionFailed(format!("Malformed worker reply: {}", e)))?;
+        let solution = reply?;
+        report_solution(observer, &solution);
+        Ok(solution)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports_mip(&self) -> bool {
+        true
+    }
+}
+
+/// Entry point for the worker side of [`SubprocessSolver`]
+///
+/// Reads one encoded problem from stdin, solves it in-process and writes the
+/// encoded result to stdout.
+pub fn run_solve_worker() -> ExitCode {
+    let mut input = Vec::new();
+    if let Err(e) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("solve worker: cannot read request: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let request: WorkerRequest = match bincode::deserialize(&input) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("solve worker: malformed request: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    drop(input);
+
+    let solver = SolverFactory::create_for(request.backend, &request.problem);
+    let started_at = Instant::now();
+    let reply: WorkerReply = solver.solve(&request.problem).map(|mut solution| {
+        solution.statistics.resources = Some(process_usage(started_at));
+        solution
+    });
+
+    let encoded = match bincode::serialize(&reply) {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            eprintln!("solve worker: cannot encode reply: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    if stdout
+        .write_all(&encoded)
+        .and_then(|_| stdout.flush())
+        .is_err()
+    {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Log the worker's stderr line by line, in the caller's span so each line names
+/// its job, and hand the lines to `observer` for the job's log stream
+///
+/// Returns whether the worker reported a failed allocation.
+fn forward_diagnostics(stderr: impl Read, observer: &dyn SolveObserver) -> bool {
+    let mut allocation_failed = false;
+    for line in BufReader::new(stderr).lines() {
+        let Ok(line) = line else { break };
+        allocation_failed |= ALLOCATION_FAILURE_MARKERS
+            .iter()
+            .any(|marker| line.contains(marker));
+        info!(%line, "Solve worker output");
+        observer.on_log(&line);
+    }
+    allocation_failed
+}
+
+/// Why the wait for a worker ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stopped {
+    Finished,
+    TimedOut,
+    Cancelled,
+}
+
+/// Wait for the worker to exit, killing it at the deadline or once cancelled
+fn wait_for_worker(
+    child: &mut Child,
+    deadline: Option<Instant>,
+    cancel: &CancellationToken,
+) -> Result<(ExitStatus, Stopped)> {
+    let wait_error =
+        |e: std::io::Error| SolverError::ExecutionFailed(format!("Cannot wait for worker: {}", e));
+
+    loop {
+        if let Some(status) = child.try_wait().map_err(wait_error)? {
+            return Ok((status, Stopped::Finished));
+        }
+        let stopped = if cancel.is_cancelled() {
+            Stopped::Cancelled
+        } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Stopped::TimedOut
+        } else {
+            thread::sleep(Duration::from_millis(10));
+            continue;
+        };
+        let _ = child.kill();
+        let status = child.wait().map_err(wait_error)?;
+        return Ok((status, stopped));
+    }
+}
+
+#[cfg(unix)]
+fn apply_rlimits(command: &mut Command, limits: &IsolationConfig) {
+    use std::os::unix::process::CommandExt;
+
+    let memory_bytes = limits
+        .memory_limit_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024) as libc::rlim_t);
+    let cpu_seconds = limits.cpu_time_limit_secs.map(|s| s as libc::rlim_t);
+
+    if memory_bytes.is_none() && cpu_seconds.is_none() {
+        return;
+    }
+
+    // SAFETY: the closure runs between fork and exec and only calls setrlimit,
+    // which is async-signal-safe.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(bytes) = memory_bytes {
+                set_rlimit(|limit| libc::setrlimit(libc::RLIMIT_AS, limit), bytes)?;
+            }
+            if let Some(seconds) = cpu_seconds {
+                set_rlimit(|limit| libc::setrlimit(libc::RLIMIT_CPU, limit), seconds)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(unix)]
+fn set_rlimit(
+    resource: impl FnOnce(&libc::rlimit) -> libc::c_int,
+    value: libc::rlim_t,
+) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value,
+        rlim_max: value,
+    };
+    if resource(&limit) != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn apply_rlimits(_command: &mut Command, _limits: &IsolationConfig) {}
+
+/// How the worker process was terminated
+enum Signal {
+    CpuLimit,
+    /// SIGSEGV or SIGBUS
+    Crash(i32),
+    Abort,
+    Killed,
+    Other(i32),
+}
+
+#[cfg(unix)]
+fn termination_signal(status: ExitStatus) -> Option<Signal> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal().map(|signal| match signal {
+        libc::SIGXCPU => Signal::CpuLimit,
+        libc::SIGSEGV | libc::SIGBUS => Signal::Crash(signal),
+        libc::SIGABRT => Signal::Abort,
+        libc::SIGKILL => Signal::Killed,
+        other => Signal::Other(other),
+    })
+}
+
+#[cfg(not(unix))]
+fn termination_signal(_status: ExitStatus) -> Option<Signal> {
+    None
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn limited() -> SubprocessSolver {
+        SubprocessSolver::new(
+            "isolated",
+            SolverBackend::Auto,
+            IsolationConfig::new().with_memory_limit_mb(64),
+        )
+    }
+
+    fn signalled(signal: i32) -> ExitStatus {
+        ExitStatus::from_raw(signal)
+    }
+
+    #[test]
+    fn allocation_failures_are_recognised_on_stderr() {
+        let rust = "memory allocation of 1073741824 bytes failed\n";
+        let cpp = "terminate called after throwing an instance of 'std::bad_alloc'\n";
+        assert!(forward_diagnostics(rust.as_bytes(), &NullObserver));
+        assert!(forward_diagnostics(cpp.as_bytes(), &NullObserver));
+        assert!(!forward_diagnostics(
+            "Segmentation fault\n".as_bytes(),
+            &NullObserver
+        ));
+    }
+
+    #[test]
+    fn worker_stderr_reaches_the_job_log() {
+        #[derive(Default)]
+        struct Lines(std::sync::Mutex<Vec<String>>);
+
+        impl SolveObserver for Lines {
+            fn on_log(&self, line: &str) {
+                self.0.lock().unwrap().push(line.to_string());
+            }
+        }
+
+        let lines = Lines::default();
+        forward_diagnostics("Presolving\nSolving\n".as_bytes(), &lines);
+        assert_eq!(*lines.0.lock().unwrap(), ["Presolving", "Solving"]);
+    }
+
+    #[test]
+    fn crashes_without_an_allocation_failure_are_not_memory_limits() {
+        let solver = limited();
+        for signal in [libc::SIGSEGV, libc::SIGABRT, libc::SIGKILL] {
+            assert!(!solver.hit_memory_limit(signalled(signal), false));
+        }
+        assert!(solver.hit_memory_limit(signalled(libc::SIGABRT), true));
+    }
+
+    #[test]
+    fn crashes_report_their_signal() {
+        let error = limited().describe_failure(signalled(libc::SIGBUS), Stopped::Finished);
+        assert!(error
+            .to_string()
+            .contains(&format!("signal {}", libc::SIGBUS)));
+    }
+
+    #[test]
+    fn no_memory_limit_means_no_memory_limit_result() {
+        let solver = SubprocessSolver::new("isolated", SolverBackend::Auto, IsolationConfig::new());
+        assert!(!solver.hit_memory_limit(signalled(libc::SIGABRT), true));
+    }
+}
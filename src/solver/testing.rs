@@ -0,0 +1,30 @@
+// Test helpers shared by the solver adapters' unit tests
+
+use crate::domain::{
+    models::{OptimizationProblem, Solution},
+    solver_service::{Result, SolverService},
+};
+
+/// Fallback for specialised solvers that answers every problem with a marker
+/// solution, so tests can tell a delegated solve from a direct one
+pub(crate) struct StubFallback;
+
+impl StubFallback {
+    pub(crate) const MESSAGE: &'static str = "Solved by the fallback";
+}
+
+impl SolverService for StubFallback {
+    fn solve(&self, _problem: &OptimizationProblem) -> Result<Solution> {
+        let mut solution = Solution::optimal(0.0, Vec::new());
+        solution.message = Self::MESSAGE.to_string();
+        Ok(solution)
+    }
+
+    fn name(&self) -> &str {
+        "Stub"
+    }
+
+    fn supports_mip(&self) -> bool {
+        true
+    }
+}